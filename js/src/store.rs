@@ -1,10 +1,10 @@
 use crate::format_err;
 use crate::model::*;
 use crate::utils::to_err;
-use js_sys::{Array, Map};
+use js_sys::{Array, Function, Map, Object, Reflect};
 use oxigraph::io::{DatasetFormat, GraphFormat};
 use oxigraph::model::*;
-use oxigraph::sparql::QueryResults;
+use oxigraph::sparql::{QueryOptions, QueryResults};
 use oxigraph::MemoryStore;
 use std::convert::{TryFrom, TryInto};
 use std::io::Cursor;
@@ -76,7 +76,7 @@ impl JsMemoryStore {
                     None
                 }
                 .as_ref()
-                .map(|t: &NamedOrBlankNode| t.into()),
+                .map(|t: &Subject| t.into()),
                 if let Some(predicate) = self.from_js.to_optional_term(predicate)? {
                     Some(NamedNode::try_from(predicate)?)
                 } else {
@@ -104,8 +104,36 @@ impl JsMemoryStore {
             .into_boxed_slice())
     }
 
-    pub fn query(&self, query: &str) -> Result<JsValue, JsValue> {
-        let results = self.store.query(query).map_err(to_err)?;
+    pub fn query(&self, query: &str, options: &JsValue) -> Result<JsValue, JsValue> {
+        let mut query_options = QueryOptions::default();
+        if !options.is_null() && !options.is_undefined() {
+            let custom_functions = Reflect::get(options, &JsValue::from_str("customFunctions"))?;
+            if !custom_functions.is_null() && !custom_functions.is_undefined() {
+                for entry in Object::entries(&Object::from(custom_functions)).iter() {
+                    let entry = Array::from(&entry);
+                    let name = NamedNode::new(entry.get(0).as_string().unwrap_or_default())
+                        .map_err(to_err)?;
+                    let function = Function::from(entry.get(1));
+                    query_options = query_options.with_custom_function(name, move |args| {
+                        let args = args
+                            .iter()
+                            .map(|arg| JsValue::from(JsTerm::from(arg.clone())))
+                            .collect::<Array>();
+                        let result = function.apply(&JsValue::NULL, &args).ok()?;
+                        if result.is_null() || result.is_undefined() {
+                            None
+                        } else {
+                            FromJsConverter::default()
+                                .to_term(&result)
+                                .ok()?
+                                .try_into()
+                                .ok()
+                        }
+                    })
+                }
+            }
+        }
+        let results = self.store.query_opt(query, query_options).map_err(to_err)?;
         let output = match results {
             QueryResults::Solutions(solutions) => {
                 let results = Array::new();
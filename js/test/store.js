@@ -83,6 +83,19 @@ describe('MemoryStore', function () {
       const results = store.query('SELECT (RAND() AS ?y) WHERE {}')
       assert.strictEqual(1, results.length)
     })
+
+    it('SELECT with custom function', function () {
+      const store = new MemoryStore([dataFactory.triple(ex, ex, ex)])
+      const results = store.query('SELECT (<http://example.com/f>(?o) AS ?v) (<http://example.com/g>(?o) AS ?u) WHERE { ?s ?p ?o }', {
+        customFunctions: {
+          'http://example.com/f': o => dataFactory.literal(o.value.toUpperCase()),
+          'http://example.com/g': o => null
+        }
+      })
+      assert.strictEqual(1, results.length)
+      assert(dataFactory.literal('HTTP://EXAMPLE.COM').equals(results[0].get('v')))
+      assert(!results[0].has('u'))
+    })
   })
 
   describe('#update()', function () {
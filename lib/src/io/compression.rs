@@ -0,0 +1,372 @@
+//! Transparent decompression of the parsers inputs and compression of the serializers outputs
+
+use crate::error::invalid_input_error;
+#[cfg(feature = "bzip2")]
+use bzip2::bufread::MultiBzDecoder;
+#[cfg(feature = "bzip2")]
+use bzip2::write::BzEncoder;
+#[cfg(feature = "flate2")]
+use flate2::bufread::MultiGzDecoder;
+#[cfg(feature = "flate2")]
+use flate2::write::GzEncoder;
+use std::io;
+#[cfg(any(feature = "flate2", feature = "zstd", feature = "bzip2"))]
+use std::io::BufReader;
+use std::io::{BufRead, Read, Write};
+use std::path::Path;
+
+/// Compression formats of the RDF files.
+///
+/// The parsers detect the compressed inputs by themselves and the serializers compress their output if
+/// [`GraphSerializer::with_compression`](super::GraphSerializer::with_compression()) is used.
+/// The support of each compression format requires a crate feature: `flate2` for gzip, `zstd` for Zstandard and `bzip2` for bzip2.
+/// The `compression` feature enables all of them.
+///
+/// This enumeration is non exhaustive. New formats might be added in the future.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+#[non_exhaustive]
+pub enum Compression {
+    /// [gzip](https://tools.ietf.org/html/rfc1952)
+    Gzip,
+    /// [Zstandard](https://tools.ietf.org/html/rfc8878)
+    Zstd,
+    /// [bzip2](https://sourceware.org/bzip2/)
+    Bzip2,
+}
+
+impl Compression {
+    /// The format usual file extension.
+    ///
+    /// ```
+    /// use oxigraph::io::Compression;
+    ///
+    /// assert_eq!(Compression::Gzip.file_extension(), "gz")
+    /// ```
+    #[inline]
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+            Compression::Bzip2 => "bz2",
+        }
+    }
+
+    /// The format HTTP [content coding](https://tools.ietf.org/html/rfc7231#section-3.1.2.1).
+    ///
+    /// ```
+    /// use oxigraph::io::Compression;
+    ///
+    /// assert_eq!(Compression::Zstd.content_encoding(), "zstd")
+    /// ```
+    #[inline]
+    pub fn content_encoding(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+            Compression::Bzip2 => "bzip2",
+        }
+    }
+
+    /// Looks for a known compression format from a file extension.
+    ///
+    /// The extensions of compressed RDF files like `ttl.gz` are also supported.
+    ///
+    /// ```
+    /// use oxigraph::io::Compression;
+    ///
+    /// assert_eq!(Compression::from_extension("nq.zst"), Some(Compression::Zstd));
+    /// assert_eq!(Compression::from_extension("nq"), None);
+    /// ```
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        split_compression_extension(extension).1
+    }
+
+    /// Looks for a known compression format from a file path.
+    ///
+    /// ```
+    /// use oxigraph::io::Compression;
+    ///
+    /// assert_eq!(Compression::from_path("dump/data.ttl.gz"), Some(Compression::Gzip));
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        Self::from_extension(file_extension(path.as_ref())?)
+    }
+
+    /// Looks for a known compression format from a HTTP `Content-Encoding` header value.
+    ///
+    /// ```
+    /// use oxigraph::io::Compression;
+    ///
+    /// assert_eq!(Compression::from_content_encoding("x-gzip"), Some(Compression::Gzip));
+    /// ```
+    pub fn from_content_encoding(content_encoding: &str) -> Option<Self> {
+        let content_encoding = content_encoding.trim();
+        if content_encoding.eq_ignore_ascii_case("gzip")
+            || content_encoding.eq_ignore_ascii_case("x-gzip")
+        {
+            Some(Compression::Gzip)
+        } else if content_encoding.eq_ignore_ascii_case("zstd") {
+            Some(Compression::Zstd)
+        } else if content_encoding.eq_ignore_ascii_case("bzip2")
+            || content_encoding.eq_ignore_ascii_case("x-bzip2")
+        {
+            Some(Compression::Bzip2)
+        } else {
+            None
+        }
+    }
+
+    /// Detects the compression format from the magic number at the start of the data
+    fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&[0x1F, 0x8B]) {
+            Some(Compression::Gzip)
+        } else if data.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+            Some(Compression::Zstd)
+        } else if data.starts_with(b"BZh") && matches!(data.get(3), Some(b'1'..=b'9')) {
+            Some(Compression::Bzip2)
+        } else {
+            None
+        }
+    }
+}
+
+/// Splits an extension like `ttl.gz` into the RDF format extension and the compression format
+pub(crate) fn split_compression_extension(extension: &str) -> (&str, Option<Compression>) {
+    let extension = extension.strip_prefix('.').unwrap_or(extension);
+    let (format, compression) = match extension.rfind('.') {
+        Some(position) => (&extension[..position], &extension[position + 1..]),
+        None => ("", extension),
+    };
+    for candidate in &[Compression::Gzip, Compression::Zstd, Compression::Bzip2] {
+        if compression.eq_ignore_ascii_case(candidate.file_extension()) {
+            return (format, Some(*candidate));
+        }
+    }
+    (extension, None)
+}
+
+/// Returns the extension of a file name including the compression extension like `ttl.gz`
+pub(crate) fn file_extension(path: &Path) -> Option<&str> {
+    let file_name = path.file_name()?.to_str()?;
+    let mut parts = file_name.rsplitn(3, '.');
+    let last = parts.next()?;
+    let before_last = parts.next()?;
+    if parts.next().is_some()
+        && split_compression_extension(last).1.is_some()
+        && !before_last.is_empty()
+    {
+        // The extension is the one of the file format followed by the compression one
+        Some(&file_name[file_name.len() - last.len() - before_last.len() - 1..])
+    } else {
+        Some(last)
+    }
+}
+
+fn unsupported_compression(compression: Compression) -> io::Error {
+    invalid_input_error(format!(
+        "Oxigraph has been compiled without the {} compression support",
+        compression.content_encoding()
+    ))
+}
+
+/// A [`BufRead`] decompressing its input if it is compressed
+pub(crate) enum DecompressReader<R: BufRead> {
+    Plain(R),
+    #[cfg(feature = "flate2")]
+    Gzip(BufReader<MultiGzDecoder<R>>),
+    #[cfg(feature = "zstd")]
+    Zstd(BufReader<zstd::stream::read::Decoder<'static, R>>),
+    #[cfg(feature = "bzip2")]
+    Bzip2(BufReader<MultiBzDecoder<R>>),
+}
+
+impl<R: BufRead> DecompressReader<R> {
+    /// Detects the compression from the first bytes available in the reader buffer
+    pub fn new(mut reader: R) -> Result<Self, io::Error> {
+        let compression = Compression::detect(reader.fill_buf()?);
+        match compression {
+            None => Ok(Self::Plain(reader)),
+            #[cfg(feature = "flate2")]
+            Some(Compression::Gzip) => Ok(Self::Gzip(BufReader::new(MultiGzDecoder::new(reader)))),
+            #[cfg(feature = "zstd")]
+            Some(Compression::Zstd) => Ok(Self::Zstd(BufReader::new(
+                zstd::stream::read::Decoder::with_buffer(reader)?,
+            ))),
+            #[cfg(feature = "bzip2")]
+            Some(Compression::Bzip2) => {
+                Ok(Self::Bzip2(BufReader::new(MultiBzDecoder::new(reader))))
+            }
+            #[allow(unreachable_patterns)]
+            Some(compression) => Err(unsupported_compression(compression)),
+        }
+    }
+}
+
+impl<R: BufRead> Read for DecompressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(reader) => reader.read(buf),
+            #[cfg(feature = "flate2")]
+            Self::Gzip(reader) => reader.read(buf),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(reader) => reader.read(buf),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl<R: BufRead> BufRead for DecompressReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        match self {
+            Self::Plain(reader) => reader.fill_buf(),
+            #[cfg(feature = "flate2")]
+            Self::Gzip(reader) => reader.fill_buf(),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(reader) => reader.fill_buf(),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(reader) => reader.fill_buf(),
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        match self {
+            Self::Plain(reader) => reader.consume(amt),
+            #[cfg(feature = "flate2")]
+            Self::Gzip(reader) => reader.consume(amt),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(reader) => reader.consume(amt),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(reader) => reader.consume(amt),
+        }
+    }
+}
+
+/// A [`Write`] compressing its output
+pub(crate) enum CompressWriter<W: Write> {
+    Plain(W),
+    #[cfg(feature = "flate2")]
+    Gzip(GzEncoder<W>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, W>),
+    #[cfg(feature = "bzip2")]
+    Bzip2(BzEncoder<W>),
+}
+
+impl<W: Write> CompressWriter<W> {
+    pub fn new(writer: W, compression: Option<Compression>) -> Result<Self, io::Error> {
+        match compression {
+            None => Ok(Self::Plain(writer)),
+            #[cfg(feature = "flate2")]
+            Some(Compression::Gzip) => Ok(Self::Gzip(GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            ))),
+            #[cfg(feature = "zstd")]
+            Some(Compression::Zstd) => {
+                Ok(Self::Zstd(zstd::stream::write::Encoder::new(writer, 0)?))
+            }
+            #[cfg(feature = "bzip2")]
+            Some(Compression::Bzip2) => Ok(Self::Bzip2(BzEncoder::new(
+                writer,
+                bzip2::Compression::default(),
+            ))),
+            #[allow(unreachable_patterns)]
+            Some(compression) => Err(unsupported_compression(compression)),
+        }
+    }
+
+    /// Writes the end of the compressed stream and flushes the underlying writer
+    pub fn finish(self) -> Result<(), io::Error> {
+        let mut writer = match self {
+            Self::Plain(writer) => writer,
+            #[cfg(feature = "flate2")]
+            Self::Gzip(encoder) => encoder.finish()?,
+            #[cfg(feature = "zstd")]
+            Self::Zstd(encoder) => encoder.finish()?,
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(encoder) => encoder.finish()?,
+        };
+        writer.flush()
+    }
+}
+
+impl<W: Write> Write for CompressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(writer) => writer.write(buf),
+            #[cfg(feature = "flate2")]
+            Self::Gzip(writer) => writer.write(buf),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(writer) => writer.write(buf),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(writer) => writer.flush(),
+            #[cfg(feature = "flate2")]
+            Self::Gzip(writer) => writer.flush(),
+            #[cfg(feature = "zstd")]
+            Self::Zstd(writer) => writer.flush(),
+            #[cfg(feature = "bzip2")]
+            Self::Bzip2(writer) => writer.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extensions() {
+        assert_eq!(
+            split_compression_extension("ttl.gz"),
+            ("ttl", Some(Compression::Gzip))
+        );
+        assert_eq!(
+            split_compression_extension(".NQ.ZST"),
+            ("NQ", Some(Compression::Zstd))
+        );
+        assert_eq!(
+            split_compression_extension("bz2"),
+            ("", Some(Compression::Bzip2))
+        );
+        assert_eq!(split_compression_extension("ttl"), ("ttl", None));
+        assert_eq!(file_extension(Path::new("a/b.c/data.nt.gz")), Some("nt.gz"));
+        assert_eq!(file_extension(Path::new("data.v2.nt")), Some("nt"));
+        assert_eq!(file_extension(Path::new("data.gz")), Some("gz"));
+        assert_eq!(file_extension(Path::new("data")), None);
+    }
+
+    #[test]
+    fn test_plain_input() -> Result<(), io::Error> {
+        let mut output = String::new();
+        DecompressReader::new(b"BZh <s> <p> <o> .".as_ref())?.read_to_string(&mut output)?;
+        assert_eq!(output, "BZh <s> <p> <o> .");
+        Ok(())
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<(), io::Error> {
+        let data = "<http://example.com/s> <http://example.com/p> \"o\" .\n".repeat(1000);
+        for compression in &[Compression::Gzip, Compression::Zstd, Compression::Bzip2] {
+            let mut buffer = Vec::new();
+            let mut writer = match CompressWriter::new(&mut buffer, Some(*compression)) {
+                Ok(writer) => writer,
+                // The compression is not enabled
+                Err(_) => continue,
+            };
+            writer.write_all(data.as_bytes())?;
+            writer.finish()?;
+            assert_eq!(Compression::detect(&buffer), Some(*compression));
+            let mut output = String::new();
+            DecompressReader::new(buffer.as_slice())?.read_to_string(&mut output)?;
+            assert_eq!(output, data);
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
+use crate::io::compression::{file_extension, split_compression_extension};
+use crate::io::sniff::{sniff, RdfFormat};
+use std::path::Path;
+
 /// [RDF graph](https://www.w3.org/TR/rdf11-concepts/#dfn-graph) serialization formats.
 ///
-/// This enumeration is non exhaustive. New formats like JSON-LD will be added in the future.
+/// This enumeration is non exhaustive. New formats might be added in the future.
 #[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
 #[non_exhaustive]
 pub enum GraphFormat {
@@ -85,11 +89,64 @@ impl GraphFormat {
             None
         }
     }
+
+    /// Looks for a known format from a file extension.
+    ///
+    /// It supports some file extension aliases like `owl` for RDF/XML.
+    /// The extensions of compressed files like `ttl.gz` are also supported, the compression being ignored.
+    ///
+    /// Example:
+    /// ```
+    /// use oxigraph::io::GraphFormat;
+    ///
+    /// assert_eq!(GraphFormat::from_extension("ttl.gz"), Some(GraphFormat::Turtle));
+    /// assert_eq!(GraphFormat::from_extension("NT"), Some(GraphFormat::NTriples));
+    /// ```
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let (extension, _) = split_compression_extension(extension);
+        match extension.to_ascii_lowercase().as_str() {
+            "nt" => Some(GraphFormat::NTriples),
+            "ttl" => Some(GraphFormat::Turtle),
+            "rdf" | "owl" | "xml" => Some(GraphFormat::RdfXml),
+            _ => None,
+        }
+    }
+
+    /// Looks for a known format from a file path using its extension.
+    ///
+    /// Example:
+    /// ```
+    /// use oxigraph::io::GraphFormat;
+    ///
+    /// assert_eq!(GraphFormat::from_path("dump/data.nt.gz"), Some(GraphFormat::NTriples));
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        Self::from_extension(file_extension(path.as_ref())?)
+    }
+
+    /// Guesses the format from the first bytes of an uncompressed file.
+    ///
+    /// It returns `None` if the content looks like a dataset format like N-Quads. Use [`DatasetFormat::from_content`] for them.
+    /// N-Triples is only returned if at least one full statement is present in the data.
+    ///
+    /// Example:
+    /// ```
+    /// use oxigraph::io::GraphFormat;
+    ///
+    /// assert_eq!(GraphFormat::from_content(b"@prefix ex: <http://example.com/> .\nex:s ex:p ex:o ."), Some(GraphFormat::Turtle));
+    /// assert_eq!(GraphFormat::from_content(b"<http://example.com/s> <http://example.com/p> <http://example.com/o> ."), Some(GraphFormat::NTriples));
+    /// ```
+    pub fn from_content(data: &[u8]) -> Option<Self> {
+        match sniff(data)? {
+            RdfFormat::Graph(format) => Some(format),
+            RdfFormat::Dataset(_) => None,
+        }
+    }
 }
 
 /// [RDF dataset](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset) serialization formats.
 ///
-/// This enumeration is non exhaustive. New formats like JSON-LD will be added in the future.
+/// This enumeration is non exhaustive. New formats might be added in the future.
 #[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
 #[non_exhaustive]
 pub enum DatasetFormat {
@@ -97,6 +154,8 @@ pub enum DatasetFormat {
     NQuads,
     /// [TriG](https://www.w3.org/TR/trig/)
     TriG,
+    /// [JSON-LD](https://www.w3.org/TR/json-ld11/)
+    JsonLd,
 }
 
 impl DatasetFormat {
@@ -112,6 +171,7 @@ impl DatasetFormat {
         match self {
             DatasetFormat::NQuads => "http://www.w3.org/ns/formats/N-Quads",
             DatasetFormat::TriG => "http://www.w3.org/ns/formats/TriG",
+            DatasetFormat::JsonLd => "http://www.w3.org/ns/formats/JSON-LD",
         }
     }
 
@@ -127,6 +187,7 @@ impl DatasetFormat {
         match self {
             DatasetFormat::NQuads => "application/n-quads",
             DatasetFormat::TriG => "application/trig",
+            DatasetFormat::JsonLd => "application/ld+json",
         }
     }
 
@@ -142,6 +203,7 @@ impl DatasetFormat {
         match self {
             DatasetFormat::NQuads => "nq",
             DatasetFormat::TriG => "trig",
+            DatasetFormat::JsonLd => "jsonld",
         }
     }
     /// Looks for a known format from a media type.
@@ -161,10 +223,63 @@ impl DatasetFormat {
                     Some(DatasetFormat::NQuads)
                 }
                 "application/trig" | "application/x-trig" => Some(DatasetFormat::TriG),
+                "application/ld+json" => Some(DatasetFormat::JsonLd),
                 _ => None,
             }
         } else {
             None
         }
     }
+
+    /// Looks for a known format from a file extension.
+    ///
+    /// The extensions of compressed files like `nq.zst` are also supported, the compression being ignored.
+    ///
+    /// Example:
+    /// ```
+    /// use oxigraph::io::DatasetFormat;
+    ///
+    /// assert_eq!(DatasetFormat::from_extension("nq.zst"), Some(DatasetFormat::NQuads));
+    /// assert_eq!(DatasetFormat::from_extension("TriG"), Some(DatasetFormat::TriG));
+    /// ```
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let (extension, _) = split_compression_extension(extension);
+        match extension.to_ascii_lowercase().as_str() {
+            "nq" => Some(DatasetFormat::NQuads),
+            "trig" => Some(DatasetFormat::TriG),
+            "jsonld" => Some(DatasetFormat::JsonLd),
+            _ => None,
+        }
+    }
+
+    /// Looks for a known format from a file path using its extension.
+    ///
+    /// Example:
+    /// ```
+    /// use oxigraph::io::DatasetFormat;
+    ///
+    /// assert_eq!(DatasetFormat::from_path("dump/data.trig"), Some(DatasetFormat::TriG));
+    /// ```
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        Self::from_extension(file_extension(path.as_ref())?)
+    }
+
+    /// Guesses the format from the first bytes of an uncompressed file.
+    ///
+    /// It returns `None` if the content looks like a graph format like N-Triples. Use [`GraphFormat::from_content`] for them.
+    /// N-Quads is only returned if at least one full statement with a graph name is present in the data.
+    ///
+    /// Example:
+    /// ```
+    /// use oxigraph::io::DatasetFormat;
+    ///
+    /// assert_eq!(DatasetFormat::from_content(b"<http://example.com/g> { <http://example.com/s> <http://example.com/p> <http://example.com/o> }"), Some(DatasetFormat::TriG));
+    /// assert_eq!(DatasetFormat::from_content(b"<http://example.com/s> <http://example.com/p> <http://example.com/o> ."), None);
+    /// ```
+    pub fn from_content(data: &[u8]) -> Option<Self> {
+        match sniff(data)? {
+            RdfFormat::Dataset(format) => Some(format),
+            RdfFormat::Graph(_) => None,
+        }
+    }
 }
@@ -0,0 +1,1828 @@
+//! Implementation of the [JSON-LD 1.1](https://www.w3.org/TR/json-ld11/) to RDF and from RDF conversions
+
+use crate::error::{invalid_data_error, invalid_input_error};
+use crate::json::JsonValue;
+use crate::model::vocab::{rdf, xsd};
+use crate::model::*;
+use crate::sparql::http::Client;
+use http::header::{ACCEPT, USER_AGENT};
+use http::{Method, Request, StatusCode};
+use oxiri::Iri;
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Cursor, Write};
+use std::sync::Arc;
+
+const MAX_NUMBER_OF_REMOTE_CONTEXTS: usize = 32;
+const RDF_JSON: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#JSON";
+
+/// Loader for the remote documents referenced from [JSON-LD](https://www.w3.org/TR/json-ld11/) contexts.
+///
+/// Could be given to [`DatasetParser::with_document_loader`](super::DatasetParser::with_document_loader())
+/// and to [`DatasetSerializer::with_document_loader`](super::DatasetSerializer::with_document_loader()).
+/// By default the documents are fetched using HTTP if the `http_client` feature is enabled.
+///
+/// It is implemented for `HashMap<String, String>` in order to use a static mapping from IRIs to documents:
+/// ```
+/// use oxigraph::io::{DatasetFormat, DatasetParser};
+/// use std::collections::HashMap;
+/// use std::io::Cursor;
+///
+/// let mut documents = HashMap::new();
+/// documents.insert("http://example.com/context.jsonld".to_owned(), r#"{"@context": {"@vocab": "http://example.com/"}}"#.to_owned());
+///
+/// let file = r#"{"@context": "http://example.com/context.jsonld", "@id": "http://example.com/s", "p": "o"}"#;
+///
+/// let parser = DatasetParser::from_format(DatasetFormat::JsonLd).with_document_loader(documents);
+/// let quads = parser.read_quads(Cursor::new(file))?.collect::<Result<Vec<_>,_>>()?;
+///
+///assert_eq!(quads.len(), 1);
+///assert_eq!(quads[0].predicate.as_str(), "http://example.com/p");
+/// # std::io::Result::Ok(())
+/// ```
+pub trait JsonLdDocumentLoader: Send + Sync {
+    /// Returns a reader on the JSON document identified by the given IRI.
+    fn load(&self, iri: &str) -> Result<Box<dyn BufRead>, io::Error>;
+}
+
+impl JsonLdDocumentLoader for HashMap<String, String> {
+    fn load(&self, iri: &str) -> Result<Box<dyn BufRead>, io::Error> {
+        if let Some(document) = self.get(iri) {
+            Ok(Box::new(Cursor::new(document.clone())))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("The JSON-LD document {} is not available", iri),
+            ))
+        }
+    }
+}
+
+/// Fetches the documents using the HTTP client
+pub(crate) struct SimpleDocumentLoader {
+    client: Client,
+}
+
+impl SimpleDocumentLoader {
+    pub fn new() -> Self {
+        Self {
+            client: Client::new(),
+        }
+    }
+}
+
+impl JsonLdDocumentLoader for SimpleDocumentLoader {
+    fn load(&self, iri: &str) -> Result<Box<dyn BufRead>, io::Error> {
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(iri)
+            .header(ACCEPT, "application/ld+json, application/json")
+            .header(USER_AGENT, concat!("Oxigraph/", env!("CARGO_PKG_VERSION")))
+            .body(None)
+            .map_err(invalid_input_error)?;
+        let response = self.client.request(&request)?;
+        if response.status() != StatusCode::OK {
+            return Err(invalid_data_error(format!(
+                "HTTP error code {} returned when fetching the JSON-LD document {}",
+                response.status(),
+                iri
+            )));
+        }
+        Ok(response.into_body())
+    }
+}
+
+/// An active context as defined by the [JSON-LD context processing algorithm](https://www.w3.org/TR/json-ld11-api/#context-processing-algorithm)
+#[derive(Clone, Default)]
+struct Context {
+    base: Option<Iri<String>>,
+    vocab: Option<String>,
+    language: Option<String>,
+    terms: HashMap<String, TermDefinition>,
+}
+
+#[derive(Clone, Default)]
+struct TermDefinition {
+    /// `None` if the term is explicitly not mapped to anything
+    iri: Option<String>,
+    prefix: bool,
+    reverse: bool,
+    /// `@id`, `@vocab`, `@json`, `@none` or a datatype IRI
+    type_mapping: Option<String>,
+    /// `Some(None)` if the term has an explicit `null` language
+    language: Option<Option<String>>,
+    container: Vec<String>,
+    context: Option<JsonValue>,
+}
+
+impl TermDefinition {
+    fn has_container(&self, container: &str) -> bool {
+        self.container.iter().any(|c| c == container)
+    }
+}
+
+impl Context {
+    fn new(base: Option<Iri<String>>) -> Self {
+        Self {
+            base,
+            ..Self::default()
+        }
+    }
+
+    /// [IRI expansion](https://www.w3.org/TR/json-ld11-api/#iri-expansion) on a context where all the terms are already defined
+    fn expand_iri(&self, value: &str, document_relative: bool, vocab: bool) -> Option<String> {
+        if is_keyword(value) {
+            return Some(value.to_owned());
+        }
+        if looks_like_keyword(value) {
+            return None;
+        }
+        if vocab {
+            if let Some(definition) = self.terms.get(value) {
+                return definition.iri.clone();
+            }
+        }
+        if let Some((prefix, suffix)) = split_compact_iri(value) {
+            if prefix == "_" || suffix.starts_with("//") {
+                return Some(value.to_owned());
+            }
+            if let Some(definition) = self.terms.get(prefix) {
+                if let (Some(iri), true) = (&definition.iri, definition.prefix) {
+                    return Some(format!("{}{}", iri, suffix));
+                }
+            }
+            if Iri::parse(value).is_ok() {
+                return Some(value.to_owned());
+            }
+        }
+        if vocab {
+            if let Some(vocab) = &self.vocab {
+                return Some(format!("{}{}", vocab, value));
+            }
+        }
+        if document_relative {
+            if let Some(base) = &self.base {
+                if let Ok(iri) = base.resolve(value) {
+                    return Some(iri.into_inner());
+                }
+            }
+        }
+        Some(value.to_owned())
+    }
+}
+
+/// Converts a JSON-LD document to RDF quads
+pub(crate) struct JsonLdParser<R: BufRead> {
+    state: Option<(R, Option<Iri<String>>, Arc<dyn JsonLdDocumentLoader>)>,
+    quads: std::vec::IntoIter<Quad>,
+}
+
+impl<R: BufRead> JsonLdParser<R> {
+    pub fn new(
+        reader: R,
+        base_iri: Option<Iri<String>>,
+        loader: Arc<dyn JsonLdDocumentLoader>,
+    ) -> Self {
+        Self {
+            state: Some((reader, base_iri, loader)),
+            quads: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLdParser<R> {
+    type Item = Result<Quad, io::Error>;
+
+    fn next(&mut self) -> Option<Result<Quad, io::Error>> {
+        if let Some((reader, base_iri, loader)) = self.state.take() {
+            let mut converter = ToRdfConverter {
+                loader: loader.as_ref(),
+                remote_documents: HashMap::new(),
+                bnodes: HashMap::new(),
+                quads: Vec::new(),
+            };
+            if let Err(error) = JsonValue::read(reader)
+                .and_then(|document| converter.convert_document(&document, base_iri))
+            {
+                return Some(Err(error));
+            }
+            self.quads = converter.quads.into_iter();
+        }
+        self.quads.next().map(Ok)
+    }
+}
+
+/// Implements the JSON-LD [expansion](https://www.w3.org/TR/json-ld11-api/#expansion-algorithm) and [RDF serialization](https://www.w3.org/TR/json-ld11-api/#deserialize-json-ld-to-rdf-algorithm) algorithms together.
+///
+/// The expanded document is never built: the quads are emitted directly while walking the input document.
+struct ToRdfConverter<'a> {
+    loader: &'a dyn JsonLdDocumentLoader,
+    remote_documents: HashMap<String, JsonValue>,
+    bnodes: HashMap<String, BlankNode>,
+    quads: Vec<Quad>,
+}
+
+impl<'a> ToRdfConverter<'a> {
+    fn convert_document(
+        &mut self,
+        document: &JsonValue,
+        base_iri: Option<Iri<String>>,
+    ) -> Result<(), io::Error> {
+        let context = Context::new(base_iri);
+        if let JsonValue::Object(entries) = document {
+            let context = self.node_context(entries, &context)?;
+            // A top level object containing only @graph describes the default graph
+            let mut graph = None;
+            let mut only_graph = true;
+            for (key, value) in entries {
+                if key == "@context" {
+                    continue;
+                }
+                match context.expand_iri(key, false, true).as_deref() {
+                    Some("@graph") => graph = Some(value),
+                    _ => only_graph = false,
+                }
+            }
+            if let (Some(graph), true) = (graph, only_graph) {
+                let mut objects = Vec::new();
+                return self.convert_value(
+                    graph,
+                    &context,
+                    None,
+                    &GraphName::DefaultGraph,
+                    &mut objects,
+                );
+            }
+        }
+        let mut objects = Vec::new();
+        self.convert_value(
+            document,
+            &context,
+            None,
+            &GraphName::DefaultGraph,
+            &mut objects,
+        )
+    }
+
+    /// Converts a JSON-LD value and adds the RDF terms it represents to `results`
+    fn convert_value(
+        &mut self,
+        value: &JsonValue,
+        context: &Context,
+        definition: Option<&TermDefinition>,
+        graph: &GraphName,
+        results: &mut Vec<Term>,
+    ) -> Result<(), io::Error> {
+        if let Some(definition) = definition {
+            if definition.type_mapping.as_deref() == Some("@json") {
+                results.push(json_literal(value)?.into());
+                return Ok(());
+            }
+        }
+        match value {
+            JsonValue::Null => Ok(()),
+            JsonValue::Array(values) => {
+                if matches!(definition, Some(d) if d.has_container("@list")) {
+                    let head = self.convert_list(values, context, definition, graph)?;
+                    results.push(head);
+                } else {
+                    for value in values {
+                        self.convert_value(value, context, definition, graph, results)?;
+                    }
+                }
+                Ok(())
+            }
+            JsonValue::Object(entries) => {
+                let context = self.node_context(entries, context)?;
+                let mut value_entry = None;
+                let mut list_entry = None;
+                let mut set_entry = None;
+                for (key, value) in entries {
+                    if key == "@context" {
+                        continue;
+                    }
+                    match context.expand_iri(key, false, true).as_deref() {
+                        Some("@value") => value_entry = Some(value),
+                        Some("@list") => list_entry = Some(value),
+                        Some("@set") => set_entry = Some(value),
+                        _ => (),
+                    }
+                }
+                if let Some(value) = value_entry {
+                    if let Some(literal) = self.convert_value_object(entries, value, &context)? {
+                        results.push(literal.into());
+                    }
+                } else if let Some(list) = list_entry {
+                    let head = if let JsonValue::Array(values) = list {
+                        self.convert_list(values, &context, definition, graph)?
+                    } else {
+                        self.convert_list(std::slice::from_ref(list), &context, definition, graph)?
+                    };
+                    results.push(head);
+                } else if let Some(set) = set_entry {
+                    let definition = definition.map(|d| TermDefinition {
+                        container: Vec::new(),
+                        ..d.clone()
+                    });
+                    self.convert_value(set, &context, definition.as_ref(), graph, results)?;
+                } else if let Some(node) = self.convert_node(entries, &context, graph)? {
+                    results.push(node.into());
+                }
+                Ok(())
+            }
+            JsonValue::String(value) => {
+                match definition.and_then(|d| d.type_mapping.as_deref()) {
+                    Some("@id") => {
+                        if let Some(node) = self.node(&context.expand_iri(value, true, false)) {
+                            results.push(node.into())
+                        }
+                    }
+                    Some("@vocab") => {
+                        if let Some(node) = self.node(&context.expand_iri(value, true, true)) {
+                            results.push(node.into())
+                        }
+                    }
+                    Some(datatype) if datatype != "@none" => {
+                        if let Ok(datatype) = NamedNode::new(datatype) {
+                            results.push(Literal::new_typed_literal(value, datatype).into())
+                        }
+                    }
+                    _ => {
+                        let language = match definition.and_then(|d| d.language.as_ref()) {
+                            Some(language) => language.as_deref(),
+                            None => context.language.as_deref(),
+                        };
+                        results.push(string_literal(value, language)?.into())
+                    }
+                }
+                Ok(())
+            }
+            JsonValue::Number(value) => {
+                results.push(
+                    number_literal(value, self.coerced_datatype(definition).as_deref()).into(),
+                );
+                Ok(())
+            }
+            JsonValue::Boolean(value) => {
+                results.push(
+                    Literal::new_typed_literal(
+                        if *value { "true" } else { "false" },
+                        self.coerced_datatype(definition)
+                            .map_or_else(|| xsd::BOOLEAN.into(), NamedNode::new_unchecked),
+                    )
+                    .into(),
+                );
+                Ok(())
+            }
+        }
+    }
+
+    fn coerced_datatype(&self, definition: Option<&TermDefinition>) -> Option<String> {
+        definition
+            .and_then(|d| d.type_mapping.as_ref())
+            .filter(|t| !t.starts_with('@') && Iri::parse(t.as_str()).is_ok())
+            .cloned()
+    }
+
+    /// Converts a [node object](https://www.w3.org/TR/json-ld11/#node-objects) and returns its identifier
+    fn convert_node(
+        &mut self,
+        entries: &[(String, JsonValue)],
+        context: &Context,
+        graph: &GraphName,
+    ) -> Result<Option<NamedOrBlankNode>, io::Error> {
+        // We apply the type-scoped contexts
+        let mut types = Vec::new();
+        for (key, value) in entries {
+            if key != "@context" && context.expand_iri(key, false, true).as_deref() == Some("@type")
+            {
+                for value in as_array(value) {
+                    if let JsonValue::String(value) = value {
+                        types.push(value.as_str())
+                    } else {
+                        return Err(invalid_data_error("@type values must be strings"));
+                    }
+                }
+            }
+        }
+        types.sort_unstable();
+        let mut node_context = context.clone();
+        for t in &types {
+            if let Some(scoped_context) = context.terms.get(*t).and_then(|d| d.context.clone()) {
+                node_context =
+                    self.process_context(&node_context, &scoped_context, &mut Vec::new())?;
+            }
+        }
+
+        let mut id = None;
+        let mut has_id = false;
+        for (key, value) in entries {
+            if key != "@context"
+                && node_context.expand_iri(key, false, true).as_deref() == Some("@id")
+            {
+                if let JsonValue::String(value) = value {
+                    id = self.node(&node_context.expand_iri(value, true, false));
+                    has_id = true;
+                } else {
+                    return Err(invalid_data_error("@id value must be a string"));
+                }
+            }
+        }
+        if !has_id {
+            id = Some(BlankNode::default().into())
+        }
+
+        for t in types {
+            if let Some(t) = node_context.expand_iri(t, true, true) {
+                if let (Some(id), Ok(t)) = (&id, NamedNode::new(t)) {
+                    self.quads
+                        .push(Quad::new(id.clone(), rdf::TYPE, t, graph.clone()));
+                }
+            }
+        }
+        self.convert_node_entries(entries, &node_context, context, graph, &id)?;
+        Ok(id)
+    }
+
+    fn convert_node_entries(
+        &mut self,
+        entries: &[(String, JsonValue)],
+        node_context: &Context,
+        context: &Context,
+        graph: &GraphName,
+        id: &Option<NamedOrBlankNode>,
+    ) -> Result<(), io::Error> {
+        for (key, value) in entries {
+            if key == "@context" {
+                continue;
+            }
+            let property = if let Some(property) = node_context.expand_iri(key, false, true) {
+                property
+            } else {
+                continue;
+            };
+            match property.as_str() {
+                "@id" | "@type" | "@index" | "@context" => (),
+                "@graph" => {
+                    if let Some(id) = id {
+                        let mut objects = Vec::new();
+                        self.convert_value(value, context, None, &id.clone().into(), &mut objects)?;
+                    }
+                }
+                "@included" => {
+                    let mut objects = Vec::new();
+                    self.convert_value(value, context, None, graph, &mut objects)?;
+                }
+                "@nest" => {
+                    for value in as_array(value) {
+                        if let JsonValue::Object(entries) = value {
+                            self.convert_node_entries(entries, node_context, context, graph, id)?;
+                        } else {
+                            return Err(invalid_data_error("@nest values must be objects"));
+                        }
+                    }
+                }
+                "@reverse" => {
+                    if let JsonValue::Object(entries) = value {
+                        for (key, value) in entries {
+                            let property = node_context.expand_iri(key, false, true);
+                            let mut subjects = Vec::new();
+                            self.convert_value(value, context, None, graph, &mut subjects)?;
+                            self.emit_reverse(property, subjects, id, graph)?;
+                        }
+                    } else {
+                        return Err(invalid_data_error("@reverse value must be an object"));
+                    }
+                }
+                "@value" | "@list" | "@set" | "@language" | "@direction" => {
+                    return Err(invalid_data_error(format!(
+                        "{} is not allowed in node objects",
+                        property
+                    )))
+                }
+                _ if property.starts_with('@') => (),
+                _ => {
+                    let definition = node_context.terms.get(key).cloned().unwrap_or_default();
+                    let value_context = if let Some(scoped_context) = &definition.context {
+                        self.process_context(context, scoped_context, &mut Vec::new())?
+                    } else {
+                        context.clone()
+                    };
+                    let mut objects = Vec::new();
+                    self.convert_property_value(
+                        value,
+                        &value_context,
+                        &definition,
+                        graph,
+                        &mut objects,
+                    )?;
+                    if definition.reverse {
+                        self.emit_reverse(Some(property), objects, id, graph)?;
+                    } else if let (Some(id), Ok(predicate)) = (id, NamedNode::new(property)) {
+                        for object in objects {
+                            self.quads.push(Quad::new(
+                                id.clone(),
+                                predicate.clone(),
+                                object,
+                                graph.clone(),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Converts the value of a property taking care of the language, index, id, type and graph maps
+    fn convert_property_value(
+        &mut self,
+        value: &JsonValue,
+        context: &Context,
+        definition: &TermDefinition,
+        graph: &GraphName,
+        results: &mut Vec<Term>,
+    ) -> Result<(), io::Error> {
+        let entries = if let JsonValue::Object(entries) = value {
+            entries
+        } else {
+            return self.convert_value(value, context, Some(definition), graph, results);
+        };
+        if definition.has_container("@language") {
+            for (language, value) in entries {
+                let language =
+                    if context.expand_iri(language, false, true).as_deref() == Some("@none") {
+                        None
+                    } else {
+                        Some(language.as_str())
+                    };
+                for value in as_array(value) {
+                    match value {
+                        JsonValue::String(value) => {
+                            results.push(string_literal(value, language)?.into())
+                        }
+                        JsonValue::Null => (),
+                        _ => {
+                            return Err(invalid_data_error(
+                                "The values of language maps must be strings",
+                            ))
+                        }
+                    }
+                }
+            }
+        } else if definition.has_container("@id") || definition.has_container("@type") {
+            let key_kind = if definition.has_container("@id") {
+                "@id"
+            } else {
+                "@type"
+            };
+            let item_definition = TermDefinition {
+                container: Vec::new(),
+                ..definition.clone()
+            };
+            for (key, value) in entries {
+                for value in as_array(value) {
+                    let value = match value {
+                        JsonValue::Object(entries) => {
+                            let mut entries = entries.clone();
+                            if context.expand_iri(key, false, true).as_deref() != Some("@none") {
+                                entries.push((key_kind.to_owned(), JsonValue::String(key.clone())));
+                            }
+                            JsonValue::Object(entries)
+                        }
+                        JsonValue::String(id) if key_kind == "@type" => JsonValue::Object(vec![
+                            ("@id".to_owned(), JsonValue::String(id.clone())),
+                            ("@type".to_owned(), JsonValue::String(key.clone())),
+                        ]),
+                        _ => {
+                            return Err(invalid_data_error(format!(
+                                "The values of {} maps must be node objects",
+                                key_kind
+                            )))
+                        }
+                    };
+                    self.convert_value(&value, context, Some(&item_definition), graph, results)?;
+                }
+            }
+        } else if definition.has_container("@graph") {
+            for value in as_array(value) {
+                let graph_name = BlankNode::default();
+                let mut objects = Vec::new();
+                self.convert_value(
+                    value,
+                    context,
+                    None,
+                    &graph_name.clone().into(),
+                    &mut objects,
+                )?;
+                results.push(graph_name.into());
+            }
+        } else if definition.has_container("@index") {
+            let item_definition = TermDefinition {
+                container: Vec::new(),
+                ..definition.clone()
+            };
+            for (_, value) in entries {
+                self.convert_value(value, context, Some(&item_definition), graph, results)?;
+            }
+        } else {
+            self.convert_value(value, context, Some(definition), graph, results)?;
+        }
+        Ok(())
+    }
+
+    /// Converts a [value object](https://www.w3.org/TR/json-ld11/#value-objects)
+    fn convert_value_object(
+        &mut self,
+        entries: &[(String, JsonValue)],
+        value: &JsonValue,
+        context: &Context,
+    ) -> Result<Option<Literal>, io::Error> {
+        let mut datatype = None;
+        let mut language = None;
+        for (key, v) in entries {
+            match context.expand_iri(key, false, true).as_deref() {
+                Some("@type") => {
+                    if let JsonValue::String(t) = v {
+                        datatype = context.expand_iri(t, true, true);
+                    } else {
+                        return Err(invalid_data_error("@type value must be a string"));
+                    }
+                }
+                Some("@language") => {
+                    if let JsonValue::String(l) = v {
+                        language = Some(l.as_str());
+                    } else {
+                        return Err(invalid_data_error("@language value must be a string"));
+                    }
+                }
+                _ => (),
+            }
+        }
+        if datatype.as_deref() == Some("@json") {
+            return Ok(Some(json_literal(value)?));
+        }
+        if let Some(t) = &datatype {
+            if NamedNode::new(t.as_str()).is_err() {
+                return Ok(None); // Relative datatype IRIs are dropped
+            }
+        }
+        Ok(match value {
+            JsonValue::Null => None,
+            JsonValue::String(value) => Some(if let Some(datatype) = datatype {
+                Literal::new_typed_literal(value, NamedNode::new_unchecked(datatype))
+            } else {
+                string_literal(value, language)?
+            }),
+            JsonValue::Number(value) => Some(number_literal(value, datatype.as_deref())),
+            JsonValue::Boolean(value) => Some(Literal::new_typed_literal(
+                if *value { "true" } else { "false" },
+                datatype.map_or_else(|| xsd::BOOLEAN.into(), NamedNode::new_unchecked),
+            )),
+            JsonValue::Array(_) | JsonValue::Object(_) => {
+                return Err(invalid_data_error(
+                    "@value must be a scalar if @type is not @json",
+                ))
+            }
+        })
+    }
+
+    /// Converts a [list](https://www.w3.org/TR/json-ld11/#lists) and returns its head
+    fn convert_list(
+        &mut self,
+        values: &[JsonValue],
+        context: &Context,
+        definition: Option<&TermDefinition>,
+        graph: &GraphName,
+    ) -> Result<Term, io::Error> {
+        let item_definition = definition.map(|d| TermDefinition {
+            container: Vec::new(),
+            ..d.clone()
+        });
+        let mut items = Vec::new();
+        for value in values {
+            if let JsonValue::Array(values) = value {
+                // Lists of lists
+                items.push(self.convert_list(values, context, definition, graph)?);
+            } else {
+                self.convert_value(value, context, item_definition.as_ref(), graph, &mut items)?;
+            }
+        }
+        let mut head: Term = rdf::NIL.into();
+        for item in items.into_iter().rev() {
+            let node = BlankNode::default();
+            self.quads
+                .push(Quad::new(node.clone(), rdf::FIRST, item, graph.clone()));
+            self.quads
+                .push(Quad::new(node.clone(), rdf::REST, head, graph.clone()));
+            head = node.into();
+        }
+        Ok(head)
+    }
+
+    fn emit_reverse(
+        &mut self,
+        property: Option<String>,
+        subjects: Vec<Term>,
+        id: &Option<NamedOrBlankNode>,
+        graph: &GraphName,
+    ) -> Result<(), io::Error> {
+        let (id, predicate) =
+            if let (Some(id), Some(Ok(predicate))) = (id, property.map(NamedNode::new)) {
+                (id, predicate)
+            } else {
+                return Ok(());
+            };
+        for subject in subjects {
+            let subject = match subject {
+                Term::NamedNode(node) => Subject::from(node),
+                Term::BlankNode(node) => node.into(),
+                Term::Literal(_) | Term::Triple(_) => {
+                    return Err(invalid_data_error(
+                        "The values of reverse properties must be node objects",
+                    ))
+                }
+            };
+            self.quads.push(Quad::new(
+                subject,
+                predicate.clone(),
+                id.clone(),
+                graph.clone(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builds a node from an expanded IRI or blank node identifier
+    ///
+    /// Returns `None` if the IRI is not absolute
+    fn node(&mut self, iri: &Option<String>) -> Option<NamedOrBlankNode> {
+        let iri = iri.as_ref()?;
+        if let Some(id) = iri.strip_prefix("_:") {
+            Some(self.bnodes.entry(id.to_owned()).or_default().clone().into())
+        } else {
+            NamedNode::new(iri.as_str()).ok().map(|n| n.into())
+        }
+    }
+
+    /// Applies the `@context` entry of an object if it exists
+    fn node_context(
+        &mut self,
+        entries: &[(String, JsonValue)],
+        context: &Context,
+    ) -> Result<Context, io::Error> {
+        if let Some((_, local_context)) = entries.iter().find(|(k, _)| k == "@context") {
+            self.process_context(context, local_context, &mut Vec::new())
+        } else {
+            Ok(context.clone())
+        }
+    }
+
+    /// The [context processing algorithm](https://www.w3.org/TR/json-ld11-api/#context-processing-algorithm)
+    fn process_context(
+        &mut self,
+        active_context: &Context,
+        local_context: &JsonValue,
+        remote_contexts: &mut Vec<String>,
+    ) -> Result<Context, io::Error> {
+        process_context(
+            active_context,
+            local_context,
+            remote_contexts,
+            self.loader,
+            &mut self.remote_documents,
+        )
+    }
+}
+
+fn process_context(
+    active_context: &Context,
+    local_context: &JsonValue,
+    remote_contexts: &mut Vec<String>,
+    loader: &dyn JsonLdDocumentLoader,
+    remote_documents: &mut HashMap<String, JsonValue>,
+) -> Result<Context, io::Error> {
+    let mut result = active_context.clone();
+    for context in as_array(local_context) {
+        match context {
+            JsonValue::Null => {
+                result = Context::new(active_context.base.clone());
+            }
+            JsonValue::String(iri) => {
+                let iri = resolve_iri(result.base.as_ref(), iri)?;
+                if remote_contexts.contains(&iri) {
+                    return Err(invalid_data_error(format!(
+                        "Recursive inclusion of the JSON-LD context {}",
+                        iri
+                    )));
+                }
+                if remote_contexts.len() >= MAX_NUMBER_OF_REMOTE_CONTEXTS {
+                    return Err(invalid_data_error(format!(
+                        "More than {} nested remote JSON-LD contexts",
+                        MAX_NUMBER_OF_REMOTE_CONTEXTS
+                    )));
+                }
+                let context = remote_context(&iri, loader, remote_documents)?;
+                remote_contexts.push(iri);
+                result =
+                    process_context(&result, &context, remote_contexts, loader, remote_documents)?;
+                remote_contexts.pop();
+            }
+            JsonValue::Object(entries) => {
+                let mut entries = entries.clone();
+                if let Some(import) = entries.iter().find(|(k, _)| k == "@import") {
+                    let import = if let JsonValue::String(iri) = &import.1 {
+                        resolve_iri(result.base.as_ref(), iri)?
+                    } else {
+                        return Err(invalid_data_error("@import value must be a string"));
+                    };
+                    if let JsonValue::Object(imported) =
+                        remote_context(&import, loader, remote_documents)?
+                    {
+                        for (key, value) in imported {
+                            if !entries.iter().any(|(k, _)| *k == key) {
+                                entries.push((key, value));
+                            }
+                        }
+                    } else {
+                        return Err(invalid_data_error(
+                            "Imported JSON-LD contexts must be objects",
+                        ));
+                    }
+                }
+                for (key, value) in &entries {
+                    match key.as_str() {
+                        "@base" if remote_contexts.is_empty() => {
+                            result.base = match value {
+                                JsonValue::Null => None,
+                                JsonValue::String(iri) => Some(
+                                    Iri::parse(resolve_iri(result.base.as_ref(), iri)?)
+                                        .map_err(invalid_data_error)?,
+                                ),
+                                _ => {
+                                    return Err(invalid_data_error("@base value must be a string"))
+                                }
+                            };
+                        }
+                        "@vocab" => {
+                            result.vocab = match value {
+                                JsonValue::Null => None,
+                                JsonValue::String(iri) => result.expand_iri(iri, true, true),
+                                _ => {
+                                    return Err(invalid_data_error("@vocab value must be a string"))
+                                }
+                            };
+                        }
+                        "@language" => {
+                            result.language = match value {
+                                JsonValue::Null => None,
+                                JsonValue::String(language) => Some(language.clone()),
+                                _ => {
+                                    return Err(invalid_data_error(
+                                        "@language value must be a string",
+                                    ))
+                                }
+                            };
+                        }
+                        _ => (),
+                    }
+                }
+                let mut defined = HashMap::new();
+                for (key, _) in &entries {
+                    if !matches!(
+                        key.as_str(),
+                        "@base"
+                            | "@vocab"
+                            | "@language"
+                            | "@version"
+                            | "@import"
+                            | "@propagate"
+                            | "@protected"
+                            | "@direction"
+                    ) {
+                        create_term_definition(&mut result, &entries, key, &mut defined)?;
+                    }
+                }
+            }
+            _ => return Err(invalid_data_error("Invalid JSON-LD context")),
+        }
+    }
+    Ok(result)
+}
+
+/// The [create term definition algorithm](https://www.w3.org/TR/json-ld11-api/#create-term-definition)
+fn create_term_definition(
+    active_context: &mut Context,
+    local_context: &[(String, JsonValue)],
+    term: &str,
+    defined: &mut HashMap<String, bool>,
+) -> Result<(), io::Error> {
+    match defined.get(term) {
+        Some(true) => return Ok(()),
+        Some(false) => {
+            return Err(invalid_data_error(format!(
+                "Cyclic IRI mapping for the JSON-LD term {}",
+                term
+            )))
+        }
+        None => (),
+    }
+    if term.is_empty() {
+        return Err(invalid_data_error("Empty JSON-LD term definition"));
+    }
+    let value = if let Some((_, value)) = local_context.iter().find(|(k, _)| k == term) {
+        value
+    } else {
+        return Ok(());
+    };
+    if is_keyword(term) && term != "@type" {
+        return Err(invalid_data_error(format!(
+            "The JSON-LD keyword {} can't be redefined",
+            term
+        )));
+    }
+    if term == "@type" || looks_like_keyword(term) {
+        // @type may only be redefined to set its container and unknown keywords are ignored
+        defined.insert(term.to_owned(), true);
+        return Ok(());
+    }
+    defined.insert(term.to_owned(), false);
+    active_context.terms.remove(term);
+
+    let (entries, simple_term) = match value {
+        JsonValue::Null => (vec![("@id".to_owned(), JsonValue::Null)], true),
+        JsonValue::String(id) => (
+            vec![("@id".to_owned(), JsonValue::String(id.clone()))],
+            true,
+        ),
+        JsonValue::Object(entries) => (entries.clone(), false),
+        _ => {
+            return Err(invalid_data_error(format!(
+                "Invalid JSON-LD term definition for {}",
+                term
+            )))
+        }
+    };
+    let mut definition = TermDefinition::default();
+    let mut id_entry = None;
+    for (key, value) in &entries {
+        match key.as_str() {
+            "@id" => id_entry = Some(value),
+            "@reverse" => {
+                if let JsonValue::String(reverse) = value {
+                    definition.iri =
+                        expand_iri_in_definition(active_context, local_context, defined, reverse)?;
+                    definition.reverse = true;
+                } else {
+                    return Err(invalid_data_error("@reverse value must be a string"));
+                }
+            }
+            "@type" => {
+                if let JsonValue::String(t) = value {
+                    let t = expand_iri_in_definition(active_context, local_context, defined, t)?
+                        .filter(|t| {
+                            matches!(t.as_str(), "@id" | "@vocab" | "@json" | "@none")
+                                || Iri::parse(t.as_str()).is_ok()
+                        })
+                        .ok_or_else(|| {
+                            invalid_data_error(format!(
+                                "Invalid type mapping for the term {}",
+                                term
+                            ))
+                        })?;
+                    definition.type_mapping = Some(t);
+                } else {
+                    return Err(invalid_data_error("@type value must be a string"));
+                }
+            }
+            "@container" => {
+                for container in as_array(value) {
+                    if let JsonValue::String(container) = container {
+                        if !matches!(
+                            container.as_str(),
+                            "@list" | "@set" | "@language" | "@index" | "@id" | "@type" | "@graph"
+                        ) {
+                            return Err(invalid_data_error(format!(
+                                "Invalid container mapping {}",
+                                container
+                            )));
+                        }
+                        definition.container.push(container.clone());
+                    } else if container != &JsonValue::Null {
+                        return Err(invalid_data_error("@container values must be strings"));
+                    }
+                }
+            }
+            "@language" => {
+                definition.language = Some(match value {
+                    JsonValue::Null => None,
+                    JsonValue::String(language) => Some(language.clone()),
+                    _ => return Err(invalid_data_error("@language value must be a string")),
+                });
+            }
+            "@context" => definition.context = Some(value.clone()),
+            "@prefix" => {
+                if let JsonValue::Boolean(prefix) = value {
+                    definition.prefix = *prefix;
+                } else {
+                    return Err(invalid_data_error("@prefix value must be a boolean"));
+                }
+            }
+            "@index" | "@nest" | "@direction" | "@protected" => (),
+            _ => {
+                return Err(invalid_data_error(format!(
+                    "Invalid entry {} in the definition of the JSON-LD term {}",
+                    key, term
+                )))
+            }
+        }
+    }
+    if !definition.reverse {
+        match id_entry {
+            Some(JsonValue::Null) => (),
+            Some(JsonValue::String(id)) if id != term => {
+                definition.iri =
+                    expand_iri_in_definition(active_context, local_context, defined, id)?;
+                match &definition.iri {
+                    Some(iri) if iri.starts_with('@') && !is_keyword(iri) => definition.iri = None,
+                    Some(iri) => {
+                        if simple_term
+                            && !term.contains(':')
+                            && !term.contains('/')
+                            && (iri.starts_with("_:")
+                                || iri.ends_with(|c| {
+                                    matches!(c, ':' | '/' | '?' | '#' | '[' | ']' | '@')
+                                }))
+                        {
+                            definition.prefix = true;
+                        }
+                    }
+                    None => {
+                        return Err(invalid_data_error(format!(
+                            "Invalid IRI mapping for the JSON-LD term {}",
+                            term
+                        )))
+                    }
+                }
+            }
+            Some(JsonValue::String(_)) | None => {
+                definition.iri = if let Some((prefix, suffix)) = split_compact_iri(term) {
+                    if local_context.iter().any(|(k, _)| k == prefix) {
+                        create_term_definition(active_context, local_context, prefix, defined)?;
+                    }
+                    match active_context
+                        .terms
+                        .get(prefix)
+                        .and_then(|d| d.iri.as_ref())
+                    {
+                        Some(iri) => Some(format!("{}{}", iri, suffix)),
+                        None => Some(term.to_owned()),
+                    }
+                } else if term.contains('/') {
+                    active_context.expand_iri(term, false, true)
+                } else if let Some(vocab) = &active_context.vocab {
+                    Some(format!("{}{}", vocab, term))
+                } else {
+                    return Err(invalid_data_error(format!(
+                        "The JSON-LD term {} has no IRI mapping and no @vocab is defined",
+                        term
+                    )));
+                };
+            }
+            Some(_) => return Err(invalid_data_error("@id value must be a string")),
+        }
+    }
+    active_context.terms.insert(term.to_owned(), definition);
+    defined.insert(term.to_owned(), true);
+    Ok(())
+}
+
+/// IRI expansion that defines first the terms of the local context the value depends on
+fn expand_iri_in_definition(
+    active_context: &mut Context,
+    local_context: &[(String, JsonValue)],
+    defined: &mut HashMap<String, bool>,
+    value: &str,
+) -> Result<Option<String>, io::Error> {
+    if local_context.iter().any(|(k, _)| k == value) {
+        create_term_definition(active_context, local_context, value, defined)?;
+    }
+    if let Some((prefix, _)) = split_compact_iri(value) {
+        if local_context.iter().any(|(k, _)| k == prefix) {
+            create_term_definition(active_context, local_context, prefix, defined)?;
+        }
+    }
+    Ok(active_context.expand_iri(value, false, true))
+}
+
+fn remote_context(
+    iri: &str,
+    loader: &dyn JsonLdDocumentLoader,
+    remote_documents: &mut HashMap<String, JsonValue>,
+) -> Result<JsonValue, io::Error> {
+    if !remote_documents.contains_key(iri) {
+        let document = JsonValue::read(loader.load(iri)?)?;
+        remote_documents.insert(iri.to_owned(), document);
+    }
+    remote_documents[iri]
+        .get("@context")
+        .cloned()
+        .ok_or_else(|| {
+            invalid_data_error(format!(
+                "The remote JSON-LD context {} does not contain a @context entry",
+                iri
+            ))
+        })
+}
+
+fn resolve_iri(base: Option<&Iri<String>>, iri: &str) -> Result<String, io::Error> {
+    Ok(if let Some(base) = base {
+        base.resolve(iri).map_err(invalid_data_error)?.into_inner()
+    } else {
+        Iri::parse(iri.to_owned())
+            .map_err(invalid_data_error)?
+            .into_inner()
+    })
+}
+
+fn is_keyword(value: &str) -> bool {
+    matches!(
+        value,
+        "@base"
+            | "@container"
+            | "@context"
+            | "@direction"
+            | "@graph"
+            | "@id"
+            | "@import"
+            | "@included"
+            | "@index"
+            | "@json"
+            | "@language"
+            | "@list"
+            | "@nest"
+            | "@none"
+            | "@prefix"
+            | "@propagate"
+            | "@protected"
+            | "@reverse"
+            | "@set"
+            | "@type"
+            | "@value"
+            | "@version"
+            | "@vocab"
+    )
+}
+
+/// Values of the form `@` followed by ASCII letters are reserved for future keywords
+fn looks_like_keyword(value: &str) -> bool {
+    value.len() > 1 && value.starts_with('@') && value[1..].bytes().all(|c| c.is_ascii_alphabetic())
+}
+
+fn split_compact_iri(value: &str) -> Option<(&str, &str)> {
+    let position = value.find(':')?;
+    Some((&value[..position], &value[position + 1..]))
+}
+
+fn as_array(value: &JsonValue) -> &[JsonValue] {
+    if let JsonValue::Array(values) = value {
+        values
+    } else {
+        std::slice::from_ref(value)
+    }
+}
+
+fn string_literal(value: &str, language: Option<&str>) -> Result<Literal, io::Error> {
+    Ok(if let Some(language) = language {
+        Literal::new_language_tagged_literal(value, language).map_err(invalid_data_error)?
+    } else {
+        Literal::new_simple_literal(value)
+    })
+}
+
+/// Converts a JSON number following the [JSON-LD rules](https://www.w3.org/TR/json-ld11-api/#data-round-tripping)
+fn number_literal(value: &str, datatype: Option<&str>) -> Literal {
+    let number: f64 = value.parse().unwrap_or(f64::NAN);
+    let is_integer = !value.contains(&['.', 'e', 'E'][..]);
+    if datatype != Some(xsd::DOUBLE.as_str()) && number.fract() == 0. && number.abs() < 1e21 {
+        let lexical = if is_integer {
+            value.to_owned()
+        } else {
+            format!("{:.0}", number)
+        };
+        Literal::new_typed_literal(
+            lexical,
+            datatype.map_or_else(|| xsd::INTEGER.into(), NamedNode::new_unchecked),
+        )
+    } else {
+        // Canonical xsd:double lexical form
+        let mut lexical = format!("{:E}", number);
+        if let Some(position) = lexical.find('E') {
+            if !lexical[..position].contains('.') {
+                lexical.insert_str(position, ".0");
+            }
+        }
+        Literal::new_typed_literal(
+            lexical,
+            datatype.map_or_else(|| xsd::DOUBLE.into(), NamedNode::new_unchecked),
+        )
+    }
+}
+
+fn json_literal(value: &JsonValue) -> Result<Literal, io::Error> {
+    let mut buffer = Vec::new();
+    canonicalize_json(value).write(&mut buffer)?;
+    Ok(Literal::new_typed_literal(
+        String::from_utf8(buffer).map_err(invalid_data_error)?,
+        NamedNode::new_unchecked(RDF_JSON),
+    ))
+}
+
+/// Sorts the object keys
+fn canonicalize_json(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Array(values) => {
+            JsonValue::Array(values.iter().map(canonicalize_json).collect())
+        }
+        JsonValue::Object(entries) => {
+            let mut entries = entries
+                .iter()
+                .map(|(k, v)| (k.clone(), canonicalize_json(v)))
+                .collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            JsonValue::Object(entries)
+        }
+        value => value.clone(),
+    }
+}
+
+/// Writes quads as a JSON-LD document.
+///
+/// The quads are buffered until [`finish`](JsonLdFormatter::finish()) is called in order to group them by graph and subject.
+/// The output is in [expanded form](https://www.w3.org/TR/json-ld11/#expanded-document-form)
+/// or in [compacted form](https://www.w3.org/TR/json-ld11/#compacted-document-form) if a context is given.
+pub(crate) struct JsonLdFormatter<W: Write> {
+    write: W,
+    context: Option<(JsonValue, Context)>,
+    graphs: Vec<(GraphName, Vec<NodeDescription>)>,
+    graph_ids: HashMap<GraphName, usize>,
+    node_ids: HashMap<(usize, NamedOrBlankNode), usize>,
+}
+
+struct NodeDescription {
+    id: NamedOrBlankNode,
+    types: Vec<NamedNode>,
+    properties: Vec<(NamedNode, Vec<Term>)>,
+}
+
+impl<W: Write> JsonLdFormatter<W> {
+    pub fn new(
+        write: W,
+        context: Option<&str>,
+        loader: &dyn JsonLdDocumentLoader,
+    ) -> Result<Self, io::Error> {
+        let context = if let Some(context) = context {
+            let raw = JsonValue::read(context.as_bytes()).map_err(invalid_input_error)?;
+            let processed = process_context(
+                &Context::default(),
+                &raw,
+                &mut Vec::new(),
+                loader,
+                &mut HashMap::new(),
+            )
+            .map_err(invalid_input_error)?;
+            Some((raw, processed))
+        } else {
+            None
+        };
+        Ok(Self {
+            write,
+            context,
+            graphs: Vec::new(),
+            graph_ids: HashMap::new(),
+            node_ids: HashMap::new(),
+        })
+    }
+
+    pub fn format(&mut self, quad: QuadRef<'_>) -> Result<(), io::Error> {
+        let id = match quad.subject {
+            SubjectRef::NamedNode(node) => NamedOrBlankNode::from(node.into_owned()),
+            SubjectRef::BlankNode(node) => node.into_owned().into(),
+            SubjectRef::Triple(_) => return Err(quoted_triples_not_supported()),
+        };
+        if quad.object.is_triple() {
+            return Err(quoted_triples_not_supported());
+        }
+        let graphs = &mut self.graphs;
+        let graph_id = *self
+            .graph_ids
+            .entry(quad.graph_name.into_owned())
+            .or_insert_with(|| {
+                graphs.push((quad.graph_name.into_owned(), Vec::new()));
+                graphs.len() - 1
+            });
+        let nodes = &mut self.graphs[graph_id].1;
+        let node_id = *self
+            .node_ids
+            .entry((graph_id, id.clone()))
+            .or_insert_with(|| {
+                nodes.push(NodeDescription {
+                    id,
+                    types: Vec::new(),
+                    properties: Vec::new(),
+                });
+                nodes.len() - 1
+            });
+        let node = &mut nodes[node_id];
+        if quad.predicate == rdf::TYPE {
+            if let TermRef::NamedNode(t) = quad.object {
+                node.types.push(t.into_owned());
+                return Ok(());
+            }
+        }
+        if let Some((_, objects)) = node
+            .properties
+            .iter_mut()
+            .find(|(p, _)| *p == quad.predicate)
+        {
+            objects.push(quad.object.into_owned());
+        } else {
+            node.properties
+                .push((quad.predicate.into_owned(), vec![quad.object.into_owned()]));
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<W, io::Error> {
+        let mut default_graph_nodes = Vec::new();
+        let mut named_graphs = Vec::new();
+        for (graph_name, nodes) in &self.graphs {
+            let nodes = nodes
+                .iter()
+                .map(|n| self.node_to_json(n))
+                .collect::<Vec<_>>();
+            if graph_name.is_default_graph() {
+                default_graph_nodes = nodes;
+            } else {
+                named_graphs.push((graph_name, nodes));
+            }
+        }
+        for (graph_name, nodes) in named_graphs {
+            let id = match graph_name {
+                GraphName::NamedNode(node) => self.compact_id(node.as_str()),
+                GraphName::BlankNode(node) => format!("_:{}", node.as_str()),
+                GraphName::DefaultGraph => unreachable!(),
+            };
+            let graph_key = self.compact_keyword("@graph");
+            let graph = (graph_key, JsonValue::Array(nodes));
+            let id_key = self.compact_keyword("@id");
+            if let Some(JsonValue::Object(node)) = default_graph_nodes
+                .iter_mut()
+                .find(|n| n.get(&id_key) == Some(&JsonValue::String(id.clone())))
+            {
+                node.push(graph);
+            } else {
+                default_graph_nodes.push(JsonValue::Object(vec![
+                    (id_key, JsonValue::String(id)),
+                    graph,
+                ]));
+            }
+        }
+        let document = if let Some((context, _)) = &self.context {
+            JsonValue::Object(vec![
+                ("@context".to_owned(), context.clone()),
+                (
+                    self.compact_keyword("@graph"),
+                    JsonValue::Array(default_graph_nodes),
+                ),
+            ])
+        } else {
+            JsonValue::Array(default_graph_nodes)
+        };
+        document.write(&mut self.write)?;
+        self.write.flush()?;
+        Ok(self.write)
+    }
+
+    fn node_to_json(&self, node: &NodeDescription) -> JsonValue {
+        let mut entries = vec![(
+            self.compact_keyword("@id"),
+            JsonValue::String(match &node.id {
+                NamedOrBlankNode::NamedNode(node) => self.compact_id(node.as_str()),
+                NamedOrBlankNode::BlankNode(node) => format!("_:{}", node.as_str()),
+            }),
+        )];
+        if !node.types.is_empty() {
+            let types = node
+                .types
+                .iter()
+                .map(|t| JsonValue::String(self.compact_vocab(t.as_str())))
+                .collect::<Vec<_>>();
+            entries.push((
+                self.compact_keyword("@type"),
+                self.compact_array(types, false),
+            ));
+        }
+        for (predicate, objects) in &node.properties {
+            let mut values: Vec<(String, bool, Vec<JsonValue>)> = Vec::new();
+            for object in objects {
+                let (key, definition) = self.select_term(predicate, object.as_ref());
+                let value = self.value_to_json(object.as_ref(), definition);
+                if let Some((_, _, v)) = values.iter_mut().find(|(k, _, _)| *k == key) {
+                    v.push(value);
+                } else {
+                    let is_set = matches!(definition, Some(d) if d.has_container("@set"));
+                    values.push((key, is_set, vec![value]));
+                }
+            }
+            for (key, is_set, values) in values {
+                entries.push((key, self.compact_array(values, is_set)));
+            }
+        }
+        JsonValue::Object(entries)
+    }
+
+    fn value_to_json(&self, value: TermRef<'_>, definition: Option<&TermDefinition>) -> JsonValue {
+        let type_mapping = definition.and_then(|d| d.type_mapping.as_deref());
+        match value {
+            TermRef::NamedNode(node) => match type_mapping {
+                Some("@id") => JsonValue::String(self.compact_id(node.as_str())),
+                Some("@vocab") => JsonValue::String(self.compact_vocab(node.as_str())),
+                _ => JsonValue::Object(vec![(
+                    self.compact_keyword("@id"),
+                    JsonValue::String(self.compact_id(node.as_str())),
+                )]),
+            },
+            TermRef::BlankNode(node) => {
+                let id = format!("_:{}", node.as_str());
+                if matches!(type_mapping, Some("@id") | Some("@vocab")) {
+                    JsonValue::String(id)
+                } else {
+                    JsonValue::Object(vec![(self.compact_keyword("@id"), JsonValue::String(id))])
+                }
+            }
+            TermRef::Literal(literal) => {
+                let value = JsonValue::String(literal.value().to_owned());
+                if let Some(language) = literal.language() {
+                    if self.effective_language(definition) == Some(language) {
+                        value
+                    } else {
+                        JsonValue::Object(vec![
+                            (self.compact_keyword("@value"), value),
+                            (
+                                self.compact_keyword("@language"),
+                                JsonValue::String(language.to_owned()),
+                            ),
+                        ])
+                    }
+                } else if literal.datatype() == xsd::STRING {
+                    if self.context.is_some()
+                        && type_mapping.is_none()
+                        && self.effective_language(definition).is_none()
+                    {
+                        value
+                    } else {
+                        JsonValue::Object(vec![(self.compact_keyword("@value"), value)])
+                    }
+                } else if type_mapping == Some(literal.datatype().as_str()) {
+                    value
+                } else {
+                    JsonValue::Object(vec![
+                        (self.compact_keyword("@value"), value),
+                        (
+                            self.compact_keyword("@type"),
+                            JsonValue::String(self.compact_vocab(literal.datatype().as_str())),
+                        ),
+                    ])
+                }
+            }
+            TermRef::Triple(_) => unreachable!(),
+        }
+    }
+
+    fn effective_language<'b>(&'b self, definition: Option<&'b TermDefinition>) -> Option<&'b str> {
+        match definition.and_then(|d| d.language.as_ref()) {
+            Some(language) => language.as_deref(),
+            None => self
+                .context
+                .as_ref()
+                .and_then(|(_, c)| c.language.as_deref()),
+        }
+    }
+
+    fn compact_array(&self, mut values: Vec<JsonValue>, is_set: bool) -> JsonValue {
+        if values.len() == 1 && !is_set && self.context.is_some() {
+            values.pop().unwrap()
+        } else {
+            JsonValue::Array(values)
+        }
+    }
+
+    /// Selects the term to use for a property value (a simplified version of the [term selection algorithm](https://www.w3.org/TR/json-ld11-api/#term-selection))
+    fn select_term(
+        &self,
+        predicate: &NamedNode,
+        value: TermRef<'_>,
+    ) -> (String, Option<&TermDefinition>) {
+        let context = if let Some((_, context)) = &self.context {
+            context
+        } else {
+            return (predicate.as_str().to_owned(), None);
+        };
+        let mut best: Option<(u8, &str, &TermDefinition)> = None;
+        for (term, definition) in &context.terms {
+            if definition.iri.as_deref() != Some(predicate.as_str())
+                || definition.reverse
+                || definition.container.iter().any(|c| c != "@set")
+            {
+                continue;
+            }
+            let score = match (value, definition.type_mapping.as_deref()) {
+                (TermRef::NamedNode(_), Some("@id"))
+                | (TermRef::NamedNode(_), Some("@vocab"))
+                | (TermRef::BlankNode(_), Some("@id")) => 2,
+                (TermRef::Literal(literal), Some(datatype))
+                    if literal.datatype().as_str() == datatype =>
+                {
+                    2
+                }
+                (TermRef::Literal(literal), None)
+                    if definition.language.is_some()
+                        && literal.language()
+                            == definition.language.as_ref().unwrap().as_deref()
+                        && (literal.language().is_some() || literal.datatype() == xsd::STRING) =>
+                {
+                    2
+                }
+                (_, None) if definition.language.is_none() => 1,
+                _ => continue,
+            };
+            let is_better = match best {
+                None => true,
+                Some((best_score, best_term, _)) => {
+                    score > best_score
+                        || (score == best_score
+                            && (term.len(), term.as_str()) < (best_term.len(), best_term))
+                }
+            };
+            if is_better {
+                best = Some((score, term, definition));
+            }
+        }
+        if let Some((_, term, definition)) = best {
+            (term.to_owned(), Some(definition))
+        } else {
+            (self.compact_vocab(predicate.as_str()), None)
+        }
+    }
+
+    fn compact_keyword(&self, keyword: &str) -> String {
+        if let Some((_, context)) = &self.context {
+            context
+                .terms
+                .iter()
+                .filter(|(_, d)| d.iri.as_deref() == Some(keyword))
+                .map(|(t, _)| t)
+                .min_by(|a, b| (a.len(), a).cmp(&(b.len(), b)))
+                .cloned()
+                .unwrap_or_else(|| keyword.to_owned())
+        } else {
+            keyword.to_owned()
+        }
+    }
+
+    /// Compacts an IRI used as a property or a type
+    fn compact_vocab(&self, iri: &str) -> String {
+        let context = if let Some((_, context)) = &self.context {
+            context
+        } else {
+            return iri.to_owned();
+        };
+        if let Some(term) = context
+            .terms
+            .iter()
+            .filter(|(_, d)| {
+                d.iri.as_deref() == Some(iri)
+                    && !d.reverse
+                    && d.type_mapping.is_none()
+                    && d.language.is_none()
+                    && d.container.is_empty()
+            })
+            .map(|(t, _)| t)
+            .min_by(|a, b| (a.len(), a).cmp(&(b.len(), b)))
+        {
+            return term.clone();
+        }
+        if let Some(vocab) = &context.vocab {
+            if let Some(suffix) = iri.strip_prefix(vocab.as_str()) {
+                if !suffix.is_empty() && !context.terms.contains_key(suffix) {
+                    return suffix.to_owned();
+                }
+            }
+        }
+        self.compact_iri(context, iri)
+    }
+
+    /// Compacts an IRI used as a node identifier
+    fn compact_id(&self, iri: &str) -> String {
+        if let Some((_, context)) = &self.context {
+            self.compact_iri(context, iri)
+        } else {
+            iri.to_owned()
+        }
+    }
+
+    /// Builds the shortest [compact IRI](https://www.w3.org/TR/json-ld11/#compact-iris)
+    fn compact_iri(&self, context: &Context, iri: &str) -> String {
+        let mut best: Option<String> = None;
+        for (term, definition) in &context.terms {
+            if !definition.prefix || term.contains(':') {
+                continue;
+            }
+            if let Some(prefix) = &definition.iri {
+                if let Some(suffix) = iri.strip_prefix(prefix.as_str()) {
+                    if suffix.is_empty() {
+                        continue;
+                    }
+                    let candidate = format!("{}:{}", term, suffix);
+                    if matches!(context.terms.get(&candidate), Some(d) if d.iri.as_deref() != Some(iri))
+                    {
+                        continue;
+                    }
+                    let is_better = match &best {
+                        Some(best) => (candidate.len(), &candidate) < (best.len(), best),
+                        None => true,
+                    };
+                    if is_better {
+                        best = Some(candidate);
+                    }
+                }
+            }
+        }
+        best.unwrap_or_else(|| iri.to_owned())
+    }
+}
+
+fn quoted_triples_not_supported() -> io::Error {
+    invalid_input_error("JSON-LD does not support RDF-star quoted triples")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(document: &str) -> Result<Vec<String>, io::Error> {
+        let mut documents = HashMap::new();
+        documents.insert(
+            "http://example.com/context".to_owned(),
+            r#"{"@context": {"ex": "http://example.com/", "name": "ex:name", "knows": {"@id": "ex:knows", "@type": "@id"}}}"#.to_owned(),
+        );
+        let mut quads = JsonLdParser::new(
+            document.as_bytes(),
+            Some(Iri::parse("http://example.com/base/".to_owned()).unwrap()),
+            Arc::new(documents),
+        )
+        .map(|q| q.map(|q| q.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+        quads.sort();
+        Ok(quads)
+    }
+
+    #[test]
+    fn test_to_rdf() -> Result<(), io::Error> {
+        assert_eq!(
+            parse(
+                r#"{
+                    "@context": ["http://example.com/context", {"@language": "en", "age": {"@id": "ex:age"}}],
+                    "@id": "alice",
+                    "@type": "ex:Person",
+                    "name": "Alice",
+                    "age": 42,
+                    "ex:height": 1.7,
+                    "knows": ["bob", {"@id": "ex:carol", "name": {"@value": "Carol", "@language": "fr"}}],
+                    "ex:tagged": {"@value": "1", "@type": "ex:dt"},
+                    "ex:flag": true,
+                    "ex:absent": null
+                }"#
+            )?,
+            vec![
+                "<http://example.com/base/alice> <http://example.com/age> \"42\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+                "<http://example.com/base/alice> <http://example.com/flag> \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean>",
+                "<http://example.com/base/alice> <http://example.com/height> \"1.7E0\"^^<http://www.w3.org/2001/XMLSchema#double>",
+                "<http://example.com/base/alice> <http://example.com/knows> <http://example.com/base/bob>",
+                "<http://example.com/base/alice> <http://example.com/knows> <http://example.com/carol>",
+                "<http://example.com/base/alice> <http://example.com/name> \"Alice\"@en",
+                "<http://example.com/base/alice> <http://example.com/tagged> \"1\"^^<http://example.com/dt>",
+                "<http://example.com/base/alice> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.com/Person>",
+                "<http://example.com/carol> <http://example.com/name> \"Carol\"@fr",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_rdf_graphs_and_lists() -> Result<(), io::Error> {
+        let quads = parse(
+            r#"{
+                "@context": {"@vocab": "http://example.com/", "list": {"@container": "@list"}},
+                "@graph": [
+                    {"@id": "http://example.com/g", "@graph": {"@id": "http://example.com/s", "p": "o"}},
+                    {"@id": "http://example.com/s", "list": [1, 2], "@reverse": {"r": {"@id": "http://example.com/o"}}}
+                ]
+            }"#,
+        )?;
+        assert_eq!(quads.len(), 7);
+        assert!(quads.contains(
+            &"<http://example.com/s> <http://example.com/p> \"o\" <http://example.com/g>"
+                .to_owned()
+        ));
+        assert!(quads.contains(
+            &"<http://example.com/o> <http://example.com/r> <http://example.com/s>".to_owned()
+        ));
+        assert_eq!(
+            quads
+                .iter()
+                .filter(|q| q.contains("22-rdf-syntax-ns#first"))
+                .count(),
+            2
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_documents() {
+        for document in &[
+            "{\"@context\": \"http://example.com/unknown\"}",
+            "{\"@context\": {\"a\": \"b:c\", \"b\": \"a:d\"}, \"a\": 1}",
+            "{\"@context\": {\"@id\": \"http://example.com/\"}}",
+            "{\"@id\": 1}",
+            "[1",
+        ] {
+            assert!(parse(document).is_err(), "{} should be invalid", document);
+        }
+    }
+
+    fn serialize(quads: &[Quad], context: Option<&str>) -> Result<String, io::Error> {
+        let mut buffer = Vec::new();
+        let mut formatter =
+            JsonLdFormatter::new(&mut buffer, context, &HashMap::<String, String>::new())?;
+        for quad in quads {
+            formatter.format(quad.as_ref())?;
+        }
+        formatter.finish()?;
+        Ok(String::from_utf8(buffer).unwrap())
+    }
+
+    #[test]
+    fn test_from_rdf() -> Result<(), io::Error> {
+        let s = NamedNode::new_unchecked("http://example.com/s");
+        let p = NamedNode::new_unchecked("http://example.com/p");
+        let g = NamedNode::new_unchecked("http://example.com/g");
+        let quads = vec![
+            Quad::new(
+                s.clone(),
+                rdf::TYPE,
+                NamedNode::new_unchecked("http://example.com/C"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(s.clone(), p.clone(), s.clone(), GraphName::DefaultGraph),
+            Quad::new(
+                s.clone(),
+                p.clone(),
+                Literal::new_simple_literal("foo"),
+                GraphName::DefaultGraph,
+            ),
+            Quad::new(
+                s.clone(),
+                p.clone(),
+                Literal::new_language_tagged_literal_unchecked("bar", "en"),
+                g.clone(),
+            ),
+            Quad::new(s.clone(), p.clone(), Literal::from(1), g.clone()),
+        ];
+        assert_eq!(
+            serialize(&quads, None)?,
+            r#"[{"@id":"http://example.com/s","@type":["http://example.com/C"],"http://example.com/p":[{"@id":"http://example.com/s"},{"@value":"foo"}]},{"@id":"http://example.com/g","@graph":[{"@id":"http://example.com/s","http://example.com/p":[{"@value":"bar","@language":"en"},{"@value":"1","@type":"http://www.w3.org/2001/XMLSchema#integer"}]}]}]"#
+        );
+        assert_eq!(
+            serialize(
+                &quads,
+                Some(
+                    r#"{"ex": "http://example.com/", "xsd": "http://www.w3.org/2001/XMLSchema#", "p": {"@id": "ex:p", "@type": "@id"}, "label": {"@id": "ex:p", "@language": "en"}}"#
+                )
+            )?,
+            r#"{"@context":{"ex":"http://example.com/","xsd":"http://www.w3.org/2001/XMLSchema#","p":{"@id":"ex:p","@type":"@id"},"label":{"@id":"ex:p","@language":"en"}},"@graph":[{"@id":"ex:s","@type":"ex:C","p":"ex:s","ex:p":"foo"},{"@id":"ex:g","@graph":[{"@id":"ex:s","label":"bar","ex:p":{"@value":"1","@type":"xsd:integer"}}]}]}"#
+        );
+        let mut roundtrip = JsonLdParser::new(
+            serialize(&quads, None)?.as_bytes(),
+            None,
+            Arc::new(HashMap::new()),
+        )
+        .collect::<Result<Vec<_>, _>>()?;
+        roundtrip.sort_by_key(|q| q.to_string());
+        let mut expected = quads;
+        expected.sort_by_key(|q| q.to_string());
+        assert_eq!(roundtrip, expected);
+        Ok(())
+    }
+}
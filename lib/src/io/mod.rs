@@ -1,11 +1,17 @@
 //! Utilities to read and write RDF graphs and datasets
 
+pub(crate) mod compression;
 mod format;
+mod jsonld;
 pub mod read;
+pub(crate) mod sniff;
+pub(crate) mod turtle;
 pub mod write;
 
+pub use self::compression::Compression;
 pub use self::format::DatasetFormat;
 pub use self::format::GraphFormat;
+pub use self::jsonld::JsonLdDocumentLoader;
 pub use self::read::DatasetParser;
 pub use self::read::GraphParser;
 pub use self::write::DatasetSerializer;
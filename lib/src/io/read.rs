@@ -1,15 +1,18 @@
 //! Utilities to read RDF graphs and datasets
 
-use crate::io::{DatasetFormat, GraphFormat};
+use crate::io::compression::DecompressReader;
+use crate::io::jsonld::{JsonLdParser, SimpleDocumentLoader};
+use crate::io::turtle::{TurtleParser, TurtleSyntax};
+use crate::io::{DatasetFormat, GraphFormat, JsonLdDocumentLoader};
 use crate::model::*;
 use oxiri::{Iri, IriParseError};
 use rio_api::model as rio;
-use rio_api::parser::{QuadsParser, TriplesParser};
-use rio_turtle::{NQuadsParser, NTriplesParser, TriGParser, TurtleParser};
+use rio_api::parser::TriplesParser;
 use rio_xml::RdfXmlParser;
 use std::collections::HashMap;
 use std::io;
 use std::io::BufRead;
+use std::sync::Arc;
 
 /// Parsers for RDF graph serialization formats.
 ///
@@ -18,6 +21,10 @@ use std::io::BufRead;
 /// * [Turtle](https://www.w3.org/TR/turtle/) ([`GraphFormat::Turtle`](super::GraphFormat::Turtle))
 /// * [RDF/XML](https://www.w3.org/TR/rdf-syntax-grammar/) ([`GraphFormat::RdfXml`](super::GraphFormat::RdfXml))
 ///
+/// The N-Triples and Turtle parsers also support the [RDF-star](https://w3c.github.io/rdf-star/cg-spec/) quoted triples.
+///
+/// Compressed inputs are detected and decompressed if the matching [`Compression`](super::Compression) is enabled.
+///
 /// ```
 /// use oxigraph::io::{GraphFormat, GraphParser};
 /// use std::io::Cursor;
@@ -34,6 +41,7 @@ use std::io::BufRead;
 pub struct GraphParser {
     format: GraphFormat,
     base_iri: Option<Iri<String>>,
+    error_recovery: bool,
 }
 
 impl GraphParser {
@@ -42,6 +50,7 @@ impl GraphParser {
         Self {
             format,
             base_iri: None,
+            error_recovery: false,
         }
     }
 
@@ -65,22 +74,62 @@ impl GraphParser {
         Ok(self)
     }
 
+    /// Does not stop the parsing at the first syntax error.
+    ///
+    /// The reader returns an error for each invalid statement and resumes the parsing after it.
+    /// The errors messages contain the line and column where the error has been found.
+    /// The N-Triples parser resumes at the next line and the Turtle one after the next `.` followed by a whitespace.
+    /// The RDF/XML parser does not support it and still stops at the first error.
+    ///
+    /// ```
+    /// use oxigraph::io::{GraphFormat, GraphParser};
+    /// use std::io::Cursor;
+    ///
+    /// let file = "<http://example.com/s> <http://example.com/p> <http://example.com/o> .
+    /// <http://example.com/s> <http://example.com/p> <not an iri> .
+    /// <http://example.com/s> <http://example.com/p> <http://example.com/o2> .";
+    ///
+    /// let parser = GraphParser::from_format(GraphFormat::NTriples).with_error_recovery();
+    /// let (triples, errors): (Vec<_>, Vec<_>) = parser.read_triples(Cursor::new(file))?.partition(Result::is_ok);
+    ///
+    /// assert_eq!(triples.len(), 2);
+    /// assert_eq!(errors.len(), 1);
+    /// assert!(errors[0].as_ref().unwrap_err().to_string().contains("on line 2"));
+    /// # std::io::Result::Ok(())
+    /// ```
+    pub fn with_error_recovery(mut self) -> Self {
+        self.error_recovery = true;
+        self
+    }
+
     /// Executes the parsing itself on a [`BufRead`](std::io::BufRead) implementation and returns an iterator of triples
     pub fn read_triples<R: BufRead>(&self, reader: R) -> Result<TripleReader<R>, io::Error> {
+        let reader = DecompressReader::new(reader)?;
         Ok(TripleReader {
-            mapper: RioMapper::default(),
             parser: match self.format {
-                GraphFormat::NTriples => TripleReaderKind::NTriples(NTriplesParser::new(reader)),
-                GraphFormat::Turtle => {
-                    TripleReaderKind::Turtle(TurtleParser::new(reader, self.base_iri.clone()))
+                GraphFormat::NTriples => {
+                    TripleReaderKind::Turtle(self.turtle_parser(reader, TurtleSyntax::NTriples))
                 }
-                GraphFormat::RdfXml => {
-                    TripleReaderKind::RdfXml(RdfXmlParser::new(reader, self.base_iri.clone()))
+                GraphFormat::Turtle => {
+                    TripleReaderKind::Turtle(self.turtle_parser(reader, TurtleSyntax::Turtle))
                 }
+                GraphFormat::RdfXml => TripleReaderKind::RdfXml {
+                    parser: RdfXmlParser::new(reader, self.base_iri.clone()),
+                    mapper: RioMapper::default(),
+                    buffer: Vec::new(),
+                },
             },
-            buffer: Vec::new(),
         })
     }
+
+    fn turtle_parser<R: BufRead>(&self, reader: R, syntax: TurtleSyntax) -> TurtleParser<R> {
+        let parser = TurtleParser::new(reader, syntax, self.base_iri.clone());
+        if self.error_recovery {
+            parser.with_error_recovery()
+        } else {
+            parser
+        }
+    }
 }
 
 /// An iterator yielding read triples.
@@ -101,61 +150,106 @@ impl GraphParser {
 /// ```
 #[must_use]
 pub struct TripleReader<R: BufRead> {
-    mapper: RioMapper,
     parser: TripleReaderKind<R>,
-    buffer: Vec<Triple>,
 }
 
 enum TripleReaderKind<R: BufRead> {
-    NTriples(NTriplesParser<R>),
-    Turtle(TurtleParser<R>),
-    RdfXml(RdfXmlParser<R>),
+    Turtle(TurtleParser<DecompressReader<R>>),
+    RdfXml {
+        parser: RdfXmlParser<DecompressReader<R>>,
+        mapper: RioMapper,
+        buffer: Vec<Triple>,
+    },
+}
+
+impl<R: BufRead> TripleReader<R> {
+    /// The prefixes declared in the part of the file read so far, ordered by prefix name.
+    ///
+    /// Only the N-Triples and Turtle parsers report prefixes, the RDF/XML one always returns an empty iterator.
+    ///
+    /// ```
+    /// use oxigraph::io::{GraphFormat, GraphParser};
+    /// use std::io::Cursor;
+    ///
+    /// let file = "@prefix schema: <http://schema.org/> .
+    /// @prefix ex: <http://example.com/> .
+    /// ex:foo a schema:Person .";
+    ///
+    /// let mut reader = GraphParser::from_format(GraphFormat::Turtle).read_triples(Cursor::new(file))?;
+    /// assert_eq!(reader.prefixes().count(), 0);
+    /// reader.next().unwrap()?;
+    /// assert_eq!(
+    ///     reader.prefixes().collect::<Vec<_>>(),
+    ///     [("ex", "http://example.com/"), ("schema", "http://schema.org/")]
+    /// );
+    /// # std::io::Result::Ok(())
+    /// ```
+    pub fn prefixes(&self) -> impl Iterator<Item = (&str, &str)> {
+        match &self.parser {
+            TripleReaderKind::Turtle(parser) => Some(parser.prefixes()),
+            TripleReaderKind::RdfXml { .. } => None,
+        }
+        .into_iter()
+        .flatten()
+        .map(|(name, iri)| (name.as_str(), iri.as_str()))
+    }
+
+    /// The base IRI in use at the current position of the file.
+    ///
+    /// It is the one set with [`GraphParser::with_base_iri`] or the last one declared in the file with `@base` or `BASE`.
+    /// Only the N-Triples and Turtle parsers report it, the RDF/XML one always returns `None`.
+    ///
+    /// ```
+    /// use oxigraph::io::{GraphFormat, GraphParser};
+    /// use std::io::Cursor;
+    ///
+    /// let file = "@base <http://example.com/> . <s> <p> <o> .";
+    ///
+    /// let mut reader = GraphParser::from_format(GraphFormat::Turtle).read_triples(Cursor::new(file))?;
+    /// reader.next().unwrap()?;
+    /// assert_eq!(reader.base_iri(), Some("http://example.com/"));
+    /// # std::io::Result::Ok(())
+    /// ```
+    pub fn base_iri(&self) -> Option<&str> {
+        match &self.parser {
+            TripleReaderKind::Turtle(parser) => parser.base_iri(),
+            TripleReaderKind::RdfXml { .. } => None,
+        }
+    }
+
+    /// If the reader resumes the parsing after the syntax errors
+    pub(crate) fn recovers_from_errors(&self) -> bool {
+        match &self.parser {
+            TripleReaderKind::Turtle(parser) => parser.recovers_from_errors(),
+            TripleReaderKind::RdfXml { .. } => false,
+        }
+    }
 }
 
 impl<R: BufRead> Iterator for TripleReader<R> {
     type Item = Result<Triple, io::Error>;
 
     fn next(&mut self) -> Option<Result<Triple, io::Error>> {
-        loop {
-            if let Some(r) = self.buffer.pop() {
-                return Some(Ok(r));
-            }
-
-            if let Err(error) = match &mut self.parser {
-                TripleReaderKind::NTriples(parser) => {
-                    Self::read(parser, &mut self.buffer, &mut self.mapper)
+        match &mut self.parser {
+            TripleReaderKind::Turtle(parser) => Some(parser.next()?.map(Triple::from)),
+            TripleReaderKind::RdfXml {
+                parser,
+                mapper,
+                buffer,
+            } => loop {
+                if let Some(r) = buffer.pop() {
+                    return Some(Ok(r));
                 }
-                TripleReaderKind::Turtle(parser) => {
-                    Self::read(parser, &mut self.buffer, &mut self.mapper)
+                if parser.is_end() {
+                    return None;
                 }
-                TripleReaderKind::RdfXml(parser) => {
-                    Self::read(parser, &mut self.buffer, &mut self.mapper)
+                if let Err(error) = parser.parse_step(&mut |t| -> Result<(), io::Error> {
+                    buffer.push(mapper.triple(&t));
+                    Ok(())
+                }) {
+                    return Some(Err(error));
                 }
-            }? {
-                return Some(Err(error));
-            }
-        }
-    }
-}
-
-impl<R: BufRead> TripleReader<R> {
-    fn read<P: TriplesParser>(
-        parser: &mut P,
-        buffer: &mut Vec<Triple>,
-        mapper: &mut RioMapper,
-    ) -> Option<Result<(), io::Error>>
-    where
-        io::Error: From<P::Error>,
-    {
-        if parser.is_end() {
-            None
-        } else if let Err(e) = parser.parse_step(&mut |t| {
-            buffer.push(mapper.triple(&t));
-            Ok(())
-        }) {
-            Some(Err(e))
-        } else {
-            Some(Ok(()))
+            },
         }
     }
 }
@@ -165,6 +259,14 @@ impl<R: BufRead> TripleReader<R> {
 /// It currently supports the following formats:
 /// * [N-Quads](https://www.w3.org/TR/n-quads/) ([`DatasetFormat::NQuads`](super::DatasetFormat::NQuads))
 /// * [TriG](https://www.w3.org/TR/trig/) ([`DatasetFormat::TriG`](super::DatasetFormat::TriG))
+/// * [JSON-LD](https://www.w3.org/TR/json-ld11/) ([`DatasetFormat::JsonLd`](super::DatasetFormat::JsonLd))
+///
+/// The N-Quads and TriG parsers also support the [RDF-star](https://w3c.github.io/rdf-star/cg-spec/) quoted triples.
+///
+/// Compressed inputs are detected and decompressed if the matching [`Compression`](super::Compression) is enabled.
+///
+/// The JSON-LD parser loads the full document in memory before returning the first quad.
+/// The remote contexts are fetched using the [`JsonLdDocumentLoader`] provided with [`with_document_loader`](DatasetParser::with_document_loader()).
 ///
 /// ```
 /// use oxigraph::io::{DatasetFormat, DatasetParser};
@@ -182,6 +284,8 @@ impl<R: BufRead> TripleReader<R> {
 pub struct DatasetParser {
     format: DatasetFormat,
     base_iri: Option<Iri<String>>,
+    document_loader: Arc<dyn JsonLdDocumentLoader>,
+    error_recovery: bool,
 }
 
 impl DatasetParser {
@@ -190,6 +294,8 @@ impl DatasetParser {
         Self {
             format,
             base_iri: None,
+            document_loader: Arc::new(SimpleDocumentLoader::new()),
+            error_recovery: false,
         }
     }
 
@@ -213,19 +319,59 @@ impl DatasetParser {
         Ok(self)
     }
 
+    /// Provides the loader used to fetch the remote JSON-LD contexts
+    ///
+    /// By default they are fetched using HTTP if the `http_client` feature is enabled.
+    /// See [`JsonLdDocumentLoader`] for an example.
+    pub fn with_document_loader(
+        mut self,
+        document_loader: impl JsonLdDocumentLoader + 'static,
+    ) -> Self {
+        self.document_loader = Arc::new(document_loader);
+        self
+    }
+
+    /// Does not stop the parsing at the first syntax error.
+    ///
+    /// The reader returns an error for each invalid statement and resumes the parsing after it.
+    /// The N-Quads parser resumes at the next line and the TriG one after the next `.` followed by a whitespace
+    /// or the `}` closing the current graph.
+    /// The JSON-LD parser does not support it and still stops at the first error.
+    ///
+    /// See [`GraphParser::with_error_recovery`] for an example.
+    pub fn with_error_recovery(mut self) -> Self {
+        self.error_recovery = true;
+        self
+    }
+
     /// Executes the parsing itself on a [`BufRead`](std::io::BufRead) implementation and returns an iterator of quads
     pub fn read_quads<R: BufRead>(&self, reader: R) -> Result<QuadReader<R>, io::Error> {
+        let reader = DecompressReader::new(reader)?;
         Ok(QuadReader {
-            mapper: RioMapper::default(),
             parser: match self.format {
-                DatasetFormat::NQuads => QuadReaderKind::NQuads(NQuadsParser::new(reader)),
+                DatasetFormat::NQuads => QuadReaderKind::Turtle(Box::new(
+                    self.turtle_parser(reader, TurtleSyntax::NQuads),
+                )),
                 DatasetFormat::TriG => {
-                    QuadReaderKind::TriG(TriGParser::new(reader, self.base_iri.clone()))
+                    QuadReaderKind::Turtle(Box::new(self.turtle_parser(reader, TurtleSyntax::TriG)))
                 }
+                DatasetFormat::JsonLd => QuadReaderKind::JsonLd(Box::new(JsonLdParser::new(
+                    reader,
+                    self.base_iri.clone(),
+                    self.document_loader.clone(),
+                ))),
             },
-            buffer: Vec::new(),
         })
     }
+
+    fn turtle_parser<R: BufRead>(&self, reader: R, syntax: TurtleSyntax) -> TurtleParser<R> {
+        let parser = TurtleParser::new(reader, syntax, self.base_iri.clone());
+        if self.error_recovery {
+            parser.with_error_recovery()
+        } else {
+            parser
+        }
+    }
 }
 
 /// An iterator yielding read quads.
@@ -246,57 +392,67 @@ impl DatasetParser {
 /// ```
 #[must_use]
 pub struct QuadReader<R: BufRead> {
-    mapper: RioMapper,
     parser: QuadReaderKind<R>,
-    buffer: Vec<Quad>,
 }
 
 enum QuadReaderKind<R: BufRead> {
-    NQuads(NQuadsParser<R>),
-    TriG(TriGParser<R>),
+    Turtle(Box<TurtleParser<DecompressReader<R>>>),
+    JsonLd(Box<JsonLdParser<DecompressReader<R>>>),
 }
 
-impl<R: BufRead> Iterator for QuadReader<R> {
-    type Item = Result<Quad, io::Error>;
+impl<R: BufRead> QuadReader<R> {
+    /// The prefixes declared in the part of the file read so far, ordered by prefix name.
+    ///
+    /// Only the N-Quads and TriG parsers report prefixes, the JSON-LD one always returns an empty iterator.
+    ///
+    /// ```
+    /// use oxigraph::io::{DatasetFormat, DatasetParser};
+    /// use std::io::Cursor;
+    ///
+    /// let file = "@prefix ex: <http://example.com/> . ex:g { ex:s ex:p ex:o . }";
+    ///
+    /// let mut reader = DatasetParser::from_format(DatasetFormat::TriG).read_quads(Cursor::new(file))?;
+    /// reader.next().unwrap()?;
+    /// assert_eq!(reader.prefixes().collect::<Vec<_>>(), [("ex", "http://example.com/")]);
+    /// # std::io::Result::Ok(())
+    /// ```
+    pub fn prefixes(&self) -> impl Iterator<Item = (&str, &str)> {
+        match &self.parser {
+            QuadReaderKind::Turtle(parser) => Some(parser.prefixes()),
+            QuadReaderKind::JsonLd(_) => None,
+        }
+        .into_iter()
+        .flatten()
+        .map(|(name, iri)| (name.as_str(), iri.as_str()))
+    }
 
-    fn next(&mut self) -> Option<Result<Quad, io::Error>> {
-        loop {
-            if let Some(r) = self.buffer.pop() {
-                return Some(Ok(r));
-            }
+    /// The base IRI in use at the current position of the file.
+    ///
+    /// It is the one set with [`DatasetParser::with_base_iri`] or the last one declared in the file with `@base` or `BASE`.
+    /// Only the N-Quads and TriG parsers report it, the JSON-LD one always returns `None`.
+    pub fn base_iri(&self) -> Option<&str> {
+        match &self.parser {
+            QuadReaderKind::Turtle(parser) => parser.base_iri(),
+            QuadReaderKind::JsonLd(_) => None,
+        }
+    }
 
-            if let Err(error) = match &mut self.parser {
-                QuadReaderKind::NQuads(parser) => {
-                    Self::read(parser, &mut self.buffer, &mut self.mapper)
-                }
-                QuadReaderKind::TriG(parser) => {
-                    Self::read(parser, &mut self.buffer, &mut self.mapper)
-                }
-            }? {
-                return Some(Err(error));
-            }
+    /// If the reader resumes the parsing after the syntax errors
+    pub(crate) fn recovers_from_errors(&self) -> bool {
+        match &self.parser {
+            QuadReaderKind::Turtle(parser) => parser.recovers_from_errors(),
+            QuadReaderKind::JsonLd(_) => false,
         }
     }
 }
 
-impl<R: BufRead> QuadReader<R> {
-    fn read<P: QuadsParser>(
-        parser: &mut P,
-        buffer: &mut Vec<Quad>,
-        mapper: &mut RioMapper,
-    ) -> Option<Result<(), io::Error>>
-    where
-        io::Error: From<P::Error>,
-    {
-        if parser.is_end() {
-            None
-        } else if let Err(e) = parser.parse_step(&mut |t| {
-            buffer.push(mapper.quad(&t));
-            Ok(())
-        }) {
-            Some(Err(e))
-        } else {
-            Some(Ok(()))
+impl<R: BufRead> Iterator for QuadReader<R> {
+    type Item = Result<Quad, io::Error>;
+
+    fn next(&mut self) -> Option<Result<Quad, io::Error>> {
+        match &mut self.parser {
+            QuadReaderKind::Turtle(parser) => parser.next(),
+            QuadReaderKind::JsonLd(parser) => parser.next(),
         }
     }
 }
@@ -330,7 +486,7 @@ impl<'a> RioMapper {
         }
     }
 
-    fn named_or_blank_node(&mut self, node: rio::NamedOrBlankNode<'a>) -> NamedOrBlankNode {
+    fn named_or_blank_node(&mut self, node: rio::NamedOrBlankNode<'a>) -> Subject {
         match node {
             rio::NamedOrBlankNode::NamedNode(node) => self.named_node(node).into(),
             rio::NamedOrBlankNode::BlankNode(node) => self.blank_node(node).into(),
@@ -352,21 +508,4 @@ impl<'a> RioMapper {
             object: self.term(triple.object),
         }
     }
-
-    fn graph_name(&mut self, graph_name: Option<rio::NamedOrBlankNode<'a>>) -> GraphName {
-        match graph_name {
-            Some(rio::NamedOrBlankNode::NamedNode(node)) => self.named_node(node).into(),
-            Some(rio::NamedOrBlankNode::BlankNode(node)) => self.blank_node(node).into(),
-            None => GraphName::DefaultGraph,
-        }
-    }
-
-    fn quad(&mut self, quad: &rio::Quad<'a>) -> Quad {
-        Quad {
-            subject: self.named_or_blank_node(quad.subject),
-            predicate: self.named_node(quad.predicate),
-            object: self.term(quad.object),
-            graph_name: self.graph_name(quad.graph_name),
-        }
-    }
 }
@@ -0,0 +1,322 @@
+//! Guessing of the serialization format of an RDF file from its first bytes
+
+use crate::io::{DatasetFormat, GraphFormat};
+use std::str;
+
+/// A graph or dataset serialization format
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub(crate) enum RdfFormat {
+    Graph(GraphFormat),
+    Dataset(DatasetFormat),
+}
+
+/// Guesses the format of a file from its first bytes.
+///
+/// The data might be truncated anywhere, only the complete statements are considered
+/// to choose between N-Triples and N-Quads.
+pub(crate) fn sniff(data: &[u8]) -> Option<RdfFormat> {
+    let data = if data.starts_with(b"\xEF\xBB\xBF") {
+        &data[3..]
+    } else {
+        data
+    };
+    let text = match str::from_utf8(data) {
+        Ok(text) => text,
+        // The data is cut in the middle of a character
+        Err(e) if e.error_len().is_none() => str::from_utf8(&data[..e.valid_up_to()]).ok()?,
+        Err(_) => return None,
+    };
+    let text = text.trim_start();
+    if looks_like_xml(text) {
+        Some(RdfFormat::Graph(GraphFormat::RdfXml))
+    } else if looks_like_json(text) {
+        Some(RdfFormat::Dataset(DatasetFormat::JsonLd))
+    } else {
+        TurtleFamilySniffer::default().sniff(text)
+    }
+}
+
+fn looks_like_xml(text: &str) -> bool {
+    let tag = if let Some(tag) = text.strip_prefix('<') {
+        tag
+    } else {
+        return false;
+    };
+    if tag.starts_with('?') || tag.starts_with('!') {
+        return true;
+    }
+    if !tag.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        return false;
+    }
+    // IRIs can not contain whitespaces but tags with attributes do
+    match tag.find('>') {
+        Some(end) => tag[..end].contains(char::is_whitespace),
+        None => tag.contains(char::is_whitespace),
+    }
+}
+
+fn looks_like_json(text: &str) -> bool {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some('{') => matches!(
+            chars.find(|c| !c.is_whitespace()),
+            Some('"') | Some('}') | None
+        ),
+        Some('[') => matches!(
+            chars.find(|c| !c.is_whitespace()),
+            Some('{') | Some('[') | Some('"') | None
+        ),
+        _ => false,
+    }
+}
+
+/// Guesses between N-Triples, N-Quads, Turtle and TriG using the syntax elements found
+#[derive(Default)]
+struct TurtleFamilySniffer {
+    /// Number of terms in the current statement
+    terms: usize,
+    /// Depth of the current RDF-star quoted triple
+    quoted_triple_depth: usize,
+    has_statement_end: bool,
+    has_quad: bool,
+    has_turtle_syntax: bool,
+    has_graph_block: bool,
+}
+
+impl TurtleFamilySniffer {
+    fn sniff(mut self, text: &str) -> Option<RdfFormat> {
+        self.read(text);
+        if !self.has_statement_end {
+            None
+        } else if self.has_graph_block {
+            Some(RdfFormat::Dataset(DatasetFormat::TriG))
+        } else if self.has_turtle_syntax {
+            Some(RdfFormat::Graph(GraphFormat::Turtle))
+        } else if self.has_quad {
+            Some(RdfFormat::Dataset(DatasetFormat::NQuads))
+        } else {
+            Some(RdfFormat::Graph(GraphFormat::NTriples))
+        }
+    }
+
+    /// Reads the text until its end or the first token cut by the end of the data
+    fn read(&mut self, mut text: &str) {
+        while let Some(c) = text.chars().next() {
+            text = match c {
+                c if c.is_whitespace() => &text[c.len_utf8()..],
+                '#' => match text.find('\n') {
+                    Some(end) => &text[end..],
+                    None => return,
+                },
+                '<' if text.starts_with("<<") => {
+                    self.quoted_triple_depth += 1;
+                    &text[2..]
+                }
+                '>' if text.starts_with(">>") && self.quoted_triple_depth > 0 => {
+                    self.quoted_triple_depth -= 1;
+                    self.add_term();
+                    &text[2..]
+                }
+                '<' => match text.find('>') {
+                    Some(end) => {
+                        self.add_term();
+                        &text[end + 1..]
+                    }
+                    None => return,
+                },
+                '"' | '\'' => {
+                    if c == '\'' || text.starts_with("\"\"\"") {
+                        self.has_turtle_syntax = true;
+                    }
+                    match skip_literal(text) {
+                        Some(remaining) => {
+                            self.add_term();
+                            remaining
+                        }
+                        None => return,
+                    }
+                }
+                '_' if text.starts_with("_:") => {
+                    self.add_term();
+                    &text[word_end(text)..]
+                }
+                '.' => {
+                    self.end_statement();
+                    &text[1..]
+                }
+                '{' => {
+                    self.has_graph_block = true;
+                    &text[1..]
+                }
+                '}' => {
+                    self.has_graph_block = true;
+                    self.has_statement_end = true;
+                    &text[1..]
+                }
+                ';' | ',' | '[' | ']' | '(' | ')' | '@' => {
+                    self.has_turtle_syntax = true;
+                    &text[1..]
+                }
+                c if c.is_alphanumeric() || matches!(c, ':' | '_' | '+' | '-') => {
+                    // Prefixed names, keywords, numbers and booleans are only allowed in Turtle and TriG
+                    let end = word_end(text);
+                    if text[..end].eq_ignore_ascii_case("GRAPH") {
+                        self.has_graph_block = true;
+                    }
+                    self.has_turtle_syntax = true;
+                    self.add_term();
+                    &text[end..]
+                }
+                // It does not look like a Turtle family format
+                _ => return,
+            }
+        }
+    }
+
+    fn add_term(&mut self) {
+        if self.quoted_triple_depth == 0 {
+            self.terms += 1;
+        }
+    }
+
+    fn end_statement(&mut self) {
+        self.has_statement_end = true;
+        match self.terms {
+            3 => (),
+            4 => self.has_quad = true,
+            _ => self.has_turtle_syntax = true,
+        }
+        self.terms = 0;
+    }
+}
+
+/// Returns the text after the literal starting the text including its language tag or datatype
+fn skip_literal(text: &str) -> Option<&str> {
+    let quote = if text.starts_with("\"\"\"") || text.starts_with("'''") {
+        &text[..3]
+    } else {
+        &text[..1]
+    };
+    let mut remaining = &text[quote.len()..];
+    loop {
+        let position = remaining.find(|c| c == '\\' || quote.starts_with(c))?;
+        if remaining[position..].starts_with('\\') {
+            // We skip the escaped character
+            let escaped = remaining[position + 1..].chars().next()?;
+            remaining = &remaining[position + 1 + escaped.len_utf8()..];
+        } else if remaining[position..].starts_with(quote) {
+            remaining = &remaining[position + quote.len()..];
+            break;
+        } else {
+            remaining = &remaining[position + 1..];
+        }
+    }
+    if let Some(language) = remaining.strip_prefix('@') {
+        Some(&language[word_end(language)..])
+    } else if let Some(datatype) = remaining.strip_prefix("^^") {
+        if datatype.starts_with('<') {
+            datatype.find('>').map(|end| &datatype[end + 1..])
+        } else {
+            Some(&datatype[word_end(datatype)..])
+        }
+    } else {
+        Some(remaining)
+    }
+}
+
+/// Returns the length of the prefixed name, blank node label, keyword or number starting the text
+fn word_end(text: &str) -> usize {
+    let mut end = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((position, c)) = chars.next() {
+        let is_part_of_word = if c == '.' {
+            // A dot is the end of the statement if it is not followed by other characters of the word
+            matches!(chars.peek(), Some((_, next)) if !is_delimiter(*next))
+        } else {
+            !is_delimiter(c)
+        };
+        if !is_part_of_word {
+            break;
+        }
+        end = position + c.len_utf8();
+    }
+    end
+}
+
+fn is_delimiter(c: char) -> bool {
+    c.is_whitespace() || "<\"'{}()[];,#".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        for (data, format) in &[
+            (
+                "<http://example.com/s> <http://example.com/p> \"o\\\"\"@en .\n",
+                RdfFormat::Graph(GraphFormat::NTriples),
+            ),
+            (
+                "# comment\n<http://example.com/s> <http://example.com/p> _:o .\n<http://example.com/s> <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> <http://example.com/g> .\n",
+                RdfFormat::Dataset(DatasetFormat::NQuads),
+            ),
+            (
+                "<< <http://example.com/s> <http://example.com/p> <http://example.com/o> >> <http://example.com/p> <http://example.com/o> .\n<http://exa",
+                RdfFormat::Graph(GraphFormat::NTriples),
+            ),
+            (
+                "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o .",
+                RdfFormat::Graph(GraphFormat::Turtle),
+            ),
+            (
+                "<http://example.com/s> a <http://example.com/C> .",
+                RdfFormat::Graph(GraphFormat::Turtle),
+            ),
+            (
+                "<http://example.com/s> <http://example.com/p> \"\"\"a\nb\"\"\" .",
+                RdfFormat::Graph(GraphFormat::Turtle),
+            ),
+            (
+                "PREFIX ex: <http://example.com/>\nex:g { ex:s ex:p ex:o }",
+                RdfFormat::Dataset(DatasetFormat::TriG),
+            ),
+            (
+                "<http://example.com/s> <http://example.com/p> \"{\" .\nGRAPH <http://example.com/g> {",
+                RdfFormat::Dataset(DatasetFormat::TriG),
+            ),
+            (
+                "\u{feff}<?xml version=\"1.0\"?>\n<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"/>",
+                RdfFormat::Graph(GraphFormat::RdfXml),
+            ),
+            (
+                "<rdf:RDF\n  xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+                RdfFormat::Graph(GraphFormat::RdfXml),
+            ),
+            (
+                "{\"@context\": {}, \"@id\": \"http://example.com/s\"}",
+                RdfFormat::Dataset(DatasetFormat::JsonLd),
+            ),
+            (
+                " [ {\"@id\": \"http://example.com/s\"} ]",
+                RdfFormat::Dataset(DatasetFormat::JsonLd),
+            ),
+        ] {
+            assert_eq!(sniff(data.as_bytes()), Some(*format), "{}", data);
+        }
+    }
+
+    #[test]
+    fn test_sniff_unknown() {
+        assert_eq!(sniff(b""), None);
+        assert_eq!(sniff(b"# only a comment\n"), None);
+        assert_eq!(sniff(b"\x1F\x8B\x08\x00\xFF"), None);
+        assert_eq!(sniff(b"\x00\x01 ."), None);
+        // The first statement is not complete
+        assert_eq!(
+            sniff(b"<http://example.com/s> <http://example.com/p>"),
+            None
+        );
+    }
+}
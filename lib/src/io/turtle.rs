@@ -0,0 +1,1592 @@
+//! A streaming parser for [N-Triples](https://www.w3.org/TR/n-triples/), [N-Quads](https://www.w3.org/TR/n-quads/),
+//! [Turtle](https://www.w3.org/TR/turtle/) and [TriG](https://www.w3.org/TR/trig/)
+//! with support of their [RDF-star](https://w3c.github.io/rdf-star/cg-spec/) extensions.
+
+use crate::error::invalid_data_error;
+use crate::model::vocab::{rdf, xsd};
+use crate::model::*;
+use oxiri::Iri;
+use siphasher::sip128::{Hasher128, SipHasher24};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::Hasher;
+use std::io;
+use std::io::{BufRead, ErrorKind};
+use std::str;
+
+const MAX_NUMBER_OF_NESTED_TRIPLES: usize = 128;
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+pub(crate) enum TurtleSyntax {
+    NTriples,
+    NQuads,
+    Turtle,
+    TriG,
+}
+
+/// Parses one of the Turtle family syntaxes and returns the read quads.
+///
+/// Triples formats (N-Triples and Turtle) only output quads in the default graph.
+pub(crate) struct TurtleParser<R: BufRead> {
+    read: LookAheadCharReader<R>,
+    syntax: TurtleSyntax,
+    base_iri: Option<Iri<String>>,
+    prefixes: BTreeMap<String, String>,
+    bnodes: HashMap<String, BlankNode>,
+    /// If set, the blank node ids are derived from their labels using this seed
+    bnode_seed: Option<u128>,
+    buffer: VecDeque<Quad>,
+    /// The name of the TriG wrapped graph the parser is in
+    graph_block: Option<GraphName>,
+    /// If set, the parser skips the invalid statements instead of stopping at the first error
+    error_recovery: bool,
+    /// The line where the statement being parsed starts
+    statement_line: u64,
+    is_ended: bool,
+}
+
+impl<R: BufRead> TurtleParser<R> {
+    pub fn new(reader: R, syntax: TurtleSyntax, base_iri: Option<Iri<String>>) -> Self {
+        Self {
+            read: LookAheadCharReader::new(reader),
+            syntax,
+            base_iri,
+            prefixes: BTreeMap::default(),
+            bnodes: HashMap::default(),
+            bnode_seed: None,
+            buffer: VecDeque::default(),
+            graph_block: None,
+            error_recovery: false,
+            statement_line: 1,
+            is_ended: false,
+        }
+    }
+
+    /// Derives the blank node ids from their labels and `seed`.
+    ///
+    /// Parsers sharing the same seed map the same label to the same blank node.
+    /// It allows to parse the different parts of a N-Triples or N-Quads file independently.
+    #[cfg(any(feature = "rocksdb", test))]
+    pub fn with_blank_node_seed(mut self, seed: u128) -> Self {
+        self.bnode_seed = Some(seed);
+        self
+    }
+
+    /// Skips the invalid statements after returning their errors instead of stopping at the first error.
+    ///
+    /// The N-Triples and N-Quads parsers resume at the next line.
+    /// The Turtle and TriG parsers resume after the next `.` followed by a whitespace or the `}` closing the current TriG graph.
+    pub fn with_error_recovery(mut self) -> Self {
+        self.error_recovery = true;
+        self
+    }
+
+    /// Sets the number of the first line of the input, used in the error messages.
+    ///
+    /// It allows to parse a part of a bigger file.
+    #[cfg(feature = "rocksdb")]
+    pub fn with_first_line_number(mut self, line_number: u64) -> Self {
+        self.read.line_number = line_number;
+        self.statement_line = line_number;
+        self
+    }
+
+    /// If the invalid statements are skipped
+    pub fn recovers_from_errors(&self) -> bool {
+        self.error_recovery
+    }
+
+    /// The prefixes declared in the statements parsed so far
+    pub fn prefixes(&self) -> &BTreeMap<String, String> {
+        &self.prefixes
+    }
+
+    /// The base IRI currently in use
+    pub fn base_iri(&self) -> Option<&str> {
+        self.base_iri.as_ref().map(Iri::as_str)
+    }
+
+    /// Parses the next statement and adds the read quads to the buffer.
+    ///
+    /// Returns `false` if the end of the file has been reached.
+    fn parse_step(&mut self) -> Result<bool, io::Error> {
+        self.skip_whitespace()?;
+        self.statement_line = self.read.line_number;
+        if self.read.current()?.is_none() {
+            return if self.graph_block.take().is_some() {
+                Err(self.read.error("Unexpected end of file"))
+            } else {
+                Ok(false)
+            };
+        }
+        match self.syntax {
+            TurtleSyntax::NTriples | TurtleSyntax::NQuads => self.parse_line()?,
+            TurtleSyntax::Turtle => self.parse_turtle_statement()?,
+            TurtleSyntax::TriG => {
+                if let Some(graph_name) = self.graph_block.take() {
+                    let result = self.parse_wrapped_graph_triples(&graph_name);
+                    if !matches!(result, Ok(true)) {
+                        self.graph_block = Some(graph_name);
+                    }
+                    result?;
+                } else {
+                    self.parse_trig_block()?
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Skips the rest of the statement that failed to parse
+    fn recover(&mut self) -> Result<(), io::Error> {
+        match self.syntax {
+            TurtleSyntax::NTriples | TurtleSyntax::NQuads => {
+                // A statement is a line. If the parser has already read the next line, it is the next statement.
+                if self.read.line_number > self.statement_line {
+                    return Ok(());
+                }
+                while let Some(c) = self.next_recovery_char()? {
+                    if c == '\n' {
+                        return Ok(());
+                    }
+                }
+            }
+            TurtleSyntax::Turtle | TurtleSyntax::TriG => {
+                while let Some(c) = self.next_recovery_char()? {
+                    match c {
+                        '.' => {
+                            if matches!(
+                                self.read.current(),
+                                Ok(None)
+                                    | Ok(Some(' '))
+                                    | Ok(Some('\t'))
+                                    | Ok(Some('\n'))
+                                    | Ok(Some('\r'))
+                            ) {
+                                return Ok(());
+                            }
+                        }
+                        '}' if self.graph_block.is_some() => {
+                            self.graph_block = None;
+                            return Ok(());
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Consumes the next char while skipping an invalid statement, ignoring the invalid UTF-8 sequences
+    fn next_recovery_char(&mut self) -> Result<Option<char>, io::Error> {
+        loop {
+            match self.read.current() {
+                Ok(Some(c)) => {
+                    self.read.consume()?;
+                    return Ok(Some(c));
+                }
+                Ok(None) => return Ok(None),
+                // The invalid sequence has been dropped by the reader
+                Err(e) if e.kind() == ErrorKind::InvalidData => (),
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn parse_line(&mut self) -> Result<(), io::Error> {
+        // [2] triple ::= subject predicate object '.'
+        // [2] statement ::= subject predicate object graphLabel? '.'
+        let subject = match self.parse_line_term(0)? {
+            Term::NamedNode(node) => Subject::NamedNode(node),
+            Term::BlankNode(node) => Subject::BlankNode(node),
+            Term::Triple(triple) => Subject::Triple(triple),
+            Term::Literal(_) => {
+                return Err(self
+                    .read
+                    .error("Literals are not allowed in the subject position"))
+            }
+        };
+        self.skip_whitespace()?;
+        let predicate = self.parse_iriref()?;
+        self.skip_whitespace()?;
+        let object = self.parse_line_term(0)?;
+        self.skip_whitespace()?;
+        let graph_name = if self.syntax == TurtleSyntax::NQuads {
+            match self.read.current()? {
+                Some('<') => {
+                    let graph_name = self.parse_iriref()?.into();
+                    self.skip_whitespace()?;
+                    graph_name
+                }
+                Some('_') => {
+                    let graph_name = self.parse_blank_node_label()?.into();
+                    self.skip_whitespace()?;
+                    graph_name
+                }
+                _ => GraphName::DefaultGraph,
+            }
+        } else {
+            GraphName::DefaultGraph
+        };
+        self.expect('.')?;
+        self.buffer.push_back(Quad {
+            subject,
+            predicate,
+            object,
+            graph_name,
+        });
+        Ok(())
+    }
+
+    fn parse_line_term(&mut self, number_of_recursive_calls: usize) -> Result<Term, io::Error> {
+        match self.read.current()? {
+            Some('<') => {
+                if self.read.ahead(1)? == Some('<') {
+                    self.read.consume_many(2)?;
+                    let triple = self.parse_line_quoted_triple(number_of_recursive_calls + 1)?;
+                    Ok(triple.into())
+                } else {
+                    Ok(self.parse_iriref()?.into())
+                }
+            }
+            Some('_') => Ok(self.parse_blank_node_label()?.into()),
+            Some('"') => {
+                let value = self.parse_string()?;
+                Ok(match self.read.current()? {
+                    Some('@') => self.parse_lang_tag(value)?,
+                    Some('^') => {
+                        self.expect('^')?;
+                        self.expect('^')?;
+                        Literal::new_typed_literal(value, self.parse_iriref()?)
+                    }
+                    _ => Literal::new_simple_literal(value),
+                }
+                .into())
+            }
+            _ => Err(self.read.unexpected_char_error()),
+        }
+    }
+
+    fn parse_line_quoted_triple(
+        &mut self,
+        number_of_recursive_calls: usize,
+    ) -> Result<Triple, io::Error> {
+        // [6t] quotedTriple ::= '<<' subject predicate object '>>'
+        if number_of_recursive_calls == MAX_NUMBER_OF_NESTED_TRIPLES {
+            return Err(self.read.error("Too many nested triples"));
+        }
+        self.skip_whitespace()?;
+        let subject = match self.parse_line_term(number_of_recursive_calls)? {
+            Term::NamedNode(node) => Subject::NamedNode(node),
+            Term::BlankNode(node) => Subject::BlankNode(node),
+            Term::Triple(triple) => Subject::Triple(triple),
+            Term::Literal(_) => {
+                return Err(self
+                    .read
+                    .error("Literals are not allowed in the subject position"))
+            }
+        };
+        self.skip_whitespace()?;
+        let predicate = self.parse_iriref()?;
+        self.skip_whitespace()?;
+        let object = self.parse_line_term(number_of_recursive_calls)?;
+        self.skip_whitespace()?;
+        self.expect('>')?;
+        self.expect('>')?;
+        Ok(Triple {
+            subject,
+            predicate,
+            object,
+        })
+    }
+
+    fn parse_turtle_statement(&mut self) -> Result<(), io::Error> {
+        // [2] statement ::= directive | triples '.'
+        if !self.parse_directive()? {
+            self.parse_triples(&GraphName::DefaultGraph)?;
+            self.skip_whitespace()?;
+            self.expect('.')?;
+        }
+        Ok(())
+    }
+
+    fn parse_trig_block(&mut self) -> Result<(), io::Error> {
+        // [1g] trigDoc ::= (directive | block)*
+        // [2g] block ::= triplesOrGraph | wrappedGraph | triples2 | "GRAPH" labelOrSubject wrappedGraph
+        if self.parse_directive()? {
+            return Ok(());
+        }
+        if self.read.starts_with_keyword("GRAPH")? {
+            self.read.consume_many("GRAPH".len())?;
+            self.skip_whitespace()?;
+            let graph_name = self.parse_label_or_subject()?;
+            self.skip_whitespace()?;
+            return self.parse_wrapped_graph(graph_name.into());
+        }
+        match self.read.current()? {
+            Some('{') => self.parse_wrapped_graph(GraphName::DefaultGraph),
+            Some('[') if !self.is_anon()? => {
+                // [4g] triples2 ::= blankNodePropertyList predicateObjectList? '.' | collection predicateObjectList '.'
+                let subject = self.parse_blank_node_property_list(&GraphName::DefaultGraph)?;
+                self.skip_whitespace()?;
+                if self.read.current()? != Some('.') {
+                    self.parse_predicate_object_list(&subject.into(), &GraphName::DefaultGraph)?;
+                    self.skip_whitespace()?;
+                }
+                self.expect('.')
+            }
+            Some('(') => {
+                let subject = self.parse_collection(&GraphName::DefaultGraph)?;
+                self.skip_whitespace()?;
+                self.parse_predicate_object_list(&subject, &GraphName::DefaultGraph)?;
+                self.skip_whitespace()?;
+                self.expect('.')
+            }
+            Some('<') if self.read.ahead(1)? == Some('<') => {
+                let subject = self.parse_quoted_triple(0)?;
+                self.skip_whitespace()?;
+                self.parse_predicate_object_list(&subject.into(), &GraphName::DefaultGraph)?;
+                self.skip_whitespace()?;
+                self.expect('.')
+            }
+            _ => {
+                // [3g] triplesOrGraph ::= labelOrSubject (wrappedGraph | predicateObjectList '.')
+                let label = self.parse_label_or_subject()?;
+                self.skip_whitespace()?;
+                if self.read.current()? == Some('{') {
+                    self.parse_wrapped_graph(label.into())
+                } else {
+                    self.parse_predicate_object_list(&label.into(), &GraphName::DefaultGraph)?;
+                    self.skip_whitespace()?;
+                    self.expect('.')
+                }
+            }
+        }
+    }
+
+    fn parse_wrapped_graph(&mut self, graph_name: GraphName) -> Result<(), io::Error> {
+        // [5g] wrappedGraph ::= '{' triplesBlock? '}'
+        // The graph content is parsed by the next steps
+        self.expect('{')?;
+        self.graph_block = Some(graph_name);
+        Ok(())
+    }
+
+    /// Parses the next triples of the current wrapped graph and returns if the graph has been closed
+    fn parse_wrapped_graph_triples(&mut self, graph_name: &GraphName) -> Result<bool, io::Error> {
+        // [6g] triplesBlock ::= triples ('.' triplesBlock?)?
+        if self.read.current()? == Some('}') {
+            self.read.consume()?;
+            return Ok(true);
+        }
+        self.parse_triples(graph_name)?;
+        self.skip_whitespace()?;
+        match self.read.current()? {
+            Some('.') => {
+                self.read.consume()?;
+                Ok(false)
+            }
+            Some('}') => {
+                self.read.consume()?;
+                Ok(true)
+            }
+            _ => Err(self.read.unexpected_char_error()),
+        }
+    }
+
+    fn parse_label_or_subject(&mut self) -> Result<NamedOrBlankNode, io::Error> {
+        // [7g] labelOrSubject ::= iri | BlankNode
+        Ok(match self.read.current()? {
+            Some('_') => self.parse_blank_node_label()?.into(),
+            Some('[') => {
+                self.parse_anon()?;
+                BlankNode::default().into()
+            }
+            _ => self.parse_iri()?.into(),
+        })
+    }
+
+    /// Parses a directive if there is one and returns if a directive has been found
+    fn parse_directive(&mut self) -> Result<bool, io::Error> {
+        // [3] directive ::= prefixID | base | sparqlPrefix | sparqlBase
+        if self.read.starts_with("@prefix")? {
+            self.read.consume_many("@prefix".len())?;
+            self.parse_prefix_declaration()?;
+            self.skip_whitespace()?;
+            self.expect('.')?;
+        } else if self.read.starts_with("@base")? {
+            self.read.consume_many("@base".len())?;
+            self.parse_base_declaration()?;
+            self.skip_whitespace()?;
+            self.expect('.')?;
+        } else if self.read.starts_with_keyword("PREFIX")? {
+            self.read.consume_many("PREFIX".len())?;
+            self.parse_prefix_declaration()?;
+        } else if self.read.starts_with_keyword("BASE")? {
+            self.read.consume_many("BASE".len())?;
+            self.parse_base_declaration()?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    fn parse_prefix_declaration(&mut self) -> Result<(), io::Error> {
+        // [4] prefixID ::= '@prefix' PNAME_NS IRIREF '.'
+        // [6s] sparqlPrefix ::= "PREFIX" PNAME_NS IRIREF
+        self.skip_whitespace()?;
+        let prefix = self.parse_pname_ns()?;
+        self.skip_whitespace()?;
+        let iri = self.parse_iriref_value()?;
+        self.prefixes.insert(prefix, iri);
+        Ok(())
+    }
+
+    fn parse_base_declaration(&mut self) -> Result<(), io::Error> {
+        // [5] base ::= '@base' IRIREF '.'
+        // [5s] sparqlBase ::= "BASE" IRIREF
+        self.skip_whitespace()?;
+        let iri = self.parse_iriref_value()?;
+        self.base_iri =
+            Some(Iri::parse(iri).map_err(|e| self.read.error(format!("Invalid base IRI: {}", e)))?);
+        Ok(())
+    }
+
+    fn parse_triples(&mut self, graph_name: &GraphName) -> Result<(), io::Error> {
+        // [6] triples ::= subject predicateObjectList | blankNodePropertyList predicateObjectList?
+        if self.read.current()? == Some('[') && !self.is_anon()? {
+            let subject = self.parse_blank_node_property_list(graph_name)?;
+            self.skip_whitespace()?;
+            match self.read.current()? {
+                Some('.') | Some('}') | None => Ok(()),
+                _ => self.parse_predicate_object_list(&subject.into(), graph_name),
+            }
+        } else {
+            let subject = self.parse_subject(graph_name)?;
+            self.skip_whitespace()?;
+            self.parse_predicate_object_list(&subject, graph_name)
+        }
+    }
+
+    fn parse_predicate_object_list(
+        &mut self,
+        subject: &Subject,
+        graph_name: &GraphName,
+    ) -> Result<(), io::Error> {
+        // [7] predicateObjectList ::= verb objectList (';' (verb objectList)?)*
+        loop {
+            let predicate = self.parse_verb()?;
+            self.skip_whitespace()?;
+            self.parse_object_list(subject, &predicate, graph_name)?;
+            self.skip_whitespace()?;
+            if self.read.current()? != Some(';') {
+                return Ok(());
+            }
+            while self.read.current()? == Some(';') {
+                self.read.consume()?;
+                self.skip_whitespace()?;
+            }
+            match self.read.current()? {
+                Some('.') | Some(']') | Some('}') | None => return Ok(()),
+                Some('|') if self.read.ahead(1)? == Some('}') => return Ok(()),
+                _ => (),
+            }
+        }
+    }
+
+    fn parse_object_list(
+        &mut self,
+        subject: &Subject,
+        predicate: &NamedNode,
+        graph_name: &GraphName,
+    ) -> Result<(), io::Error> {
+        // [8] objectList ::= object annotation? ( ',' object annotation? )*
+        // [30t] annotation ::= '{|' predicateObjectList '|}'
+        loop {
+            let object = self.parse_object(graph_name)?;
+            self.buffer.push_back(Quad {
+                subject: subject.clone(),
+                predicate: predicate.clone(),
+                object: object.clone(),
+                graph_name: graph_name.clone(),
+            });
+            self.skip_whitespace()?;
+            if self.read.starts_with("{|")? {
+                self.read.consume_many(2)?;
+                self.skip_whitespace()?;
+                let triple = Triple {
+                    subject: subject.clone(),
+                    predicate: predicate.clone(),
+                    object,
+                };
+                self.parse_predicate_object_list(&triple.into(), graph_name)?;
+                self.skip_whitespace()?;
+                self.expect('|')?;
+                self.expect('}')?;
+                self.skip_whitespace()?;
+            }
+            if self.read.current()? != Some(',') {
+                return Ok(());
+            }
+            self.read.consume()?;
+            self.skip_whitespace()?;
+        }
+    }
+
+    fn parse_verb(&mut self) -> Result<NamedNode, io::Error> {
+        // [9] verb ::= predicate | 'a'
+        if self.read.current()? == Some('a') {
+            match self.read.ahead(1)? {
+                Some(c) if is_possible_pn_chars(c) || c == ':' => (),
+                _ => {
+                    self.read.consume()?;
+                    return Ok(rdf::TYPE.into());
+                }
+            }
+        }
+        self.parse_iri()
+    }
+
+    fn parse_subject(&mut self, graph_name: &GraphName) -> Result<Subject, io::Error> {
+        // [10] subject ::= iri | BlankNode | collection | quotedTriple
+        Ok(match self.read.current()? {
+            Some('<') if self.read.ahead(1)? == Some('<') => self.parse_quoted_triple(0)?.into(),
+            Some('_') => self.parse_blank_node_label()?.into(),
+            Some('[') => {
+                self.parse_anon()?;
+                BlankNode::default().into()
+            }
+            Some('(') => self.parse_collection(graph_name)?,
+            _ => self.parse_iri()?.into(),
+        })
+    }
+
+    fn parse_object(&mut self, graph_name: &GraphName) -> Result<Term, io::Error> {
+        // [12] object ::= iri | BlankNode | collection | blankNodePropertyList | literal | quotedTriple
+        match self.read.current()? {
+            Some('[') => {
+                if self.is_anon()? {
+                    self.parse_anon()?;
+                    Ok(BlankNode::default().into())
+                } else {
+                    Ok(self.parse_blank_node_property_list(graph_name)?.into())
+                }
+            }
+            Some('(') => Ok(self.parse_collection(graph_name)?.into()),
+            _ => self.parse_quoted_object(0),
+        }
+    }
+
+    /// Parses the terms that are allowed in the object position of quoted triples
+    fn parse_quoted_object(&mut self, number_of_recursive_calls: usize) -> Result<Term, io::Error> {
+        // [32t] qtObject ::= iri | BlankNode | literal | quotedTriple
+        match self.read.current()? {
+            Some('<') if self.read.ahead(1)? == Some('<') => {
+                Ok(self.parse_quoted_triple(number_of_recursive_calls)?.into())
+            }
+            Some('<') => Ok(self.parse_iriref()?.into()),
+            Some('_') => Ok(self.parse_blank_node_label()?.into()),
+            Some('[') => {
+                self.parse_anon()?;
+                Ok(BlankNode::default().into())
+            }
+            Some('"') | Some('\'') => Ok(self.parse_rdf_literal()?.into()),
+            Some(c) if c == '+' || c == '-' || c == '.' || c.is_ascii_digit() => {
+                Ok(self.parse_numeric_literal()?.into())
+            }
+            _ => {
+                if self.read.starts_with_keyword("true")? {
+                    self.read.consume_many("true".len())?;
+                    Ok(Literal::from(true).into())
+                } else if self.read.starts_with_keyword("false")? {
+                    self.read.consume_many("false".len())?;
+                    Ok(Literal::from(false).into())
+                } else {
+                    Ok(self.parse_prefixed_name()?.into())
+                }
+            }
+        }
+    }
+
+    fn parse_quoted_triple(
+        &mut self,
+        number_of_recursive_calls: usize,
+    ) -> Result<Triple, io::Error> {
+        // [27t] quotedTriple ::= '<<' qtSubject verb qtObject '>>'
+        // [28t] qtSubject ::= iri | BlankNode | quotedTriple
+        if number_of_recursive_calls == MAX_NUMBER_OF_NESTED_TRIPLES {
+            return Err(self.read.error("Too many nested triples"));
+        }
+        self.expect('<')?;
+        self.expect('<')?;
+        self.skip_whitespace()?;
+        let subject = match self.parse_quoted_object(number_of_recursive_calls + 1)? {
+            Term::NamedNode(node) => Subject::NamedNode(node),
+            Term::BlankNode(node) => Subject::BlankNode(node),
+            Term::Triple(triple) => Subject::Triple(triple),
+            Term::Literal(_) => {
+                return Err(self
+                    .read
+                    .error("Literals are not allowed in the subject position"))
+            }
+        };
+        self.skip_whitespace()?;
+        let predicate = self.parse_verb()?;
+        self.skip_whitespace()?;
+        let object = self.parse_quoted_object(number_of_recursive_calls + 1)?;
+        self.skip_whitespace()?;
+        self.expect('>')?;
+        self.expect('>')?;
+        Ok(Triple {
+            subject,
+            predicate,
+            object,
+        })
+    }
+
+    fn parse_blank_node_property_list(
+        &mut self,
+        graph_name: &GraphName,
+    ) -> Result<BlankNode, io::Error> {
+        // [14] blankNodePropertyList ::= '[' predicateObjectList ']'
+        self.expect('[')?;
+        self.skip_whitespace()?;
+        let node = BlankNode::default();
+        self.parse_predicate_object_list(&node.clone().into(), graph_name)?;
+        self.skip_whitespace()?;
+        self.expect(']')?;
+        Ok(node)
+    }
+
+    fn parse_collection(&mut self, graph_name: &GraphName) -> Result<Subject, io::Error> {
+        // [15] collection ::= '(' object* ')'
+        self.expect('(')?;
+        self.skip_whitespace()?;
+        let mut items = Vec::new();
+        loop {
+            match self.read.current()? {
+                Some(')') => {
+                    self.read.consume()?;
+                    break;
+                }
+                None => return Err(self.read.unexpected_char_error()),
+                _ => {
+                    items.push(self.parse_object(graph_name)?);
+                    self.skip_whitespace()?;
+                }
+            }
+        }
+        let nodes: Vec<Subject> = items.iter().map(|_| BlankNode::default().into()).collect();
+        for (i, item) in items.into_iter().enumerate() {
+            self.buffer.push_back(Quad {
+                subject: nodes[i].clone(),
+                predicate: rdf::FIRST.into(),
+                object: item,
+                graph_name: graph_name.clone(),
+            });
+            self.buffer.push_back(Quad {
+                subject: nodes[i].clone(),
+                predicate: rdf::REST.into(),
+                object: nodes
+                    .get(i + 1)
+                    .map_or_else(|| rdf::NIL.into(), |next| next.clone().into()),
+                graph_name: graph_name.clone(),
+            });
+        }
+        Ok(nodes.into_iter().next().unwrap_or_else(|| rdf::NIL.into()))
+    }
+
+    fn parse_rdf_literal(&mut self) -> Result<Literal, io::Error> {
+        // [128s] RDFLiteral ::= String (LANGTAG | '^^' iri)?
+        let value = self.parse_string()?;
+        match self.read.current()? {
+            Some('@') => self.parse_lang_tag(value),
+            Some('^') => {
+                self.expect('^')?;
+                self.expect('^')?;
+                Ok(Literal::new_typed_literal(value, self.parse_iri()?))
+            }
+            _ => Ok(Literal::new_simple_literal(value)),
+        }
+    }
+
+    fn parse_lang_tag(&mut self, value: String) -> Result<Literal, io::Error> {
+        // [144s] LANGTAG ::= '@' [a-zA-Z]+ ('-' [a-zA-Z0-9]+)*
+        self.expect('@')?;
+        let mut language = String::new();
+        while let Some(c) = self.read.current()? {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                language.push(c);
+                self.read.consume()?;
+            } else {
+                break;
+            }
+        }
+        Literal::new_language_tagged_literal(value, &language).map_err(|e| {
+            self.read
+                .error(format!("Invalid language tag '{}': {}", language, e))
+        })
+    }
+
+    fn parse_numeric_literal(&mut self) -> Result<Literal, io::Error> {
+        // [16] NumericLiteral ::= INTEGER | DECIMAL | DOUBLE
+        let mut value = String::new();
+        if let Some(c) = self.read.current()? {
+            if c == '+' || c == '-' {
+                value.push(c);
+                self.read.consume()?;
+            }
+        }
+        let mut datatype = xsd::INTEGER;
+        let mut has_digits = self.read_digits(&mut value)?;
+        if self.read.current()? == Some('.') {
+            match self.read.ahead(1)? {
+                Some(c) if c.is_ascii_digit() => {
+                    value.push('.');
+                    self.read.consume()?;
+                    self.read_digits(&mut value)?;
+                    datatype = xsd::DECIMAL;
+                    has_digits = true;
+                }
+                Some('e') | Some('E') if has_digits => {
+                    value.push('.');
+                    self.read.consume()?;
+                }
+                _ => (),
+            }
+        }
+        if !has_digits {
+            return Err(self.read.unexpected_char_error());
+        }
+        if let Some(c) = self.read.current()? {
+            if c == 'e' || c == 'E' {
+                value.push(c);
+                self.read.consume()?;
+                if let Some(c) = self.read.current()? {
+                    if c == '+' || c == '-' {
+                        value.push(c);
+                        self.read.consume()?;
+                    }
+                }
+                if !self.read_digits(&mut value)? {
+                    return Err(self.read.unexpected_char_error());
+                }
+                datatype = xsd::DOUBLE;
+            }
+        }
+        Ok(Literal::new_typed_literal(value, datatype))
+    }
+
+    fn read_digits(&mut self, buffer: &mut String) -> Result<bool, io::Error> {
+        let mut has_digits = false;
+        while let Some(c) = self.read.current()? {
+            if c.is_ascii_digit() {
+                buffer.push(c);
+                self.read.consume()?;
+                has_digits = true;
+            } else {
+                break;
+            }
+        }
+        Ok(has_digits)
+    }
+
+    fn parse_string(&mut self) -> Result<String, io::Error> {
+        // [17] String ::= STRING_LITERAL_QUOTE | STRING_LITERAL_SINGLE_QUOTE | STRING_LITERAL_LONG_SINGLE_QUOTE | STRING_LITERAL_LONG_QUOTE
+        let quote = match self.read.current()? {
+            Some(c) if c == '"' || c == '\'' => c,
+            _ => return Err(self.read.unexpected_char_error()),
+        };
+        let is_long = self.syntax != TurtleSyntax::NTriples
+            && self.syntax != TurtleSyntax::NQuads
+            && self.read.ahead(1)? == Some(quote)
+            && self.read.ahead(2)? == Some(quote);
+        let mut value = String::new();
+        if is_long {
+            self.read.consume_many(3)?;
+            loop {
+                match self.read.current()? {
+                    Some(c) if c == quote => {
+                        if self.read.ahead(1)? == Some(quote) && self.read.ahead(2)? == Some(quote)
+                        {
+                            self.read.consume_many(3)?;
+                            return Ok(value);
+                        }
+                        value.push(c);
+                        self.read.consume()?;
+                    }
+                    Some('\\') => value.push(self.parse_escape()?),
+                    Some(c) => {
+                        value.push(c);
+                        self.read.consume()?;
+                    }
+                    None => return Err(self.read.unexpected_char_error()),
+                }
+            }
+        } else {
+            self.read.consume()?;
+            loop {
+                match self.read.current()? {
+                    Some(c) if c == quote => {
+                        self.read.consume()?;
+                        return Ok(value);
+                    }
+                    Some('\\') => value.push(self.parse_escape()?),
+                    Some('\n') | Some('\r') | None => {
+                        return Err(self.read.unexpected_char_error())
+                    }
+                    Some(c) => {
+                        value.push(c);
+                        self.read.consume()?;
+                    }
+                }
+            }
+        }
+    }
+
+    fn parse_escape(&mut self) -> Result<char, io::Error> {
+        // [159s] ECHAR ::= '\' [tbnrf"'\]
+        // [26] UCHAR ::= '\u' HEX HEX HEX HEX | '\U' HEX HEX HEX HEX HEX HEX HEX HEX
+        self.expect('\\')?;
+        let c = match self.read.current()? {
+            Some('t') => '\t',
+            Some('b') => '\u{8}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('f') => '\u{C}',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('\\') => '\\',
+            Some('u') => return self.parse_unicode_escape(4),
+            Some('U') => return self.parse_unicode_escape(8),
+            _ => return Err(self.read.unexpected_char_error()),
+        };
+        self.read.consume()?;
+        Ok(c)
+    }
+
+    fn parse_unicode_escape(&mut self, len: usize) -> Result<char, io::Error> {
+        self.read.consume()?;
+        let mut value = 0;
+        for _ in 0..len {
+            let digit = self
+                .read
+                .current()?
+                .and_then(|c| c.to_digit(16))
+                .ok_or_else(|| self.read.unexpected_char_error())?;
+            value = value * 16 + digit;
+            self.read.consume()?;
+        }
+        char::from_u32(value).ok_or_else(|| {
+            self.read
+                .error(format!("Invalid unicode code point U+{:X}", value))
+        })
+    }
+
+    fn parse_iri(&mut self) -> Result<NamedNode, io::Error> {
+        // [135s] iri ::= IRIREF | PrefixedName
+        if self.read.current()? == Some('<') {
+            self.parse_iriref()
+        } else {
+            self.parse_prefixed_name()
+        }
+    }
+
+    fn parse_iriref(&mut self) -> Result<NamedNode, io::Error> {
+        Ok(NamedNode::new_unchecked(self.parse_iriref_value()?))
+    }
+
+    fn parse_iriref_value(&mut self) -> Result<String, io::Error> {
+        // [18] IRIREF ::= '<' ([^#x00-#x20<>"{}|^`\] | UCHAR)* '>'
+        self.expect('<')?;
+        let mut value = String::new();
+        loop {
+            match self.read.current()? {
+                Some('>') => {
+                    self.read.consume()?;
+                    break;
+                }
+                Some('\\') => {
+                    self.read.consume()?;
+                    value.push(match self.read.current()? {
+                        Some('u') => self.parse_unicode_escape(4)?,
+                        Some('U') => self.parse_unicode_escape(8)?,
+                        _ => return Err(self.read.unexpected_char_error()),
+                    });
+                }
+                Some(c) if c <= ' ' || matches!(c, '<' | '"' | '{' | '}' | '|' | '^' | '`') => {
+                    return Err(self.read.unexpected_char_error())
+                }
+                Some(c) => {
+                    value.push(c);
+                    self.read.consume()?;
+                }
+                None => return Err(self.read.unexpected_char_error()),
+            }
+        }
+        // N-Triples and N-Quads only allow absolute IRIs
+        match &self.base_iri {
+            Some(base_iri) if matches!(self.syntax, TurtleSyntax::Turtle | TurtleSyntax::TriG) => {
+                base_iri.resolve(&value).map(Iri::into_inner)
+            }
+            _ => Iri::parse(value.clone()).map(Iri::into_inner),
+        }
+        .map_err(|e| self.read.error(format!("Invalid IRI <{}>: {}", value, e)))
+    }
+
+    fn parse_pname_ns(&mut self) -> Result<String, io::Error> {
+        // [139s] PNAME_NS ::= PN_PREFIX? ':'
+        // [167s] PN_PREFIX ::= PN_CHARS_BASE ((PN_CHARS | '.')* PN_CHARS)?
+        let mut prefix = String::new();
+        if let Some(c) = self.read.current()? {
+            if is_pn_chars_base(c) {
+                prefix.push(c);
+                self.read.consume()?;
+                self.read_pn_chars_with_dots(&mut prefix)?;
+            }
+        }
+        self.expect(':')?;
+        Ok(prefix)
+    }
+
+    fn parse_prefixed_name(&mut self) -> Result<NamedNode, io::Error> {
+        // [136s] PrefixedName ::= PNAME_LN | PNAME_NS
+        // [168s] PN_LOCAL ::= (PN_CHARS_U | ':' | [0-9] | PLX) ((PN_CHARS | '.' | ':' | PLX)* (PN_CHARS | ':' | PLX))?
+        let prefix = self.parse_pname_ns()?;
+        let mut iri = if let Some(namespace) = self.prefixes.get(&prefix) {
+            namespace.clone()
+        } else {
+            return Err(self
+                .read
+                .error(format!("The prefix {}: has not been declared", prefix)));
+        };
+        if let Some(c) = self.read.current()? {
+            if is_pn_chars_u(c) || c == ':' || c.is_ascii_digit() || c == '%' || c == '\\' {
+                loop {
+                    match self.read.current()? {
+                        Some('%') => {
+                            iri.push('%');
+                            self.read.consume()?;
+                            for _ in 0..2 {
+                                match self.read.current()? {
+                                    Some(c) if c.is_ascii_hexdigit() => {
+                                        iri.push(c);
+                                        self.read.consume()?;
+                                    }
+                                    _ => return Err(self.read.unexpected_char_error()),
+                                }
+                            }
+                        }
+                        Some('\\') => {
+                            self.read.consume()?;
+                            match self.read.current()? {
+                                Some(c) if is_pn_local_esc(c) => {
+                                    iri.push(c);
+                                    self.read.consume()?;
+                                }
+                                _ => return Err(self.read.unexpected_char_error()),
+                            }
+                        }
+                        Some(c) if is_pn_chars(c) || c == ':' => {
+                            iri.push(c);
+                            self.read.consume()?;
+                        }
+                        Some('.') if self.is_followed_by_local_char()? => {
+                            iri.push('.');
+                            self.read.consume()?;
+                        }
+                        _ => break,
+                    }
+                }
+            }
+        }
+        Ok(NamedNode::new_unchecked(iri))
+    }
+
+    fn parse_blank_node_label(&mut self) -> Result<BlankNode, io::Error> {
+        // [141s] BLANK_NODE_LABEL ::= '_:' (PN_CHARS_U | [0-9]) ((PN_CHARS | '.')* PN_CHARS)?
+        self.expect('_')?;
+        self.expect(':')?;
+        let mut id = String::new();
+        match self.read.current()? {
+            Some(c) if is_pn_chars_u(c) || c.is_ascii_digit() => {
+                id.push(c);
+                self.read.consume()?;
+            }
+            _ => return Err(self.read.unexpected_char_error()),
+        }
+        self.read_pn_chars_with_dots(&mut id)?;
+        let seed = self.bnode_seed;
+        Ok(self
+            .bnodes
+            .entry(id)
+            .or_insert_with_key(|id| {
+                if let Some(seed) = seed {
+                    let mut hasher = SipHasher24::new();
+                    hasher.write(&seed.to_be_bytes());
+                    hasher.write(id.as_bytes());
+                    BlankNode::new_from_unique_id(hasher.finish128())
+                } else {
+                    BlankNode::default()
+                }
+            })
+            .clone())
+    }
+
+    fn read_pn_chars_with_dots(&mut self, buffer: &mut String) -> Result<(), io::Error> {
+        loop {
+            match self.read.current()? {
+                Some(c) if is_pn_chars(c) => {
+                    buffer.push(c);
+                    self.read.consume()?;
+                }
+                Some('.') => {
+                    // Dots are only allowed if they are not the last character
+                    let mut i = 1;
+                    while self.read.ahead(i)? == Some('.') {
+                        i += 1;
+                    }
+                    match self.read.ahead(i)? {
+                        Some(c) if is_pn_chars(c) => {
+                            for _ in 0..i {
+                                buffer.push('.');
+                            }
+                            self.read.consume_many(i)?;
+                        }
+                        _ => return Ok(()),
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn is_followed_by_local_char(&mut self) -> Result<bool, io::Error> {
+        let mut i = 1;
+        while self.read.ahead(i)? == Some('.') {
+            i += 1;
+        }
+        Ok(match self.read.ahead(i)? {
+            Some(c) => is_pn_chars(c) || c == ':' || c == '%' || c == '\\',
+            None => false,
+        })
+    }
+
+    fn is_anon(&mut self) -> Result<bool, io::Error> {
+        // [162s] ANON ::= '[' WS* ']'
+        let mut i = 1;
+        loop {
+            match self.read.ahead(i)? {
+                Some(' ') | Some('\t') | Some('\n') | Some('\r') => i += 1,
+                Some(']') => return Ok(true),
+                _ => return Ok(false),
+            }
+        }
+    }
+
+    fn parse_anon(&mut self) -> Result<(), io::Error> {
+        self.expect('[')?;
+        self.skip_whitespace()?;
+        self.expect(']')
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), io::Error> {
+        if self.read.current()? == Some(expected) {
+            self.read.consume()
+        } else {
+            Err(self.read.unexpected_char_error())
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), io::Error> {
+        loop {
+            match self.read.current()? {
+                Some(' ') | Some('\t') | Some('\n') | Some('\r') => self.read.consume()?,
+                Some('#') => {
+                    while let Some(c) = self.read.current()? {
+                        self.read.consume()?;
+                        if c == '\n' {
+                            break;
+                        }
+                    }
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for TurtleParser<R> {
+    type Item = Result<Quad, io::Error>;
+
+    fn next(&mut self) -> Option<Result<Quad, io::Error>> {
+        loop {
+            if let Some(quad) = self.buffer.pop_front() {
+                return Some(Ok(quad));
+            }
+            if self.is_ended {
+                return None;
+            }
+            match self.parse_step() {
+                Ok(true) => (),
+                Ok(false) => self.is_ended = true,
+                Err(error) => {
+                    // The quads of the invalid statement are dropped
+                    self.buffer.clear();
+                    if self.error_recovery && error.kind() == ErrorKind::InvalidData {
+                        if let Err(error) = self.recover() {
+                            self.is_ended = true;
+                            return Some(Err(error));
+                        }
+                    } else {
+                        self.is_ended = true;
+                    }
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+/// Reads the file in streaming, decoding UTF-8 and keeping track of the current position
+struct LookAheadCharReader<R: BufRead> {
+    inner: R,
+    buffer: VecDeque<char>,
+    pending_bytes: Vec<u8>,
+    line_number: u64,
+    column_number: u64,
+}
+
+impl<R: BufRead> LookAheadCharReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            buffer: VecDeque::default(),
+            pending_bytes: Vec::new(),
+            line_number: 1,
+            column_number: 1,
+        }
+    }
+
+    /// Reads more chars into the buffer. Returns `false` if the end of the input is reached.
+    fn fill(&mut self) -> Result<bool, io::Error> {
+        loop {
+            if self.decode_pending_bytes()? {
+                return Ok(true);
+            }
+            let data = match self.inner.fill_buf() {
+                Ok(data) => data,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if data.is_empty() {
+                return if self.pending_bytes.is_empty() {
+                    Ok(false)
+                } else {
+                    self.pending_bytes.clear();
+                    Err(self.error("Invalid UTF-8 sequence at the end of the file"))
+                };
+            }
+            let len = data.len();
+            self.pending_bytes.extend_from_slice(data);
+            self.inner.consume(len);
+        }
+    }
+
+    /// Moves the valid chars at the start of the pending bytes into the buffer and returns if there were some
+    fn decode_pending_bytes(&mut self) -> Result<bool, io::Error> {
+        let valid_up_to = match str::from_utf8(&self.pending_bytes) {
+            Ok(data) => {
+                let is_empty = data.is_empty();
+                self.buffer.extend(data.chars());
+                self.pending_bytes.clear();
+                return Ok(!is_empty);
+            }
+            Err(e) => {
+                if let (0, Some(error_len)) = (e.valid_up_to(), e.error_len()) {
+                    // The invalid sequence is dropped to allow the parsing to resume after it
+                    self.pending_bytes.drain(..error_len);
+                    return Err(self.error("Invalid UTF-8 sequence"));
+                }
+                // An invalid sequence is only reported once the valid chars before it have been read
+                e.valid_up_to()
+            }
+        };
+        if valid_up_to == 0 {
+            return Ok(false);
+        }
+        if let Ok(data) = str::from_utf8(&self.pending_bytes[..valid_up_to]) {
+            self.buffer.extend(data.chars());
+        }
+        self.pending_bytes.drain(..valid_up_to);
+        Ok(true)
+    }
+
+    fn current(&mut self) -> Result<Option<char>, io::Error> {
+        self.ahead(0)
+    }
+
+    fn ahead(&mut self, count: usize) -> Result<Option<char>, io::Error> {
+        while self.buffer.len() <= count {
+            if !self.fill()? {
+                return Ok(None);
+            }
+        }
+        Ok(self.buffer.get(count).copied())
+    }
+
+    fn consume(&mut self) -> Result<(), io::Error> {
+        self.consume_many(1)
+    }
+
+    fn consume_many(&mut self, count: usize) -> Result<(), io::Error> {
+        for _ in 0..count {
+            if self.buffer.is_empty() && !self.fill()? {
+                return Err(self.error("Unexpected end of file"));
+            }
+            if self.buffer.pop_front() == Some('\n') {
+                self.line_number += 1;
+                self.column_number = 1;
+            } else {
+                self.column_number += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn starts_with(&mut self, prefix: &str) -> Result<bool, io::Error> {
+        for (i, expected) in prefix.chars().enumerate() {
+            if self.ahead(i)? != Some(expected) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Checks that the input starts with a given ASCII keyword (case insensitive) not followed by a name character
+    fn starts_with_keyword(&mut self, keyword: &str) -> Result<bool, io::Error> {
+        for (i, expected) in keyword.chars().enumerate() {
+            match self.ahead(i)? {
+                Some(c) if c.eq_ignore_ascii_case(&expected) => (),
+                _ => return Ok(false),
+            }
+        }
+        Ok(match self.ahead(keyword.len())? {
+            Some(c) => !is_possible_pn_chars(c) && c != ':',
+            None => true,
+        })
+    }
+
+    fn error(&self, msg: impl Into<String>) -> io::Error {
+        invalid_data_error(format!(
+            "{} on line {} at column {}",
+            msg.into(),
+            self.line_number,
+            self.column_number
+        ))
+    }
+
+    fn unexpected_char_error(&self) -> io::Error {
+        match self.buffer.front() {
+            Some(c) => self.error(format!("Unexpected character '{}'", c.escape_debug())),
+            None => self.error("Unexpected end of file"),
+        }
+    }
+}
+
+fn is_pn_chars_base(c: char) -> bool {
+    matches!(c,
+        'A'..='Z'
+        | 'a'..='z'
+        | '\u{00C0}'..='\u{00D6}'
+        | '\u{00D8}'..='\u{00F6}'
+        | '\u{00F8}'..='\u{02FF}'
+        | '\u{0370}'..='\u{037D}'
+        | '\u{037F}'..='\u{1FFF}'
+        | '\u{200C}'..='\u{200D}'
+        | '\u{2070}'..='\u{218F}'
+        | '\u{2C00}'..='\u{2FEF}'
+        | '\u{3001}'..='\u{D7FF}'
+        | '\u{F900}'..='\u{FDCF}'
+        | '\u{FDF0}'..='\u{FFFD}'
+        | '\u{10000}'..='\u{EFFFF}')
+}
+
+fn is_pn_chars_u(c: char) -> bool {
+    c == '_' || is_pn_chars_base(c)
+}
+
+fn is_pn_chars(c: char) -> bool {
+    is_pn_chars_u(c)
+        || matches!(c,
+            '-' | '0'..='9' | '\u{00B7}' | '\u{0300}'..='\u{036F}' | '\u{203F}'..='\u{2040}')
+}
+
+/// Returns if the char could continue a keyword or a prefixed name
+fn is_possible_pn_chars(c: char) -> bool {
+    is_pn_chars(c) || c == '.'
+}
+
+fn is_pn_local_esc(c: char) -> bool {
+    matches!(
+        c,
+        '_' | '~'
+            | '.'
+            | '-'
+            | '!'
+            | '$'
+            | '&'
+            | '\''
+            | '('
+            | ')'
+            | '*'
+            | '+'
+            | ','
+            | ';'
+            | '='
+            | '/'
+            | '?'
+            | '#'
+            | '@'
+            | '%'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(data: &str, syntax: TurtleSyntax) -> Result<Vec<Quad>, io::Error> {
+        TurtleParser::new(data.as_bytes(), syntax, None).collect()
+    }
+
+    fn to_canonical(quads: &[Quad]) -> Vec<String> {
+        // blank node ids are random so we remove them
+        quads
+            .iter()
+            .map(|q| {
+                let s = q.to_string();
+                let mut out = String::new();
+                let mut in_bnode = false;
+                for c in s.chars() {
+                    if in_bnode {
+                        if c.is_whitespace() {
+                            in_bnode = false;
+                            out.push(c);
+                        }
+                    } else if c == '_' {
+                        in_bnode = true;
+                        out.push_str("_:b");
+                    } else {
+                        out.push(c);
+                    }
+                }
+                out
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ntriples_star() -> Result<(), io::Error> {
+        let quads = parse(
+            "<http://ex.com/s> <http://ex.com/p> \"o\\u00E9\"@en . # comment\n\
+             << <http://ex.com/s> <http://ex.com/p> << _:a <http://ex.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> >> >> <http://ex.com/p> _:a .",
+            TurtleSyntax::NTriples,
+        )?;
+        assert_eq!(quads.len(), 2);
+        assert_eq!(
+            quads[0].object,
+            Literal::new_language_tagged_literal("o\u{e9}", "en")
+                .unwrap()
+                .into()
+        );
+        if let Subject::Triple(triple) = &quads[1].subject {
+            if let Term::Triple(inner) = &triple.object {
+                assert_eq!(Term::from(inner.subject.clone()), quads[1].object);
+                assert_eq!(inner.object, Literal::from(1).into());
+                return Ok(());
+            }
+        }
+        panic!("Quoted triples not parsed properly: {:?}", quads)
+    }
+
+    #[test]
+    fn test_nquads() -> Result<(), io::Error> {
+        let quads = parse(
+            "<http://ex.com/s> <http://ex.com/p> <http://ex.com/o> <http://ex.com/g> .\n\
+             <http://ex.com/s> <http://ex.com/p> <http://ex.com/o> .",
+            TurtleSyntax::NQuads,
+        )?;
+        assert_eq!(
+            quads[0].graph_name,
+            NamedNode::new("http://ex.com/g").unwrap().into()
+        );
+        assert_eq!(quads[1].graph_name, GraphName::DefaultGraph);
+        Ok(())
+    }
+
+    #[test]
+    fn test_turtle() -> Result<(), io::Error> {
+        let quads = parse(
+            "@prefix ex: <http://ex.com/> .\n\
+             PREFIX : <http://ex.com/default#>\n\
+             @base <http://ex.com/base/> .\n\
+             ex:s a ex:C ; ex:p 1, -2.5, 1e3, true, 'a', \"\"\"b\n\"c\"\"\"@en-US, \"c\"^^ex:dt ;\n\
+                 ex:q [ ex:r <rel> ], ( :a [] ) ;\n\
+                 ex:s\\.x ex:a.b.c .\n\
+             [ ex:p ex:o ] .",
+            TurtleSyntax::Turtle,
+        )?;
+        assert_eq!(
+            to_canonical(&quads),
+            vec![
+                "<http://ex.com/s> <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://ex.com/C>",
+                "<http://ex.com/s> <http://ex.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+                "<http://ex.com/s> <http://ex.com/p> \"-2.5\"^^<http://www.w3.org/2001/XMLSchema#decimal>",
+                "<http://ex.com/s> <http://ex.com/p> \"1e3\"^^<http://www.w3.org/2001/XMLSchema#double>",
+                "<http://ex.com/s> <http://ex.com/p> \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean>",
+                "<http://ex.com/s> <http://ex.com/p> \"a\"",
+                "<http://ex.com/s> <http://ex.com/p> \"b\\n\\\"c\"@en-us",
+                "<http://ex.com/s> <http://ex.com/p> \"c\"^^<http://ex.com/dt>",
+                "_:b <http://ex.com/r> <http://ex.com/base/rel>",
+                "<http://ex.com/s> <http://ex.com/q> _:b",
+                "_:b <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> <http://ex.com/default#a>",
+                "_:b <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> _:b",
+                "_:b <http://www.w3.org/1999/02/22-rdf-syntax-ns#first> _:b",
+                "_:b <http://www.w3.org/1999/02/22-rdf-syntax-ns#rest> <http://www.w3.org/1999/02/22-rdf-syntax-ns#nil>",
+                "<http://ex.com/s> <http://ex.com/q> _:b",
+                "<http://ex.com/s> <http://ex.com/s.x> <http://ex.com/a.b.c>",
+                "_:b <http://ex.com/p> <http://ex.com/o>",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_turtle_star() -> Result<(), io::Error> {
+        let quads = parse(
+            "PREFIX : <http://ex.com/>\n\
+             << :s :p << [] :p2 'o' >> >> :q :r .\n\
+             :s :p :o {| :source :x ; :date 2 |} .",
+            TurtleSyntax::Turtle,
+        )?;
+        assert_eq!(
+            to_canonical(&quads),
+            vec![
+                "<< <http://ex.com/s> <http://ex.com/p> << _:b <http://ex.com/p2> \"o\" >> >> <http://ex.com/q> <http://ex.com/r>",
+                "<http://ex.com/s> <http://ex.com/p> <http://ex.com/o>",
+                "<< <http://ex.com/s> <http://ex.com/p> <http://ex.com/o> >> <http://ex.com/source> <http://ex.com/x>",
+                "<< <http://ex.com/s> <http://ex.com/p> <http://ex.com/o> >> <http://ex.com/date> \"2\"^^<http://www.w3.org/2001/XMLSchema#integer>",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_trig() -> Result<(), io::Error> {
+        let quads = parse(
+            "@prefix : <http://ex.com/> .\n\
+             :g { :s :p :o . << :s :p :o >> :q :r }\n\
+             GRAPH _:g { :s :p :o2 }\n\
+             { :s :p :o3 . }\n\
+             :s :p :o4 .",
+            TurtleSyntax::TriG,
+        )?;
+        assert_eq!(
+            to_canonical(&quads),
+            vec![
+                "<http://ex.com/s> <http://ex.com/p> <http://ex.com/o> <http://ex.com/g>",
+                "<< <http://ex.com/s> <http://ex.com/p> <http://ex.com/o> >> <http://ex.com/q> <http://ex.com/r> <http://ex.com/g>",
+                "<http://ex.com/s> <http://ex.com/p> <http://ex.com/o2> _:b",
+                "<http://ex.com/s> <http://ex.com/p> <http://ex.com/o3>",
+                "<http://ex.com/s> <http://ex.com/p> <http://ex.com/o4>",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_blank_node_seed() -> Result<(), io::Error> {
+        let parse_with_seed = |data: &str, seed| {
+            TurtleParser::new(data.as_bytes(), TurtleSyntax::NTriples, None)
+                .with_blank_node_seed(seed)
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let first = parse_with_seed("_:a <http://ex.com/p> _:b .", 1)?;
+        let second = parse_with_seed("_:b <http://ex.com/p> _:a .", 1)?;
+        assert_eq!(Term::from(first[0].subject.clone()), second[0].object);
+        assert_eq!(first[0].object, Term::from(second[0].subject.clone()));
+        assert_ne!(Term::from(first[0].subject.clone()), first[0].object);
+        let other = parse_with_seed("_:a <http://ex.com/p> _:b .", 2)?;
+        assert_ne!(first[0].subject, other[0].subject);
+        Ok(())
+    }
+
+    #[test]
+    fn test_error_recovery() {
+        let parse_with_recovery = |data: &[u8], syntax| {
+            let mut quads = Vec::new();
+            let mut errors = Vec::new();
+            for result in TurtleParser::new(data, syntax, None).with_error_recovery() {
+                match result {
+                    Ok(quad) => quads.push(quad.object.to_string()),
+                    Err(error) => errors.push(error.to_string()),
+                }
+            }
+            (quads, errors)
+        };
+
+        let (quads, errors) = parse_with_recovery(
+            b"<http://ex.com/s> <http://ex.com/p> <http://ex.com/o1> .\n\
+            <http://ex.com/s> <http://ex.com/p> <http://ex.com/o2>\n\
+            <http://ex.com/s> <http://ex.com/p> \"\xFF\" .\n\
+            <http://ex.com/s> <http://ex.com/p> <http://ex.com/o3> .\n\
+            <http://ex.com/s> <http://ex.com/p> <o4> .",
+            TurtleSyntax::NTriples,
+        );
+        assert_eq!(quads, vec!["<http://ex.com/o1>", "<http://ex.com/o3>"]);
+        assert_eq!(errors.len(), 3);
+        assert!(
+            errors[0].ends_with("on line 3 at column 1"),
+            "{}",
+            errors[0]
+        );
+        assert!(errors[2].contains("on line 5"), "{}", errors[2]);
+
+        let (quads, errors) = parse_with_recovery(
+            b"@prefix : <http://ex.com/> .\n\
+            :s :p :o1 , ( .\n\
+            :s :p :o2 .\n\
+            :g { :s :p :o3 . :s :p :p :o4 . :s :p :o5 }\n\
+            :s :p :o6 .",
+            TurtleSyntax::TriG,
+        );
+        assert_eq!(
+            quads,
+            vec![
+                "<http://ex.com/o2>",
+                "<http://ex.com/o3>",
+                "<http://ex.com/o5>",
+                "<http://ex.com/o6>"
+            ]
+        );
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid() {
+        for (data, syntax) in &[
+            ("<http://ex.com/s> <http://ex.com/p> .", TurtleSyntax::NTriples),
+            ("\"s\" <http://ex.com/p> <http://ex.com/o> .", TurtleSyntax::NTriples),
+            ("<s> <http://ex.com/p> <http://ex.com/o> .", TurtleSyntax::NTriples),
+            ("<http://ex.com/s> <http://ex.com/p> 'o' .", TurtleSyntax::NTriples),
+            ("ex:s ex:p ex:o .", TurtleSyntax::Turtle),
+            ("<http://ex.com/s> <http://ex.com/p> ( .", TurtleSyntax::Turtle),
+            ("<< <http://ex.com/s> <http://ex.com/p> [ <http://ex.com/p> 1 ] >> <http://ex.com/p> 1 .", TurtleSyntax::Turtle),
+            ("<http://ex.com/s> <http://ex.com/p> \"o\"@ .", TurtleSyntax::Turtle),
+            ("{ <http://ex.com/s> <http://ex.com/p> <http://ex.com/o> .", TurtleSyntax::TriG),
+        ] {
+            assert!(parse(data, *syntax).is_err(), "{} should be invalid", data);
+        }
+    }
+}
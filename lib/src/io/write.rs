@@ -1,12 +1,20 @@
 //! Utilities to write RDF graphs and datasets
 
-use crate::io::{DatasetFormat, GraphFormat};
+use crate::error::invalid_input_error;
+use crate::io::compression::CompressWriter;
+use crate::io::jsonld::{JsonLdFormatter, SimpleDocumentLoader};
+use crate::io::{Compression, DatasetFormat, GraphFormat, JsonLdDocumentLoader};
+use crate::model::vocab::{rdf, xsd};
 use crate::model::*;
-use rio_api::formatter::{QuadsFormatter, TriplesFormatter};
-use rio_turtle::{NQuadsFormatter, NTriplesFormatter, TriGFormatter, TurtleFormatter};
+use oxiri::Iri;
+use rio_api::formatter::TriplesFormatter;
+use rio_api::model as rio;
 use rio_xml::RdfXmlFormatter;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::io;
 use std::io::Write;
+use std::sync::Arc;
 
 /// A serializer for RDF graph serialization formats.
 ///
@@ -15,6 +23,13 @@ use std::io::Write;
 /// * [Turtle](https://www.w3.org/TR/turtle/) ([`GraphFormat::Turtle`](super::GraphFormat::Turtle))
 /// * [RDF/XML](https://www.w3.org/TR/rdf-syntax-grammar/) ([`GraphFormat::RdfXml`](super::GraphFormat::RdfXml))
 ///
+/// The N-Triples and Turtle serializers also support the [RDF-star](https://w3c.github.io/rdf-star/cg-spec/) quoted triples.
+/// RDF/XML does not allow them and returns an error if one is written.
+///
+/// The Turtle serializer groups the consecutive triples sharing the same subject and predicate.
+/// Prefixes could be declared with [`with_prefix`](GraphSerializer::with_prefix())
+/// and a more compact output is written if [`with_pretty_formatting`](GraphSerializer::with_pretty_formatting()) is used.
+///
 /// ```
 /// use oxigraph::io::{GraphFormat, GraphSerializer};
 /// use oxigraph::model::*;
@@ -31,29 +46,197 @@ use std::io::Write;
 ///assert_eq!(buffer.as_slice(), "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n".as_bytes());
 /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
 /// ```
-#[allow(missing_copy_implementations)]
+#[derive(Clone)]
 pub struct GraphSerializer {
     format: GraphFormat,
+    turtle_options: TurtleOptions,
+    compression: Option<Compression>,
 }
 
 impl GraphSerializer {
     /// Builds a serializer for the given format
     pub fn from_format(format: GraphFormat) -> Self {
-        Self { format }
+        Self {
+            format,
+            turtle_options: TurtleOptions::default(),
+            compression: None,
+        }
+    }
+
+    /// Declares a prefix used to abbreviate the IRIs in the Turtle output.
+    ///
+    /// It is ignored by the other formats.
+    /// An [`InvalidInput`](std::io::ErrorKind::InvalidInput) error is returned if the prefix name or the IRI is not valid.
+    ///
+    /// ```
+    /// use oxigraph::io::{GraphFormat, GraphSerializer};
+    /// use oxigraph::model::*;
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut writer = GraphSerializer::from_format(GraphFormat::Turtle)
+    ///     .with_prefix("ex", "http://example.com/")?
+    ///     .triple_writer(&mut buffer)?;
+    /// writer.write(&Triple {
+    ///    subject: NamedNode::new("http://example.com/s")?.into(),
+    ///    predicate: NamedNode::new("http://example.com/p")?,
+    ///    object: NamedNode::new("http://example.com/o")?.into()
+    /// })?;
+    /// writer.finish()?;
+    ///
+    ///assert_eq!(buffer.as_slice(), "@prefix ex: <http://example.com/> .\n\nex:s ex:p ex:o .\n".as_bytes());
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn with_prefix(
+        mut self,
+        prefix_name: impl Into<String>,
+        prefix_iri: impl Into<String>,
+    ) -> Result<Self, io::Error> {
+        self.turtle_options
+            .add_prefix(prefix_name.into(), prefix_iri.into())?;
+        Ok(self)
+    }
+
+    /// Declares several prefixes at once, for example the ones read by a [`TripleReader`](super::read::TripleReader).
+    ///
+    /// See [`with_prefix`](GraphSerializer::with_prefix()) for the details.
+    ///
+    /// ```
+    /// use oxigraph::io::{GraphFormat, GraphParser, GraphSerializer};
+    /// use std::io::Cursor;
+    ///
+    /// let file = "@prefix ex: <http://example.com/> .\n\nex:s ex:p ex:o .\n";
+    ///
+    /// let mut reader = GraphParser::from_format(GraphFormat::Turtle).read_triples(Cursor::new(file))?;
+    /// let triples = reader.by_ref().collect::<Result<Vec<_>, _>>()?;
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut writer = GraphSerializer::from_format(GraphFormat::Turtle)
+    ///     .with_prefixes(reader.prefixes())?
+    ///     .triple_writer(&mut buffer)?;
+    /// for triple in &triples {
+    ///     writer.write(triple)?;
+    /// }
+    /// writer.finish()?;
+    ///
+    /// assert_eq!(buffer.as_slice(), file.as_bytes());
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn with_prefixes(
+        mut self,
+        prefixes: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Result<Self, io::Error> {
+        for (prefix_name, prefix_iri) in prefixes {
+            self.turtle_options
+                .add_prefix(prefix_name.into(), prefix_iri.into())?;
+        }
+        Ok(self)
+    }
+
+    /// Writes a more compact and readable Turtle output.
+    ///
+    /// The triples are grouped by subject, `a` is used for `rdf:type`,
+    /// the integers, decimals, doubles and booleans are written without their datatype
+    /// and the [RDF collections](https://www.w3.org/TR/turtle/#collections) are written using the `( )` syntax.
+    /// All the triples are kept in memory until [`finish`](TripleWriter::finish()) is called.
+    ///
+    /// It is ignored by the other formats.
+    ///
+    /// ```
+    /// use oxigraph::io::{GraphFormat, GraphSerializer};
+    /// use oxigraph::model::*;
+    /// use oxigraph::model::vocab::rdf;
+    ///
+    /// let s = NamedNode::new("http://example.com/s")?;
+    /// let p = NamedNode::new("http://example.com/p")?;
+    /// let list = [BlankNode::default(), BlankNode::default()];
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut writer = GraphSerializer::from_format(GraphFormat::Turtle)
+    ///     .with_prefix("ex", "http://example.com/")?
+    ///     .with_pretty_formatting()
+    ///     .triple_writer(&mut buffer)?;
+    /// writer.write(TripleRef::new(&s, &p, &Literal::from(1)))?;
+    /// writer.write(TripleRef::new(&s, rdf::TYPE, NamedNodeRef::new("http://example.com/C")?))?;
+    /// writer.write(TripleRef::new(&s, &p, &list[0]))?;
+    /// writer.write(TripleRef::new(&list[0], rdf::FIRST, &Literal::from("a")))?;
+    /// writer.write(TripleRef::new(&list[0], rdf::REST, &list[1]))?;
+    /// writer.write(TripleRef::new(&list[1], rdf::FIRST, &Literal::from(true)))?;
+    /// writer.write(TripleRef::new(&list[1], rdf::REST, rdf::NIL))?;
+    /// writer.finish()?;
+    ///
+    ///assert_eq!(
+    ///    std::str::from_utf8(&buffer)?,
+    ///    "@prefix ex: <http://example.com/> .\n\nex:s a ex:C ;\n\tex:p 1 , ( \"a\" true ) .\n"
+    ///);
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn with_pretty_formatting(mut self) -> Self {
+        self.turtle_options.pretty = true;
+        self
+    }
+
+    /// Compresses the output with the given format.
+    ///
+    /// An error is returned when the writer is created if the support of the compression format has not been enabled.
+    ///
+    /// ```
+    /// use oxigraph::io::{Compression, GraphFormat, GraphParser, GraphSerializer};
+    /// use oxigraph::model::*;
+    ///
+    /// let triple = Triple::new(
+    ///    NamedNode::new("http://example.com/s")?,
+    ///    NamedNode::new("http://example.com/p")?,
+    ///    NamedNode::new("http://example.com/o")?
+    /// );
+    ///
+    /// let mut buffer = Vec::new();
+    /// let compressed = match GraphSerializer::from_format(GraphFormat::NTriples)
+    ///     .with_compression(Compression::Gzip)
+    ///     .triple_writer(&mut buffer) {
+    ///     Ok(mut writer) => {
+    ///         writer.write(&triple)?;
+    ///         writer.finish()?;
+    ///         true
+    ///     }
+    ///     Err(_) => false, // The gzip support is not enabled
+    /// };
+    ///
+    /// if compressed {
+    ///     // The parser detects the compression by itself
+    ///     let triples = GraphParser::from_format(GraphFormat::NTriples)
+    ///         .read_triples(buffer.as_slice())?
+    ///         .collect::<Result<Vec<_>,_>>()?;
+    ///     assert_eq!(triples, vec![triple]);
+    /// }
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
     }
 
     /// Returns a `TripleWriter` allowing writing triples into the given [`Write`](std::io::Write) implementation
     pub fn triple_writer<W: Write>(&self, writer: W) -> Result<TripleWriter<W>, io::Error> {
+        let writer = CompressWriter::new(writer, self.compression)?;
         Ok(TripleWriter {
             formatter: match self.format {
-                GraphFormat::NTriples => TripleWriterKind::NTriples(NTriplesFormatter::new(writer)),
-                GraphFormat::Turtle => TripleWriterKind::Turtle(TurtleFormatter::new(writer)),
+                GraphFormat::NTriples => TripleWriterKind::NTriples(writer),
+                GraphFormat::Turtle => TripleWriterKind::Turtle(TurtleFormatter::new(
+                    writer,
+                    self.turtle_options.clone(),
+                )?),
                 GraphFormat::RdfXml => TripleWriterKind::RdfXml(RdfXmlFormatter::new(writer)?),
             },
         })
     }
 }
 
+impl From<GraphFormat> for GraphSerializer {
+    fn from(format: GraphFormat) -> Self {
+        Self::from_format(format)
+    }
+}
+
 /// Allows writing triples.
 /// Could be built using a [`GraphSerializer`].
 ///
@@ -81,9 +264,9 @@ pub struct TripleWriter<W: Write> {
 }
 
 enum TripleWriterKind<W: Write> {
-    NTriples(NTriplesFormatter<W>),
-    Turtle(TurtleFormatter<W>),
-    RdfXml(RdfXmlFormatter<W>),
+    NTriples(CompressWriter<W>),
+    Turtle(TurtleFormatter<CompressWriter<W>>),
+    RdfXml(RdfXmlFormatter<CompressWriter<W>>),
 }
 
 impl<W: Write> TripleWriter<W> {
@@ -91,9 +274,22 @@ impl<W: Write> TripleWriter<W> {
     pub fn write<'a>(&mut self, triple: impl Into<TripleRef<'a>>) -> Result<(), io::Error> {
         let triple = triple.into();
         match &mut self.formatter {
-            TripleWriterKind::NTriples(formatter) => formatter.format(&triple.into())?,
-            TripleWriterKind::Turtle(formatter) => formatter.format(&triple.into())?,
-            TripleWriterKind::RdfXml(formatter) => formatter.format(&triple.into())?,
+            TripleWriterKind::NTriples(writer) => writeln!(writer, "{} .", triple)?,
+            TripleWriterKind::Turtle(formatter) => formatter.format(triple)?,
+            TripleWriterKind::RdfXml(formatter) => formatter.format(&rio::Triple {
+                subject: match triple.subject {
+                    SubjectRef::NamedNode(node) => rio::NamedNode::from(node).into(),
+                    SubjectRef::BlankNode(node) => rio::BlankNode::from(node).into(),
+                    SubjectRef::Triple(_) => return Err(quoted_triples_not_supported()),
+                },
+                predicate: triple.predicate.into(),
+                object: match triple.object {
+                    TermRef::NamedNode(node) => rio::NamedNode::from(node).into(),
+                    TermRef::BlankNode(node) => rio::BlankNode::from(node).into(),
+                    TermRef::Literal(literal) => rio::Literal::from(literal).into(),
+                    TermRef::Triple(_) => return Err(quoted_triples_not_supported()),
+                },
+            })?,
         }
         Ok(())
     }
@@ -101,11 +297,11 @@ impl<W: Write> TripleWriter<W> {
     /// Writes the last bytes of the file
     pub fn finish(self) -> Result<(), io::Error> {
         match self.formatter {
-            TripleWriterKind::NTriples(formatter) => formatter.finish(),
+            TripleWriterKind::NTriples(writer) => writer,
             TripleWriterKind::Turtle(formatter) => formatter.finish()?,
             TripleWriterKind::RdfXml(formatter) => formatter.finish()?,
-        };
-        Ok(())
+        }
+        .finish()
     }
 }
 
@@ -114,6 +310,18 @@ impl<W: Write> TripleWriter<W> {
 /// It currently supports the following formats:
 /// * [N-Quads](https://www.w3.org/TR/n-quads/) ([`DatasetFormat::NQuads`](super::DatasetFormat::NQuads))
 /// * [TriG](https://www.w3.org/TR/trig/) ([`DatasetFormat::TriG`](super::DatasetFormat::TriG))
+/// * [JSON-LD](https://www.w3.org/TR/json-ld11/) ([`DatasetFormat::JsonLd`](super::DatasetFormat::JsonLd))
+///
+/// The N-Quads and TriG serializers also support the [RDF-star](https://w3c.github.io/rdf-star/cg-spec/) quoted triples.
+/// JSON-LD does not allow them and returns an error if one is written.
+///
+/// The TriG serializer groups the consecutive quads sharing the same graph name, subject and predicate.
+/// Prefixes could be declared with [`with_prefix`](DatasetSerializer::with_prefix())
+/// and a more compact output is written if [`with_pretty_formatting`](DatasetSerializer::with_pretty_formatting()) is used.
+///
+/// The JSON-LD serializer keeps all the quads in memory until [`finish`](QuadWriter::finish()) is called.
+/// It writes the [expanded form](https://www.w3.org/TR/json-ld11/#expanded-document-form) by default
+/// and the [compacted form](https://www.w3.org/TR/json-ld11/#compacted-document-form) if a context is given using [`with_json_ld_context`](DatasetSerializer::with_json_ld_context()).
 ///
 /// ```
 /// use oxigraph::io::{DatasetFormat, DatasetSerializer};
@@ -132,28 +340,166 @@ impl<W: Write> TripleWriter<W> {
 ///assert_eq!(buffer.as_slice(), "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .\n".as_bytes());
 /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
 /// ```
-#[allow(missing_copy_implementations)]
+#[derive(Clone)]
 pub struct DatasetSerializer {
     format: DatasetFormat,
+    json_ld_context: Option<String>,
+    document_loader: Arc<dyn JsonLdDocumentLoader>,
+    turtle_options: TurtleOptions,
+    compression: Option<Compression>,
 }
 
 impl DatasetSerializer {
     /// Builds a serializer for the given format
     pub fn from_format(format: DatasetFormat) -> Self {
-        Self { format }
+        Self {
+            format,
+            json_ld_context: None,
+            document_loader: Arc::new(SimpleDocumentLoader::new()),
+            turtle_options: TurtleOptions::default(),
+            compression: None,
+        }
+    }
+
+    /// Declares a prefix used to abbreviate the IRIs in the TriG output.
+    ///
+    /// It is ignored by the other formats.
+    /// An [`InvalidInput`](std::io::ErrorKind::InvalidInput) error is returned if the prefix name or the IRI is not valid.
+    pub fn with_prefix(
+        mut self,
+        prefix_name: impl Into<String>,
+        prefix_iri: impl Into<String>,
+    ) -> Result<Self, io::Error> {
+        self.turtle_options
+            .add_prefix(prefix_name.into(), prefix_iri.into())?;
+        Ok(self)
+    }
+
+    /// Declares several prefixes at once, for example the ones read by a [`QuadReader`](super::read::QuadReader).
+    ///
+    /// See [`with_prefix`](DatasetSerializer::with_prefix()) for the details.
+    pub fn with_prefixes(
+        mut self,
+        prefixes: impl IntoIterator<Item = (impl Into<String>, impl Into<String>)>,
+    ) -> Result<Self, io::Error> {
+        for (prefix_name, prefix_iri) in prefixes {
+            self.turtle_options
+                .add_prefix(prefix_name.into(), prefix_iri.into())?;
+        }
+        Ok(self)
+    }
+
+    /// Writes a more compact and readable TriG output.
+    ///
+    /// It works like [`GraphSerializer::with_pretty_formatting`] with the triples also grouped by graph.
+    /// All the quads are kept in memory until [`finish`](QuadWriter::finish()) is called.
+    ///
+    /// It is ignored by the other formats.
+    ///
+    /// ```
+    /// use oxigraph::io::{DatasetFormat, DatasetSerializer};
+    /// use oxigraph::model::*;
+    /// use oxigraph::model::vocab::xsd;
+    ///
+    /// let ex = |name| NamedNode::new(format!("http://example.com/{}", name));
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut writer = DatasetSerializer::from_format(DatasetFormat::TriG)
+    ///     .with_prefix("ex", "http://example.com/")?
+    ///     .with_pretty_formatting()
+    ///     .quad_writer(&mut buffer)?;
+    /// writer.write(&Quad::new(ex("s")?, ex("p")?, ex("o")?, ex("g")?))?;
+    /// writer.write(&Quad::new(ex("s")?, ex("p")?, Literal::new_typed_literal("1.5", xsd::DECIMAL), GraphName::DefaultGraph))?;
+    /// writer.write(&Quad::new(ex("s")?, ex("q")?, ex("o")?, ex("g")?))?;
+    /// writer.finish()?;
+    ///
+    ///assert_eq!(
+    ///    std::str::from_utf8(&buffer)?,
+    ///    "@prefix ex: <http://example.com/> .\n\nex:g {\n\tex:s ex:p ex:o ;\n\t\tex:q ex:o .\n}\n\nex:s ex:p 1.5 .\n"
+    ///);
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn with_pretty_formatting(mut self) -> Self {
+        self.turtle_options.pretty = true;
+        self
+    }
+
+    /// Provides the [JSON-LD context](https://www.w3.org/TR/json-ld11/#the-context) used to compact the JSON-LD output
+    ///
+    /// It is the JSON serialization of the value of the `@context` key.
+    /// It is ignored by the other formats.
+    ///
+    /// ```
+    /// use oxigraph::io::{DatasetFormat, DatasetSerializer};
+    /// use oxigraph::model::*;
+    ///
+    /// let mut buffer = Vec::new();
+    /// let mut writer = DatasetSerializer::from_format(DatasetFormat::JsonLd)
+    ///     .with_json_ld_context(r#"{"ex": "http://example.com/"}"#)
+    ///     .quad_writer(&mut buffer)?;
+    /// writer.write(&Quad {
+    ///    subject: NamedNode::new("http://example.com/s")?.into(),
+    ///    predicate: NamedNode::new("http://example.com/p")?,
+    ///    object: NamedNode::new("http://example.com/o")?.into(),
+    ///    graph_name: GraphName::DefaultGraph,
+    /// })?;
+    /// writer.finish()?;
+    ///
+    ///assert_eq!(buffer.as_slice(), r#"{"@context":{"ex":"http://example.com/"},"@graph":[{"@id":"ex:s","ex:p":{"@id":"ex:o"}}]}"#.as_bytes());
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn with_json_ld_context(mut self, context: impl Into<String>) -> Self {
+        self.json_ld_context = Some(context.into());
+        self
+    }
+
+    /// Provides the loader used to fetch the remote contexts referenced by the JSON-LD context
+    ///
+    /// By default they are fetched using HTTP if the `http_client` feature is enabled.
+    pub fn with_document_loader(
+        mut self,
+        document_loader: impl JsonLdDocumentLoader + 'static,
+    ) -> Self {
+        self.document_loader = Arc::new(document_loader);
+        self
+    }
+
+    /// Compresses the output with the given format.
+    ///
+    /// An error is returned when the writer is created if the support of the compression format has not been enabled.
+    /// See [`GraphSerializer::with_compression`] for an example.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
     }
 
     /// Returns a `QuadWriter` allowing writing triples into the given [`Write`](std::io::Write) implementation
+    ///
+    /// Errors related to an invalid JSON-LD context use the [`InvalidInput`](std::io::ErrorKind::InvalidInput) error kind.
     pub fn quad_writer<W: Write>(&self, writer: W) -> Result<QuadWriter<W>, io::Error> {
+        let writer = CompressWriter::new(writer, self.compression)?;
         Ok(QuadWriter {
             formatter: match self.format {
-                DatasetFormat::NQuads => QuadWriterKind::NQuads(NQuadsFormatter::new(writer)),
-                DatasetFormat::TriG => QuadWriterKind::TriG(TriGFormatter::new(writer)),
+                DatasetFormat::NQuads => QuadWriterKind::NQuads(writer),
+                DatasetFormat::TriG => {
+                    QuadWriterKind::TriG(TriGFormatter::new(writer, self.turtle_options.clone())?)
+                }
+                DatasetFormat::JsonLd => QuadWriterKind::JsonLd(JsonLdFormatter::new(
+                    writer,
+                    self.json_ld_context.as_deref(),
+                    self.document_loader.as_ref(),
+                )?),
             },
         })
     }
 }
 
+impl From<DatasetFormat> for DatasetSerializer {
+    fn from(format: DatasetFormat) -> Self {
+        Self::from_format(format)
+    }
+}
+
 /// Allows writing triples.
 /// Could be built using a [`DatasetSerializer`].
 ///
@@ -182,8 +528,9 @@ pub struct QuadWriter<W: Write> {
 }
 
 enum QuadWriterKind<W: Write> {
-    NQuads(NQuadsFormatter<W>),
-    TriG(TriGFormatter<W>),
+    NQuads(CompressWriter<W>),
+    TriG(TriGFormatter<CompressWriter<W>>),
+    JsonLd(JsonLdFormatter<CompressWriter<W>>),
 }
 
 impl<W: Write> QuadWriter<W> {
@@ -191,8 +538,9 @@ impl<W: Write> QuadWriter<W> {
     pub fn write<'a>(&mut self, quad: impl Into<QuadRef<'a>>) -> Result<(), io::Error> {
         let quad = quad.into();
         match &mut self.formatter {
-            QuadWriterKind::NQuads(formatter) => formatter.format(&quad.into())?,
-            QuadWriterKind::TriG(formatter) => formatter.format(&quad.into())?,
+            QuadWriterKind::NQuads(writer) => writeln!(writer, "{} .", quad)?,
+            QuadWriterKind::TriG(formatter) => formatter.format(quad)?,
+            QuadWriterKind::JsonLd(formatter) => formatter.format(quad)?,
         }
         Ok(())
     }
@@ -200,9 +548,815 @@ impl<W: Write> QuadWriter<W> {
     /// Writes the last bytes of the file
     pub fn finish(self) -> Result<(), io::Error> {
         match self.formatter {
-            QuadWriterKind::NQuads(formatter) => formatter.finish(),
+            QuadWriterKind::NQuads(writer) => writer,
             QuadWriterKind::TriG(formatter) => formatter.finish()?,
+            QuadWriterKind::JsonLd(formatter) => formatter.finish()?,
+        }
+        .finish()
+    }
+}
+
+/// Writes [Turtle](https://www.w3.org/TR/turtle/) grouping the triples sharing the same subject and predicate
+struct TurtleFormatter<W: Write> {
+    write: W,
+    options: TurtleOptions,
+    current: Option<(Subject, NamedNode)>,
+    /// The triples kept until the end by the pretty formatting
+    buffer: Vec<Triple>,
+}
+
+impl<W: Write> TurtleFormatter<W> {
+    fn new(mut write: W, options: TurtleOptions) -> Result<Self, io::Error> {
+        options.write_prefixes(&mut write)?;
+        Ok(Self {
+            write,
+            options,
+            current: None,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn format(&mut self, triple: TripleRef<'_>) -> Result<(), io::Error> {
+        if self.options.pretty {
+            self.buffer.push(triple.into_owned());
+            return Ok(());
+        }
+        if let Some((current_subject, current_predicate)) = &self.current {
+            if current_subject.as_ref() == triple.subject {
+                if current_predicate.as_ref() == triple.predicate {
+                    write!(self.write, " , {}", self.options.term(triple.object))?;
+                } else {
+                    write!(
+                        self.write,
+                        " ;\n\t{} {}",
+                        self.options.predicate(triple.predicate),
+                        self.options.term(triple.object)
+                    )?;
+                }
+            } else {
+                write!(self.write, " .\n{}", self.options.triple(triple))?;
+            }
+        } else {
+            write!(self.write, "{}", self.options.triple(triple))?;
+        }
+        self.current = Some((triple.subject.into_owned(), triple.predicate.into_owned()));
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<W, io::Error> {
+        if self.options.pretty {
+            let triples = self.buffer.iter().map(Triple::as_ref).collect::<Vec<_>>();
+            write_pretty_triples(
+                &mut self.write,
+                &triples,
+                "",
+                &self.options,
+                &HashSet::default(),
+            )?;
+        } else if self.current.is_some() {
+            writeln!(self.write, " .")?;
+        }
+        self.write.flush()?;
+        Ok(self.write)
+    }
+}
+
+/// Writes [TriG](https://www.w3.org/TR/trig/) grouping the quads sharing the same graph name, subject and predicate
+struct TriGFormatter<W: Write> {
+    write: W,
+    options: TurtleOptions,
+    current: Option<(GraphName, Subject, NamedNode)>,
+    /// The quads kept until the end by the pretty formatting
+    buffer: Vec<Quad>,
+}
+
+impl<W: Write> TriGFormatter<W> {
+    fn new(mut write: W, options: TurtleOptions) -> Result<Self, io::Error> {
+        options.write_prefixes(&mut write)?;
+        Ok(Self {
+            write,
+            options,
+            current: None,
+            buffer: Vec::new(),
+        })
+    }
+
+    fn format(&mut self, quad: QuadRef<'_>) -> Result<(), io::Error> {
+        if self.options.pretty {
+            self.buffer.push(quad.into_owned());
+            return Ok(());
+        }
+        if let Some((current_graph_name, current_subject, current_predicate)) = &self.current {
+            if current_graph_name.as_ref() == quad.graph_name {
+                if current_subject.as_ref() == quad.subject {
+                    if current_predicate.as_ref() == quad.predicate {
+                        write!(self.write, " , {}", self.options.term(quad.object))?;
+                    } else {
+                        write!(
+                            self.write,
+                            " ;\n\t\t{} {}",
+                            self.options.predicate(quad.predicate),
+                            self.options.term(quad.object)
+                        )?;
+                    }
+                } else {
+                    writeln!(self.write, " .")?;
+                    self.write_start_of_triple(quad)?;
+                }
+            } else {
+                if current_graph_name.is_default_graph() {
+                    writeln!(self.write, " .")?;
+                } else {
+                    writeln!(self.write, " .\n}}")?;
+                }
+                self.write_start_of_graph(quad)?;
+            }
+        } else {
+            self.write_start_of_graph(quad)?;
+        }
+        self.current = Some((
+            quad.graph_name.into_owned(),
+            quad.subject.into_owned(),
+            quad.predicate.into_owned(),
+        ));
+        Ok(())
+    }
+
+    fn write_start_of_graph(&mut self, quad: QuadRef<'_>) -> Result<(), io::Error> {
+        if let Some(graph_name) = graph_name_term(quad.graph_name) {
+            writeln!(self.write, "{} {{", self.options.term(graph_name))?;
+        }
+        self.write_start_of_triple(quad)
+    }
+
+    fn write_start_of_triple(&mut self, quad: QuadRef<'_>) -> Result<(), io::Error> {
+        let triple = self.options.triple(TripleRef {
+            subject: quad.subject,
+            predicate: quad.predicate,
+            object: quad.object,
+        });
+        if quad.graph_name.is_default_graph() {
+            write!(self.write, "{}", triple)
+        } else {
+            write!(self.write, "\t{}", triple)
+        }
+    }
+
+    fn finish(mut self) -> Result<W, io::Error> {
+        if self.options.pretty {
+            self.write_pretty()?;
+        } else if let Some((current_graph_name, _, _)) = &self.current {
+            if current_graph_name.is_default_graph() {
+                writeln!(self.write, " .")?;
+            } else {
+                writeln!(self.write, " .\n}}")?;
+            }
+        }
+        self.write.flush()?;
+        Ok(self.write)
+    }
+
+    fn write_pretty(&mut self) -> Result<(), io::Error> {
+        let mut graph_names = Vec::new();
+        let mut triples_for_graph = HashMap::<_, Vec<_>>::new();
+        // The blank nodes used in multiple graphs, as graph names or in quoted triples are never written as collections
+        let mut excluded = HashSet::new();
+        let mut graph_for_blank_node = HashMap::new();
+        for quad in &self.buffer {
+            let quad = quad.as_ref();
+            if let GraphNameRef::BlankNode(node) = quad.graph_name {
+                excluded.insert(node);
+            }
+            let subject = match quad.subject {
+                SubjectRef::BlankNode(node) => Some(node),
+                SubjectRef::Triple(triple) => {
+                    add_blank_nodes(triple, &mut excluded);
+                    None
+                }
+                SubjectRef::NamedNode(_) => None,
+            };
+            let object = match quad.object {
+                TermRef::BlankNode(node) => Some(node),
+                TermRef::Triple(triple) => {
+                    add_blank_nodes(triple, &mut excluded);
+                    None
+                }
+                TermRef::NamedNode(_) | TermRef::Literal(_) => None,
+            };
+            for node in subject.into_iter().chain(object) {
+                if *graph_for_blank_node.entry(node).or_insert(quad.graph_name) != quad.graph_name {
+                    excluded.insert(node);
+                }
+            }
+            triples_for_graph
+                .entry(quad.graph_name)
+                .or_insert_with(|| {
+                    graph_names.push(quad.graph_name);
+                    Vec::new()
+                })
+                .push(TripleRef {
+                    subject: quad.subject,
+                    predicate: quad.predicate,
+                    object: quad.object,
+                });
+        }
+        for (i, graph_name) in graph_names.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(self.write)?;
+            }
+            let triples = &triples_for_graph[&graph_name];
+            if let Some(graph_name) = graph_name_term(graph_name) {
+                writeln!(self.write, "{} {{", self.options.term(graph_name))?;
+                write_pretty_triples(&mut self.write, triples, "\t", &self.options, &excluded)?;
+                writeln!(self.write, "}}")?;
+            } else {
+                write_pretty_triples(&mut self.write, triples, "", &self.options, &excluded)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The options of the Turtle and TriG serializers
+#[derive(Default, Clone)]
+struct TurtleOptions {
+    /// Pairs of prefix name and prefix IRI
+    prefixes: Vec<(String, String)>,
+    pretty: bool,
+}
+
+impl TurtleOptions {
+    fn add_prefix(&mut self, prefix_name: String, prefix_iri: String) -> Result<(), io::Error> {
+        if !is_valid_prefix_name(&prefix_name) {
+            return Err(invalid_input_error(format!(
+                "'{}' is not a valid prefix name",
+                prefix_name
+            )));
+        }
+        let prefix_iri = Iri::parse(prefix_iri)
+            .map_err(invalid_input_error)?
+            .into_inner();
+        if let Some((_, iri)) = self
+            .prefixes
+            .iter_mut()
+            .find(|(name, _)| *name == prefix_name)
+        {
+            *iri = prefix_iri;
+        } else {
+            self.prefixes.push((prefix_name, prefix_iri));
+        }
+        Ok(())
+    }
+
+    fn write_prefixes(&self, write: &mut impl Write) -> Result<(), io::Error> {
+        for (name, iri) in &self.prefixes {
+            writeln!(write, "@prefix {}: <{}> .", name, iri)?;
+        }
+        if !self.prefixes.is_empty() {
+            writeln!(write)?;
+        }
+        Ok(())
+    }
+
+    fn term<'a>(&'a self, term: impl Into<TermRef<'a>>) -> TurtleTerm<'a> {
+        TurtleTerm {
+            term: term.into(),
+            is_predicate: false,
+            options: self,
+        }
+    }
+
+    fn predicate<'a>(&'a self, predicate: NamedNodeRef<'a>) -> TurtleTerm<'a> {
+        TurtleTerm {
+            term: predicate.into(),
+            is_predicate: true,
+            options: self,
+        }
+    }
+
+    fn triple<'a>(&'a self, triple: TripleRef<'a>) -> TurtleTriple<'a> {
+        TurtleTriple {
+            triple,
+            options: self,
+        }
+    }
+
+    /// Returns the prefix name and the local name of the longest matching prefix
+    fn abbreviate<'a>(&'a self, iri: &'a str) -> Option<(&'a str, &'a str)> {
+        self.prefixes
+            .iter()
+            .filter_map(|(name, prefix)| {
+                let local = iri.strip_prefix(prefix.as_str())?;
+                if is_valid_local_name(local) {
+                    Some((name.as_str(), local, prefix.len()))
+                } else {
+                    None
+                }
+            })
+            .max_by_key(|(_, _, len)| *len)
+            .map(|(name, local, _)| (name, local))
+    }
+}
+
+/// Formats a term using the prefixes and the compact forms enabled by the [`TurtleOptions`]
+struct TurtleTerm<'a> {
+    term: TermRef<'a>,
+    is_predicate: bool,
+    options: &'a TurtleOptions,
+}
+
+impl fmt::Display for TurtleTerm<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.term {
+            TermRef::NamedNode(node) => {
+                if self.is_predicate && self.options.pretty && node == rdf::TYPE {
+                    f.write_str("a")
+                } else if let Some((name, local)) = self.options.abbreviate(node.as_str()) {
+                    write!(f, "{}:{}", name, local)
+                } else {
+                    node.fmt(f)
+                }
+            }
+            TermRef::BlankNode(node) => node.fmt(f),
+            TermRef::Literal(literal) => match literal.destruct() {
+                (value, Some(datatype), None) => {
+                    if self.options.pretty && is_compact_literal(value, datatype) {
+                        f.write_str(value)
+                    } else {
+                        write!(
+                            f,
+                            "{}^^{}",
+                            rio::Literal::Simple { value },
+                            self.options.term(datatype)
+                        )
+                    }
+                }
+                _ => literal.fmt(f),
+            },
+            TermRef::Triple(triple) => write!(f, "<< {} >>", self.options.triple(triple.as_ref())),
+        }
+    }
+}
+
+struct TurtleTriple<'a> {
+    triple: TripleRef<'a>,
+    options: &'a TurtleOptions,
+}
+
+impl fmt::Display for TurtleTriple<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {}",
+            self.options.term(self.triple.subject),
+            self.options.predicate(self.triple.predicate),
+            self.options.term(self.triple.object)
+        )
+    }
+}
+
+/// Writes the triples grouped by subject and the [RDF collections](https://www.w3.org/TR/turtle/#collections) using the `( )` syntax.
+///
+/// The blank nodes in `excluded` are never written as collections.
+fn write_pretty_triples<'a>(
+    write: &mut impl Write,
+    triples: &[TripleRef<'a>],
+    indent: &str,
+    options: &TurtleOptions,
+    excluded: &HashSet<BlankNodeRef<'a>>,
+) -> Result<(), io::Error> {
+    let mut subjects = Vec::new();
+    let mut predicates_for_subject =
+        HashMap::<SubjectRef<'a>, Vec<(NamedNodeRef<'a>, Vec<TermRef<'a>>)>>::new();
+    let mut object_references = HashMap::<BlankNodeRef<'a>, usize>::new();
+    let mut quoted = HashSet::new();
+    for triple in triples {
+        if let SubjectRef::Triple(t) = triple.subject {
+            add_blank_nodes(t, &mut quoted);
+        }
+        match triple.object {
+            TermRef::BlankNode(node) => *object_references.entry(node).or_default() += 1,
+            TermRef::Triple(t) => add_blank_nodes(t, &mut quoted),
+            TermRef::NamedNode(_) | TermRef::Literal(_) => (),
+        }
+        let predicates = predicates_for_subject
+            .entry(triple.subject)
+            .or_insert_with(|| {
+                subjects.push(triple.subject);
+                Vec::new()
+            });
+        if let Some((_, objects)) = predicates
+            .iter_mut()
+            .find(|(predicate, _)| *predicate == triple.predicate)
+        {
+            objects.push(triple.object);
+        } else {
+            predicates.push((triple.predicate, vec![triple.object]));
+        }
+    }
+
+    // The collection members are the blank nodes used once as object with only a rdf:first and a rdf:rest value
+    let mut members = HashMap::new();
+    for (subject, predicates) in &predicates_for_subject {
+        if let SubjectRef::BlankNode(node) = subject {
+            if object_references.get(node) == Some(&1)
+                && !quoted.contains(node)
+                && !excluded.contains(node)
+            {
+                if let Some(member) = collection_member(predicates) {
+                    members.insert(*node, member);
+                }
+            }
+        }
+    }
+    let rests = members
+        .values()
+        .filter_map(|(_, rest)| {
+            if let TermRef::BlankNode(rest) = rest {
+                Some(*rest)
+            } else {
+                None
+            }
+        })
+        .collect::<HashSet<_>>();
+    let mut collections = HashMap::new();
+    for head in members.keys() {
+        if rests.contains(head) {
+            continue;
+        }
+        let mut items = Vec::new();
+        let mut nodes = Vec::new();
+        let mut current = *head;
+        loop {
+            let (first, rest) = members[&current];
+            items.push(first);
+            nodes.push(current);
+            match rest {
+                TermRef::NamedNode(rest) if rest == rdf::NIL => {
+                    collections.insert(*head, (items, nodes));
+                    break;
+                }
+                TermRef::BlankNode(rest) if members.contains_key(&rest) => current = rest,
+                _ => break,
+            }
+        }
+    }
+
+    // We only keep the collections reachable from a written subject to not lose any triple
+    let inlined = loop {
+        let inlined = collections
+            .values()
+            .flat_map(|(_, nodes)| nodes.iter().copied())
+            .collect::<HashSet<_>>();
+        let mut stack = subjects
+            .iter()
+            .filter(
+                |subject| !matches!(subject, SubjectRef::BlankNode(node) if inlined.contains(node)),
+            )
+            .flat_map(|subject| &predicates_for_subject[subject])
+            .flat_map(|(_, objects)| objects.iter().copied())
+            .collect::<Vec<_>>();
+        let mut reachable = HashSet::new();
+        while let Some(term) = stack.pop() {
+            if let TermRef::BlankNode(node) = term {
+                if let Some((items, _)) = collections.get(&node) {
+                    if reachable.insert(node) {
+                        stack.extend(items.iter().copied());
+                    }
+                }
+            }
+        }
+        if reachable.len() == collections.len() {
+            break inlined;
+        }
+        collections.retain(|head, _| reachable.contains(head));
+    };
+
+    let mut is_first_subject = true;
+    for subject in subjects {
+        if let SubjectRef::BlankNode(node) = subject {
+            if inlined.contains(&node) {
+                continue;
+            }
+        }
+        if is_first_subject {
+            is_first_subject = false;
+        } else {
+            writeln!(write)?;
+        }
+        write!(write, "{}{}", indent, options.term(subject))?;
+        let mut predicates = predicates_for_subject[&subject].iter().collect::<Vec<_>>();
+        predicates.sort_by_key(|(predicate, _)| *predicate != rdf::TYPE);
+        for (i, (predicate, objects)) in predicates.into_iter().enumerate() {
+            if i == 0 {
+                write!(write, " {}", options.predicate(*predicate))?;
+            } else {
+                write!(write, " ;\n{}\t{}", indent, options.predicate(*predicate))?;
+            }
+            for (j, object) in objects.iter().enumerate() {
+                write!(write, "{}", if j == 0 { " " } else { " , " })?;
+                write_pretty_object(write, *object, options, &collections)?;
+            }
+        }
+        writeln!(write, " .")?;
+    }
+    Ok(())
+}
+
+fn write_pretty_object<'a>(
+    write: &mut impl Write,
+    object: TermRef<'a>,
+    options: &TurtleOptions,
+    collections: &HashMap<BlankNodeRef<'a>, (Vec<TermRef<'a>>, Vec<BlankNodeRef<'a>>)>,
+) -> Result<(), io::Error> {
+    match object {
+        TermRef::NamedNode(node) if node == rdf::NIL => write!(write, "()"),
+        TermRef::BlankNode(node) if collections.contains_key(&node) => {
+            write!(write, "(")?;
+            for item in &collections[&node].0 {
+                write!(write, " ")?;
+                write_pretty_object(write, *item, options, collections)?;
+            }
+            write!(write, " )")
+        }
+        _ => write!(write, "{}", options.term(object)),
+    }
+}
+
+/// Returns the rdf:first and rdf:rest values if they are the only values of the node
+fn collection_member<'a>(
+    predicates: &[(NamedNodeRef<'a>, Vec<TermRef<'a>>)],
+) -> Option<(TermRef<'a>, TermRef<'a>)> {
+    let mut first = None;
+    let mut rest = None;
+    for (predicate, objects) in predicates {
+        let object = match objects.as_slice() {
+            [object] => *object,
+            _ => return None,
         };
+        if *predicate == rdf::FIRST && first.is_none() {
+            first = Some(object);
+        } else if *predicate == rdf::REST && rest.is_none() {
+            rest = Some(object);
+        } else {
+            return None;
+        }
+    }
+    Some((first?, rest?))
+}
+
+fn add_blank_nodes<'a>(triple: &'a Triple, blank_nodes: &mut HashSet<BlankNodeRef<'a>>) {
+    match &triple.subject {
+        Subject::BlankNode(node) => {
+            blank_nodes.insert(node.as_ref());
+        }
+        Subject::Triple(triple) => add_blank_nodes(triple, blank_nodes),
+        Subject::NamedNode(_) => (),
+    }
+    match &triple.object {
+        Term::BlankNode(node) => {
+            blank_nodes.insert(node.as_ref());
+        }
+        Term::Triple(triple) => add_blank_nodes(triple, blank_nodes),
+        Term::NamedNode(_) | Term::Literal(_) => (),
+    }
+}
+
+fn graph_name_term(graph_name: GraphNameRef<'_>) -> Option<TermRef<'_>> {
+    match graph_name {
+        GraphNameRef::NamedNode(node) => Some(node.into()),
+        GraphNameRef::BlankNode(node) => Some(node.into()),
+        GraphNameRef::DefaultGraph => None,
+    }
+}
+
+/// Checks if the literal could be written without quotes and datatype
+fn is_compact_literal(value: &str, datatype: NamedNodeRef<'_>) -> bool {
+    if datatype == xsd::INTEGER {
+        is_digits(strip_sign(value))
+    } else if datatype == xsd::DECIMAL {
+        if let Some((before, after)) = split_at_char(strip_sign(value), '.') {
+            (before.is_empty() || is_digits(before)) && is_digits(after)
+        } else {
+            false
+        }
+    } else if datatype == xsd::DOUBLE {
+        if let Some((mantissa, exponent)) =
+            split_at_char(strip_sign(value), 'e').or_else(|| split_at_char(strip_sign(value), 'E'))
+        {
+            let mantissa = if let Some((before, after)) = split_at_char(mantissa, '.') {
+                (before.is_empty() || is_digits(before))
+                    && (after.is_empty() || is_digits(after))
+                    && !(before.is_empty() && after.is_empty())
+            } else {
+                is_digits(mantissa)
+            };
+            mantissa && is_digits(strip_sign(exponent))
+        } else {
+            false
+        }
+    } else if datatype == xsd::BOOLEAN {
+        value == "true" || value == "false"
+    } else {
+        false
+    }
+}
+
+fn strip_sign(value: &str) -> &str {
+    value
+        .strip_prefix(|c| c == '+' || c == '-')
+        .unwrap_or(value)
+}
+
+fn split_at_char(value: &str, c: char) -> Option<(&str, &str)> {
+    let i = value.find(c)?;
+    Some((&value[..i], &value[i + c.len_utf8()..]))
+}
+
+fn is_digits(value: &str) -> bool {
+    !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// A conservative subset of the Turtle `PN_PREFIX` production
+fn is_valid_prefix_name(name: &str) -> bool {
+    match name.chars().next() {
+        None => true,
+        Some(c) if c.is_ascii_alphabetic() => {
+            name.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+                && !name.ends_with('.')
+        }
+        Some(_) => false,
+    }
+}
+
+/// A conservative subset of the Turtle `PN_LOCAL` production
+fn is_valid_local_name(name: &str) -> bool {
+    match name.chars().next() {
+        None => true,
+        Some(c) if c.is_ascii_alphanumeric() || c == '_' => {
+            name.chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+                && !name.ends_with('.')
+        }
+        Some(_) => false,
+    }
+}
+
+fn quoted_triples_not_supported() -> io::Error {
+    invalid_input_error("RDF/XML does not support RDF-star quoted triples")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryStore;
+
+    fn round_trip_dataset(data: &str, serializer: DatasetSerializer) -> Result<(), io::Error> {
+        let store = MemoryStore::new();
+        store.load_dataset(data.as_bytes(), DatasetFormat::TriG, None)?;
+        let mut buffer = Vec::new();
+        store.dump_dataset(&mut buffer, serializer)?;
+        let new_store = MemoryStore::new();
+        new_store.load_dataset(buffer.as_slice(), DatasetFormat::TriG, None)?;
+        assert!(
+            store.is_isomorphic(&new_store),
+            "{}",
+            String::from_utf8_lossy(&buffer)
+        );
         Ok(())
     }
+
+    #[test]
+    fn test_pretty_turtle() -> Result<(), io::Error> {
+        let data = r#"@prefix ex: <http://example.com/> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+ex:s a ex:C ;
+    ex:list ( 1 ( 2.5 "b"@en ) ex:o ) ;
+    ex:empty () ;
+    ex:double 1.0E3 ;
+    ex:bad "1."^^<http://www.w3.org/2001/XMLSchema#decimal> , "a b"^^ex:dt ;
+    ex:bool false ;
+    ex:shared _:shared ;
+    ex:cycle _:cycle .
+ex:t ex:shared _:shared .
+_:shared rdf:first 1 ; rdf:rest rdf:nil .
+_:cycle rdf:first 1 ; rdf:rest _:cycle .
+_:self rdf:first _:self ; rdf:rest rdf:nil .
+<http://example.com/with/slash> ex:p "x\n\"y\"" ."#;
+
+        let store = MemoryStore::new();
+        store.load_graph(
+            data.as_bytes(),
+            GraphFormat::Turtle,
+            GraphNameRef::DefaultGraph,
+            None,
+        )?;
+        let mut buffer = Vec::new();
+        store.dump_graph(
+            &mut buffer,
+            GraphSerializer::from_format(GraphFormat::Turtle)
+                .with_prefix("ex", "http://example.com/")?
+                .with_pretty_formatting(),
+            GraphNameRef::DefaultGraph,
+        )?;
+        let output = String::from_utf8_lossy(&buffer);
+        assert!(output.contains("ex:s a ex:C ;"), "{}", output);
+        assert!(
+            output.contains("ex:list ( 1 ( 2.5 \"b\"@en ) ex:o )"),
+            "{}",
+            output
+        );
+        assert!(output.contains("ex:empty ()"), "{}", output);
+        // The store normalizes the numeric literals to lexical forms without a Turtle shorthand
+        assert!(
+            output.contains("ex:double \"1000\"^^<http://www.w3.org/2001/XMLSchema#double>"),
+            "{}",
+            output
+        );
+        assert!(output.contains("\"1\"^^<http://www.w3.org/2001/XMLSchema#decimal>"));
+        assert!(output.contains("\"a b\"^^ex:dt"), "{}", output);
+        assert!(output.contains("<http://example.com/with/slash> ex:p"));
+
+        let new_store = MemoryStore::new();
+        new_store.load_graph(
+            buffer.as_slice(),
+            GraphFormat::Turtle,
+            GraphNameRef::DefaultGraph,
+            None,
+        )?;
+        assert!(store.is_isomorphic(&new_store), "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretty_turtle_quoted_collection() -> Result<(), io::Error> {
+        let store = MemoryStore::new();
+        store.load_graph(
+            br#"@prefix ex: <http://example.com/> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+<< ex:s ex:p _:quoted >> ex:p _:quoted .
+_:quoted rdf:first 2 ; rdf:rest rdf:nil ."#
+                .as_ref(),
+            GraphFormat::Turtle,
+            GraphNameRef::DefaultGraph,
+            None,
+        )?;
+        let mut buffer = Vec::new();
+        store.dump_graph(
+            &mut buffer,
+            GraphSerializer::from_format(GraphFormat::Turtle)
+                .with_prefix("ex", "http://example.com/")?
+                .with_pretty_formatting(),
+            GraphNameRef::DefaultGraph,
+        )?;
+        let output = String::from_utf8_lossy(&buffer);
+        // The blank nodes used in quoted triples are never written as collections
+        assert!(!output.contains("( 2 )"), "{}", output);
+
+        let new_store = MemoryStore::new();
+        new_store.load_graph(
+            buffer.as_slice(),
+            GraphFormat::Turtle,
+            GraphNameRef::DefaultGraph,
+            None,
+        )?;
+        assert_eq!(new_store.len(), 3, "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretty_trig() -> Result<(), io::Error> {
+        let data = r#"@prefix ex: <http://example.com/> .
+@prefix rdf: <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+ex:s ex:p ( 1 2 ) .
+ex:g1 { ex:s ex:p _:l , ( "a" ) . _:l rdf:first 1 ; rdf:rest rdf:nil }
+ex:g2 { ex:t ex:p _:l }
+_:g { ex:s ex:p 1 }"#;
+        round_trip_dataset(
+            data,
+            DatasetSerializer::from_format(DatasetFormat::TriG)
+                .with_prefix("ex", "http://example.com/")?
+                .with_pretty_formatting(),
+        )?;
+        round_trip_dataset(
+            data,
+            DatasetSerializer::from_format(DatasetFormat::TriG)
+                .with_prefix("", "http://example.com/")?,
+        )
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        let serializer = GraphSerializer::from_format(GraphFormat::Turtle);
+        assert!(serializer
+            .clone()
+            .with_prefix("1ex", "http://example.com/")
+            .is_err());
+        assert!(serializer
+            .clone()
+            .with_prefix("ex.", "http://example.com/")
+            .is_err());
+        assert!(serializer.with_prefix("ex", "foo").is_err());
+    }
 }
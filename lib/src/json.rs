@@ -0,0 +1,630 @@
+//! A small streaming [JSON](https://www.json.org/) parser.
+
+use crate::error::invalid_data_error;
+use std::char;
+use std::io;
+use std::io::{BufRead, Write};
+
+const MAX_NESTING_DEPTH: usize = 512;
+
+/// An event returned by [`JsonReader::read_event`].
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub(crate) enum JsonEvent {
+    String(String),
+    Number(String),
+    Boolean(bool),
+    Null,
+    StartArray,
+    EndArray,
+    StartObject,
+    EndObject,
+    ObjectKey(String),
+    Eof,
+}
+
+#[derive(Eq, PartialEq, Clone, Copy)]
+enum Container {
+    Array,
+    Object,
+}
+
+#[derive(Eq, PartialEq, Clone, Copy)]
+enum Expect {
+    Value,
+    ArrayValueOrEnd,
+    ObjectKeyOrEnd,
+    ObjectKey,
+    CommaOrEnd,
+    Eof,
+}
+
+/// A streaming JSON parser returning a sequence of [`JsonEvent`]s.
+pub(crate) struct JsonReader<R: BufRead> {
+    reader: R,
+    stack: Vec<Container>,
+    expect: Expect,
+}
+
+impl<R: BufRead> JsonReader<R> {
+    pub fn from_reader(reader: R) -> Self {
+        Self {
+            reader,
+            stack: Vec::new(),
+            expect: Expect::Value,
+        }
+    }
+
+    pub fn read_event(&mut self) -> Result<JsonEvent, io::Error> {
+        loop {
+            let c = self.next_non_whitespace()?;
+            match self.expect {
+                Expect::Value => return self.read_value(c),
+                Expect::ArrayValueOrEnd => {
+                    return if c == Some(b']') {
+                        self.end_container(Container::Array)
+                    } else {
+                        self.read_value(c)
+                    }
+                }
+                Expect::ObjectKeyOrEnd => {
+                    return if c == Some(b'}') {
+                        self.end_container(Container::Object)
+                    } else {
+                        self.read_key(c)
+                    }
+                }
+                Expect::ObjectKey => return self.read_key(c),
+                Expect::CommaOrEnd => match c {
+                    Some(b',') => {
+                        self.expect = if self.stack.last() == Some(&Container::Array) {
+                            Expect::Value
+                        } else {
+                            Expect::ObjectKey
+                        };
+                    }
+                    Some(b']') => return self.end_container(Container::Array),
+                    Some(b'}') => return self.end_container(Container::Object),
+                    Some(c) => return Err(unexpected_char(c)),
+                    None => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+                },
+                Expect::Eof => {
+                    return if let Some(c) = c {
+                        Err(unexpected_char(c))
+                    } else {
+                        Ok(JsonEvent::Eof)
+                    }
+                }
+            }
+        }
+    }
+
+    fn read_value(&mut self, c: Option<u8>) -> Result<JsonEvent, io::Error> {
+        let c = c.ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        match c {
+            b'{' => {
+                self.stack.push(Container::Object);
+                self.expect = Expect::ObjectKeyOrEnd;
+                Ok(JsonEvent::StartObject)
+            }
+            b'[' => {
+                self.stack.push(Container::Array);
+                self.expect = Expect::ArrayValueOrEnd;
+                Ok(JsonEvent::StartArray)
+            }
+            b'"' => {
+                let value = self.read_string()?;
+                self.end_value();
+                Ok(JsonEvent::String(value))
+            }
+            b't' => {
+                self.read_keyword(b"rue")?;
+                self.end_value();
+                Ok(JsonEvent::Boolean(true))
+            }
+            b'f' => {
+                self.read_keyword(b"alse")?;
+                self.end_value();
+                Ok(JsonEvent::Boolean(false))
+            }
+            b'n' => {
+                self.read_keyword(b"ull")?;
+                self.end_value();
+                Ok(JsonEvent::Null)
+            }
+            b'-' | b'0'..=b'9' => {
+                let value = self.read_number(c)?;
+                self.end_value();
+                Ok(JsonEvent::Number(value))
+            }
+            c => Err(unexpected_char(c)),
+        }
+    }
+
+    fn read_key(&mut self, c: Option<u8>) -> Result<JsonEvent, io::Error> {
+        match c {
+            Some(b'"') => {
+                let key = self.read_string()?;
+                match self.next_non_whitespace()? {
+                    Some(b':') => {
+                        self.expect = Expect::Value;
+                        Ok(JsonEvent::ObjectKey(key))
+                    }
+                    Some(c) => Err(unexpected_char(c)),
+                    None => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+                }
+            }
+            Some(c) => Err(unexpected_char(c)),
+            None => Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+        }
+    }
+
+    fn end_container(&mut self, container: Container) -> Result<JsonEvent, io::Error> {
+        if self.stack.pop() != Some(container) {
+            return Err(invalid_data_error("Unbalanced JSON arrays or objects"));
+        }
+        self.end_value();
+        Ok(match container {
+            Container::Array => JsonEvent::EndArray,
+            Container::Object => JsonEvent::EndObject,
+        })
+    }
+
+    fn end_value(&mut self) {
+        self.expect = if self.stack.is_empty() {
+            Expect::Eof
+        } else {
+            Expect::CommaOrEnd
+        }
+    }
+
+    fn read_string(&mut self) -> Result<String, io::Error> {
+        let mut buffer = Vec::new();
+        loop {
+            match self.next_byte()? {
+                Some(b'"') => return String::from_utf8(buffer).map_err(invalid_data_error),
+                Some(b'\\') => {
+                    let c = match self.next_byte()? {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{C}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            let high = self.read_hex_code_unit()?;
+                            if (0xD800..0xDC00).contains(&high) {
+                                if self.next_byte()? != Some(b'\\')
+                                    || self.next_byte()? != Some(b'u')
+                                {
+                                    return Err(invalid_data_error(
+                                        "Unpaired UTF-16 surrogate in a JSON string",
+                                    ));
+                                }
+                                let low = self.read_hex_code_unit()?;
+                                if !(0xDC00..0xE000).contains(&low) {
+                                    return Err(invalid_data_error(
+                                        "Unpaired UTF-16 surrogate in a JSON string",
+                                    ));
+                                }
+                                char::from_u32(0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00))
+                            } else {
+                                char::from_u32(high)
+                            }
+                            .ok_or_else(|| {
+                                invalid_data_error("Invalid unicode escape in a JSON string")
+                            })?
+                        }
+                        Some(c) => return Err(unexpected_char(c)),
+                        None => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+                    };
+                    let mut encoded = [0; 4];
+                    buffer.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+                }
+                Some(c) if c < 0x20 => {
+                    return Err(invalid_data_error(
+                        "Control characters are not allowed in JSON strings",
+                    ))
+                }
+                Some(c) => buffer.push(c),
+                None => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            }
+        }
+    }
+
+    fn read_hex_code_unit(&mut self) -> Result<u32, io::Error> {
+        let mut value = 0;
+        for _ in 0..4 {
+            let c = self
+                .next_byte()?
+                .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+            value = value * 16
+                + match c {
+                    b'0'..=b'9' => u32::from(c - b'0'),
+                    b'a'..=b'f' => u32::from(c - b'a' + 10),
+                    b'A'..=b'F' => u32::from(c - b'A' + 10),
+                    c => return Err(unexpected_char(c)),
+                };
+        }
+        Ok(value)
+    }
+
+    fn read_number(&mut self, first: u8) -> Result<String, io::Error> {
+        let mut value = String::new();
+        value.push(char::from(first));
+        while let Some(c) = self.peek_byte()? {
+            if matches!(c, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E') {
+                value.push(char::from(c));
+                self.reader.consume(1);
+            } else {
+                break;
+            }
+        }
+        if is_valid_number(&value) {
+            Ok(value)
+        } else {
+            Err(invalid_data_error(format!(
+                "Invalid JSON number: {}",
+                value
+            )))
+        }
+    }
+
+    fn read_keyword(&mut self, expected: &[u8]) -> Result<(), io::Error> {
+        for e in expected {
+            match self.next_byte()? {
+                Some(c) if c == *e => (),
+                Some(c) => return Err(unexpected_char(c)),
+                None => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            }
+        }
+        Ok(())
+    }
+
+    fn next_non_whitespace(&mut self) -> Result<Option<u8>, io::Error> {
+        loop {
+            match self.next_byte()? {
+                Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') => (),
+                c => return Ok(c),
+            }
+        }
+    }
+
+    fn peek_byte(&mut self) -> Result<Option<u8>, io::Error> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+
+    fn next_byte(&mut self) -> Result<Option<u8>, io::Error> {
+        let c = self.peek_byte()?;
+        if c.is_some() {
+            self.reader.consume(1);
+        }
+        Ok(c)
+    }
+}
+
+/// A JSON document loaded in memory.
+///
+/// The object keys are kept in the document order.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum JsonValue {
+    String(String),
+    Number(String),
+    Boolean(bool),
+    Null,
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Reads a full JSON document.
+    pub fn read(reader: impl BufRead) -> Result<Self, io::Error> {
+        let mut reader = JsonReader::from_reader(reader);
+        let mut stack: Vec<(JsonValue, Option<String>)> = Vec::new();
+        let mut key = None;
+        loop {
+            let value = match reader.read_event()? {
+                JsonEvent::String(value) => JsonValue::String(value),
+                JsonEvent::Number(value) => JsonValue::Number(value),
+                JsonEvent::Boolean(value) => JsonValue::Boolean(value),
+                JsonEvent::Null => JsonValue::Null,
+                JsonEvent::StartArray | JsonEvent::StartObject if stack.len() >= MAX_NESTING_DEPTH => {
+                    return Err(invalid_data_error(format!(
+                        "JSON documents with more than {} nested arrays or objects are not supported",
+                        MAX_NESTING_DEPTH
+                    )))
+                }
+                JsonEvent::StartArray => {
+                    stack.push((JsonValue::Array(Vec::new()), key.take()));
+                    continue;
+                }
+                JsonEvent::StartObject => {
+                    stack.push((JsonValue::Object(Vec::new()), key.take()));
+                    continue;
+                }
+                JsonEvent::EndArray | JsonEvent::EndObject => {
+                    let (value, parent_key) = stack
+                        .pop()
+                        .ok_or_else(|| invalid_data_error("Unbalanced JSON arrays or objects"))?;
+                    key = parent_key;
+                    value
+                }
+                JsonEvent::ObjectKey(k) => {
+                    key = Some(k);
+                    continue;
+                }
+                JsonEvent::Eof => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            };
+            match stack.last_mut() {
+                Some((JsonValue::Array(values), _)) => values.push(value),
+                Some((JsonValue::Object(entries), _)) => entries.push((
+                    key.take()
+                        .ok_or_else(|| invalid_data_error("JSON object value without key"))?,
+                    value,
+                )),
+                Some(_) => unreachable!(),
+                None => {
+                    return if reader.read_event()? == JsonEvent::Eof {
+                        Ok(value)
+                    } else {
+                        Err(invalid_data_error(
+                            "Unexpected data after the end of the JSON document",
+                        ))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the value of an object entry
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        if let JsonValue::Object(entries) = self {
+            entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+        } else {
+            None
+        }
+    }
+
+    /// Writes the value without any whitespace
+    pub fn write(&self, sink: &mut impl Write) -> Result<(), io::Error> {
+        match self {
+            JsonValue::String(value) => write_escaped_json_string(value, sink),
+            JsonValue::Number(value) => sink.write_all(value.as_bytes()),
+            JsonValue::Boolean(value) => sink.write_all(if *value { b"true" } else { b"false" }),
+            JsonValue::Null => sink.write_all(b"null"),
+            JsonValue::Array(values) => {
+                sink.write_all(b"[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        sink.write_all(b",")?;
+                    }
+                    value.write(sink)?;
+                }
+                sink.write_all(b"]")
+            }
+            JsonValue::Object(entries) => {
+                sink.write_all(b"{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        sink.write_all(b",")?;
+                    }
+                    write_escaped_json_string(key, sink)?;
+                    sink.write_all(b":")?;
+                    value.write(sink)?;
+                }
+                sink.write_all(b"}")
+            }
+        }
+    }
+}
+
+pub(crate) fn write_escaped_json_string(s: &str, sink: &mut impl Write) -> Result<(), io::Error> {
+    sink.write_all(b"\"")?;
+    for c in s.chars() {
+        match c {
+            '\\' => sink.write_all(b"\\\\"),
+            '"' => sink.write_all(b"\\\""),
+            c => {
+                if c < char::from(32) {
+                    match c {
+                        '\u{08}' => sink.write_all(b"\\b"),
+                        '\u{0C}' => sink.write_all(b"\\f"),
+                        '\n' => sink.write_all(b"\\n"),
+                        '\r' => sink.write_all(b"\\r"),
+                        '\t' => sink.write_all(b"\\t"),
+                        c => {
+                            let mut c = c as u8;
+                            let mut result = [b'\\', b'u', 0, 0, 0, 0];
+                            for i in (2..6).rev() {
+                                let ch = c % 16;
+                                result[i] = if ch < 10 { b'0' + ch } else { b'A' + ch - 10 };
+                                c /= 16;
+                            }
+                            sink.write_all(&result)
+                        }
+                    }
+                } else {
+                    write!(sink, "{}", c)
+                }
+            }
+        }?;
+    }
+    sink.write_all(b"\"")
+}
+
+/// Checks that a number follows the JSON grammar `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+fn is_valid_number(value: &str) -> bool {
+    let mut value = value.strip_prefix('-').unwrap_or(value).as_bytes();
+    value = match value {
+        [b'0', rest @ ..] => rest,
+        [b'1'..=b'9', ..] => skip_digits(value),
+        _ => return false,
+    };
+    if let [b'.', rest @ ..] = value {
+        let after = skip_digits(rest);
+        if after.len() == rest.len() {
+            return false;
+        }
+        value = after;
+    }
+    if let [e, rest @ ..] = value {
+        if *e != b'e' && *e != b'E' {
+            return false;
+        }
+        let rest = match rest {
+            [sign, rest @ ..] if *sign == b'+' || *sign == b'-' => rest,
+            _ => rest,
+        };
+        let after = skip_digits(rest);
+        if after.len() == rest.len() {
+            return false;
+        }
+        value = after;
+    }
+    value.is_empty()
+}
+
+fn skip_digits(value: &[u8]) -> &[u8] {
+    let end = value
+        .iter()
+        .position(|c| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    &value[end..]
+}
+
+fn unexpected_char(c: u8) -> io::Error {
+    invalid_data_error(format!(
+        "Unexpected character '{}' in JSON",
+        char::from(c).escape_default()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events(data: &str) -> Result<Vec<JsonEvent>, io::Error> {
+        let mut reader = JsonReader::from_reader(data.as_bytes());
+        let mut events = Vec::new();
+        loop {
+            match reader.read_event()? {
+                JsonEvent::Eof => return Ok(events),
+                event => events.push(event),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_events() -> Result<(), io::Error> {
+        assert_eq!(
+            events(" {\"a\": [1, -2.5e3, true, null], \"b\\u00e9\\ud83d\\ude00\" : {} , \"c\":\"\\n\"} ")?,
+            vec![
+                JsonEvent::StartObject,
+                JsonEvent::ObjectKey("a".into()),
+                JsonEvent::StartArray,
+                JsonEvent::Number("1".into()),
+                JsonEvent::Number("-2.5e3".into()),
+                JsonEvent::Boolean(true),
+                JsonEvent::Null,
+                JsonEvent::EndArray,
+                JsonEvent::ObjectKey("b\u{e9}\u{1f600}".into()),
+                JsonEvent::StartObject,
+                JsonEvent::EndObject,
+                JsonEvent::ObjectKey("c".into()),
+                JsonEvent::String("\n".into()),
+                JsonEvent::EndObject,
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_numbers() -> Result<(), io::Error> {
+        for data in &["0", "-0", "10", "0.5", "-1.25", "1e5", "1E+2", "2.5e-3"] {
+            assert_eq!(events(data)?, vec![JsonEvent::Number((*data).into())]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_escaped_string_roundtrip() -> Result<(), io::Error> {
+        let value = (0..0x20_u8)
+            .map(char::from)
+            .chain("\\\"a\u{e9}".chars())
+            .collect::<String>();
+        let mut buffer = Vec::new();
+        write_escaped_json_string(&value, &mut buffer)?;
+        let serialization = String::from_utf8_lossy(&buffer);
+        assert!(serialization.contains("\\u000B"), "{}", serialization);
+        assert!(serialization.contains("\\u001F"), "{}", serialization);
+        assert_eq!(events(&serialization)?, vec![JsonEvent::String(value)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_documents() {
+        for data in &[
+            "{",
+            "[1,]",
+            "{\"a\" 1}",
+            "[1] 2",
+            "{\"a\":1]",
+            "tru",
+            "\"\\x\"",
+            "01",
+            "-00",
+            "[-01]",
+            "1.",
+            "-.5",
+            "1.e5",
+            "1e",
+            "1e+",
+            "--1",
+            "-",
+        ] {
+            assert!(events(data).is_err(), "{} should be invalid", data);
+        }
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        for data in &[
+            "]",
+            "}",
+            "[1}",
+            "{\"a\":1,}",
+            "{1:2}",
+            "{\"a\"}",
+            "[01]",
+            "",
+        ] {
+            assert!(
+                JsonValue::read(data.as_bytes()).is_err(),
+                "{} should be invalid",
+                data
+            );
+        }
+    }
+
+    #[test]
+    fn test_value_roundtrip() -> Result<(), io::Error> {
+        let data = "{\"a\":[1,-2.5e3,true,null,{}],\"b\\\"\\n\":\"c\\u0001\"}";
+        let value = JsonValue::read(data.as_bytes())?;
+        assert_eq!(
+            value.get("a"),
+            Some(&JsonValue::Array(vec![
+                JsonValue::Number("1".into()),
+                JsonValue::Number("-2.5e3".into()),
+                JsonValue::Boolean(true),
+                JsonValue::Null,
+                JsonValue::Object(Vec::new())
+            ]))
+        );
+        let mut buffer = Vec::new();
+        value.write(&mut buffer)?;
+        assert_eq!(String::from_utf8(buffer).unwrap(), data);
+        assert!(JsonValue::read("[1] [2]".as_bytes()).is_err());
+        Ok(())
+    }
+}
@@ -123,7 +123,9 @@
 
 mod error;
 pub mod io;
+mod json;
 pub mod model;
+pub mod shacl;
 pub mod sparql;
 pub mod store;
 
@@ -17,8 +17,8 @@ pub use crate::model::literal::{Literal, LiteralRef};
 pub use crate::model::named_node::{NamedNode, NamedNodeRef};
 pub use crate::model::parser::TermParseError;
 pub use crate::model::triple::{
-    GraphName, GraphNameRef, NamedOrBlankNode, NamedOrBlankNodeRef, Quad, QuadRef, Term, TermRef,
-    Triple, TripleRef,
+    GraphName, GraphNameRef, NamedOrBlankNode, NamedOrBlankNodeRef, Quad, QuadRef, Subject,
+    SubjectRef, Term, TermRef, Triple, TripleRef,
 };
 pub use oxilangtag::LanguageTagParseError;
 pub use oxiri::IriParseError;
@@ -1,7 +1,7 @@
 use crate::model::blank_node::{BlankNode, BlankNodeIdParseError};
 use crate::model::named_node::NamedNode;
 use crate::model::vocab::xsd;
-use crate::model::{Literal, Term};
+use crate::model::{GraphName, Literal, Quad, Subject, Term, Triple};
 use crate::sparql::{Variable, VariableNameParseError};
 use oxilangtag::LanguageTagParseError;
 use oxiri::IriParseError;
@@ -22,17 +22,13 @@ impl FromStr for NamedNode {
     /// assert_eq!(NamedNode::from_str("<http://example.com>").unwrap(), NamedNode::new("http://example.com").unwrap())
     /// ```
     fn from_str(s: &str) -> Result<Self, TermParseError> {
-        if !s.starts_with('<') || !s.ends_with('>') {
+        let (term, left) = read_named_node(s)?;
+        if !left.is_empty() {
             return Err(TermParseError::msg(
-                "Named node serialization should be enclosed between < and >",
+                "Named node serialization should end with a >",
             ));
         }
-        NamedNode::new(&s[1..s.len() - 1]).map_err(|error| TermParseError {
-            kind: TermParseErrorKind::Iri {
-                value: s.to_owned(),
-                error,
-            },
-        })
+        Ok(term)
     }
 }
 
@@ -48,17 +44,13 @@ impl FromStr for BlankNode {
     /// assert_eq!(BlankNode::from_str("_:ex").unwrap(), BlankNode::new("ex").unwrap())
     /// ```
     fn from_str(s: &str) -> Result<Self, TermParseError> {
-        if !s.starts_with("_:") {
+        let (term, left) = read_blank_node(s)?;
+        if !left.is_empty() {
             return Err(TermParseError::msg(
-                "Blank node serialization should start with '_:'",
+                "Blank node serialization should not contain whitespaces",
             ));
         }
-        BlankNode::new(&s[2..]).map_err(|error| TermParseError {
-            kind: TermParseErrorKind::BlankNode {
-                value: s.to_owned(),
-                error,
-            },
-        })
+        Ok(term)
     }
 }
 
@@ -80,128 +72,337 @@ impl FromStr for Literal {
     /// assert_eq!(Literal::from_str("-122e+1").unwrap(), Literal::new_typed_literal("-122e+1", xsd::DOUBLE));
     /// ```
     fn from_str(s: &str) -> Result<Self, TermParseError> {
-        if let Some(s) = s.strip_prefix('"') {
-            let mut value = String::with_capacity(s.len() - 1);
-            let mut chars = s.chars();
-            while let Some(c) = chars.next() {
-                match c {
-                    '"' => {
-                        let remain = chars.as_str();
-                        return if remain.is_empty() {
-                            Ok(Literal::new_simple_literal(value))
-                        } else if let Some(language) = remain.strip_prefix('@') {
-                            Literal::new_language_tagged_literal(value, &remain[1..]).map_err(
+        let (term, left) = read_literal(s)?;
+        if !left.is_empty() {
+            return Err(TermParseError::msg("Unexpected characters after a literal"));
+        }
+        Ok(term)
+    }
+}
+
+impl FromStr for Term {
+    type Err = TermParseError;
+
+    /// Parses a term from its NTriples or Turtle serialization
+    ///
+    /// ```
+    /// use oxigraph::model::{Literal, NamedNode, Term, Triple};
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(Term::from_str("\"ex\"").unwrap(), Literal::new_simple_literal("ex").into());
+    /// assert_eq!(
+    ///     Term::from_str("<< <http://example.com/s> <http://example.com/p> \"o\" >>").unwrap(),
+    ///     Triple::new(
+    ///         NamedNode::new("http://example.com/s").unwrap(),
+    ///         NamedNode::new("http://example.com/p").unwrap(),
+    ///         Literal::new_simple_literal("o")
+    ///     ).into()
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, TermParseError> {
+        let (term, left) = read_term(s, 0)?;
+        if !left.is_empty() {
+            return Err(TermParseError::msg("Invalid term serialization"));
+        }
+        Ok(term)
+    }
+}
+
+impl FromStr for Triple {
+    type Err = TermParseError;
+
+    /// Parses a triple from its N-Triples serialization, without the trailing dot.
+    ///
+    /// Quoted triples are supported using the [N-Triples-star](https://w3c.github.io/rdf-star/cg-spec/#n-triples-star) syntax.
+    ///
+    /// ```
+    /// use oxigraph::model::{Literal, NamedNode, Triple};
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     Triple::from_str("<http://example.com/s> <http://example.com/p> \"o\"").unwrap(),
+    ///     Triple::new(
+    ///         NamedNode::new("http://example.com/s").unwrap(),
+    ///         NamedNode::new("http://example.com/p").unwrap(),
+    ///         Literal::new_simple_literal("o")
+    ///     )
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, TermParseError> {
+        let (triple, left) = read_triple(s, 0)?;
+        if !left.trim_start().is_empty() {
+            return Err(TermParseError::msg("Unexpected characters after a triple"));
+        }
+        Ok(triple)
+    }
+}
+
+impl FromStr for Quad {
+    type Err = TermParseError;
+
+    /// Parses a quad from its N-Quads serialization, without the trailing dot.
+    ///
+    /// Quoted triples are supported using the [N-Triples-star](https://w3c.github.io/rdf-star/cg-spec/#n-triples-star) syntax.
+    ///
+    /// ```
+    /// use oxigraph::model::{Literal, NamedNode, Quad};
+    /// use std::str::FromStr;
+    ///
+    /// assert_eq!(
+    ///     Quad::from_str("<http://example.com/s> <http://example.com/p> \"o\" <http://example.com/g>").unwrap(),
+    ///     Quad::new(
+    ///         NamedNode::new("http://example.com/s").unwrap(),
+    ///         NamedNode::new("http://example.com/p").unwrap(),
+    ///         Literal::new_simple_literal("o"),
+    ///         NamedNode::new("http://example.com/g").unwrap()
+    ///     )
+    /// );
+    /// ```
+    fn from_str(s: &str) -> Result<Self, TermParseError> {
+        let (triple, left) = read_triple(s, 0)?;
+        let left = left.trim_start();
+        let (graph_name, left) = if left.is_empty() {
+            (GraphName::DefaultGraph, left)
+        } else if left.starts_with('<') {
+            let (graph_name, left) = read_named_node(left)?;
+            (graph_name.into(), left)
+        } else {
+            let (graph_name, left) = read_blank_node(left)?;
+            (graph_name.into(), left)
+        };
+        if !left.trim_start().is_empty() {
+            return Err(TermParseError::msg("Unexpected characters after a quad"));
+        }
+        Ok(triple.in_graph(graph_name))
+    }
+}
+
+const MAX_NUMBER_OF_NESTED_TRIPLES: usize = 128;
+
+fn read_named_node(s: &str) -> Result<(NamedNode, &str), TermParseError> {
+    if let Some(remain) = s.strip_prefix('<') {
+        let end = remain
+            .find('>')
+            .ok_or_else(|| TermParseError::msg("Named node serialization should end with a >"))?;
+        let (value, remain) = remain.split_at(end);
+        let term = NamedNode::new(value).map_err(|error| TermParseError {
+            kind: TermParseErrorKind::Iri {
+                value: value.to_owned(),
+                error,
+            },
+        })?;
+        Ok((term, &remain[1..]))
+    } else {
+        Err(TermParseError::msg(
+            "Named node serialization should start with a <",
+        ))
+    }
+}
+
+fn read_blank_node(s: &str) -> Result<(BlankNode, &str), TermParseError> {
+    if let Some(remain) = s.strip_prefix("_:") {
+        let end = remain
+            .find(|v: char| v.is_whitespace() || matches!(v, '<' | '>' | '"'))
+            .unwrap_or(remain.len());
+        let (value, remain) = remain.split_at(end);
+        let term = BlankNode::new(value).map_err(|error| TermParseError {
+            kind: TermParseErrorKind::BlankNode {
+                value: value.to_owned(),
+                error,
+            },
+        })?;
+        Ok((term, remain))
+    } else {
+        Err(TermParseError::msg(
+            "Blank node serialization should start with '_:'",
+        ))
+    }
+}
+
+fn read_literal(s: &str) -> Result<(Literal, &str), TermParseError> {
+    if let Some(s) = s.strip_prefix('"') {
+        let mut value = String::with_capacity(s.len());
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    let remain = chars.as_str();
+                    return if let Some(remain) = remain.strip_prefix('@') {
+                        let end = remain
+                            .find(|v: char| !v.is_ascii_alphanumeric() && v != '-')
+                            .unwrap_or(remain.len());
+                        let (language, remain) = remain.split_at(end);
+                        Ok((
+                            Literal::new_language_tagged_literal(value, language).map_err(
                                 |error| TermParseError {
                                     kind: TermParseErrorKind::LanguageTag {
                                         value: language.to_owned(),
                                         error,
                                     },
                                 },
-                            )
-                        } else if let Some(datatype) = remain.strip_prefix("^^") {
-                            Ok(Literal::new_typed_literal(
-                                value,
-                                NamedNode::from_str(datatype)?,
-                            ))
-                        } else {
-                            Err(TermParseError::msg("Unexpected characters after a literal"))
-                        };
-                    }
-                    '\\' => {
-                        if let Some(c) = chars.next() {
-                            value.push(match c {
-                                't' => '\t',
-                                'b' => '\u{8}',
-                                'n' => '\n',
-                                'r' => '\r',
-                                'f' => '\u{C}',
-                                '"' => '"',
-                                '\'' => '\'',
-                                '\\' => '\\',
-                                'u' => read_hexa_char(&mut chars, 4)?,
-                                'U' => read_hexa_char(&mut chars, 8)?,
-                                _ => return Err(TermParseError::msg("Unexpected escaped char")),
-                            })
-                        } else {
-                            return Err(TermParseError::msg("Unexpected literal end"));
-                        }
+                            )?,
+                            remain,
+                        ))
+                    } else if let Some(remain) = remain.strip_prefix("^^") {
+                        let (datatype, remain) = read_named_node(remain)?;
+                        Ok((Literal::new_typed_literal(value, datatype), remain))
+                    } else {
+                        Ok((Literal::new_simple_literal(value), remain))
+                    };
+                }
+                '\\' => {
+                    if let Some(c) = chars.next() {
+                        value.push(match c {
+                            't' => '\t',
+                            'b' => '\u{8}',
+                            'n' => '\n',
+                            'r' => '\r',
+                            'f' => '\u{C}',
+                            '"' => '"',
+                            '\'' => '\'',
+                            '\\' => '\\',
+                            'u' => read_hexa_char(&mut chars, 4)?,
+                            'U' => read_hexa_char(&mut chars, 8)?,
+                            _ => return Err(TermParseError::msg("Unexpected escaped char")),
+                        })
+                    } else {
+                        return Err(TermParseError::msg("Unexpected literal end"));
                     }
-                    c => value.push(c),
                 }
+                c => value.push(c),
             }
-            Err(TermParseError::msg("Unexpected literal end"))
-        } else if s == "true" {
-            Ok(Literal::new_typed_literal("true", xsd::BOOLEAN))
-        } else if s == "false" {
-            Ok(Literal::new_typed_literal("false", xsd::BOOLEAN))
-        } else {
-            let input = s.as_bytes();
-            if input.is_empty() {
-                return Err(TermParseError::msg("Empty term serialization"));
-            }
-
-            let mut cursor = match input.get(0) {
-                Some(b'+') | Some(b'-') => 1,
-                _ => 0,
-            };
-
-            let mut count_before: usize = 0;
-            while cursor < input.len() && b'0' <= input[cursor] && input[cursor] <= b'9' {
-                count_before += 1;
-                cursor += 1;
-            }
+        }
+        Err(TermParseError::msg("Unexpected literal end"))
+    } else {
+        let end = s
+            .find(|v: char| v.is_whitespace() || v == '>')
+            .unwrap_or(s.len());
+        let (value, remain) = s.split_at(end);
+        if value == "true" {
+            return Ok((Literal::new_typed_literal("true", xsd::BOOLEAN), remain));
+        } else if value == "false" {
+            return Ok((Literal::new_typed_literal("false", xsd::BOOLEAN), remain));
+        }
+        let input = value.as_bytes();
+        if input.is_empty() {
+            return Err(TermParseError::msg("Empty term serialization"));
+        }
 
-            if cursor == input.len() {
-                return if count_before > 0 {
-                    Ok(Literal::new_typed_literal(s, xsd::INTEGER))
-                } else {
-                    Err(TermParseError::msg("Empty integer serialization"))
-                };
-            }
+        let mut cursor = match input.get(0) {
+            Some(b'+') | Some(b'-') => 1,
+            _ => 0,
+        };
 
-            let mut count_after: usize = 0;
-            if input[cursor] == b'.' {
-                cursor += 1;
-                while cursor < input.len() && b'0' <= input[cursor] && input[cursor] <= b'9' {
-                    count_after += 1;
-                    cursor += 1;
-                }
-            }
+        let mut count_before: usize = 0;
+        while cursor < input.len() && b'0' <= input[cursor] && input[cursor] <= b'9' {
+            count_before += 1;
+            cursor += 1;
+        }
 
-            if cursor == input.len() {
-                return if count_after > 0 {
-                    Ok(Literal::new_typed_literal(s, xsd::DECIMAL))
-                } else {
-                    Err(TermParseError::msg(
-                        "Decimal serialization without floating part",
-                    ))
-                };
-            }
+        if cursor == input.len() {
+            return if count_before > 0 {
+                Ok((Literal::new_typed_literal(value, xsd::INTEGER), remain))
+            } else {
+                Err(TermParseError::msg("Empty integer serialization"))
+            };
+        }
 
-            if input[cursor] != b'e' && input[cursor] != b'E' {
-                return Err(TermParseError::msg("Double serialization without exponent"));
-            }
+        let mut count_after: usize = 0;
+        if input[cursor] == b'.' {
             cursor += 1;
-            cursor += match input.get(cursor) {
-                Some(b'+') | Some(b'-') => 1,
-                _ => 0,
-            };
-            let mut count_exponent = 0;
             while cursor < input.len() && b'0' <= input[cursor] && input[cursor] <= b'9' {
-                count_exponent += 1;
+                count_after += 1;
                 cursor += 1;
             }
-            if cursor == input.len() && count_exponent > 0 {
-                Ok(Literal::new_typed_literal(s, xsd::DOUBLE))
+        }
+
+        if cursor == input.len() {
+            return if count_after > 0 {
+                Ok((Literal::new_typed_literal(value, xsd::DECIMAL), remain))
             } else {
                 Err(TermParseError::msg(
-                    "Double serialization with an invalid exponent",
+                    "Decimal serialization without floating part",
                 ))
-            }
+            };
         }
+
+        if input[cursor] != b'e' && input[cursor] != b'E' {
+            return Err(TermParseError::msg("Double serialization without exponent"));
+        }
+        cursor += 1;
+        cursor += match input.get(cursor) {
+            Some(b'+') | Some(b'-') => 1,
+            _ => 0,
+        };
+        let mut count_exponent = 0;
+        while cursor < input.len() && b'0' <= input[cursor] && input[cursor] <= b'9' {
+            count_exponent += 1;
+            cursor += 1;
+        }
+        if cursor == input.len() && count_exponent > 0 {
+            Ok((Literal::new_typed_literal(value, xsd::DOUBLE), remain))
+        } else {
+            Err(TermParseError::msg(
+                "Double serialization with an invalid exponent",
+            ))
+        }
+    }
+}
+
+fn read_term(s: &str, number_of_recursive_calls: usize) -> Result<(Term, &str), TermParseError> {
+    if number_of_recursive_calls == MAX_NUMBER_OF_NESTED_TRIPLES {
+        return Err(TermParseError::msg(
+            "Too many nested triples. The parser fails here to avoid a stack overflow.",
+        ));
+    }
+    if let Some(remain) = s.strip_prefix("<<") {
+        let (triple, remain) = read_triple(remain, number_of_recursive_calls + 1)?;
+        let remain = remain.trim_start();
+        if let Some(remain) = remain.strip_prefix(">>") {
+            Ok((triple.into(), remain))
+        } else {
+            Err(TermParseError::msg(
+                "Quoted triple serialization should end with a >>",
+            ))
+        }
+    } else if s.starts_with('<') {
+        let (term, remain) = read_named_node(s)?;
+        Ok((term.into(), remain))
+    } else if s.starts_with('_') {
+        let (term, remain) = read_blank_node(s)?;
+        Ok((term.into(), remain))
+    } else {
+        let (term, remain) = read_literal(s)?;
+        Ok((term.into(), remain))
     }
 }
 
+fn read_triple(
+    s: &str,
+    number_of_recursive_calls: usize,
+) -> Result<(Triple, &str), TermParseError> {
+    let (subject, remain) = read_term(s.trim_start(), number_of_recursive_calls)?;
+    let (predicate, remain) = read_named_node(remain.trim_start())?;
+    let (object, remain) = read_term(remain.trim_start(), number_of_recursive_calls)?;
+    Ok((
+        Triple {
+            subject: match subject {
+                Term::NamedNode(s) => s.into(),
+                Term::BlankNode(s) => s.into(),
+                Term::Literal(_) => {
+                    return Err(TermParseError::msg(
+                        "Literals are not allowed in the subject position",
+                    ))
+                }
+                Term::Triple(s) => Subject::Triple(s),
+            },
+            predicate,
+            object,
+        },
+        remain,
+    ))
+}
+
 fn read_hexa_char(input: &mut Chars<'_>, len: usize) -> Result<char, TermParseError> {
     let mut value = 0;
     for _ in 0..len {
@@ -224,28 +425,6 @@ fn read_hexa_char(input: &mut Chars<'_>, len: usize) -> Result<char, TermParseEr
     char::from_u32(value).ok_or_else(|| TermParseError::msg("Invalid encoded unicode code point"))
 }
 
-impl FromStr for Term {
-    type Err = TermParseError;
-
-    /// Parses a term from its NTriples or Turtle serialization
-    ///
-    /// ```
-    /// use oxigraph::model::{Literal, Term};
-    /// use std::str::FromStr;
-    ///
-    /// assert_eq!(Term::from_str("\"ex\"").unwrap(), Literal::new_simple_literal("ex").into())
-    /// ```
-    fn from_str(s: &str) -> Result<Self, TermParseError> {
-        Ok(if s.starts_with('<') {
-            NamedNode::from_str(s)?.into()
-        } else if s.starts_with('_') {
-            BlankNode::from_str(s)?.into()
-        } else {
-            Literal::from_str(s)?.into()
-        })
-    }
-}
-
 impl FromStr for Variable {
     type Err = TermParseError;
 
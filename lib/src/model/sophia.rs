@@ -256,6 +256,79 @@ impl<'a> TTerm for NamedOrBlankNodeRef<'a> {
     }
 }
 
+impl TTerm for Subject {
+    fn kind(&self) -> TermKind {
+        use Subject::*;
+        match self {
+            NamedNode(_) => TermKind::Iri,
+            BlankNode(_) => TermKind::BlankNode,
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
+        }
+    }
+
+    fn value_raw(&self) -> RawValue<'_> {
+        use Subject::*;
+        match self {
+            NamedNode(n) => n.value_raw(),
+            BlankNode(n) => n.value_raw(),
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
+        }
+    }
+
+    fn as_dyn(&self) -> &dyn TTerm {
+        use Subject::*;
+        match self {
+            NamedNode(n) => n.as_dyn(),
+            BlankNode(n) => n.as_dyn(),
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
+        }
+    }
+}
+
+impl TryCopyTerm for Subject {
+    type Error = SophiaToOxigraphConversionError;
+
+    fn try_copy<T>(other: &T) -> Result<Self, Self::Error>
+    where
+        T: TTerm + ?Sized,
+    {
+        match other.kind() {
+            TermKind::Iri => Ok(NamedNode::try_copy(other).unwrap().into()),
+            TermKind::BlankNode => Ok(BlankNode::try_copy(other).unwrap().into()),
+            _ => Err(SophiaToOxigraphConversionError),
+        }
+    }
+}
+
+impl<'a> TTerm for SubjectRef<'a> {
+    fn kind(&self) -> TermKind {
+        use SubjectRef::*;
+        match self {
+            NamedNode(_) => TermKind::Iri,
+            BlankNode(_) => TermKind::BlankNode,
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
+        }
+    }
+
+    fn value_raw(&self) -> RawValue<'_> {
+        use SubjectRef::*;
+        match self {
+            NamedNode(n) => n.value_raw(),
+            BlankNode(n) => n.value_raw(),
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
+        }
+    }
+
+    fn as_dyn(&self) -> &dyn TTerm {
+        use SubjectRef::*;
+        match self {
+            NamedNode(n) => n.as_dyn(),
+            BlankNode(n) => n.as_dyn(),
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
+        }
+    }
+}
+
 impl TTerm for Term {
     fn kind(&self) -> TermKind {
         use Term::*;
@@ -263,6 +336,7 @@ impl TTerm for Term {
             NamedNode(_) => TermKind::Iri,
             BlankNode(_) => TermKind::BlankNode,
             Literal(_) => TermKind::Literal,
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
         }
     }
 
@@ -272,6 +346,7 @@ impl TTerm for Term {
             NamedNode(n) => n.value_raw(),
             BlankNode(n) => n.value_raw(),
             Literal(l) => l.value_raw(),
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
         }
     }
 
@@ -297,6 +372,7 @@ impl TTerm for Term {
             NamedNode(n) => n.as_dyn(),
             BlankNode(n) => n.as_dyn(),
             Literal(l) => l.as_dyn(),
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
         }
     }
 }
@@ -324,6 +400,7 @@ impl<'a> TTerm for TermRef<'a> {
             NamedNode(_) => TermKind::Iri,
             BlankNode(_) => TermKind::BlankNode,
             Literal(_) => TermKind::Literal,
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
         }
     }
 
@@ -333,6 +410,7 @@ impl<'a> TTerm for TermRef<'a> {
             NamedNode(n) => n.value_raw(),
             BlankNode(n) => n.value_raw(),
             Literal(l) => l.value_raw(),
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
         }
     }
 
@@ -358,6 +436,7 @@ impl<'a> TTerm for TermRef<'a> {
             NamedNode(n) => n.as_dyn(),
             BlankNode(n) => n.as_dyn(),
             Literal(l) => l.as_dyn(),
+            Triple(_) => panic!("RDF-star quoted triples are not supported by Sophia"),
         }
     }
 }
@@ -162,13 +162,236 @@ impl<'a> From<NamedOrBlankNodeRef<'a>> for rio::NamedOrBlankNode<'a> {
     }
 }
 
+/// The owned union of [IRIs](https://www.w3.org/TR/rdf11-concepts/#dfn-iri), [blank nodes](https://www.w3.org/TR/rdf11-concepts/#dfn-blank-node) and [quoted triples](https://w3c.github.io/rdf-star/cg-spec/#dfn-quoted).
+///
+/// It is the set of terms allowed in the [subject](https://www.w3.org/TR/rdf11-concepts/#dfn-subject) position of an [RDF-star](https://w3c.github.io/rdf-star/cg-spec/) triple.
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
+pub enum Subject {
+    NamedNode(NamedNode),
+    BlankNode(BlankNode),
+    Triple(Box<Triple>),
+}
+
+impl Subject {
+    #[inline]
+    pub fn is_named_node(&self) -> bool {
+        self.as_ref().is_named_node()
+    }
+
+    #[inline]
+    pub fn is_blank_node(&self) -> bool {
+        self.as_ref().is_blank_node()
+    }
+
+    #[inline]
+    pub fn is_triple(&self) -> bool {
+        self.as_ref().is_triple()
+    }
+
+    #[inline]
+    pub fn as_ref(&self) -> SubjectRef<'_> {
+        match self {
+            Self::NamedNode(node) => SubjectRef::NamedNode(node.as_ref()),
+            Self::BlankNode(node) => SubjectRef::BlankNode(node.as_ref()),
+            Self::Triple(triple) => SubjectRef::Triple(triple),
+        }
+    }
+}
+
+impl fmt::Display for Subject {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_ref().fmt(f)
+    }
+}
+
+impl From<NamedNode> for Subject {
+    #[inline]
+    fn from(node: NamedNode) -> Self {
+        Self::NamedNode(node)
+    }
+}
+
+impl From<NamedNodeRef<'_>> for Subject {
+    #[inline]
+    fn from(node: NamedNodeRef<'_>) -> Self {
+        node.into_owned().into()
+    }
+}
+
+impl From<BlankNode> for Subject {
+    #[inline]
+    fn from(node: BlankNode) -> Self {
+        Self::BlankNode(node)
+    }
+}
+
+impl From<BlankNodeRef<'_>> for Subject {
+    #[inline]
+    fn from(node: BlankNodeRef<'_>) -> Self {
+        node.into_owned().into()
+    }
+}
+
+impl From<Triple> for Subject {
+    #[inline]
+    fn from(triple: Triple) -> Self {
+        Self::Triple(Box::new(triple))
+    }
+}
+
+impl From<Box<Triple>> for Subject {
+    #[inline]
+    fn from(triple: Box<Triple>) -> Self {
+        Self::Triple(triple)
+    }
+}
+
+impl From<TripleRef<'_>> for Subject {
+    #[inline]
+    fn from(triple: TripleRef<'_>) -> Self {
+        triple.into_owned().into()
+    }
+}
+
+impl From<NamedOrBlankNode> for Subject {
+    #[inline]
+    fn from(node: NamedOrBlankNode) -> Self {
+        match node {
+            NamedOrBlankNode::NamedNode(node) => node.into(),
+            NamedOrBlankNode::BlankNode(node) => node.into(),
+        }
+    }
+}
+
+impl From<NamedOrBlankNodeRef<'_>> for Subject {
+    #[inline]
+    fn from(node: NamedOrBlankNodeRef<'_>) -> Self {
+        node.into_owned().into()
+    }
+}
+
+/// The borrowed union of [IRIs](https://www.w3.org/TR/rdf11-concepts/#dfn-iri), [blank nodes](https://www.w3.org/TR/rdf11-concepts/#dfn-blank-node) and [quoted triples](https://w3c.github.io/rdf-star/cg-spec/#dfn-quoted).
+///
+/// It is the set of terms allowed in the [subject](https://www.w3.org/TR/rdf11-concepts/#dfn-subject) position of an [RDF-star](https://w3c.github.io/rdf-star/cg-spec/) triple.
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+pub enum SubjectRef<'a> {
+    NamedNode(NamedNodeRef<'a>),
+    BlankNode(BlankNodeRef<'a>),
+    Triple(&'a Triple),
+}
+
+impl<'a> SubjectRef<'a> {
+    #[inline]
+    pub fn is_named_node(&self) -> bool {
+        matches!(self, Self::NamedNode(_))
+    }
+
+    #[inline]
+    pub fn is_blank_node(&self) -> bool {
+        matches!(self, Self::BlankNode(_))
+    }
+
+    #[inline]
+    pub fn is_triple(&self) -> bool {
+        matches!(self, Self::Triple(_))
+    }
+
+    #[inline]
+    pub fn into_owned(self) -> Subject {
+        match self {
+            Self::NamedNode(node) => Subject::NamedNode(node.into_owned()),
+            Self::BlankNode(node) => Subject::BlankNode(node.into_owned()),
+            Self::Triple(triple) => Subject::Triple(Box::new(triple.clone())),
+        }
+    }
+}
+
+impl fmt::Display for SubjectRef<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NamedNode(node) => node.fmt(f),
+            Self::BlankNode(node) => node.fmt(f),
+            Self::Triple(triple) => write!(f, "<< {} >>", triple),
+        }
+    }
+}
+
+impl<'a> From<NamedNodeRef<'a>> for SubjectRef<'a> {
+    #[inline]
+    fn from(node: NamedNodeRef<'a>) -> Self {
+        Self::NamedNode(node)
+    }
+}
+
+impl<'a> From<&'a NamedNode> for SubjectRef<'a> {
+    #[inline]
+    fn from(node: &'a NamedNode) -> Self {
+        node.as_ref().into()
+    }
+}
+
+impl<'a> From<BlankNodeRef<'a>> for SubjectRef<'a> {
+    #[inline]
+    fn from(node: BlankNodeRef<'a>) -> Self {
+        Self::BlankNode(node)
+    }
+}
+
+impl<'a> From<&'a BlankNode> for SubjectRef<'a> {
+    #[inline]
+    fn from(node: &'a BlankNode) -> Self {
+        node.as_ref().into()
+    }
+}
+
+impl<'a> From<&'a Triple> for SubjectRef<'a> {
+    #[inline]
+    fn from(triple: &'a Triple) -> Self {
+        Self::Triple(triple)
+    }
+}
+
+impl<'a> From<NamedOrBlankNodeRef<'a>> for SubjectRef<'a> {
+    #[inline]
+    fn from(node: NamedOrBlankNodeRef<'a>) -> Self {
+        match node {
+            NamedOrBlankNodeRef::NamedNode(node) => node.into(),
+            NamedOrBlankNodeRef::BlankNode(node) => node.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a NamedOrBlankNode> for SubjectRef<'a> {
+    #[inline]
+    fn from(node: &'a NamedOrBlankNode) -> Self {
+        node.as_ref().into()
+    }
+}
+
+impl<'a> From<&'a Subject> for SubjectRef<'a> {
+    #[inline]
+    fn from(node: &'a Subject) -> Self {
+        node.as_ref()
+    }
+}
+
+impl<'a> From<SubjectRef<'a>> for Subject {
+    #[inline]
+    fn from(node: SubjectRef<'a>) -> Self {
+        node.into_owned()
+    }
+}
+
 /// An owned RDF [term](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-term)
-/// It is the union of [IRIs](https://www.w3.org/TR/rdf11-concepts/#dfn-iri), [blank nodes](https://www.w3.org/TR/rdf11-concepts/#dfn-blank-node) and [literals](https://www.w3.org/TR/rdf11-concepts/#dfn-literal).
+/// It is the union of [IRIs](https://www.w3.org/TR/rdf11-concepts/#dfn-iri), [blank nodes](https://www.w3.org/TR/rdf11-concepts/#dfn-blank-node), [literals](https://www.w3.org/TR/rdf11-concepts/#dfn-literal) and [quoted triples](https://w3c.github.io/rdf-star/cg-spec/#dfn-quoted).
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
 pub enum Term {
     NamedNode(NamedNode),
     BlankNode(BlankNode),
     Literal(Literal),
+    Triple(Box<Triple>),
 }
 
 impl Term {
@@ -187,12 +410,18 @@ impl Term {
         self.as_ref().is_literal()
     }
 
+    #[inline]
+    pub fn is_triple(&self) -> bool {
+        self.as_ref().is_triple()
+    }
+
     #[inline]
     pub fn as_ref(&self) -> TermRef<'_> {
         match self {
             Self::NamedNode(node) => TermRef::NamedNode(node.as_ref()),
             Self::BlankNode(node) => TermRef::BlankNode(node.as_ref()),
             Self::Literal(literal) => TermRef::Literal(literal.as_ref()),
+            Self::Triple(triple) => TermRef::Triple(triple),
         }
     }
 }
@@ -263,13 +492,53 @@ impl From<NamedOrBlankNodeRef<'_>> for Term {
     }
 }
 
+impl From<Triple> for Term {
+    #[inline]
+    fn from(triple: Triple) -> Self {
+        Self::Triple(Box::new(triple))
+    }
+}
+
+impl From<Box<Triple>> for Term {
+    #[inline]
+    fn from(triple: Box<Triple>) -> Self {
+        Self::Triple(triple)
+    }
+}
+
+impl From<TripleRef<'_>> for Term {
+    #[inline]
+    fn from(triple: TripleRef<'_>) -> Self {
+        triple.into_owned().into()
+    }
+}
+
+impl From<Subject> for Term {
+    #[inline]
+    fn from(node: Subject) -> Self {
+        match node {
+            Subject::NamedNode(node) => node.into(),
+            Subject::BlankNode(node) => node.into(),
+            Subject::Triple(triple) => Self::Triple(triple),
+        }
+    }
+}
+
+impl From<SubjectRef<'_>> for Term {
+    #[inline]
+    fn from(node: SubjectRef<'_>) -> Self {
+        node.into_owned().into()
+    }
+}
+
 /// A borrowed RDF [term](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-term)
-/// It is the union of [IRIs](https://www.w3.org/TR/rdf11-concepts/#dfn-iri), [blank nodes](https://www.w3.org/TR/rdf11-concepts/#dfn-blank-node) and [literals](https://www.w3.org/TR/rdf11-concepts/#dfn-literal).
+/// It is the union of [IRIs](https://www.w3.org/TR/rdf11-concepts/#dfn-iri), [blank nodes](https://www.w3.org/TR/rdf11-concepts/#dfn-blank-node), [literals](https://www.w3.org/TR/rdf11-concepts/#dfn-literal) and [quoted triples](https://w3c.github.io/rdf-star/cg-spec/#dfn-quoted).
 #[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
 pub enum TermRef<'a> {
     NamedNode(NamedNodeRef<'a>),
     BlankNode(BlankNodeRef<'a>),
     Literal(LiteralRef<'a>),
+    Triple(&'a Triple),
 }
 
 impl<'a> TermRef<'a> {
@@ -288,12 +557,18 @@ impl<'a> TermRef<'a> {
         matches!(self, Self::Literal(_))
     }
 
+    #[inline]
+    pub fn is_triple(&self) -> bool {
+        matches!(self, Self::Triple(_))
+    }
+
     #[inline]
     pub fn into_owned(self) -> Term {
         match self {
             Self::NamedNode(node) => Term::NamedNode(node.into_owned()),
             Self::BlankNode(node) => Term::BlankNode(node.into_owned()),
             Self::Literal(literal) => Term::Literal(literal.into_owned()),
+            Self::Triple(triple) => Term::Triple(Box::new(triple.clone())),
         }
     }
 }
@@ -305,6 +580,7 @@ impl fmt::Display for TermRef<'_> {
             Self::NamedNode(node) => node.fmt(f),
             Self::BlankNode(node) => node.fmt(f),
             Self::Literal(node) => node.fmt(f),
+            Self::Triple(triple) => write!(f, "<< {} >>", triple),
         }
     }
 }
@@ -368,6 +644,31 @@ impl<'a> From<&'a NamedOrBlankNode> for TermRef<'a> {
     }
 }
 
+impl<'a> From<&'a Triple> for TermRef<'a> {
+    #[inline]
+    fn from(triple: &'a Triple) -> Self {
+        Self::Triple(triple)
+    }
+}
+
+impl<'a> From<SubjectRef<'a>> for TermRef<'a> {
+    #[inline]
+    fn from(node: SubjectRef<'a>) -> Self {
+        match node {
+            SubjectRef::NamedNode(node) => node.into(),
+            SubjectRef::BlankNode(node) => node.into(),
+            SubjectRef::Triple(triple) => triple.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a Subject> for TermRef<'a> {
+    #[inline]
+    fn from(node: &'a Subject) -> Self {
+        node.as_ref().into()
+    }
+}
+
 impl<'a> From<&'a Term> for TermRef<'a> {
     #[inline]
     fn from(node: &'a Term) -> Self {
@@ -382,22 +683,11 @@ impl<'a> From<TermRef<'a>> for Term {
     }
 }
 
-impl<'a> From<TermRef<'a>> for rio::Term<'a> {
-    #[inline]
-    fn from(node: TermRef<'a>) -> Self {
-        match node {
-            TermRef::NamedNode(node) => rio::NamedNode::from(node).into(),
-            TermRef::BlankNode(node) => rio::BlankNode::from(node).into(),
-            TermRef::Literal(node) => rio::Literal::from(node).into(),
-        }
-    }
-}
-
 /// An owned [RDF triple](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-triple)
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
 pub struct Triple {
     /// The [subject](https://www.w3.org/TR/rdf11-concepts/#dfn-subject) of this triple
-    pub subject: NamedOrBlankNode,
+    pub subject: Subject,
 
     /// The [predicate](https://www.w3.org/TR/rdf11-concepts/#dfn-predicate) of this triple
     pub predicate: NamedNode,
@@ -410,7 +700,7 @@ impl Triple {
     /// Builds an RDF [triple](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-triple)
     #[inline]
     pub fn new(
-        subject: impl Into<NamedOrBlankNode>,
+        subject: impl Into<Subject>,
         predicate: impl Into<NamedNode>,
         object: impl Into<Term>,
     ) -> Self {
@@ -453,7 +743,7 @@ impl fmt::Display for Triple {
 #[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
 pub struct TripleRef<'a> {
     /// The [subject](https://www.w3.org/TR/rdf11-concepts/#dfn-subject) of this triple
-    pub subject: NamedOrBlankNodeRef<'a>,
+    pub subject: SubjectRef<'a>,
 
     /// The [predicate](https://www.w3.org/TR/rdf11-concepts/#dfn-predicate) of this triple
     pub predicate: NamedNodeRef<'a>,
@@ -466,7 +756,7 @@ impl<'a> TripleRef<'a> {
     /// Builds an RDF [triple](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-triple)
     #[inline]
     pub fn new(
-        subject: impl Into<NamedOrBlankNodeRef<'a>>,
+        subject: impl Into<SubjectRef<'a>>,
         predicate: impl Into<NamedNodeRef<'a>>,
         object: impl Into<TermRef<'a>>,
     ) -> Self {
@@ -501,7 +791,7 @@ impl<'a> TripleRef<'a> {
 impl fmt::Display for TripleRef<'_> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        rio::Triple::from(*self).fmt(f)
+        write!(f, "{} {} {}", self.subject, self.predicate, self.object)
     }
 }
 
@@ -519,17 +809,6 @@ impl<'a> From<TripleRef<'a>> for Triple {
     }
 }
 
-impl<'a> From<TripleRef<'a>> for rio::Triple<'a> {
-    #[inline]
-    fn from(triple: TripleRef<'a>) -> Self {
-        rio::Triple {
-            subject: triple.subject.into(),
-            predicate: triple.predicate.into(),
-            object: triple.object.into(),
-        }
-    }
-}
-
 /// A possible owned graph name.
 /// It is the union of [IRIs](https://www.w3.org/TR/rdf11-concepts/#dfn-iri), [blank nodes](https://www.w3.org/TR/rdf11-concepts/#dfn-blank-node), and the [default graph name](https://www.w3.org/TR/rdf11-concepts/#dfn-default-graph).
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
@@ -781,7 +1060,7 @@ impl<'a> From<GraphNameRef<'a>> for Option<rio::NamedOrBlankNode<'a>> {
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
 pub struct Quad {
     /// The [subject](https://www.w3.org/TR/rdf11-concepts/#dfn-subject) of this triple
-    pub subject: NamedOrBlankNode,
+    pub subject: Subject,
 
     /// The [predicate](https://www.w3.org/TR/rdf11-concepts/#dfn-predicate) of this triple
     pub predicate: NamedNode,
@@ -797,7 +1076,7 @@ impl Quad {
     /// Builds an RDF [triple](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-triple) in a [RDF dataset](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset)
     #[inline]
     pub fn new(
-        subject: impl Into<NamedOrBlankNode>,
+        subject: impl Into<Subject>,
         predicate: impl Into<NamedNode>,
         object: impl Into<Term>,
         graph_name: impl Into<GraphName>,
@@ -843,7 +1122,7 @@ impl From<Quad> for Triple {
 #[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
 pub struct QuadRef<'a> {
     /// The [subject](https://www.w3.org/TR/rdf11-concepts/#dfn-subject) of this triple
-    pub subject: NamedOrBlankNodeRef<'a>,
+    pub subject: SubjectRef<'a>,
 
     /// The [predicate](https://www.w3.org/TR/rdf11-concepts/#dfn-predicate) of this triple
     pub predicate: NamedNodeRef<'a>,
@@ -859,7 +1138,7 @@ impl<'a> QuadRef<'a> {
     /// Builds an RDF [triple](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-triple) in a [RDF dataset](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset)
     #[inline]
     pub fn new(
-        subject: impl Into<NamedOrBlankNodeRef<'a>>,
+        subject: impl Into<SubjectRef<'a>>,
         predicate: impl Into<NamedNodeRef<'a>>,
         object: impl Into<TermRef<'a>>,
         graph_name: impl Into<GraphNameRef<'a>>,
@@ -886,7 +1165,15 @@ impl<'a> QuadRef<'a> {
 impl fmt::Display for QuadRef<'_> {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        rio::Quad::from(*self).fmt(f)
+        if self.graph_name.is_default_graph() {
+            write!(f, "{} {} {}", self.subject, self.predicate, self.object)
+        } else {
+            write!(
+                f,
+                "{} {} {} {}",
+                self.subject, self.predicate, self.object, self.graph_name
+            )
+        }
     }
 }
 
@@ -914,15 +1201,3 @@ impl<'a> From<QuadRef<'a>> for Quad {
         quad.into_owned()
     }
 }
-
-impl<'a> From<QuadRef<'a>> for rio::Quad<'a> {
-    #[inline]
-    fn from(quad: QuadRef<'a>) -> Self {
-        rio::Quad {
-            subject: quad.subject.into(),
-            predicate: quad.predicate.into(),
-            object: quad.object.into(),
-            graph_name: quad.graph_name.into(),
-        }
-    }
-}
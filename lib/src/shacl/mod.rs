@@ -0,0 +1,12 @@
+//! [SHACL](https://www.w3.org/TR/shacl/) validation.
+//!
+//! Stores validate their content against a [`ShapesGraph`]. See [`MemoryStore`](super::store::memory::MemoryStore::validate()) for an example.
+
+mod report;
+mod shapes;
+mod validator;
+pub mod vocab;
+
+pub use crate::shacl::report::{ValidationReport, ValidationResult};
+pub use crate::shacl::shapes::ShapesGraph;
+pub(crate) use crate::shacl::validator::validate;
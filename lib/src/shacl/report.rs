@@ -0,0 +1,246 @@
+use crate::io::{GraphFormat, GraphSerializer};
+use crate::model::vocab::rdf;
+use crate::model::*;
+use crate::shacl::vocab as sh;
+use crate::sparql::algebra::PropertyPathExpression;
+use std::io;
+use std::io::Write;
+
+/// A [SHACL validation report](https://www.w3.org/TR/shacl/#validation-report).
+///
+/// It is returned by the validation methods of the stores like [`MemoryStore::validate`](super::store::memory::MemoryStore::validate()).
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    results: Vec<ValidationResult>,
+}
+
+impl ValidationReport {
+    pub(crate) fn new(results: Vec<ValidationResult>) -> Self {
+        Self { results }
+    }
+
+    /// Returns if the data graph [conforms](https://www.w3.org/TR/shacl/#conforms) to the shapes graph
+    /// i.e. if no validation result has been produced.
+    pub fn conforms(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// The [validation results](https://www.w3.org/TR/shacl/#results-validation-result) of the report
+    pub fn results(&self) -> &[ValidationResult] {
+        &self.results
+    }
+
+    /// Returns the report encoded as RDF triples following the [SHACL validation report vocabulary](https://www.w3.org/TR/shacl/#validation-report).
+    pub fn triples(&self) -> Vec<Triple> {
+        let mut triples = Vec::new();
+        let report = BlankNode::default();
+        triples.push(Triple::new(
+            report.clone(),
+            rdf::TYPE,
+            sh::VALIDATION_REPORT,
+        ));
+        triples.push(Triple::new(
+            report.clone(),
+            sh::CONFORMS,
+            Literal::from(self.conforms()),
+        ));
+        for result in &self.results {
+            let node = BlankNode::default();
+            triples.push(Triple::new(report.clone(), sh::RESULT, node.clone()));
+            result.add_triples(&node, &mut triples);
+        }
+        triples
+    }
+
+    /// Writes the report into a file using the [SHACL validation report vocabulary](https://www.w3.org/TR/shacl/#validation-report).
+    pub fn write(&self, writer: impl Write, format: GraphFormat) -> Result<(), io::Error> {
+        let mut writer = GraphSerializer::from_format(format).triple_writer(writer)?;
+        for triple in self.triples() {
+            writer.write(&triple)?;
+        }
+        writer.finish()
+    }
+}
+
+/// A [SHACL validation result](https://www.w3.org/TR/shacl/#results-validation-result).
+#[derive(Debug, Clone)]
+pub struct ValidationResult {
+    pub(crate) focus_node: Term,
+    pub(crate) result_path: Option<PropertyPathExpression>,
+    pub(crate) value: Option<Term>,
+    pub(crate) source_shape: Term,
+    pub(crate) source_constraint_component: NamedNode,
+    pub(crate) source_constraint: Option<Term>,
+    pub(crate) severity: NamedNode,
+    pub(crate) messages: Vec<Literal>,
+}
+
+impl ValidationResult {
+    /// The [focus node](https://www.w3.org/TR/shacl/#results-focus-node) that has caused the result
+    pub fn focus_node(&self) -> &Term {
+        &self.focus_node
+    }
+
+    /// The [path](https://www.w3.org/TR/shacl/#results-path) of the property shape that has caused the result
+    pub fn result_path(&self) -> Option<&PropertyPathExpression> {
+        self.result_path.as_ref()
+    }
+
+    /// The [value](https://www.w3.org/TR/shacl/#results-value) that has caused the result
+    pub fn value(&self) -> Option<&Term> {
+        self.value.as_ref()
+    }
+
+    /// The [shape](https://www.w3.org/TR/shacl/#results-source-shape) the focus node has been validated against
+    pub fn source_shape(&self) -> &Term {
+        &self.source_shape
+    }
+
+    /// The [constraint component](https://www.w3.org/TR/shacl/#results-source-constraint-component) that has caused the result
+    pub fn source_constraint_component(&self) -> &NamedNode {
+        &self.source_constraint_component
+    }
+
+    /// The [SPARQL-based constraint](https://www.w3.org/TR/shacl/#results-source-constraint) that has caused the result
+    pub fn source_constraint(&self) -> Option<&Term> {
+        self.source_constraint.as_ref()
+    }
+
+    /// The [severity](https://www.w3.org/TR/shacl/#results-severity) of the result like `sh:Violation`
+    pub fn severity(&self) -> &NamedNode {
+        &self.severity
+    }
+
+    /// The [messages](https://www.w3.org/TR/shacl/#results-message) of the result
+    pub fn messages(&self) -> &[Literal] {
+        &self.messages
+    }
+
+    fn add_triples(&self, node: &BlankNode, triples: &mut Vec<Triple>) {
+        triples.push(Triple::new(node.clone(), rdf::TYPE, sh::VALIDATION_RESULT));
+        triples.push(Triple::new(
+            node.clone(),
+            sh::FOCUS_NODE,
+            self.focus_node.clone(),
+        ));
+        if let Some(path) = &self.result_path {
+            let path = path_to_rdf(path, triples);
+            triples.push(Triple::new(node.clone(), sh::RESULT_PATH, path));
+        }
+        if let Some(value) = &self.value {
+            triples.push(Triple::new(node.clone(), sh::VALUE, value.clone()));
+        }
+        triples.push(Triple::new(
+            node.clone(),
+            sh::SOURCE_SHAPE,
+            self.source_shape.clone(),
+        ));
+        triples.push(Triple::new(
+            node.clone(),
+            sh::SOURCE_CONSTRAINT_COMPONENT,
+            self.source_constraint_component.clone(),
+        ));
+        if let Some(constraint) = &self.source_constraint {
+            triples.push(Triple::new(
+                node.clone(),
+                sh::SOURCE_CONSTRAINT,
+                constraint.clone(),
+            ));
+        }
+        triples.push(Triple::new(
+            node.clone(),
+            sh::RESULT_SEVERITY,
+            self.severity.clone(),
+        ));
+        for message in &self.messages {
+            triples.push(Triple::new(
+                node.clone(),
+                sh::RESULT_MESSAGE,
+                message.clone(),
+            ));
+        }
+    }
+}
+
+/// Encodes a path using the [SHACL property paths](https://www.w3.org/TR/shacl/#property-paths) vocabulary
+fn path_to_rdf(path: &PropertyPathExpression, triples: &mut Vec<Triple>) -> Term {
+    match path {
+        PropertyPathExpression::NamedNode(p) => p.clone().into(),
+        PropertyPathExpression::Reverse(p) => {
+            path_node_to_rdf(sh::INVERSE_PATH, path_to_rdf(p, triples), triples)
+        }
+        PropertyPathExpression::Sequence(a, b) => {
+            let mut elements = Vec::new();
+            flatten_sequence(a, &mut elements);
+            flatten_sequence(b, &mut elements);
+            let elements = elements
+                .into_iter()
+                .map(|p| path_to_rdf(p, triples))
+                .collect::<Vec<_>>();
+            list_to_rdf(elements, triples)
+        }
+        PropertyPathExpression::Alternative(a, b) => {
+            let mut elements = Vec::new();
+            flatten_alternative(a, &mut elements);
+            flatten_alternative(b, &mut elements);
+            let elements = elements
+                .into_iter()
+                .map(|p| path_to_rdf(p, triples))
+                .collect::<Vec<_>>();
+            let list = list_to_rdf(elements, triples);
+            path_node_to_rdf(sh::ALTERNATIVE_PATH, list, triples)
+        }
+        PropertyPathExpression::ZeroOrMore(p) => {
+            path_node_to_rdf(sh::ZERO_OR_MORE_PATH, path_to_rdf(p, triples), triples)
+        }
+        PropertyPathExpression::OneOrMore(p) => {
+            path_node_to_rdf(sh::ONE_OR_MORE_PATH, path_to_rdf(p, triples), triples)
+        }
+        PropertyPathExpression::ZeroOrOne(p) => {
+            path_node_to_rdf(sh::ZERO_OR_ONE_PATH, path_to_rdf(p, triples), triples)
+        }
+        // Not expressible in SHACL and never built from a shapes graph
+        PropertyPathExpression::NegatedPropertySet(_) => BlankNode::default().into(),
+    }
+}
+
+fn path_node_to_rdf(predicate: NamedNodeRef<'_>, value: Term, triples: &mut Vec<Triple>) -> Term {
+    let node = BlankNode::default();
+    triples.push(Triple::new(node.clone(), predicate, value));
+    node.into()
+}
+
+fn list_to_rdf(elements: Vec<Term>, triples: &mut Vec<Triple>) -> Term {
+    let mut list: Term = rdf::NIL.into();
+    for element in elements.into_iter().rev() {
+        let node = BlankNode::default();
+        triples.push(Triple::new(node.clone(), rdf::FIRST, element));
+        triples.push(Triple::new(node.clone(), rdf::REST, list));
+        list = node.into();
+    }
+    list
+}
+
+fn flatten_sequence<'a>(
+    path: &'a PropertyPathExpression,
+    elements: &mut Vec<&'a PropertyPathExpression>,
+) {
+    if let PropertyPathExpression::Sequence(a, b) = path {
+        flatten_sequence(a, elements);
+        flatten_sequence(b, elements);
+    } else {
+        elements.push(path)
+    }
+}
+
+fn flatten_alternative<'a>(
+    path: &'a PropertyPathExpression,
+    elements: &mut Vec<&'a PropertyPathExpression>,
+) {
+    if let PropertyPathExpression::Alternative(a, b) = path {
+        flatten_alternative(a, elements);
+        flatten_alternative(b, elements);
+    } else {
+        elements.push(path)
+    }
+}
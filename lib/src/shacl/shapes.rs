@@ -0,0 +1,777 @@
+use crate::error::{invalid_data_error, invalid_input_error};
+use crate::io::{GraphFormat, GraphParser};
+use crate::model::vocab::{rdf, rdfs, xsd};
+use crate::model::*;
+use crate::shacl::vocab as sh;
+use crate::sparql::algebra::{PropertyPathExpression, SparqlPropertyPath};
+use crate::sparql::pattern::compile_pattern;
+use crate::sparql::Query;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::io::BufRead;
+
+/// A [SHACL shapes graph](https://www.w3.org/TR/shacl/#shapes-graph).
+///
+/// It is built from the triples of the graph describing the shapes and is used to validate the stores content.
+///
+/// ```
+/// use oxigraph::io::GraphFormat;
+/// use oxigraph::model::*;
+/// use oxigraph::shacl::ShapesGraph;
+/// use oxigraph::MemoryStore;
+///
+/// let file = b"@prefix sh: <http://www.w3.org/ns/shacl#> .
+/// <http://example.com/PersonShape> a sh:NodeShape ;
+///     sh:targetClass <http://example.com/Person> ;
+///     sh:property [ sh:path <http://example.com/name> ; sh:minCount 1 ] .";
+/// let shapes = ShapesGraph::parse(file.as_ref(), GraphFormat::Turtle, None)?;
+///
+/// let store = MemoryStore::new();
+/// let alice = NamedNodeRef::new("http://example.com/alice")?;
+/// let person = NamedNodeRef::new("http://example.com/Person")?;
+/// store.insert(QuadRef::new(alice, vocab::rdf::TYPE, person, None));
+/// assert!(!store.validate(&shapes)?.conforms());
+/// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ShapesGraph {
+    pub(crate) shapes: Vec<Shape>,
+}
+
+impl ShapesGraph {
+    /// Parses a shapes graph from a serialization.
+    pub fn parse(
+        reader: impl BufRead,
+        format: GraphFormat,
+        base_iri: Option<&str>,
+    ) -> Result<Self, io::Error> {
+        let mut parser = GraphParser::from_format(format);
+        if let Some(base_iri) = base_iri {
+            parser = parser
+                .with_base_iri(base_iri)
+                .map_err(invalid_input_error)?;
+        }
+        Self::from_triples(
+            parser
+                .read_triples(reader)?
+                .collect::<Result<Vec<_>, _>>()?,
+        )
+    }
+
+    /// Builds a shapes graph from its triples.
+    ///
+    /// An error is returned if the shapes are not [well-formed](https://www.w3.org/TR/shacl/#syntax-rules).
+    pub fn from_triples(triples: impl IntoIterator<Item = Triple>) -> Result<Self, io::Error> {
+        ShapesParser::new(triples).parse()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Shape {
+    pub node: Term,
+    /// The path of the property shapes
+    pub path: Option<PropertyPathExpression>,
+    pub targets: Vec<Target>,
+    pub constraints: Vec<Constraint>,
+    pub severity: NamedNode,
+    pub messages: Vec<Literal>,
+    pub deactivated: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Target {
+    Node(Term),
+    Class(Term),
+    SubjectsOf(NamedNode),
+    ObjectsOf(NamedNode),
+}
+
+/// The index of a shape in [`ShapesGraph`]
+pub(crate) type ShapeId = usize;
+
+#[derive(Debug, Clone)]
+pub(crate) enum Constraint {
+    Class(Term),
+    Datatype(NamedNode),
+    NodeKind(NodeKind),
+    MinCount(usize),
+    MaxCount(usize),
+    MinExclusive(Literal),
+    MinInclusive(Literal),
+    MaxExclusive(Literal),
+    MaxInclusive(Literal),
+    MinLength(usize),
+    MaxLength(usize),
+    Pattern(Regex),
+    LanguageIn(Vec<String>),
+    UniqueLang,
+    Equals(NamedNode),
+    Disjoint(NamedNode),
+    LessThan(NamedNode),
+    LessThanOrEquals(NamedNode),
+    Not(ShapeId),
+    And(Vec<ShapeId>),
+    Or(Vec<ShapeId>),
+    Xone(Vec<ShapeId>),
+    Node(ShapeId),
+    Property(ShapeId),
+    QualifiedValueShape {
+        shape: ShapeId,
+        min_count: Option<usize>,
+        max_count: Option<usize>,
+        /// The shapes the values should not conform to if sh:qualifiedValueShapesDisjoint is set
+        siblings: Vec<ShapeId>,
+    },
+    Closed {
+        ignored_properties: Vec<NamedNode>,
+    },
+    HasValue(Term),
+    In(Vec<Term>),
+    Sparql(Box<SparqlConstraint>),
+}
+
+impl Constraint {
+    pub fn component(&self) -> NamedNodeRef<'static> {
+        match self {
+            Constraint::Class(_) => sh::CLASS_CONSTRAINT_COMPONENT,
+            Constraint::Datatype(_) => sh::DATATYPE_CONSTRAINT_COMPONENT,
+            Constraint::NodeKind(_) => sh::NODE_KIND_CONSTRAINT_COMPONENT,
+            Constraint::MinCount(_) => sh::MIN_COUNT_CONSTRAINT_COMPONENT,
+            Constraint::MaxCount(_) => sh::MAX_COUNT_CONSTRAINT_COMPONENT,
+            Constraint::MinExclusive(_) => sh::MIN_EXCLUSIVE_CONSTRAINT_COMPONENT,
+            Constraint::MinInclusive(_) => sh::MIN_INCLUSIVE_CONSTRAINT_COMPONENT,
+            Constraint::MaxExclusive(_) => sh::MAX_EXCLUSIVE_CONSTRAINT_COMPONENT,
+            Constraint::MaxInclusive(_) => sh::MAX_INCLUSIVE_CONSTRAINT_COMPONENT,
+            Constraint::MinLength(_) => sh::MIN_LENGTH_CONSTRAINT_COMPONENT,
+            Constraint::MaxLength(_) => sh::MAX_LENGTH_CONSTRAINT_COMPONENT,
+            Constraint::Pattern(_) => sh::PATTERN_CONSTRAINT_COMPONENT,
+            Constraint::LanguageIn(_) => sh::LANGUAGE_IN_CONSTRAINT_COMPONENT,
+            Constraint::UniqueLang => sh::UNIQUE_LANG_CONSTRAINT_COMPONENT,
+            Constraint::Equals(_) => sh::EQUALS_CONSTRAINT_COMPONENT,
+            Constraint::Disjoint(_) => sh::DISJOINT_CONSTRAINT_COMPONENT,
+            Constraint::LessThan(_) => sh::LESS_THAN_CONSTRAINT_COMPONENT,
+            Constraint::LessThanOrEquals(_) => sh::LESS_THAN_OR_EQUALS_CONSTRAINT_COMPONENT,
+            Constraint::Not(_) => sh::NOT_CONSTRAINT_COMPONENT,
+            Constraint::And(_) => sh::AND_CONSTRAINT_COMPONENT,
+            Constraint::Or(_) => sh::OR_CONSTRAINT_COMPONENT,
+            Constraint::Xone(_) => sh::XONE_CONSTRAINT_COMPONENT,
+            Constraint::Node(_) => sh::NODE_CONSTRAINT_COMPONENT,
+            Constraint::Property(_) => sh::PROPERTY_CONSTRAINT_COMPONENT,
+            Constraint::QualifiedValueShape { .. } => sh::QUALIFIED_MIN_COUNT_CONSTRAINT_COMPONENT,
+            Constraint::Closed { .. } => sh::CLOSED_CONSTRAINT_COMPONENT,
+            Constraint::HasValue(_) => sh::HAS_VALUE_CONSTRAINT_COMPONENT,
+            Constraint::In(_) => sh::IN_CONSTRAINT_COMPONENT,
+            Constraint::Sparql(_) => sh::SPARQL_CONSTRAINT_COMPONENT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum NodeKind {
+    BlankNode,
+    Iri,
+    Literal,
+    BlankNodeOrIri,
+    BlankNodeOrLiteral,
+    IriOrLiteral,
+}
+
+impl NodeKind {
+    pub fn matches(self, term: &Term) -> bool {
+        match term {
+            Term::NamedNode(_) => matches!(
+                self,
+                NodeKind::Iri | NodeKind::BlankNodeOrIri | NodeKind::IriOrLiteral
+            ),
+            Term::BlankNode(_) => matches!(
+                self,
+                NodeKind::BlankNode | NodeKind::BlankNodeOrIri | NodeKind::BlankNodeOrLiteral
+            ),
+            Term::Literal(_) => matches!(
+                self,
+                NodeKind::Literal | NodeKind::BlankNodeOrLiteral | NodeKind::IriOrLiteral
+            ),
+            Term::Triple(_) => false,
+        }
+    }
+}
+
+/// A [SPARQL-based constraint](https://www.w3.org/TR/shacl/#sparql-constraints)
+#[derive(Debug, Clone)]
+pub(crate) struct SparqlConstraint {
+    pub node: Term,
+    pub query: Query,
+    pub messages: Vec<Literal>,
+}
+
+struct ShapesParser {
+    /// The triples indexed by subject
+    triples: HashMap<Term, Vec<(NamedNode, Term)>>,
+    shapes: Vec<Shape>,
+    ids: HashMap<Term, ShapeId>,
+}
+
+impl ShapesParser {
+    fn new(triples: impl IntoIterator<Item = Triple>) -> Self {
+        let mut index = HashMap::<_, Vec<_>>::new();
+        for triple in triples {
+            index
+                .entry(triple.subject.into())
+                .or_default()
+                .push((triple.predicate, triple.object));
+        }
+        Self {
+            triples: index,
+            shapes: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+
+    fn parse(mut self) -> Result<ShapesGraph, io::Error> {
+        let mut roots = self
+            .triples
+            .iter()
+            .filter(|(_, triples)| {
+                triples.iter().any(|(p, o)| {
+                    (*p == rdf::TYPE
+                        && (*o == sh::NODE_SHAPE.into() || *o == sh::PROPERTY_SHAPE.into()))
+                        || *p == sh::TARGET_NODE
+                        || *p == sh::TARGET_CLASS
+                        || *p == sh::TARGET_SUBJECTS_OF
+                        || *p == sh::TARGET_OBJECTS_OF
+                })
+            })
+            .map(|(s, _)| s.clone())
+            .collect::<Vec<_>>();
+        roots.sort_by_cached_key(|node| node.to_string()); // Deterministic order
+        for root in &roots {
+            self.parse_shape(root)?;
+        }
+        self.add_qualified_value_shapes_siblings();
+        Ok(ShapesGraph {
+            shapes: self.shapes,
+        })
+    }
+
+    fn parse_shape(&mut self, node: &Term) -> Result<ShapeId, io::Error> {
+        if let Some(id) = self.ids.get(node) {
+            return Ok(*id);
+        }
+        // We insert a placeholder first to allow recursive shapes
+        let id = self.shapes.len();
+        self.ids.insert(node.clone(), id);
+        self.shapes.push(Shape {
+            node: node.clone(),
+            path: None,
+            targets: Vec::new(),
+            constraints: Vec::new(),
+            severity: sh::VIOLATION.into(),
+            messages: Vec::new(),
+            deactivated: false,
+        });
+
+        let path = self
+            .object(node, sh::PATH)?
+            .map(|path| self.parse_path(&path))
+            .transpose()?;
+        let mut targets = Vec::new();
+        let mut constraints = Vec::new();
+        let mut severity = sh::VIOLATION.into();
+        let mut messages = Vec::new();
+        let mut deactivated = false;
+        let mut qualified_value_shape = None;
+        let mut qualified_min_count = None;
+        let mut qualified_max_count = None;
+        let mut qualified_value_shapes_disjoint = false;
+        let mut pattern = None;
+        let mut flags = None;
+        let mut closed = false;
+        let mut ignored_properties = Vec::new();
+        for (predicate, object) in self.triples.get(node).cloned().unwrap_or_default() {
+            let predicate = predicate.as_ref();
+            match predicate.as_str() {
+                "http://www.w3.org/1999/02/22-rdf-syntax-ns#type"
+                    if object == rdfs::CLASS.into() =>
+                {
+                    targets.push(Target::Class(node.clone()))
+                }
+                "http://www.w3.org/ns/shacl#targetNode" => targets.push(Target::Node(object)),
+                "http://www.w3.org/ns/shacl#targetClass" => targets.push(Target::Class(object)),
+                "http://www.w3.org/ns/shacl#targetSubjectsOf" => {
+                    targets.push(Target::SubjectsOf(to_named_node(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#targetObjectsOf" => {
+                    targets.push(Target::ObjectsOf(to_named_node(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#severity" => {
+                    severity = to_named_node(object, predicate)?
+                }
+                "http://www.w3.org/ns/shacl#message" => {
+                    messages.push(to_literal(object, predicate)?)
+                }
+                "http://www.w3.org/ns/shacl#deactivated" => {
+                    deactivated = to_boolean(&object, predicate)?
+                }
+                "http://www.w3.org/ns/shacl#class" => constraints.push(Constraint::Class(object)),
+                "http://www.w3.org/ns/shacl#datatype" => {
+                    constraints.push(Constraint::Datatype(to_named_node(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#nodeKind" => {
+                    constraints.push(Constraint::NodeKind(to_node_kind(&object)?))
+                }
+                "http://www.w3.org/ns/shacl#minCount" => {
+                    constraints.push(Constraint::MinCount(to_integer(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#maxCount" => {
+                    constraints.push(Constraint::MaxCount(to_integer(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#minExclusive" => {
+                    constraints.push(Constraint::MinExclusive(to_literal(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#minInclusive" => {
+                    constraints.push(Constraint::MinInclusive(to_literal(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#maxExclusive" => {
+                    constraints.push(Constraint::MaxExclusive(to_literal(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#maxInclusive" => {
+                    constraints.push(Constraint::MaxInclusive(to_literal(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#minLength" => {
+                    constraints.push(Constraint::MinLength(to_integer(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#maxLength" => {
+                    constraints.push(Constraint::MaxLength(to_integer(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#pattern" => {
+                    pattern = Some(to_literal(object, predicate)?)
+                }
+                "http://www.w3.org/ns/shacl#flags" => flags = Some(to_literal(object, predicate)?),
+                "http://www.w3.org/ns/shacl#languageIn" => {
+                    constraints.push(Constraint::LanguageIn(
+                        self.parse_list(&object)?
+                            .into_iter()
+                            .map(|tag| Ok(to_literal(tag, predicate)?.value().to_owned()))
+                            .collect::<Result<_, io::Error>>()?,
+                    ))
+                }
+                "http://www.w3.org/ns/shacl#uniqueLang" if to_boolean(&object, predicate)? => {
+                    constraints.push(Constraint::UniqueLang)
+                }
+                "http://www.w3.org/ns/shacl#equals" => {
+                    constraints.push(Constraint::Equals(to_named_node(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#disjoint" => {
+                    constraints.push(Constraint::Disjoint(to_named_node(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#lessThan" => {
+                    constraints.push(Constraint::LessThan(to_named_node(object, predicate)?))
+                }
+                "http://www.w3.org/ns/shacl#lessThanOrEquals" => constraints.push(
+                    Constraint::LessThanOrEquals(to_named_node(object, predicate)?),
+                ),
+                "http://www.w3.org/ns/shacl#not" => {
+                    constraints.push(Constraint::Not(self.parse_shape(&object)?))
+                }
+                "http://www.w3.org/ns/shacl#and" => {
+                    constraints.push(Constraint::And(self.parse_shape_list(&object)?))
+                }
+                "http://www.w3.org/ns/shacl#or" => {
+                    constraints.push(Constraint::Or(self.parse_shape_list(&object)?))
+                }
+                "http://www.w3.org/ns/shacl#xone" => {
+                    constraints.push(Constraint::Xone(self.parse_shape_list(&object)?))
+                }
+                "http://www.w3.org/ns/shacl#node" => {
+                    constraints.push(Constraint::Node(self.parse_shape(&object)?))
+                }
+                "http://www.w3.org/ns/shacl#property" => {
+                    let property = self.parse_shape(&object)?;
+                    if self.object(&object, sh::PATH)?.is_none() {
+                        return Err(invalid_data_error(format!(
+                            "The property shape {} should have a sh:path",
+                            object
+                        )));
+                    }
+                    constraints.push(Constraint::Property(property))
+                }
+                "http://www.w3.org/ns/shacl#qualifiedValueShape" => {
+                    qualified_value_shape = Some(self.parse_shape(&object)?)
+                }
+                "http://www.w3.org/ns/shacl#qualifiedMinCount" => {
+                    qualified_min_count = Some(to_integer(object, predicate)?)
+                }
+                "http://www.w3.org/ns/shacl#qualifiedMaxCount" => {
+                    qualified_max_count = Some(to_integer(object, predicate)?)
+                }
+                "http://www.w3.org/ns/shacl#qualifiedValueShapesDisjoint" => {
+                    qualified_value_shapes_disjoint = to_boolean(&object, predicate)?
+                }
+                "http://www.w3.org/ns/shacl#closed" => closed = to_boolean(&object, predicate)?,
+                "http://www.w3.org/ns/shacl#ignoredProperties" => {
+                    for property in self.parse_list(&object)? {
+                        ignored_properties.push(to_named_node(property, predicate)?)
+                    }
+                }
+                "http://www.w3.org/ns/shacl#hasValue" => {
+                    constraints.push(Constraint::HasValue(object))
+                }
+                "http://www.w3.org/ns/shacl#in" => {
+                    constraints.push(Constraint::In(self.parse_list(&object)?))
+                }
+                "http://www.w3.org/ns/shacl#sparql" => {
+                    if let Some(constraint) = self.parse_sparql_constraint(object, path.as_ref())? {
+                        constraints.push(Constraint::Sparql(Box::new(constraint)))
+                    }
+                }
+                _ => (),
+            }
+        }
+        if let Some(pattern) = pattern {
+            let flags = flags.as_ref().map(Literal::value);
+            constraints.push(Constraint::Pattern(
+                compile_pattern(pattern.value(), flags).ok_or_else(|| {
+                    invalid_data_error(format!("Invalid sh:pattern regular expression {}", pattern))
+                })?,
+            ));
+        }
+        if let Some(shape) = qualified_value_shape {
+            if qualified_min_count.is_some() || qualified_max_count.is_some() {
+                constraints.push(Constraint::QualifiedValueShape {
+                    shape,
+                    min_count: qualified_min_count,
+                    max_count: qualified_max_count,
+                    siblings: if qualified_value_shapes_disjoint {
+                        vec![shape] // Replaced by the actual siblings later
+                    } else {
+                        Vec::new()
+                    },
+                })
+            }
+        }
+        if closed {
+            constraints.push(Constraint::Closed { ignored_properties })
+        }
+
+        self.shapes[id] = Shape {
+            node: node.clone(),
+            path,
+            targets,
+            constraints,
+            severity,
+            messages,
+            deactivated,
+        };
+        Ok(id)
+    }
+
+    fn parse_shape_list(&mut self, list: &Term) -> Result<Vec<ShapeId>, io::Error> {
+        self.parse_list(list)?
+            .iter()
+            .map(|shape| self.parse_shape(shape))
+            .collect()
+    }
+
+    fn parse_path(&self, node: &Term) -> Result<PropertyPathExpression, io::Error> {
+        if let Term::NamedNode(node) = node {
+            return Ok(PropertyPathExpression::NamedNode(node.clone()));
+        }
+        if self.object(node, rdf::FIRST)?.is_some() {
+            let mut elements = self
+                .parse_list(node)?
+                .iter()
+                .map(|element| self.parse_path(element))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter();
+            let first = elements
+                .next()
+                .ok_or_else(|| invalid_data_error("A SHACL sequence path should not be empty"))?;
+            return Ok(elements.fold(first, |a, b| {
+                PropertyPathExpression::Sequence(Box::new(a), Box::new(b))
+            }));
+        }
+        if let Some(alternatives) = self.object(node, sh::ALTERNATIVE_PATH)? {
+            let mut elements = self
+                .parse_list(&alternatives)?
+                .iter()
+                .map(|element| self.parse_path(element))
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter();
+            let first = elements.next().ok_or_else(|| {
+                invalid_data_error("A SHACL alternative path should not be empty")
+            })?;
+            return Ok(elements.fold(first, |a, b| {
+                PropertyPathExpression::Alternative(Box::new(a), Box::new(b))
+            }));
+        }
+        if let Some(path) = self.object(node, sh::INVERSE_PATH)? {
+            return Ok(PropertyPathExpression::Reverse(Box::new(
+                self.parse_path(&path)?,
+            )));
+        }
+        if let Some(path) = self.object(node, sh::ZERO_OR_MORE_PATH)? {
+            return Ok(PropertyPathExpression::ZeroOrMore(Box::new(
+                self.parse_path(&path)?,
+            )));
+        }
+        if let Some(path) = self.object(node, sh::ONE_OR_MORE_PATH)? {
+            return Ok(PropertyPathExpression::OneOrMore(Box::new(
+                self.parse_path(&path)?,
+            )));
+        }
+        if let Some(path) = self.object(node, sh::ZERO_OR_ONE_PATH)? {
+            return Ok(PropertyPathExpression::ZeroOrOne(Box::new(
+                self.parse_path(&path)?,
+            )));
+        }
+        Err(invalid_data_error(format!(
+            "{} is not a valid SHACL path",
+            node
+        )))
+    }
+
+    fn parse_sparql_constraint(
+        &self,
+        node: Term,
+        path: Option<&PropertyPathExpression>,
+    ) -> Result<Option<SparqlConstraint>, io::Error> {
+        if let Some(deactivated) = self.object(&node, sh::DEACTIVATED)? {
+            if to_boolean(&deactivated, sh::DEACTIVATED)? {
+                return Ok(None);
+            }
+        }
+        let select = to_literal(
+            self.object(&node, sh::SELECT)?.ok_or_else(|| {
+                invalid_data_error(format!("The SPARQL constraint {} has no sh:select", node))
+            })?,
+            sh::SELECT,
+        )?;
+        let mut query = String::new();
+        for prefixes in self.objects(&node, sh::PREFIXES) {
+            for declaration in self.objects(prefixes, sh::DECLARE) {
+                let prefix = self.object(declaration, sh::PREFIX)?.ok_or_else(|| {
+                    invalid_data_error(format!(
+                        "The prefix declaration {} has no sh:prefix",
+                        declaration
+                    ))
+                })?;
+                let namespace = self.object(declaration, sh::NAMESPACE)?.ok_or_else(|| {
+                    invalid_data_error(format!(
+                        "The prefix declaration {} has no sh:namespace",
+                        declaration
+                    ))
+                })?;
+                query.push_str(&format!(
+                    "PREFIX {}: <{}>\n",
+                    to_literal(prefix, sh::PREFIX)?.value(),
+                    to_literal(namespace, sh::NAMESPACE)?.value()
+                ));
+            }
+        }
+        if let Some(path) = path {
+            query.push_str(
+                &select
+                    .value()
+                    .replace("$PATH", &SparqlPropertyPath(path).to_string()),
+            );
+        } else {
+            query.push_str(select.value());
+        }
+        let query = Query::parse(&query, None).map_err(|e| {
+            invalid_data_error(format!(
+                "Invalid query in SPARQL constraint {}: {}",
+                node, e
+            ))
+        })?;
+        if !matches!(query, Query::Select { .. }) {
+            return Err(invalid_data_error(format!(
+                "The query of the SPARQL constraint {} should be a SELECT query",
+                node
+            )));
+        }
+        Ok(Some(SparqlConstraint {
+            messages: self
+                .objects(&node, sh::MESSAGE)
+                .map(|message| to_literal(message.clone(), sh::MESSAGE))
+                .collect::<Result<_, _>>()?,
+            query,
+            node,
+        }))
+    }
+
+    /// Sets the siblings of the qualified value shapes with sh:qualifiedValueShapesDisjoint
+    fn add_qualified_value_shapes_siblings(&mut self) {
+        let mut siblings = HashMap::<ShapeId, HashSet<ShapeId>>::new();
+        for shape in &self.shapes {
+            let properties = shape
+                .constraints
+                .iter()
+                .filter_map(|constraint| {
+                    if let Constraint::Property(property) = constraint {
+                        Some(*property)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<_>>();
+            for property in &properties {
+                for sibling in &properties {
+                    if sibling == property {
+                        continue;
+                    }
+                    for constraint in &self.shapes[*sibling].constraints {
+                        if let Constraint::QualifiedValueShape { shape, .. } = constraint {
+                            siblings.entry(*property).or_default().insert(*shape);
+                        }
+                    }
+                }
+            }
+        }
+        for (property, property_siblings) in siblings {
+            for constraint in &mut self.shapes[property].constraints {
+                if let Constraint::QualifiedValueShape {
+                    siblings: constraint_siblings,
+                    ..
+                } = constraint
+                {
+                    if !constraint_siblings.is_empty() {
+                        *constraint_siblings = property_siblings.iter().copied().collect();
+                    }
+                }
+            }
+        }
+        // The shapes with sh:qualifiedValueShapesDisjoint without siblings
+        for shape in &mut self.shapes {
+            for constraint in &mut shape.constraints {
+                if let Constraint::QualifiedValueShape {
+                    shape: value_shape,
+                    siblings,
+                    ..
+                } = constraint
+                {
+                    siblings.retain(|sibling| sibling != value_shape);
+                }
+            }
+        }
+    }
+
+    fn objects<'a>(
+        &'a self,
+        subject: &Term,
+        predicate: NamedNodeRef<'a>,
+    ) -> impl Iterator<Item = &'a Term> + 'a {
+        self.triples
+            .get(subject)
+            .into_iter()
+            .flatten()
+            .filter(move |(p, _)| *p == predicate)
+            .map(|(_, o)| o)
+    }
+
+    fn object(
+        &self,
+        subject: &Term,
+        predicate: NamedNodeRef<'_>,
+    ) -> Result<Option<Term>, io::Error> {
+        let mut objects = self.objects(subject, predicate);
+        let object = objects.next().cloned();
+        if objects.next().is_some() {
+            return Err(invalid_data_error(format!(
+                "{} should have a single {} value",
+                subject, predicate
+            )));
+        }
+        Ok(object)
+    }
+
+    fn parse_list(&self, list: &Term) -> Result<Vec<Term>, io::Error> {
+        let mut elements = Vec::new();
+        let mut current = list.clone();
+        let mut visited = HashSet::new();
+        while current != rdf::NIL.into() {
+            if !visited.insert(current.clone()) {
+                return Err(invalid_data_error(format!("The list {} is cyclic", list)));
+            }
+            elements.push(
+                self.object(&current, rdf::FIRST)?
+                    .ok_or_else(|| invalid_data_error(format!("{} is not a valid list", list)))?,
+            );
+            current = self
+                .object(&current, rdf::REST)?
+                .ok_or_else(|| invalid_data_error(format!("{} is not a valid list", list)))?;
+        }
+        Ok(elements)
+    }
+}
+
+fn to_named_node(term: Term, predicate: NamedNodeRef<'_>) -> Result<NamedNode, io::Error> {
+    if let Term::NamedNode(node) = term {
+        Ok(node)
+    } else {
+        Err(invalid_data_error(format!(
+            "The value of {} should be an IRI, found {}",
+            predicate, term
+        )))
+    }
+}
+
+fn to_literal(term: Term, predicate: NamedNodeRef<'_>) -> Result<Literal, io::Error> {
+    if let Term::Literal(literal) = term {
+        Ok(literal)
+    } else {
+        Err(invalid_data_error(format!(
+            "The value of {} should be a literal, found {}",
+            predicate, term
+        )))
+    }
+}
+
+fn to_boolean(term: &Term, predicate: NamedNodeRef<'_>) -> Result<bool, io::Error> {
+    if let Term::Literal(literal) = term {
+        if literal.datatype() == xsd::BOOLEAN {
+            match literal.value() {
+                "true" | "1" => return Ok(true),
+                "false" | "0" => return Ok(false),
+                _ => (),
+            }
+        }
+    }
+    Err(invalid_data_error(format!(
+        "The value of {} should be a boolean, found {}",
+        predicate, term
+    )))
+}
+
+fn to_integer(term: Term, predicate: NamedNodeRef<'_>) -> Result<usize, io::Error> {
+    let literal = to_literal(term, predicate)?;
+    if literal.datatype() == xsd::INTEGER {
+        if let Ok(value) = literal.value().parse() {
+            return Ok(value);
+        }
+    }
+    Err(invalid_data_error(format!(
+        "The value of {} should be a non negative integer, found {}",
+        predicate, literal
+    )))
+}
+
+fn to_node_kind(term: &Term) -> Result<NodeKind, io::Error> {
+    if let Term::NamedNode(node) = term {
+        match node.as_str() {
+            "http://www.w3.org/ns/shacl#BlankNode" => return Ok(NodeKind::BlankNode),
+            "http://www.w3.org/ns/shacl#IRI" => return Ok(NodeKind::Iri),
+            "http://www.w3.org/ns/shacl#Literal" => return Ok(NodeKind::Literal),
+            "http://www.w3.org/ns/shacl#BlankNodeOrIRI" => return Ok(NodeKind::BlankNodeOrIri),
+            "http://www.w3.org/ns/shacl#BlankNodeOrLiteral" => {
+                return Ok(NodeKind::BlankNodeOrLiteral)
+            }
+            "http://www.w3.org/ns/shacl#IRIOrLiteral" => return Ok(NodeKind::IriOrLiteral),
+            _ => (),
+        }
+    }
+    Err(invalid_data_error(format!(
+        "The value of sh:nodeKind should be a node kind, found {}",
+        term
+    )))
+}
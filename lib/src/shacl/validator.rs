@@ -0,0 +1,727 @@
+use crate::model::vocab::{rdf, rdfs, xsd};
+use crate::model::*;
+use crate::shacl::report::{ValidationReport, ValidationResult};
+use crate::shacl::shapes::{Constraint, Shape, ShapeId, ShapesGraph, SparqlConstraint, Target};
+use crate::shacl::vocab as sh;
+use crate::sparql::algebra::{
+    GraphPattern, PropertyPathExpression, Query, QueryDataset, TriplePattern,
+};
+use crate::sparql::pattern::{PatternEvaluator, PreparedPattern};
+use crate::sparql::{EvaluationError, QueryOptions, QuerySolution, Variable};
+use crate::store::ReadableEncodedStore;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// Validates the content of the given graph of the store against a shapes graph.
+///
+/// If no graph is given, the union of all the store graphs is validated.
+pub(crate) fn validate<S: ReadableEncodedStore + 'static>(
+    store: S,
+    shapes: &ShapesGraph,
+    graph_name: Option<GraphNameRef<'_>>,
+    options: QueryOptions,
+) -> Result<ValidationReport, EvaluationError> {
+    let mut dataset = QueryDataset::default();
+    if let Some(graph_name) = graph_name {
+        dataset.set_default_graph(vec![graph_name.into()]);
+    } else {
+        dataset.set_default_graph_as_union();
+    }
+    let validator = Validator::new(store, &dataset, options, shapes)?;
+    let mut results = Vec::new();
+    for (id, shape) in shapes.shapes.iter().enumerate() {
+        if shape.deactivated || shape.targets.is_empty() {
+            continue;
+        }
+        for focus in validator.focus_nodes(shape)? {
+            validator.validate_shape(id, &focus, &mut results)?;
+        }
+    }
+    Ok(ValidationReport::new(results))
+}
+
+struct Validator<'a, S: ReadableEncodedStore + 'static> {
+    evaluator: PatternEvaluator<S>,
+    shapes: &'a ShapesGraph,
+    instance_of: PreparedPattern<S>,
+    /// The (shape, focus node) pairs currently validated, used to stop on recursive shapes
+    visiting: RefCell<HashSet<(ShapeId, Term)>>,
+    this: Variable,
+    value: Variable,
+    class: Variable,
+    predicate: Variable,
+}
+
+impl<'a, S: ReadableEncodedStore + 'static> Validator<'a, S> {
+    fn new(
+        store: S,
+        dataset: &QueryDataset,
+        options: QueryOptions,
+        shapes: &'a ShapesGraph,
+    ) -> Result<Self, EvaluationError> {
+        let evaluator = PatternEvaluator::new(store, dataset, options)?;
+        let this = Variable::new_unchecked("this");
+        let value = Variable::new_unchecked("value");
+        let class = Variable::new_unchecked("class");
+        let predicate = Variable::new_unchecked("predicate");
+        // ?value rdf:type/rdfs:subClassOf* ?class
+        let instance_of = evaluator.prepare(&GraphPattern::Path {
+            subject: value.clone().into(),
+            path: PropertyPathExpression::Sequence(
+                Box::new(PropertyPathExpression::NamedNode(rdf::TYPE.into())),
+                Box::new(PropertyPathExpression::ZeroOrMore(Box::new(
+                    PropertyPathExpression::NamedNode(rdfs::SUB_CLASS_OF.into()),
+                ))),
+            ),
+            object: class.clone().into(),
+        })?;
+        Ok(Self {
+            evaluator,
+            shapes,
+            instance_of,
+            visiting: RefCell::default(),
+            this,
+            value,
+            class,
+            predicate,
+        })
+    }
+
+    fn focus_nodes(&self, shape: &Shape) -> Result<Vec<Term>, EvaluationError> {
+        let mut nodes = Vec::new();
+        for target in &shape.targets {
+            match target {
+                Target::Node(node) => nodes.push(node.clone()),
+                Target::Class(class) => nodes.extend(self.values(
+                    &self.instance_of,
+                    &[(&self.class, class)],
+                    &self.value,
+                )?),
+                Target::SubjectsOf(predicate) => {
+                    let pattern =
+                        self.evaluator
+                            .prepare(&GraphPattern::BGP(vec![TriplePattern::new(
+                                self.value.clone(),
+                                predicate.clone(),
+                                Variable::new_unchecked("object"),
+                            )]))?;
+                    nodes.extend(self.values(&pattern, &[], &self.value)?)
+                }
+                Target::ObjectsOf(predicate) => {
+                    let pattern =
+                        self.evaluator
+                            .prepare(&GraphPattern::BGP(vec![TriplePattern::new(
+                                Variable::new_unchecked("subject"),
+                                predicate.clone(),
+                                self.value.clone(),
+                            )]))?;
+                    nodes.extend(self.values(&pattern, &[], &self.value)?)
+                }
+            }
+        }
+        Ok(deduplicate(nodes))
+    }
+
+    /// Returns the [value nodes](https://www.w3.org/TR/shacl/#value-nodes) of the focus node for the given shape
+    fn value_nodes(&self, shape: &Shape, focus: &Term) -> Result<Vec<Term>, EvaluationError> {
+        if let Some(path) = &shape.path {
+            self.path_values(path.clone(), focus)
+        } else {
+            Ok(vec![focus.clone()])
+        }
+    }
+
+    fn path_values(
+        &self,
+        path: PropertyPathExpression,
+        focus: &Term,
+    ) -> Result<Vec<Term>, EvaluationError> {
+        let pattern = self.evaluator.prepare(&GraphPattern::Path {
+            subject: self.this.clone().into(),
+            path,
+            object: self.value.clone().into(),
+        })?;
+        Ok(deduplicate(self.values(
+            &pattern,
+            &[(&self.this, focus)],
+            &self.value,
+        )?))
+    }
+
+    fn values(
+        &self,
+        pattern: &PreparedPattern<S>,
+        bindings: &[(&Variable, &Term)],
+        variable: &Variable,
+    ) -> Result<Vec<Term>, EvaluationError> {
+        let mut values = Vec::new();
+        for solution in self.evaluator.evaluate(pattern, bindings)? {
+            if let Some(value) = solution?.get(variable) {
+                values.push(value.clone());
+            }
+        }
+        Ok(values)
+    }
+
+    fn is_instance_of(&self, node: &Term, class: &Term) -> Result<bool, EvaluationError> {
+        if node.is_literal() {
+            return Ok(false);
+        }
+        Ok(self
+            .evaluator
+            .evaluate(
+                &self.instance_of,
+                &[(&self.value, node), (&self.class, class)],
+            )?
+            .next()
+            .transpose()?
+            .is_some())
+    }
+
+    fn conforms(&self, shape: ShapeId, focus: &Term) -> Result<bool, EvaluationError> {
+        let mut results = Vec::new();
+        self.validate_shape(shape, focus, &mut results)?;
+        Ok(results.is_empty())
+    }
+
+    fn validate_shape(
+        &self,
+        id: ShapeId,
+        focus: &Term,
+        results: &mut Vec<ValidationResult>,
+    ) -> Result<(), EvaluationError> {
+        let shape = &self.shapes.shapes[id];
+        if shape.deactivated {
+            return Ok(());
+        }
+        // On recursive shapes, we consider the focus node valid for the nested occurrences
+        if !self.visiting.borrow_mut().insert((id, focus.clone())) {
+            return Ok(());
+        }
+        let result = self.validate_constraints(shape, focus, results);
+        self.visiting.borrow_mut().remove(&(id, focus.clone()));
+        result
+    }
+
+    fn validate_constraints(
+        &self,
+        shape: &Shape,
+        focus: &Term,
+        results: &mut Vec<ValidationResult>,
+    ) -> Result<(), EvaluationError> {
+        let values = self.value_nodes(shape, focus)?;
+        for constraint in &shape.constraints {
+            match constraint {
+                Constraint::Class(class) => {
+                    for value in &values {
+                        if !self.is_instance_of(value, class)? {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::Datatype(datatype) => {
+                    for value in &values {
+                        if !self.has_datatype(value, datatype)? {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::NodeKind(kind) => {
+                    for value in &values {
+                        if !kind.matches(value) {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::MinCount(min) => {
+                    if values.len() < *min {
+                        results.push(new_result(shape, constraint, focus, None));
+                    }
+                }
+                Constraint::MaxCount(max) => {
+                    if values.len() > *max {
+                        results.push(new_result(shape, constraint, focus, None));
+                    }
+                }
+                Constraint::MinExclusive(bound) => {
+                    for value in &values {
+                        if !self.compare_with(value, bound, |o| o == Ordering::Greater)? {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::MinInclusive(bound) => {
+                    for value in &values {
+                        if !self.compare_with(value, bound, |o| o != Ordering::Less)? {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::MaxExclusive(bound) => {
+                    for value in &values {
+                        if !self.compare_with(value, bound, |o| o == Ordering::Less)? {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::MaxInclusive(bound) => {
+                    for value in &values {
+                        if !self.compare_with(value, bound, |o| o != Ordering::Greater)? {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::MinLength(min) => {
+                    for value in &values {
+                        if !matches!(string_value(value), Some(v) if v.chars().count() >= *min) {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::MaxLength(max) => {
+                    for value in &values {
+                        if !matches!(string_value(value), Some(v) if v.chars().count() <= *max) {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::Pattern(regex) => {
+                    for value in &values {
+                        if !matches!(string_value(value), Some(v) if regex.is_match(v)) {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::LanguageIn(ranges) => {
+                    for value in &values {
+                        let language = if let Term::Literal(literal) = value {
+                            literal.language()
+                        } else {
+                            None
+                        };
+                        let matches = matches!(
+                            language,
+                            Some(language) if ranges.iter().any(|range| language_matches(language, range))
+                        );
+                        if !matches {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::UniqueLang => {
+                    let mut counts = Vec::<(String, usize)>::new();
+                    for value in &values {
+                        if let Term::Literal(literal) = value {
+                            if let Some(language) = literal.language() {
+                                let language = language.to_ascii_lowercase();
+                                if let Some((_, count)) =
+                                    counts.iter_mut().find(|(l, _)| *l == language)
+                                {
+                                    *count += 1;
+                                } else {
+                                    counts.push((language, 1));
+                                }
+                            }
+                        }
+                    }
+                    for _ in counts.iter().filter(|(_, count)| *count > 1) {
+                        results.push(new_result(shape, constraint, focus, None));
+                    }
+                }
+                Constraint::Equals(predicate) => {
+                    let others = self
+                        .path_values(PropertyPathExpression::NamedNode(predicate.clone()), focus)?;
+                    for value in values.iter().filter(|v| !others.contains(v)) {
+                        results.push(new_result(shape, constraint, focus, Some(value)));
+                    }
+                    for other in others.iter().filter(|o| !values.contains(o)) {
+                        results.push(new_result(shape, constraint, focus, Some(other)));
+                    }
+                }
+                Constraint::Disjoint(predicate) => {
+                    let others = self
+                        .path_values(PropertyPathExpression::NamedNode(predicate.clone()), focus)?;
+                    for value in values.iter().filter(|v| others.contains(v)) {
+                        results.push(new_result(shape, constraint, focus, Some(value)));
+                    }
+                }
+                Constraint::LessThan(predicate) | Constraint::LessThanOrEquals(predicate) => {
+                    let or_equals = matches!(constraint, Constraint::LessThanOrEquals(_));
+                    let others = self
+                        .path_values(PropertyPathExpression::NamedNode(predicate.clone()), focus)?;
+                    for value in &values {
+                        for other in &others {
+                            let valid = if let Term::Literal(other) = other {
+                                self.compare_with(value, other, |o| {
+                                    o == Ordering::Less || (or_equals && o == Ordering::Equal)
+                                })?
+                            } else {
+                                false
+                            };
+                            if !valid {
+                                results.push(new_result(shape, constraint, focus, Some(value)));
+                            }
+                        }
+                    }
+                }
+                Constraint::Not(not) => {
+                    for value in &values {
+                        if self.conforms(*not, value)? {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::And(shapes) => {
+                    for value in &values {
+                        for and in shapes {
+                            if !self.conforms(*and, value)? {
+                                results.push(new_result(shape, constraint, focus, Some(value)));
+                                break;
+                            }
+                        }
+                    }
+                }
+                Constraint::Or(shapes) => {
+                    for value in &values {
+                        let mut valid = false;
+                        for or in shapes {
+                            if self.conforms(*or, value)? {
+                                valid = true;
+                                break;
+                            }
+                        }
+                        if !valid {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::Xone(shapes) => {
+                    for value in &values {
+                        let mut count = 0;
+                        for xone in shapes {
+                            if self.conforms(*xone, value)? {
+                                count += 1;
+                            }
+                        }
+                        if count != 1 {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::Node(node) => {
+                    for value in &values {
+                        if !self.conforms(*node, value)? {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::Property(property) => {
+                    for value in &values {
+                        self.validate_shape(*property, value, results)?;
+                    }
+                }
+                Constraint::QualifiedValueShape {
+                    shape: qualified,
+                    min_count,
+                    max_count,
+                    siblings,
+                } => {
+                    let mut count = 0;
+                    for value in &values {
+                        if self.conforms(*qualified, value)? {
+                            let mut in_sibling = false;
+                            for sibling in siblings {
+                                if self.conforms(*sibling, value)? {
+                                    in_sibling = true;
+                                    break;
+                                }
+                            }
+                            if !in_sibling {
+                                count += 1;
+                            }
+                        }
+                    }
+                    if matches!(min_count, Some(min) if count < *min) {
+                        results.push(new_result(shape, constraint, focus, None));
+                    }
+                    if matches!(max_count, Some(max) if count > *max) {
+                        let mut result = new_result(shape, constraint, focus, None);
+                        result.source_constraint_component =
+                            sh::QUALIFIED_MAX_COUNT_CONSTRAINT_COMPONENT.into();
+                        results.push(result);
+                    }
+                }
+                Constraint::Closed { ignored_properties } => {
+                    let allowed = shape
+                        .constraints
+                        .iter()
+                        .filter_map(|c| {
+                            if let Constraint::Property(property) = c {
+                                if let Some(PropertyPathExpression::NamedNode(predicate)) =
+                                    &self.shapes.shapes[*property].path
+                                {
+                                    return Some(predicate);
+                                }
+                            }
+                            None
+                        })
+                        .chain(ignored_properties)
+                        .collect::<HashSet<_>>();
+                    let pattern =
+                        self.evaluator
+                            .prepare(&GraphPattern::BGP(vec![TriplePattern::new(
+                                self.this.clone(),
+                                self.predicate.clone(),
+                                self.value.clone(),
+                            )]))?;
+                    for value in &values {
+                        for solution in self.evaluator.evaluate(&pattern, &[(&self.this, value)])? {
+                            let solution = solution?;
+                            if let (Some(Term::NamedNode(predicate)), Some(object)) =
+                                (solution.get(&self.predicate), solution.get(&self.value))
+                            {
+                                if !allowed.contains(predicate) {
+                                    let mut result =
+                                        new_result(shape, constraint, focus, Some(object));
+                                    result.result_path =
+                                        Some(PropertyPathExpression::NamedNode(predicate.clone()));
+                                    results.push(result);
+                                }
+                            }
+                        }
+                    }
+                }
+                Constraint::HasValue(expected) => {
+                    if !values.contains(expected) {
+                        results.push(new_result(shape, constraint, focus, None));
+                    }
+                }
+                Constraint::In(allowed) => {
+                    for value in &values {
+                        if !allowed.contains(value) {
+                            results.push(new_result(shape, constraint, focus, Some(value)));
+                        }
+                    }
+                }
+                Constraint::Sparql(sparql) => {
+                    self.validate_sparql(shape, sparql, focus, results)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_sparql(
+        &self,
+        shape: &Shape,
+        constraint: &SparqlConstraint,
+        focus: &Term,
+        results: &mut Vec<ValidationResult>,
+    ) -> Result<(), EvaluationError> {
+        let pattern = if let Query::Select { pattern, .. } = &constraint.query {
+            self.evaluator.prepare(pattern)?
+        } else {
+            return Err(EvaluationError::msg(
+                "SHACL SPARQL-based constraints should be SELECT queries",
+            ));
+        };
+        let current_shape = Variable::new_unchecked("currentShape");
+        let failure = Variable::new_unchecked("failure");
+        let path = Variable::new_unchecked("path");
+        for solution in self.evaluator.evaluate(
+            &pattern,
+            &[(&self.this, focus), (&current_shape, &shape.node)],
+        )? {
+            let solution = solution?;
+            if let Some(Term::Literal(failure)) = solution.get(&failure) {
+                if failure.value() == "true" && failure.datatype() == xsd::BOOLEAN {
+                    return Err(EvaluationError::msg(format!(
+                        "The SPARQL constraint {} has failed on {}",
+                        constraint.node, focus
+                    )));
+                }
+            }
+            let messages = if constraint.messages.is_empty() {
+                &shape.messages
+            } else {
+                &constraint.messages
+            };
+            results.push(ValidationResult {
+                focus_node: solution.get(&self.this).unwrap_or(focus).clone(),
+                result_path: match solution.get(&path) {
+                    Some(Term::NamedNode(path)) => {
+                        Some(PropertyPathExpression::NamedNode(path.clone()))
+                    }
+                    _ => shape.path.clone(),
+                },
+                value: solution.get(&self.value).cloned().or_else(|| {
+                    if shape.path.is_none() {
+                        Some(focus.clone())
+                    } else {
+                        None
+                    }
+                }),
+                source_shape: shape.node.clone(),
+                source_constraint_component: sh::SPARQL_CONSTRAINT_COMPONENT.into(),
+                source_constraint: Some(constraint.node.clone()),
+                severity: shape.severity.clone(),
+                messages: messages
+                    .iter()
+                    .map(|message| substitute_message(message, &solution))
+                    .collect(),
+            })
+        }
+        Ok(())
+    }
+
+    fn has_datatype(&self, value: &Term, datatype: &NamedNode) -> Result<bool, EvaluationError> {
+        let literal = if let Term::Literal(literal) = value {
+            literal
+        } else {
+            return Ok(false);
+        };
+        if literal.datatype() == datatype.as_ref() {
+            // The ill-formed literals of the datatypes supported by the store are kept unparsed
+            return Ok(!is_parsed_datatype(datatype.as_ref())
+                || !self.evaluator.is_unknown_typed_literal(literal)?);
+        }
+        // The values of the derived integer datatypes are stored as xsd:integer
+        if literal.datatype() == xsd::INTEGER {
+            if let Some((min, max)) = integer_datatype_range(datatype.as_ref()) {
+                if let Ok(value) = literal.value().parse::<i128>() {
+                    return Ok(!matches!(min, Some(min) if value < min)
+                        && !matches!(max, Some(max) if max < value));
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn compare_with(
+        &self,
+        value: &Term,
+        bound: &Literal,
+        predicate: impl Fn(Ordering) -> bool,
+    ) -> Result<bool, EvaluationError> {
+        Ok(if let Term::Literal(value) = value {
+            matches!(
+                self.evaluator.compare_literals(value, bound)?,
+                Some(ordering) if predicate(ordering)
+            )
+        } else {
+            false
+        })
+    }
+}
+
+fn new_result(
+    shape: &Shape,
+    constraint: &Constraint,
+    focus: &Term,
+    value: Option<&Term>,
+) -> ValidationResult {
+    ValidationResult {
+        focus_node: focus.clone(),
+        result_path: shape.path.clone(),
+        value: value.cloned(),
+        source_shape: shape.node.clone(),
+        source_constraint_component: constraint.component().into(),
+        source_constraint: None,
+        severity: shape.severity.clone(),
+        messages: shape.messages.clone(),
+    }
+}
+
+fn deduplicate(terms: Vec<Term>) -> Vec<Term> {
+    let mut seen = HashSet::with_capacity(terms.len());
+    terms
+        .into_iter()
+        .filter(|term| seen.insert(term.clone()))
+        .collect()
+}
+
+/// The string used by sh:minLength, sh:maxLength and sh:pattern
+fn string_value(term: &Term) -> Option<&str> {
+    match term {
+        Term::NamedNode(node) => Some(node.as_str()),
+        Term::Literal(literal) => Some(literal.value()),
+        Term::BlankNode(_) | Term::Triple(_) => None,
+    }
+}
+
+/// [Basic language range filtering](https://tools.ietf.org/html/rfc4647#section-3.3.1)
+fn language_matches(language: &str, range: &str) -> bool {
+    range == "*"
+        || language.eq_ignore_ascii_case(range)
+        || (language.len() > range.len()
+            && language.as_bytes()[range.len()] == b'-'
+            && language[..range.len()].eq_ignore_ascii_case(range))
+}
+
+/// Replaces the `{?var}` and `{$var}` placeholders of a SPARQL-based constraint message
+fn substitute_message(message: &Literal, solution: &QuerySolution) -> Literal {
+    if !message.value().contains('{') {
+        return message.clone();
+    }
+    let mut values = HashMap::new();
+    for (variable, value) in solution.iter() {
+        let value = match value {
+            Term::Literal(literal) => literal.value().to_owned(),
+            Term::NamedNode(node) => node.as_str().to_owned(),
+            _ => value.to_string(),
+        };
+        values.insert(format!("{{?{}}}", variable.as_str()), value.clone());
+        values.insert(format!("{{${}}}", variable.as_str()), value);
+    }
+    let mut text = message.value().to_owned();
+    for (placeholder, value) in values {
+        text = text.replace(&placeholder, &value);
+    }
+    if let Some(language) = message.language() {
+        Literal::new_language_tagged_literal_unchecked(text, language)
+    } else {
+        Literal::new_typed_literal(text, message.datatype())
+    }
+}
+
+fn is_parsed_datatype(datatype: NamedNodeRef<'_>) -> bool {
+    matches!(
+        datatype.as_str(),
+        "http://www.w3.org/2001/XMLSchema#boolean"
+            | "http://www.w3.org/2001/XMLSchema#float"
+            | "http://www.w3.org/2001/XMLSchema#double"
+            | "http://www.w3.org/2001/XMLSchema#decimal"
+            | "http://www.w3.org/2001/XMLSchema#dateTime"
+            | "http://www.w3.org/2001/XMLSchema#dateTimeStamp"
+            | "http://www.w3.org/2001/XMLSchema#time"
+            | "http://www.w3.org/2001/XMLSchema#date"
+            | "http://www.w3.org/2001/XMLSchema#gYearMonth"
+            | "http://www.w3.org/2001/XMLSchema#gYear"
+            | "http://www.w3.org/2001/XMLSchema#gMonthDay"
+            | "http://www.w3.org/2001/XMLSchema#gDay"
+            | "http://www.w3.org/2001/XMLSchema#gMonth"
+            | "http://www.w3.org/2001/XMLSchema#duration"
+            | "http://www.w3.org/2001/XMLSchema#yearMonthDuration"
+            | "http://www.w3.org/2001/XMLSchema#dayTimeDuration"
+    ) || datatype == xsd::INTEGER
+        || integer_datatype_range(datatype).is_some()
+}
+
+fn integer_datatype_range(datatype: NamedNodeRef<'_>) -> Option<(Option<i128>, Option<i128>)> {
+    Some(match datatype.as_str() {
+        "http://www.w3.org/2001/XMLSchema#byte" => (Some(i8::MIN.into()), Some(i8::MAX.into())),
+        "http://www.w3.org/2001/XMLSchema#short" => (Some(i16::MIN.into()), Some(i16::MAX.into())),
+        "http://www.w3.org/2001/XMLSchema#int" => (Some(i32::MIN.into()), Some(i32::MAX.into())),
+        "http://www.w3.org/2001/XMLSchema#long" => (Some(i64::MIN.into()), Some(i64::MAX.into())),
+        "http://www.w3.org/2001/XMLSchema#unsignedByte" => (Some(0), Some(u8::MAX.into())),
+        "http://www.w3.org/2001/XMLSchema#unsignedShort" => (Some(0), Some(u16::MAX.into())),
+        "http://www.w3.org/2001/XMLSchema#unsignedInt" => (Some(0), Some(u32::MAX.into())),
+        "http://www.w3.org/2001/XMLSchema#unsignedLong" => (Some(0), Some(u64::MAX.into())),
+        "http://www.w3.org/2001/XMLSchema#positiveInteger" => (Some(1), None),
+        "http://www.w3.org/2001/XMLSchema#negativeInteger" => (None, Some(-1)),
+        "http://www.w3.org/2001/XMLSchema#nonPositiveInteger" => (None, Some(0)),
+        "http://www.w3.org/2001/XMLSchema#nonNegativeInteger" => (Some(0), None),
+        _ => return None,
+    })
+}
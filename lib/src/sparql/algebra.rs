@@ -290,11 +290,24 @@ impl From<Variable> for NamedNodeOrVariable {
     }
 }
 
-/// The union of [`Term`]s and [`Variable`]s
+/// The union of [`Term`]s, [`Variable`]s and [quoted triple patterns](https://w3c.github.io/rdf-star/cg-spec/#sparql-star-grammar)
+///
+/// Quoted triple patterns without variables are represented as [`Term::Triple`].
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
 pub enum TermOrVariable {
     Term(Term),
     Variable(Variable),
+    Triple(Box<TriplePattern>),
+}
+
+impl TermOrVariable {
+    /// Checks if it is a quoted triple, with or without variables
+    pub fn is_triple(&self) -> bool {
+        matches!(
+            self,
+            TermOrVariable::Triple(_) | TermOrVariable::Term(Term::Triple(_))
+        )
+    }
 }
 
 impl fmt::Display for TermOrVariable {
@@ -302,6 +315,11 @@ impl fmt::Display for TermOrVariable {
         match self {
             TermOrVariable::Term(term) => term.fmt(f),
             TermOrVariable::Variable(var) => var.fmt(f),
+            TermOrVariable::Triple(triple) => write!(
+                f,
+                "<< {} {} {} >>",
+                triple.subject, triple.predicate, triple.object
+            ),
         }
     }
 }
@@ -336,6 +354,30 @@ impl From<Term> for TermOrVariable {
     }
 }
 
+impl From<TriplePattern> for TermOrVariable {
+    fn from(triple: TriplePattern) -> Self {
+        if let (
+            TermOrVariable::Term(subject),
+            NamedNodeOrVariable::NamedNode(predicate),
+            TermOrVariable::Term(object),
+        ) = (&triple.subject, &triple.predicate, &triple.object)
+        {
+            // Blank nodes in patterns behave like variables so they are kept in patterns
+            let subject = match subject {
+                Term::NamedNode(node) => Some(Subject::from(node.clone())),
+                Term::Triple(triple) => Some(triple.clone().into()),
+                Term::BlankNode(_) | Term::Literal(_) => None,
+            };
+            if let (Some(subject), false) = (subject, object.is_blank_node()) {
+                return TermOrVariable::Term(
+                    Triple::new(subject, predicate.clone(), object.clone()).into(),
+                );
+            }
+        }
+        TermOrVariable::Triple(Box::new(triple))
+    }
+}
+
 impl From<NamedNodeOrVariable> for TermOrVariable {
     fn from(element: NamedNodeOrVariable) -> Self {
         match element {
@@ -486,7 +528,7 @@ impl fmt::Display for PropertyPathExpression {
     }
 }
 
-struct SparqlPropertyPath<'a>(&'a PropertyPathExpression);
+pub(crate) struct SparqlPropertyPath<'a>(pub(crate) &'a PropertyPathExpression);
 
 impl<'a> fmt::Display for SparqlPropertyPath<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -799,6 +841,11 @@ pub enum Function {
     IsLiteral,
     IsNumeric,
     Regex,
+    Triple,
+    Subject,
+    Predicate,
+    Object,
+    IsTriple,
     Custom(NamedNode),
 }
 
@@ -851,6 +898,11 @@ impl fmt::Display for Function {
             Function::IsLiteral => write!(f, "isLITERAL"),
             Function::IsNumeric => write!(f, "isNUMERIC"),
             Function::Regex => write!(f, "REGEX"),
+            Function::Triple => write!(f, "TRIPLE"),
+            Function::Subject => write!(f, "SUBJECT"),
+            Function::Predicate => write!(f, "PREDICATE"),
+            Function::Object => write!(f, "OBJECT"),
+            Function::IsTriple => write!(f, "isTRIPLE"),
             Function::Custom(iri) => iri.fmt(f),
         }
     }
@@ -942,6 +994,24 @@ pub enum GraphPattern {
     },
 }
 
+fn add_triple_pattern_variables<'a>(pattern: &'a TriplePattern, vars: &mut BTreeSet<&'a Variable>) {
+    add_term_or_variable_variables(&pattern.subject, vars);
+    if let NamedNodeOrVariable::Variable(p) = &pattern.predicate {
+        vars.insert(p);
+    }
+    add_term_or_variable_variables(&pattern.object, vars);
+}
+
+fn add_term_or_variable_variables<'a>(term: &'a TermOrVariable, vars: &mut BTreeSet<&'a Variable>) {
+    match term {
+        TermOrVariable::Variable(v) => {
+            vars.insert(v);
+        }
+        TermOrVariable::Triple(triple) => add_triple_pattern_variables(triple, vars),
+        TermOrVariable::Term(_) => (),
+    }
+}
+
 impl fmt::Display for GraphPattern {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -1076,26 +1146,14 @@ impl GraphPattern {
         match self {
             GraphPattern::BGP(p) => {
                 for pattern in p {
-                    if let TermOrVariable::Variable(s) = &pattern.subject {
-                        vars.insert(s);
-                    }
-                    if let NamedNodeOrVariable::Variable(p) = &pattern.predicate {
-                        vars.insert(p);
-                    }
-                    if let TermOrVariable::Variable(o) = &pattern.object {
-                        vars.insert(o);
-                    }
+                    add_triple_pattern_variables(pattern, vars);
                 }
             }
             GraphPattern::Path {
                 subject, object, ..
             } => {
-                if let TermOrVariable::Variable(s) = subject {
-                    vars.insert(s);
-                }
-                if let TermOrVariable::Variable(o) = object {
-                    vars.insert(o);
-                }
+                add_term_or_variable_variables(subject, vars);
+                add_term_or_variable_variables(object, vars);
             }
             GraphPattern::Join { left, right }
             | GraphPattern::LeftJoin { left, right, .. }
@@ -1755,6 +1813,30 @@ pub enum GraphUpdateOperation {
     Create { silent: bool, graph: NamedNode },
     /// [drop](https://www.w3.org/TR/sparql11-update/#def_dropoperation)
     Drop { silent: bool, graph: GraphTarget },
+    /// [add](https://www.w3.org/TR/sparql11-update/#add)
+    ///
+    /// `None` is the default graph.
+    Add {
+        silent: bool,
+        from: Option<NamedNode>,
+        to: Option<NamedNode>,
+    },
+    /// [move](https://www.w3.org/TR/sparql11-update/#move)
+    ///
+    /// `None` is the default graph.
+    Move {
+        silent: bool,
+        from: Option<NamedNode>,
+        to: Option<NamedNode>,
+    },
+    /// [copy](https://www.w3.org/TR/sparql11-update/#copy)
+    ///
+    /// `None` is the default graph.
+    Copy {
+        silent: bool,
+        from: Option<NamedNode>,
+        to: Option<NamedNode>,
+    },
 }
 
 impl fmt::Display for GraphUpdateOperation {
@@ -1843,10 +1925,43 @@ impl fmt::Display for GraphUpdateOperation {
                 }
                 write!(f, "{}", graph)
             }
+            GraphUpdateOperation::Add { silent, from, to } => {
+                write_graph_copy("ADD", *silent, from, to, f)
+            }
+            GraphUpdateOperation::Move { silent, from, to } => {
+                write_graph_copy("MOVE", *silent, from, to, f)
+            }
+            GraphUpdateOperation::Copy { silent, from, to } => {
+                write_graph_copy("COPY", *silent, from, to, f)
+            }
         }
     }
 }
 
+fn write_graph_copy(
+    operation: &str,
+    silent: bool,
+    from: &Option<NamedNode>,
+    to: &Option<NamedNode>,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result {
+    write!(f, "{} ", operation)?;
+    if silent {
+        write!(f, "SILENT ")?;
+    }
+    write_graph_or_default(from, f)?;
+    write!(f, " TO ")?;
+    write_graph_or_default(to, f)
+}
+
+fn write_graph_or_default(graph: &Option<NamedNode>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(graph) = graph {
+        write!(f, "GRAPH {}", graph)
+    } else {
+        write!(f, "DEFAULT")
+    }
+}
+
 fn write_quads(quads: &[Quad], f: &mut fmt::Formatter<'_>) -> fmt::Result {
     for quad in quads {
         if quad.graph_name == GraphName::DefaultGraph {
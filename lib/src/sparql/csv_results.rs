@@ -67,6 +67,9 @@ fn write_csv_term<'a>(term: impl Into<TermRef<'a>>, mut sink: impl Write) -> io:
             sink.write_all(bnode.as_str().as_bytes())
         }
         TermRef::Literal(literal) => write_escaped_csv_string(literal.value(), &mut sink),
+        TermRef::Triple(triple) => {
+            write_escaped_csv_string(&format!("<< {} >>", triple), &mut sink)
+        }
     }
 }
 
@@ -158,6 +161,7 @@ fn write_tsv_term<'a>(term: impl Into<TermRef<'a>>, mut sink: impl Write) -> io:
             }
             _ => sink.write_all(literal.to_string().as_bytes()),
         },
+        TermRef::Triple(triple) => write!(sink, "<< {} >>", triple),
     }
 }
 
@@ -267,6 +271,17 @@ mod tests {
                         Some(BlankNode::new_unchecked("b1").into()),
                         Some(Literal::new_typed_literal("123", xsd::INTEGER).into()),
                     ]),
+                    Ok(vec![
+                        None,
+                        Some(
+                            Triple::new(
+                                NamedNode::new_unchecked("http://example/s"),
+                                NamedNode::new_unchecked("http://example/p"),
+                                Literal::new_simple_literal("o,1"),
+                            )
+                            .into(),
+                        ),
+                    ]),
                 ]
                 .into_iter(),
             ),
@@ -278,13 +293,13 @@ mod tests {
     fn test_csv_serialization() {
         let mut sink = Vec::new();
         write_csv_results(build_example(), &mut sink).unwrap();
-        assert_eq!(str::from_utf8(&sink).unwrap(), "x,literal\r\nhttp://example/x,String\r\nhttp://example/x,\"String-with-dquote\"\"\"\r\n_:b0,Blank node\r\n,Missing 'x'\r\n,\r\nhttp://example/x,\r\n_:b1,String-with-lang\r\n_:b1,123");
+        assert_eq!(str::from_utf8(&sink).unwrap(), "x,literal\r\nhttp://example/x,String\r\nhttp://example/x,\"String-with-dquote\"\"\"\r\n_:b0,Blank node\r\n,Missing 'x'\r\n,\r\nhttp://example/x,\r\n_:b1,String-with-lang\r\n_:b1,123\r\n,\"<< <http://example/s> <http://example/p> \"\"o,1\"\" >>\"");
     }
 
     #[test]
     fn test_tsv_serialization() {
         let mut sink = Vec::new();
         write_tsv_results(build_example(), &mut sink).unwrap();
-        assert_eq!(str::from_utf8(&sink).unwrap(), "?x\t?literal\n<http://example/x>\t\"String\"\n<http://example/x>\t\"String-with-dquote\\\"\"\n_:b0\t\"Blank node\"\n\t\"Missing 'x'\"\n\t\n<http://example/x>\t\n_:b1\t\"String-with-lang\"@en\n_:b1\t123");
+        assert_eq!(str::from_utf8(&sink).unwrap(), "?x\t?literal\n<http://example/x>\t\"String\"\n<http://example/x>\t\"String-with-dquote\\\"\"\n_:b0\t\"Blank node\"\n\t\"Missing 'x'\"\n\t\n<http://example/x>\t\n_:b1\t\"String-with-lang\"@en\n_:b1\t123\n\t<< <http://example/s> <http://example/p> \"o,1\" >>");
     }
 }
@@ -3,7 +3,8 @@ use crate::sparql::EvaluationError;
 use crate::store::numeric_encoder::{
     EncodedQuad, EncodedTerm, ReadEncoder, StrContainer, StrEncodingAware, StrId, StrLookup,
 };
-use crate::store::ReadableEncodedStore;
+use crate::store::text::TextMatch;
+use crate::store::{PredicateStatistics, ReadableEncodedStore};
 use lasso::{Rodeo, Spur};
 use std::cell::RefCell;
 use std::iter::{empty, once, Once};
@@ -215,6 +216,94 @@ impl<S: ReadableEncodedStore> ReadableEncodedStore for DatasetView<S> {
             "Graphs lookup is not implemented by DatasetView",
         ))
     }
+
+    fn encoded_quads_count(&self) -> Result<u64, EvaluationError> {
+        self.store.encoded_quads_count().map_err(|e| e.into())
+    }
+
+    fn encoded_predicate_statistics(
+        &self,
+        predicate: EncodedTerm<Self::StrId>,
+    ) -> Result<PredicateStatistics, EvaluationError> {
+        if let Ok(predicate) = predicate.try_map_id(unwrap_store_id) {
+            self.store
+                .encoded_predicate_statistics(predicate)
+                .map_err(|e| e.into())
+        } else {
+            // The predicate is not in the store
+            Ok(PredicateStatistics::default())
+        }
+    }
+
+    fn encoded_text_search(
+        &self,
+        query: &str,
+    ) -> Result<Option<Vec<TextMatch<Self::StrId>>>, EvaluationError> {
+        Ok(self
+            .store
+            .encoded_text_search(query)
+            .map_err(|e| e.into())?
+            .map(|matches| {
+                matches
+                    .into_iter()
+                    .map(|m| m.map_id(DatasetStrId::Store))
+                    .collect()
+            }))
+    }
+}
+
+impl<S: ReadableEncodedStore> StrLookup for &DatasetView<S> {
+    fn get_str(&self, id: DatasetStrId<S::StrId>) -> Result<Option<String>, EvaluationError> {
+        (*self).get_str(id)
+    }
+
+    fn get_str_id(&self, value: &str) -> Result<Option<DatasetStrId<S::StrId>>, EvaluationError> {
+        (*self).get_str_id(value)
+    }
+}
+
+impl<S: ReadableEncodedStore> ReadableEncodedStore for &DatasetView<S> {
+    type QuadsIter = <DatasetView<S> as ReadableEncodedStore>::QuadsIter;
+    type GraphsIter = <DatasetView<S> as ReadableEncodedStore>::GraphsIter;
+
+    fn encoded_quads_for_pattern(
+        &self,
+        subject: Option<EncodedTerm<Self::StrId>>,
+        predicate: Option<EncodedTerm<Self::StrId>>,
+        object: Option<EncodedTerm<Self::StrId>>,
+        graph_name: Option<EncodedTerm<Self::StrId>>,
+    ) -> Self::QuadsIter {
+        (*self).encoded_quads_for_pattern(subject, predicate, object, graph_name)
+    }
+
+    fn encoded_named_graphs(&self) -> Self::GraphsIter {
+        (*self).encoded_named_graphs()
+    }
+
+    fn contains_encoded_named_graph(
+        &self,
+        graph_name: EncodedTerm<Self::StrId>,
+    ) -> Result<bool, EvaluationError> {
+        (*self).contains_encoded_named_graph(graph_name)
+    }
+
+    fn encoded_quads_count(&self) -> Result<u64, EvaluationError> {
+        (*self).encoded_quads_count()
+    }
+
+    fn encoded_predicate_statistics(
+        &self,
+        predicate: EncodedTerm<Self::StrId>,
+    ) -> Result<PredicateStatistics, EvaluationError> {
+        (*self).encoded_predicate_statistics(predicate)
+    }
+
+    fn encoded_text_search(
+        &self,
+        query: &str,
+    ) -> Result<Option<Vec<TextMatch<Self::StrId>>>, EvaluationError> {
+        (*self).encoded_text_search(query)
+    }
 }
 
 fn map_iter<'a, I: StrId>(
@@ -16,6 +16,13 @@ pub enum EvaluationError {
     Io(io::Error),
     /// An error returned during the query evaluation itself
     Query(QueryError),
+    /// The evaluation has not been completed before the timeout set in [`QueryOptions::with_timeout`](super::QueryOptions::with_timeout())
+    Timeout,
+    /// The evaluation has been cancelled using a [`CancellationToken`](super::CancellationToken)
+    Cancelled,
+    /// The memory limit set in [`QueryOptions::with_memory_limit`](super::QueryOptions::with_memory_limit()) has been reached
+    /// and spilling to temporary files is disabled by [`QueryOptions::without_spilling`](super::QueryOptions::without_spilling())
+    MemoryLimitExceeded,
     /// A conflict during a transaction
     #[doc(hidden)]
     Conflict,
@@ -38,6 +45,12 @@ impl fmt::Display for EvaluationError {
             Self::Parsing(error) => error.fmt(f),
             Self::Io(error) => error.fmt(f),
             Self::Query(error) => error.fmt(f),
+            Self::Timeout => write!(f, "The evaluation timeout has been reached"),
+            Self::Cancelled => write!(f, "The evaluation has been cancelled"),
+            Self::MemoryLimitExceeded => write!(
+                f,
+                "The evaluation memory limit has been reached and spilling to disk is disabled"
+            ),
             Self::Conflict => write!(f, "Transaction conflict"),
         }
     }
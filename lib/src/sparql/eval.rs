@@ -1,12 +1,17 @@
 use crate::model::vocab::{rdf, xsd};
 use crate::model::xsd::*;
-use crate::model::Triple;
-use crate::model::{BlankNode, LiteralRef, NamedNodeRef};
+use crate::model::{BlankNode, Literal, LiteralRef, NamedNodeRef};
+use crate::model::{Term, Triple};
 use crate::sparql::algebra::{GraphPattern, Query, QueryDataset};
 use crate::sparql::error::EvaluationError;
+use crate::sparql::explanation::{EvaluationStats, StatsIterator};
+use crate::sparql::geo::{self, Geometry};
+use crate::sparql::interrupt::{Interrupter, InterruptibleIterator};
 use crate::sparql::model::*;
 use crate::sparql::plan::*;
 use crate::sparql::service::ServiceHandler;
+use crate::sparql::spill::{spill_partition, MemoryLimit, SpillFile};
+use crate::sparql::AggregateFunctionAccumulator;
 use crate::store::numeric_encoder::*;
 use crate::store::small_string::SmallString;
 use crate::store::ReadableEncodedStore;
@@ -23,19 +28,32 @@ use std::collections::{HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::hash::Hash;
 use std::iter::Iterator;
-use std::iter::{empty, once};
+use std::iter::{empty, once, Fuse};
+use std::mem::{size_of, size_of_val, take};
 use std::rc::Rc;
 use std::str;
+use std::time::Instant;
 
 const REGEX_SIZE_LIMIT: usize = 1_000_000;
+/// Rough estimation of the memory used by an aggregate accumulator
+const ACCUMULATOR_MEMORY_SIZE: usize = 64;
 
 type EncodedTuplesIterator<I> = Box<dyn Iterator<Item = Result<EncodedTuple<I>, EvaluationError>>>;
+/// Solutions with their position in the input of the operator
+type NumberedTuplesIterator<I> =
+    Box<dyn Iterator<Item = Result<(u64, EncodedTuple<I>), EvaluationError>>>;
 
 pub(crate) struct SimpleEvaluator<S> {
     dataset: Rc<S>,
     base_iri: Option<Rc<Iri<String>>>,
     now: DateTime,
     service_handler: Rc<dyn ServiceHandler<Error = EvaluationError>>,
+    custom_functions: Rc<CustomFunctions>,
+    custom_aggregates: Rc<CustomAggregates>,
+    interrupter: Option<Rc<Interrupter>>,
+    memory_limit: Option<Rc<MemoryLimit>>,
+    stats: Option<Rc<EvaluationStats>>,
+    pre_binding: bool,
 }
 
 impl<S> Clone for SimpleEvaluator<S> {
@@ -45,6 +63,12 @@ impl<S> Clone for SimpleEvaluator<S> {
             base_iri: self.base_iri.clone(),
             now: self.now,
             service_handler: self.service_handler.clone(),
+            custom_functions: self.custom_functions.clone(),
+            custom_aggregates: self.custom_aggregates.clone(),
+            interrupter: self.interrupter.clone(),
+            memory_limit: self.memory_limit.clone(),
+            stats: self.stats.clone(),
+            pre_binding: self.pre_binding,
         }
     }
 }
@@ -57,21 +81,56 @@ where
         dataset: Rc<S>,
         base_iri: Option<Rc<Iri<String>>>,
         service_handler: Rc<dyn ServiceHandler<Error = EvaluationError>>,
+        custom_functions: Rc<CustomFunctions>,
+        custom_aggregates: Rc<CustomAggregates>,
+        interrupter: Option<Rc<Interrupter>>,
+        memory_limit: Option<Rc<MemoryLimit>>,
     ) -> Self {
         Self {
             dataset,
             base_iri,
             now: DateTime::now().unwrap(),
             service_handler,
+            custom_functions,
+            custom_aggregates,
+            interrupter,
+            memory_limit,
+            stats: None,
+            pre_binding: false,
         }
     }
 
+    /// Collects the number of rows and the evaluation time of each plan node
+    pub fn with_stats(mut self, stats: Rc<EvaluationStats>) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Pushes the bindings given to the projections down into their children like the [SHACL pre-binding](https://www.w3.org/TR/shacl/#pre-binding) does.
+    ///
+    /// By default the subqueries are evaluated independently of the outer bindings.
+    pub fn with_pre_binding(mut self) -> Self {
+        self.pre_binding = true;
+        self
+    }
+
     pub fn evaluate_select_plan(
         &self,
         plan: &PlanNode<S::StrId>,
         variables: Rc<Vec<Variable>>,
     ) -> Result<QueryResults, EvaluationError> {
-        let iter = self.eval_plan(plan, EncodedTuple::with_capacity(variables.len()));
+        let from = EncodedTuple::with_capacity(variables.len());
+        self.evaluate_select_plan_from(plan, variables, from)
+    }
+
+    /// Evaluates a plan with some variables already bound by the `from` tuple
+    pub fn evaluate_select_plan_from(
+        &self,
+        plan: &PlanNode<S::StrId>,
+        variables: Rc<Vec<Variable>>,
+        from: EncodedTuple<S::StrId>,
+    ) -> Result<QueryResults, EvaluationError> {
+        let iter = self.eval_plan(plan, from);
         Ok(QueryResults::Solutions(
             self.decode_bindings(iter, variables),
         ))
@@ -124,6 +183,26 @@ where
         &self,
         node: &PlanNode<S::StrId>,
         from: EncodedTuple<S::StrId>,
+    ) -> EncodedTuplesIterator<S::StrId> {
+        let iter: EncodedTuplesIterator<S::StrId> = if let Some(stats) = &self.stats {
+            let start = Instant::now();
+            let iter = self.eval_plan_node(node, from);
+            stats.record_evaluation(node, start.elapsed());
+            Box::new(StatsIterator::new(iter, node, stats.clone()))
+        } else {
+            self.eval_plan_node(node, from)
+        };
+        if let Some(interrupter) = &self.interrupter {
+            Box::new(InterruptibleIterator::new(iter, interrupter.clone()))
+        } else {
+            iter
+        }
+    }
+
+    fn eval_plan_node(
+        &self,
+        node: &PlanNode<S::StrId>,
+        from: EncodedTuple<S::StrId>,
     ) -> EncodedTuplesIterator<S::StrId> {
         match node {
             PlanNode::Init => Box::new(once(Ok(from))),
@@ -220,6 +299,123 @@ where
                     iter
                 }))
             }
+            PlanNode::QuotedTripleMatch {
+                child,
+                triple,
+                subject,
+                predicate,
+                object,
+            } => {
+                let eval = self.clone();
+                let triple = *triple;
+                let subject = *subject;
+                let predicate = *predicate;
+                let object = *object;
+                Box::new(self.eval_plan(child, from).filter_map(move |tuple| {
+                    let mut tuple = match tuple {
+                        Ok(tuple) => tuple,
+                        Err(error) => return Some(Err(error)),
+                    };
+                    let triple = eval.decode_quoted_triple(get_pattern_value(&triple, &tuple)?)?;
+                    let mut encoder = eval.dataset.as_ref();
+                    let encoded_subject = encoder.encode_subject(triple.subject.as_ref()).ok()?;
+                    let encoded_predicate =
+                        encoder.encode_named_node(triple.predicate.as_ref()).ok()?;
+                    let encoded_object = encoder.encode_term(triple.object.as_ref()).ok()?;
+                    if unify_pattern_value(&subject, encoded_subject, &mut tuple)
+                        && unify_pattern_value(&predicate, encoded_predicate, &mut tuple)
+                        && unify_pattern_value(&object, encoded_object, &mut tuple)
+                    {
+                        Some(Ok(tuple))
+                    } else {
+                        None
+                    }
+                }))
+            }
+            PlanNode::TextSearch {
+                child,
+                subject,
+                property,
+                literal,
+                score,
+                query,
+                limit,
+                graph_name,
+            } => {
+                let eval = self.clone();
+                let subject = *subject;
+                let property = *property;
+                let literal = *literal;
+                let score = *score;
+                let query = *query;
+                let limit = limit.unwrap_or(usize::MAX);
+                let graph_name = *graph_name;
+                Box::new(self.eval_plan(child, from).flat_map_ok(move |tuple| {
+                    let query = if let Some(query) =
+                        get_pattern_value(&query, &tuple).and_then(|query| eval.to_string(query))
+                    {
+                        query
+                    } else {
+                        let result: EncodedTuplesIterator<_> = Box::new(empty());
+                        return result;
+                    };
+                    let matches = match eval.dataset.encoded_text_search(&query) {
+                        Ok(Some(matches)) => matches,
+                        Ok(None) => return Box::new(once(Err(EvaluationError::msg(
+                            "text:query requires the full-text index of the store to be enabled",
+                        )))),
+                        Err(error) => return Box::new(once(Err(error))),
+                    };
+                    let mut results = Vec::new();
+                    for text_match in matches.into_iter().take(limit) {
+                        let mut match_tuple = tuple.clone();
+                        if let Some(literal) = literal {
+                            if !unify_pattern_value(
+                                &PatternValue::Variable(literal),
+                                text_match.literal,
+                                &mut match_tuple,
+                            ) {
+                                continue;
+                            }
+                        }
+                        if let Some(score) = score {
+                            if !unify_pattern_value(
+                                &PatternValue::Variable(score),
+                                text_match.score.into(),
+                                &mut match_tuple,
+                            ) {
+                                continue;
+                            }
+                        }
+                        // A subject using the literal with several properties is returned once per graph
+                        let mut seen = HashSet::new();
+                        for quad in eval.dataset.encoded_quads_for_pattern(
+                            get_pattern_value(&subject, &match_tuple),
+                            property,
+                            Some(text_match.literal),
+                            get_pattern_value(&graph_name, &match_tuple),
+                        ) {
+                            let quad = match quad {
+                                Ok(quad) => quad,
+                                Err(error) => {
+                                    results.push(Err(error));
+                                    continue;
+                                }
+                            };
+                            if !seen.insert((quad.subject, quad.graph_name)) {
+                                continue;
+                            }
+                            let mut new_tuple = match_tuple.clone();
+                            if unify_pattern_value(&subject, quad.subject, &mut new_tuple)
+                                && unify_pattern_value(&graph_name, quad.graph_name, &mut new_tuple)
+                            {
+                                results.push(Ok(new_tuple));
+                            }
+                        }
+                    }
+                    Box::new(results.into_iter())
+                }))
+            }
             PlanNode::PathPatternJoin {
                 child,
                 subject,
@@ -307,6 +503,27 @@ where
                     buffered_results: errors,
                 })
             }
+            PlanNode::ForLoopJoin { left, right } => Box::new(ForLoopJoinIterator {
+                eval: self.clone(),
+                right_plan: right.clone(),
+                left_iter: self.eval_plan(left, from),
+                current_right: Box::new(empty()),
+            }),
+            PlanNode::HashJoin { left, right, keys } => {
+                let mut errors = Vec::default();
+                let mut right_values = EncodedTupleSet::new(keys.clone());
+                for result in self.eval_plan(right, from.clone()) {
+                    match result {
+                        Ok(result) => right_values.insert(result),
+                        Err(error) => errors.push(Err(error)),
+                    }
+                }
+                Box::new(HashJoinIterator {
+                    left_iter: self.eval_plan(left, from),
+                    right: right_values,
+                    buffered_results: errors,
+                })
+            }
             PlanNode::AntiJoin { left, right } => {
                 //TODO: dumb implementation
                 let right: Vec<_> = self
@@ -341,6 +558,28 @@ where
                     })
                 }
             }
+            PlanNode::HashLeftJoin {
+                left,
+                right,
+                keys,
+                expression,
+            } => {
+                let mut errors = Vec::default();
+                let mut right_values = EncodedTupleSet::new(keys.clone());
+                for result in self.eval_plan(right, from.clone()) {
+                    match result {
+                        Ok(result) => right_values.insert(result),
+                        Err(error) => errors.push(Err(error)),
+                    }
+                }
+                Box::new(HashLeftJoinIterator {
+                    eval: self.clone(),
+                    left_iter: self.eval_plan(left, from),
+                    right: right_values,
+                    expression: expression.clone(),
+                    buffered_results: errors,
+                })
+            }
             PlanNode::Filter { child, expression } => {
                 let eval = self.clone();
                 let expression = expression.clone();
@@ -379,135 +618,388 @@ where
             }
             PlanNode::Sort { child, by } => {
                 let mut errors = Vec::default();
-                let mut values = self
-                    .eval_plan(child, from)
-                    .filter_map(|result| match result {
-                        Ok(result) => Some(result),
-                        Err(error) => {
-                            errors.push(Err(error));
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                values.sort_unstable_by(|a, b| {
-                    for comp in by {
-                        match comp {
-                            Comparator::Asc(expression) => {
-                                match self.cmp_according_to_expression(a, b, expression) {
-                                    Ordering::Greater => return Ordering::Greater,
-                                    Ordering::Less => return Ordering::Less,
-                                    Ordering::Equal => (),
-                                }
-                            }
-                            Comparator::Desc(expression) => {
-                                match self.cmp_according_to_expression(a, b, expression) {
-                                    Ordering::Greater => return Ordering::Less,
-                                    Ordering::Less => return Ordering::Greater,
-                                    Ordering::Equal => (),
+                let mut values = Vec::default();
+                let mut values_size = 0;
+                let mut runs = Vec::default();
+                for result in self.eval_plan(child, from) {
+                    match result {
+                        Ok(tuple) => {
+                            if let Some(memory_limit) = &self.memory_limit {
+                                let tuple_size = tuple_memory_size(&tuple);
+                                if values_size + tuple_size > memory_limit.bytes()
+                                    && !values.is_empty()
+                                {
+                                    // We write the sorted values in a temporary file and merge the files at the end
+                                    self.sort_tuples(&mut values, by);
+                                    match self.spill_tuples(memory_limit, values.drain(..)) {
+                                        Ok(run) => runs.push(run),
+                                        Err(error) => return Box::new(once(Err(error))),
+                                    }
+                                    values_size = 0;
                                 }
+                                values_size += tuple_size;
                             }
+                            values.push(tuple);
                         }
+                        Err(error) => errors.push(Err(error)),
                     }
-                    Ordering::Equal
-                });
-                Box::new(errors.into_iter().chain(values.into_iter().map(Ok)))
-            }
-            PlanNode::HashDeduplicate { child } => {
-                Box::new(hash_deduplicate(self.eval_plan(child, from)))
+                }
+                self.sort_tuples(&mut values, by);
+                if runs.is_empty() {
+                    return Box::new(errors.into_iter().chain(values.into_iter().map(Ok)));
+                }
+                let mut sources = Vec::with_capacity(runs.len() + 1);
+                for run in runs {
+                    match self.unspill_tuples(run) {
+                        Ok(source) => sources.push(source.fuse()),
+                        Err(error) => return Box::new(once(Err(error))),
+                    }
+                }
+                let values: EncodedTuplesIterator<S::StrId> = Box::new(values.into_iter().map(Ok));
+                sources.push(values.fuse());
+                Box::new(errors.into_iter().chain(SortMergeIterator {
+                    eval: self.clone(),
+                    by: by.clone(),
+                    heads: vec![None; sources.len()],
+                    sources,
+                }))
             }
-            PlanNode::Skip { child, count } => Box::new(self.eval_plan(child, from).skip(*count)),
-            PlanNode::Limit { child, count } => Box::new(self.eval_plan(child, from).take(*count)),
-            PlanNode::Project { child, mapping } => {
-                //TODO: use from somewhere?
+            PlanNode::HashDeduplicate { child } => self.eval_subquery_node(from, |from| {
+                if let Some(memory_limit) = &self.memory_limit {
+                    // The solutions are numbered to output them in the same order after spilling
+                    let iter = self
+                        .eval_plan(child, from)
+                        .zip(0..)
+                        .map(|(tuple, position)| Ok((position, tuple?)));
+                    Box::new(
+                        SpillingHashDeduplicateIterator {
+                            eval: self.clone(),
+                            memory_limit: memory_limit.clone(),
+                            iter: Box::new(iter),
+                            already_seen: HashSet::default(),
+                            already_seen_size: 0,
+                            partitions: Vec::default(),
+                            depth: 0,
+                            output: None,
+                        }
+                        .map(|tuple| Ok(tuple?.1)),
+                    )
+                } else {
+                    Box::new(hash_deduplicate(self.eval_plan(child, from)))
+                }
+            }),
+            PlanNode::Skip { child, count } => self.eval_subquery_node(from, |from| {
+                Box::new(self.eval_plan(child, from).skip(*count))
+            }),
+            PlanNode::Limit { child, count } => self.eval_subquery_node(from, |from| {
+                Box::new(self.eval_plan(child, from).take(*count))
+            }),
+            PlanNode::Project { child, mapping, .. } => self.eval_subquery_node(from, |from| {
                 let mapping = mapping.clone();
-                Box::new(
-                    self.eval_plan(child, EncodedTuple::with_capacity(mapping.len()))
-                        .map(move |tuple| {
-                            let tuple = tuple?;
-                            let mut output_tuple = EncodedTuple::with_capacity(from.capacity());
-                            for (input_key, output_key) in mapping.iter() {
-                                if let Some(value) = tuple.get(*input_key) {
-                                    output_tuple.set(*output_key, value)
+                // The pre-bound projected variables are pushed down to the child
+                let mut input_tuple = EncodedTuple::with_capacity(mapping.len());
+                for (input_key, output_key) in mapping.iter() {
+                    if let Some(value) = from.get(*output_key) {
+                        input_tuple.set(*input_key, value)
+                    }
+                }
+                Box::new(self.eval_plan(child, input_tuple).filter_map(move |tuple| {
+                    let tuple = match tuple {
+                        Ok(tuple) => tuple,
+                        Err(error) => return Some(Err(error)),
+                    };
+                    let mut output_tuple = from.clone();
+                    for (input_key, output_key) in mapping.iter() {
+                        if let Some(value) = tuple.get(*input_key) {
+                            if let Some(existing_value) = output_tuple.get(*output_key) {
+                                if existing_value != value {
+                                    return None; // Conflict
                                 }
+                            } else {
+                                output_tuple.set(*output_key, value)
                             }
-                            Ok(output_tuple)
-                        }),
-                )
-            }
+                        }
+                    }
+                    Some(Ok(output_tuple))
+                }))
+            }),
             PlanNode::Aggregate {
                 child,
                 key_mapping,
                 aggregates,
+                ..
             } => {
                 let tuple_size = from.capacity(); //TODO: not nice
-                let key_mapping = key_mapping.clone();
-                let aggregates = aggregates.clone();
-                let mut errors = Vec::default();
-                let mut accumulators_for_group = HashMap::<
-                    Vec<Option<EncodedTerm<S::StrId>>>,
-                    Vec<Box<dyn Accumulator<S::StrId>>>,
-                >::default();
-                self.eval_plan(child, from)
-                    .filter_map(|result| match result {
-                        Ok(result) => Some(result),
-                        Err(error) => {
-                            errors.push(error);
-                            None
+                self.eval_aggregate(
+                    self.eval_plan(child, from),
+                    key_mapping.clone(),
+                    aggregates.clone(),
+                    tuple_size,
+                    0,
+                )
+            }
+        }
+    }
+
+    /// Evaluates a node of a subquery, i.e. a projection or a solution modifier.
+    ///
+    /// The subqueries are evaluated independently of the outer bindings and their solutions are then joined with them.
+    /// With the [pre-binding](SimpleEvaluator::with_pre_binding()) the outer bindings are pushed down into the subquery instead.
+    fn eval_subquery_node(
+        &self,
+        from: EncodedTuple<S::StrId>,
+        eval: impl FnOnce(EncodedTuple<S::StrId>) -> EncodedTuplesIterator<S::StrId>,
+    ) -> EncodedTuplesIterator<S::StrId> {
+        if self.pre_binding || from.iter().all(|value| value.is_none()) {
+            return eval(from);
+        }
+        Box::new(
+            eval(EncodedTuple::with_capacity(from.capacity())).filter_map(
+                move |tuple| match tuple {
+                    Ok(tuple) => tuple.combine_with(&from).map(Ok),
+                    Err(error) => Some(Err(error)),
+                },
+            ),
+        )
+    }
+
+    /// Evaluates the aggregates on the given solutions.
+    ///
+    /// If the groups go over the memory limit, the solutions of the groups not in memory yet
+    /// are written to temporary files and evaluated afterwards with an increased `depth`.
+    fn eval_aggregate(
+        &self,
+        iter: EncodedTuplesIterator<S::StrId>,
+        key_mapping: Rc<Vec<(usize, usize)>>,
+        aggregates: Rc<Vec<(PlanAggregation<S::StrId>, usize)>>,
+        tuple_size: usize,
+        depth: usize,
+    ) -> EncodedTuplesIterator<S::StrId> {
+        let mut errors = Vec::default();
+        let mut accumulators_for_group = HashMap::<
+            Vec<Option<EncodedTerm<S::StrId>>>,
+            Vec<Box<dyn Accumulator<S::StrId>>>,
+        >::default();
+        let mut groups_size = 0;
+        let mut partitions = Vec::default();
+        for tuple in iter {
+            let tuple = match tuple {
+                Ok(tuple) => tuple,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+            //TODO avoid copy for key?
+            let key = key_mapping
+                .iter()
+                .map(|(v, _)| tuple.get(*v))
+                .collect::<Vec<_>>();
+            if let Some(memory_limit) = &self.memory_limit {
+                if !accumulators_for_group.contains_key(&key) {
+                    if partitions.is_empty() {
+                        let group_size = group_memory_size(&key, aggregates.len());
+                        if groups_size + group_size > memory_limit.bytes()
+                            && !accumulators_for_group.is_empty()
+                        {
+                            partitions = match memory_limit.spill_partitions() {
+                                Ok(partitions) => partitions,
+                                Err(error) => return Box::new(once(Err(error))),
+                            };
+                        } else {
+                            groups_size += group_size;
                         }
-                    })
-                    .for_each(|tuple| {
-                        //TODO avoid copy for key?
-                        let key = key_mapping.iter().map(|(v, _)| tuple.get(*v)).collect();
-
-                        let key_accumulators =
-                            accumulators_for_group.entry(key).or_insert_with(|| {
-                                aggregates
-                                    .iter()
-                                    .map(|(aggregate, _)| {
-                                        self.accumulator_for_aggregate(
-                                            &aggregate.function,
-                                            aggregate.distinct,
-                                        )
-                                    })
-                                    .collect::<Vec<_>>()
-                            });
-                        for (i, accumulator) in key_accumulators.iter_mut().enumerate() {
-                            let (aggregate, _) = &aggregates[i];
-                            accumulator.add(
-                                aggregate
-                                    .parameter
-                                    .as_ref()
-                                    .and_then(|parameter| self.eval_expression(parameter, &tuple)),
-                            );
+                    }
+                    if !partitions.is_empty() {
+                        // The group is not in memory, we evaluate it later
+                        if let Err(error) =
+                            self.spill_tuple(&mut partitions[spill_partition(&key, depth)], &tuple)
+                        {
+                            return Box::new(once(Err(error)));
                         }
-                    });
-                if accumulators_for_group.is_empty() {
-                    // There is always at least one group
-                    accumulators_for_group.insert(vec![None; key_mapping.len()], Vec::default());
+                        continue;
+                    }
                 }
-                Box::new(
-                    errors
+            }
+
+            let key_accumulators = accumulators_for_group.entry(key).or_insert_with(|| {
+                aggregates
+                    .iter()
+                    .map(|(aggregate, _)| {
+                        self.accumulator_for_aggregate(&aggregate.function, aggregate.distinct)
+                    })
+                    .collect::<Vec<_>>()
+            });
+            for (i, accumulator) in key_accumulators.iter_mut().enumerate() {
+                let (aggregate, _) = &aggregates[i];
+                accumulator.add(
+                    aggregate
+                        .parameter
+                        .as_ref()
+                        .and_then(|parameter| self.eval_expression(parameter, &tuple)),
+                );
+            }
+        }
+        if accumulators_for_group.is_empty() && depth == 0 {
+            // There is always at least one group
+            accumulators_for_group.insert(vec![None; key_mapping.len()], Vec::default());
+        }
+        let eval = self.clone();
+        let partition_key_mapping = key_mapping.clone();
+        let partition_aggregates = aggregates.clone();
+        Box::new(
+            errors
+                .into_iter()
+                .map(Err)
+                .chain(
+                    accumulators_for_group
                         .into_iter()
-                        .map(Err)
-                        .chain(accumulators_for_group.into_iter().map(
-                            move |(key, accumulators)| {
-                                let mut result = EncodedTuple::with_capacity(tuple_size);
-                                for (from_position, to_position) in key_mapping.iter() {
-                                    if let Some(value) = key[*from_position] {
-                                        result.set(*to_position, value);
-                                    }
+                        .map(move |(key, accumulators)| {
+                            let mut result = EncodedTuple::with_capacity(tuple_size);
+                            for (from_position, to_position) in key_mapping.iter() {
+                                if let Some(value) = key[*from_position] {
+                                    result.set(*to_position, value);
                                 }
-                                for (i, accumulator) in accumulators.into_iter().enumerate() {
-                                    if let Some(value) = accumulator.state() {
-                                        result.set(aggregates[i].1, value);
-                                    }
+                            }
+                            for (i, mut accumulator) in accumulators.into_iter().enumerate() {
+                                if let Some(value) = accumulator.state() {
+                                    result.set(aggregates[i].1, value);
                                 }
-                                Ok(result)
-                            },
-                        )),
+                            }
+                            Ok(result)
+                        }),
                 )
+                .chain(partitions.into_iter().flat_map(move |partition| {
+                    match eval.unspill_tuples(partition) {
+                        Ok(iter) => eval.eval_aggregate(
+                            iter,
+                            partition_key_mapping.clone(),
+                            partition_aggregates.clone(),
+                            tuple_size,
+                            depth + 1,
+                        ),
+                        Err(error) => Box::new(once(Err(error))),
+                    }
+                })),
+        )
+    }
+
+    /// Sorts the solutions keeping the input order of the ties
+    /// in order to return the same solutions order if they are spilled to disk or not
+    fn sort_tuples(&self, tuples: &mut [EncodedTuple<S::StrId>], by: &[Comparator<S::StrId>]) {
+        tuples.sort_by(|a, b| self.cmp_tuples(a, b, by))
+    }
+
+    fn cmp_tuples(
+        &self,
+        a: &EncodedTuple<S::StrId>,
+        b: &EncodedTuple<S::StrId>,
+        by: &[Comparator<S::StrId>],
+    ) -> Ordering {
+        for comp in by {
+            match comp {
+                Comparator::Asc(expression) => {
+                    match self.cmp_according_to_expression(a, b, expression) {
+                        Ordering::Greater => return Ordering::Greater,
+                        Ordering::Less => return Ordering::Less,
+                        Ordering::Equal => (),
+                    }
+                }
+                Comparator::Desc(expression) => {
+                    match self.cmp_according_to_expression(a, b, expression) {
+                        Ordering::Greater => return Ordering::Less,
+                        Ordering::Less => return Ordering::Greater,
+                        Ordering::Equal => (),
+                    }
+                }
             }
         }
+        Ordering::Equal
+    }
+
+    /// Writes solutions to a new temporary file
+    fn spill_tuples(
+        &self,
+        memory_limit: &MemoryLimit,
+        tuples: impl IntoIterator<Item = EncodedTuple<S::StrId>>,
+    ) -> Result<SpillFile, EvaluationError> {
+        let mut file = memory_limit.spill_file()?;
+        for tuple in tuples {
+            self.spill_tuple(&mut file, &tuple)?;
+        }
+        Ok(file)
+    }
+
+    fn spill_tuple(
+        &self,
+        file: &mut SpillFile,
+        tuple: &EncodedTuple<S::StrId>,
+    ) -> Result<(), EvaluationError> {
+        let terms = tuple
+            .iter()
+            .map(|term| term.map(|term| self.dataset.decode_term(term)).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(file.write(&terms)?)
+    }
+
+    /// Reads back the solutions written to a temporary file
+    fn unspill_tuples(
+        &self,
+        file: SpillFile,
+    ) -> Result<EncodedTuplesIterator<S::StrId>, EvaluationError> {
+        let eval = self.clone();
+        Ok(Box::new(file.read()?.map(move |terms| {
+            let mut encoder = eval.dataset.as_ref();
+            terms?
+                .into_iter()
+                .map(|term| {
+                    term.map(|term| {
+                        encoder.encode_term(term.as_ref()).map_err(|e| e.into())
+                    })
+                    .transpose()
+                })
+                .collect()
+        })))
+    }
+
+    /// Writes a solution followed by its position in the operator input
+    fn spill_numbered_tuple(
+        &self,
+        file: &mut SpillFile,
+        position: u64,
+        tuple: &EncodedTuple<S::StrId>,
+    ) -> Result<(), EvaluationError> {
+        let mut terms = tuple
+            .iter()
+            .map(|term| term.map(|term| self.dataset.decode_term(term)).transpose())
+            .collect::<Result<Vec<_>, _>>()?;
+        terms.push(Some(Literal::from(position).into()));
+        Ok(file.write(&terms)?)
+    }
+
+    /// Reads back the solutions written by [`spill_numbered_tuple`](Self::spill_numbered_tuple)
+    fn unspill_numbered_tuples(
+        &self,
+        file: SpillFile,
+    ) -> Result<NumberedTuplesIterator<S::StrId>, EvaluationError> {
+        let eval = self.clone();
+        Ok(Box::new(file.read()?.map(move |terms| {
+            let mut terms = terms?;
+            let position = match terms.pop() {
+                Some(Some(Term::Literal(position))) => position.value().parse().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| EvaluationError::msg("Invalid solution position in a temporary file"))?;
+            let mut encoder = eval.dataset.as_ref();
+            let tuple = terms
+                .into_iter()
+                .map(|term| {
+                    term.map(|term| encoder.encode_term(term.as_ref()).map_err(|e| e.into()))
+                        .transpose()
+                })
+                .collect::<Result<_, EvaluationError>>()?;
+            Ok((position, tuple))
+        })))
     }
 
     fn evaluate_service(
@@ -576,6 +1068,17 @@ where
                     Box::new(GroupConcatAccumulator::new(self.clone(), separator.clone()))
                 }
             }
+            PlanAggregationFunction::Custom(name) => {
+                let accumulator = CustomAccumulator::new(
+                    self.clone(),
+                    self.custom_aggregates.get(name).map(|factory| factory()),
+                );
+                if distinct {
+                    Box::new(DistinctAccumulator::new(accumulator))
+                } else {
+                    Box::new(accumulator)
+                }
+            }
         }
     }
 
@@ -607,9 +1110,11 @@ where
             PlanPropertyPath::ZeroOrMore(p) => {
                 let eval = self.clone();
                 let p = p.clone();
-                Box::new(transitive_closure(Some(Ok(start)), move |e| {
-                    eval.eval_path_from(&p, e, graph_name)
-                }))
+                Box::new(transitive_closure(
+                    Some(Ok(start)),
+                    move |e| eval.eval_path_from(&p, e, graph_name),
+                    self.interrupter.as_deref(),
+                ))
             }
             PlanPropertyPath::OneOrMore(p) => {
                 let eval = self.clone();
@@ -617,6 +1122,7 @@ where
                 Box::new(transitive_closure(
                     self.eval_path_from(&p, start, graph_name),
                     move |e| eval.eval_path_from(&p, e, graph_name),
+                    self.interrupter.as_deref(),
                 ))
             }
             PlanPropertyPath::ZeroOrOne(p) => Box::new(hash_deduplicate(
@@ -670,9 +1176,11 @@ where
             PlanPropertyPath::ZeroOrMore(p) => {
                 let eval = self.clone();
                 let p = p.clone();
-                Box::new(transitive_closure(Some(Ok(end)), move |e| {
-                    eval.eval_path_to(&p, e, graph_name)
-                }))
+                Box::new(transitive_closure(
+                    Some(Ok(end)),
+                    move |e| eval.eval_path_to(&p, e, graph_name),
+                    self.interrupter.as_deref(),
+                ))
             }
             PlanPropertyPath::OneOrMore(p) => {
                 let eval = self.clone();
@@ -680,6 +1188,7 @@ where
                 Box::new(transitive_closure(
                     self.eval_path_to(&p, end, graph_name),
                     move |e| eval.eval_path_to(&p, e, graph_name),
+                    self.interrupter.as_deref(),
                 ))
             }
             PlanPropertyPath::ZeroOrOne(p) => Box::new(hash_deduplicate(
@@ -748,6 +1257,7 @@ where
                         eval.eval_path_from(&p, middle, graph_name)
                             .map(move |end| Ok((start, end?)))
                     },
+                    self.interrupter.as_deref(),
                 ))
             }
             PlanPropertyPath::OneOrMore(p) => {
@@ -759,6 +1269,7 @@ where
                         eval.eval_path_from(&p, middle, graph_name)
                             .map(move |end| Ok((start, end?)))
                     },
+                    self.interrupter.as_deref(),
                 ))
             }
             PlanPropertyPath::ZeroOrOne(p) => Box::new(hash_deduplicate(
@@ -1410,6 +1921,35 @@ where
             PlanExpression::IsLiteral(e) => {
                 Some(self.eval_expression(e, tuple)?.is_literal().into())
             }
+            PlanExpression::Triple(subject, predicate, object) => {
+                let subject = self.eval_expression(subject, tuple)?;
+                let predicate = self.eval_expression(predicate, tuple)?;
+                let object = self.eval_expression(object, tuple)?;
+                if !(subject.is_named_node() || subject.is_blank_node() || subject.is_triple())
+                    || !predicate.is_named_node()
+                {
+                    return None;
+                }
+                let triple = decode_triple(&*self.dataset, subject, predicate, object).ok()?;
+                let mut encoder = self.dataset.as_ref();
+                encoder.encode_triple(&triple).ok()
+            }
+            PlanExpression::Subject(e) => {
+                let triple = self.decode_quoted_triple(self.eval_expression(e, tuple)?)?;
+                let mut encoder = self.dataset.as_ref();
+                encoder.encode_subject(triple.subject.as_ref()).ok()
+            }
+            PlanExpression::Predicate(e) => {
+                let triple = self.decode_quoted_triple(self.eval_expression(e, tuple)?)?;
+                let mut encoder = self.dataset.as_ref();
+                encoder.encode_named_node(triple.predicate.as_ref()).ok()
+            }
+            PlanExpression::Object(e) => {
+                let triple = self.decode_quoted_triple(self.eval_expression(e, tuple)?)?;
+                let mut encoder = self.dataset.as_ref();
+                encoder.encode_term(triple.object.as_ref()).ok()
+            }
+            PlanExpression::IsTriple(e) => Some(self.eval_expression(e, tuple)?.is_triple().into()),
             PlanExpression::IsNumeric(e) => Some(
                 matches!(self.eval_expression(e, tuple)?,
                     EncodedTerm::FloatLiteral(_)
@@ -1560,9 +2100,76 @@ where
                     self.to_string_id(self.eval_expression(e, tuple)?)?,
                 ))
             }
+            PlanExpression::GeoDistance(a, b, unit) => {
+                let a = self.to_geometry(self.eval_expression(a, tuple)?)?;
+                let b = self.to_geometry(self.eval_expression(b, tuple)?)?;
+                let unit = if let EncodedTerm::NamedNode { iri_id } =
+                    self.eval_expression(unit, tuple)?
+                {
+                    self.dataset.get_str(iri_id).ok()??
+                } else {
+                    return None;
+                };
+                Some(geo::distance(&a, &b, &unit)?.into())
+            }
+            PlanExpression::GeoEquals(a, b) => self.eval_geo_relation(a, b, tuple, geo::equals),
+            PlanExpression::GeoDisjoint(a, b) => self.eval_geo_relation(a, b, tuple, geo::disjoint),
+            PlanExpression::GeoIntersects(a, b) => {
+                self.eval_geo_relation(a, b, tuple, geo::intersects)
+            }
+            PlanExpression::GeoWithin(a, b) => self.eval_geo_relation(a, b, tuple, geo::within),
+            PlanExpression::GeoContains(a, b) => self.eval_geo_relation(a, b, tuple, geo::contains),
+            PlanExpression::CustomFunction(name, args) => {
+                let function = self.custom_functions.get(name)?;
+                let args = args
+                    .iter()
+                    .map(|e| self.dataset.decode_term(self.eval_expression(e, tuple)?).ok())
+                    .collect::<Option<Vec<_>>>()?;
+                let mut encoder = self.dataset.as_ref();
+                encoder.encode_term(function(&args)?.as_ref()).ok()
+            }
         }
     }
 
+    fn eval_geo_relation(
+        &self,
+        a: &PlanExpression<S::StrId>,
+        b: &PlanExpression<S::StrId>,
+        tuple: &EncodedTuple<S::StrId>,
+        relation: impl Fn(&Geometry, &Geometry) -> bool,
+    ) -> Option<EncodedTerm<S::StrId>> {
+        let a = self.to_geometry(self.eval_expression(a, tuple)?)?;
+        let b = self.to_geometry(self.eval_expression(b, tuple)?)?;
+        Some(relation(&a, &b).into())
+    }
+
+    fn to_geometry(&self, term: EncodedTerm<S::StrId>) -> Option<Geometry> {
+        let (value, datatype_id): (String, _) = match term {
+            EncodedTerm::SmallTypedLiteral { value, datatype_id } => {
+                (value.into(), datatype_id)
+            }
+            EncodedTerm::BigTypedLiteral {
+                value_id,
+                datatype_id,
+            } => (self.dataset.get_str(value_id).ok()??, datatype_id),
+            _ => return None,
+        };
+        if self.dataset.get_str(datatype_id).ok()?? == geo::WKT_LITERAL {
+            Geometry::from_wkt(&value)
+        } else {
+            None
+        }
+    }
+
+    fn decode_quoted_triple(&self, term: EncodedTerm<S::StrId>) -> Option<Triple> {
+        if let EncodedTerm::Triple { .. } = term {
+            if let Term::Triple(triple) = self.dataset.decode_term(term).ok()? {
+                return Some(*triple);
+            }
+        }
+        None
+    }
+
     fn to_bool(&self, term: EncodedTerm<S::StrId>) -> Option<bool> {
         match term {
             EncodedTerm::BooleanLiteral(value) => Some(value),
@@ -1584,7 +2191,8 @@ where
             EncodedTerm::NamedNode { iri_id } => Some(iri_id.into()),
             EncodedTerm::NumericalBlankNode { .. }
             | EncodedTerm::SmallBlankNode { .. }
-            | EncodedTerm::BigBlankNode { .. } => None,
+            | EncodedTerm::BigBlankNode { .. }
+            | EncodedTerm::Triple { .. } => None,
             EncodedTerm::SmallStringLiteral(value)
             | EncodedTerm::SmallSmallLangStringLiteral { value, .. }
             | EncodedTerm::SmallBigLangStringLiteral { value, .. }
@@ -1774,30 +2382,12 @@ where
     ) -> Option<Regex> {
         // TODO Avoid to compile the regex each time
         let pattern = self.to_simple_string(pattern)?;
-        let mut regex_builder = RegexBuilder::new(&pattern);
-        regex_builder.size_limit(REGEX_SIZE_LIMIT);
-        if let Some(flags) = flags {
-            let flags = self.to_simple_string(flags)?;
-            for flag in flags.chars() {
-                match flag {
-                    's' => {
-                        regex_builder.dot_matches_new_line(true);
-                    }
-                    'm' => {
-                        regex_builder.multi_line(true);
-                    }
-                    'i' => {
-                        regex_builder.case_insensitive(true);
-                    }
-                    'x' => {
-                        regex_builder.ignore_whitespace(true);
-                    }
-                    'q' => (), //TODO: implement
-                    _ => (),
-                }
-            }
-        }
-        regex_builder.build().ok()
+        let flags = if let Some(flags) = flags {
+            Some(self.to_simple_string(flags)?)
+        } else {
+            None
+        };
+        compile_pattern(&pattern, flags.as_deref())
     }
 
     fn parse_numeric_operands(
@@ -1870,7 +2460,8 @@ where
             | EncodedTerm::SmallSmallLangStringLiteral { .. }
             | EncodedTerm::SmallBigLangStringLiteral { .. }
             | EncodedTerm::BigSmallLangStringLiteral { .. }
-            | EncodedTerm::BigBigLangStringLiteral { .. } => Some(a == b),
+            | EncodedTerm::BigBigLangStringLiteral { .. }
+            | EncodedTerm::Triple { .. } => Some(a == b),
             EncodedTerm::SmallStringLiteral(a) => match b {
                 EncodedTerm::SmallStringLiteral(b) => Some(a == b),
                 EncodedTerm::SmallTypedLiteral { .. } | EncodedTerm::BigTypedLiteral { .. } => None,
@@ -1891,7 +2482,8 @@ where
                 | EncodedTerm::SmallBigLangStringLiteral { .. }
                 | EncodedTerm::BigSmallLangStringLiteral { .. }
                 | EncodedTerm::BigBigLangStringLiteral { .. }
-                | EncodedTerm::BigTypedLiteral { .. } => Some(false),
+                | EncodedTerm::BigTypedLiteral { .. }
+                | EncodedTerm::Triple { .. } => Some(false),
                 _ => None,
             },
             EncodedTerm::BigTypedLiteral { .. } => match b {
@@ -1904,7 +2496,8 @@ where
                 | EncodedTerm::SmallBigLangStringLiteral { .. }
                 | EncodedTerm::BigSmallLangStringLiteral { .. }
                 | EncodedTerm::BigBigLangStringLiteral { .. }
-                | EncodedTerm::SmallTypedLiteral { .. } => Some(false),
+                | EncodedTerm::SmallTypedLiteral { .. }
+                | EncodedTerm::Triple { .. } => Some(false),
                 _ => None,
             },
             EncodedTerm::BooleanLiteral(a) => match b {
@@ -2050,7 +2643,7 @@ where
     }
 
     #[allow(clippy::cast_precision_loss)]
-    fn partial_cmp_literals(
+    pub fn partial_cmp_literals(
         &self,
         a: EncodedTerm<S::StrId>,
         b: EncodedTerm<S::StrId>,
@@ -2206,6 +2799,7 @@ where
             | EncodedTerm::SmallBlankNode { .. }
             | EncodedTerm::BigBlankNode { .. }
             | EncodedTerm::NumericalBlankNode { .. }
+            | EncodedTerm::Triple { .. }
             | EncodedTerm::DefaultGraph => None,
             EncodedTerm::SmallStringLiteral(_) | EncodedTerm::BigStringLiteral { .. } => {
                 self.build_named_node(xsd::STRING.as_str())
@@ -2385,6 +2979,33 @@ impl NumericBinaryOperands {
     }
 }
 
+/// Builds a regular expression following the semantic of the SPARQL `REGEX` function
+pub(crate) fn compile_pattern(pattern: &str, flags: Option<&str>) -> Option<Regex> {
+    let mut regex_builder = RegexBuilder::new(pattern);
+    regex_builder.size_limit(REGEX_SIZE_LIMIT);
+    if let Some(flags) = flags {
+        for flag in flags.chars() {
+            match flag {
+                's' => {
+                    regex_builder.dot_matches_new_line(true);
+                }
+                'm' => {
+                    regex_builder.multi_line(true);
+                }
+                'i' => {
+                    regex_builder.case_insensitive(true);
+                }
+                'x' => {
+                    regex_builder.ignore_whitespace(true);
+                }
+                'q' => (), //TODO: implement
+                _ => (),
+            }
+        }
+    }
+    regex_builder.build().ok()
+}
+
 fn get_pattern_value<I: StrId>(
     selector: &PatternValue<I>,
     tuple: &EncodedTuple<I>,
@@ -2406,6 +3027,24 @@ fn put_pattern_value<I: StrId>(
     }
 }
 
+fn unify_pattern_value<I: StrId>(
+    selector: &PatternValue<I>,
+    value: EncodedTerm<I>,
+    tuple: &mut EncodedTuple<I>,
+) -> bool {
+    match selector {
+        PatternValue::Constant(term) => *term == value,
+        PatternValue::Variable(v) => {
+            if let Some(old) = tuple.get(*v) {
+                old == value
+            } else {
+                tuple.set(*v, value);
+                true
+            }
+        }
+    }
+}
+
 fn put_variable_value<I: StrId>(
     selector: &Variable,
     variables: &[Variable],
@@ -2488,6 +3127,149 @@ impl<I: StrId> Iterator for JoinIterator<I> {
     }
 }
 
+struct ForLoopJoinIterator<S: ReadableEncodedStore + 'static> {
+    eval: SimpleEvaluator<S>,
+    right_plan: Rc<PlanNode<S::StrId>>,
+    left_iter: EncodedTuplesIterator<S::StrId>,
+    current_right: EncodedTuplesIterator<S::StrId>,
+}
+
+impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Iterator for ForLoopJoinIterator<S>
+where
+    for<'a> &'a S: StrContainer<StrId = S::StrId>,
+{
+    type Item = Result<EncodedTuple<S::StrId>, EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<EncodedTuple<S::StrId>, EvaluationError>> {
+        loop {
+            if let Some(tuple) = self.current_right.next() {
+                return Some(tuple);
+            }
+            match self.left_iter.next()? {
+                Ok(left_tuple) => {
+                    self.current_right = self.eval.eval_plan(&self.right_plan, left_tuple);
+                }
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// A set of tuples indexed by the values of some of their variables
+struct EncodedTupleSet<I: StrId> {
+    keys: Rc<Vec<usize>>,
+    map: HashMap<Vec<EncodedTerm<I>>, Vec<EncodedTuple<I>>>,
+    unkeyed: Vec<EncodedTuple<I>>, // tuples with some key variables unbound
+}
+
+impl<I: StrId> EncodedTupleSet<I> {
+    fn new(keys: Rc<Vec<usize>>) -> Self {
+        Self {
+            keys,
+            map: HashMap::default(),
+            unkeyed: Vec::default(),
+        }
+    }
+
+    fn insert(&mut self, tuple: EncodedTuple<I>) {
+        if let Some(key) = self.tuple_key(&tuple) {
+            self.map.entry(key).or_default().push(tuple);
+        } else {
+            self.unkeyed.push(tuple);
+        }
+    }
+
+    /// Returns the tuples that might be compatible with the given tuple
+    fn get(&self, tuple: &EncodedTuple<I>) -> Vec<&EncodedTuple<I>> {
+        if let Some(key) = self.tuple_key(tuple) {
+            self.map
+                .get(&key)
+                .into_iter()
+                .flatten()
+                .chain(&self.unkeyed)
+                .collect()
+        } else {
+            self.map.values().flatten().chain(&self.unkeyed).collect()
+        }
+    }
+
+    fn tuple_key(&self, tuple: &EncodedTuple<I>) -> Option<Vec<EncodedTerm<I>>> {
+        self.keys.iter().map(|v| tuple.get(*v)).collect()
+    }
+}
+
+struct HashJoinIterator<I: StrId> {
+    left_iter: EncodedTuplesIterator<I>,
+    right: EncodedTupleSet<I>,
+    buffered_results: Vec<Result<EncodedTuple<I>, EvaluationError>>,
+}
+
+impl<I: StrId> Iterator for HashJoinIterator<I> {
+    type Item = Result<EncodedTuple<I>, EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<EncodedTuple<I>, EvaluationError>> {
+        loop {
+            if let Some(result) = self.buffered_results.pop() {
+                return Some(result);
+            }
+            let left_tuple = match self.left_iter.next()? {
+                Ok(left_tuple) => left_tuple,
+                Err(error) => return Some(Err(error)),
+            };
+            for right_tuple in self.right.get(&left_tuple) {
+                if let Some(result_tuple) = left_tuple.combine_with(right_tuple) {
+                    self.buffered_results.push(Ok(result_tuple))
+                }
+            }
+        }
+    }
+}
+
+struct HashLeftJoinIterator<S: ReadableEncodedStore + 'static> {
+    eval: SimpleEvaluator<S>,
+    left_iter: EncodedTuplesIterator<S::StrId>,
+    right: EncodedTupleSet<S::StrId>,
+    expression: Option<Rc<PlanExpression<S::StrId>>>,
+    buffered_results: Vec<Result<EncodedTuple<S::StrId>, EvaluationError>>,
+}
+
+impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Iterator for HashLeftJoinIterator<S>
+where
+    for<'a> &'a S: StrContainer<StrId = S::StrId>,
+{
+    type Item = Result<EncodedTuple<S::StrId>, EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<EncodedTuple<S::StrId>, EvaluationError>> {
+        if let Some(result) = self.buffered_results.pop() {
+            return Some(result);
+        }
+        let left_tuple = match self.left_iter.next()? {
+            Ok(left_tuple) => left_tuple,
+            Err(error) => return Some(Err(error)),
+        };
+        for right_tuple in self.right.get(&left_tuple) {
+            if let Some(result_tuple) = left_tuple.combine_with(right_tuple) {
+                let is_kept = if let Some(expression) = &self.expression {
+                    self.eval
+                        .eval_expression(expression, &result_tuple)
+                        .and_then(|term| self.eval.to_bool(term))
+                        .unwrap_or(false)
+                } else {
+                    true
+                };
+                if is_kept {
+                    self.buffered_results.push(Ok(result_tuple))
+                }
+            }
+        }
+        if let Some(result) = self.buffered_results.pop() {
+            Some(result)
+        } else {
+            Some(Ok(left_tuple))
+        }
+    }
+}
+
 struct AntiJoinIterator<I: StrId> {
     left_iter: EncodedTuplesIterator<I>,
     right: Vec<EncodedTuple<I>>,
@@ -2637,7 +3419,10 @@ struct ConstructIterator<S: ReadableEncodedStore + 'static> {
     bnodes: Vec<EncodedTerm<S::StrId>>,
 }
 
-impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Iterator for ConstructIterator<S> {
+impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Iterator for ConstructIterator<S>
+where
+    for<'a> &'a S: StrContainer<StrId = S::StrId>,
+{
     type Item = Result<Triple, EvaluationError>;
 
     fn next(&mut self) -> Option<Result<Triple, EvaluationError>> {
@@ -2652,9 +3437,24 @@ impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Iterator for Co
                 };
                 for template in &self.template {
                     if let (Some(subject), Some(predicate), Some(object)) = (
-                        get_triple_template_value(&template.subject, &tuple, &mut self.bnodes),
-                        get_triple_template_value(&template.predicate, &tuple, &mut self.bnodes),
-                        get_triple_template_value(&template.object, &tuple, &mut self.bnodes),
+                        get_triple_template_value(
+                            &template.subject,
+                            &tuple,
+                            &mut self.bnodes,
+                            &*self.eval.dataset,
+                        ),
+                        get_triple_template_value(
+                            &template.predicate,
+                            &tuple,
+                            &mut self.bnodes,
+                            &*self.eval.dataset,
+                        ),
+                        get_triple_template_value(
+                            &template.object,
+                            &tuple,
+                            &mut self.bnodes,
+                            &*self.eval.dataset,
+                        ),
                     ) {
                         self.buffered_results.push(decode_triple(
                             &*self.eval.dataset,
@@ -2670,11 +3470,15 @@ impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Iterator for Co
     }
 }
 
-fn get_triple_template_value<I: StrId>(
-    selector: &TripleTemplateValue<I>,
-    tuple: &EncodedTuple<I>,
-    bnodes: &mut Vec<EncodedTerm<I>>,
-) -> Option<EncodedTerm<I>> {
+fn get_triple_template_value<S: ReadableEncodedStore<Error = EvaluationError>>(
+    selector: &TripleTemplateValue<S::StrId>,
+    tuple: &EncodedTuple<S::StrId>,
+    bnodes: &mut Vec<EncodedTerm<S::StrId>>,
+    dataset: &S,
+) -> Option<EncodedTerm<S::StrId>>
+where
+    for<'a> &'a S: StrContainer<StrId = S::StrId>,
+{
     match selector {
         TripleTemplateValue::Constant(term) => Some(*term),
         TripleTemplateValue::Variable(v) => tuple.get(*v),
@@ -2684,6 +3488,14 @@ fn get_triple_template_value<I: StrId>(
             }
             Some(bnodes[*id])
         }
+        TripleTemplateValue::Triple(triple) => {
+            let subject = get_triple_template_value(&triple.subject, tuple, bnodes, dataset)?;
+            let predicate = get_triple_template_value(&triple.predicate, tuple, bnodes, dataset)?;
+            let object = get_triple_template_value(&triple.object, tuple, bnodes, dataset)?;
+            let triple = decode_triple(dataset, subject, predicate, object).ok()?;
+            let mut encoder = dataset;
+            encoder.encode_triple(&triple).ok()
+        }
     }
 }
 
@@ -2698,7 +3510,7 @@ fn decode_triple<D: Decoder>(
     object: EncodedTerm<D::StrId>,
 ) -> Result<Triple, EvaluationError> {
     Ok(Triple::new(
-        decoder.decode_named_or_blank_node(subject)?,
+        decoder.decode_subject(subject)?,
         decoder.decode_named_node(predicate)?,
         decoder.decode_term(object)?,
     ))
@@ -2775,43 +3587,40 @@ impl<T1, T2, I1: Iterator<Item = T1>, I2: Iterator<Item = T2>> Iterator
 fn transitive_closure<T: Copy + Eq + Hash, NI: Iterator<Item = Result<T, EvaluationError>>>(
     start: impl IntoIterator<Item = Result<T, EvaluationError>>,
     next: impl Fn(T) -> NI,
+    interrupter: Option<&Interrupter>,
 ) -> impl Iterator<Item = Result<T, EvaluationError>> {
     //TODO: optimize
     let mut all = HashSet::<T>::default();
     let mut errors = Vec::default();
-    let mut current = start
-        .into_iter()
-        .filter_map(|e| match e {
-            Ok(e) => {
-                all.insert(e);
-                Some(e)
-            }
-            Err(error) => {
-                errors.push(error);
-                None
+    let mut current = Vec::default();
+    let mut add = |e: Result<T, EvaluationError>, current: &mut Vec<T>| match e {
+        Ok(e) => {
+            if all.insert(e) {
+                current.push(e);
             }
-        })
-        .collect::<Vec<_>>();
-
-    while !current.is_empty() {
-        current = current
-            .into_iter()
-            .flat_map(|e| next(e))
-            .filter_map(|e| match e {
-                Ok(e) => {
-                    if all.contains(&e) {
-                        None
-                    } else {
-                        all.insert(e);
-                        Some(e)
-                    }
-                }
-                Err(error) => {
-                    errors.push(error);
-                    None
+        }
+        Err(error) => errors.push(error),
+    };
+    for e in start {
+        add(e, &mut current);
+    }
+
+    'closure: while !current.is_empty() {
+        let mut next_current = Vec::default();
+        for e in current {
+            if let Some(interrupter) = interrupter {
+                if let Err(error) = interrupter.check() {
+                    // We stop everything and only return the interruption error
+                    all.clear();
+                    errors = vec![error];
+                    break 'closure;
                 }
-            })
-            .collect();
+            }
+            for e in next(e) {
+                add(e, &mut next_current);
+            }
+        }
+        current = next_current;
     }
     errors.into_iter().map(Err).chain(all.into_iter().map(Ok))
 }
@@ -2834,6 +3643,178 @@ fn hash_deduplicate<T: Eq + Hash + Clone>(
     })
 }
 
+/// Estimates the memory used by a buffered solution
+fn tuple_memory_size<I: StrId>(tuple: &EncodedTuple<I>) -> usize {
+    size_of::<EncodedTuple<I>>() + tuple.capacity() * size_of::<Option<EncodedTerm<I>>>()
+}
+
+/// Estimates the memory used by a group of an aggregation
+fn group_memory_size<I: StrId>(key: &[Option<EncodedTerm<I>>], aggregates_count: usize) -> usize {
+    size_of::<Vec<Option<EncodedTerm<I>>>>()
+        + size_of_val(key)
+        + aggregates_count * ACCUMULATOR_MEMORY_SIZE
+}
+
+/// Merges sorted iterators of solutions
+///
+/// The ties are returned in the order of the iterators.
+struct SortMergeIterator<S: ReadableEncodedStore + 'static> {
+    eval: SimpleEvaluator<S>,
+    by: Vec<Comparator<S::StrId>>,
+    sources: Vec<Fuse<EncodedTuplesIterator<S::StrId>>>,
+    heads: Vec<Option<EncodedTuple<S::StrId>>>,
+}
+
+impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Iterator for SortMergeIterator<S>
+where
+    for<'a> &'a S: StrContainer<StrId = S::StrId>,
+{
+    type Item = Result<EncodedTuple<S::StrId>, EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<EncodedTuple<S::StrId>, EvaluationError>> {
+        for (source, head) in self.sources.iter_mut().zip(&mut self.heads) {
+            if head.is_none() {
+                match source.next() {
+                    Some(Ok(tuple)) => *head = Some(tuple),
+                    Some(Err(error)) => return Some(Err(error)),
+                    None => (),
+                }
+            }
+        }
+        let mut min: Option<(usize, &EncodedTuple<S::StrId>)> = None;
+        for (i, head) in self.heads.iter().enumerate() {
+            if let Some(head) = head {
+                let is_smaller = match min {
+                    Some((_, min)) => self.eval.cmp_tuples(head, min, &self.by) == Ordering::Less,
+                    None => true,
+                };
+                if is_smaller {
+                    min = Some((i, head));
+                }
+            }
+        }
+        let (min, _) = min?;
+        self.heads[min].take().map(Ok)
+    }
+}
+
+/// Same as [`hash_deduplicate`] but splitting the solutions not already seen into temporary files
+/// when the memory limit is reached and deduplicating each of these files afterwards.
+///
+/// The solutions are numbered with their input position to keep the input order:
+/// each file is deduplicated in input order and the files outputs are merged back according to the positions.
+struct SpillingHashDeduplicateIterator<S: ReadableEncodedStore + 'static> {
+    eval: SimpleEvaluator<S>,
+    memory_limit: Rc<MemoryLimit>,
+    iter: NumberedTuplesIterator<S::StrId>,
+    already_seen: HashSet<EncodedTuple<S::StrId>>,
+    already_seen_size: usize,
+    partitions: Vec<SpillFile>,
+    depth: usize,
+    output: Option<PositionMergeIterator<S::StrId>>,
+}
+
+impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Iterator
+    for SpillingHashDeduplicateIterator<S>
+where
+    for<'a> &'a S: StrContainer<StrId = S::StrId>,
+{
+    type Item = Result<(u64, EncodedTuple<S::StrId>), EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<(u64, EncodedTuple<S::StrId>), EvaluationError>> {
+        loop {
+            if let Some(output) = &mut self.output {
+                return output.next();
+            }
+            match self.iter.next() {
+                Some(Ok((position, tuple))) => {
+                    if self.already_seen.contains(&tuple) {
+                        continue;
+                    }
+                    if self.partitions.is_empty() {
+                        let tuple_size = tuple_memory_size(&tuple);
+                        if self.already_seen_size + tuple_size <= self.memory_limit.bytes()
+                            || self.already_seen.is_empty()
+                        {
+                            self.already_seen_size += tuple_size;
+                            self.already_seen.insert(tuple.clone());
+                            return Some(Ok((position, tuple)));
+                        }
+                        self.partitions = match self.memory_limit.spill_partitions() {
+                            Ok(partitions) => partitions,
+                            Err(error) => return Some(Err(error)),
+                        };
+                    }
+                    let partition = &mut self.partitions[spill_partition(&tuple, self.depth)];
+                    if let Err(error) = self.eval.spill_numbered_tuple(partition, position, &tuple)
+                    {
+                        return Some(Err(error));
+                    }
+                }
+                Some(Err(error)) => return Some(Err(error)),
+                None => {
+                    if self.partitions.is_empty() {
+                        return None;
+                    }
+                    // The solutions already returned can't be in the partitions
+                    self.already_seen = HashSet::default();
+                    let mut sources = Vec::with_capacity(self.partitions.len());
+                    for partition in take(&mut self.partitions) {
+                        let source: NumberedTuplesIterator<S::StrId> =
+                            match self.eval.unspill_numbered_tuples(partition) {
+                                Ok(iter) => Box::new(SpillingHashDeduplicateIterator {
+                                    eval: self.eval.clone(),
+                                    memory_limit: self.memory_limit.clone(),
+                                    iter,
+                                    already_seen: HashSet::default(),
+                                    already_seen_size: 0,
+                                    partitions: Vec::default(),
+                                    depth: self.depth + 1,
+                                    output: None,
+                                }),
+                                Err(error) => Box::new(once(Err(error))),
+                            };
+                        sources.push(source);
+                    }
+                    self.output = Some(PositionMergeIterator {
+                        heads: vec![None; sources.len()],
+                        sources,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Merges iterators of numbered solutions each sorted by position
+struct PositionMergeIterator<I: StrId> {
+    sources: Vec<NumberedTuplesIterator<I>>,
+    heads: Vec<Option<(u64, EncodedTuple<I>)>>,
+}
+
+impl<I: StrId> Iterator for PositionMergeIterator<I> {
+    type Item = Result<(u64, EncodedTuple<I>), EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<(u64, EncodedTuple<I>), EvaluationError>> {
+        for (source, head) in self.sources.iter_mut().zip(&mut self.heads) {
+            if head.is_none() {
+                match source.next() {
+                    Some(Ok(tuple)) => *head = Some(tuple),
+                    Some(Err(error)) => return Some(Err(error)),
+                    None => (),
+                }
+            }
+        }
+        let (min, _) = self
+            .heads
+            .iter()
+            .enumerate()
+            .filter_map(|(i, head)| Some((i, head.as_ref()?.0)))
+            .min_by_key(|(_, position)| *position)?;
+        self.heads[min].take().map(Ok)
+    }
+}
+
 trait ResultIterator<T>: Iterator<Item = Result<T, EvaluationError>> + Sized {
     fn flat_map_ok<O, F: FnMut(T) -> U, U: IntoIterator<Item = Result<O, EvaluationError>>>(
         self,
@@ -2895,7 +3876,7 @@ impl<
 trait Accumulator<I: StrId> {
     fn add(&mut self, element: Option<EncodedTerm<I>>);
 
-    fn state(&self) -> Option<EncodedTerm<I>>;
+    fn state(&mut self) -> Option<EncodedTerm<I>>;
 }
 
 #[derive(Default, Debug)]
@@ -2920,7 +3901,7 @@ impl<I: StrId, T: Accumulator<I>> Accumulator<I> for DistinctAccumulator<I, T> {
         }
     }
 
-    fn state(&self) -> Option<EncodedTerm<I>> {
+    fn state(&mut self) -> Option<EncodedTerm<I>> {
         self.inner.state()
     }
 }
@@ -2935,7 +3916,7 @@ impl<I: StrId> Accumulator<I> for CountAccumulator {
         self.count += 1;
     }
 
-    fn state(&self) -> Option<EncodedTerm<I>> {
+    fn state(&mut self) -> Option<EncodedTerm<I>> {
         Some(self.count.into())
     }
 }
@@ -2972,7 +3953,7 @@ impl<I: StrId> Accumulator<I> for SumAccumulator<I> {
         }
     }
 
-    fn state(&self) -> Option<EncodedTerm<I>> {
+    fn state(&mut self) -> Option<EncodedTerm<I>> {
         self.sum
     }
 }
@@ -2998,7 +3979,7 @@ impl<I: StrId> Accumulator<I> for AvgAccumulator<I> {
         self.count.add(element);
     }
 
-    fn state(&self) -> Option<EncodedTerm<I>> {
+    fn state(&mut self) -> Option<EncodedTerm<I>> {
         let sum = self.sum.state()?;
         let count = self.count.state()?;
         if count == EncodedTerm::from(0) {
@@ -3046,7 +4027,7 @@ where
         }
     }
 
-    fn state(&self) -> Option<EncodedTerm<S::StrId>> {
+    fn state(&mut self) -> Option<EncodedTerm<S::StrId>> {
         self.min.and_then(|v| v)
     }
 }
@@ -3078,7 +4059,7 @@ where
         }
     }
 
-    fn state(&self) -> Option<EncodedTerm<S::StrId>> {
+    fn state(&mut self) -> Option<EncodedTerm<S::StrId>> {
         self.max.and_then(|v| v)
     }
 }
@@ -3101,7 +4082,7 @@ impl<I: StrId> Accumulator<I> for SampleAccumulator<I> {
         }
     }
 
-    fn state(&self) -> Option<EncodedTerm<I>> {
+    fn state(&mut self) -> Option<EncodedTerm<I>> {
         self.value
     }
 }
@@ -3148,7 +4129,7 @@ where
         }
     }
 
-    fn state(&self) -> Option<EncodedTerm<S::StrId>> {
+    fn state(&mut self) -> Option<EncodedTerm<S::StrId>> {
         self.concat.as_ref().and_then(|result| {
             self.eval
                 .build_plain_literal(result, self.language.and_then(|v| v))
@@ -3156,6 +4137,41 @@ where
     }
 }
 
+struct CustomAccumulator<S: ReadableEncodedStore + 'static> {
+    eval: SimpleEvaluator<S>,
+    /// `None` if the evaluation of an element has failed
+    inner: Option<Box<dyn AggregateFunctionAccumulator>>,
+}
+
+impl<S: ReadableEncodedStore + 'static> CustomAccumulator<S> {
+    fn new(eval: SimpleEvaluator<S>, inner: Option<Box<dyn AggregateFunctionAccumulator>>) -> Self {
+        Self { eval, inner }
+    }
+}
+
+impl<S: ReadableEncodedStore<Error = EvaluationError> + 'static> Accumulator<S::StrId>
+    for CustomAccumulator<S>
+where
+    for<'a> &'a S: StrContainer<StrId = S::StrId>,
+{
+    fn add(&mut self, element: Option<EncodedTerm<S::StrId>>) {
+        if self.inner.is_none() {
+            return;
+        }
+        let element = element.and_then(|e| self.eval.dataset.decode_term(e).ok());
+        match (&mut self.inner, element) {
+            (Some(inner), Some(element)) => inner.add(element),
+            _ => self.inner = None,
+        }
+    }
+
+    fn state(&mut self) -> Option<EncodedTerm<S::StrId>> {
+        let result = self.inner.as_mut()?.finish()?;
+        let mut encoder = self.eval.dataset.as_ref();
+        encoder.encode_term(result.as_ref()).ok()
+    }
+}
+
 fn generate_uuid(buffer: &mut String) {
     let mut uuid = random::<u128>().to_ne_bytes();
     uuid[6] = (uuid[6] & 0x0F) | 0x40;
@@ -0,0 +1,930 @@
+use crate::json::JsonValue;
+use crate::model::vocab::xsd;
+use crate::sparql::error::EvaluationError;
+use crate::sparql::model::Variable;
+use crate::sparql::plan::*;
+use crate::store::numeric_encoder::{Decoder, EncodedTerm, StrId};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::io::Write;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// The physical evaluation plan of a SPARQL query.
+///
+/// It is returned by the stores `explain` and `explain_opt` methods.
+/// It could be written as an indented text tree using its [`Display`](std::fmt::Display) implementation
+/// or as JSON using [`write_json`](QueryExplanation::write_json()).
+///
+/// The evaluation plan is not stable and might change between Oxigraph versions.
+///
+/// ```
+/// use oxigraph::MemoryStore;
+///
+/// let store = MemoryStore::new();
+/// let explanation = store.explain("SELECT ?s WHERE { ?s ?p ?o }")?;
+/// assert_eq!(explanation.root().operator(), "Project");
+/// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct QueryExplanation {
+    root: ExplanationNode,
+}
+
+impl QueryExplanation {
+    /// The root operator of the plan
+    #[inline]
+    pub fn root(&self) -> &ExplanationNode {
+        &self.root
+    }
+
+    /// Writes the plan as a JSON object.
+    ///
+    /// Each operator is an object with an `operator` and a `description` key,
+    /// the `rows`, `evaluations` and `duration` (in seconds) keys if the statistics have been collected
+    /// and a `children` key containing the child operators.
+    pub fn write_json(&self, mut writer: impl Write) -> io::Result<()> {
+        self.root.to_json().write(&mut writer)
+    }
+}
+
+impl fmt::Display for QueryExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.fmt_indented(f, 0)
+    }
+}
+
+/// An operator of a [`QueryExplanation`].
+#[derive(Debug, Clone)]
+pub struct ExplanationNode {
+    operator: &'static str,
+    description: String,
+    children: Vec<ExplanationNode>,
+    stats: Option<NodeStats>,
+}
+
+impl ExplanationNode {
+    /// The operator name like `QuadPatternJoin` or `Filter`
+    #[inline]
+    pub fn operator(&self) -> &str {
+        self.operator
+    }
+
+    /// A human readable description of the operator parameters like the evaluated triple pattern
+    #[inline]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// The child operators.
+    ///
+    /// For basic graph patterns the `QuadPatternJoin` operators are chained:
+    /// the triple pattern evaluated first is the deepest one.
+    #[inline]
+    pub fn children(&self) -> &[ExplanationNode] {
+        &self.children
+    }
+
+    /// The number of rows returned by the operator, if the statistics have been collected
+    #[inline]
+    pub fn rows(&self) -> Option<u64> {
+        self.stats.map(|s| s.rows)
+    }
+
+    /// The number of times the operator has been evaluated, if the statistics have been collected.
+    ///
+    /// For example the right side of a join is evaluated once per left row.
+    #[inline]
+    pub fn evaluations(&self) -> Option<u64> {
+        self.stats.map(|s| s.evaluations)
+    }
+
+    /// The time spent evaluating this operator including its children, if the statistics have been collected
+    #[inline]
+    pub fn duration(&self) -> Option<Duration> {
+        self.stats.map(|s| s.duration)
+    }
+
+    fn fmt_indented(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.operator, indent = indent)?;
+        if !self.description.is_empty() {
+            write!(f, " {}", self.description)?;
+        }
+        if let Some(stats) = &self.stats {
+            write!(
+                f,
+                " [rows: {}, evaluations: {}, duration: {:?}]",
+                stats.rows, stats.evaluations, stats.duration
+            )?;
+        }
+        writeln!(f)?;
+        for child in &self.children {
+            child.fmt_indented(f, indent + 2)?;
+        }
+        Ok(())
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut object = vec![
+            (
+                "operator".to_owned(),
+                JsonValue::String(self.operator.to_owned()),
+            ),
+            (
+                "description".to_owned(),
+                JsonValue::String(self.description.clone()),
+            ),
+        ];
+        if let Some(stats) = &self.stats {
+            object.push(("rows".to_owned(), JsonValue::Number(stats.rows.to_string())));
+            object.push((
+                "evaluations".to_owned(),
+                JsonValue::Number(stats.evaluations.to_string()),
+            ));
+            object.push((
+                "duration".to_owned(),
+                JsonValue::Number(stats.duration.as_secs_f64().to_string()),
+            ));
+        }
+        object.push((
+            "children".to_owned(),
+            JsonValue::Array(self.children.iter().map(|c| c.to_json()).collect()),
+        ));
+        JsonValue::Object(object)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct NodeStats {
+    rows: u64,
+    evaluations: u64,
+    duration: Duration,
+}
+
+/// Statistics collected during the evaluation, indexed by plan node address
+#[derive(Default)]
+pub(crate) struct EvaluationStats {
+    nodes: RefCell<HashMap<usize, NodeStats>>,
+}
+
+impl EvaluationStats {
+    fn key<I: StrId>(node: &PlanNode<I>) -> usize {
+        let node: *const PlanNode<I> = node;
+        node as usize
+    }
+
+    pub fn record_evaluation<I: StrId>(&self, node: &PlanNode<I>, duration: Duration) {
+        let mut nodes = self.nodes.borrow_mut();
+        let stats = nodes.entry(Self::key(node)).or_default();
+        stats.evaluations += 1;
+        stats.duration += duration;
+    }
+
+    fn record_row(&self, key: usize, is_row: bool, duration: Duration) {
+        let mut nodes = self.nodes.borrow_mut();
+        let stats = nodes.entry(key).or_default();
+        if is_row {
+            stats.rows += 1;
+        }
+        stats.duration += duration;
+    }
+
+    fn get<I: StrId>(&self, node: &PlanNode<I>) -> NodeStats {
+        self.nodes
+            .borrow()
+            .get(&Self::key(node))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Counts the rows returned by a plan node and the time spent computing them
+pub(crate) struct StatsIterator<I> {
+    iter: I,
+    key: usize,
+    stats: Rc<EvaluationStats>,
+}
+
+impl<I> StatsIterator<I> {
+    pub fn new<T: StrId>(iter: I, node: &PlanNode<T>, stats: Rc<EvaluationStats>) -> Self {
+        Self {
+            iter,
+            key: EvaluationStats::key(node),
+            stats,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for StatsIterator<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let start = Instant::now();
+        let result = self.iter.next();
+        self.stats
+            .record_row(self.key, result.is_some(), start.elapsed());
+        result
+    }
+}
+
+/// Builds a [`QueryExplanation`] from an evaluation plan
+pub(crate) struct ExplanationBuilder<'a, D> {
+    decoder: &'a D,
+    stats: Option<&'a EvaluationStats>,
+}
+
+impl<'a, D: Decoder<Error = EvaluationError>> ExplanationBuilder<'a, D> {
+    pub fn new(decoder: &'a D, stats: Option<&'a EvaluationStats>) -> Self {
+        Self { decoder, stats }
+    }
+
+    pub fn build(
+        &self,
+        plan: &PlanNode<D::StrId>,
+        variables: &[Variable],
+    ) -> Result<QueryExplanation, EvaluationError> {
+        Ok(QueryExplanation {
+            root: self.build_node(plan, variables)?,
+        })
+    }
+
+    fn build_node(
+        &self,
+        node: &PlanNode<D::StrId>,
+        variables: &[Variable],
+    ) -> Result<ExplanationNode, EvaluationError> {
+        let mut children = Vec::new();
+        let (operator, description) = match node {
+            PlanNode::Init => ("Init", String::new()),
+            PlanNode::StaticBindings { tuples } => {
+                ("StaticBindings", format!("{} rows", tuples.len()))
+            }
+            PlanNode::Service {
+                service_name,
+                silent,
+                child,
+                ..
+            } => {
+                children.push(self.build_node(child, variables)?);
+                (
+                    "Service",
+                    if *silent {
+                        format!("SILENT {}", self.pattern_value(service_name, variables)?)
+                    } else {
+                        self.pattern_value(service_name, variables)?
+                    },
+                )
+            }
+            PlanNode::QuadPatternJoin {
+                child,
+                subject,
+                predicate,
+                object,
+                graph_name,
+            } => {
+                children.push(self.build_node(child, variables)?);
+                (
+                    "QuadPatternJoin",
+                    format!(
+                        "{} {} {} {}",
+                        self.pattern_value(subject, variables)?,
+                        self.pattern_value(predicate, variables)?,
+                        self.pattern_value(object, variables)?,
+                        self.pattern_value(graph_name, variables)?
+                    ),
+                )
+            }
+            PlanNode::QuotedTripleMatch {
+                child,
+                triple,
+                subject,
+                predicate,
+                object,
+            } => {
+                children.push(self.build_node(child, variables)?);
+                (
+                    "QuotedTripleMatch",
+                    format!(
+                        "{} = << {} {} {} >>",
+                        self.pattern_value(triple, variables)?,
+                        self.pattern_value(subject, variables)?,
+                        self.pattern_value(predicate, variables)?,
+                        self.pattern_value(object, variables)?
+                    ),
+                )
+            }
+            PlanNode::PathPatternJoin {
+                child,
+                subject,
+                path,
+                object,
+                graph_name,
+            } => {
+                children.push(self.build_node(child, variables)?);
+                (
+                    "PathPatternJoin",
+                    format!(
+                        "{} {} {} {}",
+                        self.pattern_value(subject, variables)?,
+                        self.path(path)?,
+                        self.pattern_value(object, variables)?,
+                        self.pattern_value(graph_name, variables)?
+                    ),
+                )
+            }
+            PlanNode::TextSearch {
+                child,
+                subject,
+                property,
+                literal,
+                score,
+                query,
+                limit,
+                graph_name,
+            } => {
+                children.push(self.build_node(child, variables)?);
+                let mut description = format!(
+                    "{} {}",
+                    self.pattern_value(subject, variables)?,
+                    self.pattern_value(query, variables)?
+                );
+                if let Some(property) = property {
+                    description.push_str(&format!(" property {}", self.term(*property)?));
+                }
+                if let Some(literal) = literal {
+                    description.push_str(&format!(" literal {}", variable(*literal, variables)));
+                }
+                if let Some(score) = score {
+                    description.push_str(&format!(" score {}", variable(*score, variables)));
+                }
+                if let Some(limit) = limit {
+                    description.push_str(&format!(" limit {}", limit));
+                }
+                description.push(' ');
+                description.push_str(&self.pattern_value(graph_name, variables)?);
+                ("TextSearch", description)
+            }
+            PlanNode::Join { left, right } => {
+                children.push(self.build_node(left, variables)?);
+                children.push(self.build_node(right, variables)?);
+                ("Join", String::new())
+            }
+            PlanNode::ForLoopJoin { left, right } => {
+                children.push(self.build_node(left, variables)?);
+                children.push(self.build_node(right, variables)?);
+                ("ForLoopJoin", String::new())
+            }
+            PlanNode::HashJoin { left, right, keys } => {
+                children.push(self.build_node(left, variables)?);
+                children.push(self.build_node(right, variables)?);
+                ("HashJoin", variables_list(keys, variables))
+            }
+            PlanNode::AntiJoin { left, right } => {
+                children.push(self.build_node(left, variables)?);
+                children.push(self.build_node(right, variables)?);
+                ("AntiJoin", String::new())
+            }
+            PlanNode::Filter { child, expression } => {
+                children.push(self.build_node(child, variables)?);
+                (
+                    "Filter",
+                    self.expression(expression, variables, &mut children)?,
+                )
+            }
+            PlanNode::Union { children: c } => {
+                for child in c {
+                    children.push(self.build_node(child, variables)?);
+                }
+                ("Union", String::new())
+            }
+            PlanNode::LeftJoin { left, right, .. } => {
+                children.push(self.build_node(left, variables)?);
+                children.push(self.build_node(right, variables)?);
+                ("LeftJoin", String::new())
+            }
+            PlanNode::HashLeftJoin {
+                left,
+                right,
+                keys,
+                expression,
+            } => {
+                children.push(self.build_node(left, variables)?);
+                children.push(self.build_node(right, variables)?);
+                let mut description = variables_list(keys, variables);
+                if let Some(expression) = expression {
+                    description.push_str(&format!(
+                        " FILTER({})",
+                        self.expression(expression, variables, &mut children)?
+                    ));
+                }
+                ("HashLeftJoin", description)
+            }
+            PlanNode::Extend {
+                child,
+                position,
+                expression,
+            } => {
+                children.push(self.build_node(child, variables)?);
+                (
+                    "Extend",
+                    format!(
+                        "{} := {}",
+                        variable(*position, variables),
+                        self.expression(expression, variables, &mut children)?
+                    ),
+                )
+            }
+            PlanNode::Sort { child, by } => {
+                children.push(self.build_node(child, variables)?);
+                let mut description = Vec::with_capacity(by.len());
+                for comparator in by {
+                    description.push(match comparator {
+                        Comparator::Asc(e) => {
+                            format!("ASC({})", self.expression(e, variables, &mut children)?)
+                        }
+                        Comparator::Desc(e) => {
+                            format!("DESC({})", self.expression(e, variables, &mut children)?)
+                        }
+                    });
+                }
+                ("Sort", description.join(" "))
+            }
+            PlanNode::HashDeduplicate { child } => {
+                children.push(self.build_node(child, variables)?);
+                ("HashDeduplicate", String::new())
+            }
+            PlanNode::Skip { child, count } => {
+                children.push(self.build_node(child, variables)?);
+                ("Skip", count.to_string())
+            }
+            PlanNode::Limit { child, count } => {
+                children.push(self.build_node(child, variables)?);
+                ("Limit", count.to_string())
+            }
+            PlanNode::Project {
+                child,
+                mapping,
+                child_variables,
+            } => {
+                children.push(self.build_node(child, child_variables)?);
+                (
+                    "Project",
+                    mapping
+                        .iter()
+                        .map(|(_, o)| variable(*o, variables))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            }
+            PlanNode::Aggregate {
+                child,
+                key_mapping,
+                aggregates,
+                child_variables,
+            } => {
+                children.push(self.build_node(child, child_variables)?);
+                let mut description = Vec::new();
+                if !key_mapping.is_empty() {
+                    description.push(format!(
+                        "GROUP BY {}",
+                        key_mapping
+                            .iter()
+                            .map(|(_, o)| variable(*o, variables))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    ));
+                }
+                for (aggregate, position) in aggregates.iter() {
+                    description.push(format!(
+                        "{} := {}",
+                        variable(*position, variables),
+                        self.aggregate(aggregate, child_variables, &mut children)?
+                    ));
+                }
+                ("Aggregate", description.join(", "))
+            }
+        };
+        Ok(ExplanationNode {
+            operator,
+            description,
+            children,
+            stats: self.stats.map(|stats| stats.get(node)),
+        })
+    }
+
+    fn pattern_value(
+        &self,
+        value: &PatternValue<D::StrId>,
+        variables: &[Variable],
+    ) -> Result<String, EvaluationError> {
+        match value {
+            PatternValue::Constant(term) => self.term(*term),
+            PatternValue::Variable(v) => Ok(variable(*v, variables)),
+        }
+    }
+
+    fn term(&self, term: EncodedTerm<D::StrId>) -> Result<String, EvaluationError> {
+        Ok(if let EncodedTerm::DefaultGraph = term {
+            "DEFAULT".to_owned()
+        } else {
+            self.decoder.decode_term(term)?.to_string()
+        })
+    }
+
+    fn path(&self, path: &PlanPropertyPath<D::StrId>) -> Result<String, EvaluationError> {
+        Ok(match path {
+            PlanPropertyPath::Path(p) => self.term(*p)?,
+            PlanPropertyPath::Reverse(p) => format!("^({})", self.path(p)?),
+            PlanPropertyPath::Sequence(a, b) => format!("({} / {})", self.path(a)?, self.path(b)?),
+            PlanPropertyPath::Alternative(a, b) => {
+                format!("({} | {})", self.path(a)?, self.path(b)?)
+            }
+            PlanPropertyPath::ZeroOrMore(p) => format!("({})*", self.path(p)?),
+            PlanPropertyPath::OneOrMore(p) => format!("({})+", self.path(p)?),
+            PlanPropertyPath::ZeroOrOne(p) => format!("({})?", self.path(p)?),
+            PlanPropertyPath::NegatedPropertySet(ps) => format!(
+                "!({})",
+                ps.iter()
+                    .map(|p| self.term(*p))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(" | ")
+            ),
+        })
+    }
+
+    fn aggregate(
+        &self,
+        aggregate: &PlanAggregation<D::StrId>,
+        variables: &[Variable],
+        children: &mut Vec<ExplanationNode>,
+    ) -> Result<String, EvaluationError> {
+        let name = match &aggregate.function {
+            PlanAggregationFunction::Count => "COUNT".to_owned(),
+            PlanAggregationFunction::Sum => "SUM".to_owned(),
+            PlanAggregationFunction::Min => "MIN".to_owned(),
+            PlanAggregationFunction::Max => "MAX".to_owned(),
+            PlanAggregationFunction::Avg => "AVG".to_owned(),
+            PlanAggregationFunction::Sample => "SAMPLE".to_owned(),
+            PlanAggregationFunction::GroupConcat { .. } => "GROUP_CONCAT".to_owned(),
+            PlanAggregationFunction::Custom(name) => name.to_string(),
+        };
+        let parameter = if let Some(parameter) = &aggregate.parameter {
+            self.expression(parameter, variables, children)?
+        } else {
+            "*".to_owned()
+        };
+        Ok(if aggregate.distinct {
+            format!("{}(DISTINCT {})", name, parameter)
+        } else {
+            format!("{}({})", name, parameter)
+        })
+    }
+
+    /// Serializes the expression and adds the `EXISTS` plans to `children`
+    fn expression(
+        &self,
+        expression: &PlanExpression<D::StrId>,
+        variables: &[Variable],
+        children: &mut Vec<ExplanationNode>,
+    ) -> Result<String, EvaluationError> {
+        match expression {
+            PlanExpression::Constant(t) => self.term(*t),
+            PlanExpression::Variable(v) => Ok(variable(*v, variables)),
+            PlanExpression::Exists(plan) => {
+                children.push(self.build_node(plan, variables)?);
+                Ok("EXISTS".to_owned())
+            }
+            PlanExpression::Or(a, b) => self.binary("||", a, b, variables, children),
+            PlanExpression::And(a, b) => self.binary("&&", a, b, variables, children),
+            PlanExpression::Equal(a, b) => self.binary("=", a, b, variables, children),
+            PlanExpression::Greater(a, b) => self.binary(">", a, b, variables, children),
+            PlanExpression::GreaterOrEqual(a, b) => self.binary(">=", a, b, variables, children),
+            PlanExpression::Less(a, b) => self.binary("<", a, b, variables, children),
+            PlanExpression::LessOrEqual(a, b) => self.binary("<=", a, b, variables, children),
+            PlanExpression::Add(a, b) => self.binary("+", a, b, variables, children),
+            PlanExpression::Subtract(a, b) => self.binary("-", a, b, variables, children),
+            PlanExpression::Multiply(a, b) => self.binary("*", a, b, variables, children),
+            PlanExpression::Divide(a, b) => self.binary("/", a, b, variables, children),
+            PlanExpression::In(a, bs) => {
+                let a = self.expression(a, variables, children)?;
+                let bs = bs
+                    .iter()
+                    .map(|b| self.expression(b, variables, children))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(format!("({} IN ({}))", a, bs.join(", ")))
+            }
+            PlanExpression::UnaryPlus(e) => {
+                Ok(format!("+{}", self.expression(e, variables, children)?))
+            }
+            PlanExpression::UnaryMinus(e) => {
+                Ok(format!("-{}", self.expression(e, variables, children)?))
+            }
+            PlanExpression::Not(e) => Ok(format!("!{}", self.expression(e, variables, children)?)),
+            PlanExpression::Bound(v) => Ok(format!("BOUND({})", variable(*v, variables))),
+            PlanExpression::BNode(None) => Ok("BNODE()".to_owned()),
+            PlanExpression::Rand => Ok("RAND()".to_owned()),
+            PlanExpression::Now => Ok("NOW()".to_owned()),
+            PlanExpression::UUID => Ok("UUID()".to_owned()),
+            PlanExpression::StrUUID => Ok("STRUUID()".to_owned()),
+            PlanExpression::Str(e) => self.function("STR", &[e], variables, children),
+            PlanExpression::Lang(e) => self.function("LANG", &[e], variables, children),
+            PlanExpression::LangMatches(a, b) => {
+                self.function("LANGMATCHES", &[a, b], variables, children)
+            }
+            PlanExpression::Datatype(e) => self.function("DATATYPE", &[e], variables, children),
+            PlanExpression::IRI(e) => self.function("IRI", &[e], variables, children),
+            PlanExpression::BNode(Some(e)) => self.function("BNODE", &[e], variables, children),
+            PlanExpression::Abs(e) => self.function("ABS", &[e], variables, children),
+            PlanExpression::Ceil(e) => self.function("CEIL", &[e], variables, children),
+            PlanExpression::Floor(e) => self.function("FLOOR", &[e], variables, children),
+            PlanExpression::Round(e) => self.function("ROUND", &[e], variables, children),
+            PlanExpression::Concat(es) => self.list_function("CONCAT", es, variables, children),
+            PlanExpression::SubStr(a, b, None) => {
+                self.function("SUBSTR", &[a, b], variables, children)
+            }
+            PlanExpression::SubStr(a, b, Some(c)) => {
+                self.function("SUBSTR", &[a, b, c], variables, children)
+            }
+            PlanExpression::StrLen(e) => self.function("STRLEN", &[e], variables, children),
+            PlanExpression::Replace(a, b, c, None) => {
+                self.function("REPLACE", &[a, b, c], variables, children)
+            }
+            PlanExpression::Replace(a, b, c, Some(d)) => {
+                self.function("REPLACE", &[a, b, c, d], variables, children)
+            }
+            PlanExpression::UCase(e) => self.function("UCASE", &[e], variables, children),
+            PlanExpression::LCase(e) => self.function("LCASE", &[e], variables, children),
+            PlanExpression::EncodeForURI(e) => {
+                self.function("ENCODE_FOR_URI", &[e], variables, children)
+            }
+            PlanExpression::Contains(a, b) => {
+                self.function("CONTAINS", &[a, b], variables, children)
+            }
+            PlanExpression::StrStarts(a, b) => {
+                self.function("STRSTARTS", &[a, b], variables, children)
+            }
+            PlanExpression::StrEnds(a, b) => self.function("STRENDS", &[a, b], variables, children),
+            PlanExpression::StrBefore(a, b) => {
+                self.function("STRBEFORE", &[a, b], variables, children)
+            }
+            PlanExpression::StrAfter(a, b) => {
+                self.function("STRAFTER", &[a, b], variables, children)
+            }
+            PlanExpression::Year(e) => self.function("YEAR", &[e], variables, children),
+            PlanExpression::Month(e) => self.function("MONTH", &[e], variables, children),
+            PlanExpression::Day(e) => self.function("DAY", &[e], variables, children),
+            PlanExpression::Hours(e) => self.function("HOURS", &[e], variables, children),
+            PlanExpression::Minutes(e) => self.function("MINUTES", &[e], variables, children),
+            PlanExpression::Seconds(e) => self.function("SECONDS", &[e], variables, children),
+            PlanExpression::Timezone(e) => self.function("TIMEZONE", &[e], variables, children),
+            PlanExpression::Tz(e) => self.function("TZ", &[e], variables, children),
+            PlanExpression::MD5(e) => self.function("MD5", &[e], variables, children),
+            PlanExpression::SHA1(e) => self.function("SHA1", &[e], variables, children),
+            PlanExpression::SHA256(e) => self.function("SHA256", &[e], variables, children),
+            PlanExpression::SHA384(e) => self.function("SHA384", &[e], variables, children),
+            PlanExpression::SHA512(e) => self.function("SHA512", &[e], variables, children),
+            PlanExpression::Coalesce(es) => self.list_function("COALESCE", es, variables, children),
+            PlanExpression::If(a, b, c) => self.function("IF", &[a, b, c], variables, children),
+            PlanExpression::StrLang(a, b) => self.function("STRLANG", &[a, b], variables, children),
+            PlanExpression::StrDT(a, b) => self.function("STRDT", &[a, b], variables, children),
+            PlanExpression::SameTerm(a, b) => {
+                self.function("sameTerm", &[a, b], variables, children)
+            }
+            PlanExpression::IsIRI(e) => self.function("isIRI", &[e], variables, children),
+            PlanExpression::IsBlank(e) => self.function("isBLANK", &[e], variables, children),
+            PlanExpression::IsLiteral(e) => self.function("isLITERAL", &[e], variables, children),
+            PlanExpression::IsNumeric(e) => self.function("isNUMERIC", &[e], variables, children),
+            PlanExpression::Triple(a, b, c) => {
+                self.function("TRIPLE", &[a, b, c], variables, children)
+            }
+            PlanExpression::Subject(e) => self.function("SUBJECT", &[e], variables, children),
+            PlanExpression::Predicate(e) => self.function("PREDICATE", &[e], variables, children),
+            PlanExpression::Object(e) => self.function("OBJECT", &[e], variables, children),
+            PlanExpression::IsTriple(e) => self.function("isTRIPLE", &[e], variables, children),
+            PlanExpression::Regex(a, b, None) => {
+                self.function("REGEX", &[a, b], variables, children)
+            }
+            PlanExpression::Regex(a, b, Some(c)) => {
+                self.function("REGEX", &[a, b, c], variables, children)
+            }
+            PlanExpression::BooleanCast(e) => {
+                self.function(&xsd::BOOLEAN.to_string(), &[e], variables, children)
+            }
+            PlanExpression::DoubleCast(e) => {
+                self.function(&xsd::DOUBLE.to_string(), &[e], variables, children)
+            }
+            PlanExpression::FloatCast(e) => {
+                self.function(&xsd::FLOAT.to_string(), &[e], variables, children)
+            }
+            PlanExpression::DecimalCast(e) => {
+                self.function(&xsd::DECIMAL.to_string(), &[e], variables, children)
+            }
+            PlanExpression::IntegerCast(e) => {
+                self.function(&xsd::INTEGER.to_string(), &[e], variables, children)
+            }
+            PlanExpression::DateCast(e) => {
+                self.function(&xsd::DATE.to_string(), &[e], variables, children)
+            }
+            PlanExpression::TimeCast(e) => {
+                self.function(&xsd::TIME.to_string(), &[e], variables, children)
+            }
+            PlanExpression::DateTimeCast(e) => {
+                self.function(&xsd::DATE_TIME.to_string(), &[e], variables, children)
+            }
+            PlanExpression::DurationCast(e) => {
+                self.function(&xsd::DURATION.to_string(), &[e], variables, children)
+            }
+            PlanExpression::YearMonthDurationCast(e) => self.function(
+                &xsd::YEAR_MONTH_DURATION.to_string(),
+                &[e],
+                variables,
+                children,
+            ),
+            PlanExpression::DayTimeDurationCast(e) => self.function(
+                &xsd::DAY_TIME_DURATION.to_string(),
+                &[e],
+                variables,
+                children,
+            ),
+            PlanExpression::StringCast(e) => {
+                self.function(&xsd::STRING.to_string(), &[e], variables, children)
+            }
+            PlanExpression::GeoDistance(a, b, unit) => self.function(
+                "<http://www.opengis.net/def/function/geosparql/distance>",
+                &[a, b, unit],
+                variables,
+                children,
+            ),
+            PlanExpression::GeoEquals(a, b) => self.function(
+                "<http://www.opengis.net/def/function/geosparql/sfEquals>",
+                &[a, b],
+                variables,
+                children,
+            ),
+            PlanExpression::GeoDisjoint(a, b) => self.function(
+                "<http://www.opengis.net/def/function/geosparql/sfDisjoint>",
+                &[a, b],
+                variables,
+                children,
+            ),
+            PlanExpression::GeoIntersects(a, b) => self.function(
+                "<http://www.opengis.net/def/function/geosparql/sfIntersects>",
+                &[a, b],
+                variables,
+                children,
+            ),
+            PlanExpression::GeoWithin(a, b) => self.function(
+                "<http://www.opengis.net/def/function/geosparql/sfWithin>",
+                &[a, b],
+                variables,
+                children,
+            ),
+            PlanExpression::GeoContains(a, b) => self.function(
+                "<http://www.opengis.net/def/function/geosparql/sfContains>",
+                &[a, b],
+                variables,
+                children,
+            ),
+            PlanExpression::CustomFunction(name, args) => {
+                self.list_function(&name.to_string(), args, variables, children)
+            }
+        }
+    }
+
+    fn function<E: AsRef<PlanExpression<D::StrId>>>(
+        &self,
+        name: &str,
+        args: &[E],
+        variables: &[Variable],
+        children: &mut Vec<ExplanationNode>,
+    ) -> Result<String, EvaluationError> {
+        let args = args
+            .iter()
+            .map(|a| self.expression(a.as_ref(), variables, children))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("{}({})", name, args.join(", ")))
+    }
+
+    fn list_function(
+        &self,
+        name: &str,
+        args: &[PlanExpression<D::StrId>],
+        variables: &[Variable],
+        children: &mut Vec<ExplanationNode>,
+    ) -> Result<String, EvaluationError> {
+        let args = args
+            .iter()
+            .map(|a| self.expression(a, variables, children))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(format!("{}({})", name, args.join(", ")))
+    }
+
+    fn binary(
+        &self,
+        operator: &str,
+        a: &PlanExpression<D::StrId>,
+        b: &PlanExpression<D::StrId>,
+        variables: &[Variable],
+        children: &mut Vec<ExplanationNode>,
+    ) -> Result<String, EvaluationError> {
+        Ok(format!(
+            "({} {} {})",
+            self.expression(a, variables, children)?,
+            operator,
+            self.expression(b, variables, children)?
+        ))
+    }
+}
+
+fn variable(position: usize, variables: &[Variable]) -> String {
+    if let Some(variable) = variables.get(position) {
+        variable.to_string()
+    } else {
+        format!("?{}", position)
+    }
+}
+
+fn variables_list(positions: &[usize], variables: &[Variable]) -> String {
+    positions
+        .iter()
+        .map(|position| variable(*position, variables))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::*;
+    use crate::sparql::{EvaluationError, QueryOptions};
+    use crate::MemoryStore;
+
+    fn store() -> MemoryStore {
+        let store = MemoryStore::new();
+        for i in 0..10 {
+            store.insert(Quad::new(
+                NamedNode::new_unchecked(format!("http://example.com/{}", i)),
+                NamedNode::new_unchecked("http://example.com/p"),
+                Literal::from(i),
+                GraphName::DefaultGraph,
+            ));
+        }
+        store
+    }
+
+    #[test]
+    fn test_explain_without_stats() -> Result<(), EvaluationError> {
+        let explanation =
+            store().explain("SELECT ?s WHERE { ?s <http://example.com/p> ?o FILTER(?o > 5) }")?;
+        let project = explanation.root();
+        assert_eq!(project.operator(), "Project");
+        assert_eq!(project.description(), "?s");
+        assert_eq!(project.rows(), None);
+        let filter = &project.children()[0];
+        assert_eq!(filter.operator(), "Filter");
+        assert_eq!(
+            filter.description(),
+            "(?o > \"5\"^^<http://www.w3.org/2001/XMLSchema#integer>)"
+        );
+        let pattern = &filter.children()[0];
+        assert_eq!(pattern.operator(), "QuadPatternJoin");
+        assert_eq!(
+            pattern.description(),
+            "?s <http://example.com/p> ?o DEFAULT"
+        );
+        assert_eq!(pattern.children()[0].operator(), "Init");
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_with_stats() -> Result<(), EvaluationError> {
+        let explanation = store().explain_opt(
+            "SELECT ?s WHERE { ?s <http://example.com/p> ?o FILTER(?o > 5) }",
+            QueryOptions::default(),
+            true,
+        )?;
+        let project = explanation.root();
+        assert_eq!(project.rows(), Some(4));
+        assert_eq!(project.evaluations(), Some(1));
+        assert!(project.duration().is_some());
+        assert_eq!(project.children()[0].children()[0].rows(), Some(10));
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_serialization() -> Result<(), EvaluationError> {
+        let explanation = store().explain("ASK { ?s ?p ?o }")?;
+        assert_eq!(
+            explanation.to_string(),
+            "Project ?o ?p ?s\n  QuadPatternJoin ?s ?p ?o DEFAULT\n    Init\n"
+        );
+        let mut json = Vec::new();
+        explanation.write_json(&mut json)?;
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            "{\"operator\":\"Project\",\"description\":\"?o ?p ?s\",\"children\":[{\"operator\":\"QuadPatternJoin\",\"description\":\"?s ?p ?o DEFAULT\",\"children\":[{\"operator\":\"Init\",\"description\":\"\",\"children\":[]}]}]}"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,825 @@
+//! [GeoSPARQL](https://www.ogc.org/standards/geosparql) geometries.
+//!
+//! The geometries are read from the [WKT](https://en.wikipedia.org/wiki/Well-known_text_representation_of_geometry)
+//! serialization of the `geo:wktLiteral` values. Only the two first coordinates are kept.
+//! The coordinates are longitudes and latitudes in degrees following the default CRS84 reference system.
+//! The EPSG:4326 reference system is also supported with its latitude first axis order.
+//!
+//! The topological relations are evaluated in the plane of the coordinates
+//! and the distances along the great circles of a spherical Earth between the nearest points of the geometries in this plane.
+
+use std::cmp::Ordering;
+
+pub(crate) const WKT_LITERAL: &str = "http://www.opengis.net/ont/geosparql#wktLiteral";
+const CRS84: &str = "http://www.opengis.net/def/crs/OGC/1.3/CRS84";
+const EPSG_4326: &str = "http://www.opengis.net/def/crs/EPSG/0/4326";
+const METRE: &str = "http://www.opengis.net/def/uom/OGC/1.0/metre";
+const KILOMETRE: &str = "http://www.opengis.net/def/uom/OGC/1.0/kilometre";
+const DEGREE: &str = "http://www.opengis.net/def/uom/OGC/1.0/degree";
+const RADIAN: &str = "http://www.opengis.net/def/uom/OGC/1.0/radian";
+/// The mean Earth radius in metres
+const EARTH_RADIUS: f64 = 6_371_008.8;
+/// Tolerance of the collinearity tests
+const EPSILON: f64 = 1e-12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Coord {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Geometry {
+    Point(Coord),
+    LineString(Vec<Coord>),
+    /// The first ring is the exterior one and the other rings are the holes
+    Polygon(Vec<Vec<Coord>>),
+    /// The multi geometries and the geometry collections. The empty geometries are empty collections.
+    Collection(Vec<Geometry>),
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Location {
+    Interior,
+    Boundary,
+    Exterior,
+}
+
+/// An axis aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Envelope {
+    min: Coord,
+    max: Coord,
+}
+
+impl Envelope {
+    fn intersects(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    fn contains(&self, other: &Self) -> bool {
+        self.min.x <= other.min.x
+            && other.max.x <= self.max.x
+            && self.min.y <= other.min.y
+            && other.max.y <= self.max.y
+    }
+}
+
+impl Geometry {
+    /// Parses a `geo:wktLiteral` lexical form
+    pub fn from_wkt(value: &str) -> Option<Self> {
+        let mut parser = WktParser {
+            input: value.trim(),
+            position: 0,
+            swap_axes: false,
+        };
+        if parser.input.starts_with('<') {
+            let end = parser.input.find('>')?;
+            match &parser.input[1..end] {
+                CRS84 => (),
+                EPSG_4326 => parser.swap_axes = true,
+                _ => return None,
+            }
+            parser.position = end + 1;
+        }
+        let geometry = parser.geometry()?;
+        parser.skip_whitespace();
+        if parser.position == parser.input.len() {
+            Some(geometry)
+        } else {
+            None
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match self {
+            Geometry::Point(_) | Geometry::LineString(_) | Geometry::Polygon(_) => false,
+            Geometry::Collection(geometries) => geometries.iter().all(Geometry::is_empty),
+        }
+    }
+
+    fn envelope(&self) -> Option<Envelope> {
+        let mut vertices = Vec::new();
+        self.add_vertices(&mut vertices);
+        let (first, others) = vertices.split_first()?;
+        let mut envelope = Envelope {
+            min: *first,
+            max: *first,
+        };
+        for vertex in others {
+            envelope.min.x = envelope.min.x.min(vertex.x);
+            envelope.min.y = envelope.min.y.min(vertex.y);
+            envelope.max.x = envelope.max.x.max(vertex.x);
+            envelope.max.y = envelope.max.y.max(vertex.y);
+        }
+        Some(envelope)
+    }
+
+    fn add_vertices(&self, vertices: &mut Vec<Coord>) {
+        match self {
+            Geometry::Point(point) => vertices.push(*point),
+            Geometry::LineString(line) => vertices.extend_from_slice(line),
+            Geometry::Polygon(rings) => {
+                for ring in rings {
+                    vertices.extend_from_slice(ring)
+                }
+            }
+            Geometry::Collection(geometries) => {
+                for geometry in geometries {
+                    geometry.add_vertices(vertices)
+                }
+            }
+        }
+    }
+
+    /// Adds the segments of the geometry, the points being degenerated segments
+    fn add_segments(&self, segments: &mut Vec<(Coord, Coord)>) {
+        match self {
+            Geometry::Point(point) => segments.push((*point, *point)),
+            Geometry::LineString(line) => add_line_segments(line, segments),
+            Geometry::Polygon(rings) => {
+                for ring in rings {
+                    add_line_segments(ring, segments)
+                }
+            }
+            Geometry::Collection(geometries) => {
+                for geometry in geometries {
+                    geometry.add_segments(segments)
+                }
+            }
+        }
+    }
+
+    /// Adds the vertices of the polygons rings
+    fn add_ring_vertices(&self, vertices: &mut Vec<Coord>) {
+        match self {
+            Geometry::Point(_) | Geometry::LineString(_) => (),
+            Geometry::Polygon(rings) => {
+                for ring in rings {
+                    vertices.extend_from_slice(ring)
+                }
+            }
+            Geometry::Collection(geometries) => {
+                for geometry in geometries {
+                    geometry.add_ring_vertices(vertices)
+                }
+            }
+        }
+    }
+
+    /// Adds some points of the interior of the geometry
+    fn add_interior_points(&self, points: &mut Vec<Coord>) {
+        match self {
+            Geometry::Point(point) => points.push(*point),
+            Geometry::LineString(line) => {
+                let mut segments = Vec::new();
+                add_line_segments(line, &mut segments);
+                points.extend(segments.into_iter().map(|(a, b)| middle(a, b)))
+            }
+            Geometry::Polygon(rings) => points.extend(polygon_interior_point(rings)),
+            Geometry::Collection(geometries) => {
+                for geometry in geometries {
+                    geometry.add_interior_points(points)
+                }
+            }
+        }
+    }
+
+    fn locate(&self, point: Coord) -> Location {
+        match self {
+            Geometry::Point(other) => {
+                if *other == point {
+                    Location::Interior
+                } else {
+                    Location::Exterior
+                }
+            }
+            Geometry::LineString(line) => {
+                let mut segments = Vec::new();
+                add_line_segments(line, &mut segments);
+                if !segments.iter().any(|(a, b)| is_on_segment(point, *a, *b)) {
+                    Location::Exterior
+                } else if line.first() != line.last()
+                    && (line.first() == Some(&point) || line.last() == Some(&point))
+                {
+                    Location::Boundary
+                } else {
+                    Location::Interior
+                }
+            }
+            Geometry::Polygon(rings) => {
+                let mut segments = Vec::new();
+                for ring in rings {
+                    add_line_segments(ring, &mut segments);
+                }
+                if segments.iter().any(|(a, b)| is_on_segment(point, *a, *b)) {
+                    Location::Boundary
+                } else if rings.iter().filter(|ring| is_in_ring(point, ring)).count() % 2 == 1 {
+                    // The point is in the exterior ring and not in a hole
+                    Location::Interior
+                } else {
+                    Location::Exterior
+                }
+            }
+            Geometry::Collection(geometries) => {
+                let mut location = Location::Exterior;
+                for geometry in geometries {
+                    match geometry.locate(point) {
+                        Location::Interior => return Location::Interior,
+                        Location::Boundary => location = Location::Boundary,
+                        Location::Exterior => (),
+                    }
+                }
+                location
+            }
+        }
+    }
+}
+
+/// The `geof:sfIntersects` relation
+pub(crate) fn intersects(a: &Geometry, b: &Geometry) -> bool {
+    let (a_envelope, b_envelope) = match (a.envelope(), b.envelope()) {
+        (Some(a_envelope), Some(b_envelope)) => (a_envelope, b_envelope),
+        _ => return false,
+    };
+    if !a_envelope.intersects(&b_envelope) {
+        return false;
+    }
+    let mut a_segments = Vec::new();
+    a.add_segments(&mut a_segments);
+    let mut b_segments = Vec::new();
+    b.add_segments(&mut b_segments);
+    if a_segments.iter().any(|(a1, a2)| {
+        b_segments
+            .iter()
+            .any(|(b1, b2)| segments_intersect(*a1, *a2, *b1, *b2))
+    }) {
+        return true;
+    }
+    // One of the geometries might be inside of a polygon of the other one
+    let mut vertices = Vec::new();
+    a.add_vertices(&mut vertices);
+    if vertices.iter().any(|v| b.locate(*v) != Location::Exterior) {
+        return true;
+    }
+    vertices.clear();
+    b.add_vertices(&mut vertices);
+    vertices.iter().any(|v| a.locate(*v) != Location::Exterior)
+}
+
+/// The `geof:sfWithin` relation
+pub(crate) fn within(a: &Geometry, b: &Geometry) -> bool {
+    let (a_envelope, b_envelope) = match (a.envelope(), b.envelope()) {
+        (Some(a_envelope), Some(b_envelope)) => (a_envelope, b_envelope),
+        _ => return false,
+    };
+    if !b_envelope.contains(&a_envelope) {
+        return false;
+    }
+    // No point of a is outside of b
+    let mut vertices = Vec::new();
+    a.add_vertices(&mut vertices);
+    if vertices.iter().any(|v| b.locate(*v) == Location::Exterior) {
+        return false;
+    }
+    let mut a_segments = Vec::new();
+    a.add_segments(&mut a_segments);
+    let mut b_segments = Vec::new();
+    b.add_segments(&mut b_segments);
+    if a_segments.iter().any(|(a1, a2)| {
+        b.locate(middle(*a1, *a2)) == Location::Exterior
+            || b_segments
+                .iter()
+                .any(|(b1, b2)| segments_cross(*a1, *a2, *b1, *b2))
+    }) {
+        return false;
+    }
+    // The boundary of the polygons of b does not go through the interior of a
+    vertices.clear();
+    b.add_ring_vertices(&mut vertices);
+    if vertices.iter().any(|v| a.locate(*v) == Location::Interior) {
+        return false;
+    }
+    // The interiors intersect
+    let mut points = Vec::new();
+    a.add_interior_points(&mut points);
+    points.iter().any(|p| b.locate(*p) == Location::Interior)
+}
+
+/// The `geof:sfContains` relation
+pub(crate) fn contains(a: &Geometry, b: &Geometry) -> bool {
+    within(b, a)
+}
+
+/// The `geof:sfDisjoint` relation
+pub(crate) fn disjoint(a: &Geometry, b: &Geometry) -> bool {
+    !intersects(a, b)
+}
+
+/// The `geof:sfEquals` relation
+pub(crate) fn equals(a: &Geometry, b: &Geometry) -> bool {
+    if a.is_empty() || b.is_empty() {
+        a.is_empty() && b.is_empty()
+    } else {
+        within(a, b) && within(b, a)
+    }
+}
+
+/// The `geof:distance` function with the distance unit IRI
+pub(crate) fn distance(a: &Geometry, b: &Geometry, unit: &str) -> Option<f64> {
+    let angle = if intersects(a, b) {
+        0.
+    } else {
+        let (from, to) = nearest_points(a, b)?;
+        central_angle(from, to)
+    };
+    match unit {
+        METRE => Some(angle * EARTH_RADIUS),
+        KILOMETRE => Some(angle * EARTH_RADIUS / 1000.),
+        DEGREE => Some(angle.to_degrees()),
+        RADIAN => Some(angle),
+        _ => None,
+    }
+}
+
+/// Returns the nearest points of two geometries in the coordinates plane
+fn nearest_points(a: &Geometry, b: &Geometry) -> Option<(Coord, Coord)> {
+    let mut a_segments = Vec::new();
+    a.add_segments(&mut a_segments);
+    let mut b_segments = Vec::new();
+    b.add_segments(&mut b_segments);
+    let mut best: Option<(f64, Coord, Coord)> = None;
+    for (a1, a2) in &a_segments {
+        for (b1, b2) in &b_segments {
+            // The segments do not intersect so the nearest points include an end of one of them
+            let candidates = [
+                (*a1, project_on_segment(*a1, *b1, *b2)),
+                (*a2, project_on_segment(*a2, *b1, *b2)),
+                (project_on_segment(*b1, *a1, *a2), *b1),
+                (project_on_segment(*b2, *a1, *a2), *b2),
+            ];
+            for (from, to) in &candidates {
+                let distance = (from.x - to.x).hypot(from.y - to.y);
+                let is_closer = match best {
+                    Some((best_distance, _, _)) => distance < best_distance,
+                    None => true,
+                };
+                if is_closer {
+                    best = Some((distance, *from, *to));
+                }
+            }
+        }
+    }
+    best.map(|(_, from, to)| (from, to))
+}
+
+/// The angle between two points on the sphere in radians using the haversine formula
+fn central_angle(a: Coord, b: Coord) -> f64 {
+    let (lat_a, lat_b) = (a.y.to_radians(), b.y.to_radians());
+    let d_lat = lat_b - lat_a;
+    let d_lon = (b.x - a.x).to_radians();
+    let h = (d_lat / 2.).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.).sin().powi(2);
+    2. * h.sqrt().min(1.).asin()
+}
+
+fn add_line_segments(line: &[Coord], segments: &mut Vec<(Coord, Coord)>) {
+    segments.extend(line.windows(2).map(|w| (w[0], w[1])))
+}
+
+fn middle(a: Coord, b: Coord) -> Coord {
+    Coord {
+        x: (a.x + b.x) / 2.,
+        y: (a.y + b.y) / 2.,
+    }
+}
+
+/// The sign of the cross product of (b - a) and (c - a)
+fn orientation(a: Coord, b: Coord, c: Coord) -> Ordering {
+    let cross = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+    if cross > EPSILON {
+        Ordering::Greater
+    } else if cross < -EPSILON {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
+fn is_on_segment(p: Coord, a: Coord, b: Coord) -> bool {
+    orientation(a, b, p) == Ordering::Equal
+        && p.x >= a.x.min(b.x)
+        && p.x <= a.x.max(b.x)
+        && p.y >= a.y.min(b.y)
+        && p.y <= a.y.max(b.y)
+}
+
+/// Returns if the two segments share a point
+fn segments_intersect(a1: Coord, a2: Coord, b1: Coord, b2: Coord) -> bool {
+    segments_cross(a1, a2, b1, b2)
+        || is_on_segment(a1, b1, b2)
+        || is_on_segment(a2, b1, b2)
+        || is_on_segment(b1, a1, a2)
+        || is_on_segment(b2, a1, a2)
+}
+
+/// Returns if the two segments cross each other at a point that is not one of their ends
+fn segments_cross(a1: Coord, a2: Coord, b1: Coord, b2: Coord) -> bool {
+    let d1 = orientation(b1, b2, a1);
+    let d2 = orientation(b1, b2, a2);
+    let d3 = orientation(a1, a2, b1);
+    let d4 = orientation(a1, a2, b2);
+    d1 != Ordering::Equal
+        && d2 != Ordering::Equal
+        && d1 != d2
+        && d3 != Ordering::Equal
+        && d4 != Ordering::Equal
+        && d3 != d4
+}
+
+fn project_on_segment(p: Coord, a: Coord, b: Coord) -> Coord {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length = dx * dx + dy * dy;
+    if length == 0. {
+        return a;
+    }
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / length).clamp(0., 1.);
+    Coord {
+        x: a.x + t * dx,
+        y: a.y + t * dy,
+    }
+}
+
+/// Ray casting test of a point not on the ring
+fn is_in_ring(p: Coord, ring: &[Coord]) -> bool {
+    let mut inside = false;
+    for w in ring.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        if (a.y > p.y) != (b.y > p.y) && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Returns a point of the interior of a polygon
+///
+/// It is the middle of the widest interior interval of an horizontal line going between the two lowest vertices.
+fn polygon_interior_point(rings: &[Vec<Coord>]) -> Option<Coord> {
+    let mut ys = rings.first()?.iter().map(|c| c.y).collect::<Vec<_>>();
+    ys.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    ys.dedup();
+    let y = (ys.first()? + ys.get(1)?) / 2.;
+    let mut xs = Vec::new();
+    for ring in rings {
+        for w in ring.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            if (a.y > y) != (b.y > y) {
+                xs.push(a.x + (y - a.y) * (b.x - a.x) / (b.y - a.y));
+            }
+        }
+    }
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    xs.chunks_exact(2)
+        .max_by(|a, b| {
+            (a[1] - a[0])
+                .partial_cmp(&(b[1] - b[0]))
+                .unwrap_or(Ordering::Equal)
+        })
+        .map(|interval| Coord {
+            x: (interval[0] + interval[1]) / 2.,
+            y,
+        })
+}
+
+struct WktParser<'a> {
+    input: &'a str,
+    position: usize,
+    swap_axes: bool,
+}
+
+impl WktParser<'_> {
+    fn geometry(&mut self) -> Option<Geometry> {
+        let keyword = self.keyword()?;
+        self.dimension();
+        if self.is_empty() {
+            return Some(Geometry::Collection(Vec::new()));
+        }
+        Some(match keyword.as_str() {
+            "POINT" => {
+                self.expect('(')?;
+                let point = self.coord()?;
+                self.expect(')')?;
+                Geometry::Point(point)
+            }
+            "LINESTRING" => Geometry::LineString(self.line()?),
+            "POLYGON" => Geometry::Polygon(self.polygon()?),
+            "MULTIPOINT" => Geometry::Collection(
+                self.list(|parser| {
+                    // The points might be in parentheses or not
+                    Some(if parser.peek() == Some('(') {
+                        parser.expect('(')?;
+                        let point = parser.coord()?;
+                        parser.expect(')')?;
+                        point
+                    } else {
+                        parser.coord()?
+                    })
+                })?
+                .into_iter()
+                .map(Geometry::Point)
+                .collect(),
+            ),
+            "MULTILINESTRING" => Geometry::Collection(
+                self.list(WktParser::line)?
+                    .into_iter()
+                    .map(Geometry::LineString)
+                    .collect(),
+            ),
+            "MULTIPOLYGON" => Geometry::Collection(
+                self.list(WktParser::polygon)?
+                    .into_iter()
+                    .map(Geometry::Polygon)
+                    .collect(),
+            ),
+            "GEOMETRYCOLLECTION" => Geometry::Collection(self.list(WktParser::geometry)?),
+            _ => return None,
+        })
+    }
+
+    fn line(&mut self) -> Option<Vec<Coord>> {
+        let line = self.list(WktParser::coord)?;
+        if line.len() >= 2 {
+            Some(line)
+        } else {
+            None
+        }
+    }
+
+    fn polygon(&mut self) -> Option<Vec<Vec<Coord>>> {
+        self.list(|parser| {
+            let ring = parser.line()?;
+            if ring.len() >= 4 && ring.first() == ring.last() {
+                Some(ring)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn list<T>(&mut self, mut item: impl FnMut(&mut Self) -> Option<T>) -> Option<Vec<T>> {
+        self.expect('(')?;
+        let mut items = vec![item(self)?];
+        while self.peek() == Some(',') {
+            self.expect(',')?;
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Some(items)
+    }
+
+    fn coord(&mut self) -> Option<Coord> {
+        let first = self.number()?;
+        let second = self.number()?;
+        // The z and m coordinates are ignored
+        while matches!(self.peek(), Some(c) if c != ',' && c != ')') {
+            self.number()?;
+        }
+        Some(if self.swap_axes {
+            Coord {
+                x: second,
+                y: first,
+            }
+        } else {
+            Coord {
+                x: first,
+                y: second,
+            }
+        })
+    }
+
+    fn number(&mut self) -> Option<f64> {
+        self.skip_whitespace();
+        let rest = &self.input[self.position..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E')))
+            .unwrap_or(rest.len());
+        let number = rest[..end].parse::<f64>().ok()?;
+        self.position += end;
+        if number.is_finite() {
+            Some(number)
+        } else {
+            None
+        }
+    }
+
+    fn keyword(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        let rest = &self.input[self.position..];
+        let end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        self.position += end;
+        Some(rest[..end].to_ascii_uppercase())
+    }
+
+    /// Skips the Z, M or ZM dimension marker
+    fn dimension(&mut self) {
+        let position = self.position;
+        if let Some(keyword) = self.keyword() {
+            if !matches!(keyword.as_str(), "Z" | "M" | "ZM") {
+                self.position = position;
+            }
+        }
+    }
+
+    fn is_empty(&mut self) -> bool {
+        let position = self.position;
+        if self.keyword().as_deref() == Some("EMPTY") {
+            true
+        } else {
+            self.position = position;
+            false
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.input[self.position..].chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Option<()> {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::*;
+    use crate::sparql::tests::select;
+    use crate::sparql::{EvaluationError, QueryOptions};
+    use crate::MemoryStore;
+
+    fn wkt(value: &str) -> Geometry {
+        Geometry::from_wkt(value).unwrap()
+    }
+
+    #[test]
+    fn test_parse_wkt() {
+        assert_eq!(
+            wkt(" Point(1.5 -2e1) "),
+            Geometry::Point(Coord { x: 1.5, y: -20. })
+        );
+        assert_eq!(
+            wkt("<http://www.opengis.net/def/crs/EPSG/0/4326> POINT Z (1 2 3)"),
+            Geometry::Point(Coord { x: 2., y: 1. })
+        );
+        assert_eq!(
+            wkt("MULTIPOINT (1 2, (3 4))"),
+            Geometry::Collection(vec![
+                Geometry::Point(Coord { x: 1., y: 2. }),
+                Geometry::Point(Coord { x: 3., y: 4. })
+            ])
+        );
+        assert_eq!(wkt("POLYGON EMPTY"), Geometry::Collection(Vec::new()));
+        assert!(matches!(
+            wkt("GEOMETRYCOLLECTION(POINT(0 0), POLYGON((0 0, 1 0, 1 1, 0 0)), LINESTRING(0 0, 1 1))"),
+            Geometry::Collection(g) if g.len() == 3
+        ));
+        assert!(Geometry::from_wkt("POINT(1)").is_none());
+        assert!(Geometry::from_wkt("POINT(1 2) foo").is_none());
+        assert!(Geometry::from_wkt("LINESTRING(1 2)").is_none());
+        assert!(Geometry::from_wkt("POLYGON((0 0, 1 0, 1 1))").is_none());
+        assert!(Geometry::from_wkt("<http://example.com/crs> POINT(1 2)").is_none());
+    }
+
+    #[test]
+    fn test_relations() {
+        let square = wkt("POLYGON((0 0, 10 0, 10 10, 0 10, 0 0), (4 4, 6 4, 6 6, 4 6, 4 4))");
+        let inside = wkt("POINT(1 1)");
+        let in_hole = wkt("POINT(5 5)");
+        let on_boundary = wkt("POINT(0 5)");
+        let small = wkt("POLYGON((1 1, 2 1, 2 2, 1 1))");
+        let around_hole = wkt("POLYGON((3 3, 7 3, 7 7, 3 7, 3 3))");
+        let crossing = wkt("LINESTRING(-1 1, 1 1)");
+
+        assert!(within(&inside, &square));
+        assert!(!within(&in_hole, &square));
+        assert!(!within(&on_boundary, &square));
+        assert!(intersects(&on_boundary, &square));
+        assert!(disjoint(&in_hole, &square));
+        assert!(within(&small, &square));
+        assert!(contains(&square, &small));
+        assert!(!within(&square, &small));
+        assert!(!within(&around_hole, &square));
+        assert!(intersects(&around_hole, &square));
+        assert!(!within(&crossing, &square));
+        assert!(intersects(&crossing, &square));
+        assert!(equals(&square, &square.clone()));
+        assert!(!equals(&square, &small));
+        assert!(equals(
+            &wkt("MULTIPOINT((1 1), (2 2))"),
+            &wkt("MULTIPOINT((2 2), (1 1))")
+        ));
+        assert!(!intersects(&wkt("POINT EMPTY"), &square));
+    }
+
+    #[test]
+    fn test_distance() {
+        let paris = wkt("POINT(2.3522 48.8566)");
+        let london = wkt("POINT(-0.1276 51.5072)");
+        let distance = distance(&paris, &london, KILOMETRE).unwrap();
+        assert!((distance - 343.5).abs() < 1., "{}", distance);
+        assert_eq!(
+            super::distance(
+                &paris,
+                &wkt("POLYGON((2 48, 3 48, 3 49, 2 49, 2 48))"),
+                METRE
+            ),
+            Some(0.)
+        );
+        let degrees =
+            super::distance(&wkt("POINT(0 0)"), &wkt("LINESTRING(1 -1, 1 1)"), DEGREE).unwrap();
+        assert!((degrees - 1.).abs() < 1e-9);
+        assert!(super::distance(&paris, &london, "http://example.com/unit").is_none());
+    }
+
+    #[test]
+    fn test_geosparql_functions() -> Result<(), EvaluationError> {
+        let store = MemoryStore::new();
+        let geometry = NamedNodeRef::new_unchecked("http://www.opengis.net/ont/geosparql#asWKT");
+        let wkt_literal =
+            NamedNodeRef::new_unchecked("http://www.opengis.net/ont/geosparql#wktLiteral");
+        let places = [
+            ("http://example.com/paris", "POINT(2.3522 48.8566)"),
+            ("http://example.com/london", "POINT(-0.1276 51.5072)"),
+            (
+                "http://example.com/lyon",
+                "<http://www.opengis.net/def/crs/EPSG/0/4326> POINT(45.764 4.8357)",
+            ),
+        ];
+        for (place, wkt) in &places {
+            store.insert(QuadRef::new(
+                NamedNodeRef::new_unchecked(place),
+                geometry,
+                LiteralRef::new_typed_literal(wkt, wkt_literal),
+                None,
+            ));
+        }
+        let subjects = |filter: &str| -> Result<Vec<Term>, EvaluationError> {
+            Ok(select(
+                &store,
+                &format!(
+                    "PREFIX geo: <http://www.opengis.net/ont/geosparql#> \
+                     PREFIX geof: <http://www.opengis.net/def/function/geosparql/> \
+                     PREFIX uom: <http://www.opengis.net/def/uom/OGC/1.0/> \
+                     SELECT ?s WHERE {{ ?s geo:asWKT ?g FILTER({}) }} ORDER BY ?s",
+                    filter
+                ),
+                QueryOptions::default(),
+            )?
+            .into_iter()
+            .flatten()
+            .flatten()
+            .collect())
+        };
+
+        let france = "\"POLYGON((-5 42, 8 42, 8 51, -5 51, -5 42))\"^^geo:wktLiteral";
+        assert_eq!(
+            subjects(&format!("geof:sfWithin(?g, {})", france))?,
+            vec![
+                NamedNode::new_unchecked("http://example.com/lyon").into(),
+                NamedNode::new_unchecked("http://example.com/paris").into()
+            ]
+        );
+        assert_eq!(
+            subjects(&format!("geof:sfDisjoint(?g, {})", france))?,
+            vec![NamedNode::new_unchecked("http://example.com/london").into()]
+        );
+        assert_eq!(
+            subjects(
+                "geof:distance(?g, \"POINT(2.35 48.85)\"^^geo:wktLiteral, uom:kilometre) > 10 && \
+                 geof:distance(?g, \"POINT(2.35 48.85)\"^^geo:wktLiteral, uom:kilometre) < 370"
+            )?,
+            vec![NamedNode::new_unchecked("http://example.com/london").into()]
+        );
+        assert!(subjects("geof:sfWithin(?g)").is_err());
+        Ok(())
+    }
+}
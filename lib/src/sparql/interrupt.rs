@@ -0,0 +1,163 @@
+use crate::sparql::EvaluationError;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A token allowing to cancel a running SPARQL query or update evaluation.
+///
+/// It could be cloned and sent to another thread.
+/// All the clones share the same cancellation state.
+///
+/// ```
+/// use oxigraph::MemoryStore;
+/// use oxigraph::sparql::{CancellationToken, EvaluationError, QueryOptions};
+///
+/// let store = MemoryStore::new();
+/// let token = CancellationToken::new();
+/// token.cancel();
+/// let result = store.query_opt("ASK { ?s ?p ?o }", QueryOptions::default().with_cancellation_token(token));
+/// assert!(matches!(result, Err(EvaluationError::Cancelled)));
+/// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Builds a new token that is not cancelled yet
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels all the evaluations using this token
+    #[inline]
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed)
+    }
+
+    /// Returns if [`cancel`](CancellationToken::cancel()) has been called on this token or one of its clones
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Checks if an evaluation should be stopped
+pub(crate) struct Interrupter {
+    deadline: Option<Instant>,
+    cancellation_token: Option<CancellationToken>,
+}
+
+impl Interrupter {
+    /// Returns `None` if there is nothing to check
+    pub fn new(
+        timeout: Option<Duration>,
+        cancellation_token: Option<CancellationToken>,
+    ) -> Option<Rc<Self>> {
+        if timeout.is_none() && cancellation_token.is_none() {
+            return None;
+        }
+        Some(Rc::new(Self {
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            cancellation_token,
+        }))
+    }
+
+    pub fn check(&self) -> Result<(), EvaluationError> {
+        if let Some(cancellation_token) = &self.cancellation_token {
+            if cancellation_token.is_cancelled() {
+                return Err(EvaluationError::Cancelled);
+            }
+        }
+        if let Some(deadline) = self.deadline {
+            if Instant::now() >= deadline {
+                return Err(EvaluationError::Timeout);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Stops an iterator of results as soon as the evaluation should be interrupted
+pub(crate) struct InterruptibleIterator<I> {
+    iter: I,
+    interrupter: Rc<Interrupter>,
+    interrupted: bool,
+}
+
+impl<I> InterruptibleIterator<I> {
+    pub fn new(iter: I, interrupter: Rc<Interrupter>) -> Self {
+        Self {
+            iter,
+            interrupter,
+            interrupted: false,
+        }
+    }
+}
+
+impl<T, I: Iterator<Item = Result<T, EvaluationError>>> Iterator for InterruptibleIterator<I> {
+    type Item = Result<T, EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<T, EvaluationError>> {
+        if self.interrupted {
+            return None;
+        }
+        if let Err(error) = self.interrupter.check() {
+            self.interrupted = true;
+            return Some(Err(error));
+        }
+        self.iter.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::*;
+    use crate::sparql::{QueryOptions, QueryResults, UpdateOptions};
+    use crate::MemoryStore;
+
+    #[test]
+    fn test_timeout_in_path() -> Result<(), EvaluationError> {
+        let store = MemoryStore::new();
+        for i in 0..100 {
+            store.insert(Quad::new(
+                NamedNode::new_unchecked(format!("http://example.com/{}", i)),
+                NamedNode::new_unchecked("http://example.com/p"),
+                NamedNode::new_unchecked(format!("http://example.com/{}", (i + 1) % 100)),
+                GraphName::DefaultGraph,
+            ));
+        }
+        if let QueryResults::Solutions(mut solutions) = store.query_opt(
+            "SELECT * WHERE { ?s <http://example.com/p>* ?o }",
+            QueryOptions::default().with_timeout(Duration::from_secs(0)),
+        )? {
+            assert!(matches!(
+                solutions.next(),
+                Some(Err(EvaluationError::Timeout))
+            ));
+            assert!(solutions.next().is_none());
+        } else {
+            panic!("SELECT queries should return solutions")
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancelled_update() {
+        let store = MemoryStore::new();
+        let token = CancellationToken::new();
+        token.cancel();
+        assert!(matches!(
+            store.update_opt(
+                "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+                UpdateOptions::from(QueryOptions::default().with_cancellation_token(token)),
+            ),
+            Err(EvaluationError::Cancelled)
+        ));
+        assert!(store.is_empty());
+    }
+}
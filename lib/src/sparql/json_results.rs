@@ -1,10 +1,17 @@
 //! Implementation of [SPARQL Query Results JSON Format](https://www.w3.org/TR/sparql11-results-json/)
 
-use crate::error::invalid_input_error;
+use crate::error::{invalid_data_error, invalid_input_error};
+use crate::json::{write_escaped_json_string, JsonEvent, JsonReader};
+use crate::model::vocab::rdf;
 use crate::model::*;
 use crate::sparql::error::EvaluationError;
 use crate::sparql::model::*;
-use std::io::Write;
+use std::collections::BTreeMap;
+use std::io;
+use std::io::{BufRead, Write};
+use std::rc::Rc;
+
+const MAX_NUMBER_OF_NESTED_TRIPLES: usize = 128;
 
 pub fn write_json_results(
     results: QueryResults,
@@ -47,30 +54,8 @@ pub fn write_json_results(
                         sink.write_all(b",")?;
                     }
                     write_escaped_json_string(variable.as_str(), &mut sink)?;
-                    match value {
-                        Term::NamedNode(uri) => {
-                            sink.write_all(b":{\"type\":\"uri\",\"value\":")?;
-                            write_escaped_json_string(uri.as_str(), &mut sink)?;
-                            sink.write_all(b"}")?;
-                        }
-                        Term::BlankNode(bnode) => {
-                            sink.write_all(b":{\"type\":\"bnode\",\"value\":")?;
-                            write_escaped_json_string(bnode.as_str(), &mut sink)?;
-                            sink.write_all(b"}")?;
-                        }
-                        Term::Literal(literal) => {
-                            sink.write_all(b":{\"type\":\"literal\",\"value\":")?;
-                            write_escaped_json_string(literal.value(), &mut sink)?;
-                            if let Some(language) = literal.language() {
-                                sink.write_all(b",\"xml:lang\":")?;
-                                write_escaped_json_string(language, &mut sink)?;
-                            } else if !literal.is_plain() {
-                                sink.write_all(b",\"datatype\":")?;
-                                write_escaped_json_string(literal.datatype().as_str(), &mut sink)?;
-                            }
-                            sink.write_all(b"}")?;
-                        }
-                    }
+                    sink.write_all(b":")?;
+                    write_json_term(value.as_ref(), &mut sink)?;
                 }
                 sink.write_all(b"}")?;
             }
@@ -84,37 +69,610 @@ pub fn write_json_results(
     }
 }
 
-fn write_escaped_json_string(s: &str, mut sink: impl Write) -> Result<(), EvaluationError> {
-    sink.write_all(b"\"")?;
-    for c in s.chars() {
-        match c {
-            '\\' => sink.write_all(b"\\\\"),
-            '"' => sink.write_all(b"\\\""),
-            c => {
-                if c < char::from(32) {
-                    match c {
-                        '\u{08}' => sink.write_all(b"\\b"),
-                        '\u{0C}' => sink.write_all(b"\\f"),
-                        '\n' => sink.write_all(b"\\n"),
-                        '\r' => sink.write_all(b"\\r"),
-                        '\t' => sink.write_all(b"\\t"),
-                        c => {
-                            let mut c = c as u8;
-                            let mut result = [b'\\', b'u', 0, 0, 0, 0];
-                            for i in (2..6).rev() {
-                                let ch = c % 16;
-                                result[i] = ch + if ch < 10 { b'0' } else { b'A' };
-                                c /= 16;
-                            }
-                            sink.write_all(&result)
+fn write_json_term(term: TermRef<'_>, sink: &mut impl Write) -> Result<(), EvaluationError> {
+    match term {
+        TermRef::NamedNode(uri) => {
+            sink.write_all(b"{\"type\":\"uri\",\"value\":")?;
+            write_escaped_json_string(uri.as_str(), &mut *sink)?;
+            sink.write_all(b"}")?;
+        }
+        TermRef::BlankNode(bnode) => {
+            sink.write_all(b"{\"type\":\"bnode\",\"value\":")?;
+            write_escaped_json_string(bnode.as_str(), &mut *sink)?;
+            sink.write_all(b"}")?;
+        }
+        TermRef::Literal(literal) => {
+            sink.write_all(b"{\"type\":\"literal\",\"value\":")?;
+            write_escaped_json_string(literal.value(), &mut *sink)?;
+            if let Some(language) = literal.language() {
+                sink.write_all(b",\"xml:lang\":")?;
+                write_escaped_json_string(language, &mut *sink)?;
+            } else if !literal.is_plain() {
+                sink.write_all(b",\"datatype\":")?;
+                write_escaped_json_string(literal.datatype().as_str(), &mut *sink)?;
+            }
+            sink.write_all(b"}")?;
+        }
+        TermRef::Triple(triple) => {
+            sink.write_all(b"{\"type\":\"triple\",\"value\":{\"subject\":")?;
+            write_json_term(triple.subject.as_ref().into(), sink)?;
+            sink.write_all(b",\"predicate\":")?;
+            write_json_term(triple.predicate.as_ref().into(), sink)?;
+            sink.write_all(b",\"object\":")?;
+            write_json_term(triple.object.as_ref(), sink)?;
+            sink.write_all(b"}}")?;
+        }
+    }
+    Ok(())
+}
+
+pub fn read_json_results(source: impl BufRead + 'static) -> Result<QueryResults, io::Error> {
+    let mut reader = JsonReader::from_reader(source);
+    let mut variables = None;
+    // The solutions are buffered if the results key is before the head key
+    let mut buffered_solutions: Option<Vec<Vec<(String, Term)>>> = None;
+
+    if reader.read_event()? != JsonEvent::StartObject {
+        return Err(invalid_data_error(
+            "SPARQL JSON results should be an object",
+        ));
+    }
+
+    loop {
+        let event = reader.read_event()?;
+        match event {
+            JsonEvent::ObjectKey(key) => match key.as_str() {
+                "head" => {
+                    let head = read_head(&mut reader)?;
+                    if let Some(solutions) = buffered_solutions.take() {
+                        let mapping = variables_mapping(&head);
+                        let solutions = solutions
+                            .into_iter()
+                            .map(|solution| bind_solution(&mapping, solution))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        return Ok(QueryResults::Solutions(QuerySolutionIter::new(
+                            Rc::new(parse_variables(head)?),
+                            Box::new(solutions.into_iter().map(Ok)),
+                        )));
+                    }
+                    variables = Some(head);
+                }
+                "results" => {
+                    read_bindings_start(&mut reader)?;
+                    let mut results = ResultsIterator {
+                        reader,
+                        mapping: BTreeMap::default(),
+                        finished: false,
+                    };
+                    if let Some(variables) = variables {
+                        results.mapping = variables_mapping(&variables);
+                        return Ok(QueryResults::Solutions(QuerySolutionIter::new(
+                            Rc::new(parse_variables(variables)?),
+                            Box::new(results),
+                        )));
+                    }
+                    let mut solutions = Vec::new();
+                    while let Some(solution) = results.read_solution()? {
+                        solutions.push(solution);
+                    }
+                    buffered_solutions = Some(solutions);
+                    reader = results.reader;
+                    // We skip the other keys of the results object
+                    loop {
+                        match reader.read_event()? {
+                            JsonEvent::ObjectKey(_) => skip_value(&mut reader)?,
+                            JsonEvent::EndObject => break,
+                            _ => return Err(invalid_data_error("Invalid results serialization")),
                         }
                     }
-                } else {
-                    write!(sink, "{}", c)
                 }
+                "boolean" => {
+                    return if let JsonEvent::Boolean(v) = reader.read_event()? {
+                        Ok(QueryResults::Boolean(v))
+                    } else {
+                        Err(invalid_data_error("Unexpected boolean value"))
+                    }
+                }
+                _ => {
+                    return Err(invalid_data_error(format!(
+                        "Expecting head or result key, found {}",
+                        key
+                    )));
+                }
+            },
+            JsonEvent::EndObject => {
+                return Err(invalid_data_error(if buffered_solutions.is_some() {
+                    "SPARQL tuple query results should contain a head key"
+                } else {
+                    "SPARQL results should contain a bindings key or a boolean key"
+                }))
             }
-        }?;
+            JsonEvent::Eof => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            _ => return Err(invalid_data_error("Invalid SPARQL results serialization")),
+        }
+    }
+}
+
+/// Reads the results object until the start of its bindings array
+fn read_bindings_start<R: BufRead>(reader: &mut JsonReader<R>) -> Result<(), io::Error> {
+    if reader.read_event()? != JsonEvent::StartObject {
+        return Err(invalid_data_error("'results' should be an object"));
+    }
+    loop {
+        match reader.read_event()? {
+            JsonEvent::ObjectKey(key) if key == "bindings" => break,
+            JsonEvent::ObjectKey(_) => skip_value(reader)?,
+            _ => {
+                return Err(invalid_data_error(
+                    "'results' should contain a 'bindings' key",
+                ))
+            }
+        }
+    }
+    if reader.read_event()? != JsonEvent::StartArray {
+        return Err(invalid_data_error("'bindings' should be an array"));
     }
-    sink.write_all(b"\"")?;
     Ok(())
 }
+
+fn parse_variables(variables: Vec<String>) -> Result<Vec<Variable>, io::Error> {
+    variables
+        .into_iter()
+        .map(Variable::new)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(invalid_data_error)
+}
+
+fn variables_mapping(variables: &[String]) -> BTreeMap<String, usize> {
+    let mut mapping = BTreeMap::default();
+    for (i, var) in variables.iter().enumerate() {
+        mapping.insert(var.clone(), i);
+    }
+    mapping
+}
+
+/// Orders the values of a solution according to the head variables
+fn bind_solution(
+    mapping: &BTreeMap<String, usize>,
+    solution: Vec<(String, Term)>,
+) -> Result<Vec<Option<Term>>, io::Error> {
+    let mut new_bindings = vec![None; mapping.len()];
+    for (variable, value) in solution {
+        let k = *mapping.get(&variable).ok_or_else(|| {
+            invalid_data_error(format!(
+                "The variable {} has not been defined in the header",
+                variable
+            ))
+        })?;
+        new_bindings[k] = Some(value);
+    }
+    Ok(new_bindings)
+}
+
+fn read_head<R: BufRead>(reader: &mut JsonReader<R>) -> Result<Vec<String>, io::Error> {
+    if reader.read_event()? != JsonEvent::StartObject {
+        return Err(invalid_data_error("head should be an object"));
+    }
+    let mut variables = None;
+    loop {
+        match reader.read_event()? {
+            JsonEvent::ObjectKey(key) => match key.as_str() {
+                "vars" => variables = Some(read_string_array(reader)?),
+                "link" => {
+                    read_string_array(reader)?;
+                }
+                _ => {
+                    return Err(invalid_data_error(format!(
+                        "Unexpected key in head: '{}'",
+                        key
+                    )))
+                }
+            },
+            JsonEvent::EndObject => return Ok(variables.unwrap_or_else(Vec::new)),
+            _ => return Err(invalid_data_error("Invalid head serialization")),
+        }
+    }
+}
+
+fn read_string_array<R: BufRead>(reader: &mut JsonReader<R>) -> Result<Vec<String>, io::Error> {
+    if reader.read_event()? != JsonEvent::StartArray {
+        return Err(invalid_data_error("Variable list should be an array"));
+    }
+    let mut elements = Vec::new();
+    loop {
+        match reader.read_event()? {
+            JsonEvent::String(s) => elements.push(s),
+            JsonEvent::EndArray => return Ok(elements),
+            _ => return Err(invalid_data_error("Variable names should be strings")),
+        }
+    }
+}
+
+fn skip_value<R: BufRead>(reader: &mut JsonReader<R>) -> Result<(), io::Error> {
+    let mut depth = 0_usize;
+    loop {
+        match reader.read_event()? {
+            JsonEvent::StartArray | JsonEvent::StartObject => depth += 1,
+            JsonEvent::EndArray | JsonEvent::EndObject => depth -= 1,
+            JsonEvent::ObjectKey(_) => continue,
+            JsonEvent::Eof => return Err(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            _ => (),
+        }
+        if depth == 0 {
+            return Ok(());
+        }
+    }
+}
+
+struct ResultsIterator<R: BufRead> {
+    reader: JsonReader<R>,
+    mapping: BTreeMap<String, usize>,
+    finished: bool,
+}
+
+impl<R: BufRead> Iterator for ResultsIterator<R> {
+    type Item = Result<Vec<Option<Term>>, EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<Vec<Option<Term>>, EvaluationError>> {
+        self.read_next().map_err(EvaluationError::from).transpose()
+    }
+}
+
+impl<R: BufRead> ResultsIterator<R> {
+    fn read_next(&mut self) -> Result<Option<Vec<Option<Term>>>, io::Error> {
+        match self.read_solution()? {
+            Some(solution) => Ok(Some(bind_solution(&self.mapping, solution)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the next solution of the bindings array with its variable names
+    fn read_solution(&mut self) -> Result<Option<Vec<(String, Term)>>, io::Error> {
+        if self.finished {
+            return Ok(None);
+        }
+        let mut solution = Vec::new();
+        loop {
+            match self.reader.read_event()? {
+                JsonEvent::StartObject => (),
+                JsonEvent::EndObject => return Ok(Some(solution)),
+                JsonEvent::EndArray | JsonEvent::Eof => {
+                    self.finished = true;
+                    return Ok(None);
+                }
+                JsonEvent::ObjectKey(key) => {
+                    let value = self.read_value(0)?;
+                    solution.push((key, value));
+                }
+                _ => return Err(invalid_data_error("Invalid result serialization")),
+            }
+        }
+    }
+
+    fn read_value(&mut self, number_of_recursive_calls: usize) -> Result<Term, io::Error> {
+        enum Type {
+            Uri,
+            BNode,
+            Literal,
+            Triple,
+        }
+        enum State {
+            Type,
+            Value,
+            Lang,
+            Datatype,
+        }
+        let mut state = None;
+        let mut t = None;
+        let mut value = None;
+        let mut lang = None;
+        let mut datatype = None;
+        let mut triple = None;
+        if number_of_recursive_calls == MAX_NUMBER_OF_NESTED_TRIPLES {
+            return Err(invalid_data_error(
+                "Too many nested triples in the term serialization",
+            ));
+        }
+        if self.reader.read_event()? != JsonEvent::StartObject {
+            return Err(invalid_data_error(
+                "Term serializations should be an object",
+            ));
+        }
+        loop {
+            match self.reader.read_event()? {
+                JsonEvent::ObjectKey(key) => match key.as_str() {
+                    "type" => state = Some(State::Type),
+                    "value" => state = Some(State::Value),
+                    "xml:lang" => state = Some(State::Lang),
+                    "datatype" => state = Some(State::Datatype),
+                    _ => {
+                        return Err(invalid_data_error(format!(
+                            "Unexpected key in term serialization: '{}'",
+                            key
+                        )))
+                    }
+                },
+                JsonEvent::String(s) => match state {
+                    Some(State::Type) => {
+                        match s.as_str() {
+                            "uri" => t = Some(Type::Uri),
+                            "bnode" => t = Some(Type::BNode),
+                            "literal" | "typed-literal" => t = Some(Type::Literal),
+                            "triple" => t = Some(Type::Triple),
+                            _ => {
+                                return Err(invalid_data_error(format!(
+                                    "Unexpected term type: '{}'",
+                                    s
+                                )))
+                            }
+                        };
+                        state = None;
+                    }
+                    Some(State::Value) => {
+                        value = Some(s);
+                        state = None;
+                    }
+                    Some(State::Lang) => {
+                        lang = Some(s);
+                        state = None;
+                    }
+                    Some(State::Datatype) => {
+                        datatype = Some(NamedNode::new(s).map_err(|e| {
+                            invalid_data_error(format!("Invalid datatype value: {}", e))
+                        })?);
+                        state = None;
+                    }
+                    _ => (), // impossible
+                },
+                JsonEvent::StartObject if matches!(state, Some(State::Value)) => {
+                    triple = Some(self.read_triple_value(number_of_recursive_calls)?);
+                    state = None;
+                }
+                JsonEvent::EndObject => {
+                    let value = || {
+                        value.ok_or_else(|| {
+                            invalid_data_error("Term serialization should have a value key")
+                        })
+                    };
+                    return match t {
+                        None => Err(invalid_data_error(
+                            "Term serialization should have a type key",
+                        )),
+                        Some(Type::Uri) => Ok(NamedNode::new(value()?)
+                            .map_err(|e| invalid_data_error(format!("Invalid uri value: {}", e)))?
+                            .into()),
+                        Some(Type::BNode) => Ok(BlankNode::new(value()?)
+                            .map_err(|e| invalid_data_error(format!("Invalid bnode value: {}", e)))?
+                            .into()),
+                        Some(Type::Literal) => Ok(build_literal(value()?, lang, datatype)?.into()),
+                        Some(Type::Triple) => triple.map(Term::from).ok_or_else(|| {
+                            invalid_data_error("Triple serialization should have an object value")
+                        }),
+                    };
+                }
+                _ => return Err(invalid_data_error("Invalid term serialization")),
+            }
+        }
+    }
+
+    /// Reads the content of a triple value object, after its opening brace
+    fn read_triple_value(&mut self, number_of_recursive_calls: usize) -> Result<Triple, io::Error> {
+        let mut subject = None;
+        let mut predicate = None;
+        let mut object = None;
+        loop {
+            match self.reader.read_event()? {
+                JsonEvent::ObjectKey(key) => match key.as_str() {
+                    "subject" => subject = Some(self.read_value(number_of_recursive_calls + 1)?),
+                    "predicate" => {
+                        predicate = Some(self.read_value(number_of_recursive_calls + 1)?)
+                    }
+                    "object" => object = Some(self.read_value(number_of_recursive_calls + 1)?),
+                    _ => {
+                        return Err(invalid_data_error(format!(
+                            "Unexpected key in triple serialization: '{}'",
+                            key
+                        )))
+                    }
+                },
+                JsonEvent::EndObject => {
+                    return Ok(Triple::new(
+                        match subject {
+                            Some(Term::NamedNode(node)) => Subject::from(node),
+                            Some(Term::BlankNode(node)) => node.into(),
+                            Some(Term::Triple(triple)) => triple.into(),
+                            Some(Term::Literal(_)) => {
+                                return Err(invalid_data_error(
+                                    "The triple subject should not be a literal",
+                                ))
+                            }
+                            None => {
+                                return Err(invalid_data_error(
+                                    "Triple serialization should have a subject",
+                                ))
+                            }
+                        },
+                        match predicate {
+                            Some(Term::NamedNode(node)) => node,
+                            Some(_) => {
+                                return Err(invalid_data_error(
+                                    "The triple predicate should be an IRI",
+                                ))
+                            }
+                            None => {
+                                return Err(invalid_data_error(
+                                    "Triple serialization should have a predicate",
+                                ))
+                            }
+                        },
+                        object.ok_or_else(|| {
+                            invalid_data_error("Triple serialization should have an object")
+                        })?,
+                    ));
+                }
+                _ => return Err(invalid_data_error("Invalid triple serialization")),
+            }
+        }
+    }
+}
+
+fn build_literal(
+    value: String,
+    lang: Option<String>,
+    datatype: Option<NamedNode>,
+) -> Result<Literal, io::Error> {
+    match lang {
+        Some(lang) => {
+            if let Some(datatype) = datatype {
+                if datatype.as_ref() != rdf::LANG_STRING {
+                    return Err(invalid_data_error(format!(
+                        "xml:lang value '{}' provided with the datatype {}",
+                        lang, datatype
+                    )));
+                }
+            }
+            Literal::new_language_tagged_literal(value, &lang).map_err(|e| {
+                invalid_data_error(format!("Invalid xml:lang value '{}': {}", lang, e))
+            })
+        }
+        None => Ok(match datatype {
+            Some(datatype) => Literal::new_typed_literal(value, datatype),
+            None => Literal::new_simple_literal(value),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::vocab::xsd;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_boolean_roundtrip() -> Result<(), EvaluationError> {
+        for value in &[true, false] {
+            let mut buffer = Vec::new();
+            write_json_results(QueryResults::Boolean(*value), &mut buffer)?;
+            match read_json_results(Cursor::new(buffer))? {
+                QueryResults::Boolean(v) => assert_eq!(v, *value),
+                _ => panic!("A boolean result was expected"),
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_solutions_roundtrip() -> Result<(), EvaluationError> {
+        let variables = vec![Variable::new_unchecked("x"), Variable::new_unchecked("y")];
+        let solutions: Vec<Vec<Option<Term>>> = vec![
+            vec![
+                Some(NamedNode::new_unchecked("http://example/x").into()),
+                Some(Literal::new_simple_literal("String-with-dquote\"\n").into()),
+            ],
+            vec![Some(BlankNode::new_unchecked("b0").into()), None],
+            vec![
+                None,
+                Some(Literal::new_language_tagged_literal_unchecked("foo", "en").into()),
+            ],
+            vec![
+                None,
+                Some(Literal::new_typed_literal("1", xsd::INTEGER).into()),
+            ],
+            vec![
+                Some(
+                    Triple::new(
+                        BlankNode::new_unchecked("b0"),
+                        NamedNode::new_unchecked("http://example/p"),
+                        Triple::new(
+                            NamedNode::new_unchecked("http://example/s"),
+                            NamedNode::new_unchecked("http://example/p"),
+                            Literal::new_simple_literal("o"),
+                        ),
+                    )
+                    .into(),
+                ),
+                None,
+            ],
+            vec![None, None],
+        ];
+        let mut buffer = Vec::new();
+        write_json_results(
+            QueryResults::Solutions(QuerySolutionIter::new(
+                Rc::new(variables.clone()),
+                Box::new(solutions.clone().into_iter().map(Ok)),
+            )),
+            &mut buffer,
+        )?;
+        if let QueryResults::Solutions(iter) = read_json_results(Cursor::new(buffer))? {
+            assert_eq!(iter.variables(), variables.as_slice());
+            let actual = iter
+                .map(|s| Ok(s?.values().map(|v| v.cloned()).collect::<Vec<_>>()))
+                .collect::<Result<Vec<_>, EvaluationError>>()?;
+            assert_eq!(actual, solutions);
+        } else {
+            panic!("Solutions were expected")
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_typed_literal_and_link() -> Result<(), EvaluationError> {
+        let data = r#"{"head":{"link":["http://example.com/doc"],"vars":["v"]},"results":{"bindings":[{"v":{"type":"typed-literal","value":"1","datatype":"http://www.w3.org/2001/XMLSchema#integer"}}]}}"#;
+        if let QueryResults::Solutions(mut iter) = read_json_results(Cursor::new(data))? {
+            assert_eq!(
+                iter.next().unwrap()?.get("v"),
+                Some(&Literal::new_typed_literal("1", xsd::INTEGER).into())
+            );
+            assert!(iter.next().is_none());
+        } else {
+            panic!("Solutions were expected")
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_results_before_head() -> Result<(), EvaluationError> {
+        let data = r#"{"results":{"bindings":[{"y":{"type":"literal","value":"a"}},{"x":{"type":"uri","value":"http://example.com/x"}}],"distinct":false},"head":{"vars":["x","y"]}}"#;
+        if let QueryResults::Solutions(iter) = read_json_results(Cursor::new(data))? {
+            assert_eq!(
+                iter.variables(),
+                &[Variable::new_unchecked("x"), Variable::new_unchecked("y")]
+            );
+            let actual = iter
+                .map(|s| Ok(s?.values().map(|v| v.cloned()).collect::<Vec<_>>()))
+                .collect::<Result<Vec<_>, EvaluationError>>()?;
+            assert_eq!(
+                actual,
+                vec![
+                    vec![None, Some(Literal::new_simple_literal("a").into())],
+                    vec![
+                        Some(NamedNode::new_unchecked("http://example.com/x").into()),
+                        None
+                    ]
+                ]
+            );
+        } else {
+            panic!("Solutions were expected")
+        }
+        assert!(read_json_results(Cursor::new(r#"{"results":{"bindings":[]}}"#)).is_err());
+        assert!(read_json_results(Cursor::new(
+            r#"{"results":{"bindings":[{"z":{"type":"literal","value":"a"}}]},"head":{"vars":["x"]}}"#
+        ))
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_solution_after_bindings_end() -> Result<(), EvaluationError> {
+        let data = r#"{"head":{"vars":["x"]},"results":{"bindings":[{"x":{"type":"literal","value":"a"}}]},"foo":{}}"#;
+        if let QueryResults::Solutions(mut iter) = read_json_results(Cursor::new(data))? {
+            assert!(iter.next().is_some());
+            assert!(iter.next().is_none());
+            assert!(iter.next().is_none());
+            assert!(iter.next().is_none());
+        } else {
+            panic!("Solutions were expected")
+        }
+        Ok(())
+    }
+}
@@ -1,26 +1,54 @@
 //! [SPARQL](https://www.w3.org/TR/sparql11-overview/) implementation.
 //!
 //! Stores execute SPARQL. See [`MemoryStore`](super::store::memory::MemoryStore::query()) for an example.
+//!
+//! If the store [full-text index](super::store::memory::MemoryStore::set_text_index()) is enabled,
+//! the string literals could be searched with the `text:query` property function
+//! (`PREFIX text: <http://jena.apache.org/text#>`) using the same syntax as [Apache Jena](https://jena.apache.org/documentation/query/text-query.html):
+//! * `?s text:query "query"` binds `?s` to the subjects of the quads whose object is a string literal containing all the query words.
+//! * `(?s ?score ?literal) text:query (property "query" limit)` also binds the relevance score and the matching literal
+//!   and restricts the search to the literals used with `property` and to the `limit` best matching literals.
+//!   The `?score`, `?literal`, `property` and `limit` parts are optional.
+//!
+//! The matching is case-insensitive and the words are the maximal sequences of alphanumeric characters.
+//!
+//! The [GeoSPARQL](https://www.ogc.org/standards/geosparql) `geof:distance`, `geof:sfEquals`, `geof:sfDisjoint`, `geof:sfIntersects`,
+//! `geof:sfWithin` and `geof:sfContains` functions (`PREFIX geof: <http://www.opengis.net/def/function/geosparql/>`)
+//! are supported on the `geo:wktLiteral` values.
+//! The coordinates are longitudes and latitudes, the topological relations are evaluated in the plane of the coordinates
+//! and `geof:distance` returns the great-circle distance in metres, kilometres, degrees or radians
+//! depending on its `uom:metre`, `uom:kilometre`, `uom:degree` or `uom:radian` unit argument
+//! (`PREFIX uom: <http://www.opengis.net/def/uom/OGC/1.0/>`).
 
 pub mod algebra;
 mod csv_results;
 mod dataset;
 mod error;
 mod eval;
-mod http;
+mod explanation;
+mod geo;
+pub(crate) mod http;
+mod interrupt;
 mod json_results;
 mod model;
 mod parser;
+pub(crate) mod pattern;
 mod plan;
 mod plan_builder;
 mod service;
+mod spill;
 mod update;
 mod xml_results;
 
+use crate::model::{NamedNode, Term};
 pub use crate::sparql::algebra::{Query, Update};
 use crate::sparql::dataset::DatasetView;
 pub use crate::sparql::error::EvaluationError;
 use crate::sparql::eval::SimpleEvaluator;
+use crate::sparql::explanation::{EvaluationStats, ExplanationBuilder};
+pub use crate::sparql::explanation::{ExplanationNode, QueryExplanation};
+pub use crate::sparql::interrupt::CancellationToken;
+use crate::sparql::interrupt::Interrupter;
 pub use crate::sparql::model::QueryResults;
 pub use crate::sparql::model::QueryResultsFormat;
 pub use crate::sparql::model::QuerySolution;
@@ -28,21 +56,27 @@ pub use crate::sparql::model::QuerySolutionIter;
 pub use crate::sparql::model::QueryTripleIter;
 pub use crate::sparql::model::{Variable, VariableNameParseError};
 pub use crate::sparql::parser::ParseError;
+use crate::sparql::plan::{CustomAggregates, CustomFunctions};
 use crate::sparql::plan_builder::PlanBuilder;
 pub use crate::sparql::service::ServiceHandler;
 use crate::sparql::service::{EmptyServiceHandler, ErrorConversionServiceHandler};
+use crate::sparql::spill::MemoryLimit;
 use crate::sparql::update::SimpleUpdateEvaluator;
 use crate::store::numeric_encoder::StrContainer;
 use crate::store::{ReadableEncodedStore, StoreOrParseError, WritableEncodedStore};
 use std::convert::TryInto;
 use std::io;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 
 pub(crate) fn evaluate_query<R: ReadableEncodedStore + 'static>(
     store: R,
     query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
     options: QueryOptions,
 ) -> Result<QueryResults, EvaluationError> {
+    let interrupter = options.interrupter();
+    let memory_limit = options.memory_limit();
     match query.try_into().map_err(|e| e.into())? {
         Query::Select {
             pattern,
@@ -50,11 +84,20 @@ pub(crate) fn evaluate_query<R: ReadableEncodedStore + 'static>(
             dataset,
         } => {
             let dataset = DatasetView::new(store, &dataset)?;
-            let (plan, variables) = PlanBuilder::build(&dataset, &pattern)?;
+            let (plan, variables) = PlanBuilder::build(
+                &dataset,
+                &pattern,
+                &options.custom_functions,
+                &options.custom_aggregates,
+            )?;
             SimpleEvaluator::new(
                 Rc::new(dataset),
                 base_iri.map(Rc::new),
                 options.service_handler,
+                options.custom_functions,
+                options.custom_aggregates,
+                interrupter,
+                memory_limit,
             )
             .evaluate_select_plan(&plan, Rc::new(variables))
         }
@@ -64,11 +107,20 @@ pub(crate) fn evaluate_query<R: ReadableEncodedStore + 'static>(
             dataset,
         } => {
             let dataset = DatasetView::new(store, &dataset)?;
-            let (plan, _) = PlanBuilder::build(&dataset, &pattern)?;
+            let (plan, _) = PlanBuilder::build(
+                &dataset,
+                &pattern,
+                &options.custom_functions,
+                &options.custom_aggregates,
+            )?;
             SimpleEvaluator::new(
                 Rc::new(dataset),
                 base_iri.map(Rc::new),
                 options.service_handler,
+                options.custom_functions,
+                options.custom_aggregates,
+                interrupter,
+                memory_limit,
             )
             .evaluate_ask_plan(&plan)
         }
@@ -79,12 +131,21 @@ pub(crate) fn evaluate_query<R: ReadableEncodedStore + 'static>(
             dataset,
         } => {
             let dataset = DatasetView::new(store, &dataset)?;
-            let (plan, variables) = PlanBuilder::build(&dataset, &pattern)?;
+            let (plan, variables) = PlanBuilder::build(
+                &dataset,
+                &pattern,
+                &options.custom_functions,
+                &options.custom_aggregates,
+            )?;
             let construct = PlanBuilder::build_graph_template(&dataset, &template, variables)?;
             SimpleEvaluator::new(
                 Rc::new(dataset),
                 base_iri.map(Rc::new),
                 options.service_handler,
+                options.custom_functions,
+                options.custom_aggregates,
+                interrupter,
+                memory_limit,
             )
             .evaluate_construct_plan(&plan, construct)
         }
@@ -94,17 +155,100 @@ pub(crate) fn evaluate_query<R: ReadableEncodedStore + 'static>(
             dataset,
         } => {
             let dataset = DatasetView::new(store, &dataset)?;
-            let (plan, _) = PlanBuilder::build(&dataset, &pattern)?;
+            let (plan, _) = PlanBuilder::build(
+                &dataset,
+                &pattern,
+                &options.custom_functions,
+                &options.custom_aggregates,
+            )?;
             SimpleEvaluator::new(
                 Rc::new(dataset),
                 base_iri.map(Rc::new),
                 options.service_handler,
+                options.custom_functions,
+                options.custom_aggregates,
+                interrupter,
+                memory_limit,
             )
             .evaluate_describe_plan(&plan)
         }
     }
 }
 
+pub(crate) fn explain_query<R: ReadableEncodedStore + 'static>(
+    store: R,
+    query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+    options: QueryOptions,
+    with_stats: bool,
+) -> Result<QueryExplanation, EvaluationError> {
+    let interrupter = options.interrupter();
+    let memory_limit = options.memory_limit();
+    let query = query.try_into().map_err(|e| e.into())?;
+    let dataset = Rc::new(DatasetView::new(store, query.dataset())?);
+    let (pattern, base_iri) = match &query {
+        Query::Select {
+            pattern, base_iri, ..
+        }
+        | Query::Construct {
+            pattern, base_iri, ..
+        }
+        | Query::Describe {
+            pattern, base_iri, ..
+        } => (pattern, base_iri),
+        Query::Ask {
+            pattern, base_iri, ..
+        } => (pattern.as_ref(), base_iri),
+    };
+    let (plan, variables) = PlanBuilder::build(
+        dataset.as_ref(),
+        pattern,
+        &options.custom_functions,
+        &options.custom_aggregates,
+    )?;
+    let stats = if with_stats {
+        let stats = Rc::new(EvaluationStats::default());
+        let evaluator = SimpleEvaluator::new(
+            dataset.clone(),
+            base_iri.clone().map(Rc::new),
+            options.service_handler,
+            options.custom_functions.clone(),
+            options.custom_aggregates.clone(),
+            interrupter,
+            memory_limit,
+        )
+        .with_stats(stats.clone());
+        let results = match &query {
+            Query::Select { .. } => {
+                evaluator.evaluate_select_plan(&plan, Rc::new(variables.clone()))
+            }
+            Query::Ask { .. } => evaluator.evaluate_ask_plan(&plan),
+            Query::Construct { template, .. } => evaluator.evaluate_construct_plan(
+                &plan,
+                PlanBuilder::build_graph_template(dataset.as_ref(), template, variables.clone())?,
+            ),
+            Query::Describe { .. } => evaluator.evaluate_describe_plan(&plan),
+        }?;
+        // We consume the results to run the full evaluation
+        match results {
+            QueryResults::Solutions(solutions) => {
+                for solution in solutions {
+                    solution?;
+                }
+            }
+            QueryResults::Graph(triples) => {
+                for triple in triples {
+                    triple?;
+                }
+            }
+            QueryResults::Boolean(_) => (),
+        }
+        Some(stats)
+    } else {
+        None
+    };
+    ExplanationBuilder::new(dataset.as_ref(), stats.as_deref()).build(&plan, &variables)
+}
+
 /// Options for SPARQL query evaluation.
 ///
 ///
@@ -113,6 +257,13 @@ pub(crate) fn evaluate_query<R: ReadableEncodedStore + 'static>(
 #[derive(Clone)]
 pub struct QueryOptions {
     pub(crate) service_handler: Rc<dyn ServiceHandler<Error = EvaluationError>>,
+    pub(crate) custom_functions: Rc<CustomFunctions>,
+    pub(crate) custom_aggregates: Rc<CustomAggregates>,
+    timeout: Option<Duration>,
+    cancellation_token: Option<CancellationToken>,
+    memory_limit: Option<usize>,
+    spill_directory: Option<PathBuf>,
+    spilling: bool,
 }
 
 impl Default for QueryOptions {
@@ -124,6 +275,13 @@ impl Default for QueryOptions {
             } else {
                 Rc::new(EmptyServiceHandler)
             },
+            custom_functions: Rc::new(CustomFunctions::new()),
+            custom_aggregates: Rc::new(CustomAggregates::new()),
+            timeout: None,
+            cancellation_token: None,
+            memory_limit: None,
+            spill_directory: None,
+            spilling: true,
         }
     }
 }
@@ -142,6 +300,215 @@ impl QueryOptions {
         self.service_handler = Rc::new(EmptyServiceHandler);
         self
     }
+
+    /// Adds a custom SPARQL evaluation function.
+    ///
+    /// The function is called with the evaluated arguments and returns `None` to raise an evaluation error.
+    /// It takes precedence over the built-in functions with the same IRI.
+    ///
+    /// Example with a function serializing terms to N-Triples:
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{QueryOptions, QueryResults};
+    ///
+    /// let store = MemoryStore::new();
+    ///
+    /// if let QueryResults::Solutions(mut solutions) = store.query_opt(
+    ///     "SELECT (<http://www.w3.org/ns/formats/N-Triples>(1) AS ?nt) WHERE {}",
+    ///     QueryOptions::default().with_custom_function(
+    ///         NamedNode::new("http://www.w3.org/ns/formats/N-Triples")?,
+    ///         |args| args.get(0).map(|t| Literal::from(t.to_string()).into())
+    ///     )
+    /// )? {
+    ///     assert_eq!(
+    ///         solutions.next().unwrap()?.get("nt"),
+    ///         Some(&Literal::from("\"1\"^^<http://www.w3.org/2001/XMLSchema#integer>").into())
+    ///     );
+    /// }
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_custom_function(
+        mut self,
+        name: NamedNode,
+        evaluator: impl Fn(&[Term]) -> Option<Term> + 'static,
+    ) -> Self {
+        Rc::make_mut(&mut self.custom_functions).insert(name, Rc::new(evaluator));
+        self
+    }
+
+    /// Adds a custom SPARQL aggregate function.
+    ///
+    /// `accumulator` is called to create a new [`AggregateFunctionAccumulator`] for each group of solutions.
+    /// The aggregate could be called with the `DISTINCT` modifier like the built-in aggregates.
+    ///
+    /// Example with an aggregate returning the longest string:
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{AggregateFunctionAccumulator, QueryOptions, QueryResults};
+    ///
+    /// #[derive(Default)]
+    /// struct Longest(Option<Literal>);
+    ///
+    /// impl AggregateFunctionAccumulator for Longest {
+    ///     fn add(&mut self, element: Term) {
+    ///         if let Term::Literal(literal) = element {
+    ///             if self.0.as_ref().map_or(true, |l| l.value().len() < literal.value().len()) {
+    ///                 self.0 = Some(literal);
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     fn finish(&mut self) -> Option<Term> {
+    ///         self.0.take().map(Term::from)
+    ///     }
+    /// }
+    ///
+    /// let store = MemoryStore::new();
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// store.insert(Quad::new(ex.clone(), ex.clone(), Literal::from("foo"), None));
+    /// store.insert(Quad::new(ex.clone(), ex.clone(), Literal::from("foobar"), None));
+    ///
+    /// if let QueryResults::Solutions(mut solutions) = store.query_opt(
+    ///     "SELECT (<http://example.com/longest>(?o) AS ?l) WHERE { ?s ?p ?o }",
+    ///     QueryOptions::default().with_custom_aggregate(
+    ///         NamedNode::new("http://example.com/longest")?,
+    ///         || Box::new(Longest::default())
+    ///     )
+    /// )? {
+    ///     assert_eq!(solutions.next().unwrap()?.get("l"), Some(&Literal::from("foobar").into()));
+    /// }
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_custom_aggregate(
+        mut self,
+        name: NamedNode,
+        accumulator: impl Fn() -> Box<dyn AggregateFunctionAccumulator> + 'static,
+    ) -> Self {
+        Rc::make_mut(&mut self.custom_aggregates).insert(name, Rc::new(accumulator));
+        self
+    }
+
+    /// Sets a maximal duration for the evaluation.
+    ///
+    /// The timeout is counted from the start of the evaluation and also covers the iteration over the returned results.
+    /// When it is reached the evaluation returns an [`EvaluationError::Timeout`] error.
+    ///
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::sparql::{EvaluationError, QueryOptions, QueryResults};
+    /// use std::time::Duration;
+    ///
+    /// let store = MemoryStore::new();
+    /// if let QueryResults::Solutions(mut solutions) = store.query_opt(
+    ///     "SELECT * WHERE { ?s <http://example.com/p>* ?o }",
+    ///     QueryOptions::default().with_timeout(Duration::from_secs(0)),
+    /// )? {
+    ///     assert!(matches!(solutions.next(), Some(Err(EvaluationError::Timeout))));
+    /// }
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Allows to cancel the evaluation using the given [`CancellationToken`].
+    ///
+    /// When the token is cancelled the evaluation returns an [`EvaluationError::Cancelled`] error.
+    #[inline]
+    pub fn with_cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = Some(cancellation_token);
+        self
+    }
+
+    /// Limits the memory used to buffer solutions by the `ORDER BY`, `GROUP BY` and `DISTINCT` operators.
+    ///
+    /// The limit is an estimation in bytes that applies to each of these operators separately.
+    /// When it is reached the buffered solutions are written to temporary files:
+    /// `ORDER BY` does an external merge sort and `GROUP BY` and `DISTINCT` split their hash tables into partitions evaluated one after the other.
+    /// The temporary files are written in the system temporary directory unless [`with_spill_directory`](QueryOptions::with_spill_directory()) is used.
+    ///
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::model::*;
+    /// use oxigraph::sparql::{EvaluationError, QueryOptions, QueryResults};
+    ///
+    /// let store = MemoryStore::new();
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// for i in 0..100 {
+    ///     store.insert(Quad::new(ex.clone(), ex.clone(), Literal::from(i), GraphName::DefaultGraph));
+    /// }
+    ///
+    /// // The solutions are sorted using temporary files
+    /// if let QueryResults::Solutions(solutions) = store.query_opt(
+    ///     "SELECT ?o WHERE { ?s ?p ?o } ORDER BY DESC(?o)",
+    ///     QueryOptions::default().with_memory_limit(1024),
+    /// )? {
+    ///     let values = solutions.map(|s| Ok(s?.get("o").cloned())).collect::<Result<Vec<_>, EvaluationError>>()?;
+    ///     assert_eq!(values.len(), 100);
+    ///     assert_eq!(values[0], Some(Literal::from(99).into()));
+    /// }
+    ///
+    /// // Spilling is disabled: the evaluation fails
+    /// if let QueryResults::Solutions(mut solutions) = store.query_opt(
+    ///     "SELECT ?o WHERE { ?s ?p ?o } ORDER BY DESC(?o)",
+    ///     QueryOptions::default().with_memory_limit(1024).without_spilling(),
+    /// )? {
+    ///     assert!(matches!(solutions.next(), Some(Err(EvaluationError::MemoryLimitExceeded))));
+    /// }
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    #[inline]
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Sets the directory where the temporary files are written when the [memory limit](QueryOptions::with_memory_limit()) is reached.
+    #[inline]
+    pub fn with_spill_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.spill_directory = Some(directory.into());
+        self
+    }
+
+    /// Disables the use of temporary files when the [memory limit](QueryOptions::with_memory_limit()) is reached.
+    ///
+    /// The evaluation returns an [`EvaluationError::MemoryLimitExceeded`] error instead.
+    #[inline]
+    pub fn without_spilling(mut self) -> Self {
+        self.spilling = false;
+        self
+    }
+
+    pub(crate) fn interrupter(&self) -> Option<Rc<Interrupter>> {
+        Interrupter::new(self.timeout, self.cancellation_token.clone())
+    }
+
+    pub(crate) fn memory_limit(&self) -> Option<Rc<MemoryLimit>> {
+        MemoryLimit::new(
+            self.memory_limit,
+            self.spill_directory.clone(),
+            self.spilling,
+        )
+    }
+}
+
+/// An accumulator computing the value of a custom aggregate function
+/// registered with [`QueryOptions::with_custom_aggregate`] for a group of solutions.
+pub trait AggregateFunctionAccumulator {
+    /// Adds the value of the aggregate function parameter for a solution of the group.
+    ///
+    /// If the evaluation of the parameter fails for a solution the aggregate value is not bound
+    /// and the accumulator is not called anymore.
+    fn add(&mut self, element: Term);
+
+    /// Returns the aggregate value or `None` if it is not bound.
+    fn finish(&mut self) -> Option<Term>;
 }
 
 /// Options for SPARQL update evaluation
@@ -195,3 +562,184 @@ where
     SimpleUpdateEvaluator::new(read, write, update.base_iri.map(Rc::new), options)
         .eval_all(&update.operations)
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::model::*;
+    use crate::MemoryStore;
+
+    /// Evaluates a `SELECT` query and returns the values of its solutions
+    pub(crate) fn select(
+        store: &MemoryStore,
+        query: &str,
+        options: QueryOptions,
+    ) -> Result<Vec<Vec<Option<Term>>>, EvaluationError> {
+        if let QueryResults::Solutions(solutions) = store.query_opt(query, options)? {
+            solutions
+                .map(|solution| Ok(solution?.values().map(|v| v.cloned()).collect()))
+                .collect()
+        } else {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_custom_aggregate() -> Result<(), EvaluationError> {
+        /// Computes the median of the integer values
+        #[derive(Default)]
+        struct Values(Vec<i64>);
+
+        impl AggregateFunctionAccumulator for Values {
+            fn add(&mut self, element: Term) {
+                if let Term::Literal(literal) = element {
+                    if let Ok(value) = literal.value().parse() {
+                        self.0.push(value);
+                    }
+                }
+            }
+
+            fn finish(&mut self) -> Option<Term> {
+                self.0.sort_unstable();
+                self.0
+                    .get(self.0.len() / 2)
+                    .map(|value| Literal::from(*value).into())
+            }
+        }
+
+        let store = MemoryStore::new();
+        let p = NamedNodeRef::new_unchecked("http://example.com/p");
+        for (subject, value) in &[("a", 1), ("a", 2), ("a", 10), ("b", 5), ("b", 6), ("b", 7)] {
+            store.insert(QuadRef::new(
+                NamedNodeRef::new_unchecked(&format!("http://example.com/{}", subject)),
+                p,
+                &Literal::from(*value),
+                None,
+            ));
+        }
+        let median = |query: &str| {
+            select(
+                &store,
+                &format!(
+                    "PREFIX ex: <http://example.com/> SELECT {} ORDER BY ?s",
+                    query
+                ),
+                QueryOptions::default().with_custom_aggregate(
+                    NamedNode::new_unchecked("http://example.com/median"),
+                    || Box::new(Values::default()),
+                ),
+            )
+        };
+
+        assert_eq!(
+            median("?s (ex:median(?o) AS ?m) WHERE { ?s ex:p ?o } GROUP BY ?s")?,
+            vec![
+                vec![
+                    Some(NamedNode::new_unchecked("http://example.com/a").into()),
+                    Some(Literal::from(2).into())
+                ],
+                vec![
+                    Some(NamedNode::new_unchecked("http://example.com/b").into()),
+                    Some(Literal::from(6).into())
+                ]
+            ]
+        );
+        assert_eq!(
+            median("(ex:median(?o) AS ?m) WHERE { ?s ex:p ?o }")?,
+            vec![vec![Some(Literal::from(6).into())]]
+        );
+        assert_eq!(
+            median("?s (ex:median(DISTINCT ?o) + 1 AS ?m) WHERE { ?s ex:p ?o } GROUP BY ?s HAVING(ex:median(?o) > 3)")?,
+            vec![vec![
+                Some(NamedNode::new_unchecked("http://example.com/b").into()),
+                Some(Literal::from(7).into())
+            ]]
+        );
+        assert!(store
+            .query("SELECT (<http://example.com/median>(DISTINCT ?o) AS ?m) WHERE { ?s ?p ?o }")
+            .is_err());
+        Ok(())
+    }
+
+    fn subquery_store() -> MemoryStore {
+        let store = MemoryStore::new();
+        let p = NamedNodeRef::new_unchecked("http://example.com/p");
+        for (s, o) in &[("http://example.com/a", 1), ("http://example.com/b", 2)] {
+            store.insert(Quad::new(
+                NamedNodeRef::new_unchecked(s),
+                p,
+                Literal::from(*o),
+                None,
+            ));
+        }
+        store
+    }
+
+    #[test]
+    fn test_correlated_subquery() -> Result<(), EvaluationError> {
+        let store = subquery_store();
+        // The subquery is evaluated independently of the outer ?s binding
+        assert_eq!(
+            select(
+                &store,
+                "PREFIX ex: <http://example.com/> SELECT ?s WHERE { ?s ex:p ?o FILTER EXISTS { { SELECT ?s WHERE { ?s ex:p ?o2 } ORDER BY DESC(?o2) LIMIT 1 } } }",
+                QueryOptions::default()
+            )?,
+            vec![vec![Some(
+                NamedNode::new_unchecked("http://example.com/b").into()
+            )]]
+        );
+        assert_eq!(
+            select(
+                &store,
+                "PREFIX ex: <http://example.com/> SELECT ?s ?m WHERE { ?s ex:p ?o OPTIONAL { SELECT ?s (MAX(?o2) AS ?m) WHERE { ?s ex:p ?o2 FILTER(?o2 > 1) } GROUP BY ?s } } ORDER BY ?s",
+                QueryOptions::default()
+            )?,
+            vec![
+                vec![
+                    Some(NamedNode::new_unchecked("http://example.com/a").into()),
+                    None
+                ],
+                vec![
+                    Some(NamedNode::new_unchecked("http://example.com/b").into()),
+                    Some(Literal::from(2).into())
+                ]
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_non_correlated_subquery() -> Result<(), EvaluationError> {
+        let store = subquery_store();
+        assert_eq!(
+            select(
+                &store,
+                "PREFIX ex: <http://example.com/> SELECT ?s ?c WHERE { ?s ex:p ?o OPTIONAL { SELECT (COUNT(*) AS ?c) WHERE { ?x ex:p ?y } } } ORDER BY ?s",
+                QueryOptions::default()
+            )?,
+            vec![
+                vec![
+                    Some(NamedNode::new_unchecked("http://example.com/a").into()),
+                    Some(Literal::from(2).into())
+                ],
+                vec![
+                    Some(NamedNode::new_unchecked("http://example.com/b").into()),
+                    Some(Literal::from(2).into())
+                ]
+            ]
+        );
+        assert_eq!(
+            select(
+                &store,
+                "PREFIX ex: <http://example.com/> SELECT ?s WHERE { ?s ex:p ?o FILTER EXISTS { SELECT ?x WHERE { ?x ex:p 2 } } } ORDER BY ?s",
+                QueryOptions::default()
+            )?,
+            vec![
+                vec![Some(NamedNode::new_unchecked("http://example.com/a").into())],
+                vec![Some(NamedNode::new_unchecked("http://example.com/b").into())]
+            ]
+        );
+        Ok(())
+    }
+}
@@ -4,7 +4,7 @@ use crate::io::GraphSerializer;
 use crate::model::*;
 use crate::sparql::csv_results::{read_tsv_results, write_csv_results, write_tsv_results};
 use crate::sparql::error::EvaluationError;
-use crate::sparql::json_results::write_json_results;
+use crate::sparql::json_results::{read_json_results, write_json_results};
 use crate::sparql::xml_results::{read_xml_results, write_xml_results};
 use rand::random;
 use std::error::Error;
@@ -30,9 +30,7 @@ impl QueryResults {
     ) -> Result<Self, io::Error> {
         match format {
             QueryResultsFormat::Xml => read_xml_results(reader),
-            QueryResultsFormat::Json => Err(invalid_input_error(
-                "JSON SPARQL results format parsing has not been implemented yet",
-            )), //TODO: implement
+            QueryResultsFormat::Json => read_json_results(reader),
             QueryResultsFormat::Csv => Err(invalid_input_error(
                 "CSV and TSV SPARQL results format parsing is not implemented",
             )),
@@ -212,6 +210,26 @@ impl QueryResultsFormat {
             None
         }
     }
+
+    /// Looks for a known format from a file extension.
+    ///
+    /// It supports some file extension aliases like `json` for the JSON format.
+    ///
+    /// Example:
+    /// ```
+    /// use oxigraph::sparql::QueryResultsFormat;
+    ///
+    /// assert_eq!(QueryResultsFormat::from_extension("srj"), Some(QueryResultsFormat::Json))
+    /// ```
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "srx" | "xml" => Some(QueryResultsFormat::Xml),
+            "srj" | "json" => Some(QueryResultsFormat::Json),
+            "csv" => Some(QueryResultsFormat::Csv),
+            "tsv" => Some(QueryResultsFormat::Tsv),
+            _ => None,
+        }
+    }
 }
 
 /// An iterator over [`QuerySolution`]s
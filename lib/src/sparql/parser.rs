@@ -451,29 +451,48 @@ fn build_select(
     m
 }
 
-fn copy_graph(from: Option<NamedNode>, to: Option<NamedNodeOrVariable>) -> GraphUpdateOperation {
-    let bgp = GraphPattern::BGP(vec![TriplePattern::new(
-        Variable::new_unchecked("s"),
-        Variable::new_unchecked("p"),
-        Variable::new_unchecked("o"),
-    )]);
-    GraphUpdateOperation::DeleteInsert {
-        delete: Vec::new(),
-        insert: vec![QuadPattern::new(
-            Variable::new_unchecked("s"),
-            Variable::new_unchecked("p"),
-            Variable::new_unchecked("o"),
-            to,
-        )],
-        using: QueryDataset::default(),
-        pattern: Box::new(if let Some(from) = from {
-            GraphPattern::Graph {
-                graph_name: from.into(),
-                inner: Box::new(bgp),
+/// Converts a pattern without variables to a term
+fn pattern_to_term(pattern: TermOrVariable) -> Option<Term> {
+    match pattern {
+        TermOrVariable::Term(term) => Some(term),
+        TermOrVariable::Variable(_) => None,
+        TermOrVariable::Triple(triple) => Some(
+            Triple {
+                subject: match pattern_to_term(triple.subject)? {
+                    Term::NamedNode(node) => node.into(),
+                    Term::BlankNode(node) => node.into(),
+                    Term::Triple(triple) => triple.into(),
+                    Term::Literal(_) => return None,
+                },
+                predicate: if let NamedNodeOrVariable::NamedNode(node) = triple.predicate {
+                    node
+                } else {
+                    return None;
+                },
+                object: pattern_to_term(triple.object)?,
             }
-        } else {
-            bgp
-        }),
+            .into(),
+        ),
+    }
+}
+
+fn has_blank_node(term: &Term) -> bool {
+    match term {
+        Term::BlankNode(_) => true,
+        Term::Triple(triple) => {
+            has_blank_node(&triple.subject.clone().into()) || has_blank_node(&triple.object)
+        }
+        Term::NamedNode(_) | Term::Literal(_) => false,
+    }
+}
+
+fn has_blank_node_pattern(pattern: &TermOrVariable) -> bool {
+    match pattern {
+        TermOrVariable::Term(term) => has_blank_node(term),
+        TermOrVariable::Variable(_) => false,
+        TermOrVariable::Triple(triple) => {
+            has_blank_node_pattern(&triple.subject) || has_blank_node_pattern(&triple.object)
+        }
     }
 }
 
@@ -970,35 +989,17 @@ parser! {
 
         //[35]
         rule Add() -> Vec<GraphUpdateOperation> = i("ADD") _ silent:Update1_silent() _ from:GraphOrDefault() _ i("TO") _ to:GraphOrDefault() {
-            // Rewriting defined by https://www.w3.org/TR/sparql11-update/#add
-            if from == to {
-                Vec::new() // identity case
-            } else {
-                let bgp = GraphPattern::BGP(vec![TriplePattern::new(Variable::new_unchecked("s"), Variable::new_unchecked("p"), Variable::new_unchecked("o"))]);
-                vec![copy_graph(from, to.map(NamedNodeOrVariable::NamedNode))]
-            }
+            vec![GraphUpdateOperation::Add { silent, from, to }]
         }
 
         //[36]
         rule Move() -> Vec<GraphUpdateOperation> = i("MOVE") _ silent:Update1_silent() _ from:GraphOrDefault() _ i("TO") _ to:GraphOrDefault() {
-            // Rewriting defined by https://www.w3.org/TR/sparql11-update/#move
-            if from == to {
-                Vec::new() // identity case
-            } else {
-                let bgp = GraphPattern::BGP(vec![TriplePattern::new(Variable::new_unchecked("s"), Variable::new_unchecked("p"), Variable::new_unchecked("o"))]);
-                vec![GraphUpdateOperation::Drop { silent: true, graph: to.clone().map_or(GraphTarget::DefaultGraph, GraphTarget::NamedNode) }, copy_graph(from.clone(), to.map(NamedNodeOrVariable::NamedNode)), GraphUpdateOperation::Drop { silent, graph: from.map_or(GraphTarget::DefaultGraph, GraphTarget::NamedNode) }]
-            }
+            vec![GraphUpdateOperation::Move { silent, from, to }]
         }
 
         //[37]
         rule Copy() -> Vec<GraphUpdateOperation> = i("COPY") _ silent:Update1_silent() _ from:GraphOrDefault() _ i("TO") _ to:GraphOrDefault() {
-            // Rewriting defined by https://www.w3.org/TR/sparql11-update/#copy
-            if from == to {
-                Vec::new() // identity case
-            } else {
-                let bgp = GraphPattern::BGP(vec![TriplePattern::new(Variable::new_unchecked("s"), Variable::new_unchecked("p"), Variable::new_unchecked("o"))]);
-                vec![GraphUpdateOperation::Drop { silent: true, graph: to.clone().map_or(GraphTarget::DefaultGraph, GraphTarget::NamedNode) }, copy_graph(from, to.map(NamedNodeOrVariable::NamedNode))]
-            }
+            vec![GraphUpdateOperation::Copy { silent, from, to }]
         }
 
         //[38]
@@ -1008,7 +1009,7 @@ parser! {
 
         //[39]
         rule DeleteData() -> Vec<GraphUpdateOperation> = i("DELETE") _ i("DATA") _ data:QuadData() {?
-            if data.iter().any(|quad| quad.subject.is_blank_node() || quad.object.is_blank_node() || quad.graph_name.is_blank_node()) {
+            if data.iter().any(|quad| has_blank_node(&quad.subject.clone().into()) || has_blank_node(&quad.object) || quad.graph_name.is_blank_node()) {
                 Err("Blank nodes are not allowed in DELETE DATA")
             } else {
                 Ok(vec![GraphUpdateOperation::DeleteData { data }])
@@ -1017,7 +1018,7 @@ parser! {
 
         //[40]
         rule DeleteWhere() -> Vec<GraphUpdateOperation> = i("DELETE") _ i("WHERE") _ d:QuadPattern() {?
-            if d.iter().any(|quad| matches!(quad.subject, TermOrVariable::Term(Term::BlankNode(_))) || matches!(quad.object, TermOrVariable::Term(Term::BlankNode(_)))) {
+            if d.iter().any(|quad| has_blank_node_pattern(&quad.subject) || has_blank_node_pattern(&quad.object)) {
                 Err("Blank nodes are not allowed in DELETE WHERE")
             } else {
                 let pattern = d.iter().map(|q| {
@@ -1097,7 +1098,7 @@ parser! {
 
         //[42]
         rule DeleteClause() -> Vec<QuadPattern> = i("DELETE") _ q:QuadPattern() {?
-            if q.iter().any(|quad| matches!(quad.subject, TermOrVariable::Term(Term::BlankNode(_))) || matches!(quad.object, TermOrVariable::Term(Term::BlankNode(_)))) {
+            if q.iter().any(|quad| has_blank_node_pattern(&quad.subject) || has_blank_node_pattern(&quad.object)) {
                 Err("Blank nodes are not allowed in DELETE")
             } else {
                 Ok(q)
@@ -1138,9 +1139,10 @@ parser! {
         //[49]
         rule QuadData() -> Vec<Quad> = "{" _ q:Quads() _ "}" {?
             q.into_iter().map(|q| Ok(Quad {
-                subject: match q.subject {
-                    TermOrVariable::Term(Term::NamedNode(t)) => t.into(),
-                    TermOrVariable::Term(Term::BlankNode(t)) => t.into(),
+                subject: match pattern_to_term(q.subject) {
+                    Some(Term::NamedNode(t)) => t.into(),
+                    Some(Term::BlankNode(t)) => t.into(),
+                    Some(Term::Triple(t)) => t.into(),
                     _ => return Err(())
                 },
                 predicate: if let NamedNodeOrVariable::NamedNode(t) = q.predicate {
@@ -1148,7 +1150,7 @@ parser! {
                 } else {
                     return Err(())
                 },
-                object: if let TermOrVariable::Term(t) = q.object {
+                object: if let Some(t) = pattern_to_term(q.object) {
                     t
                 } else {
                     return Err(())
@@ -1301,6 +1303,7 @@ parser! {
 
         //[65]
         rule DataBlockValue() -> Option<Term> =
+            t:QuotedTripleData() { Some(t.into()) } /
             i:iri() { Some(i.into()) } /
             l:RDFLiteral() { Some(l.into()) } /
             l:NumericLiteral() { Some(l.into()) } /
@@ -1643,8 +1646,33 @@ parser! {
         //[106]
         rule VarOrTerm() -> TermOrVariable =
             v:Var() { v.into() } /
+            t:QuotedTriplePattern() { t } /
             t:GraphTerm() { t.into() }
 
+        // SPARQL-star quoted triple pattern: '<<' VarOrTerm Verb VarOrTerm '>>'
+        rule QuotedTriplePattern() -> TermOrVariable = "<<" _ s:VarOrTerm() _ p:Verb() _ o:VarOrTerm() _ ">>" {?
+            if matches!(s, TermOrVariable::Term(Term::Literal(_))) {
+                Err("Literals are not allowed in subject position")
+            } else {
+                Ok(TriplePattern::new(s, p, o).into())
+            }
+        }
+
+        // SPARQL-star quoted triple in VALUES: '<<' DataValueTerm (iri | 'a') DataValueTerm '>>'
+        rule QuotedTripleData() -> Triple = "<<" _ s:QuotedTripleData_subject() _ p:QuotedTripleData_predicate() _ o:QuotedTripleData_object() _ ">>" {
+            Triple::new(s, p, o)
+        }
+        rule QuotedTripleData_subject() -> Subject =
+            t:QuotedTripleData() { t.into() } /
+            i:iri() { i.into() }
+        rule QuotedTripleData_predicate() -> NamedNode = iri() / "a" { rdf::TYPE.into() }
+        rule QuotedTripleData_object() -> Term =
+            t:QuotedTripleData() { t.into() } /
+            i:iri() { i.into() } /
+            l:RDFLiteral() { l.into() } /
+            l:NumericLiteral() { l.into() } /
+            l:BooleanLiteral() { l.into() }
+
         //[107]
         rule VarOrIri() -> NamedNodeOrVariable =
             v:Var() { v.into() } /
@@ -1735,6 +1763,7 @@ parser! {
         //[119]
         rule PrimaryExpression() -> Expression =
             BrackettedExpression() /
+            ExprQuotedTriple() /
             iriOrFunction() /
             v:Var() { v.into() } /
             l:RDFLiteral() { l.into() } /
@@ -1742,6 +1771,21 @@ parser! {
             l:BooleanLiteral() { l.into() } /
             BuiltInCall()
 
+        // SPARQL-star quoted triple expression: '<<' ExprVarOrTerm Verb ExprVarOrTerm '>>'
+        rule ExprQuotedTriple() -> Expression = "<<" _ s:ExprVarOrTerm() _ p:Verb() _ o:ExprVarOrTerm() _ ">>" {
+            Expression::FunctionCall(Function::Triple, vec![s, match p {
+                NamedNodeOrVariable::NamedNode(p) => p.into(),
+                NamedNodeOrVariable::Variable(p) => p.into(),
+            }, o])
+        }
+        rule ExprVarOrTerm() -> Expression =
+            ExprQuotedTriple() /
+            i:iri() { i.into() } /
+            l:RDFLiteral() { l.into() } /
+            l:NumericLiteral() { l.into() } /
+            l:BooleanLiteral() { l.into() } /
+            v:Var() { v.into() }
+
         //[120]
         rule BrackettedExpression() -> Expression = "(" _ e:Expression() _ ")" { e }
 
@@ -1798,6 +1842,11 @@ parser! {
             i("isBLANK") "(" _ e:Expression() _ ")" { Expression::FunctionCall(Function::IsBlank, vec![e]) } /
             i("isLITERAL") "(" _ e:Expression() _ ")" { Expression::FunctionCall(Function::IsLiteral, vec![e]) } /
             i("isNUMERIC") "(" _ e:Expression() _ ")" { Expression::FunctionCall(Function::IsNumeric, vec![e]) } /
+            i("TRIPLE") _ "(" _ s:Expression() _ "," _ p:Expression() _ "," _ o:Expression() _ ")" { Expression::FunctionCall(Function::Triple, vec![s, p, o]) } /
+            i("SUBJECT") _ "(" _ e:Expression() _ ")" { Expression::FunctionCall(Function::Subject, vec![e]) } /
+            i("PREDICATE") _ "(" _ e:Expression() _ ")" { Expression::FunctionCall(Function::Predicate, vec![e]) } /
+            i("OBJECT") _ "(" _ e:Expression() _ ")" { Expression::FunctionCall(Function::Object, vec![e]) } /
+            i("isTRIPLE") _ "(" _ e:Expression() _ ")" { Expression::FunctionCall(Function::IsTriple, vec![e]) } /
             RegexExpression() /
             ExistsFunc() /
             NotExistsFunc()
@@ -1848,12 +1897,14 @@ parser! {
             name:iri() _ "(" _ e:Expression() _ ")" { AggregationFunction::Custom { name, expr: Box::new(e), distinct: false } }
 
         //[128]
-        rule iriOrFunction() -> Expression = i: iri() _ a: ArgList()? {
-            match a {
-                Some(a) => Expression::FunctionCall(Function::Custom(i), a),
-                None => i.into()
+        rule iriOrFunction() -> Expression =
+            name:iri() _ "(" _ i("DISTINCT") _ e:Expression() _ ")" {? state.new_aggregation(AggregationFunction::Custom { name, expr: Box::new(e), distinct: true }).map(|v| v.into()) } /
+            i: iri() _ a: ArgList()? {
+                match a {
+                    Some(a) => Expression::FunctionCall(Function::Custom(i), a),
+                    None => i.into()
+                }
             }
-        }
 
         //[129]
         rule RDFLiteral() -> Literal =
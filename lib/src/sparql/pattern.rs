@@ -0,0 +1,121 @@
+//! Evaluation of graph patterns with some variables bound beforehand.
+//!
+//! It allows to reuse the SPARQL evaluator outside of the query evaluation, for example during [SHACL](https://www.w3.org/TR/shacl/) validation.
+
+use crate::model::{Literal, Term};
+use crate::sparql::algebra::{GraphPattern, QueryDataset};
+use crate::sparql::dataset::{DatasetStrId, DatasetView};
+use crate::sparql::error::EvaluationError;
+pub(crate) use crate::sparql::eval::compile_pattern;
+use crate::sparql::eval::SimpleEvaluator;
+use crate::sparql::model::{QueryResults, QuerySolutionIter, Variable};
+use crate::sparql::plan::{CustomAggregates, CustomFunctions, EncodedTuple, PlanNode};
+use crate::sparql::plan_builder::PlanBuilder;
+use crate::sparql::QueryOptions;
+use crate::store::numeric_encoder::WriteEncoder;
+use crate::store::ReadableEncodedStore;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+/// Evaluates graph patterns against a given dataset of a store
+pub(crate) struct PatternEvaluator<S: ReadableEncodedStore + 'static> {
+    dataset: Rc<DatasetView<S>>,
+    evaluator: SimpleEvaluator<DatasetView<S>>,
+    custom_functions: Rc<CustomFunctions>,
+    custom_aggregates: Rc<CustomAggregates>,
+}
+
+/// A graph pattern compiled by a [`PatternEvaluator`]
+pub(crate) struct PreparedPattern<S: ReadableEncodedStore + 'static> {
+    plan: PlanNode<DatasetStrId<S::StrId>>,
+    variables: Rc<Vec<Variable>>,
+}
+
+impl<S: ReadableEncodedStore + 'static> PatternEvaluator<S> {
+    pub fn new(
+        store: S,
+        dataset: &QueryDataset,
+        options: QueryOptions,
+    ) -> Result<Self, EvaluationError> {
+        let interrupter = options.interrupter();
+        let memory_limit = options.memory_limit();
+        let dataset = Rc::new(DatasetView::new(store, dataset)?);
+        Ok(Self {
+            evaluator: SimpleEvaluator::new(
+                dataset.clone(),
+                None,
+                options.service_handler,
+                options.custom_functions.clone(),
+                options.custom_aggregates.clone(),
+                interrupter,
+                memory_limit,
+            )
+            .with_pre_binding(),
+            dataset,
+            custom_functions: options.custom_functions,
+            custom_aggregates: options.custom_aggregates,
+        })
+    }
+
+    pub fn prepare(&self, pattern: &GraphPattern) -> Result<PreparedPattern<S>, EvaluationError> {
+        let (plan, variables) = PlanBuilder::build(
+            self.dataset.as_ref(),
+            pattern,
+            &self.custom_functions,
+            &self.custom_aggregates,
+        )?;
+        Ok(PreparedPattern {
+            plan,
+            variables: Rc::new(variables),
+        })
+    }
+
+    /// Evaluates a prepared pattern with the given variables already bound.
+    ///
+    /// The bindings are pushed down into the pattern like the [SHACL pre-binding](https://www.w3.org/TR/shacl/#pre-binding) does.
+    pub fn evaluate(
+        &self,
+        pattern: &PreparedPattern<S>,
+        bindings: &[(&Variable, &Term)],
+    ) -> Result<QuerySolutionIter, EvaluationError> {
+        let mut encoder = self.dataset.as_ref();
+        let mut from = EncodedTuple::with_capacity(pattern.variables.len());
+        for (variable, value) in bindings {
+            if let Some(position) = pattern.variables.iter().position(|v| v == *variable) {
+                from.set(position, encoder.encode_term(value.as_ref())?);
+            }
+        }
+        match self.evaluator.evaluate_select_plan_from(
+            &pattern.plan,
+            pattern.variables.clone(),
+            from,
+        )? {
+            QueryResults::Solutions(solutions) => Ok(solutions),
+            _ => Err(EvaluationError::msg(
+                "The evaluation of a graph pattern should return solutions",
+            )),
+        }
+    }
+
+    /// Compares two literals like the SPARQL `<` operator does
+    pub fn compare_literals(
+        &self,
+        a: &Literal,
+        b: &Literal,
+    ) -> Result<Option<Ordering>, EvaluationError> {
+        let mut encoder = self.dataset.as_ref();
+        let a = encoder.encode_literal(a.as_ref())?;
+        let b = encoder.encode_literal(b.as_ref())?;
+        Ok(self.evaluator.partial_cmp_literals(a, b))
+    }
+
+    /// Checks if the literal has been encoded without being parsed according to its datatype.
+    ///
+    /// It is the case of the literals with an unsupported datatype or with an ill-formed lexical form.
+    pub fn is_unknown_typed_literal(&self, literal: &Literal) -> Result<bool, EvaluationError> {
+        let mut encoder = self.dataset.as_ref();
+        Ok(encoder
+            .encode_literal(literal.as_ref())?
+            .is_unknown_typed_literal())
+    }
+}
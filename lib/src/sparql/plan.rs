@@ -1,9 +1,19 @@
+use crate::model::{NamedNode, Term};
 use crate::sparql::algebra::GraphPattern;
 use crate::sparql::model::Variable;
+use crate::sparql::AggregateFunctionAccumulator;
 use crate::store::numeric_encoder::{EncodedTerm, StrId};
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
+use std::iter::FromIterator;
 use std::rc::Rc;
 
+/// The custom functions registered with [`QueryOptions::with_custom_function`](super::QueryOptions::with_custom_function)
+pub(crate) type CustomFunctions = HashMap<NamedNode, Rc<dyn Fn(&[Term]) -> Option<Term>>>;
+
+/// The custom aggregate functions registered with [`QueryOptions::with_custom_aggregate`](super::QueryOptions::with_custom_aggregate)
+pub(crate) type CustomAggregates =
+    HashMap<NamedNode, Rc<dyn Fn() -> Box<dyn AggregateFunctionAccumulator>>>;
+
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
 pub enum PlanNode<I: StrId> {
     Init,
@@ -24,6 +34,13 @@ pub enum PlanNode<I: StrId> {
         object: PatternValue<I>,
         graph_name: PatternValue<I>,
     },
+    QuotedTripleMatch {
+        child: Rc<PlanNode<I>>,
+        triple: PatternValue<I>,
+        subject: PatternValue<I>,
+        predicate: PatternValue<I>,
+        object: PatternValue<I>,
+    },
     PathPatternJoin {
         child: Rc<PlanNode<I>>,
         subject: PatternValue<I>,
@@ -31,10 +48,31 @@ pub enum PlanNode<I: StrId> {
         object: PatternValue<I>,
         graph_name: PatternValue<I>,
     },
+    TextSearch {
+        child: Rc<PlanNode<I>>,
+        subject: PatternValue<I>,
+        property: Option<EncodedTerm<I>>, // only the literals used with this property are matched
+        literal: Option<usize>,
+        score: Option<usize>,
+        query: PatternValue<I>,
+        limit: Option<usize>, // maximal number of matching literals
+        graph_name: PatternValue<I>,
+    },
     Join {
         left: Rc<PlanNode<I>>,
         right: Rc<PlanNode<I>>,
     },
+    ForLoopJoin {
+        // The right plan is evaluated for each left tuple with the left tuple bindings
+        left: Rc<PlanNode<I>>,
+        right: Rc<PlanNode<I>>,
+    },
+    HashJoin {
+        // The right plan is evaluated once and put in a hash table
+        left: Rc<PlanNode<I>>,
+        right: Rc<PlanNode<I>>,
+        keys: Rc<Vec<usize>>, // variables shared by the two sides used as the hash table key
+    },
     AntiJoin {
         left: Rc<PlanNode<I>>,
         right: Rc<PlanNode<I>>,
@@ -51,6 +89,13 @@ pub enum PlanNode<I: StrId> {
         right: Rc<PlanNode<I>>,
         possible_problem_vars: Rc<Vec<usize>>, //Variables that should not be part of the entry of the left join
     },
+    HashLeftJoin {
+        // The right plan is evaluated once and put in a hash table
+        left: Rc<PlanNode<I>>,
+        right: Rc<PlanNode<I>>,
+        keys: Rc<Vec<usize>>, // variables shared by the two sides used as the hash table key
+        expression: Option<Rc<PlanExpression<I>>>, // the left join filter evaluated on the joined tuples
+    },
     Extend {
         child: Rc<PlanNode<I>>,
         position: usize,
@@ -74,12 +119,14 @@ pub enum PlanNode<I: StrId> {
     Project {
         child: Rc<PlanNode<I>>,
         mapping: Rc<Vec<(usize, usize)>>, // pairs of (variable key in child, variable key in output)
+        child_variables: Rc<Vec<Variable>>, // names of the child variables, only used to explain the plan
     },
     Aggregate {
         // By definition the group by key are the range 0..key_mapping.len()
         child: Rc<PlanNode<I>>,
         key_mapping: Rc<Vec<(usize, usize)>>, // aggregate key pairs of (variable key in child, variable key in output)
         aggregates: Rc<Vec<(PlanAggregation<I>, usize)>>,
+        child_variables: Rc<Vec<Variable>>, // names of the child variables, only used to explain the plan
     },
 }
 
@@ -124,6 +171,27 @@ impl<I: StrId> PlanNode<I> {
                 }
                 child.add_maybe_bound_variables(set);
             }
+            PlanNode::QuotedTripleMatch {
+                child,
+                triple,
+                subject,
+                predicate,
+                object,
+            } => {
+                if let PatternValue::Variable(var) = triple {
+                    set.insert(*var);
+                }
+                if let PatternValue::Variable(var) = subject {
+                    set.insert(*var);
+                }
+                if let PatternValue::Variable(var) = predicate {
+                    set.insert(*var);
+                }
+                if let PatternValue::Variable(var) = object {
+                    set.insert(*var);
+                }
+                child.add_maybe_bound_variables(set);
+            }
             PlanNode::PathPatternJoin {
                 child,
                 subject,
@@ -142,6 +210,32 @@ impl<I: StrId> PlanNode<I> {
                 }
                 child.add_maybe_bound_variables(set);
             }
+            PlanNode::TextSearch {
+                child,
+                subject,
+                literal,
+                score,
+                query,
+                graph_name,
+                ..
+            } => {
+                if let PatternValue::Variable(var) = subject {
+                    set.insert(*var);
+                }
+                if let Some(var) = literal {
+                    set.insert(*var);
+                }
+                if let Some(var) = score {
+                    set.insert(*var);
+                }
+                if let PatternValue::Variable(var) = query {
+                    set.insert(*var);
+                }
+                if let PatternValue::Variable(var) = graph_name {
+                    set.insert(*var);
+                }
+                child.add_maybe_bound_variables(set);
+            }
             PlanNode::Filter { child, expression } => {
                 expression.add_maybe_bound_variables(set);
                 child.add_maybe_bound_variables(set);
@@ -152,11 +246,25 @@ impl<I: StrId> PlanNode<I> {
                 }
             }
             PlanNode::Join { left, right, .. }
+            | PlanNode::ForLoopJoin { left, right, .. }
+            | PlanNode::HashJoin { left, right, .. }
             | PlanNode::AntiJoin { left, right, .. }
             | PlanNode::LeftJoin { left, right, .. } => {
                 left.add_maybe_bound_variables(set);
                 right.add_maybe_bound_variables(set);
             }
+            PlanNode::HashLeftJoin {
+                left,
+                right,
+                expression,
+                ..
+            } => {
+                left.add_maybe_bound_variables(set);
+                right.add_maybe_bound_variables(set);
+                if let Some(expression) = expression {
+                    expression.add_maybe_bound_variables(set);
+                }
+            }
             PlanNode::Extend {
                 child,
                 position,
@@ -171,7 +279,7 @@ impl<I: StrId> PlanNode<I> {
             | PlanNode::HashDeduplicate { child }
             | PlanNode::Skip { child, .. }
             | PlanNode::Limit { child, .. } => child.add_maybe_bound_variables(set),
-            PlanNode::Project { mapping, child } => {
+            PlanNode::Project { mapping, child, .. } => {
                 let child_bound = child.maybe_bound_variables();
                 for (child_i, output_i) in mapping.iter() {
                     if child_bound.contains(child_i) {
@@ -290,6 +398,15 @@ pub enum PlanExpression<I: StrId> {
     IsBlank(Box<PlanExpression<I>>),
     IsLiteral(Box<PlanExpression<I>>),
     IsNumeric(Box<PlanExpression<I>>),
+    Triple(
+        Box<PlanExpression<I>>,
+        Box<PlanExpression<I>>,
+        Box<PlanExpression<I>>,
+    ),
+    Subject(Box<PlanExpression<I>>),
+    Predicate(Box<PlanExpression<I>>),
+    Object(Box<PlanExpression<I>>),
+    IsTriple(Box<PlanExpression<I>>),
     Regex(
         Box<PlanExpression<I>>,
         Box<PlanExpression<I>>,
@@ -307,6 +424,17 @@ pub enum PlanExpression<I: StrId> {
     YearMonthDurationCast(Box<PlanExpression<I>>),
     DayTimeDurationCast(Box<PlanExpression<I>>),
     StringCast(Box<PlanExpression<I>>),
+    GeoDistance(
+        Box<PlanExpression<I>>,
+        Box<PlanExpression<I>>,
+        Box<PlanExpression<I>>,
+    ),
+    GeoEquals(Box<PlanExpression<I>>, Box<PlanExpression<I>>),
+    GeoDisjoint(Box<PlanExpression<I>>, Box<PlanExpression<I>>),
+    GeoIntersects(Box<PlanExpression<I>>, Box<PlanExpression<I>>),
+    GeoWithin(Box<PlanExpression<I>>, Box<PlanExpression<I>>),
+    GeoContains(Box<PlanExpression<I>>, Box<PlanExpression<I>>),
+    CustomFunction(NamedNode, Vec<PlanExpression<I>>),
 }
 
 impl<I: StrId> PlanExpression<I> {
@@ -354,6 +482,10 @@ impl<I: StrId> PlanExpression<I> {
             | PlanExpression::IsBlank(e)
             | PlanExpression::IsLiteral(e)
             | PlanExpression::IsNumeric(e)
+            | PlanExpression::Subject(e)
+            | PlanExpression::Predicate(e)
+            | PlanExpression::Object(e)
+            | PlanExpression::IsTriple(e)
             | PlanExpression::BooleanCast(e)
             | PlanExpression::DoubleCast(e)
             | PlanExpression::FloatCast(e)
@@ -386,12 +518,19 @@ impl<I: StrId> PlanExpression<I> {
             | PlanExpression::StrLang(a, b)
             | PlanExpression::StrDT(a, b)
             | PlanExpression::SameTerm(a, b)
+            | PlanExpression::GeoEquals(a, b)
+            | PlanExpression::GeoDisjoint(a, b)
+            | PlanExpression::GeoIntersects(a, b)
+            | PlanExpression::GeoWithin(a, b)
+            | PlanExpression::GeoContains(a, b)
             | PlanExpression::SubStr(a, b, None)
             | PlanExpression::Regex(a, b, None) => {
                 a.add_maybe_bound_variables(set);
                 b.add_maybe_bound_variables(set);
             }
             PlanExpression::If(a, b, c)
+            | PlanExpression::Triple(a, b, c)
+            | PlanExpression::GeoDistance(a, b, c)
             | PlanExpression::SubStr(a, b, Some(c))
             | PlanExpression::Regex(a, b, Some(c))
             | PlanExpression::Replace(a, b, c, None) => {
@@ -406,7 +545,9 @@ impl<I: StrId> PlanExpression<I> {
                 d.add_maybe_bound_variables(set);
             }
 
-            PlanExpression::Concat(es) | PlanExpression::Coalesce(es) => {
+            PlanExpression::Concat(es)
+            | PlanExpression::Coalesce(es)
+            | PlanExpression::CustomFunction(_, es) => {
                 for e in es {
                     e.add_maybe_bound_variables(set);
                 }
@@ -440,6 +581,7 @@ pub enum PlanAggregationFunction {
     Avg,
     Sample,
     GroupConcat { separator: Rc<String> },
+    Custom(NamedNode),
 }
 
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
@@ -460,18 +602,19 @@ pub enum Comparator<I: StrId> {
     Desc(PlanExpression<I>),
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
 pub struct TripleTemplate<I: StrId> {
     pub subject: TripleTemplateValue<I>,
     pub predicate: TripleTemplateValue<I>,
     pub object: TripleTemplateValue<I>,
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Copy, Hash)]
+#[derive(Eq, PartialEq, Debug, Clone, Hash)]
 pub enum TripleTemplateValue<I: StrId> {
     Constant(EncodedTerm<I>),
     BlankNode(usize),
     Variable(usize),
+    Triple(Box<TripleTemplate<I>>),
 }
 
 #[derive(Eq, PartialEq, Debug, Clone, Hash)]
@@ -550,6 +693,14 @@ impl<I: StrId> EncodedTuple<I> {
     }
 }
 
+impl<I: StrId> FromIterator<Option<EncodedTerm<I>>> for EncodedTuple<I> {
+    fn from_iter<T: IntoIterator<Item = Option<EncodedTerm<I>>>>(iter: T) -> Self {
+        Self {
+            inner: iter.into_iter().collect(),
+        }
+    }
+}
+
 impl<I: StrId> IntoIterator for EncodedTuple<I> {
     type Item = Option<EncodedTerm<I>>;
     type IntoIter = std::vec::IntoIter<Option<EncodedTerm<I>>>;
@@ -1,23 +1,40 @@
+use crate::model::vocab::rdf;
 use crate::model::{BlankNode, Literal, NamedNode, Term};
 use crate::sparql::algebra::*;
 use crate::sparql::error::EvaluationError;
 use crate::sparql::model::*;
 use crate::sparql::plan::*;
-use crate::store::numeric_encoder::{EncodedTerm, WriteEncoder};
-use std::collections::{BTreeSet, HashSet};
+use crate::store::numeric_encoder::{EncodedTerm, StrId, WriteEncoder};
+use crate::store::{PredicateStatistics, ReadableEncodedStore};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::mem;
 use std::rc::Rc;
 
-pub(crate) struct PlanBuilder<E: WriteEncoder> {
+/// The full-text search property function
+const TEXT_QUERY: &str = "http://jena.apache.org/text#query";
+
+pub(crate) struct PlanBuilder<'a, E: WriteEncoder> {
     encoder: E,
+    custom_functions: &'a CustomFunctions,
+    custom_aggregates: &'a CustomAggregates,
 }
 
-impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
+impl<'a, E: WriteEncoder<Error = EvaluationError> + ReadableEncodedStore> PlanBuilder<'a, E> {
     pub fn build(
         encoder: E,
         pattern: &GraphPattern,
+        custom_functions: &'a CustomFunctions,
+        custom_aggregates: &'a CustomAggregates,
     ) -> Result<(PlanNode<E::StrId>, Vec<Variable>), EvaluationError> {
         let mut variables = Vec::default();
-        let plan = PlanBuilder { encoder }.build_for_graph_pattern(
+        let plan = PlanBuilder {
+            encoder,
+            custom_functions,
+            custom_aggregates,
+        }
+        .build_for_graph_pattern(
             pattern,
             &mut variables,
             PatternValue::Constant(EncodedTerm::DefaultGraph),
@@ -30,7 +47,12 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
         template: &[TriplePattern],
         mut variables: Vec<Variable>,
     ) -> Result<Vec<TripleTemplate<E::StrId>>, EvaluationError> {
-        PlanBuilder { encoder }.build_for_graph_template(template, &mut variables)
+        PlanBuilder {
+            encoder,
+            custom_functions: &CustomFunctions::new(),
+            custom_aggregates: &CustomAggregates::new(),
+        }
+        .build_for_graph_template(template, &mut variables)
     }
 
     fn build_for_graph_pattern(
@@ -45,13 +67,25 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                 subject,
                 path,
                 object,
-            } => PlanNode::PathPatternJoin {
-                child: Rc::new(PlanNode::Init),
-                subject: self.pattern_value_from_term_or_variable(subject, variables)?,
-                path: Rc::new(self.build_for_path(path)?),
-                object: self.pattern_value_from_term_or_variable(object, variables)?,
-                graph_name,
-            },
+            } => {
+                let mut quoted_triples = Vec::new();
+                let plan = PlanNode::PathPatternJoin {
+                    child: Rc::new(PlanNode::Init),
+                    subject: self.pattern_value_from_term_or_variable(
+                        subject,
+                        variables,
+                        &mut quoted_triples,
+                    )?,
+                    path: Rc::new(self.build_for_path(path)?),
+                    object: self.pattern_value_from_term_or_variable(
+                        object,
+                        variables,
+                        &mut quoted_triples,
+                    )?,
+                    graph_name,
+                };
+                self.build_for_quoted_triples(plan, quoted_triples, variables)?
+            }
             GraphPattern::Join { left, right } => {
                 //TODO: improve
                 if let GraphPattern::Path {
@@ -61,17 +95,44 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                 } = right.as_ref()
                 {
                     let left = self.build_for_graph_pattern(left, variables, graph_name)?;
-                    PlanNode::PathPatternJoin {
+                    let mut quoted_triples = Vec::new();
+                    let plan = PlanNode::PathPatternJoin {
                         child: Rc::new(left),
-                        subject: self.pattern_value_from_term_or_variable(subject, variables)?,
+                        subject: self.pattern_value_from_term_or_variable(
+                            subject,
+                            variables,
+                            &mut quoted_triples,
+                        )?,
                         path: Rc::new(self.build_for_path(path)?),
-                        object: self.pattern_value_from_term_or_variable(object, variables)?,
+                        object: self.pattern_value_from_term_or_variable(
+                            object,
+                            variables,
+                            &mut quoted_triples,
+                        )?,
                         graph_name,
-                    }
+                    };
+                    self.build_for_quoted_triples(plan, quoted_triples, variables)?
                 } else {
-                    PlanNode::Join {
-                        left: Rc::new(self.build_for_graph_pattern(left, variables, graph_name)?),
-                        right: Rc::new(self.build_for_graph_pattern(right, variables, graph_name)?),
+                    let left = self.build_for_graph_pattern(left, variables, graph_name)?;
+                    let right = self.build_for_graph_pattern(right, variables, graph_name)?;
+                    let keys = join_keys(&left, &right);
+                    if keys.is_empty() {
+                        PlanNode::Join {
+                            left: Rc::new(left),
+                            right: Rc::new(right),
+                        }
+                    } else if is_index_lookup(&right) {
+                        // The right side is cheap to evaluate with the left bindings
+                        PlanNode::ForLoopJoin {
+                            left: Rc::new(left),
+                            right: Rc::new(right),
+                        }
+                    } else {
+                        PlanNode::HashJoin {
+                            left: Rc::new(left),
+                            right: Rc::new(right),
+                            keys: Rc::new(keys),
+                        }
                     }
                 }
             }
@@ -79,25 +140,43 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                 let left = self.build_for_graph_pattern(left, variables, graph_name)?;
                 let right = self.build_for_graph_pattern(right, variables, graph_name)?;
 
-                let mut possible_problem_vars = BTreeSet::new();
-                self.add_left_join_problematic_variables(&right, &mut possible_problem_vars);
-
-                //We add the extra filter if needed
-                let right = if let Some(expr) = expr {
-                    PlanNode::Filter {
-                        child: Rc::new(right),
-                        expression: Rc::new(
+                let keys = join_keys(&left, &right);
+                if !keys.is_empty() && !is_index_lookup(&right) {
+                    // The right side is evaluated only once and the filter is applied on the joined tuples
+                    let expression = if let Some(expr) = expr {
+                        Some(Rc::new(
                             self.build_for_expression(expr, variables, graph_name)?,
-                        ),
+                        ))
+                    } else {
+                        None
+                    };
+                    PlanNode::HashLeftJoin {
+                        left: Rc::new(left),
+                        right: Rc::new(right),
+                        keys: Rc::new(keys),
+                        expression,
                     }
                 } else {
-                    right
-                };
+                    let mut possible_problem_vars = BTreeSet::new();
+                    self.add_left_join_problematic_variables(&right, &mut possible_problem_vars);
+
+                    //We add the extra filter if needed
+                    let right = if let Some(expr) = expr {
+                        PlanNode::Filter {
+                            child: Rc::new(right),
+                            expression: Rc::new(
+                                self.build_for_expression(expr, variables, graph_name)?,
+                            ),
+                        }
+                    } else {
+                        right
+                    };
 
-                PlanNode::LeftJoin {
-                    left: Rc::new(left),
-                    right: Rc::new(right),
-                    possible_problem_vars: Rc::new(possible_problem_vars.into_iter().collect()),
+                    PlanNode::LeftJoin {
+                        left: Rc::new(left),
+                        right: Rc::new(right),
+                        possible_problem_vars: Rc::new(possible_problem_vars.into_iter().collect()),
+                    }
                 }
             }
             GraphPattern::Filter { expr, inner } => PlanNode::Filter {
@@ -189,6 +268,7 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                             })
                             .collect::<Result<Vec<_>, EvaluationError>>()?,
                     ),
+                    child_variables: Rc::new(inner_variables),
                 }
             }
             GraphPattern::Table {
@@ -215,12 +295,22 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                 }
             }
             GraphPattern::Project { inner, projection } => {
+                let inner = if self.custom_aggregates.is_empty() {
+                    Cow::Borrowed(inner.as_ref())
+                } else {
+                    Cow::Owned(lift_custom_aggregates(
+                        inner,
+                        projection,
+                        self.custom_aggregates,
+                        &mut Vec::new(),
+                    ))
+                };
                 let mut inner_variables = projection.clone();
                 let inner_graph_name =
                     self.convert_pattern_value_id(graph_name, variables, &mut inner_variables);
                 PlanNode::Project {
                     child: Rc::new(self.build_for_graph_pattern(
-                        inner,
+                        &inner,
                         &mut inner_variables,
                         inner_graph_name,
                     )?),
@@ -233,6 +323,7 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                             })
                             .collect(),
                     ),
+                    child_variables: Rc::new(inner_variables),
                 }
             }
             GraphPattern::Distinct { inner } => PlanNode::HashDeduplicate {
@@ -270,16 +361,92 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
         variables: &mut Vec<Variable>,
         graph_name: PatternValue<E::StrId>,
     ) -> Result<PlanNode<E::StrId>, EvaluationError> {
+        let (text_searches, p) = extract_text_searches(p);
         let mut plan = PlanNode::Init;
-        for pattern in sort_bgp(p) {
+        for search in text_searches {
+            plan = self.build_for_text_search(plan, &search, variables, graph_name)?;
+        }
+        for pattern in self.sort_bgp(&p)? {
+            let mut quoted_triples = Vec::new();
             plan = PlanNode::QuadPatternJoin {
                 child: Rc::new(plan),
-                subject: self.pattern_value_from_term_or_variable(&pattern.subject, variables)?,
+                subject: self.pattern_value_from_term_or_variable(
+                    &pattern.subject,
+                    variables,
+                    &mut quoted_triples,
+                )?,
                 predicate: self
                     .pattern_value_from_named_node_or_variable(&pattern.predicate, variables)?,
-                object: self.pattern_value_from_term_or_variable(&pattern.object, variables)?,
+                object: self.pattern_value_from_term_or_variable(
+                    &pattern.object,
+                    variables,
+                    &mut quoted_triples,
+                )?,
                 graph_name,
-            }
+            };
+            plan = self.build_for_quoted_triples(plan, quoted_triples, variables)?;
+        }
+        Ok(plan)
+    }
+
+    fn build_for_text_search(
+        &mut self,
+        child: PlanNode<E::StrId>,
+        search: &TextSearchPattern<'_>,
+        variables: &mut Vec<Variable>,
+        graph_name: PatternValue<E::StrId>,
+    ) -> Result<PlanNode<E::StrId>, EvaluationError> {
+        let mut quoted_triples = Vec::new();
+        let plan = PlanNode::TextSearch {
+            child: Rc::new(child),
+            subject: self.pattern_value_from_term_or_variable(
+                search.subject,
+                variables,
+                &mut quoted_triples,
+            )?,
+            property: search
+                .property
+                .map(|property| self.build_named_node(property))
+                .transpose()?,
+            literal: search.literal.map(|v| variable_key(variables, v)),
+            score: search.score.map(|v| variable_key(variables, v)),
+            query: self.pattern_value_from_term_or_variable(
+                search.query,
+                variables,
+                &mut quoted_triples,
+            )?,
+            limit: search.limit,
+            graph_name,
+        };
+        self.build_for_quoted_triples(plan, quoted_triples, variables)
+    }
+
+    /// Adds the nodes matching the quoted triple patterns bound to the given variables
+    fn build_for_quoted_triples(
+        &mut self,
+        mut plan: PlanNode<E::StrId>,
+        quoted_triples: Vec<(usize, TriplePattern)>,
+        variables: &mut Vec<Variable>,
+    ) -> Result<PlanNode<E::StrId>, EvaluationError> {
+        for (position, pattern) in quoted_triples {
+            let mut nested_quoted_triples = Vec::new();
+            plan = PlanNode::QuotedTripleMatch {
+                child: Rc::new(plan),
+                triple: PatternValue::Variable(position),
+                subject: self.pattern_value_from_term_or_variable(
+                    &pattern.subject,
+                    variables,
+                    &mut nested_quoted_triples,
+                )?,
+                predicate: self
+                    .pattern_value_from_named_node_or_variable(&pattern.predicate, variables)?,
+                object: self.pattern_value_from_term_or_variable(
+                    &pattern.object,
+                    variables,
+                    &mut nested_quoted_triples,
+                )?,
+            };
+            plan = self.build_for_quoted_triples(plan, nested_quoted_triples, variables)?;
         }
         Ok(plan)
     }
@@ -595,6 +762,27 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                 Function::IsNumeric => PlanExpression::IsNumeric(Box::new(
                     self.build_for_expression(&parameters[0], variables, graph_name)?,
                 )),
+                Function::Triple => PlanExpression::Triple(
+                    Box::new(self.build_for_expression(&parameters[0], variables, graph_name)?),
+                    Box::new(self.build_for_expression(&parameters[1], variables, graph_name)?),
+                    Box::new(self.build_for_expression(&parameters[2], variables, graph_name)?),
+                ),
+                Function::Subject => PlanExpression::Subject(Box::new(self.build_for_expression(
+                    &parameters[0],
+                    variables,
+                    graph_name,
+                )?)),
+                Function::Predicate => PlanExpression::Predicate(Box::new(
+                    self.build_for_expression(&parameters[0], variables, graph_name)?,
+                )),
+                Function::Object => PlanExpression::Object(Box::new(self.build_for_expression(
+                    &parameters[0],
+                    variables,
+                    graph_name,
+                )?)),
+                Function::IsTriple => PlanExpression::IsTriple(Box::new(
+                    self.build_for_expression(&parameters[0], variables, graph_name)?,
+                )),
                 Function::Regex => PlanExpression::Regex(
                     Box::new(self.build_for_expression(&parameters[0], variables, graph_name)?),
                     Box::new(self.build_for_expression(&parameters[1], variables, graph_name)?),
@@ -606,7 +794,12 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                     },
                 ),
                 Function::Custom(name) => {
-                    if name == "http://www.w3.org/2001/XMLSchema#boolean" {
+                    if self.custom_functions.contains_key(name) {
+                        PlanExpression::CustomFunction(
+                            name.clone(),
+                            self.expression_list(parameters, variables, graph_name)?,
+                        )
+                    } else if name == "http://www.w3.org/2001/XMLSchema#boolean" {
                         self.build_cast(
                             parameters,
                             PlanExpression::BooleanCast,
@@ -702,6 +895,70 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                             graph_name,
                             "string",
                         )?
+                    } else if name == "http://www.opengis.net/def/function/geosparql/distance" {
+                        if parameters.len() == 3 {
+                            PlanExpression::GeoDistance(
+                                Box::new(self.build_for_expression(
+                                    &parameters[0],
+                                    variables,
+                                    graph_name,
+                                )?),
+                                Box::new(self.build_for_expression(
+                                    &parameters[1],
+                                    variables,
+                                    graph_name,
+                                )?),
+                                Box::new(self.build_for_expression(
+                                    &parameters[2],
+                                    variables,
+                                    graph_name,
+                                )?),
+                            )
+                        } else {
+                            return Err(EvaluationError::msg(
+                                "The geof:distance function takes 3 parameters",
+                            ));
+                        }
+                    } else if name == "http://www.opengis.net/def/function/geosparql/sfEquals" {
+                        self.build_geo_relation(
+                            parameters,
+                            PlanExpression::GeoEquals,
+                            variables,
+                            graph_name,
+                            "sfEquals",
+                        )?
+                    } else if name == "http://www.opengis.net/def/function/geosparql/sfDisjoint" {
+                        self.build_geo_relation(
+                            parameters,
+                            PlanExpression::GeoDisjoint,
+                            variables,
+                            graph_name,
+                            "sfDisjoint",
+                        )?
+                    } else if name == "http://www.opengis.net/def/function/geosparql/sfIntersects" {
+                        self.build_geo_relation(
+                            parameters,
+                            PlanExpression::GeoIntersects,
+                            variables,
+                            graph_name,
+                            "sfIntersects",
+                        )?
+                    } else if name == "http://www.opengis.net/def/function/geosparql/sfWithin" {
+                        self.build_geo_relation(
+                            parameters,
+                            PlanExpression::GeoWithin,
+                            variables,
+                            graph_name,
+                            "sfWithin",
+                        )?
+                    } else if name == "http://www.opengis.net/def/function/geosparql/sfContains" {
+                        self.build_geo_relation(
+                            parameters,
+                            PlanExpression::GeoContains,
+                            variables,
+                            graph_name,
+                            "sfContains",
+                        )?
                     } else {
                         return Err(EvaluationError::msg(format!(
                             "Not supported custom function {}",
@@ -747,6 +1004,30 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
         }
     }
 
+    fn build_geo_relation(
+        &mut self,
+        parameters: &[Expression],
+        constructor: impl Fn(
+            Box<PlanExpression<E::StrId>>,
+            Box<PlanExpression<E::StrId>>,
+        ) -> PlanExpression<E::StrId>,
+        variables: &mut Vec<Variable>,
+        graph_name: PatternValue<E::StrId>,
+        name: &'static str,
+    ) -> Result<PlanExpression<E::StrId>, EvaluationError> {
+        if parameters.len() == 2 {
+            Ok(constructor(
+                Box::new(self.build_for_expression(&parameters[0], variables, graph_name)?),
+                Box::new(self.build_for_expression(&parameters[1], variables, graph_name)?),
+            ))
+        } else {
+            Err(EvaluationError::msg(format!(
+                "The geof:{} function takes 2 parameters",
+                name
+            )))
+        }
+    }
+
     fn expression_list(
         &mut self,
         l: &[Expression],
@@ -762,6 +1043,7 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
         &mut self,
         term_or_variable: &TermOrVariable,
         variables: &mut Vec<Variable>,
+        quoted_triples: &mut Vec<(usize, TriplePattern)>,
     ) -> Result<PatternValue<E::StrId>, EvaluationError> {
         Ok(match term_or_variable {
             TermOrVariable::Variable(variable) => {
@@ -775,6 +1057,12 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                 //TODO: very bad hack to convert bnode to variable
             }
             TermOrVariable::Term(term) => PatternValue::Constant(self.build_term(term)?),
+            TermOrVariable::Triple(pattern) => {
+                variables.push(Variable::new_random());
+                let position = variables.len() - 1;
+                quoted_triples.push((position, pattern.as_ref().clone()));
+                PatternValue::Variable(position)
+            }
         })
     }
 
@@ -867,9 +1155,24 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                 parameter: Some(self.build_for_expression(expr, variables, graph_name)?),
                 distinct: *distinct,
             }),
-            AggregationFunction::Custom { .. } => Err(EvaluationError::msg(
-                "Custom aggregation functions are not supported yet",
-            )),
+            AggregationFunction::Custom {
+                name,
+                expr,
+                distinct,
+            } => {
+                if self.custom_aggregates.contains_key(name) {
+                    Ok(PlanAggregation {
+                        function: PlanAggregationFunction::Custom(name.clone()),
+                        parameter: Some(self.build_for_expression(expr, variables, graph_name)?),
+                        distinct: *distinct,
+                    })
+                } else {
+                    Err(EvaluationError::msg(format!(
+                        "Not supported custom aggregate function {}",
+                        name
+                    )))
+                }
+            }
         }
     }
 
@@ -914,6 +1217,22 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                 TripleTemplateValue::BlankNode(bnode_key(bnodes, bnode))
             }
             TermOrVariable::Term(term) => TripleTemplateValue::Constant(self.build_term(term)?),
+            TermOrVariable::Triple(triple) => {
+                TripleTemplateValue::Triple(Box::new(TripleTemplate {
+                    subject: self.template_value_from_term_or_variable(
+                        &triple.subject,
+                        variables,
+                        bnodes,
+                    )?,
+                    predicate: self
+                        .template_value_from_named_node_or_variable(&triple.predicate, variables)?,
+                    object: self.template_value_from_term_or_variable(
+                        &triple.object,
+                        variables,
+                        bnodes,
+                    )?,
+                }))
+            }
         })
     }
 
@@ -975,7 +1294,9 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
             PlanNode::Init
             | PlanNode::StaticBindings { .. }
             | PlanNode::QuadPatternJoin { .. }
-            | PlanNode::PathPatternJoin { .. } => (),
+            | PlanNode::QuotedTripleMatch { .. }
+            | PlanNode::PathPatternJoin { .. }
+            | PlanNode::TextSearch { .. } => (),
             PlanNode::Filter { child, expression } => {
                 expression.add_maybe_bound_variables(set); //TODO: only if it is not already bound
                 self.add_left_join_problematic_variables(&*child, set);
@@ -985,14 +1306,16 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
                     self.add_left_join_problematic_variables(child, set);
                 }
             }
-            PlanNode::Join { left, right, .. } => {
+            PlanNode::Join { left, right, .. }
+            | PlanNode::ForLoopJoin { left, right, .. }
+            | PlanNode::HashJoin { left, right, .. } => {
                 self.add_left_join_problematic_variables(&*left, set);
                 self.add_left_join_problematic_variables(&*right, set);
             }
             PlanNode::AntiJoin { left, .. } => {
                 self.add_left_join_problematic_variables(&*left, set);
             }
-            PlanNode::LeftJoin { left, right, .. } => {
+            PlanNode::LeftJoin { left, right, .. } | PlanNode::HashLeftJoin { left, right, .. } => {
                 self.add_left_join_problematic_variables(&*left, set);
                 right.add_maybe_bound_variables(set);
             }
@@ -1010,7 +1333,7 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
             | PlanNode::Limit { child, .. } => {
                 self.add_left_join_problematic_variables(&*child, set)
             }
-            PlanNode::Project { mapping, child } => {
+            PlanNode::Project { mapping, child, .. } => {
                 let mut child_bound = BTreeSet::new();
                 self.add_left_join_problematic_variables(&*child, &mut child_bound);
                 for (child_i, output_i) in mapping.iter() {
@@ -1033,6 +1356,76 @@ impl<E: WriteEncoder<Error = EvaluationError>> PlanBuilder<E> {
         }
     }
 
+    /// Orders the triple patterns of a basic graph pattern using the store cardinality estimates
+    ///
+    /// The pattern with the lowest estimated cardinality is picked first.
+    /// Then the patterns sharing a variable with the already picked ones are preferred in order to avoid cartesian products.
+    fn sort_bgp<'b>(
+        &mut self,
+        p: &'b [TriplePattern],
+    ) -> Result<Vec<&'b TriplePattern>, EvaluationError> {
+        let mut remaining = p
+            .iter()
+            .map(|pattern| Ok((pattern, self.predicate_statistics(&pattern.predicate)?)))
+            .collect::<Result<Vec<_>, EvaluationError>>()?;
+        let mut assigned_variables = HashSet::default();
+        let mut assigned_blank_nodes = HashSet::default();
+        let mut sorted = Vec::with_capacity(p.len());
+        while !remaining.is_empty() {
+            let cost = |(pattern, statistics): &(&TriplePattern, PredicateStatistics)| {
+                (
+                    sorted.is_empty()
+                        || is_pattern_connected(
+                            pattern,
+                            &assigned_variables,
+                            &assigned_blank_nodes,
+                        ),
+                    estimate_pattern_cardinality(
+                        pattern,
+                        statistics,
+                        &assigned_variables,
+                        &assigned_blank_nodes,
+                    ),
+                    count_pattern_binds(pattern, &assigned_variables, &assigned_blank_nodes),
+                )
+            };
+            let mut best = 0;
+            let mut best_cost = cost(&remaining[0]);
+            for (i, candidate) in remaining.iter().enumerate().skip(1) {
+                let candidate_cost = cost(candidate);
+                if compare_pattern_costs(candidate_cost, best_cost) == Ordering::Less {
+                    best = i;
+                    best_cost = candidate_cost;
+                }
+            }
+            let (pattern, _) = remaining.remove(best);
+            add_pattern_variables(pattern, &mut assigned_variables, &mut assigned_blank_nodes);
+            sorted.push(pattern);
+        }
+        Ok(sorted)
+    }
+
+    fn predicate_statistics(
+        &mut self,
+        predicate: &NamedNodeOrVariable,
+    ) -> Result<PredicateStatistics, EvaluationError> {
+        Ok(match predicate {
+            NamedNodeOrVariable::NamedNode(predicate) => {
+                let predicate = self.build_named_node(predicate)?;
+                self.encoder.encoded_predicate_statistics(predicate)?
+            }
+            NamedNodeOrVariable::Variable(_) => {
+                // We do not know anything about the predicate
+                let quads = self.encoder.encoded_quads_count()?;
+                PredicateStatistics {
+                    quads,
+                    distinct_subjects: quads,
+                    distinct_objects: quads,
+                }
+            }
+        })
+    }
+
     fn build_named_node(
         &mut self,
         node: &NamedNode,
@@ -1081,21 +1474,407 @@ fn slice_key<T: Eq>(slice: &[T], element: &T) -> Option<usize> {
     None
 }
 
-fn sort_bgp(p: &[TriplePattern]) -> Vec<&TriplePattern> {
-    let mut assigned_variables = HashSet::default();
-    let mut assigned_blank_nodes = HashSet::default();
-    let mut new_p: Vec<_> = p.iter().collect();
+/// A `text:query` property function call
+///
+/// Its syntax is `subject text:query object` where `subject` is either the matched subject
+/// or the list `(subject score literal)` with the optional `score` and `literal` output variables
+/// and `object` is either the query string or the list `(property query limit)` with the optional `property` and `limit`.
+struct TextSearchPattern<'a> {
+    subject: &'a TermOrVariable,
+    score: Option<&'a Variable>,
+    literal: Option<&'a Variable>,
+    property: Option<&'a NamedNode>,
+    query: &'a TermOrVariable,
+    limit: Option<usize>,
+}
+
+/// Moves the calls to the custom aggregate functions in the SELECT, HAVING and ORDER BY clauses of a query
+/// to the aggregates of its group.
+///
+/// The parser does not know the custom aggregate functions and builds them as regular function calls
+/// if they are not used with `DISTINCT`.
+/// A group without key is added below the SELECT expressions if the query is not already grouped.
+fn lift_custom_aggregates(
+    pattern: &GraphPattern,
+    projection: &[Variable],
+    custom_aggregates: &CustomAggregates,
+    aggregates: &mut Vec<(Variable, AggregationFunction)>,
+) -> GraphPattern {
+    match pattern {
+        GraphPattern::OrderBy { inner, condition } => {
+            let mut replace =
+                |e: &Expression| replace_custom_aggregates(e, custom_aggregates, aggregates);
+            let condition = condition
+                .iter()
+                .map(|c| match c {
+                    OrderComparator::Asc(e) => OrderComparator::Asc(replace(e)),
+                    OrderComparator::Desc(e) => OrderComparator::Desc(replace(e)),
+                })
+                .collect();
+            GraphPattern::OrderBy {
+                inner: Box::new(lift_custom_aggregates(
+                    inner,
+                    projection,
+                    custom_aggregates,
+                    aggregates,
+                )),
+                condition,
+            }
+        }
+        GraphPattern::Extend { inner, var, expr } if projection.contains(var) => {
+            let expr = replace_custom_aggregates(expr, custom_aggregates, aggregates);
+            GraphPattern::Extend {
+                inner: Box::new(lift_custom_aggregates(
+                    inner,
+                    projection,
+                    custom_aggregates,
+                    aggregates,
+                )),
+                var: var.clone(),
+                expr,
+            }
+        }
+        GraphPattern::Join { left, right }
+            if matches!(right.as_ref(), GraphPattern::Table { .. }) =>
+        {
+            // VALUES clause
+            GraphPattern::Join {
+                left: Box::new(lift_custom_aggregates(
+                    left,
+                    projection,
+                    custom_aggregates,
+                    aggregates,
+                )),
+                right: right.clone(),
+            }
+        }
+        GraphPattern::Filter { expr, inner } => {
+            let new_expr = replace_custom_aggregates(expr, custom_aggregates, aggregates);
+            if new_expr != *expr || matches!(inner.as_ref(), GraphPattern::Group { .. }) {
+                // HAVING clause
+                GraphPattern::Filter {
+                    expr: new_expr,
+                    inner: Box::new(lift_custom_aggregates(
+                        inner,
+                        projection,
+                        custom_aggregates,
+                        aggregates,
+                    )),
+                }
+            } else {
+                add_custom_aggregates_group(pattern, aggregates)
+            }
+        }
+        GraphPattern::Group {
+            inner,
+            by,
+            aggregates: group_aggregates,
+        } => GraphPattern::Group {
+            inner: inner.clone(),
+            by: by.clone(),
+            aggregates: group_aggregates
+                .iter()
+                .cloned()
+                .chain(aggregates.drain(..))
+                .collect(),
+        },
+        _ => add_custom_aggregates_group(pattern, aggregates),
+    }
+}
+
+fn add_custom_aggregates_group(
+    pattern: &GraphPattern,
+    aggregates: &mut Vec<(Variable, AggregationFunction)>,
+) -> GraphPattern {
+    if aggregates.is_empty() {
+        pattern.clone()
+    } else {
+        GraphPattern::Group {
+            inner: Box::new(pattern.clone()),
+            by: Vec::new(),
+            aggregates: mem::take(aggregates),
+        }
+    }
+}
+
+/// Replaces the calls to the custom aggregate functions by variables bound to new aggregates
+fn replace_custom_aggregates(
+    expression: &Expression,
+    custom_aggregates: &CustomAggregates,
+    aggregates: &mut Vec<(Variable, AggregationFunction)>,
+) -> Expression {
+    let mut replace =
+        |e: &Expression| Box::new(replace_custom_aggregates(e, custom_aggregates, aggregates));
+    match expression {
+        Expression::NamedNode(_)
+        | Expression::Literal(_)
+        | Expression::Variable(_)
+        | Expression::Bound(_)
+        | Expression::Exists(_) => expression.clone(),
+        Expression::Or(a, b) => Expression::Or(replace(a), replace(b)),
+        Expression::And(a, b) => Expression::And(replace(a), replace(b)),
+        Expression::Equal(a, b) => Expression::Equal(replace(a), replace(b)),
+        Expression::SameTerm(a, b) => Expression::SameTerm(replace(a), replace(b)),
+        Expression::Greater(a, b) => Expression::Greater(replace(a), replace(b)),
+        Expression::GreaterOrEqual(a, b) => Expression::GreaterOrEqual(replace(a), replace(b)),
+        Expression::Less(a, b) => Expression::Less(replace(a), replace(b)),
+        Expression::LessOrEqual(a, b) => Expression::LessOrEqual(replace(a), replace(b)),
+        Expression::Add(a, b) => Expression::Add(replace(a), replace(b)),
+        Expression::Subtract(a, b) => Expression::Subtract(replace(a), replace(b)),
+        Expression::Multiply(a, b) => Expression::Multiply(replace(a), replace(b)),
+        Expression::Divide(a, b) => Expression::Divide(replace(a), replace(b)),
+        Expression::UnaryPlus(e) => Expression::UnaryPlus(replace(e)),
+        Expression::UnaryMinus(e) => Expression::UnaryMinus(replace(e)),
+        Expression::Not(e) => Expression::Not(replace(e)),
+        Expression::If(a, b, c) => Expression::If(replace(a), replace(b), replace(c)),
+        Expression::In(e, l) => Expression::In(replace(e), l.iter().map(|e| *replace(e)).collect()),
+        Expression::Coalesce(l) => Expression::Coalesce(l.iter().map(|e| *replace(e)).collect()),
+        Expression::FunctionCall(Function::Custom(name), args)
+            if args.len() == 1 && custom_aggregates.contains_key(name) =>
+        {
+            let aggregate = AggregationFunction::Custom {
+                name: name.clone(),
+                expr: Box::new(args[0].clone()),
+                distinct: false,
+            };
+            let variable = aggregates
+                .iter()
+                .find_map(|(v, a)| if *a == aggregate { Some(v) } else { None })
+                .cloned()
+                .unwrap_or_else(|| {
+                    let variable = Variable::new_random();
+                    aggregates.push((variable.clone(), aggregate));
+                    variable
+                });
+            Expression::Variable(variable)
+        }
+        Expression::FunctionCall(function, args) => {
+            Expression::FunctionCall(function.clone(), args.iter().map(|e| *replace(e)).collect())
+        }
+    }
+}
 
-    for i in 0..new_p.len() {
-        (&mut new_p[i..]).sort_by(|p1, p2| {
-            count_pattern_binds(p2, &assigned_variables, &assigned_blank_nodes).cmp(
-                &count_pattern_binds(p1, &assigned_variables, &assigned_blank_nodes),
-            )
+/// Extracts the `text:query` property function calls from a basic graph pattern and returns them with the remaining triple patterns
+///
+/// The lists used in the calls are read from the `rdf:first` and `rdf:rest` patterns generated by the parser.
+/// The calls that do not follow the property function syntax are kept as regular triple patterns.
+fn extract_text_searches(
+    p: &[TriplePattern],
+) -> (Vec<TextSearchPattern<'_>>, Cow<'_, [TriplePattern]>) {
+    let mut searches = Vec::new();
+    let mut consumed = HashSet::new();
+    let mut firsts = HashMap::new();
+    let mut rests = HashMap::new();
+    for (i, pattern) in p.iter().enumerate() {
+        if let (
+            TermOrVariable::Term(Term::BlankNode(node)),
+            NamedNodeOrVariable::NamedNode(predicate),
+        ) = (&pattern.subject, &pattern.predicate)
+        {
+            if predicate.as_ref() == rdf::FIRST {
+                firsts.insert(node, i);
+            } else if predicate.as_ref() == rdf::REST {
+                rests.insert(node, i);
+            }
+        }
+    }
+    let read_list = |node: &TermOrVariable, used: &mut Vec<usize>| {
+        let mut node = match node {
+            TermOrVariable::Term(Term::BlankNode(node)) if firsts.contains_key(node) => node,
+            _ => return None,
+        };
+        let mut items = Vec::new();
+        while items.len() < p.len() {
+            let first = *firsts.get(node)?;
+            let rest = *rests.get(node)?;
+            used.push(first);
+            used.push(rest);
+            items.push(&p[first].object);
+            match &p[rest].object {
+                TermOrVariable::Term(Term::NamedNode(nil)) if nil.as_ref() == rdf::NIL => {
+                    return Some(items)
+                }
+                TermOrVariable::Term(Term::BlankNode(next)) => node = next,
+                _ => return None,
+            }
+        }
+        None // The list is cyclic
+    };
+    for (i, pattern) in p.iter().enumerate() {
+        if !matches!(&pattern.predicate, NamedNodeOrVariable::NamedNode(predicate) if predicate.as_str() == TEXT_QUERY)
+        {
+            continue;
+        }
+        let mut used = vec![i];
+        let subject = if let Some(items) = read_list(&pattern.subject, &mut used) {
+            parse_text_search_subject(&items)
+        } else {
+            Some((&pattern.subject, None, None))
+        };
+        let object = if let Some(items) = read_list(&pattern.object, &mut used) {
+            parse_text_search_object(&items)
+        } else {
+            Some((None, &pattern.object, None))
+        };
+        let ((subject, score, literal), (property, query, limit)) =
+            if let (Some(subject), Some(object)) = (subject, object) {
+                (subject, object)
+            } else {
+                continue;
+            };
+        if !matches!(
+            query,
+            TermOrVariable::Term(Term::Literal(_)) | TermOrVariable::Variable(_)
+        ) {
+            continue;
+        }
+        consumed.extend(used);
+        searches.push(TextSearchPattern {
+            subject,
+            score,
+            literal,
+            property,
+            query,
+            limit,
         });
-        add_pattern_variables(new_p[i], &mut assigned_variables, &mut assigned_blank_nodes);
     }
+    if consumed.is_empty() {
+        return (searches, Cow::Borrowed(p));
+    }
+    let remaining = p
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !consumed.contains(i))
+        .map(|(_, pattern)| pattern.clone())
+        .collect();
+    (searches, Cow::Owned(remaining))
+}
+
+/// Parses the `(subject score literal)` list of a `text:query` call
+fn parse_text_search_subject<'a>(
+    items: &[&'a TermOrVariable],
+) -> Option<(
+    &'a TermOrVariable,
+    Option<&'a Variable>,
+    Option<&'a Variable>,
+)> {
+    Some(match items {
+        [subject] => (*subject, None, None),
+        [subject, score] => (*subject, Some(as_variable(score)?), None),
+        [subject, score, literal] => (
+            *subject,
+            Some(as_variable(score)?),
+            Some(as_variable(literal)?),
+        ),
+        _ => return None,
+    })
+}
+
+/// Parses the `(property query limit)` list of a `text:query` call
+fn parse_text_search_object<'a>(
+    items: &[&'a TermOrVariable],
+) -> Option<(Option<&'a NamedNode>, &'a TermOrVariable, Option<usize>)> {
+    let (property, items) = match items {
+        [TermOrVariable::Term(Term::NamedNode(_)), ..] => (as_named_node(items[0]), &items[1..]),
+        _ => (None, items),
+    };
+    Some(match items {
+        [query] => (property, *query, None),
+        [query, TermOrVariable::Term(Term::Literal(limit))] => {
+            (property, *query, Some(limit.value().parse().ok()?))
+        }
+        _ => return None,
+    })
+}
+
+fn as_variable(term: &TermOrVariable) -> Option<&Variable> {
+    if let TermOrVariable::Variable(variable) = term {
+        Some(variable)
+    } else {
+        None
+    }
+}
 
-    new_p
+fn as_named_node(term: &TermOrVariable) -> Option<&NamedNode> {
+    if let TermOrVariable::Term(Term::NamedNode(node)) = term {
+        Some(node)
+    } else {
+        None
+    }
+}
+
+/// Returns the variables that might be bound by both sides of a join
+fn join_keys<I: StrId>(left: &PlanNode<I>, right: &PlanNode<I>) -> Vec<usize> {
+    left.maybe_bound_variables()
+        .intersection(&right.maybe_bound_variables())
+        .copied()
+        .collect()
+}
+
+/// Returns if the plan is only made of index lookups that could use the bindings of a left join side
+fn is_index_lookup<I: StrId>(node: &PlanNode<I>) -> bool {
+    match node {
+        PlanNode::Init => true,
+        PlanNode::QuadPatternJoin { child, .. }
+        | PlanNode::QuotedTripleMatch { child, .. }
+        | PlanNode::PathPatternJoin { child, .. }
+        | PlanNode::TextSearch { child, .. } => is_index_lookup(child),
+        _ => false,
+    }
+}
+
+/// The cost of a triple pattern: is it connected to the already picked patterns, its estimated cardinality and its number of bound positions
+type PatternCost = (bool, f64, u8);
+
+fn compare_pattern_costs(a: PatternCost, b: PatternCost) -> Ordering {
+    b.0.cmp(&a.0)
+        .then_with(|| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+        .then_with(|| b.2.cmp(&a.2))
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn estimate_pattern_cardinality(
+    pattern: &TriplePattern,
+    statistics: &PredicateStatistics,
+    assigned_variables: &HashSet<&Variable>,
+    assigned_blank_nodes: &HashSet<&BlankNode>,
+) -> f64 {
+    let mut cardinality = statistics.quads as f64;
+    if is_term_bound(&pattern.subject, assigned_variables, assigned_blank_nodes) {
+        cardinality /= statistics.distinct_subjects.max(1) as f64;
+    }
+    if is_term_bound(&pattern.object, assigned_variables, assigned_blank_nodes) {
+        cardinality /= statistics.distinct_objects.max(1) as f64;
+    }
+    cardinality
+}
+
+fn is_term_bound(
+    term: &TermOrVariable,
+    assigned_variables: &HashSet<&Variable>,
+    assigned_blank_nodes: &HashSet<&BlankNode>,
+) -> bool {
+    match term {
+        TermOrVariable::Variable(v) => assigned_variables.contains(v),
+        TermOrVariable::Term(Term::BlankNode(bnode)) => assigned_blank_nodes.contains(bnode),
+        TermOrVariable::Term(_) => true,
+        TermOrVariable::Triple(_) => false,
+    }
+}
+
+fn is_pattern_connected(
+    pattern: &TriplePattern,
+    assigned_variables: &HashSet<&Variable>,
+    assigned_blank_nodes: &HashSet<&BlankNode>,
+) -> bool {
+    let is_assigned = |term: &TermOrVariable| match term {
+        TermOrVariable::Variable(v) => assigned_variables.contains(v),
+        TermOrVariable::Term(Term::BlankNode(bnode)) => assigned_blank_nodes.contains(bnode),
+        _ => false,
+    };
+    is_assigned(&pattern.subject)
+        || is_assigned(&pattern.object)
+        || matches!(&pattern.predicate, NamedNodeOrVariable::Variable(v) if assigned_variables.contains(v))
 }
 
 fn count_pattern_binds(
@@ -1155,3 +1934,107 @@ fn add_pattern_variables<'a>(
         blank_nodes.insert(bnode);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::model::vocab::rdf;
+    use crate::model::*;
+    use crate::sparql::{EvaluationError, QueryResults};
+    use crate::MemoryStore;
+
+    #[test]
+    fn test_bgp_order_uses_statistics() -> Result<(), EvaluationError> {
+        let store = MemoryStore::new();
+        let class = NamedNode::new_unchecked("http://example.com/Class");
+        let rare = NamedNode::new_unchecked("http://example.com/rare");
+        for i in 0..100 {
+            let subject = NamedNode::new_unchecked(format!("http://example.com/{}", i));
+            store.insert(Quad::new(
+                subject.clone(),
+                rdf::TYPE,
+                class.clone(),
+                GraphName::DefaultGraph,
+            ));
+            if i % 50 == 0 {
+                store.insert(Quad::new(
+                    subject,
+                    rare.clone(),
+                    Literal::from(i),
+                    GraphName::DefaultGraph,
+                ));
+            }
+        }
+
+        // The bound positions heuristic would have evaluated the rdf:type pattern first
+        let explanation = store.explain(
+            "SELECT * WHERE { ?s a <http://example.com/Class> . ?s <http://example.com/rare> ?o }",
+        )?;
+        let last = &explanation.root().children()[0];
+        assert_eq!(
+            last.description(),
+            "?s <http://www.w3.org/1999/02/22-rdf-syntax-ns#type> <http://example.com/Class> DEFAULT"
+        );
+        let first = &last.children()[0];
+        assert_eq!(
+            first.description(),
+            "?s <http://example.com/rare> ?o DEFAULT"
+        );
+        assert_eq!(first.children()[0].operator(), "Init");
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_operators() -> Result<(), EvaluationError> {
+        let store = MemoryStore::new();
+        let p = NamedNode::new_unchecked("http://example.com/p");
+        let q = NamedNode::new_unchecked("http://example.com/q");
+        for i in 0..10 {
+            let subject = NamedNode::new_unchecked(format!("http://example.com/{}", i));
+            store.insert(Quad::new(
+                subject.clone(),
+                p.clone(),
+                Literal::from(i),
+                GraphName::DefaultGraph,
+            ));
+            if i % 2 == 0 {
+                store.insert(Quad::new(
+                    subject,
+                    q.clone(),
+                    Literal::from(i),
+                    GraphName::DefaultGraph,
+                ));
+            }
+        }
+        let count = |query: &str| -> Result<usize, EvaluationError> {
+            if let QueryResults::Solutions(solutions) = store.query(query)? {
+                solutions.collect::<Result<Vec<_>, _>>().map(|s| s.len())
+            } else {
+                Ok(0)
+            }
+        };
+
+        let query = "SELECT * WHERE { { SELECT ?s WHERE { ?s <http://example.com/q> ?o } } ?s <http://example.com/p> ?o2 }";
+        assert_eq!(
+            store.explain(query)?.root().children()[0].operator(),
+            "ForLoopJoin"
+        );
+        assert_eq!(count(query)?, 5);
+
+        let query = "SELECT * WHERE { ?s <http://example.com/p> ?o { SELECT ?s WHERE { ?s <http://example.com/q> ?o2 } } }";
+        let explanation = store.explain(query)?;
+        let join = &explanation.root().children()[0];
+        assert_eq!(join.operator(), "HashJoin");
+        assert_eq!(join.description(), "?s");
+        assert_eq!(count(query)?, 5);
+
+        let query = "SELECT * WHERE { ?s <http://example.com/p> ?o OPTIONAL { SELECT ?s ?o2 WHERE { ?s <http://example.com/q> ?o2 } } FILTER(!BOUND(?o2) || ?o2 > 4) }";
+        let explanation = store.explain(query)?;
+        let left_join = &explanation.root().children()[0].children()[0];
+        assert_eq!(left_join.operator(), "HashLeftJoin");
+        assert_eq!(count(query)?, 7);
+
+        let query = "SELECT * WHERE { ?s <http://example.com/p> ?o OPTIONAL { SELECT ?s ?o2 WHERE { ?s <http://example.com/q> ?o2 } } }";
+        assert_eq!(count(query)?, 10);
+        Ok(())
+    }
+}
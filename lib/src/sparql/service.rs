@@ -117,7 +117,7 @@ impl ServiceHandler for SimpleServiceHandler {
             .method(Method::POST)
             .uri(service_name.as_str())
             .header(CONTENT_TYPE, "application/sparql-query")
-            .header(ACCEPT, QueryResultsFormat::Xml.media_type())
+            .header(ACCEPT, QueryResultsFormat::Json.media_type())
             .header(USER_AGENT, concat!("Oxigraph/", env!("CARGO_PKG_VERSION")))
             .body(Some(query.to_string().into_bytes()))
             .map_err(invalid_input_error)?;
@@ -0,0 +1,215 @@
+//! Temporary files used by the evaluation operators buffering solutions when they go over the memory limit
+
+use crate::error::invalid_data_error;
+use crate::model::Term;
+use crate::sparql::EvaluationError;
+use rand::random;
+use std::collections::hash_map::DefaultHasher;
+use std::convert::TryInto;
+use std::env;
+use std::fs::{remove_file, File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str::FromStr;
+
+/// Number of temporary files the solutions are split into when a hash table goes over the memory limit
+const SPILL_PARTITIONS: u8 = 16;
+
+/// The memory budget of the operators buffering solutions (ORDER BY, GROUP BY and DISTINCT)
+pub(crate) struct MemoryLimit {
+    bytes: usize,
+    spill_directory: Option<PathBuf>,
+    spilling: bool,
+}
+
+impl MemoryLimit {
+    /// Returns `None` if there is no limit
+    pub fn new(
+        bytes: Option<usize>,
+        spill_directory: Option<PathBuf>,
+        spilling: bool,
+    ) -> Option<Rc<Self>> {
+        Some(Rc::new(Self {
+            bytes: bytes?,
+            spill_directory,
+            spilling,
+        }))
+    }
+
+    /// The maximal number of bytes an operator is allowed to buffer
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Creates a new temporary file or fails if spilling to disk is disabled
+    pub fn spill_file(&self) -> Result<SpillFile, EvaluationError> {
+        if !self.spilling {
+            return Err(EvaluationError::MemoryLimitExceeded);
+        }
+        let mut path = self.spill_directory.clone().unwrap_or_else(env::temp_dir);
+        path.push(format!("oxigraph-spill-{:x}", random::<u128>()));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(SpillFile {
+            writer: BufWriter::new(file),
+            path: TemporaryPath(path),
+        })
+    }
+
+    /// Creates the temporary files to split a hash table into
+    pub fn spill_partitions(&self) -> Result<Vec<SpillFile>, EvaluationError> {
+        (0..SPILL_PARTITIONS).map(|_| self.spill_file()).collect()
+    }
+}
+
+/// Returns the partition of a value.
+///
+/// The depth changes the hash function each time a partition is itself split.
+pub(crate) fn spill_partition(value: &impl Hash, depth: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    depth.hash(&mut hasher);
+    value.hash(&mut hasher);
+    (hasher.finish() % u64::from(SPILL_PARTITIONS))
+        .try_into()
+        .unwrap_or(0)
+}
+
+/// A temporary file of solutions removed when dropped.
+///
+/// Each solution is written as its number of slots followed for each slot
+/// by a presence flag and the length prefixed N-Triples serialization of the term.
+pub(crate) struct SpillFile {
+    writer: BufWriter<File>,
+    path: TemporaryPath,
+}
+
+impl SpillFile {
+    pub fn write(&mut self, solution: &[Option<Term>]) -> io::Result<()> {
+        write_len(&mut self.writer, solution.len())?;
+        for term in solution {
+            if let Some(term) = term {
+                let term = term.to_string();
+                self.writer.write_all(&[1])?;
+                write_len(&mut self.writer, term.len())?;
+                self.writer.write_all(term.as_bytes())?;
+            } else {
+                self.writer.write_all(&[0])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the solutions written in the file in the same order
+    pub fn read(self) -> io::Result<SpillFileReader> {
+        let mut file = self.writer.into_inner().map_err(io::Error::from)?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(SpillFileReader {
+            reader: BufReader::new(file),
+            _path: self.path,
+        })
+    }
+}
+
+pub(crate) struct SpillFileReader {
+    reader: BufReader<File>,
+    _path: TemporaryPath,
+}
+
+impl SpillFileReader {
+    fn read_solution(&mut self) -> io::Result<Option<Vec<Option<Term>>>> {
+        let len = match read_len(&mut self.reader) {
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut solution = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mut flag = [0];
+            self.reader.read_exact(&mut flag)?;
+            solution.push(if flag[0] == 0 {
+                None
+            } else {
+                let mut buffer = vec![0; read_len(&mut self.reader)?];
+                self.reader.read_exact(&mut buffer)?;
+                let term = String::from_utf8(buffer).map_err(invalid_data_error)?;
+                Some(Term::from_str(&term).map_err(invalid_data_error)?)
+            });
+        }
+        Ok(Some(solution))
+    }
+}
+
+impl Iterator for SpillFileReader {
+    type Item = Result<Vec<Option<Term>>, EvaluationError>;
+
+    fn next(&mut self) -> Option<Result<Vec<Option<Term>>, EvaluationError>> {
+        self.read_solution()
+            .map_err(EvaluationError::from)
+            .transpose()
+    }
+}
+
+/// A file path removed when dropped
+struct TemporaryPath(PathBuf);
+
+impl Drop for TemporaryPath {
+    fn drop(&mut self) {
+        let _ = remove_file(&self.0);
+    }
+}
+
+fn write_len(writer: &mut impl Write, len: usize) -> io::Result<()> {
+    let len: u32 = len.try_into().map_err(invalid_data_error)?;
+    writer.write_all(&len.to_be_bytes())
+}
+
+fn read_len(reader: &mut impl Read) -> io::Result<usize> {
+    let mut buffer = [0; 4];
+    reader.read_exact(&mut buffer)?;
+    u32::from_be_bytes(buffer)
+        .try_into()
+        .map_err(invalid_data_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::*;
+
+    #[test]
+    fn test_spill_file() -> Result<(), EvaluationError> {
+        let limit = MemoryLimit::new(Some(0), None, true).unwrap();
+        let solutions = vec![
+            vec![
+                Some(NamedNode::new_unchecked("http://example.com/s").into()),
+                None,
+                Some(Literal::new_language_tagged_literal_unchecked("a\n\"b\"", "en").into()),
+            ],
+            vec![],
+            vec![None, Some(Literal::from(1.5).into())],
+        ];
+        let mut file = limit.spill_file()?;
+        for solution in &solutions {
+            file.write(solution)?;
+        }
+        let path = file.path.0.clone();
+        let reader = file.read()?;
+        assert_eq!(reader.collect::<Result<Vec<_>, _>>()?, solutions);
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_spilling_disabled() {
+        let limit = MemoryLimit::new(Some(0), None, false).unwrap();
+        assert!(matches!(
+            limit.spill_file(),
+            Err(EvaluationError::MemoryLimitExceeded)
+        ));
+    }
+}
@@ -1,18 +1,19 @@
 use crate::error::{invalid_data_error, invalid_input_error};
 use crate::io::GraphFormat;
-use crate::model::{BlankNode, GraphNameRef, NamedNode, NamedOrBlankNode, Quad, Term};
+use crate::model::{BlankNode, GraphNameRef, NamedNode, Quad, Subject, Term, Triple};
 use crate::sparql::algebra::{
     GraphPattern, GraphTarget, GraphUpdateOperation, NamedNodeOrVariable, QuadPattern,
-    QueryDataset, TermOrVariable,
+    QueryDataset, TermOrVariable, TriplePattern,
 };
 use crate::sparql::dataset::{DatasetStrId, DatasetView};
 use crate::sparql::eval::SimpleEvaluator;
 use crate::sparql::http::Client;
+use crate::sparql::interrupt::Interrupter;
 use crate::sparql::plan::EncodedTuple;
 use crate::sparql::plan_builder::PlanBuilder;
 use crate::sparql::{EvaluationError, UpdateOptions, Variable};
 use crate::store::numeric_encoder::{
-    EncodedQuad, EncodedTerm, ReadEncoder, StrContainer, StrLookup, WriteEncoder,
+    Decoder, EncodedQuad, EncodedTerm, ReadEncoder, StrContainer, StrLookup, WriteEncoder,
 };
 use crate::store::{load_graph, ReadableEncodedStore, StoreOrParseError, WritableEncodedStore};
 use http::header::{ACCEPT, CONTENT_TYPE, USER_AGENT};
@@ -27,6 +28,7 @@ pub(crate) struct SimpleUpdateEvaluator<'a, R, W> {
     write: &'a mut W,
     base_iri: Option<Rc<Iri<String>>>,
     options: UpdateOptions,
+    interrupter: Option<Rc<Interrupter>>,
     client: Client,
 }
 
@@ -48,6 +50,7 @@ where
             read,
             write,
             base_iri,
+            interrupter: options.query_options.interrupter(),
             options,
             client: Client::new(),
         }
@@ -55,6 +58,9 @@ where
 
     pub fn eval_all(&mut self, updates: &[GraphUpdateOperation]) -> Result<(), EvaluationError> {
         for update in updates {
+            if let Some(interrupter) = &self.interrupter {
+                interrupter.check()?;
+            }
             self.eval(update)?;
         }
         Ok(())
@@ -84,6 +90,15 @@ where
             GraphUpdateOperation::Clear { graph, silent } => self.eval_clear(graph, *silent),
             GraphUpdateOperation::Create { graph, silent } => self.eval_create(graph, *silent),
             GraphUpdateOperation::Drop { graph, silent } => self.eval_drop(graph, *silent),
+            GraphUpdateOperation::Add { silent, from, to } => {
+                self.eval_copy_graph(from, to, *silent, false, false)
+            }
+            GraphUpdateOperation::Move { silent, from, to } => {
+                self.eval_copy_graph(from, to, *silent, true, true)
+            }
+            GraphUpdateOperation::Copy { silent, from, to } => {
+                self.eval_copy_graph(from, to, *silent, true, false)
+            }
         }
     }
 
@@ -114,16 +129,43 @@ where
         algebra: &GraphPattern,
     ) -> Result<(), EvaluationError> {
         let dataset = Rc::new(DatasetView::new(self.read.clone(), using)?);
-        let (plan, variables) = PlanBuilder::build(dataset.as_ref(), algebra)?;
+        let (plan, variables) = PlanBuilder::build(
+            dataset.as_ref(),
+            algebra,
+            &self.options.query_options.custom_functions,
+            &self.options.query_options.custom_aggregates,
+        )?;
         let evaluator = SimpleEvaluator::<DatasetView<R>>::new(
             dataset.clone(),
             self.base_iri.clone(),
             self.options.query_options.service_handler.clone(),
+            self.options.query_options.custom_functions.clone(),
+            self.options.query_options.custom_aggregates.clone(),
+            self.interrupter.clone(),
+            self.options.query_options.memory_limit(),
         );
         let mut bnodes = HashMap::new();
+        // Quoted triple patterns are built from the decoded terms
+        let needs_terms = delete
+            .iter()
+            .chain(insert)
+            .any(|q| q.subject.is_triple() || q.object.is_triple());
         for tuple in evaluator.eval_plan(&plan, EncodedTuple::with_capacity(variables.len())) {
+            let tuple = tuple?;
+            let terms = if needs_terms {
+                tuple
+                    .iter()
+                    .map(|t| {
+                        t.map(|t| dataset.decode_term(t))
+                            .transpose()
+                            .map_err(to_eval_error)
+                    })
+                    .collect::<Result<Vec<_>, EvaluationError>>()?
+            } else {
+                Vec::new()
+            };
             // We map the tuple to only get store strings
-            let tuple = tuple?
+            let tuple = tuple
                 .into_iter()
                 .map(|t| {
                     Ok(if let Some(t) = t {
@@ -157,15 +199,19 @@ where
 
             for quad in delete {
                 if let Some(quad) =
-                    self.encode_quad_pattern_for_deletion(quad, &variables, &tuple)?
+                    self.encode_quad_pattern_for_deletion(quad, &variables, &tuple, &terms)?
                 {
                     self.write.remove_encoded(&quad).map_err(to_eval_error)?;
                 }
             }
             for quad in insert {
-                if let Some(quad) =
-                    self.encode_quad_pattern_for_insertion(quad, &variables, &tuple, &mut bnodes)?
-                {
+                if let Some(quad) = self.encode_quad_pattern_for_insertion(
+                    quad,
+                    &variables,
+                    &tuple,
+                    &terms,
+                    &mut bnodes,
+                )? {
                     self.write.insert_encoded(&quad).map_err(to_eval_error)?;
                 }
             }
@@ -222,6 +268,7 @@ where
             format,
             to_graph_name,
             Some(from.as_str()),
+            None,
         )
         .map_err(io::Error::from)?;
         Ok(())
@@ -352,6 +399,91 @@ where
         }
     }
 
+    /// Evaluates ADD (no clearing and no removal), COPY (clearing of the destination) and MOVE (clearing of the destination and removal of the source)
+    fn eval_copy_graph(
+        &mut self,
+        from: &Option<NamedNode>,
+        to: &Option<NamedNode>,
+        silent: bool,
+        clear_destination: bool,
+        remove_source: bool,
+    ) -> Result<(), EvaluationError> {
+        if from == to {
+            return Ok(()); // identity case
+        }
+        let encoded_from = if let Some(from) = from {
+            let encoded_from = self
+                .read
+                .get_encoded_named_node(from.as_ref())
+                .map_err(to_eval_error)?;
+            match encoded_from {
+                Some(encoded_from)
+                    if self
+                        .read
+                        .contains_encoded_named_graph(encoded_from)
+                        .map_err(to_eval_error)? =>
+                {
+                    encoded_from
+                }
+                _ => {
+                    return if silent {
+                        Ok(())
+                    } else {
+                        Err(EvaluationError::msg(format!(
+                            "The graph {} does not exists",
+                            from
+                        )))
+                    };
+                }
+            }
+        } else {
+            EncodedTerm::DefaultGraph
+        };
+        let encoded_to = if let Some(to) = to {
+            let encoded_to = self
+                .write
+                .encode_named_node(to.as_ref())
+                .map_err(to_eval_error)?;
+            self.write
+                .insert_encoded_named_graph(encoded_to)
+                .map_err(to_eval_error)?;
+            encoded_to
+        } else {
+            EncodedTerm::DefaultGraph
+        };
+
+        // We read all the source quads before writing in order to not see our own changes
+        let quads = self
+            .read
+            .encoded_quads_for_pattern(None, None, None, Some(encoded_from))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(to_eval_error)?;
+        if clear_destination {
+            self.write
+                .clear_encoded_graph(encoded_to)
+                .map_err(to_eval_error)?;
+        }
+        for quad in quads {
+            self.write
+                .insert_encoded(&EncodedQuad::new(
+                    quad.subject,
+                    quad.predicate,
+                    quad.object,
+                    encoded_to,
+                ))
+                .map_err(to_eval_error)?;
+        }
+        if remove_source {
+            if encoded_from.is_default_graph() {
+                self.write.clear_encoded_graph(encoded_from)
+            } else {
+                self.write.remove_encoded_named_graph(encoded_from)
+            }
+            .map_err(to_eval_error)?;
+        }
+        Ok(())
+    }
+
     fn encode_quad_for_insertion(
         &mut self,
         quad: &Quad,
@@ -359,12 +491,13 @@ where
     ) -> Result<Option<EncodedQuad<R::StrId>>, EvaluationError> {
         Ok(Some(EncodedQuad {
             subject: match &quad.subject {
-                NamedOrBlankNode::NamedNode(subject) => {
-                    self.write.encode_named_node(subject.as_ref())
-                }
-                NamedOrBlankNode::BlankNode(subject) => self
+                Subject::NamedNode(subject) => self.write.encode_named_node(subject.as_ref()),
+                Subject::BlankNode(subject) => self
                     .write
                     .encode_blank_node(bnodes.entry(subject.clone()).or_default().as_ref()),
+                Subject::Triple(subject) => self
+                    .write
+                    .encode_triple(&rename_triple_blank_nodes(subject, bnodes)),
             }
             .map_err(to_eval_error)?,
             predicate: self
@@ -377,6 +510,9 @@ where
                     .write
                     .encode_blank_node(bnodes.entry(object.clone()).or_default().as_ref()),
                 Term::Literal(object) => self.write.encode_literal(object.as_ref()),
+                Term::Triple(object) => self
+                    .write
+                    .encode_triple(&rename_triple_blank_nodes(object, bnodes)),
             }
             .map_err(to_eval_error)?,
             graph_name: self
@@ -391,13 +527,18 @@ where
         quad: &QuadPattern,
         variables: &[Variable],
         values: &[Option<EncodedTerm<R::StrId>>],
+        terms: &[Option<Term>],
         bnodes: &mut HashMap<BlankNode, BlankNode>,
     ) -> Result<Option<EncodedQuad<R::StrId>>, EvaluationError> {
         Ok(Some(EncodedQuad {
-            subject: if let Some(subject) =
-                self.encode_term_for_insertion(&quad.subject, variables, values, bnodes, |t| {
-                    t.is_named_node() || t.is_blank_node()
-                })? {
+            subject: if let Some(subject) = self.encode_term_for_insertion(
+                &quad.subject,
+                variables,
+                values,
+                terms,
+                bnodes,
+                |t| t.is_named_node() || t.is_blank_node() || t.is_triple(),
+            )? {
                 subject
             } else {
                 return Ok(None);
@@ -409,10 +550,14 @@ where
             } else {
                 return Ok(None);
             },
-            object: if let Some(object) =
-                self.encode_term_for_insertion(&quad.object, variables, values, bnodes, |t| {
-                    !t.is_default_graph()
-                })? {
+            object: if let Some(object) = self.encode_term_for_insertion(
+                &quad.object,
+                variables,
+                values,
+                terms,
+                bnodes,
+                |t| !t.is_default_graph(),
+            )? {
                 object
             } else {
                 return Ok(None);
@@ -436,19 +581,30 @@ where
         term: &TermOrVariable,
         variables: &[Variable],
         values: &[Option<EncodedTerm<R::StrId>>],
+        terms: &[Option<Term>],
         bnodes: &mut HashMap<BlankNode, BlankNode>,
         validate: impl FnOnce(&EncodedTerm<R::StrId>) -> bool,
     ) -> Result<Option<EncodedTerm<R::StrId>>, EvaluationError> {
         Ok(match term {
             TermOrVariable::Term(term) => Some(
-                self.write
-                    .encode_term(if let Term::BlankNode(bnode) = term {
-                        bnodes.entry(bnode.clone()).or_default().as_ref().into()
-                    } else {
-                        term.as_ref()
-                    })
-                    .map_err(to_eval_error)?,
+                match term {
+                    Term::BlankNode(bnode) => self
+                        .write
+                        .encode_blank_node(bnodes.entry(bnode.clone()).or_default().as_ref()),
+                    Term::Triple(triple) => self
+                        .write
+                        .encode_triple(&rename_triple_blank_nodes(triple, bnodes)),
+                    term => self.write.encode_term(term.as_ref()),
+                }
+                .map_err(to_eval_error)?,
             ),
+            TermOrVariable::Triple(triple) => {
+                if let Some(triple) = bind_triple_pattern(triple, variables, terms, bnodes) {
+                    Some(self.write.encode_triple(&triple).map_err(to_eval_error)?)
+                } else {
+                    None
+                }
+            }
             TermOrVariable::Variable(v) => {
                 if let Some(Some(term)) = variables
                     .iter()
@@ -504,7 +660,7 @@ where
         Ok(Some(EncodedQuad {
             subject: if let Some(subject) = self
                 .read
-                .get_encoded_named_or_blank_node(quad.subject.as_ref())
+                .get_encoded_subject(quad.subject.as_ref())
                 .map_err(to_eval_error)?
             {
                 subject
@@ -546,10 +702,11 @@ where
         quad: &QuadPattern,
         variables: &[Variable],
         values: &[Option<EncodedTerm<R::StrId>>],
+        terms: &[Option<Term>],
     ) -> Result<Option<EncodedQuad<R::StrId>>, EvaluationError> {
         Ok(Some(EncodedQuad {
             subject: if let Some(subject) =
-                self.encode_term_for_deletion(&quad.subject, variables, values)?
+                self.encode_term_for_deletion(&quad.subject, variables, values, terms)?
             {
                 subject
             } else {
@@ -563,7 +720,7 @@ where
                 return Ok(None);
             },
             object: if let Some(object) =
-                self.encode_term_for_deletion(&quad.object, variables, values)?
+                self.encode_term_for_deletion(&quad.object, variables, values, terms)?
             {
                 object
             } else {
@@ -588,6 +745,7 @@ where
         term: &TermOrVariable,
         variables: &[Variable],
         values: &[Option<EncodedTerm<R::StrId>>],
+        terms: &[Option<Term>],
     ) -> Result<Option<EncodedTerm<R::StrId>>, EvaluationError> {
         match term {
             TermOrVariable::Term(term) => {
@@ -612,6 +770,15 @@ where
                     None
                 },
             ),
+            TermOrVariable::Triple(triple) => {
+                if let Some(triple) =
+                    bind_triple_pattern(triple, variables, terms, &mut HashMap::new())
+                {
+                    self.read.get_encoded_triple(&triple).map_err(to_eval_error)
+                } else {
+                    Ok(None)
+                }
+            }
         }
     }
 
@@ -645,6 +812,129 @@ where
     }
 }
 
+/// Builds a quoted triple from a pattern and the solution values, returning `None` if it is not bound to a valid triple
+fn bind_triple_pattern(
+    pattern: &TriplePattern,
+    variables: &[Variable],
+    terms: &[Option<Term>],
+    bnodes: &mut HashMap<BlankNode, BlankNode>,
+) -> Option<Triple> {
+    Some(Triple {
+        subject: match bind_term_pattern(&pattern.subject, variables, terms, bnodes)? {
+            Term::NamedNode(node) => node.into(),
+            Term::BlankNode(node) => node.into(),
+            Term::Triple(triple) => triple.into(),
+            Term::Literal(_) => return None,
+        },
+        predicate: match &pattern.predicate {
+            NamedNodeOrVariable::NamedNode(node) => node.clone(),
+            NamedNodeOrVariable::Variable(v) => {
+                if let Term::NamedNode(node) = bind_variable(v, variables, terms)? {
+                    node
+                } else {
+                    return None;
+                }
+            }
+        },
+        object: bind_term_pattern(&pattern.object, variables, terms, bnodes)?,
+    })
+}
+
+fn bind_term_pattern(
+    pattern: &TermOrVariable,
+    variables: &[Variable],
+    terms: &[Option<Term>],
+    bnodes: &mut HashMap<BlankNode, BlankNode>,
+) -> Option<Term> {
+    Some(match pattern {
+        TermOrVariable::Term(Term::BlankNode(bnode)) => {
+            bnodes.entry(bnode.clone()).or_default().clone().into()
+        }
+        TermOrVariable::Term(Term::Triple(triple)) => {
+            rename_triple_blank_nodes(triple, bnodes).into()
+        }
+        TermOrVariable::Term(term) => term.clone(),
+        TermOrVariable::Variable(v) => bind_variable(v, variables, terms)?,
+        TermOrVariable::Triple(triple) => {
+            bind_triple_pattern(triple, variables, terms, bnodes)?.into()
+        }
+    })
+}
+
+fn bind_variable(
+    variable: &Variable,
+    variables: &[Variable],
+    terms: &[Option<Term>],
+) -> Option<Term> {
+    variables
+        .iter()
+        .position(|v| v == variable)
+        .and_then(|i| terms.get(i))
+        .cloned()
+        .flatten()
+}
+
+fn rename_triple_blank_nodes(
+    triple: &Triple,
+    bnodes: &mut HashMap<BlankNode, BlankNode>,
+) -> Triple {
+    Triple {
+        subject: match &triple.subject {
+            Subject::BlankNode(bnode) => bnodes.entry(bnode.clone()).or_default().clone().into(),
+            Subject::Triple(triple) => rename_triple_blank_nodes(triple, bnodes).into(),
+            subject => subject.clone(),
+        },
+        predicate: triple.predicate.clone(),
+        object: match &triple.object {
+            Term::BlankNode(bnode) => bnodes.entry(bnode.clone()).or_default().clone().into(),
+            Term::Triple(triple) => rename_triple_blank_nodes(triple, bnodes).into(),
+            object => object.clone(),
+        },
+    }
+}
+
 fn to_eval_error(e: impl Into<EvaluationError>) -> EvaluationError {
     e.into()
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::model::*;
+    use crate::sparql::{EvaluationError, Update};
+    use crate::MemoryStore;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_add_move_copy() -> Result<(), EvaluationError> {
+        let ex = |name: &str| NamedNode::new_unchecked(format!("http://example.com/{}", name));
+        let store = MemoryStore::new();
+        store.insert(Quad::new(ex("s"), ex("p"), ex("o1"), GraphName::DefaultGraph));
+        store.insert(Quad::new(ex("s"), ex("p"), ex("o2"), ex("g1")));
+
+        let update = Update::from_str(
+            "ADD DEFAULT TO GRAPH <http://example.com/g1> ; COPY SILENT GRAPH <http://example.com/g1> TO GRAPH <http://example.com/g2> ; MOVE GRAPH <http://example.com/g1> TO DEFAULT",
+        )?;
+        assert_eq!(update, Update::from_str(&update.to_string())?);
+        store.update(update)?;
+
+        assert!(!store.contains_named_graph(&ex("g1")));
+        assert_eq!(store.quads_for_pattern(None, None, None, None).count(), 4);
+        for graph_name in &[GraphName::DefaultGraph, ex("g2").into()] {
+            for object in &[ex("o1"), ex("o2")] {
+                assert!(store.contains(&Quad::new(
+                    ex("s"),
+                    ex("p"),
+                    object.clone(),
+                    graph_name.clone()
+                )));
+            }
+        }
+
+        assert!(store
+            .update("ADD GRAPH <http://example.com/g3> TO DEFAULT")
+            .is_err());
+        store.update("COPY SILENT GRAPH <http://example.com/g3> TO DEFAULT")?;
+        assert_eq!(store.quads_for_pattern(None, None, None, None).count(), 4);
+        Ok(())
+    }
+}
@@ -18,6 +18,8 @@ use std::io::Write;
 use std::iter::empty;
 use std::rc::Rc;
 
+const MAX_NUMBER_OF_NESTED_TRIPLES: usize = 128;
+
 pub fn write_xml_results(results: QueryResults, sink: impl Write) -> Result<(), EvaluationError> {
     match results {
         QueryResults::Boolean(value) => {
@@ -88,47 +90,7 @@ fn write_solutions(solutions: QuerySolutionIter, sink: impl Write) -> Result<(),
             writer
                 .write_event(Event::Start(binding_tag))
                 .map_err(map_xml_error)?;
-            match value {
-                Term::NamedNode(uri) => {
-                    writer
-                        .write_event(Event::Start(BytesStart::borrowed_name(b"uri")))
-                        .map_err(map_xml_error)?;
-                    writer
-                        .write_event(Event::Text(BytesText::from_plain_str(uri.as_str())))
-                        .map_err(map_xml_error)?;
-                    writer
-                        .write_event(Event::End(BytesEnd::borrowed(b"uri")))
-                        .map_err(map_xml_error)?;
-                }
-                Term::BlankNode(bnode) => {
-                    writer
-                        .write_event(Event::Start(BytesStart::borrowed_name(b"bnode")))
-                        .map_err(map_xml_error)?;
-                    writer
-                        .write_event(Event::Text(BytesText::from_plain_str(bnode.as_str())))
-                        .map_err(map_xml_error)?;
-                    writer
-                        .write_event(Event::End(BytesEnd::borrowed(b"bnode")))
-                        .map_err(map_xml_error)?;
-                }
-                Term::Literal(literal) => {
-                    let mut literal_tag = BytesStart::borrowed_name(b"literal");
-                    if let Some(language) = literal.language() {
-                        literal_tag.push_attribute(("xml:lang", language));
-                    } else if !literal.is_plain() {
-                        literal_tag.push_attribute(("datatype", literal.datatype().as_str()));
-                    }
-                    writer
-                        .write_event(Event::Start(literal_tag))
-                        .map_err(map_xml_error)?;
-                    writer
-                        .write_event(Event::Text(BytesText::from_plain_str(literal.value())))
-                        .map_err(map_xml_error)?;
-                    writer
-                        .write_event(Event::End(BytesEnd::borrowed(b"literal")))
-                        .map_err(map_xml_error)?;
-                }
-            }
+            write_xml_term(value.as_ref(), &mut writer).map_err(map_xml_error)?;
             writer
                 .write_event(Event::End(BytesEnd::borrowed(b"binding")))
                 .map_err(map_xml_error)?;
@@ -146,6 +108,49 @@ fn write_solutions(solutions: QuerySolutionIter, sink: impl Write) -> Result<(),
     Ok(())
 }
 
+fn write_xml_term<W: Write>(
+    term: TermRef<'_>,
+    writer: &mut Writer<W>,
+) -> Result<(), quick_xml::Error> {
+    match term {
+        TermRef::NamedNode(uri) => {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"uri")))?;
+            writer.write_event(Event::Text(BytesText::from_plain_str(uri.as_str())))?;
+            writer.write_event(Event::End(BytesEnd::borrowed(b"uri")))?;
+        }
+        TermRef::BlankNode(bnode) => {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"bnode")))?;
+            writer.write_event(Event::Text(BytesText::from_plain_str(bnode.as_str())))?;
+            writer.write_event(Event::End(BytesEnd::borrowed(b"bnode")))?;
+        }
+        TermRef::Literal(literal) => {
+            let mut literal_tag = BytesStart::borrowed_name(b"literal");
+            if let Some(language) = literal.language() {
+                literal_tag.push_attribute(("xml:lang", language));
+            } else if !literal.is_plain() {
+                literal_tag.push_attribute(("datatype", literal.datatype().as_str()));
+            }
+            writer.write_event(Event::Start(literal_tag))?;
+            writer.write_event(Event::Text(BytesText::from_plain_str(literal.value())))?;
+            writer.write_event(Event::End(BytesEnd::borrowed(b"literal")))?;
+        }
+        TermRef::Triple(triple) => {
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"triple")))?;
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"subject")))?;
+            write_xml_term(triple.subject.as_ref().into(), writer)?;
+            writer.write_event(Event::End(BytesEnd::borrowed(b"subject")))?;
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"predicate")))?;
+            write_xml_term(triple.predicate.as_ref().into(), writer)?;
+            writer.write_event(Event::End(BytesEnd::borrowed(b"predicate")))?;
+            writer.write_event(Event::Start(BytesStart::borrowed_name(b"object")))?;
+            write_xml_term(triple.object.as_ref(), writer)?;
+            writer.write_event(Event::End(BytesEnd::borrowed(b"object")))?;
+            writer.write_event(Event::End(BytesEnd::borrowed(b"triple")))?;
+        }
+    }
+    Ok(())
+}
+
 pub fn read_xml_results(source: impl BufRead + 'static) -> Result<QueryResults, io::Error> {
     enum State {
         Start,
@@ -307,6 +312,7 @@ impl<R: BufRead> Iterator for ResultsIterator<R> {
 
 impl<R: BufRead> ResultsIterator<R> {
     fn read_next(&mut self) -> Result<Option<Vec<Option<Term>>>, EvaluationError> {
+        #[derive(Clone, Copy)]
         enum State {
             Start,
             Result,
@@ -314,9 +320,14 @@ impl<R: BufRead> ResultsIterator<R> {
             Uri,
             BNode,
             Literal,
+            Triple,
+            Subject,
+            Predicate,
+            Object,
             End,
         }
-        let mut state = State::Start;
+        let mut state_stack = vec![State::Start];
+        let mut triple_stack: Vec<(Option<Term>, Option<Term>, Option<Term>)> = Vec::new();
 
         let mut new_bindings = Vec::default();
         new_bindings.resize(self.mapping.len(), None);
@@ -340,10 +351,10 @@ impl<R: BufRead> ResultsIterator<R> {
                 }
             }
             match event {
-                Event::Start(event) => match state {
-                    State::Start => {
+                Event::Start(event) => match state_stack.last() {
+                    Some(State::Start) => {
                         if event.name() == b"result" {
-                            state = State::Result;
+                            state_stack.push(State::Result);
                         } else {
                             return Err(invalid_data_error(format!(
                                 "Expecting <result>, found {}",
@@ -352,7 +363,7 @@ impl<R: BufRead> ResultsIterator<R> {
                             .into());
                         }
                     }
-                    State::Result => {
+                    Some(State::Result) => {
                         if event.name() == b"binding" {
                             match event
                                 .attributes()
@@ -371,7 +382,7 @@ impl<R: BufRead> ResultsIterator<R> {
                                     .into());
                                 }
                             }
-                            state = State::Binding;
+                            state_stack.push(State::Binding);
                         } else {
                             return Err(invalid_data_error(format!(
                                 "Expecting <binding>, found {}",
@@ -380,7 +391,10 @@ impl<R: BufRead> ResultsIterator<R> {
                             .into());
                         }
                     }
-                    State::Binding => {
+                    Some(State::Binding)
+                    | Some(State::Subject)
+                    | Some(State::Predicate)
+                    | Some(State::Object) => {
                         if term.is_some() {
                             return Err(invalid_data_error(
                                 "There is already a value for the current binding",
@@ -388,9 +402,15 @@ impl<R: BufRead> ResultsIterator<R> {
                             .into());
                         }
                         if event.name() == b"uri" {
-                            state = State::Uri;
+                            state_stack.push(State::Uri);
                         } else if event.name() == b"bnode" {
-                            state = State::BNode;
+                            state_stack.push(State::BNode);
+                        } else if event.name() == b"triple" {
+                            if triple_stack.len() == MAX_NUMBER_OF_NESTED_TRIPLES {
+                                return Err(invalid_data_error("Too many nested triples").into());
+                            }
+                            triple_stack.push((None, None, None));
+                            state_stack.push(State::Triple);
                         } else if event.name() == b"literal" {
                             for attr in event.attributes() {
                                 if let Ok(attr) = attr {
@@ -412,10 +432,25 @@ impl<R: BufRead> ResultsIterator<R> {
                                     }
                                 }
                             }
-                            state = State::Literal;
+                            state_stack.push(State::Literal);
+                        } else {
+                            return Err(invalid_data_error(format!(
+                                "Expecting <uri>, <bnode>, <literal> or <triple> found {}",
+                                self.reader.decode(event.name()).map_err(map_xml_error)?
+                            ))
+                            .into());
+                        }
+                    }
+                    Some(State::Triple) => {
+                        if event.name() == b"subject" {
+                            state_stack.push(State::Subject);
+                        } else if event.name() == b"predicate" {
+                            state_stack.push(State::Predicate);
+                        } else if event.name() == b"object" {
+                            state_stack.push(State::Object);
                         } else {
                             return Err(invalid_data_error(format!(
-                                "Expecting <uri>, <bnode> or <literal> found {}",
+                                "Expecting <subject>, <predicate> or <object> found {}",
                                 self.reader.decode(event.name()).map_err(map_xml_error)?
                             ))
                             .into());
@@ -425,8 +460,8 @@ impl<R: BufRead> ResultsIterator<R> {
                 },
                 Event::Text(event) => {
                     let data = event.unescaped().map_err(map_xml_error)?;
-                    match state {
-                        State::Uri => {
+                    match state_stack.last() {
+                        Some(State::Uri) => {
                             let iri = self.reader.decode(&data).map_err(map_xml_error)?;
                             term = Some(
                                 NamedNode::new(iri)
@@ -439,7 +474,7 @@ impl<R: BufRead> ResultsIterator<R> {
                                     .into(),
                             )
                         }
-                        State::BNode => {
+                        Some(State::BNode) => {
                             let bnode = self.reader.decode(&data).map_err(map_xml_error)?;
                             term = Some(
                                 BlankNode::new(bnode)
@@ -452,7 +487,7 @@ impl<R: BufRead> ResultsIterator<R> {
                                     .into(),
                             )
                         }
-                        State::Literal => {
+                        Some(State::Literal) => {
                             term = Some(
                                 build_literal(
                                     self.reader.decode(&data).map_err(map_xml_error)?,
@@ -471,10 +506,10 @@ impl<R: BufRead> ResultsIterator<R> {
                         }
                     }
                 }
-                Event::End(_) => match state {
-                    State::Start => state = State::End,
-                    State::Result => return Ok(Some(new_bindings)),
-                    State::Binding => {
+                Event::End(_) => match state_stack.pop() {
+                    Some(State::Start) => state_stack.push(State::End),
+                    Some(State::Result) => return Ok(Some(new_bindings)),
+                    Some(State::Binding) => {
                         if let Some(var) = &current_var {
                             new_bindings[self.mapping[var]] = term.clone()
                         } else {
@@ -483,17 +518,34 @@ impl<R: BufRead> ResultsIterator<R> {
                             );
                         }
                         term = None;
-                        state = State::Result;
                     }
-                    State::Uri | State::BNode => state = State::Binding,
-                    State::Literal => {
+                    Some(State::Literal) => {
                         if term.is_none() {
                             //We default to the empty literal
                             term = Some(build_literal("", lang.take(), datatype.take())?.into())
                         }
-                        state = State::Binding;
                     }
-                    _ => (),
+                    Some(State::Subject) => {
+                        if let Some(triple) = triple_stack.last_mut() {
+                            triple.0 = term.take();
+                        }
+                    }
+                    Some(State::Predicate) => {
+                        if let Some(triple) = triple_stack.last_mut() {
+                            triple.1 = term.take();
+                        }
+                    }
+                    Some(State::Object) => {
+                        if let Some(triple) = triple_stack.last_mut() {
+                            triple.2 = term.take();
+                        }
+                    }
+                    Some(State::Triple) => {
+                        if let Some((subject, predicate, object)) = triple_stack.pop() {
+                            term = Some(build_triple(subject, predicate, object)?.into());
+                        }
+                    }
+                    Some(State::Uri) | Some(State::BNode) | Some(State::End) | None => (),
                 },
                 Event::Eof => return Ok(None),
                 _ => (),
@@ -518,6 +570,41 @@ fn build_literal(
     }
 }
 
+fn build_triple(
+    subject: Option<Term>,
+    predicate: Option<Term>,
+    object: Option<Term>,
+) -> Result<Triple, EvaluationError> {
+    Ok(Triple::new(
+        match subject {
+            Some(Term::NamedNode(node)) => Subject::from(node),
+            Some(Term::BlankNode(node)) => node.into(),
+            Some(Term::Triple(triple)) => triple.into(),
+            Some(Term::Literal(_)) => {
+                return Err(invalid_data_error(
+                    "The <subject> of a <triple> should not be a literal",
+                )
+                .into())
+            }
+            None => {
+                return Err(invalid_data_error("A <triple> should have a <subject>").into());
+            }
+        },
+        match predicate {
+            Some(Term::NamedNode(node)) => node,
+            Some(_) => {
+                return Err(
+                    invalid_data_error("The <predicate> of a <triple> should be an IRI").into(),
+                )
+            }
+            None => {
+                return Err(invalid_data_error("A <triple> should have a <predicate>").into());
+            }
+        },
+        object.ok_or_else(|| invalid_data_error("A <triple> should have an <object>"))?,
+    ))
+}
+
 fn map_xml_error(error: quick_xml::Error) -> io::Error {
     match error {
         quick_xml::Error::Io(error) => error,
@@ -11,14 +11,15 @@ use std::mem::size_of;
 type EncodedTerm = crate::store::numeric_encoder::EncodedTerm<StrHash>;
 type EncodedQuad = crate::store::numeric_encoder::EncodedQuad<StrHash>;
 
-pub const LATEST_STORAGE_VERSION: u64 = 1;
+pub const LATEST_STORAGE_VERSION: u64 = 2;
 pub const WRITTEN_TERM_MAX_SIZE: usize = size_of::<u8>() + 2 * size_of::<StrHash>();
 
 // Encoded term type blocks
 // 1-7: usual named nodes (except prefixes c.f. later)
 // 8-15: blank nodes
 // 16-47: literals
-// 48-64: future use
+// 48: quoted triples
+// 49-64: future use
 // 64-127: default named node prefixes
 // 128-255: custom named node prefixes
 const TYPE_NAMED_NODE_ID: u8 = 1;
@@ -50,6 +51,7 @@ const TYPE_G_MONTH_LITERAL: u8 = 41;
 const TYPE_DURATION_LITERAL: u8 = 42;
 const TYPE_YEAR_MONTH_DURATION_LITERAL: u8 = 43;
 const TYPE_DAY_TIME_DURATION_LITERAL: u8 = 44;
+const TYPE_TRIPLE: u8 = 48;
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone, Hash)]
 #[repr(transparent)]
@@ -431,6 +433,13 @@ impl<R: Read> TermReader for R {
                     DayTimeDuration::from_be_bytes(buffer),
                 ))
             }
+            TYPE_TRIPLE => {
+                let mut buffer = [0; 16];
+                self.read_exact(&mut buffer)?;
+                Ok(EncodedTerm::Triple {
+                    triple_id: StrHash::from_be_bytes(buffer),
+                })
+            }
             _ => Err(invalid_data_error("the term buffer has an invalid type id")),
         }
     }
@@ -656,6 +665,10 @@ pub fn write_term(sink: &mut Vec<u8>, term: EncodedTerm) {
             sink.push(TYPE_DAY_TIME_DURATION_LITERAL);
             sink.extend_from_slice(&value.to_be_bytes())
         }
+        EncodedTerm::Triple { triple_id } => {
+            sink.push(TYPE_TRIPLE);
+            sink.extend_from_slice(&triple_id.to_be_bytes());
+        }
     }
 }
 
@@ -759,6 +772,22 @@ mod tests {
                 NamedNode::new_unchecked("http://foo.com"),
             )
             .into(),
+            Triple::new(
+                NamedNode::new_unchecked("http://foo.com"),
+                NamedNode::new_unchecked("http://bar.com"),
+                Literal::from(true),
+            )
+            .into(),
+            Triple::new(
+                Triple::new(
+                    BlankNode::default(),
+                    NamedNode::new_unchecked("http://bar.com"),
+                    Literal::new_language_tagged_literal_unchecked("foo \"bar\"", "fr"),
+                ),
+                NamedNode::new_unchecked("http://bar.com"),
+                Literal::new_typed_literal("2020", xsd::G_YEAR),
+            )
+            .into(),
         ];
         for term in terms {
             let encoded = store.encode_term(term.as_ref()).unwrap();
@@ -0,0 +1,532 @@
+//! Change data capture of the store writes.
+//!
+//! Each committed write that changes the asserted quads of a store is published as a [`ChangeBatch`]
+//! to the [`ChangeSubscription`]s of the store, in commit order.
+
+#[cfg(any(feature = "rocksdb", test))]
+use crate::error::invalid_data_error;
+use crate::model::Quad;
+use crate::store::numeric_encoder::{EncodedQuad, StrId};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::io::Write;
+#[cfg(any(feature = "rocksdb", test))]
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// The quads inserted and removed by a committed write.
+///
+/// A quad inserted then removed by the same write, or the opposite, is not part of the batch.
+///
+/// Usage example:
+/// ```
+/// use oxigraph::MemoryStore;
+/// use oxigraph::model::*;
+///
+/// let store = MemoryStore::new();
+/// let mut changes = store.subscribe();
+///
+/// let ex = NamedNode::new("http://example.com")?;
+/// let quad = Quad::new(ex.clone(), ex.clone(), ex.clone(), None);
+/// store.insert(quad.clone());
+///
+/// let batch = changes.try_recv().unwrap();
+/// assert_eq!(batch.sequence_number(), 1);
+/// assert_eq!(batch.inserted(), &[quad]);
+/// assert!(batch.removed().is_empty());
+///
+/// let mut patch = Vec::new();
+/// batch.write_patch(&mut patch)?;
+/// assert_eq!(
+///     patch,
+///     b"H sequence \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\nTX .\nA <http://example.com> <http://example.com> <http://example.com> .\nTC .\n"
+/// );
+/// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ChangeBatch {
+    sequence_number: u64,
+    inserted: Vec<Quad>,
+    removed: Vec<Quad>,
+    resync: bool,
+}
+
+impl ChangeBatch {
+    pub(crate) fn new(sequence_number: u64, inserted: Vec<Quad>, removed: Vec<Quad>) -> Self {
+        Self {
+            sequence_number,
+            inserted,
+            removed,
+            resync: false,
+        }
+    }
+
+    /// A batch standing for a write whose changes are not known
+    #[cfg(any(feature = "rocksdb", test))]
+    pub(crate) fn resync(sequence_number: u64) -> Self {
+        Self {
+            sequence_number,
+            inserted: Vec::new(),
+            removed: Vec::new(),
+            resync: true,
+        }
+    }
+
+    /// The position of the batch in the store commit order.
+    ///
+    /// The first batch has the number 1 and each following batch the number of the previous one plus 1.
+    pub fn sequence_number(&self) -> u64 {
+        self.sequence_number
+    }
+
+    /// The quads inserted by the write
+    pub fn inserted(&self) -> &[Quad] {
+        &self.inserted
+    }
+
+    /// The quads removed by the write
+    pub fn removed(&self) -> &[Quad] {
+        &self.removed
+    }
+
+    /// If the batch stands for a write whose changes are not known, like a [bulk load](super::rocksdb::RocksDbStore::bulk_loader()).
+    ///
+    /// Such a batch has no inserted or removed quads: the consumers mirroring the store content should read it again.
+    pub fn requires_resync(&self) -> bool {
+        self.resync
+    }
+
+    /// Writes the batch as a [RDF Patch](https://afs.github.io/rdf-patch/) transaction.
+    ///
+    /// The sequence number is written in a `sequence` header, the [resync requirement](ChangeBatch::requires_resync()) in a `resync` header
+    /// and the quads are written using the [N-Quads](https://www.w3.org/TR/n-quads/) syntax.
+    pub fn write_patch(&self, mut writer: impl Write) -> Result<(), io::Error> {
+        writeln!(
+            writer,
+            "H sequence \"{}\"^^<http://www.w3.org/2001/XMLSchema#integer> .",
+            self.sequence_number
+        )?;
+        if self.resync {
+            writeln!(
+                writer,
+                "H resync \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> ."
+            )?;
+        }
+        writeln!(writer, "TX .")?;
+        self.write_body(&mut writer)?;
+        writeln!(writer, "TC .")
+    }
+
+    /// Writes the removals then the insertions, one per line
+    fn write_body(&self, writer: impl Write) -> Result<(), io::Error> {
+        write_quads(writer, &self.inserted, &self.removed)
+    }
+
+    /// Encodes the quads of a batch in order to persist them
+    #[cfg(any(feature = "rocksdb", test))]
+    pub(crate) fn encode_body(inserted: &[Quad], removed: &[Quad]) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        // Writing to a Vec never fails
+        let _ = write_quads(&mut buffer, inserted, removed);
+        buffer
+    }
+
+    /// Encodes a [resync batch](ChangeBatch::requires_resync()) in order to persist it
+    #[cfg(any(feature = "rocksdb", test))]
+    pub(crate) fn encode_resync_body() -> Vec<u8> {
+        format!("{}\n", RESYNC_LINE).into_bytes()
+    }
+
+    /// Decodes a batch persisted with [`encode_body`](ChangeBatch::encode_body()) or [`encode_resync_body`](ChangeBatch::encode_resync_body())
+    #[cfg(any(feature = "rocksdb", test))]
+    pub(crate) fn decode_body(sequence_number: u64, body: &[u8]) -> Result<Self, io::Error> {
+        let mut batch = Self::new(sequence_number, Vec::new(), Vec::new());
+        for line in std::str::from_utf8(body)
+            .map_err(invalid_data_error)?
+            .lines()
+        {
+            if line == RESYNC_LINE {
+                batch.resync = true;
+                continue;
+            }
+            let quad = line
+                .get(2..)
+                .and_then(|quad| quad.strip_suffix(" ."))
+                .ok_or_else(|| invalid_data_error(format!("Invalid change line: {}", line)))?;
+            let quad = Quad::from_str(quad).map_err(invalid_data_error)?;
+            if line.starts_with('A') {
+                batch.inserted.push(quad);
+            } else {
+                batch.removed.push(quad);
+            }
+        }
+        Ok(batch)
+    }
+}
+
+/// The change log line of a [resync batch](ChangeBatch::requires_resync())
+#[cfg(any(feature = "rocksdb", test))]
+const RESYNC_LINE: &str = "R .";
+
+fn write_quads(
+    mut writer: impl Write,
+    inserted: &[Quad],
+    removed: &[Quad],
+) -> Result<(), io::Error> {
+    for quad in removed {
+        writeln!(writer, "D {} .", quad)?;
+    }
+    for quad in inserted {
+        writeln!(writer, "A {} .", quad)?;
+    }
+    Ok(())
+}
+
+/// A subscription to the changes of a store returned by methods like [`MemoryStore::subscribe`](super::MemoryStore::subscribe()).
+///
+/// It receives the [`ChangeBatch`]es committed after its creation in commit order.
+/// The batches are buffered in memory until they are received: a subscription that is not read should be dropped.
+///
+/// It is also an [`Iterator`] that blocks until the next batch is committed
+/// and ends when all the clones of the store are dropped.
+pub struct ChangeSubscription {
+    replay: VecDeque<ChangeBatch>,
+    receiver: Receiver<ChangeBatch>,
+}
+
+impl ChangeSubscription {
+    /// Waits for the next batch.
+    ///
+    /// Returns `None` if all the clones of the store have been dropped.
+    pub fn recv(&mut self) -> Option<ChangeBatch> {
+        if let Some(batch) = self.replay.pop_front() {
+            return Some(batch);
+        }
+        self.receiver.recv().ok()
+    }
+
+    /// Returns the next batch if it has already been committed.
+    pub fn try_recv(&mut self) -> Option<ChangeBatch> {
+        if let Some(batch) = self.replay.pop_front() {
+            return Some(batch);
+        }
+        self.receiver.try_recv().ok()
+    }
+
+    /// Waits for the next batch at most `timeout`.
+    ///
+    /// Returns `None` if the timeout is reached or if all the clones of the store have been dropped.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Option<ChangeBatch> {
+        if let Some(batch) = self.replay.pop_front() {
+            return Some(batch);
+        }
+        match self.receiver.recv_timeout(timeout) {
+            Ok(batch) => Some(batch),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+}
+
+impl Iterator for ChangeSubscription {
+    type Item = ChangeBatch;
+
+    fn next(&mut self) -> Option<ChangeBatch> {
+        self.recv()
+    }
+}
+
+/// Assigns the sequence numbers and dispatches the batches to the subscriptions
+pub(crate) struct ChangeFeed {
+    state: Mutex<ChangeFeedState>,
+    /// If there are subscriptions, possibly already dropped
+    watched: AtomicBool,
+}
+
+struct ChangeFeedState {
+    last_sequence_number: u64,
+    subscribers: Vec<Sender<ChangeBatch>>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl ChangeFeed {
+    pub fn new(last_sequence_number: u64) -> Self {
+        Self {
+            state: Mutex::new(ChangeFeedState {
+                last_sequence_number,
+                subscribers: Vec::new(),
+            }),
+            watched: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns if the changes should be captured in order to be published
+    pub fn is_watched(&self) -> bool {
+        self.watched.load(Ordering::Relaxed)
+    }
+
+    #[cfg(any(feature = "rocksdb", test))]
+    pub fn last_sequence_number(&self) -> u64 {
+        self.state().last_sequence_number
+    }
+
+    /// Sets the last sequence number after some writes done by an other process
+    #[cfg(feature = "rocksdb")]
+    pub fn set_last_sequence_number(&self, last_sequence_number: u64) {
+        self.state().last_sequence_number = last_sequence_number;
+    }
+
+    pub fn subscribe(&self) -> ChangeSubscription {
+        let (sender, receiver) = channel();
+        self.state().subscribers.push(sender);
+        self.watched.store(true, Ordering::Relaxed);
+        ChangeSubscription {
+            replay: VecDeque::new(),
+            receiver,
+        }
+    }
+
+    /// Subscribes to the changes after having built the batches to replay from the last sequence number
+    ///
+    /// No write could be committed in between.
+    #[cfg(feature = "rocksdb")]
+    pub fn subscribe_with_replay<E>(
+        &self,
+        replay: impl FnOnce(u64) -> Result<VecDeque<ChangeBatch>, E>,
+    ) -> Result<ChangeSubscription, E> {
+        let mut state = self.state();
+        let replay = replay(state.last_sequence_number)?;
+        let (sender, receiver) = channel();
+        state.subscribers.push(sender);
+        self.watched.store(true, Ordering::Relaxed);
+        Ok(ChangeSubscription { replay, receiver })
+    }
+
+    /// Executes the `write` and publishes the inserted and removed quads it returns along its output.
+    ///
+    /// The `write` gets the sequence number of the batch, to use if the write changes something.
+    /// The writes done using this method are serialized in order to publish them in commit order.
+    pub fn commit<T, E>(
+        &self,
+        write: impl FnOnce(u64) -> Result<(T, Vec<Quad>, Vec<Quad>), E>,
+    ) -> Result<T, E> {
+        let mut state = self.state();
+        let sequence_number = state.last_sequence_number + 1;
+        let (output, inserted, removed) = write(sequence_number)?;
+        if inserted.is_empty() && removed.is_empty() {
+            return Ok(output);
+        }
+        self.publish(
+            &mut state,
+            &ChangeBatch::new(sequence_number, inserted, removed),
+        );
+        Ok(output)
+    }
+
+    /// Executes the `write` of changes that are not known and publishes a [resync batch](ChangeBatch::requires_resync()).
+    ///
+    /// The `write` gets the sequence number of the batch in order to persist it.
+    #[cfg(any(feature = "rocksdb", test))]
+    pub fn commit_resync<T, E>(&self, write: impl FnOnce(u64) -> Result<T, E>) -> Result<T, E> {
+        let mut state = self.state();
+        let sequence_number = state.last_sequence_number + 1;
+        let output = write(sequence_number)?;
+        self.publish(&mut state, &ChangeBatch::resync(sequence_number));
+        Ok(output)
+    }
+
+    fn publish(&self, state: &mut ChangeFeedState, batch: &ChangeBatch) {
+        state.last_sequence_number = batch.sequence_number;
+        state
+            .subscribers
+            .retain(|subscriber| subscriber.send(batch.clone()).is_ok());
+        self.watched
+            .store(!state.subscribers.is_empty(), Ordering::Relaxed);
+    }
+
+    #[allow(clippy::expect_used)]
+    fn state(&self) -> MutexGuard<'_, ChangeFeedState> {
+        self.state
+            .lock()
+            .expect("the change feed mutex has been poisoned because of a panic")
+    }
+}
+
+/// Computes the net changes of a write from the insertions and removals it does
+pub(crate) struct ChangeCollector<I: StrId> {
+    quads: Vec<EncodedQuad<I>>,
+    states: HashMap<EncodedQuad<I>, QuadState>,
+}
+
+#[derive(Clone, Copy)]
+struct QuadState {
+    was_asserted: bool,
+    is_asserted: bool,
+}
+
+impl<I: StrId> Default for ChangeCollector<I> {
+    fn default() -> Self {
+        Self {
+            quads: Vec::new(),
+            states: HashMap::new(),
+        }
+    }
+}
+
+impl<I: StrId> ChangeCollector<I> {
+    /// Records the insertion of a quad
+    ///
+    /// `was_asserted` is only called the first time the quad is recorded
+    /// and should return if the quad was asserted in the store before the write.
+    pub fn record_insertion<E>(
+        &mut self,
+        quad: &EncodedQuad<I>,
+        was_asserted: impl FnOnce() -> Result<bool, E>,
+    ) -> Result<(), E> {
+        self.record(quad, true, was_asserted)
+    }
+
+    /// Records the removal of a quad
+    ///
+    /// `was_asserted` is only called the first time the quad is recorded
+    /// and should return if the quad was asserted in the store before the write.
+    pub fn record_removal<E>(
+        &mut self,
+        quad: &EncodedQuad<I>,
+        was_asserted: impl FnOnce() -> Result<bool, E>,
+    ) -> Result<(), E> {
+        self.record(quad, false, was_asserted)
+    }
+
+    fn record<E>(
+        &mut self,
+        quad: &EncodedQuad<I>,
+        is_asserted: bool,
+        was_asserted: impl FnOnce() -> Result<bool, E>,
+    ) -> Result<(), E> {
+        match self.states.entry(*quad) {
+            Entry::Occupied(mut entry) => entry.get_mut().is_asserted = is_asserted,
+            Entry::Vacant(entry) => {
+                entry.insert(QuadState {
+                    was_asserted: was_asserted()?,
+                    is_asserted,
+                });
+                self.quads.push(*quad);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the inserted and the removed quads in the order of their first write and resets the collector
+    pub fn take_changes(&mut self) -> (Vec<EncodedQuad<I>>, Vec<EncodedQuad<I>>) {
+        let mut inserted = Vec::new();
+        let mut removed = Vec::new();
+        for quad in self.quads.drain(..) {
+            if let Some(state) = self.states.get(&quad) {
+                match (state.was_asserted, state.is_asserted) {
+                    (false, true) => inserted.push(quad),
+                    (true, false) => removed.push(quad),
+                    _ => (),
+                }
+            }
+        }
+        self.states.clear();
+        (inserted, removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::*;
+    use crate::store::numeric_encoder::EncodedTerm;
+    use lasso::LargeSpur;
+    use std::convert::Infallible;
+
+    fn quad(id: i64) -> EncodedQuad<LargeSpur> {
+        EncodedQuad::new(
+            EncodedTerm::IntegerLiteral(id),
+            EncodedTerm::IntegerLiteral(id),
+            EncodedTerm::IntegerLiteral(id),
+            EncodedTerm::DefaultGraph,
+        )
+    }
+
+    #[test]
+    fn test_collector_net_changes() -> Result<(), Infallible> {
+        let mut collector = ChangeCollector::default();
+        // Inserted then removed
+        collector.record_insertion(&quad(1), || Ok::<_, Infallible>(false))?;
+        collector.record_removal(&quad(1), || Ok::<_, Infallible>(true))?;
+        // Removed then inserted
+        collector.record_removal(&quad(2), || Ok::<_, Infallible>(true))?;
+        collector.record_insertion(&quad(2), || Ok::<_, Infallible>(false))?;
+        // Already there
+        collector.record_insertion(&quad(3), || Ok::<_, Infallible>(true))?;
+        // Inserted twice
+        collector.record_insertion(&quad(4), || Ok::<_, Infallible>(false))?;
+        collector.record_insertion(&quad(4), || Ok::<_, Infallible>(true))?;
+        // Removed
+        collector.record_removal(&quad(5), || Ok::<_, Infallible>(true))?;
+        let (inserted, removed) = collector.take_changes();
+        assert_eq!(inserted, vec![quad(4)]);
+        assert_eq!(removed, vec![quad(5)]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_body_encoding() -> Result<(), io::Error> {
+        let ex = NamedNode::new_unchecked("http://example.com");
+        let batch = ChangeBatch::new(
+            12,
+            vec![Quad::new(
+                BlankNode::new_unchecked("b"),
+                ex.clone(),
+                Literal::new_language_tagged_literal_unchecked("foo\n \" .", "en"),
+                ex.clone(),
+            )],
+            vec![Quad::new(ex.clone(), ex.clone(), ex, None)],
+        );
+        assert_eq!(
+            ChangeBatch::decode_body(
+                12,
+                &ChangeBatch::encode_body(batch.inserted(), batch.removed())
+            )?,
+            batch
+        );
+        assert_eq!(
+            ChangeBatch::decode_body(13, &ChangeBatch::encode_resync_body())?,
+            ChangeBatch::resync(13)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_resync_batch() -> Result<(), io::Error> {
+        let feed = ChangeFeed::new(4);
+        let mut subscription = feed.subscribe();
+        feed.commit_resync(|sequence_number| {
+            assert_eq!(sequence_number, 5);
+            Ok::<_, io::Error>(())
+        })?;
+        assert_eq!(feed.last_sequence_number(), 5);
+        let batch = subscription.try_recv().unwrap();
+        assert!(batch.requires_resync());
+        assert!(batch.inserted().is_empty() && batch.removed().is_empty());
+        let mut patch = Vec::new();
+        batch.write_patch(&mut patch)?;
+        assert_eq!(
+            String::from_utf8(patch).unwrap(),
+            "H sequence \"5\"^^<http://www.w3.org/2001/XMLSchema#integer> .\nH resync \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> .\nTX .\nTC .\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,673 @@
+//! Forward-chaining materialization of the [RDFS](https://www.w3.org/TR/rdf11-mt/#rdfs-entailment)
+//! and [OWL 2 RL](https://www.w3.org/TR/owl2-profiles/#Reasoning_in_OWL_2_RL_and_RDF_Graphs_using_Rules) entailments.
+//!
+//! The inferred quads are written into the store next to the asserted ones and flagged as inferred.
+//! They are maintained incrementally:
+//! * the insertions are propagated using a semi-naive evaluation of the rules,
+//! * the removals are propagated using the "delete and rederive" algorithm:
+//!   the inferred quads that might depend on the removed ones are deleted and then the ones still
+//!   derivable from the remaining quads are added back.
+//!
+//! The rules are only applied to quads in the same graph.
+
+#[cfg(any(feature = "rocksdb", feature = "sled"))]
+use crate::error::invalid_data_error;
+use crate::model::vocab::{rdf, rdfs};
+use crate::model::NamedNodeRef;
+use crate::store::numeric_encoder::{EncodedQuad, EncodedTerm, StrId};
+use crate::store::ReadableEncodedStore;
+use std::collections::{HashMap, HashSet};
+#[cfg(any(feature = "rocksdb", feature = "sled"))]
+use std::io;
+
+/// An entailment regime used to materialize inferred quads in a store.
+///
+/// The inferred quads are returned by the quad pattern lookups and by the SPARQL queries
+/// but are not written by the store dump methods.
+///
+/// Usage example:
+/// ```
+/// use oxigraph::MemoryStore;
+/// use oxigraph::model::*;
+/// use oxigraph::model::vocab::{rdf, rdfs};
+/// use oxigraph::store::EntailmentRegime;
+///
+/// let store = MemoryStore::new();
+/// store.set_entailment_regime(Some(EntailmentRegime::Rdfs));
+///
+/// let person = NamedNodeRef::new("http://example.com/Person")?;
+/// let agent = NamedNodeRef::new("http://example.com/Agent")?;
+/// let alice = NamedNodeRef::new("http://example.com/alice")?;
+/// store.insert(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, None));
+/// store.insert(QuadRef::new(alice, rdf::TYPE, person, None));
+///
+/// assert!(store.contains(QuadRef::new(alice, rdf::TYPE, agent, None)));
+/// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum EntailmentRegime {
+    /// The [RDFS entailment rules](https://www.w3.org/TR/rdf11-mt/#patterns-of-rdfs-entailment-informative)
+    /// about domains, ranges, sub-properties and sub-classes (rdfs2, rdfs3, rdfs5, rdfs7, rdfs9 and rdfs11).
+    ///
+    /// The axiomatic triples and the rules that only produce trivial types like `rdfs:Resource` are not applied.
+    Rdfs,
+    /// The RDFS rules extended with the [OWL 2 RL rules](https://www.w3.org/TR/owl2-profiles/#Reasoning_in_OWL_2_RL_and_RDF_Graphs_using_Rules)
+    /// about equality, property characteristics, equivalences and `owl:hasValue`, `owl:someValuesFrom` and `owl:allValuesFrom` restrictions.
+    ///
+    /// The rules based on RDF lists (`owl:intersectionOf`, `owl:unionOf`, `owl:propertyChainAxiom`, `owl:hasKey`...)
+    /// and the rules detecting inconsistencies are not applied.
+    Owl2Rl,
+}
+
+/// Encodes an entailment regime in order to persist it
+#[cfg(any(feature = "rocksdb", feature = "sled"))]
+pub(crate) fn encode_entailment_regime(regime: Option<EntailmentRegime>) -> u8 {
+    match regime {
+        None => 0,
+        Some(EntailmentRegime::Rdfs) => 1,
+        Some(EntailmentRegime::Owl2Rl) => 2,
+    }
+}
+
+#[cfg(any(feature = "rocksdb", feature = "sled"))]
+pub(crate) fn decode_entailment_regime(value: u8) -> Result<Option<EntailmentRegime>, io::Error> {
+    match value {
+        0 => Ok(None),
+        1 => Ok(Some(EntailmentRegime::Rdfs)),
+        2 => Ok(Some(EntailmentRegime::Owl2Rl)),
+        _ => Err(invalid_data_error(format!(
+            "Unexpected entailment regime identifier: {}",
+            value
+        ))),
+    }
+}
+
+/// A store able to keep track of the quads inferred by an entailment regime
+///
+/// The writes done using this trait should not be recorded as [`QuadChanges`].
+pub(crate) trait InferenceStore: ReadableEncodedStore {
+    /// Encodes a vocabulary term used by the rules
+    fn encode_vocabulary(
+        &self,
+        node: NamedNodeRef<'static>,
+    ) -> Result<EncodedTerm<Self::StrId>, Self::Error>;
+
+    fn is_inferred(&self, quad: &EncodedQuad<Self::StrId>) -> Result<bool, Self::Error>;
+
+    fn inferred_quads(&self) -> Result<Vec<EncodedQuad<Self::StrId>>, Self::Error>;
+
+    /// Inserts quads and flags them as inferred
+    fn insert_inferred(&self, quads: &[EncodedQuad<Self::StrId>]) -> Result<(), Self::Error>;
+
+    /// Removes quads and their inferred flags
+    fn remove_inferred(&self, quads: &[EncodedQuad<Self::StrId>]) -> Result<(), Self::Error>;
+
+    /// Removes the inferred flags of some quads without removing the quads
+    fn unmark_inferred(&self, quads: &[EncodedQuad<Self::StrId>]) -> Result<(), Self::Error>;
+}
+
+/// The asserted quads insertions and removals that have not been propagated yet to the inferred quads
+pub(crate) struct QuadChanges<I: StrId> {
+    inserted: Vec<EncodedQuad<I>>,
+    removed: Vec<EncodedQuad<I>>,
+}
+
+impl<I: StrId> Default for QuadChanges<I> {
+    fn default() -> Self {
+        Self {
+            inserted: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+impl<I: StrId> QuadChanges<I> {
+    pub fn record_insertion(&mut self, quad: &EncodedQuad<I>) {
+        self.inserted.push(*quad)
+    }
+
+    pub fn record_removal(&mut self, quad: &EncodedQuad<I>) {
+        self.removed.push(*quad)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inserted.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Above this number of removed quads the inferred quads are fully materialized again
+const MAX_INCREMENTAL_REMOVALS: usize = 1024;
+
+/// Updates the inferred quads after some changes of the asserted quads
+pub(crate) fn propagate_changes<S: InferenceStore>(
+    store: &S,
+    regime: EntailmentRegime,
+    changes: QuadChanges<S::StrId>,
+) -> Result<(), S::Error> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let reasoner = Reasoner::new(store, regime)?;
+
+    // The changes contain all the writes, even the ones that did not change anything,
+    // and a quad might have been inserted and then removed
+    let mut inserted = Vec::new();
+    let mut seen = HashSet::new();
+    for quad in changes.inserted {
+        if seen.insert(quad) && reasoner.contains(&quad)? {
+            inserted.push(quad);
+        }
+    }
+    let mut removed = HashSet::new();
+    for quad in changes.removed {
+        if !reasoner.contains(&quad)? {
+            removed.insert(quad);
+        }
+    }
+
+    // The inserted quads are now asserted
+    store.unmark_inferred(&inserted)?;
+
+    if removed.len() > MAX_INCREMENTAL_REMOVALS {
+        remove_inferred_quads(store)?;
+        return materialize(store, regime);
+    }
+    if !removed.is_empty() {
+        reasoner.propagate_removals(&removed)?;
+    }
+    reasoner.saturate(inserted)
+}
+
+/// Computes all the quads inferred from the store content
+pub(crate) fn materialize<S: InferenceStore>(
+    store: &S,
+    regime: EntailmentRegime,
+) -> Result<(), S::Error> {
+    let quads = store
+        .encoded_quads_for_pattern(None, None, None, None)
+        .collect::<Result<Vec<_>, _>>()?;
+    Reasoner::new(store, regime)?.saturate(quads)
+}
+
+/// Removes all the inferred quads from the store
+pub(crate) fn remove_inferred_quads<S: InferenceStore>(store: &S) -> Result<(), S::Error> {
+    store.remove_inferred(&store.inferred_quads()?)
+}
+
+/// A rule term as declared in the rule tables
+#[derive(Clone, Copy)]
+enum DeclaredTerm {
+    Variable(usize),
+    Constant(NamedNodeRef<'static>),
+}
+
+struct DeclaredRule {
+    body: &'static [[DeclaredTerm; 3]],
+    head: [DeclaredTerm; 3],
+    /// Variables that should be bound to different terms
+    different: Option<(usize, usize)>,
+}
+
+const fn rule(body: &'static [[DeclaredTerm; 3]], head: [DeclaredTerm; 3]) -> DeclaredRule {
+    DeclaredRule {
+        body,
+        head,
+        different: None,
+    }
+}
+
+const VARIABLES_COUNT: usize = 8;
+const P: DeclaredTerm = DeclaredTerm::Variable(0);
+const Q: DeclaredTerm = DeclaredTerm::Variable(1);
+const X: DeclaredTerm = DeclaredTerm::Variable(2);
+const Y: DeclaredTerm = DeclaredTerm::Variable(3);
+const Z: DeclaredTerm = DeclaredTerm::Variable(4);
+const U: DeclaredTerm = DeclaredTerm::Variable(5);
+const C: DeclaredTerm = DeclaredTerm::Variable(6);
+const D: DeclaredTerm = DeclaredTerm::Variable(7);
+
+const TYPE: DeclaredTerm = DeclaredTerm::Constant(rdf::TYPE);
+const DOMAIN: DeclaredTerm = DeclaredTerm::Constant(rdfs::DOMAIN);
+const RANGE: DeclaredTerm = DeclaredTerm::Constant(rdfs::RANGE);
+const SUB_CLASS_OF: DeclaredTerm = DeclaredTerm::Constant(rdfs::SUB_CLASS_OF);
+const SUB_PROPERTY_OF: DeclaredTerm = DeclaredTerm::Constant(rdfs::SUB_PROPERTY_OF);
+const ALL_VALUES_FROM: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#allValuesFrom",
+));
+const EQUIVALENT_CLASS: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#equivalentClass",
+));
+const EQUIVALENT_PROPERTY: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#equivalentProperty",
+));
+const FUNCTIONAL_PROPERTY: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#FunctionalProperty",
+));
+const HAS_VALUE: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#hasValue",
+));
+const INVERSE_FUNCTIONAL_PROPERTY: DeclaredTerm = DeclaredTerm::Constant(
+    NamedNodeRef::new_unchecked("http://www.w3.org/2002/07/owl#InverseFunctionalProperty"),
+);
+const INVERSE_OF: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#inverseOf",
+));
+const ON_PROPERTY: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#onProperty",
+));
+const SAME_AS: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#sameAs",
+));
+const SOME_VALUES_FROM: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#someValuesFrom",
+));
+const SYMMETRIC_PROPERTY: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#SymmetricProperty",
+));
+const TRANSITIVE_PROPERTY: DeclaredTerm = DeclaredTerm::Constant(NamedNodeRef::new_unchecked(
+    "http://www.w3.org/2002/07/owl#TransitiveProperty",
+));
+
+const RDFS_RULES: &[DeclaredRule] = &[
+    // rdfs2
+    rule(&[[P, DOMAIN, C], [X, P, Y]], [X, TYPE, C]),
+    // rdfs3
+    rule(&[[P, RANGE, C], [X, P, Y]], [Y, TYPE, C]),
+    // rdfs5
+    rule(
+        &[[P, SUB_PROPERTY_OF, Q], [Q, SUB_PROPERTY_OF, U]],
+        [P, SUB_PROPERTY_OF, U],
+    ),
+    // rdfs7
+    rule(&[[P, SUB_PROPERTY_OF, Q], [X, P, Y]], [X, Q, Y]),
+    // rdfs9
+    rule(&[[C, SUB_CLASS_OF, D], [X, TYPE, C]], [X, TYPE, D]),
+    // rdfs11
+    rule(
+        &[[C, SUB_CLASS_OF, D], [D, SUB_CLASS_OF, U]],
+        [C, SUB_CLASS_OF, U],
+    ),
+];
+
+const OWL2_RL_RULES: &[DeclaredRule] = &[
+    // eq-sym
+    rule(&[[X, SAME_AS, Y]], [Y, SAME_AS, X]),
+    // eq-trans
+    rule(&[[X, SAME_AS, Y], [Y, SAME_AS, Z]], [X, SAME_AS, Z]),
+    // eq-rep-s
+    rule(&[[X, SAME_AS, U], [X, P, Y]], [U, P, Y]),
+    // eq-rep-p
+    rule(&[[P, SAME_AS, Q], [X, P, Y]], [X, Q, Y]),
+    // eq-rep-o
+    rule(&[[Y, SAME_AS, U], [X, P, Y]], [X, P, U]),
+    // prp-fp
+    DeclaredRule {
+        body: &[[P, TYPE, FUNCTIONAL_PROPERTY], [X, P, Y], [X, P, Z]],
+        head: [Y, SAME_AS, Z],
+        different: Some((3, 4)),
+    },
+    // prp-ifp
+    DeclaredRule {
+        body: &[[P, TYPE, INVERSE_FUNCTIONAL_PROPERTY], [X, P, Y], [Z, P, Y]],
+        head: [X, SAME_AS, Z],
+        different: Some((2, 4)),
+    },
+    // prp-symp
+    rule(&[[P, TYPE, SYMMETRIC_PROPERTY], [X, P, Y]], [Y, P, X]),
+    // prp-trp
+    rule(
+        &[[P, TYPE, TRANSITIVE_PROPERTY], [X, P, Y], [Y, P, Z]],
+        [X, P, Z],
+    ),
+    // prp-eqp1
+    rule(&[[P, EQUIVALENT_PROPERTY, Q], [X, P, Y]], [X, Q, Y]),
+    // prp-eqp2
+    rule(&[[P, EQUIVALENT_PROPERTY, Q], [X, Q, Y]], [X, P, Y]),
+    // prp-inv1
+    rule(&[[P, INVERSE_OF, Q], [X, P, Y]], [Y, Q, X]),
+    // prp-inv2
+    rule(&[[P, INVERSE_OF, Q], [X, Q, Y]], [Y, P, X]),
+    // cls-hv1
+    rule(
+        &[[C, HAS_VALUE, Y], [C, ON_PROPERTY, P], [X, TYPE, C]],
+        [X, P, Y],
+    ),
+    // cls-hv2
+    rule(
+        &[[C, HAS_VALUE, Y], [C, ON_PROPERTY, P], [X, P, Y]],
+        [X, TYPE, C],
+    ),
+    // cls-svf1
+    rule(
+        &[
+            [C, SOME_VALUES_FROM, D],
+            [C, ON_PROPERTY, P],
+            [X, P, Y],
+            [Y, TYPE, D],
+        ],
+        [X, TYPE, C],
+    ),
+    // cls-avf
+    rule(
+        &[
+            [C, ALL_VALUES_FROM, D],
+            [C, ON_PROPERTY, P],
+            [X, TYPE, C],
+            [X, P, Y],
+        ],
+        [Y, TYPE, D],
+    ),
+    // cax-eqc1
+    rule(&[[C, EQUIVALENT_CLASS, D], [X, TYPE, C]], [X, TYPE, D]),
+    // cax-eqc2
+    rule(&[[C, EQUIVALENT_CLASS, D], [X, TYPE, D]], [X, TYPE, C]),
+    // scm-eqc1
+    rule(&[[C, EQUIVALENT_CLASS, D]], [C, SUB_CLASS_OF, D]),
+    rule(&[[C, EQUIVALENT_CLASS, D]], [D, SUB_CLASS_OF, C]),
+    // scm-eqc2
+    rule(
+        &[[C, SUB_CLASS_OF, D], [D, SUB_CLASS_OF, C]],
+        [C, EQUIVALENT_CLASS, D],
+    ),
+    // scm-eqp1
+    rule(&[[P, EQUIVALENT_PROPERTY, Q]], [P, SUB_PROPERTY_OF, Q]),
+    rule(&[[P, EQUIVALENT_PROPERTY, Q]], [Q, SUB_PROPERTY_OF, P]),
+    // scm-eqp2
+    rule(
+        &[[P, SUB_PROPERTY_OF, Q], [Q, SUB_PROPERTY_OF, P]],
+        [P, EQUIVALENT_PROPERTY, Q],
+    ),
+    // scm-dom1
+    rule(&[[P, DOMAIN, C], [C, SUB_CLASS_OF, D]], [P, DOMAIN, D]),
+    // scm-dom2
+    rule(&[[Q, DOMAIN, C], [P, SUB_PROPERTY_OF, Q]], [P, DOMAIN, C]),
+    // scm-rng1
+    rule(&[[P, RANGE, C], [C, SUB_CLASS_OF, D]], [P, RANGE, D]),
+    // scm-rng2
+    rule(&[[Q, RANGE, C], [P, SUB_PROPERTY_OF, Q]], [P, RANGE, C]),
+];
+
+#[derive(Clone, Copy)]
+enum RuleTerm<I: StrId> {
+    Variable(usize),
+    Constant(EncodedTerm<I>),
+}
+
+type RuleAtom<I> = [RuleTerm<I>; 3];
+type Bindings<I> = [Option<EncodedTerm<I>>; VARIABLES_COUNT];
+
+struct Rule<I: StrId> {
+    body: Vec<RuleAtom<I>>,
+    head: RuleAtom<I>,
+    different: Option<(usize, usize)>,
+}
+
+struct Reasoner<'a, S: InferenceStore> {
+    store: &'a S,
+    rules: Vec<Rule<S::StrId>>,
+}
+
+impl<'a, S: InferenceStore> Reasoner<'a, S> {
+    fn new(store: &'a S, regime: EntailmentRegime) -> Result<Self, S::Error> {
+        let declarations: &[&[DeclaredRule]] = match regime {
+            EntailmentRegime::Rdfs => &[RDFS_RULES],
+            EntailmentRegime::Owl2Rl => &[RDFS_RULES, OWL2_RL_RULES],
+        };
+        let mut constants = HashMap::new();
+        let mut encode = |term: &DeclaredTerm| -> Result<RuleTerm<S::StrId>, S::Error> {
+            Ok(match term {
+                DeclaredTerm::Variable(id) => RuleTerm::Variable(*id),
+                DeclaredTerm::Constant(node) => RuleTerm::Constant(
+                    if let Some(encoded) = constants.get(node.as_str()) {
+                        *encoded
+                    } else {
+                        let encoded = store.encode_vocabulary(*node)?;
+                        constants.insert(node.as_str(), encoded);
+                        encoded
+                    },
+                ),
+            })
+        };
+        let mut rules = Vec::new();
+        for declaration in declarations.iter().flat_map(|rules| rules.iter()) {
+            let mut body = Vec::with_capacity(declaration.body.len());
+            for atom in declaration.body {
+                body.push([encode(&atom[0])?, encode(&atom[1])?, encode(&atom[2])?]);
+            }
+            let head = &declaration.head;
+            rules.push(Rule {
+                body,
+                head: [encode(&head[0])?, encode(&head[1])?, encode(&head[2])?],
+                different: declaration.different,
+            });
+        }
+        Ok(Self { store, rules })
+    }
+
+    fn contains(&self, quad: &EncodedQuad<S::StrId>) -> Result<bool, S::Error> {
+        Ok(self
+            .store
+            .encoded_quads_for_pattern(
+                Some(quad.subject),
+                Some(quad.predicate),
+                Some(quad.object),
+                Some(quad.graph_name),
+            )
+            .next()
+            .transpose()?
+            .is_some())
+    }
+
+    /// Adds all the quads inferred from the `delta` quads that are already in the store
+    fn saturate(&self, mut delta: Vec<EncodedQuad<S::StrId>>) -> Result<(), S::Error> {
+        let no_extra_quads = HashSet::new();
+        while !delta.is_empty() {
+            let mut new_quads = Vec::new();
+            let mut seen = HashSet::new();
+            for quad in &delta {
+                for consequence in self.consequences(quad, &no_extra_quads)? {
+                    if seen.insert(consequence) && !self.contains(&consequence)? {
+                        new_quads.push(consequence);
+                    }
+                }
+            }
+            self.store.insert_inferred(&new_quads)?;
+            delta = new_quads;
+        }
+        Ok(())
+    }
+
+    /// Updates the inferred quads after the removal of some quads that are not in the store anymore
+    fn propagate_removals(&self, removed: &HashSet<EncodedQuad<S::StrId>>) -> Result<(), S::Error> {
+        // We remove all the inferred quads that may have been derived from the removed quads
+        let mut overdeleted = HashSet::new();
+        let mut to_visit = removed.iter().copied().collect::<Vec<_>>();
+        while let Some(quad) = to_visit.pop() {
+            for consequence in self.consequences(&quad, removed)? {
+                if !overdeleted.contains(&consequence) && self.store.is_inferred(&consequence)? {
+                    overdeleted.insert(consequence);
+                    to_visit.push(consequence);
+                }
+            }
+        }
+        let overdeleted = overdeleted.into_iter().collect::<Vec<_>>();
+        self.store.remove_inferred(&overdeleted)?;
+        self.store
+            .unmark_inferred(&removed.iter().copied().collect::<Vec<_>>())?;
+
+        // We add back the ones that are still derivable
+        let mut rederived = Vec::new();
+        for quad in overdeleted.iter().chain(removed.iter()) {
+            if self.is_derivable(quad)? {
+                rederived.push(*quad);
+            }
+        }
+        self.store.insert_inferred(&rederived)?;
+        self.saturate(rederived)
+    }
+
+    /// Returns the quads derivable in one step using the given quad
+    ///
+    /// The `extra` quads are considered as part of the store
+    fn consequences(
+        &self,
+        quad: &EncodedQuad<S::StrId>,
+        extra: &HashSet<EncodedQuad<S::StrId>>,
+    ) -> Result<Vec<EncodedQuad<S::StrId>>, S::Error> {
+        let mut consequences = Vec::new();
+        for rule in &self.rules {
+            for (i, atom) in rule.body.iter().enumerate() {
+                let mut bindings = [None; VARIABLES_COUNT];
+                if !unify(atom, quad, &mut bindings) {
+                    continue;
+                }
+                let others = rule
+                    .body
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| i != *j)
+                    .map(|(_, atom)| atom)
+                    .collect::<Vec<_>>();
+                let mut solutions = Vec::new();
+                self.solve(&others, bindings, quad.graph_name, extra, &mut solutions)?;
+                consequences.extend(
+                    solutions
+                        .iter()
+                        .filter_map(|bindings| instantiate(rule, bindings, quad.graph_name)),
+                );
+            }
+        }
+        Ok(consequences)
+    }
+
+    /// Checks if the quad is derivable in one step from the store quads
+    fn is_derivable(&self, quad: &EncodedQuad<S::StrId>) -> Result<bool, S::Error> {
+        let no_extra_quads = HashSet::new();
+        for rule in &self.rules {
+            let mut bindings = [None; VARIABLES_COUNT];
+            if !unify(&rule.head, quad, &mut bindings) {
+                continue;
+            }
+            let body = rule.body.iter().collect::<Vec<_>>();
+            let mut solutions = Vec::new();
+            self.solve(
+                &body,
+                bindings,
+                quad.graph_name,
+                &no_extra_quads,
+                &mut solutions,
+            )?;
+            if solutions
+                .iter()
+                .any(|bindings| instantiate(rule, bindings, quad.graph_name).is_some())
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Finds all the bindings matching the atoms in the given graph
+    fn solve(
+        &self,
+        atoms: &[&RuleAtom<S::StrId>],
+        bindings: Bindings<S::StrId>,
+        graph_name: EncodedTerm<S::StrId>,
+        extra: &HashSet<EncodedQuad<S::StrId>>,
+        solutions: &mut Vec<Bindings<S::StrId>>,
+    ) -> Result<(), S::Error> {
+        // We evaluate first the atom with the largest number of bound terms
+        let (position, atom) = if let Some(best) = atoms
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by_key(|(_, atom)| atom.iter().filter(|t| bound(t, &bindings).is_some()).count())
+        {
+            best
+        } else {
+            solutions.push(bindings);
+            return Ok(());
+        };
+        let others = atoms
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != position)
+            .map(|(_, atom)| *atom)
+            .collect::<Vec<_>>();
+
+        for quad in self.store.encoded_quads_for_pattern(
+            bound(&atom[0], &bindings),
+            bound(&atom[1], &bindings),
+            bound(&atom[2], &bindings),
+            Some(graph_name),
+        ) {
+            let mut new_bindings = bindings;
+            if unify(atom, &quad?, &mut new_bindings) {
+                self.solve(&others, new_bindings, graph_name, extra, solutions)?;
+            }
+        }
+        for quad in extra {
+            let mut new_bindings = bindings;
+            if quad.graph_name == graph_name && unify(atom, quad, &mut new_bindings) {
+                self.solve(&others, new_bindings, graph_name, extra, solutions)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn bound<I: StrId>(term: &RuleTerm<I>, bindings: &Bindings<I>) -> Option<EncodedTerm<I>> {
+    match term {
+        RuleTerm::Variable(id) => bindings[*id],
+        RuleTerm::Constant(term) => Some(*term),
+    }
+}
+
+/// Binds the atom variables to the quad terms and returns if the atom matches the quad
+fn unify<I: StrId>(atom: &RuleAtom<I>, quad: &EncodedQuad<I>, bindings: &mut Bindings<I>) -> bool {
+    for (term, value) in atom
+        .iter()
+        .zip([quad.subject, quad.predicate, quad.object].iter())
+    {
+        match term {
+            RuleTerm::Variable(id) => {
+                if let Some(bound) = bindings[*id] {
+                    if bound != *value {
+                        return false;
+                    }
+                } else {
+                    bindings[*id] = Some(*value);
+                }
+            }
+            RuleTerm::Constant(constant) => {
+                if constant != value {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Builds the rule head quad if it is a valid RDF quad
+fn instantiate<I: StrId>(
+    rule: &Rule<I>,
+    bindings: &Bindings<I>,
+    graph_name: EncodedTerm<I>,
+) -> Option<EncodedQuad<I>> {
+    if let Some((a, b)) = rule.different {
+        if bindings[a] == bindings[b] {
+            return None;
+        }
+    }
+    let subject = bound(&rule.head[0], bindings)?;
+    let predicate = bound(&rule.head[1], bindings)?;
+    let object = bound(&rule.head[2], bindings)?;
+    if (subject.is_named_node() || subject.is_blank_node() || subject.is_triple())
+        && predicate.is_named_node()
+    {
+        Some(EncodedQuad::new(subject, predicate, object, graph_name))
+    } else {
+        None
+    }
+}
@@ -1,26 +1,36 @@
 //! In-memory store.
 
 use crate::error::{invalid_input_error, UnwrapInfallible};
-use crate::io::{DatasetFormat, DatasetParser, GraphFormat, GraphParser};
+use crate::io::{
+    DatasetFormat, DatasetParser, DatasetSerializer, GraphFormat, GraphParser, GraphSerializer,
+};
 use crate::model::*;
+use crate::shacl::{validate, ShapesGraph, ValidationReport};
 use crate::sparql::{
-    evaluate_query, evaluate_update, EvaluationError, Query, QueryOptions, QueryResults, Update,
-    UpdateOptions,
+    evaluate_query, evaluate_update, explain_query, EvaluationError, Query, QueryExplanation,
+    QueryOptions, QueryResults, Update, UpdateOptions,
+};
+use crate::store::changes::{ChangeCollector, ChangeFeed, ChangeSubscription};
+use crate::store::inference::{
+    materialize, propagate_changes, remove_inferred_quads, EntailmentRegime, InferenceStore,
+    QuadChanges,
 };
 use crate::store::numeric_encoder::{
     Decoder, ReadEncoder, StrContainer, StrEncodingAware, StrId, StrLookup, WriteEncoder,
 };
+use crate::store::text::{text_literal_value, MemoryTextIndex, TextMatch};
 use crate::store::{
-    dump_dataset, dump_graph, get_encoded_quad_pattern, load_dataset, load_graph,
-    ReadableEncodedStore, WritableEncodedStore,
+    dump_dataset, dump_graph, get_encoded_quad_pattern, load_dataset, load_from_path, load_graph,
+    PredicateStatistics, ReadableEncodedStore, WritableEncodedStore,
 };
 use lasso::{LargeSpur, ThreadedRodeo};
 use std::collections::hash_map::DefaultHasher;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::{Infallible, TryInto};
 use std::hash::{Hash, Hasher};
 use std::io::{BufRead, Write};
 use std::iter::FromIterator;
+use std::path::Path;
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use std::vec::IntoIter;
 use std::{fmt, io};
@@ -56,6 +66,7 @@ use std::{fmt, io};
 pub struct MemoryStore {
     indexes: Arc<RwLock<MemoryStoreIndexes>>,
     strings: Arc<ThreadedRodeo<LargeSpur>>,
+    changes: Arc<ChangeFeed>,
 }
 
 type TripleMap<T> = HashMap<T, HashMap<T, HashSet<T>>>;
@@ -74,6 +85,16 @@ struct MemoryStoreIndexes {
     default_spo: TripleMap<EncodedTerm>,
     default_pos: TripleMap<EncodedTerm>,
     default_osp: TripleMap<EncodedTerm>,
+    statistics: HashMap<EncodedTerm, PredicateStatistics>,
+    entailment_regime: Option<EntailmentRegime>,
+    inferred: HashSet<EncodedQuad>,
+    text_index: Option<MemoryStoreTextIndex>,
+}
+
+/// The full-text index with the strings required to read the indexed literals
+struct MemoryStoreTextIndex {
+    index: MemoryTextIndex<LargeSpur>,
+    strings: Arc<ThreadedRodeo<LargeSpur>>,
 }
 
 impl Default for MemoryStore {
@@ -88,6 +109,7 @@ impl MemoryStore {
         Self {
             indexes: Arc::new(RwLock::default()),
             strings: Arc::new(ThreadedRodeo::new()),
+            changes: Arc::new(ChangeFeed::default()),
         }
     }
 
@@ -129,6 +151,78 @@ impl MemoryStore {
         evaluate_query(self.clone(), query, options)
     }
 
+    /// Returns the evaluation plan of a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) without executing it.
+    ///
+    /// The default query options are used.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::MemoryStore;
+    ///
+    /// let store = MemoryStore::new();
+    /// let explanation = store.explain("SELECT ?s WHERE { ?s <http://example.com/p> ?o . ?o <http://example.com/q> ?s }")?;
+    /// println!("{}", explanation);
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn explain(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+    ) -> Result<QueryExplanation, EvaluationError> {
+        self.explain_opt(query, QueryOptions::default(), false)
+    }
+
+    /// Returns the evaluation plan of a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) with some options.
+    ///
+    /// If `with_stats` is set, the query is fully evaluated and the number of rows and the evaluation time of each plan node are returned.
+    pub fn explain_opt(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+        options: QueryOptions,
+        with_stats: bool,
+    ) -> Result<QueryExplanation, EvaluationError> {
+        explain_query(self.clone(), query, options, with_stats)
+    }
+
+    /// Validates the content of the store against a [SHACL](https://www.w3.org/TR/shacl/) shapes graph.
+    ///
+    /// The data graph is the union of all the store graphs.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::io::GraphFormat;
+    /// use oxigraph::model::*;
+    /// use oxigraph::shacl::ShapesGraph;
+    ///
+    /// let shapes = ShapesGraph::parse(b"@prefix sh: <http://www.w3.org/ns/shacl#> .
+    /// <http://example.com/s> sh:targetNode <http://example.com/n> ; sh:property [ sh:path <http://example.com/p> ; sh:minCount 1 ] .".as_ref(), GraphFormat::Turtle, None)?;
+    ///
+    /// let store = MemoryStore::new();
+    /// assert!(!store.validate(&shapes)?.conforms());
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com/n")?;
+    /// store.insert(QuadRef::new(ex, NamedNodeRef::new("http://example.com/p")?, ex, None));
+    /// assert!(store.validate(&shapes)?.conforms());
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn validate(&self, shapes: &ShapesGraph) -> Result<ValidationReport, EvaluationError> {
+        validate(self.clone(), shapes, None, QueryOptions::default())
+    }
+
+    /// Validates a graph of the store against a [SHACL](https://www.w3.org/TR/shacl/) shapes graph.
+    pub fn validate_graph<'a>(
+        &self,
+        shapes: &ShapesGraph,
+        graph_name: impl Into<GraphNameRef<'a>>,
+    ) -> Result<ValidationReport, EvaluationError> {
+        validate(
+            self.clone(),
+            shapes,
+            Some(graph_name.into()),
+            QueryOptions::default(),
+        )
+    }
+
     /// Retrieves quads with a filter on each quad component
     ///
     /// Usage example:
@@ -150,7 +244,7 @@ impl MemoryStore {
     /// ```
     pub fn quads_for_pattern(
         &self,
-        subject: Option<NamedOrBlankNodeRef<'_>>,
+        subject: Option<SubjectRef<'_>>,
         predicate: Option<NamedNodeRef<'_>>,
         object: Option<TermRef<'_>>,
         graph_name: Option<GraphNameRef<'_>>,
@@ -285,19 +379,45 @@ impl MemoryStore {
         f(&mut transaction)?;
 
         let mut this = self;
+        let mut changes = QuadChanges::default();
         let mut indexes = self.indexes_mut();
+        let record_changes = indexes.entailment_regime.is_some();
+        let mut collector = self.change_collector();
         for op in transaction.ops {
             match op {
                 TransactionOp::Insert(quad) => {
                     let quad = this.encode_quad(quad.as_ref()).unwrap_infallible();
-                    indexes.insert_encoded(&quad).unwrap_infallible()
+                    if let Some(collector) = &mut collector {
+                        collector
+                            .record_insertion(&quad, || {
+                                Ok::<_, Infallible>(indexes.contains_asserted(&quad))
+                            })
+                            .unwrap_infallible();
+                    }
+                    indexes.insert_encoded(&quad).unwrap_infallible();
+                    if record_changes {
+                        changes.record_insertion(&quad);
+                    }
                 }
                 TransactionOp::Delete(quad) => {
                     let quad = this.encode_quad(quad.as_ref()).unwrap_infallible();
-                    indexes.remove_encoded(&quad).unwrap_infallible()
+                    if let Some(collector) = &mut collector {
+                        collector
+                            .record_removal(&quad, || {
+                                Ok::<_, Infallible>(indexes.contains_asserted(&quad))
+                            })
+                            .unwrap_infallible();
+                    }
+                    indexes.remove_encoded(&quad).unwrap_infallible();
+                    if record_changes {
+                        changes.record_removal(&quad);
+                    }
                 }
             }
         }
+        self.publish_changes(collector);
+        drop(indexes);
+        self.propagate_inferences(changes).unwrap_infallible();
         Ok(())
     }
 
@@ -335,7 +455,98 @@ impl MemoryStore {
         base_iri: Option<&str>,
     ) -> Result<(), io::Error> {
         let mut store = self;
-        load_graph(&mut store, reader, format, to_graph_name.into(), base_iri)?;
+        load_graph(
+            &mut store,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// Loads a graph file (i.e. triples) into the store like [`load_graph`](MemoryStore::load_graph()) and returns the prefixes declared in it.
+    ///
+    /// The prefixes could be given to [`GraphSerializer::with_prefixes`] to use them when dumping the store.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::io::{GraphFormat, GraphSerializer};
+    /// use oxigraph::model::*;
+    ///
+    /// let store = MemoryStore::new();
+    /// let file = "@prefix ex: <http://example.com/> .\n\nex:s ex:p ex:o .\n";
+    /// let prefixes = store.load_graph_with_prefixes(file.as_bytes(), GraphFormat::Turtle, GraphNameRef::DefaultGraph, None)?;
+    /// assert_eq!(prefixes.get("ex").map(String::as_str), Some("http://example.com/"));
+    ///
+    /// let mut buffer = Vec::new();
+    /// store.dump_graph(
+    ///     &mut buffer,
+    ///     GraphSerializer::from_format(GraphFormat::Turtle).with_prefixes(prefixes)?,
+    ///     GraphNameRef::DefaultGraph
+    /// )?;
+    /// assert_eq!(buffer.as_slice(), file.as_bytes());
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn load_graph_with_prefixes<'a>(
+        &self,
+        reader: impl BufRead,
+        format: GraphFormat,
+        to_graph_name: impl Into<GraphNameRef<'a>>,
+        base_iri: Option<&str>,
+    ) -> Result<BTreeMap<String, String>, io::Error> {
+        let mut store = self;
+        Ok(load_graph(
+            &mut store,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            None,
+        )?)
+    }
+
+    /// Loads a graph file (i.e. triples) into the store like [`load_graph`](MemoryStore::load_graph()) but skips the invalid statements.
+    ///
+    /// The syntax error of each skipped statement is given to `on_error`. The error messages contain the line and column of the error.
+    /// Only the N-Triples and Turtle parsers are able to resume after an error, the RDF/XML one still fails on the first error.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::io::GraphFormat;
+    /// use oxigraph::model::*;
+    ///
+    /// let store = MemoryStore::new();
+    /// let file = "<http://example.com/s> <http://example.com/p> <http://example.com/o> .
+    /// <http://example.com/s> <http://example.com/p> \"bad literal .
+    /// <http://example.com/s> <http://example.com/p> <http://example.com/o2> .";
+    ///
+    /// let mut errors = Vec::new();
+    /// store.load_graph_lenient(file.as_bytes(), GraphFormat::NTriples, GraphNameRef::DefaultGraph, None, |e| errors.push(e))?;
+    /// assert_eq!(store.len(), 2);
+    /// assert_eq!(errors.len(), 1);
+    /// # std::io::Result::Ok(())
+    /// ```
+    pub fn load_graph_lenient<'a>(
+        &self,
+        reader: impl BufRead,
+        format: GraphFormat,
+        to_graph_name: impl Into<GraphNameRef<'a>>,
+        base_iri: Option<&str>,
+        mut on_error: impl FnMut(io::Error),
+    ) -> Result<(), io::Error> {
+        let mut store = self;
+        load_graph(
+            &mut store,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            Some(&mut on_error),
+        )?;
         Ok(())
     }
 
@@ -372,7 +583,62 @@ impl MemoryStore {
         base_iri: Option<&str>,
     ) -> Result<(), io::Error> {
         let mut store = self;
-        load_dataset(&mut store, reader, format, base_iri)?;
+        load_dataset(&mut store, reader, format, base_iri, None)?;
+        Ok(())
+    }
+
+    /// Loads a dataset file (i.e. quads) into the store like [`load_dataset`](MemoryStore::load_dataset()) and returns the prefixes declared in it.
+    ///
+    /// The prefixes could be given to [`DatasetSerializer::with_prefixes`] to use them when dumping the store.
+    pub fn load_dataset_with_prefixes(
+        &self,
+        reader: impl BufRead,
+        format: DatasetFormat,
+        base_iri: Option<&str>,
+    ) -> Result<BTreeMap<String, String>, io::Error> {
+        let mut store = self;
+        Ok(load_dataset(&mut store, reader, format, base_iri, None)?)
+    }
+
+    /// Loads a dataset file (i.e. quads) into the store like [`load_dataset`](MemoryStore::load_dataset()) but skips the invalid statements.
+    ///
+    /// The syntax error of each skipped statement is given to `on_error`. The error messages contain the line and column of the error.
+    /// Only the N-Quads and TriG parsers are able to resume after an error, the JSON-LD one still fails on the first error.
+    pub fn load_dataset_lenient(
+        &self,
+        reader: impl BufRead,
+        format: DatasetFormat,
+        base_iri: Option<&str>,
+        mut on_error: impl FnMut(io::Error),
+    ) -> Result<(), io::Error> {
+        let mut store = self;
+        load_dataset(&mut store, reader, format, base_iri, Some(&mut on_error))?;
+        Ok(())
+    }
+
+    /// Loads a file into the store guessing its format from the file extension or, if it is not known, from the file content.
+    ///
+    /// The triples are loaded into the default graph.
+    /// The files compressed with a supported [`Compression`](crate::io::Compression) are decompressed.
+    ///
+    /// Usage example:
+    /// ```no_run
+    /// use oxigraph::MemoryStore;
+    ///
+    /// let store = MemoryStore::new();
+    /// store.load_from_path("dump.nq.gz", None)?;
+    /// # std::io::Result::Ok(())
+    /// ```
+    ///
+    /// An [`InvalidInput`](std::io::ErrorKind::InvalidInput) error is returned if the format could not be guessed.
+    /// The other errors are the same as the ones of [`load_graph`](MemoryStore::load_graph()) and [`load_dataset`](MemoryStore::load_dataset()).
+    pub fn load_from_path(
+        &self,
+        path: impl AsRef<Path>,
+        base_iri: Option<&str>,
+    ) -> Result<(), io::Error> {
+        let mut store = self;
+        load_from_path(&mut store, path.as_ref(), base_iri)?;
         Ok(())
     }
 
@@ -404,6 +670,10 @@ impl MemoryStore {
 
     /// Dumps a store graph into a file.
     ///
+    /// The quads inferred by the [entailment regime](MemoryStore::set_entailment_regime()) are not written.
+    ///
+    /// A [`GraphSerializer`] could be given instead of the format to set serialization options like the Turtle prefixes.
+    ///
     /// Usage example:
     /// ```
     /// use oxigraph::{MemoryStore};
@@ -423,12 +693,19 @@ impl MemoryStore {
     pub fn dump_graph<'a>(
         &self,
         writer: impl Write,
-        format: GraphFormat,
+        format: impl Into<GraphSerializer>,
         from_graph_name: impl Into<GraphNameRef<'a>>,
     ) -> Result<(), io::Error> {
+        let quads = if let Some(graph_name) = self
+            .get_encoded_graph_name(from_graph_name.into())
+            .unwrap_infallible()
+        {
+            self.encoded_quads_for_graph(graph_name)
+        } else {
+            Vec::new()
+        };
         dump_graph(
-            self.quads_for_pattern(None, None, None, Some(from_graph_name.into()))
-                .map(|q| Ok(q.into())),
+            self.asserted_quads(quads).map(|q| Ok(q.into())),
             writer,
             format,
         )
@@ -436,6 +713,10 @@ impl MemoryStore {
 
     /// Dumps the store into a file.
     ///
+    /// The quads inferred by the [entailment regime](MemoryStore::set_entailment_regime()) are not written.
+    ///
+    /// A [`DatasetSerializer`] could be given instead of the format to set serialization options like the TriG prefixes.
+    ///
     /// Usage example:
     /// ```
     /// use oxigraph::MemoryStore;
@@ -451,8 +732,16 @@ impl MemoryStore {
     /// assert_eq!(file, buffer.as_slice());
     /// # std::io::Result::Ok(())
     /// ```
-    pub fn dump_dataset(&self, writer: impl Write, format: DatasetFormat) -> Result<(), io::Error> {
-        dump_dataset(self.iter().map(Ok), writer, format)
+    pub fn dump_dataset(
+        &self,
+        writer: impl Write,
+        format: impl Into<DatasetSerializer>,
+    ) -> Result<(), io::Error> {
+        dump_dataset(
+            self.asserted_quads(self.encoded_quads()).map(Ok),
+            writer,
+            format,
+        )
     }
 
     /// Returns all the store named graphs
@@ -598,7 +887,113 @@ impl MemoryStore {
     /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
     /// ```
     pub fn clear(&self) {
-        self.indexes_mut().clear().unwrap_infallible()
+        let mut this = self;
+        (&mut this).clear().unwrap_infallible()
+    }
+
+    /// Sets the [entailment regime](EntailmentRegime) used to materialize inferred quads in the store.
+    ///
+    /// All the quads inferred by the previous regime are removed and the ones entailed by the new regime are computed.
+    /// The inferred quads are then maintained on each store write. `None` disables the inference.
+    ///
+    /// The inferred quads are returned by the quad pattern lookups and by the SPARQL queries
+    /// but are not written by [`dump_graph`](MemoryStore::dump_graph()) and [`dump_dataset`](MemoryStore::dump_dataset()).
+    /// Removing an inferred quad has no effect as long as it is entailed by the other quads.
+    ///
+    /// See [`EntailmentRegime`] for a usage example.
+    pub fn set_entailment_regime(&self, regime: Option<EntailmentRegime>) {
+        remove_inferred_quads(self).unwrap_infallible();
+        self.indexes_mut().entailment_regime = regime;
+        if let Some(regime) = regime {
+            materialize(self, regime).unwrap_infallible();
+        }
+    }
+
+    /// Returns the [entailment regime](EntailmentRegime) used to materialize inferred quads in the store, if any.
+    pub fn entailment_regime(&self) -> Option<EntailmentRegime> {
+        self.indexes().entailment_regime
+    }
+
+    /// Enables or disables the full-text index.
+    ///
+    /// When it is enabled, the string literals used as quad objects are indexed and could be searched in SPARQL
+    /// with the `text:query` property function. See the [`sparql`](crate::sparql) module documentation for its syntax.
+    /// The index is built from the current content of the store when it is enabled and then maintained on each store write.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::model::*;
+    /// use oxigraph::model::vocab::rdfs;
+    /// use oxigraph::sparql::QueryResults;
+    ///
+    /// let store = MemoryStore::new();
+    /// store.set_text_index(true);
+    ///
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// store.insert(QuadRef::new(ex, rdfs::LABEL, LiteralRef::new_language_tagged_literal_unchecked("An example", "en"), None));
+    ///
+    /// if let QueryResults::Solutions(mut solutions) = store.query(
+    ///     "PREFIX text: <http://jena.apache.org/text#> SELECT ?s WHERE { ?s text:query \"EXAMPLE\" }"
+    /// )? {
+    ///     assert_eq!(solutions.next().unwrap()?.get("s"), Some(&ex.into_owned().into()));
+    /// }
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn set_text_index(&self, enabled: bool) {
+        let mut indexes = self.indexes_mut();
+        indexes.text_index = if enabled {
+            let mut text_index = MemoryStoreTextIndex {
+                index: MemoryTextIndex::default(),
+                strings: self.strings.clone(),
+            };
+            for quad in indexes.quads(None) {
+                text_index.insert(quad.object);
+            }
+            Some(text_index)
+        } else {
+            None
+        };
+    }
+
+    /// Returns if the [full-text index](MemoryStore::set_text_index()) is enabled.
+    pub fn is_text_index_enabled(&self) -> bool {
+        self.indexes().text_index.is_some()
+    }
+
+    /// Subscribes to the changes of the store.
+    ///
+    /// The subscription receives a [`ChangeBatch`](super::ChangeBatch) for each write committed after its creation
+    /// that inserts or removes some quads, in commit order.
+    /// A transaction is a single write and so is each call to the other write methods.
+    /// The quads inferred by the [entailment regime](MemoryStore::set_entailment_regime()) are not part of the batches.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::MemoryStore;
+    /// use oxigraph::model::*;
+    /// use std::convert::Infallible;
+    ///
+    /// let store = MemoryStore::new();
+    /// let mut changes = store.subscribe();
+    ///
+    /// let ex = NamedNode::new("http://example.com")?;
+    /// let quad = Quad::new(ex.clone(), ex.clone(), ex.clone(), None);
+    /// store.transaction(|transaction| {
+    ///     transaction.insert(quad.clone());
+    ///     Ok(()) as Result<(),Infallible>
+    /// })?;
+    /// store.remove(&quad);
+    ///
+    /// let batch = changes.try_recv().unwrap();
+    /// assert_eq!(batch.inserted(), &[quad.clone()]);
+    /// let batch = changes.try_recv().unwrap();
+    /// assert_eq!(batch.removed(), &[quad]);
+    /// assert!(changes.try_recv().is_none());
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn subscribe(&self) -> ChangeSubscription {
+        self.changes.subscribe()
     }
 
     #[allow(clippy::expect_used)]
@@ -615,23 +1010,91 @@ impl MemoryStore {
             .expect("the Memory store mutex has been poisoned because of a panic")
     }
 
-    fn contains_encoded(&self, quad: &EncodedQuad) -> bool {
-        let indexes = self.indexes();
-        if quad.graph_name.is_default_graph() {
-            indexes.default_spo.get(&quad.subject).map_or(false, |po| {
-                po.get(&quad.predicate)
-                    .map_or(false, |o| o.contains(&quad.object))
-            })
+    /// Returns a collector if the changes should be published
+    fn change_collector(&self) -> Option<ChangeCollector<LargeSpur>> {
+        if self.changes.is_watched() {
+            Some(ChangeCollector::default())
         } else {
-            indexes.spog.get(&quad.subject).map_or(false, |pog| {
-                pog.get(&quad.predicate).map_or(false, |og| {
-                    og.get(&quad.object)
-                        .map_or(false, |g| g.contains(&quad.graph_name))
+            None
+        }
+    }
+
+    /// Returns a collector with the removal of the asserted quads of the given graph or of all graphs if `None`
+    fn graph_removal_collector(
+        &self,
+        indexes: &MemoryStoreIndexes,
+        graph_name: Option<EncodedTerm>,
+    ) -> Option<ChangeCollector<LargeSpur>> {
+        let mut collector = self.change_collector()?;
+        for quad in indexes.quads(graph_name) {
+            if !indexes.inferred.contains(&quad) {
+                collector
+                    .record_removal(&quad, || Ok::<_, Infallible>(true))
+                    .unwrap_infallible();
+            }
+        }
+        Some(collector)
+    }
+
+    /// Publishes the collected changes
+    ///
+    /// Should be called while holding the indexes write lock in order to publish the changes in commit order.
+    fn publish_changes(&self, collector: Option<ChangeCollector<LargeSpur>>) {
+        if let Some(mut collector) = collector {
+            let (inserted, removed) = collector.take_changes();
+            self.changes
+                .commit(|_| {
+                    Ok::<_, Infallible>((
+                        (),
+                        inserted
+                            .iter()
+                            .map(|q| self.decode_quad(q).unwrap())
+                            .collect(),
+                        removed
+                            .iter()
+                            .map(|q| self.decode_quad(q).unwrap())
+                            .collect(),
+                    ))
                 })
-            })
+                .unwrap_infallible()
+        }
+    }
+
+    fn propagate_inferences(&self, changes: QuadChanges<LargeSpur>) -> Result<(), Infallible> {
+        if let Some(regime) = self.entailment_regime() {
+            propagate_changes(self, regime, changes)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn graph_removal_changes(&self, graph_name: EncodedTerm) -> QuadChanges<LargeSpur> {
+        let mut changes = QuadChanges::default();
+        if self.entailment_regime().is_some() {
+            for quad in self.encoded_quads_for_graph(graph_name) {
+                changes.record_removal(&quad);
+            }
+        }
+        changes
+    }
+
+    /// Returns the given quads without the inferred ones
+    fn asserted_quads(&self, quads: Vec<EncodedQuad>) -> MemoryQuadIter {
+        let indexes = self.indexes();
+        let quads = quads
+            .into_iter()
+            .filter(|quad| !indexes.inferred.contains(quad))
+            .collect::<Vec<_>>();
+        MemoryQuadIter {
+            iter: quads.into_iter(),
+            store: self.clone(),
         }
     }
 
+    fn contains_encoded(&self, quad: &EncodedQuad) -> bool {
+        self.indexes().contains(quad)
+    }
+
     fn encoded_quads_for_pattern_inner(
         &self,
         subject: Option<EncodedTerm>,
@@ -1018,15 +1481,71 @@ impl<'a> ReadableEncodedStore for MemoryStore {
     fn contains_encoded_named_graph(&self, graph_name: EncodedTerm) -> Result<bool, Infallible> {
         Ok(self.indexes().gspo.contains_key(&graph_name))
     }
+
+    fn encoded_quads_count(&self) -> Result<u64, Infallible> {
+        Ok(self
+            .indexes()
+            .statistics
+            .values()
+            .map(|statistics| statistics.quads)
+            .sum())
+    }
+
+    fn encoded_predicate_statistics(
+        &self,
+        predicate: EncodedTerm,
+    ) -> Result<PredicateStatistics, Infallible> {
+        Ok(self
+            .indexes()
+            .statistics
+            .get(&predicate)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn encoded_text_search(
+        &self,
+        query: &str,
+    ) -> Result<Option<Vec<TextMatch<LargeSpur>>>, Infallible> {
+        Ok(self
+            .indexes()
+            .text_index
+            .as_ref()
+            .map(|text_index| text_index.index.search(query)))
+    }
 }
 
 impl<'a> WritableEncodedStore for &'a MemoryStore {
     fn insert_encoded(&mut self, quad: &EncodedQuad) -> Result<(), Infallible> {
-        self.indexes_mut().insert_encoded(quad)
+        let mut indexes = self.indexes_mut();
+        let mut collector = self.change_collector();
+        if let Some(collector) = &mut collector {
+            collector.record_insertion(quad, || {
+                Ok::<_, Infallible>(indexes.contains_asserted(quad))
+            })?;
+        }
+        indexes.insert_encoded(quad)?;
+        self.publish_changes(collector);
+        drop(indexes);
+        let mut changes = QuadChanges::default();
+        changes.record_insertion(quad);
+        self.propagate_inferences(changes)
     }
 
     fn remove_encoded(&mut self, quad: &EncodedQuad) -> Result<(), Infallible> {
-        self.indexes_mut().remove_encoded(quad)
+        let mut indexes = self.indexes_mut();
+        let mut collector = self.change_collector();
+        if let Some(collector) = &mut collector {
+            collector.record_removal(quad, || {
+                Ok::<_, Infallible>(indexes.contains_asserted(quad))
+            })?;
+        }
+        indexes.remove_encoded(quad)?;
+        self.publish_changes(collector);
+        drop(indexes);
+        let mut changes = QuadChanges::default();
+        changes.record_removal(quad);
+        self.propagate_inferences(changes)
     }
 
     fn insert_encoded_named_graph(&mut self, graph_name: EncodedTerm) -> Result<(), Infallible> {
@@ -1034,15 +1553,144 @@ impl<'a> WritableEncodedStore for &'a MemoryStore {
     }
 
     fn clear_encoded_graph(&mut self, graph_name: EncodedTerm) -> Result<(), Infallible> {
-        self.indexes_mut().clear_encoded_graph(graph_name)
+        let changes = self.graph_removal_changes(graph_name);
+        let mut indexes = self.indexes_mut();
+        let collector = self.graph_removal_collector(&indexes, Some(graph_name));
+        indexes.clear_encoded_graph(graph_name)?;
+        self.publish_changes(collector);
+        drop(indexes);
+        self.propagate_inferences(changes)
     }
 
     fn remove_encoded_named_graph(&mut self, graph_name: EncodedTerm) -> Result<(), Infallible> {
-        self.indexes_mut().remove_encoded_named_graph(graph_name)
+        let changes = self.graph_removal_changes(graph_name);
+        let mut indexes = self.indexes_mut();
+        let collector = self.graph_removal_collector(&indexes, Some(graph_name));
+        indexes.remove_encoded_named_graph(graph_name)?;
+        self.publish_changes(collector);
+        drop(indexes);
+        self.propagate_inferences(changes)
     }
 
     fn clear(&mut self) -> Result<(), Self::Error> {
-        self.indexes_mut().clear()
+        let mut indexes = self.indexes_mut();
+        let collector = self.graph_removal_collector(&indexes, None);
+        indexes.clear()?;
+        self.publish_changes(collector);
+        Ok(())
+    }
+}
+
+impl InferenceStore for MemoryStore {
+    fn encode_vocabulary(&self, node: NamedNodeRef<'static>) -> Result<EncodedTerm, Infallible> {
+        let mut this = self;
+        this.encode_named_node(node)
+    }
+
+    fn is_inferred(&self, quad: &EncodedQuad) -> Result<bool, Infallible> {
+        Ok(self.indexes().inferred.contains(quad))
+    }
+
+    fn inferred_quads(&self) -> Result<Vec<EncodedQuad>, Infallible> {
+        Ok(self.indexes().inferred.iter().copied().collect())
+    }
+
+    fn insert_inferred(&self, quads: &[EncodedQuad]) -> Result<(), Infallible> {
+        let mut indexes = self.indexes_mut();
+        for quad in quads {
+            indexes.insert_encoded(quad)?;
+            indexes.inferred.insert(*quad);
+        }
+        Ok(())
+    }
+
+    fn remove_inferred(&self, quads: &[EncodedQuad]) -> Result<(), Infallible> {
+        let mut indexes = self.indexes_mut();
+        for quad in quads {
+            indexes.remove_encoded(quad)?;
+            indexes.inferred.remove(quad);
+        }
+        Ok(())
+    }
+
+    fn unmark_inferred(&self, quads: &[EncodedQuad]) -> Result<(), Infallible> {
+        let mut indexes = self.indexes_mut();
+        for quad in quads {
+            indexes.inferred.remove(quad);
+        }
+        Ok(())
+    }
+}
+
+impl MemoryStoreIndexes {
+    fn contains(&self, quad: &EncodedQuad) -> bool {
+        if quad.graph_name.is_default_graph() {
+            matches!(
+                self.default_spo
+                    .get(&quad.subject)
+                    .and_then(|po| po.get(&quad.predicate)),
+                Some(o) if o.contains(&quad.object)
+            )
+        } else {
+            matches!(
+                self.spog
+                    .get(&quad.subject)
+                    .and_then(|pog| pog.get(&quad.predicate))
+                    .and_then(|og| og.get(&quad.object)),
+                Some(g) if g.contains(&quad.graph_name)
+            )
+        }
+    }
+
+    /// Returns if the quad is in the store and has not been inferred
+    fn contains_asserted(&self, quad: &EncodedQuad) -> bool {
+        self.contains(quad) && !self.inferred.contains(quad)
+    }
+
+    /// Returns the quads of the given graph or of all graphs if `None`
+    fn quads(&self, graph_name: Option<EncodedTerm>) -> Vec<EncodedQuad> {
+        if let Some(graph_name) = graph_name {
+            let spo = if graph_name.is_default_graph() {
+                Some(&self.default_spo)
+            } else {
+                self.gspo.get(&graph_name)
+            };
+            spo.into_iter()
+                .flat_map(triple_map_flatten)
+                .map(|(s, p, o)| EncodedQuad::new(s, p, o, graph_name))
+                .collect()
+        } else {
+            triple_map_flatten(&self.default_spo)
+                .map(|(s, p, o)| EncodedQuad::new(s, p, o, EncodedTerm::DefaultGraph))
+                .chain(
+                    quad_map_flatten(&self.gspo).map(|(g, s, p, o)| EncodedQuad::new(s, p, o, g)),
+                )
+                .collect()
+        }
+    }
+
+    fn contains_subject_predicate(&self, subject: &EncodedTerm, predicate: &EncodedTerm) -> bool {
+        self.default_spo
+            .get(subject)
+            .and_then(|po| po.get(predicate))
+            .is_some()
+            || self
+                .spog
+                .get(subject)
+                .and_then(|pog| pog.get(predicate))
+                .is_some()
+    }
+
+    fn contains_predicate_object(&self, predicate: &EncodedTerm, object: &EncodedTerm) -> bool {
+        self.default_pos
+            .get(predicate)
+            .and_then(|os| os.get(object))
+            .is_some()
+            || self
+                .posg
+                .get(predicate)
+                .and_then(|osg| osg.get(object))
+                .is_some()
     }
 }
 
@@ -1053,8 +1701,10 @@ impl StrEncodingAware for MemoryStoreIndexes {
 
 impl WritableEncodedStore for MemoryStoreIndexes {
     fn insert_encoded(&mut self, quad: &EncodedQuad) -> Result<(), Infallible> {
-        if quad.graph_name.is_default_graph() {
-            insert_into_triple_map(
+        let new_subject = !self.contains_subject_predicate(&quad.subject, &quad.predicate);
+        let new_object = !self.contains_predicate_object(&quad.predicate, &quad.object);
+        let is_new = if quad.graph_name.is_default_graph() {
+            let is_new = insert_into_triple_map(
                 &mut self.default_spo,
                 quad.subject,
                 quad.predicate,
@@ -1072,8 +1722,9 @@ impl WritableEncodedStore for MemoryStoreIndexes {
                 quad.subject,
                 quad.predicate,
             );
+            is_new
         } else {
-            insert_into_quad_map(
+            let is_new = insert_into_quad_map(
                 &mut self.gspo,
                 quad.graph_name,
                 quad.subject,
@@ -1115,13 +1766,27 @@ impl WritableEncodedStore for MemoryStoreIndexes {
                 quad.predicate,
                 quad.graph_name,
             );
+            is_new
+        };
+        if is_new {
+            if let Some(text_index) = &mut self.text_index {
+                text_index.insert(quad.object);
+            }
+            let statistics = self.statistics.entry(quad.predicate).or_default();
+            statistics.quads += 1;
+            if new_subject {
+                statistics.distinct_subjects += 1;
+            }
+            if new_object {
+                statistics.distinct_objects += 1;
+            }
         }
         Ok(())
     }
 
     fn remove_encoded(&mut self, quad: &EncodedQuad) -> Result<(), Infallible> {
-        if quad.graph_name.is_default_graph() {
-            remove_from_triple_map(
+        let was_present = if quad.graph_name.is_default_graph() {
+            let was_present = remove_from_triple_map(
                 &mut self.default_spo,
                 &quad.subject,
                 &quad.predicate,
@@ -1139,10 +1804,13 @@ impl WritableEncodedStore for MemoryStoreIndexes {
                 &quad.subject,
                 &quad.predicate,
             );
+            was_present
         } else {
-            if let Some(spo) = self.gspo.get_mut(&quad.graph_name) {
-                remove_from_triple_map(spo, &quad.subject, &quad.predicate, &quad.object);
-            }
+            let was_present = if let Some(spo) = self.gspo.get_mut(&quad.graph_name) {
+                remove_from_triple_map(spo, &quad.subject, &quad.predicate, &quad.object)
+            } else {
+                false
+            };
             if let Some(pos) = self.gpos.get_mut(&quad.graph_name) {
                 remove_from_triple_map(pos, &quad.predicate, &quad.object, &quad.subject);
             }
@@ -1170,6 +1838,26 @@ impl WritableEncodedStore for MemoryStoreIndexes {
                 &quad.predicate,
                 &quad.graph_name,
             );
+            was_present
+        };
+        if was_present {
+            if let Some(text_index) = &mut self.text_index {
+                text_index.remove(quad.object);
+            }
+            let removed_subject = !self.contains_subject_predicate(&quad.subject, &quad.predicate);
+            let removed_object = !self.contains_predicate_object(&quad.predicate, &quad.object);
+            if let Some(statistics) = self.statistics.get_mut(&quad.predicate) {
+                statistics.quads -= 1;
+                if removed_subject {
+                    statistics.distinct_subjects -= 1;
+                }
+                if removed_object {
+                    statistics.distinct_objects -= 1;
+                }
+                if statistics.quads == 0 {
+                    self.statistics.remove(&quad.predicate);
+                }
+            }
         }
         Ok(())
     }
@@ -1182,47 +1870,15 @@ impl WritableEncodedStore for MemoryStoreIndexes {
     }
 
     fn clear_encoded_graph(&mut self, graph_name: EncodedTerm) -> Result<(), Infallible> {
-        if graph_name.is_default_graph() {
-            self.default_spo.clear();
-            self.default_pos.clear();
-            self.default_osp.clear();
-        } else {
-            if let Some(spo) = self.gspo.get(&graph_name) {
-                for (s, po) in spo {
-                    for (p, os) in po {
-                        for o in os {
-                            remove_from_quad_map(&mut self.spog, s, p, o, &graph_name);
-                            remove_from_quad_map(&mut self.posg, p, o, s, &graph_name);
-                            remove_from_quad_map(&mut self.ospg, o, s, p, &graph_name);
-                        }
-                    }
-                }
-            }
-            if let Some(spo) = self.gspo.get_mut(&graph_name) {
-                spo.clear();
-            }
-            if let Some(pos) = self.gpos.get_mut(&graph_name) {
-                pos.clear();
-            }
-            if let Some(osp) = self.gosp.get_mut(&graph_name) {
-                osp.clear();
-            }
+        // We remove the quads one by one in order to keep the statistics up to date
+        for quad in &self.quads(Some(graph_name)) {
+            self.remove_encoded(quad)?;
         }
         Ok(())
     }
 
     fn remove_encoded_named_graph(&mut self, graph_name: EncodedTerm) -> Result<(), Infallible> {
-        if let Some(spo) = self.gspo.get(&graph_name) {
-            for (s, po) in spo {
-                for (p, os) in po {
-                    for o in os {
-                        remove_from_quad_map(&mut self.spog, s, p, o, &graph_name);
-                        remove_from_quad_map(&mut self.posg, p, o, s, &graph_name);
-                        remove_from_quad_map(&mut self.ospg, o, s, p, &graph_name);
-                    }
-                }
-            }
-        }
+        self.clear_encoded_graph(graph_name)?;
         self.gspo.remove(&graph_name);
         self.gpos.remove(&graph_name);
         self.gosp.remove(&graph_name);
@@ -1230,25 +1886,61 @@ impl WritableEncodedStore for MemoryStoreIndexes {
     }
 
     fn clear(&mut self) -> Result<(), Infallible> {
-        *self = MemoryStoreIndexes::default();
+        // The entailment regime and the full-text index enabling are settings of the store and not a part of its content
+        *self = MemoryStoreIndexes {
+            entailment_regime: self.entailment_regime,
+            text_index: self
+                .text_index
+                .take()
+                .map(|text_index| MemoryStoreTextIndex {
+                    index: MemoryTextIndex::default(),
+                    strings: text_index.strings,
+                }),
+            ..MemoryStoreIndexes::default()
+        };
         Ok(())
     }
 }
 
-fn insert_into_triple_map<T: Eq + Hash>(map: &mut TripleMap<T>, e1: T, e2: T, e3: T) {
-    map.entry(e1).or_default().entry(e2).or_default().insert(e3);
+impl MemoryStoreTextIndex {
+    fn insert(&mut self, literal: EncodedTerm) {
+        let strings = &self.strings;
+        self.index
+            .insert(literal, || literal_value(strings, literal));
+    }
+
+    fn remove(&mut self, literal: EncodedTerm) {
+        let strings = &self.strings;
+        self.index
+            .remove(literal, || literal_value(strings, literal));
+    }
+}
+
+fn literal_value(strings: &ThreadedRodeo<LargeSpur>, literal: EncodedTerm) -> Option<String> {
+    text_literal_value(literal, |id| {
+        Ok::<_, Infallible>(strings.try_resolve(&id).map(str::to_owned))
+    })
+    .unwrap_infallible()
+}
+
+/// Returns if the triple was not already in the map
+fn insert_into_triple_map<T: Eq + Hash>(map: &mut TripleMap<T>, e1: T, e2: T, e3: T) -> bool {
+    map.entry(e1).or_default().entry(e2).or_default().insert(e3)
 }
 
-fn insert_into_quad_map<T: Eq + Hash>(map: &mut QuadMap<T>, e1: T, e2: T, e3: T, e4: T) {
-    insert_into_triple_map(map.entry(e1).or_default(), e2, e3, e4);
+/// Returns if the quad was not already in the map
+fn insert_into_quad_map<T: Eq + Hash>(map: &mut QuadMap<T>, e1: T, e2: T, e3: T, e4: T) -> bool {
+    insert_into_triple_map(map.entry(e1).or_default(), e2, e3, e4)
 }
 
-fn remove_from_triple_map<T: Eq + Hash>(map1: &mut TripleMap<T>, e1: &T, e2: &T, e3: &T) {
+/// Returns if the triple was in the map
+fn remove_from_triple_map<T: Eq + Hash>(map1: &mut TripleMap<T>, e1: &T, e2: &T, e3: &T) -> bool {
+    let mut removed = false;
     let mut map2empty = false;
     if let Some(map2) = map1.get_mut(e1) {
         let mut set3empty = false;
         if let Some(set3) = map2.get_mut(e2) {
-            set3.remove(e3);
+            removed = set3.remove(e3);
             set3empty = set3.is_empty();
         }
         if set3empty {
@@ -1259,6 +1951,7 @@ fn remove_from_triple_map<T: Eq + Hash>(map1: &mut TripleMap<T>, e1: &T, e2: &T,
     if map2empty {
         map1.remove(e1);
     }
+    removed
 }
 
 fn remove_from_quad_map<T: Eq + Hash>(quad_map: &mut QuadMap<T>, e1: &T, e2: &T, e3: &T, e4: &T) {
@@ -1734,3 +2427,753 @@ fn hash_tuple(v: impl Hash) -> u64 {
     v.hash(&mut hasher);
     hasher.finish()
 }
+
+#[test]
+fn statistics() {
+    let store = MemoryStore::new();
+    let s1 = NamedNode::new_unchecked("http://example.com/s1");
+    let s2 = NamedNode::new_unchecked("http://example.com/s2");
+    let p = NamedNode::new_unchecked("http://example.com/p");
+    let o = Literal::from(1);
+    let g = NamedNode::new_unchecked("http://example.com/g");
+    store.insert(Quad::new(
+        s1.clone(),
+        p.clone(),
+        o.clone(),
+        GraphName::DefaultGraph,
+    ));
+    store.insert(Quad::new(s1.clone(), p.clone(), o.clone(), g.clone()));
+    store.insert(Quad::new(s2.clone(), p.clone(), o.clone(), g.clone()));
+    store.insert(Quad::new(s2.clone(), p.clone(), o.clone(), g.clone()));
+
+    let encoded_p = store
+        .get_encoded_named_node(p.as_ref())
+        .unwrap_infallible()
+        .unwrap();
+    assert_eq!(store.encoded_quads_count().unwrap_infallible(), 3);
+    assert_eq!(
+        store
+            .encoded_predicate_statistics(encoded_p)
+            .unwrap_infallible(),
+        PredicateStatistics {
+            quads: 3,
+            distinct_subjects: 2,
+            distinct_objects: 1
+        }
+    );
+
+    store.remove(&Quad::new(s2, p.clone(), o, g.clone()));
+    assert_eq!(
+        store
+            .encoded_predicate_statistics(encoded_p)
+            .unwrap_infallible(),
+        PredicateStatistics {
+            quads: 2,
+            distinct_subjects: 1,
+            distinct_objects: 1
+        }
+    );
+
+    store.clear_graph(g.as_ref());
+    store.clear_graph(GraphNameRef::DefaultGraph);
+    assert_eq!(store.encoded_quads_count().unwrap_infallible(), 0);
+    assert_eq!(
+        store
+            .encoded_predicate_statistics(encoded_p)
+            .unwrap_infallible(),
+        PredicateStatistics::default()
+    );
+}
+
+#[test]
+fn rdfs_inference() {
+    use crate::model::vocab::{rdf, rdfs};
+
+    let store = MemoryStore::new();
+    let person = NamedNodeRef::new_unchecked("http://example.com/Person");
+    let agent = NamedNodeRef::new_unchecked("http://example.com/Agent");
+    let thing = NamedNodeRef::new_unchecked("http://example.com/Thing");
+    let knows = NamedNodeRef::new_unchecked("http://example.com/knows");
+    let alice = NamedNodeRef::new_unchecked("http://example.com/alice");
+    let bob = NamedNodeRef::new_unchecked("http://example.com/bob");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+
+    store.insert(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, None));
+    store.insert(QuadRef::new(alice, rdf::TYPE, person, None));
+    store.insert(QuadRef::new(alice, knows, bob, g));
+    store.insert(QuadRef::new(knows, rdfs::RANGE, person, g));
+    assert_eq!(store.len(), 4);
+
+    // Materialization of the existing quads
+    store.set_entailment_regime(Some(EntailmentRegime::Rdfs));
+    assert_eq!(store.entailment_regime(), Some(EntailmentRegime::Rdfs));
+    assert!(store.contains(QuadRef::new(alice, rdf::TYPE, agent, None)));
+    assert!(store.contains(QuadRef::new(bob, rdf::TYPE, person, g)));
+    assert!(!store.contains(QuadRef::new(bob, rdf::TYPE, person, None)));
+
+    // Incremental insertion
+    store.insert(QuadRef::new(agent, rdfs::SUB_CLASS_OF, thing, None));
+    assert!(store.contains(QuadRef::new(person, rdfs::SUB_CLASS_OF, thing, None)));
+    assert!(store.contains(QuadRef::new(alice, rdf::TYPE, thing, None)));
+
+    // The inferred quads are not dumped
+    let mut buffer = Vec::new();
+    store
+        .dump_graph(
+            &mut buffer,
+            GraphFormat::NTriples,
+            GraphNameRef::DefaultGraph,
+        )
+        .unwrap();
+    let dumped = MemoryStore::new();
+    dumped
+        .load_graph(
+            buffer.as_slice(),
+            GraphFormat::NTriples,
+            GraphNameRef::DefaultGraph,
+            None,
+        )
+        .unwrap();
+    assert_eq!(dumped.len(), 3);
+
+    // An asserted quad that is also inferred stays after the removal of its derivation
+    store.insert(QuadRef::new(alice, rdf::TYPE, thing, None));
+    store.remove(QuadRef::new(agent, rdfs::SUB_CLASS_OF, thing, None));
+    assert!(!store.contains(QuadRef::new(person, rdfs::SUB_CLASS_OF, thing, None)));
+    assert!(store.contains(QuadRef::new(alice, rdf::TYPE, thing, None)));
+
+    // Incremental removal
+    store.remove(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, None));
+    assert!(!store.contains(QuadRef::new(alice, rdf::TYPE, agent, None)));
+    store.remove(QuadRef::new(alice, knows, bob, g));
+    assert!(!store.contains(QuadRef::new(bob, rdf::TYPE, person, g)));
+
+    // Inferred quads can not be removed while they are entailed
+    store.remove(QuadRef::new(alice, rdf::TYPE, thing, None));
+    store.insert(QuadRef::new(alice, knows, bob, g));
+    store.remove(QuadRef::new(bob, rdf::TYPE, person, g));
+    assert!(store.contains(QuadRef::new(bob, rdf::TYPE, person, g)));
+
+    // Disabling the inference
+    store.set_entailment_regime(None);
+    assert_eq!(store.len(), 3);
+}
+
+#[test]
+fn owl2_rl_inference() {
+    use crate::model::vocab::rdf;
+
+    let store = MemoryStore::new();
+    store.set_entailment_regime(Some(EntailmentRegime::Owl2Rl));
+    let same_as = NamedNodeRef::new_unchecked("http://www.w3.org/2002/07/owl#sameAs");
+    let inverse_of = NamedNodeRef::new_unchecked("http://www.w3.org/2002/07/owl#inverseOf");
+    let transitive =
+        NamedNodeRef::new_unchecked("http://www.w3.org/2002/07/owl#TransitiveProperty");
+    let part_of = NamedNodeRef::new_unchecked("http://example.com/partOf");
+    let has_part = NamedNodeRef::new_unchecked("http://example.com/hasPart");
+    let a = NamedNodeRef::new_unchecked("http://example.com/a");
+    let b = NamedNodeRef::new_unchecked("http://example.com/b");
+    let c = NamedNodeRef::new_unchecked("http://example.com/c");
+    let c2 = NamedNodeRef::new_unchecked("http://example.com/c2");
+
+    store
+        .transaction(|transaction| {
+            transaction.insert(QuadRef::new(part_of, rdf::TYPE, transitive, None).into());
+            transaction.insert(QuadRef::new(part_of, inverse_of, has_part, None).into());
+            transaction.insert(QuadRef::new(a, part_of, b, None).into());
+            transaction.insert(QuadRef::new(b, part_of, c, None).into());
+            Ok::<_, Infallible>(())
+        })
+        .unwrap();
+    assert!(store.contains(QuadRef::new(a, part_of, c, None)));
+    assert!(store.contains(QuadRef::new(c, has_part, a, None)));
+
+    store.insert(QuadRef::new(c, same_as, c2, None));
+    assert!(store.contains(QuadRef::new(c2, same_as, c, None)));
+    assert!(store.contains(QuadRef::new(a, part_of, c2, None)));
+    assert!(store.contains(QuadRef::new(c2, has_part, a, None)));
+
+    store.remove(QuadRef::new(b, part_of, c, None));
+    assert!(!store.contains(QuadRef::new(a, part_of, c, None)));
+    assert!(!store.contains(QuadRef::new(a, part_of, c2, None)));
+    assert!(!store.contains(QuadRef::new(c2, has_part, a, None)));
+    assert!(store.contains(QuadRef::new(b, has_part, a, None)));
+}
+
+#[test]
+fn shacl_validation() {
+    use crate::shacl::vocab as sh;
+
+    let shapes = ShapesGraph::parse(
+        br#"
+@prefix sh: <http://www.w3.org/ns/shacl#> .
+@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .
+@prefix ex: <http://example.com/> .
+
+ex:PersonShape a sh:NodeShape ;
+    sh:targetClass ex:Person ;
+    sh:property [
+        sh:path ex:name ;
+        sh:minCount 1 ;
+        sh:maxCount 1 ;
+        sh:datatype xsd:string
+    ] , [
+        sh:path ex:age ;
+        sh:datatype xsd:int ;
+        sh:minInclusive 0
+    ] , [
+        sh:path ( ex:knows ex:name ) ;
+        sh:pattern "^[A-Z]"
+    ] ;
+    sh:sparql [
+        sh:message "{$this} knows itself" ;
+        sh:select "SELECT $this WHERE { $this <http://example.com/knows> $this }"
+    ] .
+"#
+        .as_ref(),
+        GraphFormat::Turtle,
+        None,
+    )
+    .unwrap();
+
+    let store = MemoryStore::new();
+    store
+        .load_graph(
+            br#"
+@prefix ex: <http://example.com/> .
+ex:alice a ex:Person ; ex:name "Alice" ; ex:age 20 ; ex:knows ex:bob .
+ex:bob a ex:Person ; ex:name "Bob" .
+"#
+            .as_ref(),
+            GraphFormat::Turtle,
+            GraphNameRef::DefaultGraph,
+            None,
+        )
+        .unwrap();
+    assert!(store.validate(&shapes).unwrap().conforms());
+
+    let alice = NamedNodeRef::new_unchecked("http://example.com/alice");
+    let bob = NamedNodeRef::new_unchecked("http://example.com/bob");
+    let name = NamedNodeRef::new_unchecked("http://example.com/name");
+    let age = NamedNodeRef::new_unchecked("http://example.com/age");
+    let knows = NamedNodeRef::new_unchecked("http://example.com/knows");
+    store.remove(QuadRef::new(
+        bob,
+        name,
+        LiteralRef::new_simple_literal("Bob"),
+        None,
+    ));
+    store.insert(QuadRef::new(
+        bob,
+        name,
+        LiteralRef::new_simple_literal("bob"),
+        None,
+    ));
+    store.insert(Quad::new(alice, age, Literal::from(-1), None));
+    store.insert(QuadRef::new(alice, knows, alice, None));
+    store.remove(QuadRef::new(
+        alice,
+        name,
+        LiteralRef::new_simple_literal("Alice"),
+        None,
+    ));
+
+    let report = store.validate(&shapes).unwrap();
+    assert!(!report.conforms());
+    let mut components = report
+        .results()
+        .iter()
+        .map(|r| r.source_constraint_component().as_str())
+        .collect::<Vec<_>>();
+    components.sort_unstable();
+    assert_eq!(
+        components,
+        vec![
+            sh::MIN_COUNT_CONSTRAINT_COMPONENT.as_str(),
+            sh::MIN_INCLUSIVE_CONSTRAINT_COMPONENT.as_str(),
+            sh::PATTERN_CONSTRAINT_COMPONENT.as_str(),
+            sh::SPARQL_CONSTRAINT_COMPONENT.as_str(),
+        ]
+    );
+    assert_eq!(
+        report
+            .results()
+            .iter()
+            .find(|r| r.source_constraint_component().as_ref() == sh::SPARQL_CONSTRAINT_COMPONENT)
+            .unwrap()
+            .messages(),
+        &[Literal::new_simple_literal(
+            "http://example.com/alice knows itself"
+        )]
+    );
+
+    // Validation of an empty named graph
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    assert!(store.validate_graph(&shapes, g).unwrap().conforms());
+}
+
+#[test]
+fn shacl_sparql_constraint_pre_binding() {
+    // $this is also pre-bound inside of the subqueries
+    let shapes = ShapesGraph::parse(
+        br#"
+@prefix sh: <http://www.w3.org/ns/shacl#> .
+@prefix ex: <http://example.com/> .
+
+ex:PersonShape a sh:NodeShape ;
+    sh:targetClass ex:Person ;
+    sh:sparql [
+        sh:select "SELECT $this WHERE { { SELECT $this WHERE { $this <http://example.com/knows> ?o } LIMIT 1 } }"
+    ] .
+"#
+        .as_ref(),
+        GraphFormat::Turtle,
+        None,
+    )
+    .unwrap();
+
+    let store = MemoryStore::new();
+    store
+        .load_graph(
+            br#"
+@prefix ex: <http://example.com/> .
+ex:alice a ex:Person ; ex:knows ex:bob .
+ex:bob a ex:Person ; ex:knows ex:alice .
+ex:carol a ex:Person .
+"#
+            .as_ref(),
+            GraphFormat::Turtle,
+            GraphNameRef::DefaultGraph,
+            None,
+        )
+        .unwrap();
+    let report = store.validate(&shapes).unwrap();
+    let mut focus_nodes = report
+        .results()
+        .iter()
+        .map(|r| r.focus_node().to_string())
+        .collect::<Vec<_>>();
+    focus_nodes.sort_unstable();
+    assert_eq!(
+        focus_nodes,
+        vec!["<http://example.com/alice>", "<http://example.com/bob>"]
+    );
+}
+
+#[test]
+fn change_subscription() {
+    use crate::model::vocab::{rdf, rdfs};
+
+    let store = MemoryStore::new();
+    let ex = NamedNodeRef::new_unchecked("http://example.com/ex");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    let before = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    store.insert(before);
+    let mut changes = store.subscribe();
+
+    // Simple writes
+    let quad = QuadRef::new(ex, ex, ex, g);
+    store.insert(quad);
+    store.insert(quad);
+    store.remove(quad);
+    store.remove(quad);
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(batch.sequence_number(), 1);
+    assert_eq!(batch.inserted(), &[quad.into_owned()]);
+    assert!(batch.removed().is_empty());
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(batch.sequence_number(), 2);
+    assert!(batch.inserted().is_empty());
+    assert_eq!(batch.removed(), &[quad.into_owned()]);
+    assert!(changes.try_recv().is_none());
+
+    // A transaction only contains the net changes
+    store
+        .transaction(|t| {
+            t.insert(quad.into_owned());
+            t.remove(quad.into_owned());
+            t.remove(before.into_owned());
+            t.insert(before.into_owned());
+            t.insert(QuadRef::new(ex, rdf::TYPE, ex, g).into_owned());
+            Ok::<_, Infallible>(())
+        })
+        .unwrap();
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(batch.sequence_number(), 3);
+    assert_eq!(
+        batch.inserted(),
+        &[QuadRef::new(ex, rdf::TYPE, ex, g).into_owned()]
+    );
+    assert!(batch.removed().is_empty());
+
+    // The inferred quads are ignored
+    store.set_entailment_regime(Some(EntailmentRegime::Rdfs));
+    store.insert(QuadRef::new(ex, rdfs::SUB_CLASS_OF, rdfs::RESOURCE, g));
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(
+        batch.inserted(),
+        &[QuadRef::new(ex, rdfs::SUB_CLASS_OF, rdfs::RESOURCE, g).into_owned()]
+    );
+    assert!(changes.try_recv().is_none());
+
+    // Clear
+    store.clear();
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(batch.sequence_number(), 5);
+    assert!(batch.inserted().is_empty());
+    assert_eq!(batch.removed().len(), 3);
+    store.clear();
+    assert!(changes.try_recv().is_none());
+
+    // Dropped subscriptions are not fed anymore
+    drop(changes);
+    store.insert(quad);
+    assert!(!store.changes.is_watched());
+}
+
+#[test]
+fn text_search() -> Result<(), EvaluationError> {
+    use crate::model::vocab::{rdfs, xsd};
+    use crate::sparql::{EvaluationError, QueryResults, QuerySolution};
+
+    let store = MemoryStore::new();
+    let a = NamedNodeRef::new_unchecked("http://example.com/a");
+    let b = NamedNodeRef::new_unchecked("http://example.com/b");
+    let a_label = QuadRef::new(
+        a,
+        rdfs::LABEL,
+        LiteralRef::new_simple_literal("Red apple"),
+        None,
+    );
+    store.insert(a_label);
+    store.insert(QuadRef::new(
+        b,
+        rdfs::LABEL,
+        LiteralRef::new_language_tagged_literal_unchecked("Red car", "en"),
+        None,
+    ));
+    store.insert(QuadRef::new(
+        b,
+        rdfs::COMMENT,
+        LiteralRef::new_simple_literal("A red car that is really red and that is not an apple"),
+        None,
+    ));
+    let select = |pattern: &str| -> Result<Vec<QuerySolution>, EvaluationError> {
+        if let QueryResults::Solutions(solutions) = store.query(
+            format!(
+                "PREFIX text: <http://jena.apache.org/text#> PREFIX rdfs: <{}> SELECT * WHERE {{ {} }}",
+                "http://www.w3.org/2000/01/rdf-schema#", pattern
+            )
+            .as_str(),
+        )? {
+            solutions.collect()
+        } else {
+            unreachable!()
+        }
+    };
+    let subjects = |pattern: &str| -> Result<Vec<Term>, EvaluationError> {
+        Ok(select(pattern)?
+            .into_iter()
+            .filter_map(|solution| solution.get("s").cloned())
+            .collect())
+    };
+
+    // The index is not enabled
+    assert!(select("?s text:query \"red\"").is_err());
+
+    store.set_text_index(true);
+    assert!(store.is_text_index_enabled());
+    assert_eq!(subjects("?s text:query \"RED\"")?.len(), 3);
+    assert_eq!(
+        subjects("?s text:query (\"red apple\")")?,
+        vec![b.into_owned().into(), a.into_owned().into()]
+    );
+    assert_eq!(
+        subjects("?s text:query (\"red\" 1)")?,
+        vec![b.into_owned().into()]
+    );
+    assert_eq!(subjects("?s text:query (rdfs:label \"red\")")?.len(), 2);
+    assert_eq!(
+        subjects("?s text:query \"red\" ; rdfs:label \"Red apple\"")?,
+        vec![a.into_owned().into()]
+    );
+    assert!(subjects("?s text:query \"blue\"")?.is_empty());
+
+    let solutions = select("(?s ?score ?literal) text:query (rdfs:label \"apple\")")?;
+    assert_eq!(solutions.len(), 1);
+    assert_eq!(
+        solutions[0].get("literal"),
+        Some(
+            &LiteralRef::new_simple_literal("Red apple")
+                .into_owned()
+                .into()
+        )
+    );
+    if let Some(Term::Literal(score)) = solutions[0].get("score") {
+        assert_eq!(score.datatype(), xsd::DOUBLE);
+    } else {
+        panic!("The score should be a literal")
+    }
+
+    // The index is maintained
+    store.remove(a_label);
+    assert_eq!(
+        subjects("?s text:query \"apple\"")?,
+        vec![b.into_owned().into()]
+    );
+    store.clear();
+    assert!(store.is_text_index_enabled());
+    assert!(subjects("?s text:query \"red\"")?.is_empty());
+    Ok(())
+}
+
+#[test]
+fn memory_limit() -> Result<(), EvaluationError> {
+    use crate::sparql::{EvaluationError, QueryOptions, QueryResults};
+    use std::fs;
+
+    let store = MemoryStore::new();
+    let p = NamedNodeRef::new_unchecked("http://example.com/p");
+    for i in 0..200 {
+        store.insert(QuadRef::new(
+            NamedNodeRef::new_unchecked(&format!("http://example.com/s{}", i)),
+            p,
+            &Literal::new_language_tagged_literal_unchecked(format!("\"{}\"\n", i % 50), "en"),
+            None,
+        ));
+    }
+    let spill_directory =
+        std::env::temp_dir().join(format!("oxigraph-test-spill-{:x}", rand::random::<u64>()));
+    fs::create_dir(&spill_directory)?;
+    let select =
+        |query: &str, options: QueryOptions| -> Result<Vec<Vec<Option<Term>>>, EvaluationError> {
+            if let QueryResults::Solutions(solutions) = store.query_opt(
+                format!("PREFIX ex: <http://example.com/> {}", query).as_str(),
+                options,
+            )? {
+                solutions
+                    .map(|solution| Ok(solution?.values().map(|v| v.cloned()).collect()))
+                    .collect()
+            } else {
+                unreachable!()
+            }
+        };
+
+    for query in &[
+        "SELECT ?s ?o WHERE { ?s ex:p ?o } ORDER BY DESC(?o) ?s",
+        "SELECT DISTINCT ?o WHERE { ?s ex:p ?o } ORDER BY STR(?o)",
+        "SELECT ?o (COUNT(?s) AS ?c) (MAX(?s) AS ?m) WHERE { ?s ex:p ?o } GROUP BY ?o ORDER BY STR(?o)",
+        "SELECT (COUNT(*) AS ?c) WHERE { ?s ex:q ?o }",
+    ] {
+        let expected = select(query, QueryOptions::default())?;
+        let spilled = select(
+            query,
+            QueryOptions::default()
+                .with_memory_limit(256)
+                .with_spill_directory(&spill_directory),
+        )?;
+        assert_eq!(spilled, expected, "{}", query);
+        // The temporary files are removed after the evaluation
+        assert!(fs::read_dir(&spill_directory)?.next().is_none());
+    }
+    assert_eq!(
+        select(
+            "SELECT ?o (COUNT(?s) AS ?c) WHERE { ?s ex:p ?o } GROUP BY ?o ORDER BY ?o",
+            QueryOptions::default()
+        )?
+        .len(),
+        50
+    );
+    assert!(matches!(
+        select(
+            "SELECT ?s WHERE { ?s ex:p ?o } ORDER BY ?s",
+            QueryOptions::default()
+                .with_memory_limit(256)
+                .without_spilling()
+        ),
+        Err(EvaluationError::MemoryLimitExceeded)
+    ));
+    fs::remove_dir(&spill_directory)?;
+    Ok(())
+}
+
+#[test]
+fn memory_limit_spilled_sort_and_group() -> Result<(), EvaluationError> {
+    use crate::sparql::{EvaluationError, QueryOptions, QueryResults};
+    use std::fs;
+
+    let store = MemoryStore::new();
+    let p = NamedNodeRef::new_unchecked("http://example.com/p");
+    for i in 0..300 {
+        // Many solutions share the same object to check that the ties keep their input order
+        store.insert(QuadRef::new(
+            NamedNodeRef::new_unchecked(&format!("http://example.com/s{}", (i * 7) % 300)),
+            p,
+            &Literal::from((i % 13) as i64),
+            None,
+        ));
+    }
+    let spill_directory =
+        std::env::temp_dir().join(format!("oxigraph-test-spill-{:x}", rand::random::<u64>()));
+    fs::create_dir(&spill_directory)?;
+    let select = |query: &str, options: QueryOptions| -> Result<Vec<String>, EvaluationError> {
+        if let QueryResults::Solutions(solutions) = store.query_opt(
+            format!("PREFIX ex: <http://example.com/> {}", query).as_str(),
+            options,
+        )? {
+            solutions
+                .map(|solution| {
+                    Ok(solution?
+                        .values()
+                        .map(|v| v.map_or_else(String::new, |v| v.to_string()))
+                        .collect::<Vec<_>>()
+                        .join(" "))
+                })
+                .collect()
+        } else {
+            unreachable!()
+        }
+    };
+
+    for (query, ordered) in &[
+        ("SELECT ?s ?o WHERE { ?s ex:p ?o } ORDER BY ?o", true),
+        ("SELECT ?s ?o WHERE { ?s ex:p ?o } ORDER BY DESC(?o) STR(?s)", true),
+        (
+            "SELECT ?o (COUNT(?s) AS ?c) (MIN(STR(?s)) AS ?min) (SUM(?o) AS ?sum) WHERE { ?s ex:p ?o } GROUP BY ?o",
+            false,
+        ),
+        (
+            "SELECT ?g (COUNT(DISTINCT ?o) AS ?c) WHERE { ?s ex:p ?o BIND(STRLEN(STR(?s)) AS ?g) } GROUP BY ?g",
+            false,
+        ),
+    ] {
+        let limited = QueryOptions::default().with_memory_limit(256);
+        // The memory limit is reached by the query
+        assert!(
+            matches!(
+                select(query, limited.clone().without_spilling()),
+                Err(EvaluationError::MemoryLimitExceeded)
+            ),
+            "{}",
+            query
+        );
+        let mut expected = select(query, QueryOptions::default())?;
+        let mut spilled = select(query, limited.with_spill_directory(&spill_directory))?;
+        if !ordered {
+            // The order of the groups is not defined
+            expected.sort();
+            spilled.sort();
+        }
+        assert_eq!(spilled, expected, "{}", query);
+    }
+    fs::remove_dir(&spill_directory)?;
+    Ok(())
+}
+
+#[test]
+fn load_with_prefixes() -> Result<(), io::Error> {
+    let data = "@prefix ex: <http://example.com/> .
+@prefix schema: <http://schema.org/> .
+ex:g { ex:s a schema:Person . }
+@prefix ex: <http://example.org/> .
+";
+    let store = MemoryStore::new();
+    let prefixes = store.load_dataset_with_prefixes(data.as_bytes(), DatasetFormat::TriG, None)?;
+    assert_eq!(
+        prefixes.into_iter().collect::<Vec<_>>(),
+        [
+            ("ex".to_owned(), "http://example.org/".to_owned()),
+            ("schema".to_owned(), "http://schema.org/".to_owned())
+        ]
+    );
+    assert_eq!(store.len(), 1);
+
+    let prefixes = store.load_graph_with_prefixes(
+        b"<http://example.com/s> <http://example.com/p> <http://example.com/o> .".as_ref(),
+        GraphFormat::NTriples,
+        GraphNameRef::DefaultGraph,
+        None,
+    )?;
+    assert!(prefixes.is_empty());
+    assert_eq!(store.len(), 2);
+    Ok(())
+}
+
+#[test]
+fn load_lenient() -> Result<(), io::Error> {
+    let store = MemoryStore::new();
+    let mut errors = Vec::new();
+    store.load_dataset_lenient(
+        "@prefix ex: <http://example.com/> .
+ex:g { ex:s ex:p ex:o1 . ex:s ex:p \"o2 .
+ex:s ex:p ex:o3 . ex:s ex:p ex:o4 }
+ex:s ex:p ex:o5 ; ex:p .
+ex:s ex:p ex:o6 ."
+            .as_bytes(),
+        DatasetFormat::TriG,
+        None,
+        |e| errors.push(e),
+    )?;
+    let ex = |name: &str| NamedNode::new_unchecked(format!("http://example.com/{}", name));
+    // The statement after the invalid string is skipped too because the string contains its end
+    assert_eq!(store.len(), 3);
+    assert!(store.contains(QuadRef::new(&ex("s"), &ex("p"), &ex("o1"), &ex("g"))));
+    assert!(store.contains(QuadRef::new(&ex("s"), &ex("p"), &ex("o4"), &ex("g"))));
+    assert!(store.contains(QuadRef::new(&ex("s"), &ex("p"), &ex("o6"), None)));
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].to_string().contains("on line 2"));
+    assert!(errors[1].to_string().contains("on line 4"));
+
+    // RDF/XML does not support recovery
+    assert!(store
+        .load_graph_lenient(
+            b"<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"></rdf:Foo>"
+                .as_ref(),
+            GraphFormat::RdfXml,
+            GraphNameRef::DefaultGraph,
+            None,
+            |_| (),
+        )
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn load_from_path_format_detection() -> Result<(), io::Error> {
+    use std::fs;
+
+    let directory =
+        std::env::temp_dir().join(format!("oxigraph-test-load-{:x}", rand::random::<u64>()));
+    fs::create_dir(&directory)?;
+    let files = [
+        (
+            "data.ttl",
+            "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o1 .",
+        ),
+        (
+            "data.unknown",
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o2> <http://example.com/g> .\n",
+        ),
+        ("data", "<http://example.com/s> <http://example.com/p> <http://example.com/o3> .\n"),
+        ("data.bin", "\u{0}\u{1}"),
+    ];
+    for (name, content) in &files {
+        fs::write(directory.join(name), content)?;
+    }
+    let store = MemoryStore::new();
+    store.load_from_path(directory.join("data.ttl"), None)?;
+    store.load_from_path(directory.join("data.unknown"), None)?;
+    store.load_from_path(directory.join("data"), None)?;
+    let error = store
+        .load_from_path(directory.join("data.bin"), None)
+        .unwrap_err();
+    fs::remove_dir_all(&directory)?;
+
+    let ex = |name: &str| NamedNode::new_unchecked(format!("http://example.com/{}", name));
+    assert_eq!(store.len(), 3);
+    assert!(store.contains(QuadRef::new(&ex("s"), &ex("p"), &ex("o1"), None)));
+    assert!(store.contains(QuadRef::new(&ex("s"), &ex("p"), &ex("o2"), &ex("g"))));
+    assert!(store.contains(QuadRef::new(&ex("s"), &ex("p"), &ex("o3"), None)));
+    assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+    Ok(())
+}
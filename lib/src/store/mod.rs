@@ -2,6 +2,8 @@
 
 #[cfg(any(feature = "rocksdb", feature = "sled"))]
 mod binary_encoder;
+mod changes;
+mod inference;
 pub mod memory;
 pub(crate) mod numeric_encoder;
 #[cfg(feature = "rocksdb")]
@@ -11,7 +13,12 @@ pub mod sled;
 pub(crate) mod small_string;
 #[cfg(feature = "sophia")]
 mod sophia;
+#[cfg(any(feature = "rocksdb", feature = "sled"))]
+mod statistics;
+pub(crate) mod text;
 
+pub use crate::store::changes::{ChangeBatch, ChangeSubscription};
+pub use crate::store::inference::EntailmentRegime;
 pub use crate::store::memory::MemoryStore;
 #[cfg(feature = "rocksdb")]
 pub use crate::store::rocksdb::RocksDbStore;
@@ -19,18 +26,21 @@ pub use crate::store::rocksdb::RocksDbStore;
 pub use crate::store::sled::SledStore;
 
 use crate::error::invalid_input_error;
-use crate::io::{DatasetFormat, DatasetSerializer, GraphFormat, GraphSerializer};
+use crate::io::compression::DecompressReader;
+use crate::io::sniff::{sniff, RdfFormat};
+use crate::io::{
+    DatasetFormat, DatasetParser, DatasetSerializer, GraphFormat, GraphParser, GraphSerializer,
+};
 use crate::model::*;
 use crate::store::numeric_encoder::*;
-use oxiri::Iri;
-use rio_api::parser::{QuadsParser, TriplesParser};
-use rio_turtle::{NQuadsParser, NTriplesParser, TriGParser, TurtleError, TurtleParser};
-use rio_xml::{RdfXmlError, RdfXmlParser};
-use std::collections::HashMap;
+use crate::store::text::TextMatch;
+use std::collections::BTreeMap;
 use std::convert::Infallible;
+use std::fs::File;
 use std::io;
-use std::io::{BufRead, Write};
+use std::io::{BufRead, BufReader, Write};
 use std::iter::Iterator;
+use std::path::Path;
 
 pub(crate) trait ReadableEncodedStore: StrLookup {
     type QuadsIter: Iterator<Item = Result<EncodedQuad<Self::StrId>, Self::Error>> + 'static;
@@ -50,6 +60,35 @@ pub(crate) trait ReadableEncodedStore: StrLookup {
         &self,
         graph_name: EncodedTerm<Self::StrId>,
     ) -> Result<bool, Self::Error>;
+
+    /// Returns a cheap estimate of the number of quads in the store
+    fn encoded_quads_count(&self) -> Result<u64, Self::Error>;
+
+    /// Returns cheap cardinality estimates for the quads using the given predicate
+    fn encoded_predicate_statistics(
+        &self,
+        predicate: EncodedTerm<Self::StrId>,
+    ) -> Result<PredicateStatistics, Self::Error>;
+
+    /// Returns the string literals matching the full-text query sorted by decreasing score
+    /// or `None` if the store has no full-text index
+    fn encoded_text_search(
+        &self,
+        query: &str,
+    ) -> Result<Option<Vec<TextMatch<Self::StrId>>>, Self::Error>;
+}
+
+/// Cardinality estimates about the quads using a given predicate
+///
+/// They are used by the query planner to order the joins.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub(crate) struct PredicateStatistics {
+    /// The number of quads using the predicate
+    pub quads: u64,
+    /// The number of distinct subjects of these quads
+    pub distinct_subjects: u64,
+    /// The number of distinct objects of these quads
+    pub distinct_objects: u64,
 }
 
 pub(crate) trait WritableEncodedStore: StrEncodingAware {
@@ -81,54 +120,46 @@ pub(crate) fn load_graph<S: WritableEncodedStore + StrContainer>(
     format: GraphFormat,
     to_graph_name: GraphNameRef<'_>,
     base_iri: Option<&str>,
-) -> Result<(), StoreOrParseError<S::Error>> {
-    let base_iri = if let Some(base_iri) = base_iri {
-        Some(Iri::parse(base_iri.into()).map_err(invalid_input_error)?)
-    } else {
-        None
-    };
-    match format {
-        GraphFormat::NTriples => {
-            load_from_triple_parser(store, NTriplesParser::new(reader), to_graph_name)
-        }
-        GraphFormat::Turtle => {
-            load_from_triple_parser(store, TurtleParser::new(reader, base_iri), to_graph_name)
-        }
-        GraphFormat::RdfXml => {
-            load_from_triple_parser(store, RdfXmlParser::new(reader, base_iri), to_graph_name)
-        }
+    mut on_error: Option<&mut dyn FnMut(io::Error)>,
+) -> Result<BTreeMap<String, String>, StoreOrParseError<S::Error>> {
+    let mut parser = GraphParser::from_format(format);
+    if let Some(base_iri) = base_iri {
+        parser = parser
+            .with_base_iri(base_iri)
+            .map_err(invalid_input_error)?;
+    }
+    if on_error.is_some() {
+        parser = parser.with_error_recovery();
     }
-}
-
-fn load_from_triple_parser<S: WritableEncodedStore + StrContainer, P: TriplesParser>(
-    store: &mut S,
-    mut parser: P,
-    to_graph_name: GraphNameRef<'_>,
-) -> Result<(), StoreOrParseError<S::Error>>
-where
-    StoreOrParseError<S::Error>: From<P::Error>,
-{
-    let mut bnode_map = HashMap::default();
     let to_graph_name = store
         .encode_graph_name(to_graph_name)
         .map_err(StoreOrParseError::Store)?;
-    parser.parse_all(&mut move |t| {
+    let mut triples = parser.read_triples(reader)?;
+    let recovers = triples.recovers_from_errors();
+    for triple in &mut triples {
+        let triple = match triple {
+            Ok(triple) => triple,
+            Err(error) => {
+                report_syntax_error(error, recovers, &mut on_error)?;
+                continue;
+            }
+        };
         let quad = store
-            .encode_rio_triple_in_graph(t, to_graph_name, &mut bnode_map)
+            .encode_triple_in_graph(triple.as_ref(), to_graph_name)
             .map_err(StoreOrParseError::Store)?;
         store
             .insert_encoded(&quad)
             .map_err(StoreOrParseError::Store)?;
-        Ok(())
-    })
+    }
+    Ok(owned_prefixes(triples.prefixes()))
 }
 
 fn dump_graph(
     triples: impl Iterator<Item = Result<Triple, io::Error>>,
     writer: impl Write,
-    format: GraphFormat,
+    serializer: impl Into<GraphSerializer>,
 ) -> Result<(), io::Error> {
-    let mut writer = GraphSerializer::from_format(format).triple_writer(writer)?;
+    let mut writer = serializer.into().triple_writer(writer)?;
     for triple in triples {
         writer.write(&triple?)?;
     }
@@ -140,43 +171,104 @@ fn load_dataset<S: WritableEncodedStore + StrContainer>(
     reader: impl BufRead,
     format: DatasetFormat,
     base_iri: Option<&str>,
+    mut on_error: Option<&mut dyn FnMut(io::Error)>,
+) -> Result<BTreeMap<String, String>, StoreOrParseError<S::Error>> {
+    let mut parser = DatasetParser::from_format(format);
+    if let Some(base_iri) = base_iri {
+        parser = parser
+            .with_base_iri(base_iri)
+            .map_err(invalid_input_error)?;
+    }
+    if on_error.is_some() {
+        parser = parser.with_error_recovery();
+    }
+    let mut quads = parser.read_quads(reader)?;
+    let recovers = quads.recovers_from_errors();
+    for quad in &mut quads {
+        let quad = match quad {
+            Ok(quad) => quad,
+            Err(error) => {
+                report_syntax_error(error, recovers, &mut on_error)?;
+                continue;
+            }
+        };
+        let quad = store
+            .encode_quad(quad.as_ref())
+            .map_err(StoreOrParseError::Store)?;
+        store
+            .insert_encoded(&quad)
+            .map_err(StoreOrParseError::Store)?;
+    }
+    Ok(owned_prefixes(quads.prefixes()))
+}
+
+/// Loads a file guessing its format from its extension or, if it is not known, from its content
+fn load_from_path<S: WritableEncodedStore + StrContainer>(
+    store: &mut S,
+    path: &Path,
+    base_iri: Option<&str>,
 ) -> Result<(), StoreOrParseError<S::Error>> {
-    let base_iri = if let Some(base_iri) = base_iri {
-        Some(Iri::parse(base_iri.into()).map_err(invalid_input_error)?)
+    let mut reader = DecompressReader::new(BufReader::new(File::open(path)?))?;
+    let format = if let Some(format) = GraphFormat::from_path(path) {
+        RdfFormat::Graph(format)
+    } else if let Some(format) = DatasetFormat::from_path(path) {
+        RdfFormat::Dataset(format)
+    } else if let Some(format) = sniff(reader.fill_buf()?) {
+        format
     } else {
-        None
+        return Err(invalid_input_error(format!(
+            "The format of {} could not be guessed from its extension or its content",
+            path.display()
+        ))
+        .into());
     };
     match format {
-        DatasetFormat::NQuads => load_from_quad_parser(store, NQuadsParser::new(reader)),
-        DatasetFormat::TriG => load_from_quad_parser(store, TriGParser::new(reader, base_iri)),
+        RdfFormat::Graph(format) => {
+            load_graph(
+                store,
+                reader,
+                format,
+                GraphNameRef::DefaultGraph,
+                base_iri,
+                None,
+            )?;
+        }
+        RdfFormat::Dataset(format) => {
+            load_dataset(store, reader, format, base_iri, None)?;
+        }
     }
+    Ok(())
 }
 
-fn load_from_quad_parser<S: WritableEncodedStore + StrContainer, P: QuadsParser>(
-    store: &mut S,
-    mut parser: P,
-) -> Result<(), StoreOrParseError<S::Error>>
-where
-    StoreOrParseError<S::Error>: From<P::Error>,
-{
-    let mut bnode_map = HashMap::default();
-    parser.parse_all(&mut move |q| {
-        let quad = store
-            .encode_rio_quad(q, &mut bnode_map)
-            .map_err(StoreOrParseError::Store)?;
-        store
-            .insert_encoded(&quad)
-            .map_err(StoreOrParseError::Store)?;
-        Ok(())
-    })
+/// Gives the syntax error to `on_error` if the parser is able to resume after it or returns it
+fn report_syntax_error(
+    error: io::Error,
+    recovers: bool,
+    on_error: &mut Option<&mut dyn FnMut(io::Error)>,
+) -> Result<(), io::Error> {
+    match on_error {
+        Some(on_error) if recovers && error.kind() == io::ErrorKind::InvalidData => {
+            on_error(error);
+            Ok(())
+        }
+        _ => Err(error),
+    }
+}
+
+fn owned_prefixes<'a>(
+    prefixes: impl Iterator<Item = (&'a str, &'a str)>,
+) -> BTreeMap<String, String> {
+    prefixes
+        .map(|(name, iri)| (name.to_owned(), iri.to_owned()))
+        .collect()
 }
 
 fn dump_dataset(
     quads: impl Iterator<Item = Result<Quad, io::Error>>,
     writer: impl Write,
-    format: DatasetFormat,
+    serializer: impl Into<DatasetSerializer>,
 ) -> Result<(), io::Error> {
-    let mut writer = DatasetSerializer::from_format(format).quad_writer(writer)?;
+    let mut writer = serializer.into().quad_writer(writer)?;
     for quad in quads {
         writer.write(&quad?)?;
     }
@@ -188,18 +280,6 @@ pub(crate) enum StoreOrParseError<S> {
     Parse(io::Error),
 }
 
-impl<S> From<TurtleError> for StoreOrParseError<S> {
-    fn from(error: TurtleError) -> Self {
-        Self::Parse(error.into())
-    }
-}
-
-impl<S> From<RdfXmlError> for StoreOrParseError<S> {
-    fn from(error: RdfXmlError) -> Self {
-        Self::Parse(error.into())
-    }
-}
-
 impl<S> From<io::Error> for StoreOrParseError<S> {
     fn from(error: io::Error) -> Self {
         Self::Parse(error)
@@ -233,7 +313,7 @@ type QuadPattern<I> = (
 
 fn get_encoded_quad_pattern<E: ReadEncoder>(
     encoder: &E,
-    subject: Option<NamedOrBlankNodeRef<'_>>,
+    subject: Option<SubjectRef<'_>>,
     predicate: Option<NamedNodeRef<'_>>,
     object: Option<TermRef<'_>>,
     graph_name: Option<GraphNameRef<'_>>,
@@ -241,7 +321,7 @@ fn get_encoded_quad_pattern<E: ReadEncoder>(
     Ok(Some((
         if let Some(subject) = transpose(
             subject
-                .map(|t| encoder.get_encoded_named_or_blank_node(t))
+                .map(|t| encoder.get_encoded_subject(t))
                 .transpose()?,
         ) {
             subject
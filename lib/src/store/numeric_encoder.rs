@@ -5,14 +5,13 @@ use crate::model::xsd::*;
 use crate::model::*;
 use crate::sparql::EvaluationError;
 use crate::store::small_string::SmallString;
-use rand::random;
 use rio_api::model as rio;
-use std::collections::HashMap;
 use std::convert::{TryFrom, TryInto};
 use std::error::Error;
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::str::FromStr;
 use std::{fmt, io, str};
 
 pub trait StrId: Eq + Debug + Copy + Hash {}
@@ -74,6 +73,9 @@ pub enum EncodedTerm<I: StrId> {
     DurationLiteral(Duration),
     YearMonthDurationLiteral(YearMonthDuration),
     DayTimeDurationLiteral(DayTimeDuration),
+    Triple {
+        triple_id: I,
+    },
 }
 
 impl<I: StrId> PartialEq for EncodedTerm<I> {
@@ -187,6 +189,14 @@ impl<I: StrId> PartialEq for EncodedTerm<I> {
             (Self::DurationLiteral(a), Self::DurationLiteral(b)) => a == b,
             (Self::YearMonthDurationLiteral(a), Self::YearMonthDurationLiteral(b)) => a == b,
             (Self::DayTimeDurationLiteral(a), Self::DayTimeDurationLiteral(b)) => a == b,
+            (
+                Self::Triple {
+                    triple_id: triple_id_a,
+                },
+                Self::Triple {
+                    triple_id: triple_id_b,
+                },
+            ) => triple_id_a == triple_id_b,
             (_, _) => false,
         }
     }
@@ -250,53 +260,64 @@ impl<I: StrId> Hash for EncodedTerm<I> {
             Self::DurationLiteral(value) => value.hash(state),
             Self::YearMonthDurationLiteral(value) => value.hash(state),
             Self::DayTimeDurationLiteral(value) => value.hash(state),
+            Self::Triple { triple_id } => triple_id.hash(state),
         }
     }
 }
 
 impl<I: StrId> EncodedTerm<I> {
     pub fn is_named_node(&self) -> bool {
-        matches!(self,
-            Self::NamedNode { .. })
+        matches!(self, Self::NamedNode { .. })
     }
 
     pub fn is_blank_node(&self) -> bool {
-        matches!(self,
+        matches!(
+            self,
             Self::NumericalBlankNode { .. }
-            | Self::SmallBlankNode { .. }
-            | Self::BigBlankNode { .. })
+                | Self::SmallBlankNode { .. }
+                | Self::BigBlankNode { .. }
+        )
     }
 
     pub fn is_literal(&self) -> bool {
-        matches!(self,
+        matches!(
+            self,
             Self::SmallStringLiteral { .. }
-            | Self::BigStringLiteral { .. }
-            | Self::SmallSmallLangStringLiteral { .. }
-            | Self::SmallBigLangStringLiteral { .. }
-            | Self::BigSmallLangStringLiteral { .. }
-            | Self::BigBigLangStringLiteral { .. }
-            | Self::SmallTypedLiteral { .. }
-            | Self::BigTypedLiteral { .. }
-            | Self::BooleanLiteral(_)
-            | Self::FloatLiteral(_)
-            | Self::DoubleLiteral(_)
-            | Self::IntegerLiteral(_)
-            | Self::DecimalLiteral(_)
-            | Self::DateTimeLiteral(_)
-            | Self::TimeLiteral(_)
-            | Self::DateLiteral(_)
-            | Self::GYearMonthLiteral(_)
-            | Self::GYearLiteral(_)
-            | Self::GMonthDayLiteral(_)
-            | Self::GDayLiteral(_)
-            | Self::GMonthLiteral(_)
-            | Self::DurationLiteral(_)
-            | Self::YearMonthDurationLiteral(_)
-            | Self::DayTimeDurationLiteral(_))
+                | Self::BigStringLiteral { .. }
+                | Self::SmallSmallLangStringLiteral { .. }
+                | Self::SmallBigLangStringLiteral { .. }
+                | Self::BigSmallLangStringLiteral { .. }
+                | Self::BigBigLangStringLiteral { .. }
+                | Self::SmallTypedLiteral { .. }
+                | Self::BigTypedLiteral { .. }
+                | Self::BooleanLiteral(_)
+                | Self::FloatLiteral(_)
+                | Self::DoubleLiteral(_)
+                | Self::IntegerLiteral(_)
+                | Self::DecimalLiteral(_)
+                | Self::DateTimeLiteral(_)
+                | Self::TimeLiteral(_)
+                | Self::DateLiteral(_)
+                | Self::GYearMonthLiteral(_)
+                | Self::GYearLiteral(_)
+                | Self::GMonthDayLiteral(_)
+                | Self::GDayLiteral(_)
+                | Self::GMonthLiteral(_)
+                | Self::DurationLiteral(_)
+                | Self::YearMonthDurationLiteral(_)
+                | Self::DayTimeDurationLiteral(_)
+        )
+    }
+
+    pub fn is_triple(&self) -> bool {
+        matches!(self, Self::Triple { .. })
     }
 
     pub fn is_unknown_typed_literal(&self) -> bool {
-        matches!(self, Self::SmallTypedLiteral { .. } | Self::BigTypedLiteral { .. })
+        matches!(
+            self,
+            Self::SmallTypedLiteral { .. } | Self::BigTypedLiteral { .. }
+        )
     }
 
     pub fn is_default_graph(&self) -> bool {
@@ -367,6 +388,9 @@ impl<I: StrId> EncodedTerm<I> {
             Self::DurationLiteral(value) => EncodedTerm::DurationLiteral(value),
             Self::YearMonthDurationLiteral(value) => EncodedTerm::YearMonthDurationLiteral(value),
             Self::DayTimeDurationLiteral(value) => EncodedTerm::DayTimeDurationLiteral(value),
+            Self::Triple { triple_id } => EncodedTerm::Triple {
+                triple_id: mapping(triple_id),
+            },
         }
     }
 
@@ -437,6 +461,9 @@ impl<I: StrId> EncodedTerm<I> {
             Self::DurationLiteral(value) => EncodedTerm::DurationLiteral(value),
             Self::YearMonthDurationLiteral(value) => EncodedTerm::YearMonthDurationLiteral(value),
             Self::DayTimeDurationLiteral(value) => EncodedTerm::DayTimeDurationLiteral(value),
+            Self::Triple { triple_id } => EncodedTerm::Triple {
+                triple_id: mapping(triple_id)?,
+            },
         })
     }
 }
@@ -753,6 +780,30 @@ pub(crate) trait ReadEncoder: StrEncodingAware {
         }
     }
 
+    fn get_encoded_triple(
+        &self,
+        triple: &Triple,
+    ) -> Result<Option<EncodedTerm<Self::StrId>>, Self::Error> {
+        Ok(Some(EncodedTerm::Triple {
+            triple_id: if let Some(triple_id) = self.get_encoded_str(&triple.to_string())? {
+                triple_id
+            } else {
+                return Ok(None);
+            },
+        }))
+    }
+
+    fn get_encoded_subject(
+        &self,
+        term: SubjectRef<'_>,
+    ) -> Result<Option<EncodedTerm<Self::StrId>>, Self::Error> {
+        match term {
+            SubjectRef::NamedNode(named_node) => self.get_encoded_named_node(named_node),
+            SubjectRef::BlankNode(blank_node) => self.get_encoded_blank_node(blank_node),
+            SubjectRef::Triple(triple) => self.get_encoded_triple(triple),
+        }
+    }
+
     fn get_encoded_term(
         &self,
         term: TermRef<'_>,
@@ -761,6 +812,7 @@ pub(crate) trait ReadEncoder: StrEncodingAware {
             TermRef::NamedNode(named_node) => self.get_encoded_named_node(named_node),
             TermRef::BlankNode(blank_node) => self.get_encoded_blank_node(blank_node),
             TermRef::Literal(literal) => self.get_encoded_literal(literal),
+            TermRef::Triple(triple) => self.get_encoded_triple(triple),
         }
     }
 
@@ -780,7 +832,7 @@ pub(crate) trait ReadEncoder: StrEncodingAware {
         quad: QuadRef<'_>,
     ) -> Result<Option<EncodedQuad<Self::StrId>>, Self::Error> {
         Ok(Some(EncodedQuad {
-            subject: if let Some(subject) = self.get_encoded_named_or_blank_node(quad.subject)? {
+            subject: if let Some(subject) = self.get_encoded_subject(quad.subject)? {
                 subject
             } else {
                 return Ok(None);
@@ -856,11 +908,29 @@ pub(crate) trait WriteEncoder: StrEncodingAware {
         }
     }
 
+    fn encode_triple(&mut self, triple: &Triple) -> Result<EncodedTerm<Self::StrId>, Self::Error> {
+        Ok(EncodedTerm::Triple {
+            triple_id: self.encode_str(&triple.to_string())?,
+        })
+    }
+
+    fn encode_subject(
+        &mut self,
+        term: SubjectRef<'_>,
+    ) -> Result<EncodedTerm<Self::StrId>, Self::Error> {
+        match term {
+            SubjectRef::NamedNode(named_node) => self.encode_named_node(named_node),
+            SubjectRef::BlankNode(blank_node) => self.encode_blank_node(blank_node),
+            SubjectRef::Triple(triple) => self.encode_triple(triple),
+        }
+    }
+
     fn encode_term(&mut self, term: TermRef<'_>) -> Result<EncodedTerm<Self::StrId>, Self::Error> {
         match term {
             TermRef::NamedNode(named_node) => self.encode_named_node(named_node),
             TermRef::BlankNode(blank_node) => self.encode_blank_node(blank_node),
             TermRef::Literal(literal) => self.encode_literal(literal),
+            TermRef::Triple(triple) => self.encode_triple(triple),
         }
     }
 
@@ -877,7 +947,7 @@ pub(crate) trait WriteEncoder: StrEncodingAware {
 
     fn encode_quad(&mut self, quad: QuadRef<'_>) -> Result<EncodedQuad<Self::StrId>, Self::Error> {
         Ok(EncodedQuad {
-            subject: self.encode_named_or_blank_node(quad.subject)?,
+            subject: self.encode_subject(quad.subject)?,
             predicate: self.encode_named_node(quad.predicate)?,
             object: self.encode_term(quad.object)?,
             graph_name: self.encode_graph_name(quad.graph_name)?,
@@ -890,7 +960,7 @@ pub(crate) trait WriteEncoder: StrEncodingAware {
         graph_name: EncodedTerm<Self::StrId>,
     ) -> Result<EncodedQuad<Self::StrId>, Self::Error> {
         Ok(EncodedQuad {
-            subject: self.encode_named_or_blank_node(triple.subject)?,
+            subject: self.encode_subject(triple.subject)?,
             predicate: self.encode_named_node(triple.predicate)?,
             object: self.encode_term(triple.object)?,
             graph_name,
@@ -906,19 +976,6 @@ pub(crate) trait WriteEncoder: StrEncodingAware {
         })
     }
 
-    fn encode_rio_blank_node(
-        &mut self,
-        blank_node: rio::BlankNode<'_>,
-        bnodes_map: &mut HashMap<String, u128>,
-    ) -> Result<EncodedTerm<Self::StrId>, Self::Error> {
-        Ok(if let Some(id) = bnodes_map.get(blank_node.id) {
-            EncodedTerm::NumericalBlankNode { id: *id }
-        } else {
-            let id = random::<u128>();
-            bnodes_map.insert(blank_node.id.to_owned(), id);
-            EncodedTerm::NumericalBlankNode { id }
-        })
-    }
     fn encode_rio_literal(
         &mut self,
         literal: rio::Literal<'_>,
@@ -1024,61 +1081,6 @@ pub(crate) trait WriteEncoder: StrEncodingAware {
         })
     }
 
-    fn encode_rio_named_or_blank_node(
-        &mut self,
-        term: rio::NamedOrBlankNode<'_>,
-        bnodes_map: &mut HashMap<String, u128>,
-    ) -> Result<EncodedTerm<Self::StrId>, Self::Error> {
-        match term {
-            rio::NamedOrBlankNode::NamedNode(named_node) => self.encode_rio_named_node(named_node),
-            rio::NamedOrBlankNode::BlankNode(blank_node) => {
-                self.encode_rio_blank_node(blank_node, bnodes_map)
-            }
-        }
-    }
-
-    fn encode_rio_term(
-        &mut self,
-        term: rio::Term<'_>,
-        bnodes_map: &mut HashMap<String, u128>,
-    ) -> Result<EncodedTerm<Self::StrId>, Self::Error> {
-        match term {
-            rio::Term::NamedNode(named_node) => self.encode_rio_named_node(named_node),
-            rio::Term::BlankNode(blank_node) => self.encode_rio_blank_node(blank_node, bnodes_map),
-            rio::Term::Literal(literal) => self.encode_rio_literal(literal),
-        }
-    }
-
-    fn encode_rio_quad(
-        &mut self,
-        quad: rio::Quad<'_>,
-        bnodes_map: &mut HashMap<String, u128>,
-    ) -> Result<EncodedQuad<Self::StrId>, Self::Error> {
-        Ok(EncodedQuad {
-            subject: self.encode_rio_named_or_blank_node(quad.subject, bnodes_map)?,
-            predicate: self.encode_rio_named_node(quad.predicate)?,
-            object: self.encode_rio_term(quad.object, bnodes_map)?,
-            graph_name: match quad.graph_name {
-                Some(graph_name) => self.encode_rio_named_or_blank_node(graph_name, bnodes_map)?,
-                None => EncodedTerm::DefaultGraph,
-            },
-        })
-    }
-
-    fn encode_rio_triple_in_graph(
-        &mut self,
-        triple: rio::Triple<'_>,
-        graph_name: EncodedTerm<Self::StrId>,
-        bnodes_map: &mut HashMap<String, u128>,
-    ) -> Result<EncodedQuad<Self::StrId>, Self::Error> {
-        Ok(EncodedQuad {
-            subject: self.encode_rio_named_or_blank_node(triple.subject, bnodes_map)?,
-            predicate: self.encode_rio_named_node(triple.predicate)?,
-            object: self.encode_rio_term(triple.object, bnodes_map)?,
-            graph_name,
-        })
-    }
-
     fn encode_str(&mut self, value: &str) -> Result<Self::StrId, Self::Error>;
 }
 
@@ -1165,6 +1167,20 @@ pub(crate) trait Decoder: StrLookup {
         encoded: EncodedTerm<Self::StrId>,
     ) -> Result<Term, DecoderError<Self::Error>>;
 
+    fn decode_subject(
+        &self,
+        encoded: EncodedTerm<Self::StrId>,
+    ) -> Result<Subject, DecoderError<Self::Error>> {
+        match self.decode_term(encoded)? {
+            Term::NamedNode(named_node) => Ok(named_node.into()),
+            Term::BlankNode(blank_node) => Ok(blank_node.into()),
+            Term::Literal(_) => Err(DecoderError::Decoder {
+                msg: "A literal has ben found instead of a named node".to_owned(),
+            }),
+            Term::Triple(triple) => Ok(Subject::Triple(triple)),
+        }
+    }
+
     fn decode_named_or_blank_node(
         &self,
         encoded: EncodedTerm<Self::StrId>,
@@ -1175,6 +1191,9 @@ pub(crate) trait Decoder: StrLookup {
             Term::Literal(_) => Err(DecoderError::Decoder {
                 msg: "A literal has ben found instead of a named node".to_owned(),
             }),
+            Term::Triple(_) => Err(DecoderError::Decoder {
+                msg: "A triple has been found instead of a named node".to_owned(),
+            }),
         }
     }
 
@@ -1190,6 +1209,9 @@ pub(crate) trait Decoder: StrLookup {
             Term::Literal(_) => Err(DecoderError::Decoder {
                 msg: "A literal has ben found instead of a named node".to_owned(),
             }),
+            Term::Triple(_) => Err(DecoderError::Decoder {
+                msg: "A triple has been found instead of a named node".to_owned(),
+            }),
         }
     }
 
@@ -1198,7 +1220,7 @@ pub(crate) trait Decoder: StrLookup {
         encoded: &EncodedQuad<Self::StrId>,
     ) -> Result<Triple, DecoderError<Self::Error>> {
         Ok(Triple::new(
-            self.decode_named_or_blank_node(encoded.subject)?,
+            self.decode_subject(encoded.subject)?,
             self.decode_named_node(encoded.predicate)?,
             self.decode_term(encoded.object)?,
         ))
@@ -1209,7 +1231,7 @@ pub(crate) trait Decoder: StrLookup {
         encoded: &EncodedQuad<Self::StrId>,
     ) -> Result<Quad, DecoderError<Self::Error>> {
         Ok(Quad::new(
-            self.decode_named_or_blank_node(encoded.subject)?,
+            self.decode_subject(encoded.subject)?,
             self.decode_named_node(encoded.predicate)?,
             self.decode_term(encoded.object)?,
             match encoded.graph_name {
@@ -1297,6 +1319,13 @@ impl<S: StrLookup> Decoder for S {
             EncodedTerm::DurationLiteral(value) => Ok(Literal::from(value).into()),
             EncodedTerm::YearMonthDurationLiteral(value) => Ok(Literal::from(value).into()),
             EncodedTerm::DayTimeDurationLiteral(value) => Ok(Literal::from(value).into()),
+            EncodedTerm::Triple { triple_id } => {
+                Ok(Triple::from_str(&get_required_str(self, triple_id)?)
+                    .map_err(|e| DecoderError::Decoder {
+                        msg: format!("Invalid quoted triple serialization in the database: {}", e),
+                    })?
+                    .into())
+            }
         }
     }
 }
@@ -1,30 +1,51 @@
 //! Store based on the [RocksDB](https://rocksdb.org/) key-value database.
 
-use crate::error::invalid_data_error;
-use crate::io::{DatasetFormat, GraphFormat};
+use crate::error::{invalid_data_error, invalid_input_error};
+use crate::io::compression::DecompressReader;
+use crate::io::turtle::{TurtleParser, TurtleSyntax};
+use crate::io::{
+    DatasetFormat, DatasetParser, DatasetSerializer, GraphFormat, GraphParser, GraphSerializer,
+};
 use crate::model::*;
+use crate::shacl::{validate, ShapesGraph, ValidationReport};
 use crate::sparql::{
-    evaluate_query, evaluate_update, EvaluationError, Query, QueryOptions, QueryResults, Update,
-    UpdateOptions,
+    evaluate_query, evaluate_update, explain_query, EvaluationError, Query, QueryExplanation,
+    QueryOptions, QueryResults, Update, UpdateOptions,
 };
 use crate::store::binary_encoder::*;
+use crate::store::changes::{ChangeBatch, ChangeCollector, ChangeFeed, ChangeSubscription};
+use crate::store::inference::{
+    decode_entailment_regime, encode_entailment_regime, materialize, propagate_changes,
+    remove_inferred_quads, EntailmentRegime, InferenceStore, QuadChanges,
+};
 use crate::store::numeric_encoder::{
     Decoder, ReadEncoder, StrContainer, StrEncodingAware, StrLookup, WriteEncoder,
 };
+use crate::store::statistics::{
+    add_delta, decode_counter, encode_counter, read_predicate_statistics, read_quads_count,
+    record_quad_insertion, record_quad_removal, search_text_index, CountersStore,
+};
+use crate::store::text::TextMatch;
 use crate::store::{
-    dump_dataset, dump_graph, get_encoded_quad_pattern, load_dataset, load_graph,
-    ReadableEncodedStore, WritableEncodedStore,
+    dump_dataset, dump_graph, get_encoded_quad_pattern, load_dataset, load_from_path, load_graph,
+    PredicateStatistics, ReadableEncodedStore, WritableEncodedStore,
 };
+use oxiri::Iri;
+use rand::random;
 use rocksdb::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::convert::TryInto;
+use std::fs::{create_dir_all, remove_dir_all};
 use std::io;
 use std::io::{BufRead, Write};
 use std::iter::{once, Once};
 use std::mem::{take, transmute};
-use std::path::Path;
-use std::sync::Arc;
-use std::{fmt, str};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::{fmt, str, thread};
 
 /// Store based on the [RocksDB](https://rocksdb.org/) key-value database.
 /// It encodes a [RDF dataset](https://www.w3.org/TR/rdf11-concepts/#dfn-rdf-dataset) and allows to query it using SPARQL.
@@ -62,6 +83,21 @@ use std::{fmt, str};
 #[derive(Clone)]
 pub struct RocksDbStore {
     db: Arc<DB>,
+    entailment_regime: Arc<AtomicU8>,
+    /// If set, all the reads are done against this snapshot instead of the latest state of the database
+    snapshot: Option<Arc<StaticSnapshot>>,
+    mode: AccessMode,
+    changes: Arc<ChangeFeed>,
+    change_log: Arc<AtomicBool>,
+    text_index: Arc<AtomicBool>,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum AccessMode {
+    ReadWrite,
+    ReadOnly,
+    /// A RocksDB secondary instance following a primary one
+    Secondary,
 }
 
 type EncodedTerm = crate::store::numeric_encoder::EncodedTerm<StrHash>;
@@ -78,27 +114,176 @@ const DSPO_CF: &str = "dspo";
 const DPOS_CF: &str = "dpos";
 const DOSP_CF: &str = "dosp";
 const GRAPHS_CF: &str = "graphs";
-
-const COLUMN_FAMILIES: [&str; 11] = [
-    ID2STR_CF, SPOG_CF, POSG_CF, OSPG_CF, GSPO_CF, GPOS_CF, GOSP_CF, DSPO_CF, DPOS_CF, DOSP_CF,
+const STATS_CF: &str = "stats";
+const INFERRED_CF: &str = "inferred";
+
+const COLUMN_FAMILIES: [&str; 13] = [
+    ID2STR_CF,
+    SPOG_CF,
+    POSG_CF,
+    OSPG_CF,
+    GSPO_CF,
+    GPOS_CF,
+    GOSP_CF,
+    DSPO_CF,
+    DPOS_CF,
+    DOSP_CF,
     GRAPHS_CF,
+    STATS_CF,
+    INFERRED_CF,
 ];
 
+/// Key of the sequence number of the last captured change batch
+const CHANGE_SEQUENCE_KEY: &[u8] = b"oxsequence";
+/// Key of the flag enabling the change log
+const CHANGE_LOG_KEY: &[u8] = b"oxchangelog";
+/// Prefix of the change log entries keys, followed by the big endian sequence number
+const CHANGE_LOG_PREFIX: &[u8] = b"oxchange/";
+/// Key of the flag enabling the full-text index
+const TEXT_INDEX_KEY: &[u8] = b"oxtextindex";
+
 const MAX_TRANSACTION_SIZE: usize = 1024;
+/// Maximal number of quads kept in memory by each bulk loader thread
+const BULK_LOAD_BATCH_SIZE: usize = 1_000_000;
+/// Number of N-Triples or N-Quads bytes in each bulk loader parsing job
+const BULK_LOAD_CHUNK_SIZE: usize = 1 << 20;
+/// Number of already parsed quads in each bulk loader job
+const BULK_LOAD_CHUNK_QUADS: usize = 10_000;
 
 impl RocksDbStore {
     /// Opens a [`RocksDbStore`]()
     pub fn open(path: impl AsRef<Path>) -> Result<Self, io::Error> {
-        let mut options = Options::default();
+        let mut options = Self::db_options();
         options.create_if_missing(true);
         options.create_missing_column_families(true);
+        Self::setup(
+            DB::open_cf(&options, path, &COLUMN_FAMILIES).map_err(map_err)?,
+            AccessMode::ReadWrite,
+        )
+    }
+
+    /// Opens an existing [`RocksDbStore`] in read-only mode.
+    ///
+    /// Several read-only stores and one read-write store could be opened on the same directory at the same time,
+    /// for example by different processes. The read-only stores only see the state of the database when they have been opened.
+    /// All the write operations fail with an error of kind [`PermissionDenied`](std::io::ErrorKind::PermissionDenied).
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::RocksDbStore;
+    /// use oxigraph::model::*;
+    /// # use std::fs::remove_dir_all;
+    ///
+    /// # {
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = RocksDbStore::open("example_read_only.db")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, None))?;
+    ///
+    /// let read_only = RocksDbStore::open_read_only("example_read_only.db")?;
+    /// assert!(read_only.contains(QuadRef::new(ex, ex, ex, None))?);
+    /// assert!(read_only.insert(QuadRef::new(ex, ex, ex, ex)).is_err());
+    /// # };
+    /// # remove_dir_all("example_read_only.db")?;
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn open_read_only(path: impl AsRef<Path>) -> Result<Self, io::Error> {
+        Self::setup(
+            DB::open_cf_for_read_only(&Self::db_options(), path, &COLUMN_FAMILIES, false)
+                .map_err(map_err)?,
+            AccessMode::ReadOnly,
+        )
+    }
+
+    /// Opens an existing [`RocksDbStore`] as a [secondary instance](https://github.com/facebook/rocksdb/wiki/Read-only-and-Secondary-instances).
+    ///
+    /// It is a read-only store that could follow the writes done by the read-write store opened on `primary_path`
+    /// by calling [`catch_up`](RocksDbStore::catch_up()).
+    /// The secondary instance keeps its own logs in `secondary_path`, which must be distinct for each secondary instance.
+    /// All the write operations fail with an error of kind [`PermissionDenied`](std::io::ErrorKind::PermissionDenied).
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::RocksDbStore;
+    /// use oxigraph::model::*;
+    /// # use std::fs::remove_dir_all;
+    ///
+    /// # {
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = RocksDbStore::open("example_primary.db")?;
+    /// let secondary = RocksDbStore::open_secondary("example_primary.db", "example_secondary.db")?;
+    ///
+    /// store.insert(QuadRef::new(ex, ex, ex, None))?;
+    /// secondary.catch_up()?;
+    /// assert!(secondary.contains(QuadRef::new(ex, ex, ex, None))?);
+    /// # };
+    /// # remove_dir_all("example_primary.db")?;
+    /// # remove_dir_all("example_secondary.db")?;
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn open_secondary(
+        primary_path: impl AsRef<Path>,
+        secondary_path: impl AsRef<Path>,
+    ) -> Result<Self, io::Error> {
+        let mut options = Self::db_options();
+        // Required by the secondary instances
+        options.set_max_open_files(-1);
+        Self::setup(
+            DB::open_cf_as_secondary(
+                &options,
+                primary_path.as_ref(),
+                secondary_path.as_ref(),
+                &COLUMN_FAMILIES,
+            )
+            .map_err(map_err)?,
+            AccessMode::Secondary,
+        )
+    }
+
+    /// Makes a store opened with [`open_secondary`](RocksDbStore::open_secondary()) see the latest writes of the primary store.
+    ///
+    /// Fails with an error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) if the store is not a secondary instance.
+    pub fn catch_up(&self) -> Result<(), io::Error> {
+        if self.mode != AccessMode::Secondary {
+            return Err(invalid_input_error(
+                "Only the stores opened with RocksDbStore::open_secondary could catch up with the primary store",
+            ));
+        }
+        self.db.try_catch_up_with_primary().map_err(map_err)?;
+        // The entailment regime and the change log may have been changed by the primary store
+        self.load_entailment_regime()?;
+        self.load_change_settings()
+    }
+
+    /// Returns if the write operations are disallowed because the store has been opened with
+    /// [`open_read_only`](RocksDbStore::open_read_only()) or [`open_secondary`](RocksDbStore::open_secondary()).
+    pub fn is_read_only(&self) -> bool {
+        self.mode != AccessMode::ReadWrite
+    }
+
+    fn db_options() -> Options {
+        let mut options = Options::default();
         options.set_compaction_style(DBCompactionStyle::Universal);
+        options
+    }
 
+    fn setup(db: DB, mode: AccessMode) -> Result<Self, io::Error> {
         let this = Self {
-            db: Arc::new(DB::open_cf(&options, path, &COLUMN_FAMILIES).map_err(map_err)?),
+            db: Arc::new(db),
+            entailment_regime: Arc::new(AtomicU8::new(encode_entailment_regime(None))),
+            snapshot: None,
+            mode,
+            changes: Arc::new(ChangeFeed::default()),
+            change_log: Arc::new(AtomicBool::new(false)),
+            text_index: Arc::new(AtomicBool::new(false)),
         };
 
         let mut version = this.ensure_version()?;
+        if version < LATEST_STORAGE_VERSION && this.is_read_only() {
+            return Err(invalid_data_error(format!(
+                "The RocksDB database is using the outdated encoding version {}. Open it once in read-write mode to migrate it",
+                version
+            )));
+        }
         if version == 0 {
             // We migrate to v1
             let mut transaction = this.auto_batch_writer();
@@ -113,6 +298,17 @@ impl RocksDbStore {
             this.set_version(version)?;
             this.flush()?;
         }
+        if version == 1 {
+            // We migrate to v2
+            this.rebuild_statistics()?;
+            version = 2;
+            this.set_version(version)?;
+            this.flush()?;
+        }
+
+        this.load_entailment_regime()?;
+        this.load_change_settings()?;
+        this.load_text_index()?;
 
         match version {
             _ if version < LATEST_STORAGE_VERSION => Err(invalid_data_error(format!(
@@ -141,11 +337,52 @@ impl RocksDbStore {
     }
 
     fn set_version(&self, version: u64) -> Result<(), io::Error> {
+        self.ensure_writable()?;
         self.db
             .put("oxversion", &version.to_be_bytes())
             .map_err(map_err)
     }
 
+    fn load_entailment_regime(&self) -> Result<(), io::Error> {
+        if let Some(regime) = self.db.get("oxentailment").map_err(map_err)? {
+            let regime = regime.first().copied().unwrap_or_default();
+            decode_entailment_regime(regime)?;
+            self.entailment_regime.store(regime, Ordering::Relaxed);
+        }
+        Ok(())
+    }
+
+    fn load_change_settings(&self) -> Result<(), io::Error> {
+        if let Some(sequence_number) = self.db.get(CHANGE_SEQUENCE_KEY).map_err(map_err)? {
+            self.changes
+                .set_last_sequence_number(decode_sequence_number(&sequence_number)?);
+        }
+        self.change_log.store(
+            matches!(self.db.get(CHANGE_LOG_KEY).map_err(map_err)?, Some(value) if value == [1]),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn load_text_index(&self) -> Result<(), io::Error> {
+        self.text_index.store(
+            matches!(self.db.get(TEXT_INDEX_KEY).map_err(map_err)?, Some(value) if value == [1]),
+            Ordering::Relaxed,
+        );
+        Ok(())
+    }
+
+    fn ensure_writable(&self) -> Result<(), io::Error> {
+        if self.is_read_only() {
+            Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "The RocksDB store has been opened in read-only mode and could not be written",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     fn flush(&self) -> Result<(), io::Error> {
         let mut options = FlushOptions::new();
         options.set_wait(true);
@@ -153,8 +390,38 @@ impl RocksDbStore {
         Ok(())
     }
 
+    /// Returns a read-only [snapshot](RocksDbSnapshot) of the current state of the store.
+    ///
+    /// The writes done after the snapshot creation are not visible from it.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::RocksDbStore;
+    /// use oxigraph::model::*;
+    /// # use std::fs::remove_dir_all;
+    ///
+    /// # {
+    /// let store = RocksDbStore::open("example_snapshot.db")?;
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let snapshot = store.snapshot();
+    /// store.insert(QuadRef::new(ex, ex, ex, None))?;
+    /// assert!(store.contains(QuadRef::new(ex, ex, ex, None))?);
+    /// assert!(!snapshot.contains(QuadRef::new(ex, ex, ex, None))?);
+    /// # };
+    /// # remove_dir_all("example_snapshot.db")?;
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn snapshot(&self) -> RocksDbSnapshot {
+        RocksDbSnapshot {
+            store: self.with_snapshot(),
+        }
+    }
+
     /// Executes a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/).
     ///
+    /// The query is evaluated against a snapshot of the store taken when this method is called:
+    /// the writes done during the evaluation are not visible from the query.
+    ///
     /// See [`MemoryStore`](super::memory::MemoryStore::query()) for a usage example.
     pub fn query(
         &self,
@@ -169,7 +436,50 @@ impl RocksDbStore {
         query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
         options: QueryOptions,
     ) -> Result<QueryResults, EvaluationError> {
-        evaluate_query(self.clone(), query, options)
+        evaluate_query(self.with_snapshot(), query, options)
+    }
+
+    /// Returns the evaluation plan of a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) without executing it.
+    ///
+    /// The default query options are used.
+    pub fn explain(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+    ) -> Result<QueryExplanation, EvaluationError> {
+        self.explain_opt(query, QueryOptions::default(), false)
+    }
+
+    /// Returns the evaluation plan of a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) with some options.
+    ///
+    /// If `with_stats` is set, the query is fully evaluated and the number of rows and the evaluation time of each plan node are returned.
+    pub fn explain_opt(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+        options: QueryOptions,
+        with_stats: bool,
+    ) -> Result<QueryExplanation, EvaluationError> {
+        explain_query(self.with_snapshot(), query, options, with_stats)
+    }
+
+    /// Validates the content of the store against a [SHACL](https://www.w3.org/TR/shacl/) shapes graph.
+    ///
+    /// The data graph is the union of all the store graphs.
+    pub fn validate(&self, shapes: &ShapesGraph) -> Result<ValidationReport, EvaluationError> {
+        validate(self.with_snapshot(), shapes, None, QueryOptions::default())
+    }
+
+    /// Validates a graph of the store against a [SHACL](https://www.w3.org/TR/shacl/) shapes graph.
+    pub fn validate_graph<'a>(
+        &self,
+        shapes: &ShapesGraph,
+        graph_name: impl Into<GraphNameRef<'a>>,
+    ) -> Result<ValidationReport, EvaluationError> {
+        validate(
+            self.with_snapshot(),
+            shapes,
+            Some(graph_name.into()),
+            QueryOptions::default(),
+        )
     }
 
     /// Retrieves quads with a filter on each quad component
@@ -177,7 +487,7 @@ impl RocksDbStore {
     /// See [`MemoryStore`](super::memory::MemoryStore::quads_for_pattern()) for a usage example.
     pub fn quads_for_pattern(
         &self,
-        subject: Option<NamedOrBlankNodeRef<'_>>,
+        subject: Option<SubjectRef<'_>>,
         predicate: Option<NamedNodeRef<'_>>,
         object: Option<TermRef<'_>>,
         graph_name: Option<GraphNameRef<'_>>,
@@ -212,30 +522,13 @@ impl RocksDbStore {
     ///
     /// Warning: this function executes a full scan
     pub fn len(&self) -> usize {
-        let default = self
-            .db
-            .full_iterator_cf(self.dspo_cf(), IteratorMode::Start)
-            .count();
-        let named = self
-            .db
-            .full_iterator_cf(self.gspo_cf(), IteratorMode::Start)
-            .count();
-        default + named
+        let store = self.with_snapshot();
+        store.cf_len(store.dspo_cf()) + store.cf_len(store.gspo_cf())
     }
 
     /// Returns if the store is empty
     pub fn is_empty(&self) -> bool {
-        let default = self
-            .db
-            .full_iterator_cf(self.dspo_cf(), IteratorMode::Start)
-            .next()
-            .is_none();
-        let named = self
-            .db
-            .full_iterator_cf(self.gspo_cf(), IteratorMode::Start)
-            .next()
-            .is_none();
-        default && named
+        self.cf_is_empty(self.dspo_cf()) && self.cf_is_empty(self.gspo_cf())
     }
 
     /// Executes a [SPARQL 1.1 update](https://www.w3.org/TR/sparql11-update/).
@@ -279,11 +572,15 @@ impl RocksDbStore {
         &'a self,
         f: impl FnOnce(&mut RocksDbTransaction<'a>) -> Result<(), E>,
     ) -> Result<(), E> {
+        self.ensure_writable()?;
         let mut transaction = RocksDbTransaction {
             store: self,
             batch: WriteBatch::default(),
             buffer: Vec::new(),
             new_strings: HashMap::new(),
+            statistics: PendingStatistics::default(),
+            changes: self.entailment_regime().map(|_| QuadChanges::default()),
+            collector: self.change_collector(),
         };
         f(&mut transaction)?;
         Ok(transaction.apply()?)
@@ -313,6 +610,58 @@ impl RocksDbStore {
             format,
             to_graph_name.into(),
             base_iri,
+            None,
+        )?;
+        Ok(transaction.apply()?)
+    }
+
+    /// Loads a graph file (i.e. triples) into the store like [`load_graph`](RocksDbStore::load_graph()) and returns the prefixes declared in it.
+    ///
+    /// The prefixes could be given to [`GraphSerializer::with_prefixes`] to use them when dumping the store.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::load_graph_with_prefixes()) for a usage example.
+    pub fn load_graph_with_prefixes<'a>(
+        &self,
+        reader: impl BufRead,
+        format: GraphFormat,
+        to_graph_name: impl Into<GraphNameRef<'a>>,
+        base_iri: Option<&str>,
+    ) -> Result<BTreeMap<String, String>, io::Error> {
+        let mut transaction = self.auto_batch_writer();
+        let prefixes = load_graph(
+            &mut transaction,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            None,
+        )?;
+        transaction.apply()?;
+        Ok(prefixes)
+    }
+
+    /// Loads a graph file (i.e. triples) into the store like [`load_graph`](RocksDbStore::load_graph()) but skips the invalid statements.
+    ///
+    /// The syntax error of each skipped statement is given to `on_error`. The error messages contain the line and column of the error.
+    /// Only the N-Triples and Turtle parsers are able to resume after an error, the RDF/XML one still fails on the first error.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::load_graph_lenient()) for a usage example.
+    pub fn load_graph_lenient<'a>(
+        &self,
+        reader: impl BufRead,
+        format: GraphFormat,
+        to_graph_name: impl Into<GraphNameRef<'a>>,
+        base_iri: Option<&str>,
+        mut on_error: impl FnMut(io::Error),
+    ) -> Result<(), io::Error> {
+        let mut transaction = self.auto_batch_writer();
+        load_graph(
+            &mut transaction,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            Some(&mut on_error),
         )?;
         Ok(transaction.apply()?)
     }
@@ -334,10 +683,76 @@ impl RocksDbStore {
         base_iri: Option<&str>,
     ) -> Result<(), io::Error> {
         let mut transaction = self.auto_batch_writer();
-        load_dataset(&mut transaction, reader, format, base_iri)?;
+        load_dataset(&mut transaction, reader, format, base_iri, None)?;
+        Ok(transaction.apply()?)
+    }
+
+    /// Loads a dataset file (i.e. quads) into the store like [`load_dataset`](RocksDbStore::load_dataset()) and returns the prefixes declared in it.
+    ///
+    /// The prefixes could be given to [`DatasetSerializer::with_prefixes`] to use them when dumping the store.
+    pub fn load_dataset_with_prefixes(
+        &self,
+        reader: impl BufRead,
+        format: DatasetFormat,
+        base_iri: Option<&str>,
+    ) -> Result<BTreeMap<String, String>, io::Error> {
+        let mut transaction = self.auto_batch_writer();
+        let prefixes = load_dataset(&mut transaction, reader, format, base_iri, None)?;
+        transaction.apply()?;
+        Ok(prefixes)
+    }
+
+    /// Loads a dataset file (i.e. quads) into the store like [`load_dataset`](RocksDbStore::load_dataset()) but skips the invalid statements.
+    ///
+    /// The syntax error of each skipped statement is given to `on_error`. The error messages contain the line and column of the error.
+    /// Only the N-Quads and TriG parsers are able to resume after an error, the JSON-LD one still fails on the first error.
+    pub fn load_dataset_lenient(
+        &self,
+        reader: impl BufRead,
+        format: DatasetFormat,
+        base_iri: Option<&str>,
+        mut on_error: impl FnMut(io::Error),
+    ) -> Result<(), io::Error> {
+        let mut transaction = self.auto_batch_writer();
+        load_dataset(
+            &mut transaction,
+            reader,
+            format,
+            base_iri,
+            Some(&mut on_error),
+        )?;
+        Ok(transaction.apply()?)
+    }
+
+    /// Loads a file into the store guessing its format from the file extension or, if it is not known, from the file content.
+    ///
+    /// The triples are loaded into the default graph.
+    /// The files compressed with a supported [`Compression`](crate::io::Compression) are decompressed.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::load_from_path()) for a usage example.
+    pub fn load_from_path(
+        &self,
+        path: impl AsRef<Path>,
+        base_iri: Option<&str>,
+    ) -> Result<(), io::Error> {
+        let mut transaction = self.auto_batch_writer();
+        load_from_path(&mut transaction, path.as_ref(), base_iri)?;
         Ok(transaction.apply()?)
     }
 
+    /// Returns a [`RocksDbBulkLoader`] allowing to load very large files much faster than [`load_dataset`](RocksDbStore::load_dataset()).
+    ///
+    /// See [`RocksDbBulkLoader`] for a usage example.
+    pub fn bulk_loader(&self) -> RocksDbBulkLoader {
+        RocksDbBulkLoader {
+            store: self.clone(),
+            num_threads: thread::available_parallelism().map_or(1, NonZeroUsize::get),
+            batch_size: BULK_LOAD_BATCH_SIZE,
+            on_progress: None,
+            on_parse_error: None,
+        }
+    }
+
     /// Adds a quad to this store.
     /// This operation is atomic and could not leave the store in a bad state.
     pub fn insert<'a>(&self, quad: impl Into<QuadRef<'a>>) -> Result<(), io::Error> {
@@ -360,27 +775,45 @@ impl RocksDbStore {
     }
 
     /// Dumps a store graph into a file.
-    ///    
+    ///
+    /// The quads inferred by the [entailment regime](RocksDbStore::set_entailment_regime()) are not written.
+    ///
+    /// The dump is done from a snapshot of the store taken when this method is called.
+    ///
     /// See [`MemoryStore`](super::memory::MemoryStore::dump_graph()) for a usage example.
     pub fn dump_graph<'a>(
         &self,
         writer: impl Write,
-        format: GraphFormat,
+        format: impl Into<GraphSerializer>,
         from_graph_name: impl Into<GraphNameRef<'a>>,
     ) -> Result<(), io::Error> {
+        let store = self.with_snapshot();
+        let quads = store
+            .get_encoded_graph_name(from_graph_name.into())?
+            .map(|graph_name| store.quads_for_graph(graph_name))
+            .into_iter()
+            .flatten();
         dump_graph(
-            self.quads_for_pattern(None, None, None, Some(from_graph_name.into()))
-                .map(|q| Ok(q?.into())),
+            store.asserted_quads(quads).map(|q| Ok(q?.into())),
             writer,
             format,
         )
     }
 
     /// Dumps the store into a file.
-    ///    
+    ///
+    /// The quads inferred by the [entailment regime](RocksDbStore::set_entailment_regime()) are not written.
+    ///
+    /// The dump is done from a snapshot of the store taken when this method is called.
+    ///
     /// See [`MemoryStore`](super::memory::MemoryStore::dump_dataset()) for a usage example.
-    pub fn dump_dataset(&self, writer: impl Write, syntax: DatasetFormat) -> Result<(), io::Error> {
-        dump_dataset(self.iter(), writer, syntax)
+    pub fn dump_dataset(
+        &self,
+        writer: impl Write,
+        syntax: impl Into<DatasetSerializer>,
+    ) -> Result<(), io::Error> {
+        let store = self.with_snapshot();
+        dump_dataset(store.asserted_quads(store.quads()), writer, syntax)
     }
 
     /// Returns all the store named graphs
@@ -460,85 +893,481 @@ impl RocksDbStore {
         transaction.apply()
     }
 
-    fn id2str_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, ID2STR_CF)
+    /// Creates a new backup of the store in the `backup_directory` using the [RocksDB backup engine](https://github.com/facebook/rocksdb/wiki/How-to-backup-RocksDB).
+    ///
+    /// The backup is done online: the store could still be read and written during the backup and the backup contains a consistent state of the store.
+    /// The backups are incremental: the files already saved by a previous backup in the same directory are not copied again.
+    /// Use [`RocksDbStore::restore`] to restore the latest backup.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::RocksDbStore;
+    /// use oxigraph::model::*;
+    /// # use std::fs::remove_dir_all;
+    ///
+    /// # {
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = RocksDbStore::open("example_backup.db")?;
+    /// store.insert(QuadRef::new(ex, ex, ex, None))?;
+    /// store.backup("example_backups")?;
+    ///
+    /// RocksDbStore::restore("example_backups", "example_restored.db")?;
+    /// let restored = RocksDbStore::open("example_restored.db")?;
+    /// assert!(restored.contains(QuadRef::new(ex, ex, ex, None))?);
+    /// # };
+    /// # remove_dir_all("example_backup.db")?;
+    /// # remove_dir_all("example_backups")?;
+    /// # remove_dir_all("example_restored.db")?;
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn backup(&self, backup_directory: impl AsRef<Path>) -> Result<(), io::Error> {
+        let mut engine =
+            backup::BackupEngine::open(&backup::BackupEngineOptions::default(), backup_directory)
+                .map_err(map_err)?;
+        engine.create_new_backup(&self.db).map_err(map_err)
     }
 
-    fn spog_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, SPOG_CF)
+    /// Restores the latest backup done with [`RocksDbStore::backup`] in `backup_directory` into the store directory `path`.
+    ///
+    /// The store at `path` must not be opened during the restoration. Its existing content is replaced.
+    pub fn restore(
+        backup_directory: impl AsRef<Path>,
+        path: impl AsRef<Path>,
+    ) -> Result<(), io::Error> {
+        let mut engine =
+            backup::BackupEngine::open(&backup::BackupEngineOptions::default(), backup_directory)
+                .map_err(map_err)?;
+        engine
+            .restore_from_latest_backup(&path, &path, &backup::RestoreOptions::default())
+            .map_err(map_err)
     }
 
-    fn posg_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, POSG_CF)
+    /// Creates a [checkpoint](https://github.com/facebook/rocksdb/wiki/Checkpoints) of the store in the directory `path`.
+    ///
+    /// It is a point-in-time snapshot of the store that could be directly opened with [`RocksDbStore::open`].
+    /// If `path` is on the same file system as the store, the database files are hard linked instead of copied, making it very fast.
+    /// The directory `path` must not exist yet.
+    pub fn checkpoint(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+        checkpoint::Checkpoint::new(&self.db)
+            .map_err(map_err)?
+            .create_checkpoint(path)
+            .map_err(map_err)
     }
 
-    fn ospg_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, OSPG_CF)
+    /// Sets the [entailment regime](EntailmentRegime) used to materialize inferred quads in the store.
+    ///
+    /// The regime is persisted in the database.
+    /// All the quads inferred by the previous regime are removed and the ones entailed by the new regime are computed.
+    /// The inferred quads are then maintained on each store write. `None` disables the inference.
+    ///
+    /// The inferred quads are returned by the quad pattern lookups and by the SPARQL queries
+    /// but are not written by [`dump_graph`](RocksDbStore::dump_graph()) and [`dump_dataset`](RocksDbStore::dump_dataset()).
+    /// Removing an inferred quad has no effect as long as it is entailed by the other quads.
+    ///
+    /// See [`EntailmentRegime`] for a usage example.
+    pub fn set_entailment_regime(&self, regime: Option<EntailmentRegime>) -> Result<(), io::Error> {
+        self.ensure_writable()?;
+        remove_inferred_quads(self)?;
+        let regime_id = encode_entailment_regime(regime);
+        self.db.put("oxentailment", &[regime_id]).map_err(map_err)?;
+        self.entailment_regime.store(regime_id, Ordering::Relaxed);
+        if let Some(regime) = regime {
+            materialize(self, regime)?;
+        }
+        Ok(())
     }
 
-    fn gspo_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, GSPO_CF)
+    /// Returns the [entailment regime](EntailmentRegime) used to materialize inferred quads in the store, if any.
+    pub fn entailment_regime(&self) -> Option<EntailmentRegime> {
+        // The value has been validated when it has been written
+        decode_entailment_regime(self.entailment_regime.load(Ordering::Relaxed)).unwrap_or(None)
     }
 
-    fn gpos_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, GPOS_CF)
+    /// Enables or disables the full-text index.
+    ///
+    /// When it is enabled, the string literals used as quad objects are indexed and could be searched in SPARQL
+    /// with the `text:query` property function. See the [`sparql`](crate::sparql) module documentation for its syntax.
+    /// The setting is persisted in the database.
+    /// The index is built from the current content of the store when it is enabled and then maintained on each store write.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::set_text_index()) for a usage example.
+    pub fn set_text_index(&self, enabled: bool) -> Result<(), io::Error> {
+        self.ensure_writable()?;
+        self.db
+            .put(TEXT_INDEX_KEY, &[u8::from(enabled)])
+            .map_err(map_err)?;
+        self.text_index.store(enabled, Ordering::Relaxed);
+        // The index is stored with the statistics
+        self.rebuild_statistics()
     }
 
-    fn gosp_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, GOSP_CF)
+    /// Returns if the [full-text index](RocksDbStore::set_text_index()) is enabled.
+    pub fn is_text_index_enabled(&self) -> bool {
+        self.text_index.load(Ordering::Relaxed)
     }
 
-    fn dspo_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, DSPO_CF)
+    /// Subscribes to the changes of the store.
+    ///
+    /// The subscription receives a [`ChangeBatch`](super::ChangeBatch) for each write committed after its creation
+    /// that inserts or removes some quads, in commit order.
+    /// A transaction is a single write. The other write methods might be split in several batches if they write many quads.
+    /// The quads inferred by the [entailment regime](RocksDbStore::set_entailment_regime()) are not part of the batches.
+    /// The [bulk loader](RocksDbStore::bulk_loader()) does not capture the quads it loads: each bulk load is published
+    /// as a batch without quads that [requires a resync](super::ChangeBatch::requires_resync()) of the consumers mirroring the store.
+    ///
+    /// The sequence numbers are persisted in the database. Use [`subscribe_from`](RocksDbStore::subscribe_from())
+    /// with the [change log](RocksDbStore::set_change_log()) enabled to resume a subscription after a restart.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::subscribe()) for a usage example.
+    pub fn subscribe(&self) -> ChangeSubscription {
+        self.changes.subscribe()
     }
 
-    fn dpos_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, DPOS_CF)
+    /// Subscribes to the changes of the store committed after the batch with the given sequence number.
+    ///
+    /// The batches already committed are read from the [change log](RocksDbStore::set_change_log()).
+    /// Fails with an error of kind [`InvalidInput`](std::io::ErrorKind::InvalidInput) if some of them are not in the change log anymore.
+    ///
+    /// Usage example:
+    /// ```
+    /// use oxigraph::RocksDbStore;
+    /// use oxigraph::model::*;
+    /// # use std::fs::remove_dir_all;
+    ///
+    /// # {
+    /// let ex = NamedNodeRef::new("http://example.com")?;
+    /// let store = RocksDbStore::open("example_changes.db")?;
+    /// store.set_change_log(true)?;
+    /// store.insert(QuadRef::new(ex, ex, ex, None))?;
+    /// let last_sequence_number = store.last_sequence_number();
+    /// store.remove(QuadRef::new(ex, ex, ex, None))?;
+    ///
+    /// // After a restart
+    /// let mut changes = store.subscribe_from(last_sequence_number)?;
+    /// let batch = changes.try_recv().unwrap();
+    /// assert_eq!(batch.sequence_number(), last_sequence_number + 1);
+    /// assert_eq!(batch.removed(), &[Quad::new(ex, ex, ex, None)]);
+    /// # };
+    /// # remove_dir_all("example_changes.db")?;
+    /// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+    /// ```
+    pub fn subscribe_from(&self, sequence_number: u64) -> Result<ChangeSubscription, io::Error> {
+        self.changes.subscribe_with_replay(|last_sequence_number| {
+            let mut batches = VecDeque::new();
+            if sequence_number >= last_sequence_number {
+                return Ok(batches);
+            }
+            let mut iter = self.db.raw_iterator();
+            iter.seek(change_log_key(sequence_number + 1));
+            let mut expected = sequence_number + 1;
+            while let (Some(key), Some(value)) = (iter.key(), iter.value()) {
+                let current = if let Some(key) = key.strip_prefix(CHANGE_LOG_PREFIX) {
+                    decode_sequence_number(key)?
+                } else {
+                    break;
+                };
+                if current != expected || current > last_sequence_number {
+                    break;
+                }
+                batches.push_back(ChangeBatch::decode_body(current, value)?);
+                expected += 1;
+                iter.next();
+            }
+            iter.status().map_err(map_err)?;
+            if expected <= last_sequence_number {
+                return Err(invalid_input_error(format!(
+                    "The change batch {} is not in the change log",
+                    expected
+                )));
+            }
+            Ok(batches)
+        })
     }
 
-    fn dosp_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, DOSP_CF)
+    /// Returns the sequence number of the last committed [`ChangeBatch`](super::ChangeBatch) or 0 if there is none.
+    ///
+    /// The changes are only captured, and so the sequence number only increases,
+    /// while some subscriptions are alive or the [change log](RocksDbStore::set_change_log()) is enabled.
+    pub fn last_sequence_number(&self) -> u64 {
+        self.changes.last_sequence_number()
     }
 
-    fn graphs_cf(&self) -> &ColumnFamily {
-        get_cf(&self.db, GRAPHS_CF)
+    /// Enables or disables the change log.
+    ///
+    /// When it is enabled, all the [change batches](super::ChangeBatch) are persisted in the database
+    /// in order to be read later by [`subscribe_from`](RocksDbStore::subscribe_from()).
+    /// The setting is persisted in the database.
+    /// Disabling the change log does not remove the existing entries, use [`truncate_change_log`](RocksDbStore::truncate_change_log()) for that.
+    pub fn set_change_log(&self, enabled: bool) -> Result<(), io::Error> {
+        self.ensure_writable()?;
+        self.db
+            .put(CHANGE_LOG_KEY, &[u8::from(enabled)])
+            .map_err(map_err)?;
+        self.change_log.store(enabled, Ordering::Relaxed);
+        Ok(())
     }
-    fn auto_batch_writer(&self) -> AutoBatchWriter<'_> {
-        AutoBatchWriter {
-            store: self,
-            batch: WriteBatch::default(),
-            buffer: Vec::default(),
-        }
+
+    /// Returns if the change log is enabled.
+    pub fn is_change_log_enabled(&self) -> bool {
+        self.change_log.load(Ordering::Relaxed)
     }
 
-    fn contains_encoded(&self, quad: &EncodedQuad) -> Result<bool, io::Error> {
-        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
-        if quad.graph_name.is_default_graph() {
-            write_spo_quad(&mut buffer, quad);
-            Ok(self
-                .db
-                .get_pinned_cf(self.dspo_cf(), &buffer)
-                .map_err(map_err)?
-                .is_some())
-        } else {
-            write_gspo_quad(&mut buffer, quad);
-            Ok(self
-                .db
-                .get_pinned_cf(self.gspo_cf(), &buffer)
-                .map_err(map_err)?
-                .is_some())
+    /// Removes from the change log all the batches with a sequence number lower or equal to `sequence_number`.
+    ///
+    /// It should be called when all the consumers have processed these batches in order to keep the log small.
+    pub fn truncate_change_log(&self, sequence_number: u64) -> Result<(), io::Error> {
+        self.ensure_writable()?;
+        let mut batch = WriteBatch::default();
+        let mut iter = self.db.raw_iterator();
+        iter.seek(CHANGE_LOG_PREFIX);
+        while let Some(key) = iter.key() {
+            if let Some(current) = key.strip_prefix(CHANGE_LOG_PREFIX) {
+                if decode_sequence_number(current)? > sequence_number {
+                    break;
+                }
+            } else {
+                break;
+            }
+            batch.delete(key);
+            iter.next();
         }
+        iter.status().map_err(map_err)?;
+        self.db.write(batch).map_err(map_err)
     }
 
-    fn quads(&self) -> DecodingIndexesIterator {
-        DecodingIndexesIterator::pair(
-            self.dspo_quads(Vec::default()),
-            self.gspo_quads(Vec::default()),
-        )
+    /// Returns if the writes should capture their changes
+    fn captures_changes(&self) -> bool {
+        self.changes.is_watched() || self.change_log.load(Ordering::Relaxed)
     }
 
-    fn quads_for_subject(&self, subject: EncodedTerm) -> DecodingIndexesIterator {
-        DecodingIndexesIterator::pair(
+    fn change_collector(&self) -> Option<ChangeCollector<StrHash>> {
+        if self.captures_changes() {
+            Some(ChangeCollector::default())
+        } else {
+            None
+        }
+    }
+
+    /// Returns if the quad is in the store and has not been inferred
+    fn contains_asserted(
+        &self,
+        statistics: &PendingStatistics,
+        quad: &EncodedQuad,
+    ) -> Result<bool, io::Error> {
+        Ok(statistics.contains_quad(self, quad)?
+            && !(self.entailment_regime().is_some() && self.is_inferred(quad)?))
+    }
+
+    /// Writes the batch and publishes the collected changes, if any
+    ///
+    /// The collected changes are also persisted in the batch: the sequence number and the change log entry, if enabled.
+    fn write_with_changes(
+        &self,
+        mut batch: WriteBatch,
+        collector: Option<&mut ChangeCollector<StrHash>>,
+        new_strings: &HashMap<StrHash, String>,
+    ) -> Result<(), io::Error> {
+        let (inserted, removed) = if let Some(collector) = collector {
+            collector.take_changes()
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        if inserted.is_empty() && removed.is_empty() {
+            return self.db.write(batch).map_err(map_err);
+        }
+        // The strings are decoded before the write that might remove them
+        let strings = PendingStrings {
+            store: self,
+            new_strings,
+        };
+        let inserted = inserted
+            .iter()
+            .map(|quad| strings.decode_quad(quad))
+            .collect::<Result<Vec<_>, _>>()?;
+        let removed = removed
+            .iter()
+            .map(|quad| strings.decode_quad(quad))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.changes.commit(|sequence_number| {
+            batch.put(CHANGE_SEQUENCE_KEY, &sequence_number.to_be_bytes());
+            if self.change_log.load(Ordering::Relaxed) {
+                batch.put(
+                    change_log_key(sequence_number),
+                    ChangeBatch::encode_body(&inserted, &removed),
+                );
+            }
+            self.db.write(batch).map_err(map_err)?;
+            Ok(((), inserted, removed))
+        })
+    }
+
+    fn id2str_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, ID2STR_CF)
+    }
+
+    fn spog_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, SPOG_CF)
+    }
+
+    fn posg_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, POSG_CF)
+    }
+
+    fn ospg_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, OSPG_CF)
+    }
+
+    fn gspo_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, GSPO_CF)
+    }
+
+    fn gpos_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, GPOS_CF)
+    }
+
+    fn gosp_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, GOSP_CF)
+    }
+
+    fn dspo_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, DSPO_CF)
+    }
+
+    fn dpos_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, DPOS_CF)
+    }
+
+    fn dosp_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, DOSP_CF)
+    }
+
+    fn graphs_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, GRAPHS_CF)
+    }
+
+    fn stats_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, STATS_CF)
+    }
+
+    fn inferred_cf(&self) -> &ColumnFamily {
+        get_cf(&self.db, INFERRED_CF)
+    }
+
+    fn auto_batch_writer(&self) -> AutoBatchWriter<'_> {
+        AutoBatchWriter {
+            store: self,
+            batch: WriteBatch::default(),
+            buffer: Vec::default(),
+            statistics: PendingStatistics::default(),
+            changes: self.entailment_regime().map(|_| QuadChanges::default()),
+            collector: self.change_collector(),
+            new_strings: HashMap::new(),
+        }
+    }
+
+    /// A batch writer whose writes are not propagated to the inferred quads
+    fn inference_batch_writer(&self) -> AutoBatchWriter<'_> {
+        AutoBatchWriter {
+            store: self,
+            batch: WriteBatch::default(),
+            buffer: Vec::default(),
+            statistics: PendingStatistics::default(),
+            changes: None,
+            collector: None,
+            new_strings: HashMap::new(),
+        }
+    }
+
+    /// Recomputes all the statistics counters from the quads in the store
+    fn rebuild_statistics(&self) -> Result<(), io::Error> {
+        let mut transaction = self.inference_batch_writer();
+        transaction.clear_cf(self.stats_cf());
+        transaction.write_batch()?;
+        for quad in self.encoded_quads_for_pattern(None, None, None, None) {
+            // The quads are already in the database, we only count them
+            record_quad_insertion(
+                &mut transaction
+                    .statistics
+                    .counters(self, &transaction.new_strings),
+                &quad?,
+            )?;
+            if transaction.statistics.counters.len() > MAX_TRANSACTION_SIZE {
+                transaction.write_batch()?;
+            }
+        }
+        transaction.apply()
+    }
+
+    fn propagate_inferences(&self, changes: QuadChanges<StrHash>) -> Result<(), io::Error> {
+        if let Some(regime) = self.entailment_regime() {
+            propagate_changes(self, regime, changes)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Decodes the given quads without the inferred ones
+    fn asserted_quads<'a>(
+        &'a self,
+        quads: impl Iterator<Item = Result<EncodedQuad, io::Error>> + 'a,
+    ) -> impl Iterator<Item = Result<Quad, io::Error>> + 'a {
+        quads.filter_map(move |quad| {
+            let quad = match quad {
+                Ok(quad) => quad,
+                Err(error) => return Some(Err(error)),
+            };
+            match self.is_inferred(&quad) {
+                Ok(true) => None,
+                Ok(false) => Some(self.decode_quad(&quad).map_err(|e| e.into())),
+                Err(error) => Some(Err(error)),
+            }
+        })
+    }
+
+    /// Returns all the quads of the store without the inferred ones
+    fn asserted_encoded_quads(&self) -> impl Iterator<Item = Result<EncodedQuad, io::Error>> + '_ {
+        let check_inferred = self.entailment_regime().is_some();
+        self.quads().filter_map(move |quad| {
+            let quad = match quad {
+                Ok(quad) => quad,
+                Err(error) => return Some(Err(error)),
+            };
+            if !check_inferred {
+                return Some(Ok(quad));
+            }
+            match self.is_inferred(&quad) {
+                Ok(true) => None,
+                Ok(false) => Some(Ok(quad)),
+                Err(error) => Some(Err(error)),
+            }
+        })
+    }
+
+    fn get_counter(&self, key: &[u8]) -> Result<u64, io::Error> {
+        self.db_get(self.stats_cf(), key)?
+            .map_or(Ok(0), |value| decode_counter(&value))
+    }
+
+    fn contains_encoded(&self, quad: &EncodedQuad) -> Result<bool, io::Error> {
+        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+        if quad.graph_name.is_default_graph() {
+            write_spo_quad(&mut buffer, quad);
+            self.db_contains(self.dspo_cf(), &buffer)
+        } else {
+            write_gspo_quad(&mut buffer, quad);
+            self.db_contains(self.gspo_cf(), &buffer)
+        }
+    }
+
+    fn quads(&self) -> DecodingIndexesIterator {
+        DecodingIndexesIterator::pair(
+            self.dspo_quads(Vec::default()),
+            self.gspo_quads(Vec::default()),
+        )
+    }
+
+    fn quads_for_subject(&self, subject: EncodedTerm) -> DecodingIndexesIterator {
+        DecodingIndexesIterator::pair(
             self.dspo_quads(encode_term(subject)),
             self.spog_quads(encode_term(subject)),
         )
@@ -700,61 +1529,802 @@ impl RocksDbStore {
         })
     }
 
-    fn spog_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.spog_cf(), prefix, QuadEncoding::SPOG)
+    fn spog_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.spog_cf(), prefix, QuadEncoding::SPOG)
+    }
+
+    fn posg_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.posg_cf(), prefix, QuadEncoding::POSG)
+    }
+
+    fn ospg_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.ospg_cf(), prefix, QuadEncoding::OSPG)
+    }
+
+    fn gspo_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.gspo_cf(), prefix, QuadEncoding::GSPO)
+    }
+
+    fn gpos_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.gpos_cf(), prefix, QuadEncoding::GPOS)
+    }
+
+    fn gosp_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.gosp_cf(), prefix, QuadEncoding::GOSP)
+    }
+
+    fn dspo_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.dspo_cf(), prefix, QuadEncoding::DSPO)
+    }
+
+    fn dpos_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.dpos_cf(), prefix, QuadEncoding::DPOS)
+    }
+
+    fn dosp_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
+        self.inner_quads(self.dosp_cf(), prefix, QuadEncoding::DOSP)
+    }
+
+    fn inner_quads(
+        &self,
+        cf: &ColumnFamily,
+        prefix: Vec<u8>,
+        encoding: QuadEncoding,
+    ) -> DecodingIndexIterator {
+        let mut iter = self.db_iter(cf);
+        iter.iter.seek(&prefix);
+        DecodingIndexIterator {
+            iter,
+            prefix,
+            encoding,
+        }
+    }
+
+    /// Reads a value from the snapshot if the store has one and else from the latest state of the database
+    fn db_get(&self, cf: &ColumnFamily, key: &[u8]) -> Result<Option<Vec<u8>>, io::Error> {
+        if let Some(snapshot) = &self.snapshot {
+            snapshot.snapshot.get_cf(cf, key)
+        } else {
+            self.db.get_cf(cf, key)
+        }
+        .map_err(map_err)
+    }
+
+    fn db_contains(&self, cf: &ColumnFamily, key: &[u8]) -> Result<bool, io::Error> {
+        Ok(if let Some(snapshot) = &self.snapshot {
+            snapshot
+                .snapshot
+                .get_cf(cf, key)
+                .map_err(map_err)?
+                .is_some()
+        } else {
+            self.db.get_pinned_cf(cf, key).map_err(map_err)?.is_some()
+        })
+    }
+
+    #[allow(unsafe_code)]
+    fn db_iter(&self, cf: &ColumnFamily) -> StaticDBRowIterator {
+        // Valid because it's the same database so db can't be dropped before iter
+        unsafe {
+            if let Some(snapshot) = &self.snapshot {
+                StaticDBRowIterator::new(
+                    snapshot.snapshot.raw_iterator_cf(cf),
+                    self.db.clone(),
+                    Some(snapshot.clone()),
+                )
+            } else {
+                StaticDBRowIterator::new(self.db.raw_iterator_cf(cf), self.db.clone(), None)
+            }
+        }
+    }
+
+    fn cf_len(&self, cf: &ColumnFamily) -> usize {
+        let mut iter = self.db_iter(cf);
+        iter.iter.seek_to_first();
+        let mut len = 0;
+        while iter.key().is_some() {
+            len += 1;
+            iter.next();
+        }
+        len
+    }
+
+    fn cf_is_empty(&self, cf: &ColumnFamily) -> bool {
+        let mut iter = self.db_iter(cf);
+        iter.iter.seek_to_first();
+        iter.key().is_none()
+    }
+
+    /// Returns a clone of the store reading from a snapshot of the database.
+    ///
+    /// If the store is already reading from a snapshot, the same snapshot is used.
+    fn with_snapshot(&self) -> Self {
+        if self.snapshot.is_some() {
+            self.clone()
+        } else {
+            Self {
+                db: self.db.clone(),
+                entailment_regime: self.entailment_regime.clone(),
+                snapshot: Some(Arc::new(StaticSnapshot::new(self.db.clone()))),
+                mode: self.mode,
+                changes: self.changes.clone(),
+                change_log: self.change_log.clone(),
+                text_index: self.text_index.clone(),
+            }
+        }
+    }
+}
+
+/// A read-only snapshot of a [`RocksDbStore`] returned by [`RocksDbStore::snapshot`].
+///
+/// All the reads see the state of the store when the snapshot has been taken.
+/// The snapshot is released when all its clones and the iterators and query results built from it are dropped.
+#[derive(Clone)]
+pub struct RocksDbSnapshot {
+    store: RocksDbStore,
+}
+
+impl RocksDbSnapshot {
+    /// Executes a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) against the snapshot.
+    pub fn query(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+    ) -> Result<QueryResults, EvaluationError> {
+        self.store.query(query)
+    }
+
+    /// Executes a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) with some options against the snapshot.
+    pub fn query_opt(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+        options: QueryOptions,
+    ) -> Result<QueryResults, EvaluationError> {
+        self.store.query_opt(query, options)
+    }
+
+    /// Retrieves quads with a filter on each quad component
+    pub fn quads_for_pattern(
+        &self,
+        subject: Option<SubjectRef<'_>>,
+        predicate: Option<NamedNodeRef<'_>>,
+        object: Option<TermRef<'_>>,
+        graph_name: Option<GraphNameRef<'_>>,
+    ) -> RocksDbQuadIter {
+        self.store
+            .quads_for_pattern(subject, predicate, object, graph_name)
+    }
+
+    /// Returns all the quads contained in the snapshot
+    pub fn iter(&self) -> RocksDbQuadIter {
+        self.store.iter()
+    }
+
+    /// Checks if the snapshot contains a given quad
+    pub fn contains<'a>(&self, quad: impl Into<QuadRef<'a>>) -> Result<bool, io::Error> {
+        self.store.contains(quad)
+    }
+
+    /// Returns the number of quads in the snapshot
+    ///
+    /// Warning: this function executes a full scan
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    /// Returns if the snapshot is empty
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
+    /// Dumps a graph of the snapshot into a file.
+    pub fn dump_graph<'a>(
+        &self,
+        writer: impl Write,
+        format: impl Into<GraphSerializer>,
+        from_graph_name: impl Into<GraphNameRef<'a>>,
+    ) -> Result<(), io::Error> {
+        self.store.dump_graph(writer, format, from_graph_name)
+    }
+
+    /// Dumps the snapshot into a file.
+    pub fn dump_dataset(
+        &self,
+        writer: impl Write,
+        format: impl Into<DatasetSerializer>,
+    ) -> Result<(), io::Error> {
+        self.store.dump_dataset(writer, format)
+    }
+
+    /// Returns all the named graphs of the snapshot
+    pub fn named_graphs(&self) -> impl Iterator<Item = Result<NamedOrBlankNode, io::Error>> {
+        self.store.named_graphs()
+    }
+
+    /// Checks if the snapshot contains a given graph
+    pub fn contains_named_graph<'a>(
+        &self,
+        graph_name: impl Into<NamedOrBlankNodeRef<'a>>,
+    ) -> Result<bool, io::Error> {
+        self.store.contains_named_graph(graph_name)
+    }
+}
+
+/// A bulk loader for [`RocksDbStore`] returned by [`RocksDbStore::bulk_loader`].
+///
+/// It is designed to load very large files: the quads are encoded and sorted by several threads
+/// that write them into [SST files](https://github.com/facebook/rocksdb/wiki/Creating-and-Ingesting-SST-files) directly ingested into the database.
+/// N-Triples and N-Quads files are also parsed in parallel. The other formats are parsed by the calling thread.
+///
+/// The statistics counters are updated with the loaded quads that were not in the store yet, which requires a lookup per loaded quad.
+/// They might be approximate if the same quads are written concurrently by other means during the load.
+/// If an [entailment regime](RocksDbStore::set_entailment_regime()) is set, the inferred quads are removed before the load and materialized again after it.
+///
+/// The loaded quads are not published to the [change subscriptions](RocksDbStore::subscribe()).
+/// A [resync batch](super::ChangeBatch::requires_resync()) is published instead at the end of each load that has written some quads.
+///
+/// Warning: The load is not atomic. Each batch of quads is ingested separately into each index.
+/// If the load fails in the middle of the file, for example because of a syntax error, the batches written before the failure stay in the store:
+/// their quads are in all the indexes and counted in the statistics but the rest of the file is not loaded.
+/// A storage error while a batch is ingested may leave this batch in only some of the indexes.
+/// The loaded quads are visible to the readers as soon as their batch is ingested.
+///
+/// Usage example:
+/// ```
+/// use oxigraph::RocksDbStore;
+/// use oxigraph::io::DatasetFormat;
+/// use oxigraph::model::*;
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+/// # use std::fs::remove_dir_all;
+///
+/// # {
+/// let store = RocksDbStore::open("example_bulk_load.db")?;
+///
+/// let file = "<http://example.com> <http://example.com> <http://example.com> <http://example.com/g> .";
+/// let loaded = Arc::new(AtomicU64::new(0));
+/// let progress = loaded.clone();
+/// store
+///     .bulk_loader()
+///     .with_num_threads(2)
+///     .on_progress(move |count| progress.store(count, Ordering::Relaxed))
+///     .load_dataset(file.as_bytes(), DatasetFormat::NQuads, None)?;
+/// assert_eq!(loaded.load(Ordering::Relaxed), 1);
+///
+/// let ex = NamedNodeRef::new("http://example.com")?;
+/// let g = NamedNodeRef::new("http://example.com/g")?;
+/// assert!(store.contains(QuadRef::new(ex, ex, ex, g))?);
+/// # };
+/// # remove_dir_all("example_bulk_load.db")?;
+/// # Result::<_,Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub struct RocksDbBulkLoader {
+    store: RocksDbStore,
+    num_threads: usize,
+    batch_size: usize,
+    on_progress: Option<Arc<dyn Fn(u64) + Send + Sync>>,
+    on_parse_error: Option<Arc<dyn Fn(io::Error) + Send + Sync>>,
+}
+
+impl RocksDbBulkLoader {
+    /// Sets the number of threads used to parse, encode and index the quads.
+    ///
+    /// By default, it is the number of CPUs available.
+    pub fn with_num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = num_threads.max(1);
+        self
+    }
+
+    /// Sets the maximal number of quads each thread keeps in memory before writing them into the store.
+    ///
+    /// Bigger batches use more memory but lead to fewer files to ingest. The default is 1,000,000.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets a function called with the number of quads loaded so far each time a batch has been written into the store.
+    ///
+    /// It is called from the loader threads.
+    pub fn on_progress(mut self, callback: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Skips the invalid statements instead of failing and calls the given function with their syntax errors.
+    ///
+    /// The error messages contain the line and column of the error.
+    /// Only the N-Triples, N-Quads, Turtle and TriG parsers are able to resume after an error, the other ones still fail on the first error.
+    /// It is called from the loader threads.
+    pub fn on_parse_error(mut self, callback: impl Fn(io::Error) + Send + Sync + 'static) -> Self {
+        self.on_parse_error = Some(Arc::new(callback));
+        self
+    }
+
+    /// Loads a graph file (i.e. triples) into the store.
+    ///
+    /// Errors related to parameter validation like the base IRI use the [`InvalidInput`](std::io::ErrorKind::InvalidInput) error kind.
+    /// Errors related to a bad syntax in the loaded file use the [`InvalidData`](std::io::ErrorKind::InvalidData) or [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) error kinds.
+    /// Errors related to data loading into the store use the other error kinds.
+    pub fn load_graph<'a>(
+        &self,
+        reader: impl BufRead,
+        format: GraphFormat,
+        to_graph_name: impl Into<GraphNameRef<'a>>,
+        base_iri: Option<&str>,
+    ) -> Result<(), io::Error> {
+        let to_graph_name = to_graph_name.into().into_owned();
+        if format == GraphFormat::NTriples {
+            return self.load_lines(
+                reader,
+                TurtleSyntax::NTriples,
+                Some(to_graph_name),
+                base_iri,
+            );
+        }
+        let mut parser = GraphParser::from_format(format);
+        if let Some(base_iri) = base_iri {
+            parser = parser
+                .with_base_iri(base_iri)
+                .map_err(invalid_input_error)?;
+        }
+        if self.on_parse_error.is_some() {
+            parser = parser.with_error_recovery();
+        }
+        let triples = parser.read_triples(reader)?;
+        let recovers = triples.recovers_from_errors();
+        self.load_quads(
+            triples.map(|triple| Ok(triple?.in_graph(to_graph_name.clone()))),
+            recovers,
+        )
+    }
+
+    /// Loads a dataset file (i.e. quads) into the store.
+    ///
+    /// Errors related to parameter validation like the base IRI use the [`InvalidInput`](std::io::ErrorKind::InvalidInput) error kind.
+    /// Errors related to a bad syntax in the loaded file use the [`InvalidData`](std::io::ErrorKind::InvalidData) or [`UnexpectedEof`](std::io::ErrorKind::UnexpectedEof) error kinds.
+    /// Errors related to data loading into the store use the other error kinds.
+    pub fn load_dataset(
+        &self,
+        reader: impl BufRead,
+        format: DatasetFormat,
+        base_iri: Option<&str>,
+    ) -> Result<(), io::Error> {
+        if format == DatasetFormat::NQuads {
+            return self.load_lines(reader, TurtleSyntax::NQuads, None, base_iri);
+        }
+        let mut parser = DatasetParser::from_format(format);
+        if let Some(base_iri) = base_iri {
+            parser = parser
+                .with_base_iri(base_iri)
+                .map_err(invalid_input_error)?;
+        }
+        if self.on_parse_error.is_some() {
+            parser = parser.with_error_recovery();
+        }
+        let quads = parser.read_quads(reader)?;
+        let recovers = quads.recovers_from_errors();
+        self.load_quads(quads, recovers)
+    }
+
+    /// Splits a N-Triples or N-Quads file in chunks of lines parsed by the loader threads
+    fn load_lines(
+        &self,
+        reader: impl BufRead,
+        syntax: TurtleSyntax,
+        to_graph_name: Option<GraphName>,
+        base_iri: Option<&str>,
+    ) -> Result<(), io::Error> {
+        let base_iri = base_iri
+            .map(|base_iri| Iri::parse(base_iri.to_owned()))
+            .transpose()
+            .map_err(invalid_input_error)?;
+        let mut reader = DecompressReader::new(reader)?;
+        let mut first_line = 1;
+        self.run(syntax, base_iri, to_graph_name, move |sender| loop {
+            let mut chunk = Vec::with_capacity(BULK_LOAD_CHUNK_SIZE);
+            let mut lines = 0;
+            while chunk.len() < BULK_LOAD_CHUNK_SIZE {
+                if reader.read_until(b'\n', &mut chunk)? == 0 {
+                    break;
+                }
+                lines += 1;
+            }
+            // If the sending fails, all the loader threads have stopped because of an error
+            if chunk.is_empty()
+                || sender
+                    .send(BulkLoadJob::Lines {
+                        data: chunk,
+                        first_line,
+                    })
+                    .is_err()
+            {
+                return Ok(());
+            }
+            first_line += lines;
+        })
+    }
+
+    /// Sends the quads parsed by the current thread in chunks to the loader threads
+    ///
+    /// If `recovers` is set, the parser resumes after the syntax errors.
+    fn load_quads(
+        &self,
+        quads: impl Iterator<Item = Result<Quad, io::Error>>,
+        recovers: bool,
+    ) -> Result<(), io::Error> {
+        let on_parse_error = self.on_parse_error.clone();
+        self.run(TurtleSyntax::NQuads, None, None, move |sender| {
+            let mut chunk = Vec::with_capacity(BULK_LOAD_CHUNK_QUADS);
+            for quad in quads {
+                match (quad, &on_parse_error) {
+                    (Ok(quad), _) => chunk.push(quad),
+                    (Err(error), Some(on_parse_error))
+                        if recovers && error.kind() == io::ErrorKind::InvalidData =>
+                    {
+                        on_parse_error(error)
+                    }
+                    (Err(error), _) => return Err(error),
+                }
+                if chunk.len() >= BULK_LOAD_CHUNK_QUADS
+                    && sender.send(BulkLoadJob::Quads(take(&mut chunk))).is_err()
+                {
+                    // All the loader threads have stopped because of an error
+                    return Ok(());
+                }
+            }
+            if !chunk.is_empty() {
+                // An error here is returned by the loader threads
+                let _ = sender.send(BulkLoadJob::Quads(chunk));
+            }
+            Ok(())
+        })
+    }
+
+    fn run(
+        &self,
+        syntax: TurtleSyntax,
+        base_iri: Option<Iri<String>>,
+        to_graph_name: Option<GraphName>,
+        produce: impl FnOnce(&SyncSender<BulkLoadJob>) -> Result<(), io::Error>,
+    ) -> Result<(), io::Error> {
+        self.store.ensure_writable()?;
+        let regime = self.store.entailment_regime();
+        if regime.is_some() {
+            // Some loaded quads may be already there as inferred quads
+            remove_inferred_quads(&self.store)?;
+        }
+        let temp_dir = self
+            .store
+            .db
+            .path()
+            .join(format!("bulk_load_{:x}", random::<u128>()));
+        create_dir_all(&temp_dir)?;
+
+        let context = Arc::new(BulkLoadContext {
+            store: self.store.clone(),
+            syntax,
+            base_iri,
+            to_graph_name,
+            blank_node_seed: random(),
+            temp_dir: temp_dir.clone(),
+            batch_size: self.batch_size,
+            loaded: AtomicU64::new(0),
+            failed: AtomicBool::new(false),
+            write_lock: Mutex::new(()),
+            on_progress: self.on_progress.clone(),
+            on_parse_error: self.on_parse_error.clone(),
+        });
+        let (sender, receiver) = sync_channel(2 * self.num_threads);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..self.num_threads)
+            .map(|_| {
+                let context = context.clone();
+                let receiver = receiver.clone();
+                thread::spawn(move || context.work(&receiver))
+            })
+            .collect::<Vec<_>>();
+        // The channel is closed as soon as all the loader threads have stopped
+        drop(receiver);
+
+        let mut result = produce(&sender);
+        drop(sender);
+        for worker in workers {
+            let worker_result = worker.join().unwrap_or_else(|_| {
+                Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    "A bulk loader thread has panicked",
+                ))
+            });
+            if result.is_ok() {
+                result = worker_result;
+            }
+        }
+        let cleanup = remove_dir_all(&temp_dir);
+
+        // The ingested files bypass the change capture, even if the load has failed after having written some quads
+        if (result.is_err() || context.loaded.load(Ordering::Relaxed) > 0)
+            && self.store.captures_changes()
+        {
+            self.store.changes.commit_resync(|sequence_number| {
+                let mut batch = WriteBatch::default();
+                batch.put(CHANGE_SEQUENCE_KEY, &sequence_number.to_be_bytes());
+                if self.store.change_log.load(Ordering::Relaxed) {
+                    batch.put(
+                        change_log_key(sequence_number),
+                        ChangeBatch::encode_resync_body(),
+                    );
+                }
+                self.store.db.write(batch).map_err(map_err)
+            })?;
+        }
+
+        // The ingested files bypass the inference maintenance
+        if let Some(regime) = regime {
+            materialize(&self.store, regime)?;
+        }
+        result.and(cleanup)
     }
+}
 
-    fn posg_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.posg_cf(), prefix, QuadEncoding::POSG)
-    }
+/// A job sent to the bulk loader threads
+enum BulkLoadJob {
+    /// Complete N-Triples or N-Quads lines to parse
+    Lines {
+        data: Vec<u8>,
+        /// The line number of the first line in the file, used in the error messages
+        first_line: u64,
+    },
+    /// Already parsed quads
+    Quads(Vec<Quad>),
+}
 
-    fn ospg_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.ospg_cf(), prefix, QuadEncoding::OSPG)
-    }
+struct BulkLoadContext {
+    store: RocksDbStore,
+    /// The syntax of the [`BulkLoadJob::Lines`] jobs
+    syntax: TurtleSyntax,
+    base_iri: Option<Iri<String>>,
+    /// If set, the quads parsed from the [`BulkLoadJob::Lines`] jobs are moved to this graph
+    to_graph_name: Option<GraphName>,
+    /// Shared by all the parsers to map the same blank node label to the same blank node
+    blank_node_seed: u128,
+    temp_dir: PathBuf,
+    batch_size: usize,
+    loaded: AtomicU64,
+    /// Set if a loader thread has failed in order to stop the other ones
+    failed: AtomicBool,
+    /// Held while a batch is written so that the quads loaded by several threads are counted once in the statistics
+    write_lock: Mutex<()>,
+    on_progress: Option<Arc<dyn Fn(u64) + Send + Sync>>,
+    /// If set, the invalid lines of the [`BulkLoadJob::Lines`] jobs are skipped
+    on_parse_error: Option<Arc<dyn Fn(io::Error) + Send + Sync>>,
+}
 
-    fn gspo_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.gspo_cf(), prefix, QuadEncoding::GSPO)
+impl BulkLoadContext {
+    fn work(&self, receiver: &Mutex<Receiver<BulkLoadJob>>) -> Result<(), io::Error> {
+        let result = self.process_jobs(receiver);
+        if result.is_err() {
+            self.failed.store(true, Ordering::Relaxed);
+        }
+        result
     }
 
-    fn gpos_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.gpos_cf(), prefix, QuadEncoding::GPOS)
+    fn process_jobs(&self, receiver: &Mutex<Receiver<BulkLoadJob>>) -> Result<(), io::Error> {
+        let mut batch = BulkLoadBatch::default();
+        while let Some(job) = self.next_job(receiver) {
+            match job {
+                BulkLoadJob::Lines { data, first_line } => {
+                    let mut parser =
+                        TurtleParser::new(data.as_slice(), self.syntax, self.base_iri.clone())
+                            .with_blank_node_seed(self.blank_node_seed)
+                            .with_first_line_number(first_line);
+                    if self.on_parse_error.is_some() {
+                        parser = parser.with_error_recovery();
+                    }
+                    for quad in parser {
+                        let mut quad = match (quad, &self.on_parse_error) {
+                            (Ok(quad), _) => quad,
+                            (Err(error), Some(on_parse_error))
+                                if error.kind() == io::ErrorKind::InvalidData =>
+                            {
+                                on_parse_error(error);
+                                continue;
+                            }
+                            (Err(error), _) => return Err(error),
+                        };
+                        if let Some(graph_name) = &self.to_graph_name {
+                            quad.graph_name = graph_name.clone();
+                        }
+                        batch.insert(quad.as_ref())?;
+                    }
+                }
+                BulkLoadJob::Quads(quads) => {
+                    for quad in &quads {
+                        batch.insert(quad.as_ref())?;
+                    }
+                }
+            }
+            if batch.quads.len() >= self.batch_size {
+                self.write(&mut batch)?;
+            }
+        }
+        self.write(&mut batch)
     }
 
-    fn gosp_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.gosp_cf(), prefix, QuadEncoding::GOSP)
+    fn next_job(&self, receiver: &Mutex<Receiver<BulkLoadJob>>) -> Option<BulkLoadJob> {
+        if self.failed.load(Ordering::Relaxed) {
+            return None;
+        }
+        receiver.lock().ok()?.recv().ok()
     }
 
-    fn dspo_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.dspo_cf(), prefix, QuadEncoding::DSPO)
+    /// Writes the batch content into the store
+    fn write(&self, batch: &mut BulkLoadBatch) -> Result<(), io::Error> {
+        if batch.quads.is_empty() {
+            return Ok(());
+        }
+        self.ingest(
+            ID2STR_CF,
+            batch
+                .id2str
+                .drain()
+                .map(|(id, value)| (id.to_be_bytes().to_vec(), value.into_bytes()))
+                .collect(),
+        )?;
+        let count = batch.quads.len() as u64;
+        let (default_quads, named_quads): (Vec<_>, Vec<_>) = batch
+            .quads
+            .drain(..)
+            .partition(|quad| quad.graph_name.is_default_graph());
+
+        let lock = self.write_lock.lock().map_err(|_| {
+            io::Error::new(io::ErrorKind::Other, "A bulk loader thread has panicked")
+        })?;
+        // Only the quads that are not already in the store are written and counted in the statistics
+        let default_quads = self.new_quads(self.store.dspo_cf(), default_quads, write_spo_quad)?;
+        let named_quads = self.new_quads(self.store.gspo_cf(), named_quads, write_gspo_quad)?;
+        // The strings have already been ingested
+        let strings = HashMap::new();
+        let mut statistics = PendingStatistics::default();
+        for quad in default_quads.iter().chain(&named_quads) {
+            record_quad_insertion(&mut statistics.counters(&self.store, &strings), quad)?;
+        }
+        self.ingest_index(DSPO_CF, &default_quads, write_spo_quad)?;
+        self.ingest_index(DPOS_CF, &default_quads, write_pos_quad)?;
+        self.ingest_index(DOSP_CF, &default_quads, write_osp_quad)?;
+        self.ingest_index(SPOG_CF, &named_quads, write_spog_quad)?;
+        self.ingest_index(POSG_CF, &named_quads, write_posg_quad)?;
+        self.ingest_index(OSPG_CF, &named_quads, write_ospg_quad)?;
+        self.ingest_index(GSPO_CF, &named_quads, write_gspo_quad)?;
+        self.ingest_index(GPOS_CF, &named_quads, write_gpos_quad)?;
+        self.ingest_index(GOSP_CF, &named_quads, write_gosp_quad)?;
+        self.ingest(
+            GRAPHS_CF,
+            named_quads
+                .iter()
+                .map(|quad| (encode_term(quad.graph_name), Vec::new()))
+                .collect(),
+        )?;
+        let mut statistics_batch = WriteBatch::default();
+        statistics.write(&self.store, &mut statistics_batch);
+        self.store.db.write(statistics_batch).map_err(map_err)?;
+        drop(lock);
+
+        let loaded = self.loaded.fetch_add(count, Ordering::Relaxed) + count;
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(loaded);
+        }
+        Ok(())
     }
 
-    fn dpos_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.dpos_cf(), prefix, QuadEncoding::DPOS)
+    /// Returns the quads that are not in the index `cf` yet, each of them once
+    ///
+    /// The quads are sorted by their key in the index and looked up with a single forward iterator
+    /// instead of a random read per quad.
+    fn new_quads(
+        &self,
+        cf: &ColumnFamily,
+        quads: Vec<EncodedQuad>,
+        write_quad: fn(&mut Vec<u8>, &EncodedQuad),
+    ) -> Result<Vec<EncodedQuad>, io::Error> {
+        let mut entries = quads
+            .into_iter()
+            .map(|quad| {
+                let mut key = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+                write_quad(&mut key, &quad);
+                (key, quad)
+            })
+            .collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+        entries.dedup_by(|a, b| a.0 == b.0);
+
+        let mut iter = self.store.db.raw_iterator_cf(cf);
+        let mut new_quads = Vec::with_capacity(entries.len());
+        for (key, quad) in entries {
+            // The iterator only moves forward: it is already at the right place if it is after the key
+            if !matches!(iter.key(), Some(current) if current >= key.as_slice()) {
+                iter.seek(&key);
+            }
+            if iter.key() != Some(key.as_slice()) {
+                new_quads.push(quad);
+            }
+        }
+        iter.status().map_err(map_err)?;
+        Ok(new_quads)
     }
 
-    fn dosp_quads(&self, prefix: Vec<u8>) -> DecodingIndexIterator {
-        self.inner_quads(self.dosp_cf(), prefix, QuadEncoding::DOSP)
+    fn ingest_index(
+        &self,
+        cf: &str,
+        quads: &[EncodedQuad],
+        write_quad: fn(&mut Vec<u8>, &EncodedQuad),
+    ) -> Result<(), io::Error> {
+        self.ingest(
+            cf,
+            quads
+                .iter()
+                .map(|quad| {
+                    let mut key = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+                    write_quad(&mut key, quad);
+                    (key, Vec::new())
+                })
+                .collect(),
+        )
     }
 
-    fn inner_quads(
-        &self,
-        cf: &ColumnFamily,
-        prefix: Vec<u8>,
-        encoding: QuadEncoding,
-    ) -> DecodingIndexIterator {
-        let mut iter = self.db_iter(cf);
-        iter.iter.seek(&prefix);
-        DecodingIndexIterator {
-            iter,
-            prefix,
-            encoding,
+    /// Writes the entries into a SST file and ingests it into the column family `cf`
+    fn ingest(&self, cf: &str, mut entries: Vec<(Vec<u8>, Vec<u8>)>) -> Result<(), io::Error> {
+        if entries.is_empty() {
+            // RocksDB does not allow empty SST files
+            return Ok(());
         }
+        entries.sort_unstable();
+        entries.dedup_by(|a, b| a.0 == b.0);
+
+        let path = self
+            .temp_dir
+            .join(format!("{}_{:x}.sst", cf, random::<u128>()));
+        let options = Options::default();
+        let mut writer = SstFileWriter::create(&options);
+        writer.open(&path).map_err(map_err)?;
+        for (key, value) in entries {
+            writer.put(key, value).map_err(map_err)?;
+        }
+        writer.finish().map_err(map_err)?;
+
+        let mut ingest_options = IngestExternalFileOptions::default();
+        ingest_options.set_move_files(true);
+        self.store
+            .db
+            .ingest_external_file_cf_opts(get_cf(&self.store.db, cf), &ingest_options, vec![path])
+            .map_err(map_err)
     }
+}
 
-    #[allow(unsafe_code)]
-    fn db_iter(&self, cf: &ColumnFamily) -> StaticDBRowIterator {
-        // Valid because it's the same database so db can't be dropped before iter
-        unsafe { StaticDBRowIterator::new(self.db.raw_iterator_cf(cf), self.db.clone()) }
+/// The quads encoded by a bulk loader thread and not written yet
+#[derive(Default)]
+struct BulkLoadBatch {
+    id2str: HashMap<StrHash, String>,
+    quads: Vec<EncodedQuad>,
+}
+
+impl BulkLoadBatch {
+    fn insert(&mut self, quad: QuadRef<'_>) -> Result<(), io::Error> {
+        let quad = self.encode_quad(quad)?;
+        self.quads.push(quad);
+        Ok(())
+    }
+}
+
+impl StrEncodingAware for BulkLoadBatch {
+    type Error = io::Error;
+    type StrId = StrHash;
+}
+
+impl StrContainer for BulkLoadBatch {
+    fn insert_str(&mut self, value: &str) -> Result<StrHash, io::Error> {
+        let key = StrHash::new(value);
+        self.id2str.entry(key).or_insert_with(|| value.to_owned());
+        Ok(key)
     }
 }
 
@@ -774,9 +2344,7 @@ impl StrEncodingAware for RocksDbStore {
 
 impl StrLookup for RocksDbStore {
     fn get_str(&self, id: StrHash) -> Result<Option<String>, io::Error> {
-        self.db
-            .get_cf(self.id2str_cf(), &id.to_be_bytes())
-            .map_err(map_err)?
+        self.db_get(self.id2str_cf(), &id.to_be_bytes())?
             .map(String::from_utf8)
             .transpose()
             .map_err(invalid_data_error)
@@ -784,18 +2352,11 @@ impl StrLookup for RocksDbStore {
 
     fn get_str_id(&self, value: &str) -> Result<Option<StrHash>, io::Error> {
         let id = StrHash::new(value);
-        Ok(
-            if self
-                .db
-                .get_cf(self.id2str_cf(), &id.to_be_bytes())
-                .map_err(map_err)?
-                .is_some()
-            {
-                Some(id)
-            } else {
-                None
-            },
-        )
+        Ok(if self.db_contains(self.id2str_cf(), &id.to_be_bytes())? {
+            Some(id)
+        } else {
+            None
+        })
     }
 }
 
@@ -874,11 +2435,206 @@ impl ReadableEncodedStore for RocksDbStore {
     }
 
     fn contains_encoded_named_graph(&self, graph_name: EncodedTerm) -> Result<bool, io::Error> {
-        Ok(self
-            .db
-            .get_cf(self.graphs_cf(), &encode_term(graph_name))
-            .map_err(map_err)?
-            .is_some())
+        self.db_contains(self.graphs_cf(), &encode_term(graph_name))
+    }
+
+    fn encoded_quads_count(&self) -> Result<u64, io::Error> {
+        read_quads_count(|key| self.get_counter(key))
+    }
+
+    fn encoded_predicate_statistics(
+        &self,
+        predicate: EncodedTerm,
+    ) -> Result<PredicateStatistics, io::Error> {
+        read_predicate_statistics(predicate, |key| self.get_counter(key))
+    }
+
+    fn encoded_text_search(
+        &self,
+        query: &str,
+    ) -> Result<Option<Vec<TextMatch<StrHash>>>, io::Error> {
+        if !self.is_text_index_enabled() {
+            return Ok(None);
+        }
+        search_text_index(
+            query,
+            |key| self.get_counter(key),
+            |prefix| {
+                let mut counters = Vec::new();
+                let mut iter = self.db_iter(self.stats_cf());
+                iter.iter.seek(prefix);
+                while let (Some(key), Some(value)) = (iter.key(), iter.value()) {
+                    if let Some(key) = key.strip_prefix(prefix) {
+                        counters.push((key.to_vec(), decode_counter(value)?));
+                    } else {
+                        break;
+                    }
+                    iter.next();
+                }
+                Ok(counters)
+            },
+        )
+        .map(Some)
+    }
+}
+
+impl InferenceStore for RocksDbStore {
+    fn encode_vocabulary(&self, node: NamedNodeRef<'static>) -> Result<EncodedTerm, io::Error> {
+        if let Some(term) = self.get_encoded_named_node(node)? {
+            return Ok(term);
+        }
+        let mut writer = self.inference_batch_writer();
+        let term = writer.encode_named_node(node)?;
+        writer.apply()?;
+        Ok(term)
+    }
+
+    fn is_inferred(&self, quad: &EncodedQuad) -> Result<bool, io::Error> {
+        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+        write_spog_quad(&mut buffer, quad);
+        self.db_contains(self.inferred_cf(), &buffer)
+    }
+
+    fn inferred_quads(&self) -> Result<Vec<EncodedQuad>, io::Error> {
+        self.inner_quads(self.inferred_cf(), Vec::default(), QuadEncoding::SPOG)
+            .collect()
+    }
+
+    fn insert_inferred(&self, quads: &[EncodedQuad]) -> Result<(), io::Error> {
+        let mut writer = self.inference_batch_writer();
+        for quad in quads {
+            write_spog_quad(&mut writer.buffer, quad);
+            writer.batch.put_cf(self.inferred_cf(), &writer.buffer, &[]);
+            writer.buffer.clear();
+            writer.insert_encoded(quad)?;
+        }
+        writer.apply()
+    }
+
+    fn remove_inferred(&self, quads: &[EncodedQuad]) -> Result<(), io::Error> {
+        let mut writer = self.inference_batch_writer();
+        for quad in quads {
+            write_spog_quad(&mut writer.buffer, quad);
+            writer.batch.delete_cf(self.inferred_cf(), &writer.buffer);
+            writer.buffer.clear();
+            writer.remove_encoded(quad)?;
+        }
+        writer.apply()
+    }
+
+    fn unmark_inferred(&self, quads: &[EncodedQuad]) -> Result<(), io::Error> {
+        let mut writer = self.inference_batch_writer();
+        for quad in quads {
+            write_spog_quad(&mut writer.buffer, quad);
+            writer.batch.delete_cf(self.inferred_cf(), &writer.buffer);
+            writer.buffer.clear();
+            writer.apply_if_big()?;
+        }
+        writer.apply()
+    }
+}
+
+/// Statistics changes that are not written yet in the database
+///
+/// The batch writes are not visible before the batch is applied so we keep track here of the quads
+/// inserted or removed by the batch in order to update the statistics counters only once per quad.
+#[derive(Default)]
+struct PendingStatistics {
+    counters: HashMap<Vec<u8>, u64>,
+    quads: HashMap<EncodedQuad, bool>,
+}
+
+impl PendingStatistics {
+    fn contains_quad(&self, store: &RocksDbStore, quad: &EncodedQuad) -> Result<bool, io::Error> {
+        if let Some(contains) = self.quads.get(quad) {
+            Ok(*contains)
+        } else {
+            store.contains_encoded(quad)
+        }
+    }
+
+    fn record_insertion(
+        &mut self,
+        store: &RocksDbStore,
+        strings: &HashMap<StrHash, String>,
+        quad: &EncodedQuad,
+    ) -> Result<(), io::Error> {
+        if !self.contains_quad(store, quad)? {
+            record_quad_insertion(&mut self.counters(store, strings), quad)?;
+            self.quads.insert(*quad, true);
+        }
+        Ok(())
+    }
+
+    fn record_removal(
+        &mut self,
+        store: &RocksDbStore,
+        strings: &HashMap<StrHash, String>,
+        quad: &EncodedQuad,
+    ) -> Result<(), io::Error> {
+        if self.contains_quad(store, quad)? {
+            record_quad_removal(&mut self.counters(store, strings), quad)?;
+            self.quads.insert(*quad, false);
+        }
+        Ok(())
+    }
+
+    /// `strings` are the strings written by the batch that are not visible yet in the database
+    fn counters<'a>(
+        &'a mut self,
+        store: &'a RocksDbStore,
+        strings: &'a HashMap<StrHash, String>,
+    ) -> PendingCounters<'a> {
+        PendingCounters {
+            store,
+            strings,
+            counters: &mut self.counters,
+        }
+    }
+
+    /// Adds the counters changes to the batch
+    fn write(&mut self, store: &RocksDbStore, batch: &mut WriteBatch) {
+        for (key, value) in self.counters.drain() {
+            if value == 0 {
+                batch.delete_cf(store.stats_cf(), key);
+            } else {
+                batch.put_cf(store.stats_cf(), key, encode_counter(value));
+            }
+        }
+        self.quads.clear();
+    }
+}
+
+struct PendingCounters<'a> {
+    store: &'a RocksDbStore,
+    strings: &'a HashMap<StrHash, String>,
+    counters: &'a mut HashMap<Vec<u8>, u64>,
+}
+
+impl CountersStore for PendingCounters<'_> {
+    type Error = io::Error;
+
+    fn add_to_counter(&mut self, key: &[u8], delta: i64) -> Result<u64, io::Error> {
+        let value = if let Some(value) = self.counters.get(key) {
+            *value
+        } else {
+            self.store.get_counter(key)?
+        };
+        let value = add_delta(value, delta);
+        self.counters.insert(key.to_vec(), value);
+        Ok(value)
+    }
+
+    fn has_text_index(&self) -> bool {
+        self.store.is_text_index_enabled()
+    }
+
+    fn get_text_str(&self, id: StrHash) -> Result<Option<String>, io::Error> {
+        if let Some(value) = self.strings.get(&id) {
+            Ok(Some(value.clone()))
+        } else {
+            self.store.get_str(id)
+        }
     }
 }
 
@@ -886,23 +2642,43 @@ struct AutoBatchWriter<'a> {
     store: &'a RocksDbStore,
     batch: WriteBatch,
     buffer: Vec<u8>,
+    statistics: PendingStatistics,
+    /// The changes to propagate to the inferred quads if an entailment regime is set
+    changes: Option<QuadChanges<StrHash>>,
+    /// The changes to publish if they are captured
+    collector: Option<ChangeCollector<StrHash>>,
+    /// The strings inserted by the batch, only kept if the changes are captured or the full-text index is enabled
+    new_strings: HashMap<StrHash, String>,
 }
 
 impl AutoBatchWriter<'_> {
-    fn apply(self) -> Result<(), io::Error> {
-        self.store.db.write(self.batch).map_err(map_err)
+    fn apply(mut self) -> Result<(), io::Error> {
+        self.write_batch()?;
+        if let Some(changes) = self.changes.take() {
+            self.store.propagate_inferences(changes)?;
+        }
+        Ok(())
     }
 
     fn apply_if_big(&mut self) -> Result<(), io::Error> {
         if self.batch.len() > MAX_TRANSACTION_SIZE {
-            self.store
-                .db
-                .write(take(&mut self.batch))
-                .map_err(map_err)?;
+            self.write_batch()?;
         }
         Ok(())
     }
 
+    fn write_batch(&mut self) -> Result<(), io::Error> {
+        self.store.ensure_writable()?;
+        self.statistics.write(self.store, &mut self.batch);
+        self.store.write_with_changes(
+            take(&mut self.batch),
+            self.collector.as_mut(),
+            &self.new_strings,
+        )?;
+        self.new_strings.clear();
+        Ok(())
+    }
+
     fn clear_cf(&mut self, cf: &ColumnFamily) {
         self.batch.delete_range_cf(
             cf,
@@ -940,12 +2716,25 @@ impl StrContainer for AutoBatchWriter<'_> {
         let key = StrHash::new(value);
         self.batch
             .put_cf(self.store.id2str_cf(), &key.to_be_bytes(), value);
+        if self.collector.is_some() || self.store.is_text_index_enabled() {
+            self.new_strings.insert(key, value.to_owned());
+        }
         Ok(key)
     }
 }
 
 impl WritableEncodedStore for AutoBatchWriter<'_> {
     fn insert_encoded(&mut self, quad: &EncodedQuad) -> Result<(), io::Error> {
+        if let Some(collector) = &mut self.collector {
+            let (store, statistics) = (self.store, &self.statistics);
+            collector.record_insertion(quad, || store.contains_asserted(statistics, quad))?;
+        }
+        self.statistics
+            .record_insertion(self.store, &self.new_strings, quad)?;
+        if let Some(changes) = &mut self.changes {
+            changes.record_insertion(quad);
+        }
+
         if quad.graph_name.is_default_graph() {
             write_spo_quad(&mut self.buffer, quad);
             self.batch.put_cf(self.store.dspo_cf(), &self.buffer, &[]);
@@ -992,6 +2781,16 @@ impl WritableEncodedStore for AutoBatchWriter<'_> {
     }
 
     fn remove_encoded(&mut self, quad: &EncodedQuad) -> Result<(), io::Error> {
+        if let Some(collector) = &mut self.collector {
+            let (store, statistics) = (self.store, &self.statistics);
+            collector.record_removal(quad, || store.contains_asserted(statistics, quad))?;
+        }
+        self.statistics
+            .record_removal(self.store, &self.new_strings, quad)?;
+        if let Some(changes) = &mut self.changes {
+            changes.record_removal(quad);
+        }
+
         if quad.graph_name.is_default_graph() {
             write_spo_quad(&mut self.buffer, quad);
             self.batch.delete_cf(self.store.dspo_cf(), &self.buffer);
@@ -1041,15 +2840,34 @@ impl WritableEncodedStore for AutoBatchWriter<'_> {
 
     fn clear_encoded_graph(&mut self, graph_name: EncodedTerm) -> Result<(), io::Error> {
         if graph_name.is_default_graph() {
+            // We write the pending changes first in order to count all the removed quads
+            self.write_batch()?;
+            let mut counters = self.statistics.counters(self.store, &self.new_strings);
+            for quad in self.store.quads_for_graph(graph_name) {
+                let quad = quad?;
+                record_quad_removal(&mut counters, &quad)?;
+                if let Some(changes) = &mut self.changes {
+                    changes.record_removal(&quad);
+                }
+                if let Some(collector) = &mut self.collector {
+                    let store = self.store;
+                    collector.record_removal(&quad, || {
+                        Ok::<_, io::Error>(
+                            !(store.entailment_regime().is_some() && store.is_inferred(&quad)?),
+                        )
+                    })?;
+                }
+            }
             self.clear_cf(self.store.dspo_cf());
             self.clear_cf(self.store.dpos_cf());
             self.clear_cf(self.store.dosp_cf());
+            self.write_batch()
         } else {
             for quad in self.store.quads_for_graph(graph_name) {
                 self.remove_encoded(&quad?)?;
             }
+            self.apply_if_big()
         }
-        self.apply_if_big()
     }
 
     fn remove_encoded_named_graph(&mut self, graph_name: EncodedTerm) -> Result<(), io::Error> {
@@ -1062,6 +2880,15 @@ impl WritableEncodedStore for AutoBatchWriter<'_> {
     }
 
     fn clear(&mut self) -> Result<(), io::Error> {
+        if self.collector.is_some() {
+            // We write the pending changes first in order to capture all the removed quads
+            self.write_batch()?;
+            if let Some(collector) = &mut self.collector {
+                for quad in self.store.asserted_encoded_quads() {
+                    collector.record_removal(&quad?, || Ok::<_, io::Error>(true))?;
+                }
+            }
+        }
         self.clear_cf(self.store.spog_cf());
         self.clear_cf(self.store.posg_cf());
         self.clear_cf(self.store.ospg_cf());
@@ -1072,8 +2899,15 @@ impl WritableEncodedStore for AutoBatchWriter<'_> {
         self.clear_cf(self.store.dpos_cf());
         self.clear_cf(self.store.dosp_cf());
         self.clear_cf(self.store.graphs_cf());
+        self.clear_cf(self.store.stats_cf());
+        self.clear_cf(self.store.inferred_cf());
         self.clear_cf(self.store.id2str_cf());
-        self.apply_if_big()
+        self.statistics = PendingStatistics::default();
+        if let Some(changes) = &mut self.changes {
+            // There is nothing left to propagate
+            *changes = QuadChanges::default();
+        }
+        self.write_batch()
     }
 }
 
@@ -1083,6 +2917,9 @@ pub struct RocksDbTransaction<'a> {
     batch: WriteBatch,
     buffer: Vec<u8>,
     new_strings: HashMap<StrHash, String>,
+    statistics: PendingStatistics,
+    changes: Option<QuadChanges<StrHash>>,
+    collector: Option<ChangeCollector<StrHash>>,
 }
 
 impl RocksDbTransaction<'_> {
@@ -1107,7 +2944,7 @@ impl RocksDbTransaction<'_> {
         to_graph_name: impl Into<GraphNameRef<'a>>,
         base_iri: Option<&str>,
     ) -> Result<(), io::Error> {
-        load_graph(self, reader, syntax, to_graph_name.into(), base_iri)?;
+        load_graph(self, reader, syntax, to_graph_name.into(), base_iri, None)?;
         Ok(())
     }
 
@@ -1131,7 +2968,7 @@ impl RocksDbTransaction<'_> {
         format: DatasetFormat,
         base_iri: Option<&str>,
     ) -> Result<(), io::Error> {
-        load_dataset(self, reader, format, base_iri)?;
+        load_dataset(self, reader, format, base_iri, None)?;
         Ok(())
     }
 
@@ -1151,8 +2988,15 @@ impl RocksDbTransaction<'_> {
         }
     }
 
-    fn apply(self) -> Result<(), io::Error> {
-        self.store.db.write(self.batch).map_err(map_err)
+    fn apply(mut self) -> Result<(), io::Error> {
+        self.store.ensure_writable()?;
+        self.statistics.write(self.store, &mut self.batch);
+        self.store
+            .write_with_changes(self.batch, self.collector.as_mut(), &self.new_strings)?;
+        if let Some(changes) = self.changes {
+            self.store.propagate_inferences(changes)?;
+        }
+        Ok(())
     }
 }
 
@@ -1201,6 +3045,16 @@ impl StrContainer for RocksDbTransaction<'_> {
 
 impl WritableEncodedStore for RocksDbTransaction<'_> {
     fn insert_encoded(&mut self, quad: &EncodedQuad) -> Result<(), io::Error> {
+        if let Some(collector) = &mut self.collector {
+            let (store, statistics) = (self.store, &self.statistics);
+            collector.record_insertion(quad, || store.contains_asserted(statistics, quad))?;
+        }
+        self.statistics
+            .record_insertion(self.store, &self.new_strings, quad)?;
+        if let Some(changes) = &mut self.changes {
+            changes.record_insertion(quad);
+        }
+
         if quad.graph_name.is_default_graph() {
             write_spo_quad(&mut self.buffer, quad);
             self.batch.put_cf(self.store.dspo_cf(), &self.buffer, &[]);
@@ -1247,6 +3101,16 @@ impl WritableEncodedStore for RocksDbTransaction<'_> {
     }
 
     fn remove_encoded(&mut self, quad: &EncodedQuad) -> Result<(), io::Error> {
+        if let Some(collector) = &mut self.collector {
+            let (store, statistics) = (self.store, &self.statistics);
+            collector.record_removal(quad, || store.contains_asserted(statistics, quad))?;
+        }
+        self.statistics
+            .record_removal(self.store, &self.new_strings, quad)?;
+        if let Some(changes) = &mut self.changes {
+            changes.record_removal(quad);
+        }
+
         if quad.graph_name.is_default_graph() {
             write_spo_quad(&mut self.buffer, quad);
             self.batch.delete_cf(self.store.dspo_cf(), &self.buffer);
@@ -1324,19 +3188,26 @@ fn get_cf<'a>(db: &'a DB, name: &str) -> &'a ColumnFamily {
 
 struct StaticDBRowIterator {
     iter: DBRawIterator<'static>,
+    _snapshot: Option<Arc<StaticSnapshot>>, // needed to ensure that the snapshot still lives while iter is used
     _db: Arc<DB>, // needed to ensure that DB still lives while iter is used
 }
 
 impl StaticDBRowIterator {
     /// Creates a static iterator from a non static one by keeping a ARC reference to the database
-    /// Caller must ensure that the iterator belongs to the same database
+    /// and to the snapshot the iterator is reading from, if any.
+    /// Caller must ensure that the iterator belongs to the same database and snapshot
     ///
     /// This unsafe method is required to get static iterators and ease the usage of the library
     /// and make streaming Python bindings possible
     #[allow(unsafe_code)]
-    unsafe fn new(iter: DBRawIterator<'_>, db: Arc<DB>) -> Self {
+    unsafe fn new(
+        iter: DBRawIterator<'_>,
+        db: Arc<DB>,
+        snapshot: Option<Arc<StaticSnapshot>>,
+    ) -> Self {
         Self {
             iter: transmute(iter),
+            _snapshot: snapshot,
             _db: db,
         }
     }
@@ -1345,11 +3216,39 @@ impl StaticDBRowIterator {
         self.iter.key()
     }
 
+    fn value(&self) -> Option<&[u8]> {
+        self.iter.value()
+    }
+
     fn next(&mut self) {
         self.iter.next()
     }
 }
 
+/// A RocksDB snapshot keeping a ARC reference to its database
+struct StaticSnapshot {
+    snapshot: Snapshot<'static>,
+    _db: Arc<DB>, // needed to ensure that DB still lives while snapshot is used
+}
+
+impl StaticSnapshot {
+    #[allow(unsafe_code)]
+    fn new(db: Arc<DB>) -> Self {
+        Self {
+            // Valid because the snapshot is dropped before db
+            snapshot: unsafe { transmute(db.snapshot()) },
+            _db: db,
+        }
+    }
+}
+
+// RocksDB snapshots are immutable and could be read from multiple threads
+#[allow(unsafe_code)]
+unsafe impl Send for StaticSnapshot {}
+
+#[allow(unsafe_code)]
+unsafe impl Sync for StaticSnapshot {}
+
 pub(crate) struct DecodingIndexesIterator {
     first: DecodingIndexIterator,
     second: Option<DecodingIndexIterator>,
@@ -1394,23 +3293,65 @@ struct DecodingIndexIterator {
 impl Iterator for DecodingIndexIterator {
     type Item = Result<EncodedQuad, io::Error>;
 
-    fn next(&mut self) -> Option<Result<EncodedQuad, io::Error>> {
-        if let Some(key) = self.iter.key() {
-            if key.starts_with(&self.prefix) {
-                let result = self.encoding.decode(key);
-                self.iter.next();
-                Some(result)
-            } else {
-                None
-            }
+    fn next(&mut self) -> Option<Result<EncodedQuad, io::Error>> {
+        if let Some(key) = self.iter.key() {
+            if key.starts_with(&self.prefix) {
+                let result = self.encoding.decode(key);
+                self.iter.next();
+                Some(result)
+            } else {
+                None
+            }
+        } else {
+            None
+        }
+    }
+}
+
+fn map_err(e: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+fn change_log_key(sequence_number: u64) -> Vec<u8> {
+    let mut key = CHANGE_LOG_PREFIX.to_vec();
+    key.extend_from_slice(&sequence_number.to_be_bytes());
+    key
+}
+
+fn decode_sequence_number(value: &[u8]) -> Result<u64, io::Error> {
+    Ok(u64::from_be_bytes(value.try_into().map_err(|_| {
+        invalid_data_error("The change sequence numbers should be encoded on 8 bytes")
+    })?))
+}
+
+/// Reads the strings from the store and from the ones inserted by a pending write
+struct PendingStrings<'a> {
+    store: &'a RocksDbStore,
+    new_strings: &'a HashMap<StrHash, String>,
+}
+
+impl StrEncodingAware for PendingStrings<'_> {
+    type Error = io::Error;
+    type StrId = StrHash;
+}
+
+impl StrLookup for PendingStrings<'_> {
+    fn get_str(&self, id: StrHash) -> Result<Option<String>, io::Error> {
+        if let Some(value) = self.new_strings.get(&id) {
+            Ok(Some(value.clone()))
         } else {
-            None
+            self.store.get_str(id)
         }
     }
-}
 
-fn map_err(e: Error) -> io::Error {
-    io::Error::new(io::ErrorKind::Other, e)
+    fn get_str_id(&self, value: &str) -> Result<Option<StrHash>, io::Error> {
+        let id = StrHash::new(value);
+        if self.new_strings.contains_key(&id) {
+            Ok(Some(id))
+        } else {
+            self.store.get_str_id(value)
+        }
+    }
 }
 
 /// An iterator returning the quads contained in a [`RocksDbStore`].
@@ -1466,7 +3407,7 @@ fn store() -> Result<(), io::Error> {
     use std::env::temp_dir;
     use std::fs::remove_dir_all;
 
-    let main_s = NamedOrBlankNode::from(BlankNode::default());
+    let main_s = Subject::from(BlankNode::default());
     let main_p = NamedNode::new("http://example.com").unwrap();
     let main_o = Term::from(Literal::from(1));
     let main_g = GraphName::from(BlankNode::default());
@@ -1663,3 +3604,644 @@ fn store() -> Result<(), io::Error> {
     remove_dir_all(&repo_path)?;
     Ok(())
 }
+
+#[test]
+fn statistics() -> Result<(), io::Error> {
+    use crate::model::*;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    let s1 = NamedNode::new_unchecked("http://example.com/s1");
+    let s2 = NamedNode::new_unchecked("http://example.com/s2");
+    let p = NamedNode::new_unchecked("http://example.com/p");
+    let o = Literal::from(1);
+    let g = NamedNode::new_unchecked("http://example.com/g");
+
+    let mut repo_path = temp_dir();
+    repo_path.push(random::<u128>().to_string());
+
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        store.insert(QuadRef::new(&s1, &p, &o, GraphNameRef::DefaultGraph))?;
+        store.insert(QuadRef::new(&s1, &p, &o, GraphNameRef::DefaultGraph))?;
+        store.transaction(|t| {
+            t.insert(QuadRef::new(&s1, &p, &o, &g))?;
+            t.insert(QuadRef::new(&s2, &p, &o, &g))?;
+            t.insert(QuadRef::new(&s2, &p, &o, &g))
+        })?;
+
+        let encoded_p = store.get_encoded_named_node(p.as_ref())?.unwrap();
+        assert_eq!(store.encoded_quads_count()?, 3);
+        assert_eq!(
+            store.encoded_predicate_statistics(encoded_p)?,
+            PredicateStatistics {
+                quads: 3,
+                distinct_subjects: 2,
+                distinct_objects: 1
+            }
+        );
+
+        store.remove(QuadRef::new(&s2, &p, &o, &g))?;
+        assert_eq!(
+            store.encoded_predicate_statistics(encoded_p)?,
+            PredicateStatistics {
+                quads: 2,
+                distinct_subjects: 1,
+                distinct_objects: 1
+            }
+        );
+
+        store.clear_graph(GraphNameRef::DefaultGraph)?;
+        store.remove_named_graph(&g)?;
+        assert_eq!(store.encoded_quads_count()?, 0);
+        assert_eq!(
+            store.encoded_predicate_statistics(encoded_p)?,
+            PredicateStatistics::default()
+        );
+    }
+
+    remove_dir_all(&repo_path)?;
+    Ok(())
+}
+
+#[test]
+fn inference() -> Result<(), io::Error> {
+    use crate::model::vocab::{rdf, rdfs};
+    use crate::model::*;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    let person = NamedNodeRef::new_unchecked("http://example.com/Person");
+    let agent = NamedNodeRef::new_unchecked("http://example.com/Agent");
+    let alice = NamedNodeRef::new_unchecked("http://example.com/alice");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+
+    let mut repo_path = temp_dir();
+    repo_path.push(random::<u128>().to_string());
+
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        store.set_entailment_regime(Some(EntailmentRegime::Rdfs))?;
+        store.transaction(|t| {
+            t.insert(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, g))?;
+            t.insert(QuadRef::new(alice, rdf::TYPE, person, g))
+        })?;
+        assert!(store.contains(QuadRef::new(alice, rdf::TYPE, agent, g))?);
+        assert_eq!(store.len(), 3);
+
+        let mut buffer = Vec::new();
+        store.dump_dataset(&mut buffer, DatasetFormat::NQuads)?;
+        assert_eq!(buffer.iter().filter(|c| **c == b'\n').count(), 2);
+    }
+
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        assert_eq!(store.entailment_regime(), Some(EntailmentRegime::Rdfs));
+        store.remove(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, g))?;
+        assert!(!store.contains(QuadRef::new(alice, rdf::TYPE, agent, g))?);
+        store.insert(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, g))?;
+        assert!(store.contains(QuadRef::new(alice, rdf::TYPE, agent, g))?);
+        store.clear_graph(g)?;
+        assert!(store.is_empty());
+        store.set_entailment_regime(None)?;
+        assert_eq!(store.entailment_regime(), None);
+    }
+
+    remove_dir_all(&repo_path)?;
+    Ok(())
+}
+
+#[test]
+fn backup_and_restore() -> Result<(), io::Error> {
+    use crate::model::*;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let quad = QuadRef::new(ex, ex, ex, None);
+    let other_quad = QuadRef::new(ex, ex, ex, ex);
+
+    let mut store_path = temp_dir();
+    store_path.push(random::<u128>().to_string());
+    let mut backup_path = temp_dir();
+    backup_path.push(random::<u128>().to_string());
+    let mut restore_path = temp_dir();
+    restore_path.push(random::<u128>().to_string());
+    let mut checkpoint_path = temp_dir();
+    checkpoint_path.push(random::<u128>().to_string());
+
+    {
+        let store = RocksDbStore::open(&store_path)?;
+        store.insert(quad)?;
+        store.backup(&backup_path)?;
+        store.checkpoint(&checkpoint_path)?;
+        store.insert(other_quad)?;
+    }
+
+    RocksDbStore::restore(&backup_path, &restore_path)?;
+    for path in &[&restore_path, &checkpoint_path] {
+        let store = RocksDbStore::open(path)?;
+        assert!(store.contains(quad)?);
+        assert!(!store.contains(other_quad)?);
+        assert_eq!(store.len(), 1);
+    }
+
+    remove_dir_all(&store_path)?;
+    remove_dir_all(&backup_path)?;
+    remove_dir_all(&restore_path)?;
+    remove_dir_all(&checkpoint_path)?;
+    Ok(())
+}
+
+#[test]
+fn snapshot() -> Result<(), io::Error> {
+    use crate::model::*;
+    use crate::sparql::QueryResults;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let quad = QuadRef::new(ex, ex, ex, None);
+    let other_quad = QuadRef::new(ex, ex, ex, ex);
+
+    let mut repo_path = temp_dir();
+    repo_path.push(random::<u128>().to_string());
+
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        store.insert(quad)?;
+        let snapshot = store.snapshot();
+        let results = store
+            .query("SELECT ?s WHERE { ?s ?p ?o }")
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        store.insert(other_quad)?;
+        store.remove(quad)?;
+
+        assert!(!store.contains(quad)?);
+        assert!(store.contains(other_quad)?);
+        assert!(snapshot.contains(quad)?);
+        assert!(!snapshot.contains(other_quad)?);
+        assert_eq!(snapshot.len(), 1);
+        assert!(!snapshot.contains_named_graph(ex)?);
+        assert_eq!(
+            snapshot.iter().collect::<Result<Vec<_>, _>>()?,
+            vec![quad.into_owned()]
+        );
+
+        // The query results are computed from the state of the store at the query evaluation start
+        if let QueryResults::Solutions(solutions) = results {
+            assert_eq!(solutions.count(), 1);
+        } else {
+            unreachable!()
+        }
+    }
+
+    remove_dir_all(&repo_path)?;
+    Ok(())
+}
+
+#[test]
+fn bulk_load() -> Result<(), io::Error> {
+    use crate::model::*;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    let s1 = NamedNode::new_unchecked("http://example.com/s1");
+    let s2 = NamedNode::new_unchecked("http://example.com/s2");
+    let p = NamedNode::new_unchecked("http://example.com/p");
+    let o = Literal::from(1);
+    let g1 = NamedNode::new_unchecked("http://example.com/g1");
+    let g2 = NamedNode::new_unchecked("http://example.com/g2");
+
+    let mut repo_path = temp_dir();
+    repo_path.push(random::<u128>().to_string());
+
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        store.insert(QuadRef::new(&s1, &p, &o, GraphNameRef::DefaultGraph))?;
+
+        let loaded = Arc::new(AtomicU64::new(0));
+        let progress = loaded.clone();
+        store
+            .bulk_loader()
+            .with_num_threads(2)
+            .with_batch_size(2)
+            .on_progress(move |count| {
+                progress.fetch_max(count, Ordering::Relaxed);
+            })
+            .load_dataset(
+                "<http://example.com/s1> <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .\n\
+                <http://example.com/s1> <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> <http://example.com/g1> .\n\
+                <http://example.com/s2> <http://example.com/p> _:b <http://example.com/g1> .\n\
+                _:b <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> <http://example.com/g1> .\n\
+                <http://example.com/s2> <http://example.com/p> _:b <http://example.com/g1> .\n"
+                    .as_bytes(),
+                DatasetFormat::NQuads,
+                None,
+            )?;
+        assert_eq!(loaded.load(Ordering::Relaxed), 5);
+        assert_eq!(store.len(), 4);
+        assert!(store.contains(QuadRef::new(&s1, &p, &o, &g1))?);
+        assert!(store.contains_named_graph(&g1)?);
+
+        // The blank node is the same in all the quads
+        let b = store
+            .quads_for_pattern(Some(s2.as_ref().into()), None, None, None)
+            .next()
+            .unwrap()?
+            .object;
+        if let Term::BlankNode(b) = b {
+            assert!(store.contains(QuadRef::new(&b, &p, &o, &g1))?);
+        } else {
+            unreachable!()
+        }
+
+        let encoded_p = store.get_encoded_named_node(p.as_ref())?.unwrap();
+        assert_eq!(store.encoded_quads_count()?, 4);
+        assert_eq!(
+            store.encoded_predicate_statistics(encoded_p)?,
+            PredicateStatistics {
+                quads: 4,
+                distinct_subjects: 3,
+                distinct_objects: 2
+            }
+        );
+
+        store.bulk_loader().load_graph(
+            "<s1> <p> [ <p> 1 ] .".as_bytes(),
+            GraphFormat::Turtle,
+            &g2,
+            Some("http://example.com/"),
+        )?;
+        assert_eq!(store.len(), 6);
+        assert!(store.contains_named_graph(&g2)?);
+        assert_eq!(store.encoded_quads_count()?, 6);
+
+        // The invalid lines are skipped
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let on_error = errors.clone();
+        store
+            .bulk_loader()
+            .with_num_threads(2)
+            .on_parse_error(move |error| on_error.lock().unwrap().push(error.to_string()))
+            .load_graph(
+                "<http://example.com/s3> <http://example.com/p> <http://example.com/o> .\n\
+                <http://example.com/s3> <http://example.com/p> <not an iri> .\n\
+                <http://example.com/s3> <http://example.com/p> <http://example.com/o2> .\n"
+                    .as_bytes(),
+                GraphFormat::NTriples,
+                GraphNameRef::DefaultGraph,
+                None,
+            )?;
+        assert_eq!(store.len(), 8);
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("on line 2"), "{}", errors[0]);
+    }
+
+    remove_dir_all(&repo_path)?;
+    Ok(())
+}
+
+#[test]
+fn bulk_load_failure() -> Result<(), io::Error> {
+    use crate::model::*;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fmt::Write;
+    use std::fs::remove_dir_all;
+
+    let p = NamedNode::new_unchecked("http://example.com/p");
+
+    let mut repo_path = temp_dir();
+    repo_path.push(random::<u128>().to_string());
+
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        for i in 0..100 {
+            store.insert(QuadRef::new(
+                &NamedNode::new_unchecked(format!("http://example.com/s{}", i)),
+                &p,
+                &Literal::from(i % 7),
+                GraphNameRef::DefaultGraph,
+            ))?;
+        }
+
+        // The parsed quads are sent to the loader threads by chunks of BULK_LOAD_CHUNK_QUADS quads
+        let mut file = String::new();
+        for i in 0..(BULK_LOAD_CHUNK_QUADS + BULK_LOAD_CHUNK_QUADS / 2) {
+            writeln!(file, "<s{}> <p> {} .", i, i % 7).unwrap();
+        }
+        file.push_str("<s> <p> .\n");
+        let error = store
+            .bulk_loader()
+            .with_num_threads(2)
+            .with_batch_size(1000)
+            .load_graph(
+                file.as_bytes(),
+                GraphFormat::Turtle,
+                GraphNameRef::DefaultGraph,
+                Some("http://example.com/"),
+            )
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // Only the first chunk has been written, the quads that were already there are not counted twice
+        let loaded = BULK_LOAD_CHUNK_QUADS;
+        assert_eq!(store.len(), loaded);
+        assert_eq!(store.encoded_quads_count()?, loaded as u64);
+        let encoded_p = store.get_encoded_named_node(p.as_ref())?.unwrap();
+        assert_eq!(
+            store.encoded_predicate_statistics(encoded_p)?,
+            PredicateStatistics {
+                quads: loaded as u64,
+                distinct_subjects: loaded as u64,
+                distinct_objects: 7
+            }
+        );
+
+        // All the indexes contain the same quads
+        assert_eq!(
+            store
+                .quads_for_pattern(None, Some(p.as_ref()), None, None)
+                .count(),
+            loaded
+        );
+        let mut by_object = 0;
+        for i in 0..7 {
+            by_object += store
+                .quads_for_pattern(None, None, Some(Literal::from(i).as_ref().into()), None)
+                .count();
+        }
+        assert_eq!(by_object, loaded);
+        for i in 0..loaded {
+            let subject = NamedNode::new_unchecked(format!("http://example.com/s{}", i));
+            assert_eq!(
+                store
+                    .quads_for_pattern(Some(subject.as_ref().into()), None, None, None)
+                    .count(),
+                1
+            );
+        }
+        assert!(!store.contains(QuadRef::new(
+            &NamedNode::new_unchecked(format!("http://example.com/s{}", loaded)),
+            &p,
+            &Literal::from((loaded % 7) as u64),
+            GraphNameRef::DefaultGraph,
+        ))?);
+    }
+
+    remove_dir_all(&repo_path)?;
+    Ok(())
+}
+
+#[test]
+fn read_only_and_secondary() -> Result<(), io::Error> {
+    use crate::model::*;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let quad = QuadRef::new(ex, ex, ex, None);
+    let other_quad = QuadRef::new(ex, ex, ex, ex);
+
+    let mut repo_path = temp_dir();
+    repo_path.push(random::<u128>().to_string());
+    let mut secondary_path = temp_dir();
+    secondary_path.push(random::<u128>().to_string());
+
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        store.insert(quad)?;
+        assert!(!store.is_read_only());
+        assert_eq!(
+            store.catch_up().unwrap_err().kind(),
+            io::ErrorKind::InvalidInput
+        );
+
+        let read_only = RocksDbStore::open_read_only(&repo_path)?;
+        let secondary = RocksDbStore::open_secondary(&repo_path, &secondary_path)?;
+        for store in &[&read_only, &secondary] {
+            assert!(store.is_read_only());
+            assert!(store.contains(quad)?);
+            assert_eq!(
+                store.insert(other_quad).unwrap_err().kind(),
+                io::ErrorKind::PermissionDenied
+            );
+            assert_eq!(
+                store.remove(quad).unwrap_err().kind(),
+                io::ErrorKind::PermissionDenied
+            );
+            assert_eq!(
+                store.clear().unwrap_err().kind(),
+                io::ErrorKind::PermissionDenied
+            );
+        }
+
+        store.insert(other_quad)?;
+        assert!(!secondary.contains(other_quad)?);
+        secondary.catch_up()?;
+        assert!(secondary.contains(other_quad)?);
+        assert!(!read_only.contains(other_quad)?);
+    }
+
+    remove_dir_all(&repo_path)?;
+    remove_dir_all(&secondary_path)?;
+    Ok(())
+}
+
+#[test]
+fn change_subscription() -> Result<(), io::Error> {
+    use crate::model::*;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    let ex = NamedNodeRef::new_unchecked("http://example.com");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    let quad = QuadRef::new(ex, ex, ex, None);
+    let other_quad = QuadRef::new(ex, ex, BlankNodeRef::new_unchecked("b"), g);
+
+    let mut repo_path = temp_dir();
+    repo_path.push(random::<u128>().to_string());
+
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        assert_eq!(store.last_sequence_number(), 0);
+        let mut changes = store.subscribe();
+
+        store.insert(quad)?;
+        store.insert(quad)?;
+        store.transaction(|t| {
+            t.remove(quad)?;
+            t.insert(other_quad)?;
+            t.remove(other_quad)?;
+            t.insert(other_quad)
+        })?;
+        let batch = changes.try_recv().unwrap();
+        assert_eq!(batch.sequence_number(), 1);
+        assert_eq!(batch.inserted(), &[quad.into_owned()]);
+        let batch = changes.try_recv().unwrap();
+        assert_eq!(batch.sequence_number(), 2);
+        assert_eq!(batch.inserted(), &[other_quad.into_owned()]);
+        assert_eq!(batch.removed(), &[quad.into_owned()]);
+        assert!(changes.try_recv().is_none());
+
+        // The strings removed by clear are still decoded
+        store.clear()?;
+        let batch = changes.try_recv().unwrap();
+        assert_eq!(batch.sequence_number(), 3);
+        assert_eq!(batch.removed(), &[other_quad.into_owned()]);
+
+        // Change log
+        assert!(store.subscribe_from(0).is_err());
+        store.set_change_log(true)?;
+        drop(changes);
+        store.insert(quad)?;
+        store
+            .update("INSERT DATA { <http://example.com> <http://example.com> _:b }")
+            .unwrap();
+        store.remove(quad)?;
+        assert_eq!(store.last_sequence_number(), 6);
+    }
+
+    {
+        // The sequence number and the log are persisted
+        let store = RocksDbStore::open(&repo_path)?;
+        assert!(store.is_change_log_enabled());
+        assert_eq!(store.last_sequence_number(), 6);
+        let mut changes = store.subscribe_from(4)?;
+        let batch = changes.try_recv().unwrap();
+        assert_eq!(batch.sequence_number(), 5);
+        assert_eq!(batch.inserted().len(), 1);
+        let batch = changes.try_recv().unwrap();
+        assert_eq!(batch.sequence_number(), 6);
+        assert_eq!(batch.removed(), &[quad.into_owned()]);
+        assert!(changes.try_recv().is_none());
+        store.insert(quad)?;
+        assert_eq!(changes.try_recv().unwrap().sequence_number(), 7);
+
+        store.truncate_change_log(5)?;
+        assert_eq!(
+            store.subscribe_from(4).err().unwrap().kind(),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            store
+                .subscribe_from(5)?
+                .try_recv()
+                .unwrap()
+                .sequence_number(),
+            6
+        );
+        assert!(store.subscribe_from(7)?.try_recv().is_none());
+
+        // The bulk loads are published as resync batches
+        let mut changes = store.subscribe();
+        store.bulk_loader().load_dataset(
+            b"<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n".as_ref(),
+            DatasetFormat::NQuads,
+            None,
+        )?;
+        let batch = changes.try_recv().unwrap();
+        assert_eq!(batch.sequence_number(), 8);
+        assert!(batch.requires_resync());
+        assert!(batch.inserted().is_empty());
+        assert!(store
+            .subscribe_from(7)?
+            .try_recv()
+            .unwrap()
+            .requires_resync());
+    }
+
+    remove_dir_all(&repo_path)?;
+    Ok(())
+}
+
+#[test]
+fn text_search() -> Result<(), io::Error> {
+    use crate::model::vocab::rdfs;
+    use rand::random;
+    use std::env::temp_dir;
+    use std::fs::remove_dir_all;
+
+    let a = NamedNodeRef::new_unchecked("http://example.com/a");
+    let b = NamedNodeRef::new_unchecked("http://example.com/b");
+    let long_comment = "A red car that is really red and that is not an apple";
+    let subjects = |store: &RocksDbStore, query: &str| -> Vec<Term> {
+        if let QueryResults::Solutions(solutions) = store
+            .query(
+                format!(
+                    "PREFIX text: <http://jena.apache.org/text#> SELECT ?s WHERE {{ ?s text:query \"{}\" }}",
+                    query
+                )
+                .as_str(),
+            )
+            .unwrap()
+        {
+            solutions
+                .map(|solution| solution.unwrap().get("s").unwrap().clone())
+                .collect()
+        } else {
+            unreachable!()
+        }
+    };
+
+    let mut repo_path = temp_dir();
+    repo_path.push(random::<u128>().to_string());
+    {
+        let store = RocksDbStore::open(&repo_path)?;
+        store.insert(QuadRef::new(
+            a,
+            rdfs::LABEL,
+            LiteralRef::new_simple_literal("Red apple"),
+            None,
+        ))?;
+        assert_eq!(store.encoded_text_search("red")?, None);
+
+        // The index is built from the existing quads
+        store.set_text_index(true)?;
+        assert_eq!(subjects(&store, "red"), vec![a.into_owned().into()]);
+
+        // The strings inserted in the same batch are indexed
+        store.transaction(|transaction| {
+            transaction.insert(QuadRef::new(
+                b,
+                rdfs::COMMENT,
+                LiteralRef::new_simple_literal(long_comment),
+                None,
+            ))
+        })?;
+        assert_eq!(
+            subjects(&store, "red apple"),
+            vec![b.into_owned().into(), a.into_owned().into()]
+        );
+    }
+    {
+        // The index is persisted
+        let store = RocksDbStore::open(&repo_path)?;
+        assert!(store.is_text_index_enabled());
+        assert_eq!(subjects(&store, "apple").len(), 2);
+        store.remove(QuadRef::new(
+            b,
+            rdfs::COMMENT,
+            LiteralRef::new_simple_literal(long_comment),
+            None,
+        ))?;
+        assert_eq!(subjects(&store, "apple"), vec![a.into_owned().into()]);
+
+        // Disabling the index removes it
+        store.set_text_index(false)?;
+        assert_eq!(store.encoded_text_search("red")?, None);
+        store.set_text_index(true)?;
+        assert_eq!(subjects(&store, "red"), vec![a.into_owned().into()]);
+    }
+    remove_dir_all(&repo_path)?;
+    Ok(())
+}
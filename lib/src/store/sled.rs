@@ -1,30 +1,45 @@
 //! Store based on the [Sled](https://sled.rs/) key-value database.
 
 use crate::error::invalid_data_error;
-use crate::io::{DatasetFormat, GraphFormat};
+use crate::io::{DatasetFormat, DatasetSerializer, GraphFormat, GraphSerializer};
 use crate::model::*;
+use crate::shacl::{validate, ShapesGraph, ValidationReport};
 use crate::sparql::{
-    evaluate_query, evaluate_update, EvaluationError, Query, QueryOptions, QueryResults, Update,
-    UpdateOptions,
+    evaluate_query, evaluate_update, explain_query, EvaluationError, Query, QueryExplanation,
+    QueryOptions, QueryResults, Update, UpdateOptions,
 };
 use crate::store::binary_encoder::*;
+use crate::store::changes::{ChangeCollector, ChangeFeed, ChangeSubscription};
+use crate::store::inference::{
+    decode_entailment_regime, encode_entailment_regime, materialize, propagate_changes,
+    remove_inferred_quads, EntailmentRegime, InferenceStore, QuadChanges,
+};
 use crate::store::numeric_encoder::{
     Decoder, ReadEncoder, StrContainer, StrEncodingAware, StrLookup, WriteEncoder,
 };
+use crate::store::statistics::{
+    add_delta, decode_counter, encode_counter, read_predicate_statistics, read_quads_count,
+    record_quad_insertion, record_quad_removal, search_text_index, CountersStore,
+};
+use crate::store::text::TextMatch;
 use crate::store::{
-    dump_dataset, dump_graph, get_encoded_quad_pattern, load_dataset, load_graph,
-    ReadableEncodedStore, StoreOrParseError, WritableEncodedStore,
+    dump_dataset, dump_graph, get_encoded_quad_pattern, load_dataset, load_from_path, load_graph,
+    PredicateStatistics, ReadableEncodedStore, StoreOrParseError, WritableEncodedStore,
 };
 use sled::transaction::{
     ConflictableTransactionError, TransactionError, Transactional, TransactionalTree,
     UnabortableTransactionError,
 };
 use sled::{Config, Db, Iter, Tree};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::convert::TryInto;
 use std::error::Error;
 use std::io::{BufRead, Write};
 use std::iter::{once, Once};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
 use std::{fmt, io, str};
 
 /// Store based on the [Sled](https://sled.rs/) key-value database.
@@ -34,6 +49,9 @@ use std::{fmt, io, str};
 ///
 /// Warning: Sled is not stable yet and might break its storage format.
 ///
+/// Warning: Sled does not provide snapshot isolation. SPARQL queries, SHACL validations and dumps
+/// read the latest state of the store and may observe the writes committed while they are running.
+///
 /// Usage example:
 /// ```
 /// use oxigraph::SledStore;
@@ -76,11 +94,19 @@ pub struct SledStore {
     dpos: Tree,
     dosp: Tree,
     graphs: Tree,
+    stats: Tree,
+    inferred: Tree,
+    entailment_regime: Arc<AtomicU8>,
+    changes: Arc<ChangeFeed>,
+    text_index: Arc<AtomicBool>,
 }
 
 type EncodedTerm = crate::store::numeric_encoder::EncodedTerm<StrHash>;
 type EncodedQuad = crate::store::numeric_encoder::EncodedQuad<StrHash>;
 
+/// Key of the flag enabling the full-text index
+const TEXT_INDEX_KEY: &[u8] = b"oxtextindex";
+
 //TODO: indexes for the default graph and indexes for the named graphs (no more Optional and space saving)
 
 impl SledStore {
@@ -109,6 +135,11 @@ impl SledStore {
             dpos: db.open_tree("dpos")?,
             dosp: db.open_tree("dosp")?,
             graphs: db.open_tree("graphs")?,
+            stats: db.open_tree("stats")?,
+            inferred: db.open_tree("inferred")?,
+            entailment_regime: Arc::new(AtomicU8::new(encode_entailment_regime(None))),
+            changes: Arc::new(ChangeFeed::default()),
+            text_index: Arc::new(AtomicBool::new(false)),
         };
 
         let mut version = this.ensure_version()?;
@@ -125,6 +156,23 @@ impl SledStore {
             this.set_version(version)?;
             this.graphs.flush()?;
         }
+        if version == 1 {
+            // We migrate to v2
+            this.rebuild_statistics()?;
+            version = 2;
+            this.set_version(version)?;
+            this.stats.flush()?;
+        }
+
+        if let Some(regime) = this.default.get("oxentailment")? {
+            let regime = regime.first().copied().unwrap_or_default();
+            decode_entailment_regime(regime)?;
+            this.entailment_regime.store(regime, Ordering::Relaxed);
+        }
+        this.text_index.store(
+            matches!(this.default.get(TEXT_INDEX_KEY)?, Some(value) if value == [1]),
+            Ordering::Relaxed,
+        );
 
         match version {
             _ if version < LATEST_STORAGE_VERSION => Err(invalid_data_error(format!(
@@ -174,12 +222,55 @@ impl SledStore {
         evaluate_query(self.clone(), query, options)
     }
 
+    /// Returns the evaluation plan of a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) without executing it.
+    ///
+    /// The default query options are used.
+    pub fn explain(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+    ) -> Result<QueryExplanation, EvaluationError> {
+        self.explain_opt(query, QueryOptions::default(), false)
+    }
+
+    /// Returns the evaluation plan of a [SPARQL 1.1 query](https://www.w3.org/TR/sparql11-query/) with some options.
+    ///
+    /// If `with_stats` is set, the query is fully evaluated and the number of rows and the evaluation time of each plan node are returned.
+    pub fn explain_opt(
+        &self,
+        query: impl TryInto<Query, Error = impl Into<EvaluationError>>,
+        options: QueryOptions,
+        with_stats: bool,
+    ) -> Result<QueryExplanation, EvaluationError> {
+        explain_query(self.clone(), query, options, with_stats)
+    }
+
+    /// Validates the content of the store against a [SHACL](https://www.w3.org/TR/shacl/) shapes graph.
+    ///
+    /// The data graph is the union of all the store graphs.
+    pub fn validate(&self, shapes: &ShapesGraph) -> Result<ValidationReport, EvaluationError> {
+        validate(self.clone(), shapes, None, QueryOptions::default())
+    }
+
+    /// Validates a graph of the store against a [SHACL](https://www.w3.org/TR/shacl/) shapes graph.
+    pub fn validate_graph<'a>(
+        &self,
+        shapes: &ShapesGraph,
+        graph_name: impl Into<GraphNameRef<'a>>,
+    ) -> Result<ValidationReport, EvaluationError> {
+        validate(
+            self.clone(),
+            shapes,
+            Some(graph_name.into()),
+            QueryOptions::default(),
+        )
+    }
+
     /// Retrieves quads with a filter on each quad component
     ///
     /// See [`MemoryStore`](super::memory::MemoryStore::quads_for_pattern()) for a usage example.
     pub fn quads_for_pattern(
         &self,
-        subject: Option<NamedOrBlankNodeRef<'_>>,
+        subject: Option<SubjectRef<'_>>,
         predicate: Option<NamedNodeRef<'_>>,
         object: Option<TermRef<'_>>,
         graph_name: Option<GraphNameRef<'_>>,
@@ -280,22 +371,35 @@ impl SledStore {
         &self,
         f: impl Fn(SledTransaction<'_>) -> Result<T, SledConflictableTransactionError<E>>,
     ) -> Result<T, SledTransactionError<E>> {
-        Ok((
-            &self.id2str,
-            &self.spog,
-            &self.posg,
-            &self.ospg,
-            &self.gspo,
-            &self.gpos,
-            &self.gosp,
-            &self.dspo,
-            &self.dpos,
-            &self.dosp,
-            &self.graphs,
-        )
-            .transaction(
-                move |(id2str, spog, posg, ospg, gspo, gpos, gosp, dspo, dpos, dosp, graphs)| {
-                    Ok(f(SledTransaction {
+        let changes = RefCell::new(QuadChanges::default());
+        let record_changes = self.entailment_regime().is_some();
+        let changes_ref = &changes;
+        let collector = RefCell::new(ChangeCollector::default());
+        let capture_changes = self.changes.is_watched();
+        let collector_ref = &collector;
+        let text_index = self.is_text_index_enabled();
+        let inferred = if record_changes {
+            Some(&self.inferred)
+        } else {
+            None
+        };
+        let run = || {
+            (
+                &self.id2str,
+                &self.spog,
+                &self.posg,
+                &self.ospg,
+                &self.gspo,
+                &self.gpos,
+                &self.gosp,
+                &self.dspo,
+                &self.dpos,
+                &self.dosp,
+                &self.graphs,
+                &self.stats,
+            )
+                .transaction(
+                    move |(
                         id2str,
                         spog,
                         posg,
@@ -307,9 +411,59 @@ impl SledStore {
                         dpos,
                         dosp,
                         graphs,
-                    })?)
-                },
-            )?)
+                        stats,
+                    )| {
+                        // The closure is executed again if the transaction is retried
+                        *changes_ref.borrow_mut() = QuadChanges::default();
+                        *collector_ref.borrow_mut() = ChangeCollector::default();
+                        Ok(f(SledTransaction {
+                            id2str,
+                            spog,
+                            posg,
+                            ospg,
+                            gspo,
+                            gpos,
+                            gosp,
+                            dspo,
+                            dpos,
+                            dosp,
+                            graphs,
+                            stats,
+                            changes: if record_changes {
+                                Some(changes_ref)
+                            } else {
+                                None
+                            },
+                            collector: if capture_changes {
+                                Some(collector_ref)
+                            } else {
+                                None
+                            },
+                            inferred,
+                            text_index,
+                        })?)
+                    },
+                )
+        };
+        let result = if capture_changes {
+            // The transaction is executed while holding the change feed lock in order to publish the changes in commit order
+            self.changes.commit(|_| {
+                let result = run()?;
+                let (inserted, removed) = collector.borrow_mut().take_changes();
+                Ok::<_, SledTransactionError<E>>((
+                    result,
+                    self.decode_quads(&inserted)
+                        .map_err(SledTransactionError::Storage)?,
+                    self.decode_quads(&removed)
+                        .map_err(SledTransactionError::Storage)?,
+                ))
+            })?
+        } else {
+            run()?
+        };
+        self.propagate_inferences(changes.into_inner())
+            .map_err(SledTransactionError::Storage)?;
+        Ok(result)
     }
 
     /// Loads a graph file (i.e. triples) into the store
@@ -333,7 +487,63 @@ impl SledStore {
         base_iri: Option<&str>,
     ) -> Result<(), io::Error> {
         let mut this = self;
-        load_graph(&mut this, reader, format, to_graph_name.into(), base_iri)?;
+        load_graph(
+            &mut this,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            None,
+        )?;
+        Ok(())
+    }
+
+    /// Loads a graph file (i.e. triples) into the store like [`load_graph`](SledStore::load_graph()) and returns the prefixes declared in it.
+    ///
+    /// The prefixes could be given to [`GraphSerializer::with_prefixes`] to use them when dumping the store.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::load_graph_with_prefixes()) for a usage example.
+    pub fn load_graph_with_prefixes<'a>(
+        &self,
+        reader: impl BufRead,
+        format: GraphFormat,
+        to_graph_name: impl Into<GraphNameRef<'a>>,
+        base_iri: Option<&str>,
+    ) -> Result<BTreeMap<String, String>, io::Error> {
+        let mut this = self;
+        Ok(load_graph(
+            &mut this,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            None,
+        )?)
+    }
+
+    /// Loads a graph file (i.e. triples) into the store like [`load_graph`](SledStore::load_graph()) but skips the invalid statements.
+    ///
+    /// The syntax error of each skipped statement is given to `on_error`. The error messages contain the line and column of the error.
+    /// Only the N-Triples and Turtle parsers are able to resume after an error, the RDF/XML one still fails on the first error.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::load_graph_lenient()) for a usage example.
+    pub fn load_graph_lenient<'a>(
+        &self,
+        reader: impl BufRead,
+        format: GraphFormat,
+        to_graph_name: impl Into<GraphNameRef<'a>>,
+        base_iri: Option<&str>,
+        mut on_error: impl FnMut(io::Error),
+    ) -> Result<(), io::Error> {
+        let mut this = self;
+        load_graph(
+            &mut this,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            Some(&mut on_error),
+        )?;
         Ok(())
     }
 
@@ -357,7 +567,52 @@ impl SledStore {
         base_iri: Option<&str>,
     ) -> Result<(), io::Error> {
         let mut this = self;
-        load_dataset(&mut this, reader, format, base_iri)?;
+        load_dataset(&mut this, reader, format, base_iri, None)?;
+        Ok(())
+    }
+
+    /// Loads a dataset file (i.e. quads) into the store like [`load_dataset`](SledStore::load_dataset()) and returns the prefixes declared in it.
+    ///
+    /// The prefixes could be given to [`DatasetSerializer::with_prefixes`] to use them when dumping the store.
+    pub fn load_dataset_with_prefixes(
+        &self,
+        reader: impl BufRead,
+        format: DatasetFormat,
+        base_iri: Option<&str>,
+    ) -> Result<BTreeMap<String, String>, io::Error> {
+        let mut this = self;
+        Ok(load_dataset(&mut this, reader, format, base_iri, None)?)
+    }
+
+    /// Loads a dataset file (i.e. quads) into the store like [`load_dataset`](SledStore::load_dataset()) but skips the invalid statements.
+    ///
+    /// The syntax error of each skipped statement is given to `on_error`. The error messages contain the line and column of the error.
+    /// Only the N-Quads and TriG parsers are able to resume after an error, the JSON-LD one still fails on the first error.
+    pub fn load_dataset_lenient(
+        &self,
+        reader: impl BufRead,
+        format: DatasetFormat,
+        base_iri: Option<&str>,
+        mut on_error: impl FnMut(io::Error),
+    ) -> Result<(), io::Error> {
+        let mut this = self;
+        load_dataset(&mut this, reader, format, base_iri, Some(&mut on_error))?;
+        Ok(())
+    }
+
+    /// Loads a file into the store guessing its format from the file extension or, if it is not known, from the file content.
+    ///
+    /// The triples are loaded into the default graph.
+    /// The files compressed with a supported [`Compression`](crate::io::Compression) are decompressed.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::load_from_path()) for a usage example.
+    pub fn load_from_path(
+        &self,
+        path: impl AsRef<Path>,
+        base_iri: Option<&str>,
+    ) -> Result<(), io::Error> {
+        let mut this = self;
+        load_from_path(&mut this, path.as_ref(), base_iri)?;
         Ok(())
     }
 
@@ -387,27 +642,39 @@ impl SledStore {
     }
 
     /// Dumps a store graph into a file.
-    ///    
+    ///
+    /// The quads inferred by the [entailment regime](SledStore::set_entailment_regime()) are not written.
+    ///
     /// See [`MemoryStore`](super::memory::MemoryStore::dump_graph()) for a usage example.
     pub fn dump_graph<'a>(
         &self,
         writer: impl Write,
-        format: GraphFormat,
+        format: impl Into<GraphSerializer>,
         from_graph_name: impl Into<GraphNameRef<'a>>,
     ) -> Result<(), io::Error> {
+        let quads = self
+            .get_encoded_graph_name(from_graph_name.into())?
+            .map(|graph_name| self.quads_for_graph(graph_name))
+            .into_iter()
+            .flatten();
         dump_graph(
-            self.quads_for_pattern(None, None, None, Some(from_graph_name.into()))
-                .map(|q| Ok(q?.into())),
+            self.asserted_quads(quads).map(|q| Ok(q?.into())),
             writer,
             format,
         )
     }
 
     /// Dumps the store into a file.
-    ///    
+    ///
+    /// The quads inferred by the [entailment regime](SledStore::set_entailment_regime()) are not written.
+    ///
     /// See [`MemoryStore`](super::memory::MemoryStore::dump_dataset()) for a usage example.
-    pub fn dump_dataset(&self, writer: impl Write, format: DatasetFormat) -> Result<(), io::Error> {
-        dump_dataset(self.iter(), writer, format)
+    pub fn dump_dataset(
+        &self,
+        writer: impl Write,
+        format: impl Into<DatasetSerializer>,
+    ) -> Result<(), io::Error> {
+        dump_dataset(self.asserted_quads(self.quads()), writer, format)
     }
 
     /// Returns all the store named graphs
@@ -484,6 +751,173 @@ impl SledStore {
         (&mut this).clear()
     }
 
+    /// Sets the [entailment regime](EntailmentRegime) used to materialize inferred quads in the store.
+    ///
+    /// The regime is persisted in the database.
+    /// All the quads inferred by the previous regime are removed and the ones entailed by the new regime are computed.
+    /// The inferred quads are then maintained on each store write. `None` disables the inference.
+    ///
+    /// The inferred quads are returned by the quad pattern lookups and by the SPARQL queries
+    /// but are not written by [`dump_graph`](SledStore::dump_graph()) and [`dump_dataset`](SledStore::dump_dataset()).
+    /// Removing an inferred quad has no effect as long as it is entailed by the other quads.
+    ///
+    /// See [`EntailmentRegime`] for a usage example.
+    pub fn set_entailment_regime(&self, regime: Option<EntailmentRegime>) -> Result<(), io::Error> {
+        remove_inferred_quads(self)?;
+        let regime_id = encode_entailment_regime(regime);
+        self.default.insert("oxentailment", &[regime_id])?;
+        self.entailment_regime.store(regime_id, Ordering::Relaxed);
+        if let Some(regime) = regime {
+            materialize(self, regime)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the [entailment regime](EntailmentRegime) used to materialize inferred quads in the store, if any.
+    pub fn entailment_regime(&self) -> Option<EntailmentRegime> {
+        // The value has been validated when it has been written
+        decode_entailment_regime(self.entailment_regime.load(Ordering::Relaxed)).unwrap_or(None)
+    }
+
+    /// Enables or disables the full-text index.
+    ///
+    /// When it is enabled, the string literals used as quad objects are indexed and could be searched in SPARQL
+    /// with the `text:query` property function. See the [`sparql`](crate::sparql) module documentation for its syntax.
+    /// The setting is persisted in the database.
+    /// The index is built from the current content of the store when it is enabled and then maintained on each store write.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::set_text_index()) for a usage example.
+    pub fn set_text_index(&self, enabled: bool) -> Result<(), io::Error> {
+        self.default.insert(TEXT_INDEX_KEY, &[u8::from(enabled)])?;
+        self.text_index.store(enabled, Ordering::Relaxed);
+        // The index is stored with the statistics
+        self.rebuild_statistics()?;
+        self.stats.flush()?;
+        Ok(())
+    }
+
+    /// Returns if the [full-text index](SledStore::set_text_index()) is enabled.
+    pub fn is_text_index_enabled(&self) -> bool {
+        self.text_index.load(Ordering::Relaxed)
+    }
+
+    /// Subscribes to the changes of the store.
+    ///
+    /// The subscription receives a [`ChangeBatch`](super::ChangeBatch) for each write committed after its creation
+    /// that inserts or removes some quads, in commit order.
+    /// A transaction is a single write and so is each call to the other write methods.
+    /// The quads inferred by the [entailment regime](SledStore::set_entailment_regime()) are not part of the batches.
+    ///
+    /// The sequence numbers are not persisted: they start again from 1 each time the store is opened.
+    ///
+    /// See [`MemoryStore`](super::memory::MemoryStore::subscribe()) for a usage example.
+    pub fn subscribe(&self) -> ChangeSubscription {
+        self.changes.subscribe()
+    }
+
+    /// Executes the write and publishes the changes it records in the collector, if the changes are captured
+    fn write_with_changes(
+        &self,
+        write: impl FnOnce(Option<&mut ChangeCollector<StrHash>>) -> Result<(), io::Error>,
+    ) -> Result<(), io::Error> {
+        if !self.changes.is_watched() {
+            return write(None);
+        }
+        self.changes.commit(|_| {
+            let mut collector = ChangeCollector::default();
+            write(Some(&mut collector))?;
+            let (inserted, removed) = collector.take_changes();
+            Ok((
+                (),
+                self.decode_quads(&inserted)?,
+                self.decode_quads(&removed)?,
+            ))
+        })
+    }
+
+    fn decode_quads(&self, quads: &[EncodedQuad]) -> Result<Vec<Quad>, io::Error> {
+        quads
+            .iter()
+            .map(|quad| Ok(self.decode_quad(quad)?))
+            .collect()
+    }
+
+    /// Returns if the quad has been inferred, only looking for it if an entailment regime is set
+    fn is_inferred_if_regime(&self, quad: &EncodedQuad) -> Result<bool, io::Error> {
+        Ok(self.entailment_regime().is_some() && self.is_inferred(quad)?)
+    }
+
+    /// Inserts a quad, records it in the collector and does not propagate the change to the inferred quads
+    fn insert_asserted_quad(
+        &self,
+        quad: &EncodedQuad,
+        collector: Option<&mut ChangeCollector<StrHash>>,
+    ) -> Result<(), io::Error> {
+        if let Some(collector) = collector {
+            let was_inferred = self.is_inferred_if_regime(quad)?;
+            let is_new = self.insert_quad(quad)?;
+            collector.record_insertion(quad, || Ok::<_, io::Error>(!is_new && !was_inferred))
+        } else {
+            self.insert_quad(quad)?;
+            Ok(())
+        }
+    }
+
+    /// Removes a quad, records it in the collector and does not propagate the change to the inferred quads
+    fn remove_asserted_quad(
+        &self,
+        quad: &EncodedQuad,
+        collector: Option<&mut ChangeCollector<StrHash>>,
+    ) -> Result<(), io::Error> {
+        if let Some(collector) = collector {
+            let was_inferred = self.is_inferred_if_regime(quad)?;
+            let was_present = self.remove_quad(quad)?;
+            collector.record_removal(quad, || Ok::<_, io::Error>(was_present && !was_inferred))
+        } else {
+            self.remove_quad(quad)?;
+            Ok(())
+        }
+    }
+
+    fn propagate_inferences(&self, changes: QuadChanges<StrHash>) -> Result<(), io::Error> {
+        if let Some(regime) = self.entailment_regime() {
+            propagate_changes(self, regime, changes)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn graph_removal_changes(
+        &self,
+        graph_name: EncodedTerm,
+    ) -> Result<QuadChanges<StrHash>, io::Error> {
+        let mut changes = QuadChanges::default();
+        if self.entailment_regime().is_some() {
+            for quad in self.quads_for_graph(graph_name) {
+                changes.record_removal(&quad?);
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Decodes the given quads without the inferred ones
+    fn asserted_quads<'a>(
+        &'a self,
+        quads: impl Iterator<Item = Result<EncodedQuad, io::Error>> + 'a,
+    ) -> impl Iterator<Item = Result<Quad, io::Error>> + 'a {
+        quads.filter_map(move |quad| {
+            let quad = match quad {
+                Ok(quad) => quad,
+                Err(error) => return Some(Err(error)),
+            };
+            match self.is_inferred(&quad) {
+                Ok(true) => None,
+                Ok(false) => Some(self.decode_quad(&quad).map_err(|e| e.into())),
+                Err(error) => Some(Err(error)),
+            }
+        })
+    }
+
     fn contains_encoded(&self, quad: &EncodedQuad) -> Result<bool, io::Error> {
         let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
         if quad.graph_name.is_default_graph() {
@@ -494,6 +928,22 @@ impl SledStore {
             Ok(self.gspo.contains_key(buffer)?)
         }
     }
+    fn get_counter(&self, key: &[u8]) -> Result<u64, io::Error> {
+        self.stats
+            .get(key)?
+            .map_or(Ok(0), |value| decode_counter(&value))
+    }
+
+    /// Recomputes all the statistics counters from the quads in the store
+    fn rebuild_statistics(&self) -> Result<(), io::Error> {
+        self.stats.clear()?;
+        for quad in self.encoded_quads_for_pattern(None, None, None, None) {
+            // The quads are already in the database, we only count them
+            record_quad_insertion(&mut &*self, &quad?)?;
+        }
+        Ok(())
+    }
+
     fn quads(&self) -> DecodingQuadsIterator {
         DecodingQuadsIterator::pair(
             self.dspo_quads(Vec::default()),
@@ -711,6 +1161,135 @@ impl SledStore {
             encoding,
         }
     }
+
+    fn clear_trees(&self) -> Result<(), io::Error> {
+        self.dspo.clear()?;
+        self.dpos.clear()?;
+        self.dosp.clear()?;
+        self.gspo.clear()?;
+        self.gpos.clear()?;
+        self.gosp.clear()?;
+        self.spog.clear()?;
+        self.posg.clear()?;
+        self.ospg.clear()?;
+        self.graphs.clear()?;
+        self.stats.clear()?;
+        self.inferred.clear()?;
+        self.id2str.clear()?;
+        Ok(())
+    }
+
+    /// Inserts a quad without propagating the change to the inferred quads
+    ///
+    /// Returns if the quad was not in the store
+    fn insert_quad(&self, quad: &EncodedQuad) -> Result<bool, io::Error> {
+        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE + 1);
+
+        let is_new = if quad.graph_name.is_default_graph() {
+            write_spo_quad(&mut buffer, quad);
+            let is_new = self.dspo.insert(buffer.as_slice(), &[])?.is_none();
+            buffer.clear();
+
+            write_pos_quad(&mut buffer, quad);
+            self.dpos.insert(buffer.as_slice(), &[])?;
+            buffer.clear();
+
+            write_osp_quad(&mut buffer, quad);
+            self.dosp.insert(buffer.as_slice(), &[])?;
+            buffer.clear();
+
+            is_new
+        } else {
+            write_spog_quad(&mut buffer, quad);
+            let is_new = self.spog.insert(buffer.as_slice(), &[])?.is_none();
+            buffer.clear();
+
+            write_posg_quad(&mut buffer, quad);
+            self.posg.insert(buffer.as_slice(), &[])?;
+            buffer.clear();
+
+            write_ospg_quad(&mut buffer, quad);
+            self.ospg.insert(buffer.as_slice(), &[])?;
+            buffer.clear();
+
+            write_gspo_quad(&mut buffer, quad);
+            self.gspo.insert(buffer.as_slice(), &[])?;
+            buffer.clear();
+
+            write_gpos_quad(&mut buffer, quad);
+            self.gpos.insert(buffer.as_slice(), &[])?;
+            buffer.clear();
+
+            write_gosp_quad(&mut buffer, quad);
+            self.gosp.insert(buffer.as_slice(), &[])?;
+            buffer.clear();
+
+            write_term(&mut buffer, quad.graph_name);
+            self.graphs.insert(&buffer, &[])?;
+            buffer.clear();
+
+            is_new
+        };
+
+        if is_new {
+            record_quad_insertion(&mut &*self, quad)?;
+        }
+        Ok(is_new)
+    }
+
+    /// Removes a quad without propagating the change to the inferred quads
+    ///
+    /// Returns if the quad was in the store
+    fn remove_quad(&self, quad: &EncodedQuad) -> Result<bool, io::Error> {
+        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE + 1);
+
+        let was_present = if quad.graph_name.is_default_graph() {
+            write_spo_quad(&mut buffer, quad);
+            let was_present = self.dspo.remove(buffer.as_slice())?.is_some();
+            buffer.clear();
+
+            write_pos_quad(&mut buffer, quad);
+            self.dpos.remove(buffer.as_slice())?;
+            buffer.clear();
+
+            write_osp_quad(&mut buffer, quad);
+            self.dosp.remove(buffer.as_slice())?;
+            buffer.clear();
+
+            was_present
+        } else {
+            write_spog_quad(&mut buffer, quad);
+            let was_present = self.spog.remove(buffer.as_slice())?.is_some();
+            buffer.clear();
+
+            write_posg_quad(&mut buffer, quad);
+            self.posg.remove(buffer.as_slice())?;
+            buffer.clear();
+
+            write_ospg_quad(&mut buffer, quad);
+            self.ospg.remove(buffer.as_slice())?;
+            buffer.clear();
+
+            write_gspo_quad(&mut buffer, quad);
+            self.gspo.remove(buffer.as_slice())?;
+            buffer.clear();
+
+            write_gpos_quad(&mut buffer, quad);
+            self.gpos.remove(buffer.as_slice())?;
+            buffer.clear();
+
+            write_gosp_quad(&mut buffer, quad);
+            self.gosp.remove(buffer.as_slice())?;
+            buffer.clear();
+
+            was_present
+        };
+
+        if was_present {
+            record_quad_removal(&mut &*self, quad)?;
+        }
+        Ok(was_present)
+    }
 }
 
 impl fmt::Display for SledStore {
@@ -813,14 +1392,82 @@ impl ReadableEncodedStore for SledStore {
         }
     }
 
-    fn encoded_named_graphs(&self) -> DecodingGraphIterator {
-        DecodingGraphIterator {
-            iter: self.graphs.iter(),
+    fn encoded_named_graphs(&self) -> DecodingGraphIterator {
+        DecodingGraphIterator {
+            iter: self.graphs.iter(),
+        }
+    }
+
+    fn contains_encoded_named_graph(&self, graph_name: EncodedTerm) -> Result<bool, io::Error> {
+        Ok(self.graphs.contains_key(&encode_term(graph_name))?)
+    }
+
+    fn encoded_quads_count(&self) -> Result<u64, io::Error> {
+        read_quads_count(|key| self.get_counter(key))
+    }
+
+    fn encoded_predicate_statistics(
+        &self,
+        predicate: EncodedTerm,
+    ) -> Result<PredicateStatistics, io::Error> {
+        read_predicate_statistics(predicate, |key| self.get_counter(key))
+    }
+
+    fn encoded_text_search(
+        &self,
+        query: &str,
+    ) -> Result<Option<Vec<TextMatch<StrHash>>>, io::Error> {
+        if !self.is_text_index_enabled() {
+            return Ok(None);
+        }
+        search_text_index(
+            query,
+            |key| self.get_counter(key),
+            |prefix| {
+                self.stats
+                    .scan_prefix(prefix)
+                    .map(|entry| {
+                        let (key, value) = entry?;
+                        Ok::<_, io::Error>((key[prefix.len()..].to_vec(), decode_counter(&value)?))
+                    })
+                    .collect()
+            },
+        )
+        .map(Some)
+    }
+}
+
+impl CountersStore for &SledStore {
+    type Error = io::Error;
+
+    fn add_to_counter(&mut self, key: &[u8], delta: i64) -> Result<u64, io::Error> {
+        let mut error = None;
+        let new_value = self.stats.update_and_fetch(key, |value| {
+            let value = match value.map(decode_counter).transpose() {
+                Ok(value) => add_delta(value.unwrap_or(0), delta),
+                Err(e) => {
+                    error = Some(e);
+                    0
+                }
+            };
+            if value == 0 {
+                None
+            } else {
+                Some(encode_counter(value).to_vec())
+            }
+        })?;
+        if let Some(error) = error {
+            return Err(error);
         }
+        new_value.map_or(Ok(0), |value| decode_counter(&value))
     }
 
-    fn contains_encoded_named_graph(&self, graph_name: EncodedTerm) -> Result<bool, io::Error> {
-        Ok(self.graphs.contains_key(&encode_term(graph_name))?)
+    fn has_text_index(&self) -> bool {
+        self.is_text_index_enabled()
+    }
+
+    fn get_text_str(&self, id: StrHash) -> Result<Option<String>, io::Error> {
+        self.get_str(id)
     }
 }
 
@@ -834,94 +1481,22 @@ impl<'a> StrContainer for &'a SledStore {
 
 impl<'a> WritableEncodedStore for &'a SledStore {
     fn insert_encoded(&mut self, quad: &EncodedQuad) -> Result<(), io::Error> {
-        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE + 1);
-
-        if quad.graph_name.is_default_graph() {
-            write_spo_quad(&mut buffer, quad);
-            self.dspo.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-
-            write_pos_quad(&mut buffer, quad);
-            self.dpos.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-
-            write_osp_quad(&mut buffer, quad);
-            self.dosp.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-        } else {
-            write_spog_quad(&mut buffer, quad);
-            self.spog.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-
-            write_posg_quad(&mut buffer, quad);
-            self.posg.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-
-            write_ospg_quad(&mut buffer, quad);
-            self.ospg.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-
-            write_gspo_quad(&mut buffer, quad);
-            self.gspo.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-
-            write_gpos_quad(&mut buffer, quad);
-            self.gpos.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-
-            write_gosp_quad(&mut buffer, quad);
-            self.gosp.insert(buffer.as_slice(), &[])?;
-            buffer.clear();
-
-            write_term(&mut buffer, quad.graph_name);
-            self.graphs.insert(&buffer, &[])?;
-            buffer.clear();
+        self.write_with_changes(|collector| self.insert_asserted_quad(quad, collector))?;
+        if self.entailment_regime().is_some() {
+            let mut changes = QuadChanges::default();
+            changes.record_insertion(quad);
+            self.propagate_inferences(changes)?;
         }
-
         Ok(())
     }
 
     fn remove_encoded(&mut self, quad: &EncodedQuad) -> Result<(), io::Error> {
-        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE + 1);
-
-        if quad.graph_name.is_default_graph() {
-            write_spo_quad(&mut buffer, quad);
-            self.dspo.remove(buffer.as_slice())?;
-            buffer.clear();
-
-            write_pos_quad(&mut buffer, quad);
-            self.dpos.remove(buffer.as_slice())?;
-            buffer.clear();
-
-            write_osp_quad(&mut buffer, quad);
-            self.dosp.remove(buffer.as_slice())?;
-            buffer.clear();
-        } else {
-            write_spog_quad(&mut buffer, quad);
-            self.spog.remove(buffer.as_slice())?;
-            buffer.clear();
-
-            write_posg_quad(&mut buffer, quad);
-            self.posg.remove(buffer.as_slice())?;
-            buffer.clear();
-
-            write_ospg_quad(&mut buffer, quad);
-            self.ospg.remove(buffer.as_slice())?;
-            buffer.clear();
-
-            write_gspo_quad(&mut buffer, quad);
-            self.gspo.remove(buffer.as_slice())?;
-            buffer.clear();
-
-            write_gpos_quad(&mut buffer, quad);
-            self.gpos.remove(buffer.as_slice())?;
-            buffer.clear();
-
-            write_gosp_quad(&mut buffer, quad);
-            self.gosp.remove(buffer.as_slice())?;
-            buffer.clear();
+        self.write_with_changes(|collector| self.remove_asserted_quad(quad, collector))?;
+        if self.entailment_regime().is_some() {
+            let mut changes = QuadChanges::default();
+            changes.record_removal(quad);
+            self.propagate_inferences(changes)?;
         }
-
         Ok(())
     }
 
@@ -931,38 +1506,111 @@ impl<'a> WritableEncodedStore for &'a SledStore {
     }
 
     fn clear_encoded_graph(&mut self, graph_name: EncodedTerm) -> Result<(), io::Error> {
-        if graph_name.is_default_graph() {
-            self.dspo.clear()?;
-            self.dpos.clear()?;
-            self.dosp.clear()?;
-        } else {
-            for quad in self.quads_for_graph(graph_name) {
-                self.remove_encoded(&quad?)?;
+        let changes = self.graph_removal_changes(graph_name)?;
+        let store = *self;
+        self.write_with_changes(|mut collector| {
+            if graph_name.is_default_graph() {
+                for quad in store.quads_for_graph(graph_name) {
+                    let quad = quad?;
+                    record_quad_removal(&mut &*store, &quad)?;
+                    if let Some(collector) = &mut collector {
+                        let was_inferred = store.is_inferred_if_regime(&quad)?;
+                        collector.record_removal(&quad, || Ok::<_, io::Error>(!was_inferred))?;
+                    }
+                }
+                store.dspo.clear()?;
+                store.dpos.clear()?;
+                store.dosp.clear()?;
+            } else {
+                for quad in store.quads_for_graph(graph_name) {
+                    store.remove_asserted_quad(&quad?, collector.as_deref_mut())?;
+                }
             }
+            Ok(())
+        })?;
+        self.propagate_inferences(changes)
+    }
+
+    fn remove_encoded_named_graph(&mut self, graph_name: EncodedTerm) -> Result<(), io::Error> {
+        let changes = self.graph_removal_changes(graph_name)?;
+        let store = *self;
+        self.write_with_changes(|mut collector| {
+            for quad in store.quads_for_graph(graph_name) {
+                store.remove_asserted_quad(&quad?, collector.as_deref_mut())?;
+            }
+            Ok(())
+        })?;
+        self.graphs.remove(&encode_term(graph_name))?;
+        self.propagate_inferences(changes)
+    }
+
+    fn clear(&mut self) -> Result<(), io::Error> {
+        if self.changes.is_watched() {
+            // The quads are decoded before the removal of the strings
+            let store = *self;
+            return self.changes.commit(|_| {
+                let removed = store
+                    .asserted_quads(store.quads())
+                    .collect::<Result<Vec<_>, _>>()?;
+                store.clear_trees()?;
+                Ok(((), Vec::new(), removed))
+            });
+        }
+        self.clear_trees()
+    }
+}
+
+impl InferenceStore for SledStore {
+    fn encode_vocabulary(&self, node: NamedNodeRef<'static>) -> Result<EncodedTerm, io::Error> {
+        if let Some(term) = self.get_encoded_named_node(node)? {
+            return Ok(term);
+        }
+        let mut this = self;
+        this.encode_named_node(node)
+    }
+
+    fn is_inferred(&self, quad: &EncodedQuad) -> Result<bool, io::Error> {
+        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+        write_spog_quad(&mut buffer, quad);
+        Ok(self.inferred.contains_key(buffer)?)
+    }
+
+    fn inferred_quads(&self) -> Result<Vec<EncodedQuad>, io::Error> {
+        self.inferred
+            .iter()
+            .map(|entry| QuadEncoding::SPOG.decode(&entry?.0))
+            .collect()
+    }
+
+    fn insert_inferred(&self, quads: &[EncodedQuad]) -> Result<(), io::Error> {
+        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+        for quad in quads {
+            self.insert_quad(quad)?;
+            write_spog_quad(&mut buffer, quad);
+            self.inferred.insert(buffer.as_slice(), &[])?;
+            buffer.clear();
         }
         Ok(())
     }
 
-    fn remove_encoded_named_graph(&mut self, graph_name: EncodedTerm) -> Result<(), io::Error> {
-        for quad in self.quads_for_graph(graph_name) {
-            self.remove_encoded(&quad?)?;
+    fn remove_inferred(&self, quads: &[EncodedQuad]) -> Result<(), io::Error> {
+        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+        for quad in quads {
+            self.remove_quad(quad)?;
+            write_spog_quad(&mut buffer, quad);
+            self.inferred.remove(buffer.as_slice())?;
+            buffer.clear();
         }
-        self.graphs.remove(&encode_term(graph_name))?;
         Ok(())
     }
 
-    fn clear(&mut self) -> Result<(), io::Error> {
-        self.dspo.clear()?;
-        self.dpos.clear()?;
-        self.dosp.clear()?;
-        self.gspo.clear()?;
-        self.gpos.clear()?;
-        self.gosp.clear()?;
-        self.spog.clear()?;
-        self.posg.clear()?;
-        self.ospg.clear()?;
-        self.graphs.clear()?;
-        self.id2str.clear()?;
+    fn unmark_inferred(&self, quads: &[EncodedQuad]) -> Result<(), io::Error> {
+        let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+        for quad in quads {
+            write_spog_quad(&mut buffer, quad);
+            self.inferred.remove(buffer.as_slice())?;
+            buffer.clear();
+        }
         Ok(())
     }
 }
@@ -980,9 +1628,30 @@ pub struct SledTransaction<'a> {
     dpos: &'a TransactionalTree,
     dosp: &'a TransactionalTree,
     graphs: &'a TransactionalTree,
+    stats: &'a TransactionalTree,
+    /// The changes to propagate to the inferred quads if an entailment regime is set
+    changes: Option<&'a RefCell<QuadChanges<StrHash>>>,
+    /// The changes to publish to the subscribers if there are some
+    collector: Option<&'a RefCell<ChangeCollector<StrHash>>>,
+    /// The inferred quads if an entailment regime is set
+    inferred: Option<&'a Tree>,
+    /// If the full-text index is enabled
+    text_index: bool,
 }
 
 impl SledTransaction<'_> {
+    fn is_inferred(&self, quad: &EncodedQuad) -> Result<bool, SledUnabortableTransactionError> {
+        Ok(if let Some(inferred) = self.inferred {
+            let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE);
+            write_spog_quad(&mut buffer, quad);
+            inferred
+                .contains_key(buffer)
+                .map_err(|e| SledUnabortableTransactionError::Storage(e.into()))?
+        } else {
+            false
+        })
+    }
+
     /// Loads a graph file (i.e. triples) into the store during the transaction.
     ///
     /// Warning: Because the load happens during a transaction,
@@ -1005,7 +1674,14 @@ impl SledTransaction<'_> {
         base_iri: Option<&str>,
     ) -> Result<(), SledUnabortableTransactionError> {
         let mut this = self;
-        load_graph(&mut this, reader, format, to_graph_name.into(), base_iri)?;
+        load_graph(
+            &mut this,
+            reader,
+            format,
+            to_graph_name.into(),
+            base_iri,
+            None,
+        )?;
         Ok(())
     }
 
@@ -1030,7 +1706,7 @@ impl SledTransaction<'_> {
         base_iri: Option<&str>,
     ) -> Result<(), SledUnabortableTransactionError> {
         let mut this = self;
-        load_dataset(&mut this, reader, format, base_iri)?;
+        load_dataset(&mut this, reader, format, base_iri, None)?;
         Ok(())
     }
 
@@ -1082,6 +1758,40 @@ impl<'a> StrLookup for &'a SledTransaction<'a> {
     }
 }
 
+impl<'a> CountersStore for &'a SledTransaction<'a> {
+    type Error = SledUnabortableTransactionError;
+
+    fn add_to_counter(
+        &mut self,
+        key: &[u8],
+        delta: i64,
+    ) -> Result<u64, SledUnabortableTransactionError> {
+        let value = add_delta(
+            self.stats
+                .get(key)?
+                .map(|value| decode_counter(&value))
+                .transpose()
+                .map_err(SledUnabortableTransactionError::Storage)?
+                .unwrap_or(0),
+            delta,
+        );
+        if value == 0 {
+            self.stats.remove(key)?;
+        } else {
+            self.stats.insert(key, encode_counter(value).as_ref())?;
+        }
+        Ok(value)
+    }
+
+    fn has_text_index(&self) -> bool {
+        self.text_index
+    }
+
+    fn get_text_str(&self, id: StrHash) -> Result<Option<String>, SledUnabortableTransactionError> {
+        self.get_str(id)
+    }
+}
+
 impl<'a> StrContainer for &'a SledTransaction<'a> {
     fn insert_str(&mut self, value: &str) -> Result<StrHash, SledUnabortableTransactionError> {
         let key = StrHash::new(value);
@@ -1095,11 +1805,14 @@ impl<'a> WritableEncodedStore for &'a SledTransaction<'a> {
         &mut self,
         quad: &EncodedQuad,
     ) -> Result<(), SledUnabortableTransactionError> {
+        if let Some(changes) = self.changes {
+            changes.borrow_mut().record_insertion(quad);
+        }
         let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE + 1);
 
-        if quad.graph_name.is_default_graph() {
+        let is_new = if quad.graph_name.is_default_graph() {
             write_spo_quad(&mut buffer, quad);
-            self.dspo.insert(buffer.as_slice(), &[])?;
+            let is_new = self.dspo.insert(buffer.as_slice(), &[])?.is_none();
             buffer.clear();
 
             write_pos_quad(&mut buffer, quad);
@@ -1109,9 +1822,11 @@ impl<'a> WritableEncodedStore for &'a SledTransaction<'a> {
             write_osp_quad(&mut buffer, quad);
             self.dosp.insert(buffer.as_slice(), &[])?;
             buffer.clear();
+
+            is_new
         } else {
             write_spog_quad(&mut buffer, quad);
-            self.spog.insert(buffer.as_slice(), &[])?;
+            let is_new = self.spog.insert(buffer.as_slice(), &[])?.is_none();
             buffer.clear();
 
             write_posg_quad(&mut buffer, quad);
@@ -1133,8 +1848,18 @@ impl<'a> WritableEncodedStore for &'a SledTransaction<'a> {
             write_gosp_quad(&mut buffer, quad);
             self.gosp.insert(buffer.as_slice(), &[])?;
             buffer.clear();
-        }
 
+            is_new
+        };
+
+        if let Some(collector) = self.collector {
+            collector.borrow_mut().record_insertion(quad, || {
+                Ok::<_, SledUnabortableTransactionError>(!is_new && !self.is_inferred(quad)?)
+            })?;
+        }
+        if is_new {
+            record_quad_insertion(self, quad)?;
+        }
         Ok(())
     }
 
@@ -1142,11 +1867,14 @@ impl<'a> WritableEncodedStore for &'a SledTransaction<'a> {
         &mut self,
         quad: &EncodedQuad,
     ) -> Result<(), SledUnabortableTransactionError> {
+        if let Some(changes) = self.changes {
+            changes.borrow_mut().record_removal(quad);
+        }
         let mut buffer = Vec::with_capacity(4 * WRITTEN_TERM_MAX_SIZE + 1);
 
-        if quad.graph_name.is_default_graph() {
+        let was_present = if quad.graph_name.is_default_graph() {
             write_spo_quad(&mut buffer, quad);
-            self.dspo.remove(buffer.as_slice())?;
+            let was_present = self.dspo.remove(buffer.as_slice())?.is_some();
             buffer.clear();
 
             write_pos_quad(&mut buffer, quad);
@@ -1156,9 +1884,11 @@ impl<'a> WritableEncodedStore for &'a SledTransaction<'a> {
             write_osp_quad(&mut buffer, quad);
             self.dosp.remove(buffer.as_slice())?;
             buffer.clear();
+
+            was_present
         } else {
             write_spog_quad(&mut buffer, quad);
-            self.spog.remove(buffer.as_slice())?;
+            let was_present = self.spog.remove(buffer.as_slice())?.is_some();
             buffer.clear();
 
             write_posg_quad(&mut buffer, quad);
@@ -1180,8 +1910,18 @@ impl<'a> WritableEncodedStore for &'a SledTransaction<'a> {
             write_gosp_quad(&mut buffer, quad);
             self.gosp.remove(buffer.as_slice())?;
             buffer.clear();
-        }
 
+            was_present
+        };
+
+        if let Some(collector) = self.collector {
+            collector.borrow_mut().record_removal(quad, || {
+                Ok::<_, SledUnabortableTransactionError>(was_present && !self.is_inferred(quad)?)
+            })?;
+        }
+        if was_present {
+            record_quad_removal(self, quad)?;
+        }
         Ok(())
     }
 
@@ -1494,7 +2234,7 @@ impl Iterator for SledGraphNameIter {
 fn store() -> Result<(), io::Error> {
     use crate::model::*;
 
-    let main_s = NamedOrBlankNode::from(BlankNode::default());
+    let main_s = Subject::from(BlankNode::default());
     let main_p = NamedNode::new("http://example.com").unwrap();
     let main_o = Term::from(Literal::from(1));
     let main_g = GraphName::from(BlankNode::default());
@@ -1687,3 +2427,223 @@ fn store() -> Result<(), io::Error> {
 
     Ok(())
 }
+
+#[test]
+fn statistics() -> Result<(), io::Error> {
+    use crate::model::*;
+
+    let s1 = NamedNode::new_unchecked("http://example.com/s1");
+    let s2 = NamedNode::new_unchecked("http://example.com/s2");
+    let p = NamedNode::new_unchecked("http://example.com/p");
+    let o = Literal::from(1);
+    let g = NamedNode::new_unchecked("http://example.com/g");
+
+    let store = SledStore::new()?;
+    store.insert(QuadRef::new(&s1, &p, &o, GraphNameRef::DefaultGraph))?;
+    store.insert(QuadRef::new(&s1, &p, &o, GraphNameRef::DefaultGraph))?;
+    let result: Result<_, SledTransactionError<io::Error>> = store.transaction(|t| {
+        t.insert(QuadRef::new(&s1, &p, &o, &g))?;
+        t.insert(QuadRef::new(&s2, &p, &o, &g))?;
+        Ok(())
+    });
+    result?;
+
+    let encoded_p = store.get_encoded_named_node(p.as_ref())?.unwrap();
+    assert_eq!(store.encoded_quads_count()?, 3);
+    assert_eq!(
+        store.encoded_predicate_statistics(encoded_p)?,
+        PredicateStatistics {
+            quads: 3,
+            distinct_subjects: 2,
+            distinct_objects: 1
+        }
+    );
+
+    store.remove(QuadRef::new(&s2, &p, &o, &g))?;
+    assert_eq!(
+        store.encoded_predicate_statistics(encoded_p)?,
+        PredicateStatistics {
+            quads: 2,
+            distinct_subjects: 1,
+            distinct_objects: 1
+        }
+    );
+
+    store.clear_graph(GraphNameRef::DefaultGraph)?;
+    store.remove_named_graph(&g)?;
+    assert_eq!(store.encoded_quads_count()?, 0);
+    assert_eq!(
+        store.encoded_predicate_statistics(encoded_p)?,
+        PredicateStatistics::default()
+    );
+    Ok(())
+}
+
+#[test]
+fn inference() -> Result<(), io::Error> {
+    use crate::model::vocab::{rdf, rdfs};
+    use crate::model::*;
+
+    let person = NamedNodeRef::new_unchecked("http://example.com/Person");
+    let agent = NamedNodeRef::new_unchecked("http://example.com/Agent");
+    let alice = NamedNodeRef::new_unchecked("http://example.com/alice");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+
+    let store = SledStore::new()?;
+    store.set_entailment_regime(Some(EntailmentRegime::Rdfs))?;
+    assert_eq!(store.entailment_regime(), Some(EntailmentRegime::Rdfs));
+    let result: Result<_, SledTransactionError<io::Error>> = store.transaction(|t| {
+        t.insert(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, g))?;
+        t.insert(QuadRef::new(alice, rdf::TYPE, person, g))?;
+        Ok(())
+    });
+    result?;
+    assert!(store.contains(QuadRef::new(alice, rdf::TYPE, agent, g))?);
+    assert_eq!(store.len(), 3);
+
+    let mut buffer = Vec::new();
+    store.dump_dataset(&mut buffer, DatasetFormat::NQuads)?;
+    assert_eq!(buffer.iter().filter(|c| **c == b'\n').count(), 2);
+
+    store.remove(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, g))?;
+    assert!(!store.contains(QuadRef::new(alice, rdf::TYPE, agent, g))?);
+    store.insert(QuadRef::new(person, rdfs::SUB_CLASS_OF, agent, g))?;
+    assert!(store.contains(QuadRef::new(alice, rdf::TYPE, agent, g))?);
+    store.clear_graph(g)?;
+    assert!(store.is_empty());
+    store.set_entailment_regime(None)?;
+    assert_eq!(store.entailment_regime(), None);
+    Ok(())
+}
+
+#[test]
+fn change_subscription() -> Result<(), io::Error> {
+    use crate::model::vocab::{rdf, rdfs};
+    use crate::model::*;
+
+    let store = SledStore::new()?;
+    let ex = NamedNodeRef::new_unchecked("http://example.com/ex");
+    let g = NamedNodeRef::new_unchecked("http://example.com/g");
+    let before = QuadRef::new(ex, ex, ex, GraphNameRef::DefaultGraph);
+    store.insert(before)?;
+    let mut changes = store.subscribe();
+
+    // Simple writes
+    let quad = QuadRef::new(ex, ex, ex, g);
+    store.insert(quad)?;
+    store.insert(quad)?;
+    store.remove(quad)?;
+    store.remove(quad)?;
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(batch.sequence_number(), 1);
+    assert_eq!(batch.inserted(), &[quad.into_owned()]);
+    assert!(batch.removed().is_empty());
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(batch.sequence_number(), 2);
+    assert!(batch.inserted().is_empty());
+    assert_eq!(batch.removed(), &[quad.into_owned()]);
+    assert!(changes.try_recv().is_none());
+
+    // A transaction only contains the net changes
+    let result: Result<_, SledTransactionError<io::Error>> = store.transaction(|t| {
+        t.insert(quad)?;
+        t.remove(quad)?;
+        t.remove(before)?;
+        t.insert(before)?;
+        t.insert(QuadRef::new(ex, rdf::TYPE, ex, g))?;
+        Ok(())
+    });
+    result?;
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(batch.sequence_number(), 3);
+    assert_eq!(
+        batch.inserted(),
+        &[QuadRef::new(ex, rdf::TYPE, ex, g).into_owned()]
+    );
+    assert!(batch.removed().is_empty());
+
+    // The inferred quads are ignored
+    store.set_entailment_regime(Some(EntailmentRegime::Rdfs))?;
+    store.insert(QuadRef::new(ex, rdfs::SUB_CLASS_OF, rdfs::RESOURCE, g))?;
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(
+        batch.inserted(),
+        &[QuadRef::new(ex, rdfs::SUB_CLASS_OF, rdfs::RESOURCE, g).into_owned()]
+    );
+    assert!(changes.try_recv().is_none());
+
+    // Clear
+    store.clear()?;
+    let batch = changes.try_recv().unwrap();
+    assert_eq!(batch.sequence_number(), 5);
+    assert!(batch.inserted().is_empty());
+    assert_eq!(batch.removed().len(), 3);
+    store.clear()?;
+    assert!(changes.try_recv().is_none());
+    Ok(())
+}
+
+#[test]
+fn text_search() -> Result<(), io::Error> {
+    use crate::model::vocab::rdfs;
+    use crate::model::*;
+
+    let a = NamedNodeRef::new_unchecked("http://example.com/a");
+    let b = NamedNodeRef::new_unchecked("http://example.com/b");
+    let comment = QuadRef::new(
+        b,
+        rdfs::COMMENT,
+        LiteralRef::new_simple_literal("A red car that is really red and that is not an apple"),
+        None,
+    );
+    let subjects = |store: &SledStore, query: &str| -> Vec<Term> {
+        if let QueryResults::Solutions(solutions) = store
+            .query(
+                format!(
+                    "PREFIX text: <http://jena.apache.org/text#> SELECT ?s WHERE {{ ?s text:query \"{}\" }}",
+                    query
+                )
+                .as_str(),
+            )
+            .unwrap()
+        {
+            solutions
+                .map(|solution| solution.unwrap().get("s").unwrap().clone())
+                .collect()
+        } else {
+            unreachable!()
+        }
+    };
+
+    let store = SledStore::new()?;
+    store.insert(QuadRef::new(
+        a,
+        rdfs::LABEL,
+        LiteralRef::new_simple_literal("Red apple"),
+        None,
+    ))?;
+    assert_eq!(store.encoded_text_search("red")?, None);
+
+    // The index is built from the existing quads
+    store.set_text_index(true)?;
+    assert!(store.is_text_index_enabled());
+    assert_eq!(subjects(&store, "red"), vec![a.into_owned().into()]);
+
+    // The index is maintained by the transactions
+    let result: Result<_, SledTransactionError<io::Error>> = store.transaction(|t| {
+        t.insert(comment)?;
+        Ok(())
+    });
+    result?;
+    assert_eq!(
+        subjects(&store, "red apple"),
+        vec![b.into_owned().into(), a.into_owned().into()]
+    );
+    store.remove(comment)?;
+    assert_eq!(subjects(&store, "apple"), vec![a.into_owned().into()]);
+
+    // Disabling the index removes it
+    store.set_text_index(false)?;
+    assert_eq!(store.encoded_text_search("red")?, None);
+    Ok(())
+}
@@ -648,7 +648,7 @@ fn io_err_map(err: EvaluationError) -> std::io::Error {
     }
 }
 
-fn convert_subject<'a, T>(term: &'a T, buffer: &'a mut String) -> Option<NamedOrBlankNodeRef<'a>>
+fn convert_subject<'a, T>(term: &'a T, buffer: &'a mut String) -> Option<SubjectRef<'a>>
 where
     T: TTerm + ?Sized + 'a,
 {
@@ -742,7 +742,7 @@ where
     TO: TTerm + ?Sized,
     TG: TTerm + ?Sized,
 {
-    let s = match NamedOrBlankNode::try_copy(s) {
+    let s = match Subject::try_copy(s) {
         Ok(s) => s,
         Err(_) => return None,
     };
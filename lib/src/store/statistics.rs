@@ -0,0 +1,374 @@
+//! Cardinality statistics maintained by the on-disk stores.
+//!
+//! All the statistics are stored as counters in a dedicated key-value tree.
+//! The number of distinct subjects and objects of a predicate is maintained using reference counters
+//! on the (predicate, subject) and (predicate, object) pairs.
+//!
+//! If the store has a full-text index, it is also stored as counters:
+//! the number of occurrences of each token in each indexed string literal and the number of indexed literals.
+//! The indexed literals are maintained using reference counters on the quad objects.
+
+use crate::error::invalid_data_error;
+use crate::store::binary_encoder::{decode_term, write_term, StrHash, WRITTEN_TERM_MAX_SIZE};
+use crate::store::text::{
+    is_text_literal, search_text, text_literal_value, token_frequencies, TextMatch,
+};
+use crate::store::PredicateStatistics;
+use std::io;
+use std::mem::size_of;
+
+type EncodedTerm = crate::store::numeric_encoder::EncodedTerm<StrHash>;
+type EncodedQuad = crate::store::numeric_encoder::EncodedQuad<StrHash>;
+
+const TOTAL_QUADS: u8 = 1;
+const PREDICATE_QUADS: u8 = 2;
+const PREDICATE_SUBJECTS: u8 = 3;
+const PREDICATE_OBJECTS: u8 = 4;
+const PREDICATE_SUBJECT_QUADS: u8 = 5;
+const PREDICATE_OBJECT_QUADS: u8 = 6;
+const TEXT_LITERALS: u8 = 7;
+const TEXT_LITERAL_QUADS: u8 = 8;
+const TEXT_POSTINGS: u8 = 9;
+
+const COUNTER_KEY_MAX_SIZE: usize = size_of::<u8>() + 2 * WRITTEN_TERM_MAX_SIZE;
+
+/// A storage for the statistics counters
+pub(crate) trait CountersStore {
+    type Error;
+
+    /// Adds `delta` to the counter `key` and returns its new value.
+    ///
+    /// A counter that does not exist is equal to 0 and the counters equal to 0 should be removed.
+    fn add_to_counter(&mut self, key: &[u8], delta: i64) -> Result<u64, Self::Error>;
+
+    /// Returns if the full-text index counters should be maintained
+    fn has_text_index(&self) -> bool;
+
+    /// Returns the string with the given id, used to index the string literals that are not inlined
+    fn get_text_str(&self, id: StrHash) -> Result<Option<String>, Self::Error>;
+}
+
+/// Updates the statistics after the insertion of a quad that was not in the store
+pub(crate) fn record_quad_insertion<S: CountersStore>(
+    store: &mut S,
+    quad: &EncodedQuad,
+) -> Result<(), S::Error> {
+    update_statistics(store, quad, 1)
+}
+
+/// Updates the statistics after the removal of a quad that was in the store
+pub(crate) fn record_quad_removal<S: CountersStore>(
+    store: &mut S,
+    quad: &EncodedQuad,
+) -> Result<(), S::Error> {
+    update_statistics(store, quad, -1)
+}
+
+fn update_statistics<S: CountersStore>(
+    store: &mut S,
+    quad: &EncodedQuad,
+    delta: i64,
+) -> Result<(), S::Error> {
+    let mut buffer = Vec::with_capacity(COUNTER_KEY_MAX_SIZE);
+    // The pair counter is 1 after the insertion of a new pair and 0 after the removal of the last one
+    let pair_boundary = if delta > 0 { 1 } else { 0 };
+
+    store.add_to_counter(&[TOTAL_QUADS], delta)?;
+
+    write_counter_key(&mut buffer, PREDICATE_QUADS, quad.predicate, None);
+    store.add_to_counter(&buffer, delta)?;
+    buffer.clear();
+
+    write_counter_key(
+        &mut buffer,
+        PREDICATE_SUBJECT_QUADS,
+        quad.predicate,
+        Some(quad.subject),
+    );
+    if store.add_to_counter(&buffer, delta)? == pair_boundary {
+        buffer.clear();
+        write_counter_key(&mut buffer, PREDICATE_SUBJECTS, quad.predicate, None);
+        store.add_to_counter(&buffer, delta)?;
+    }
+    buffer.clear();
+
+    write_counter_key(
+        &mut buffer,
+        PREDICATE_OBJECT_QUADS,
+        quad.predicate,
+        Some(quad.object),
+    );
+    if store.add_to_counter(&buffer, delta)? == pair_boundary {
+        buffer.clear();
+        write_counter_key(&mut buffer, PREDICATE_OBJECTS, quad.predicate, None);
+        store.add_to_counter(&buffer, delta)?;
+    }
+
+    if store.has_text_index() && is_text_literal(quad.object) {
+        buffer.clear();
+        buffer.push(TEXT_LITERAL_QUADS);
+        write_term(&mut buffer, quad.object);
+        if store.add_to_counter(&buffer, delta)? == pair_boundary {
+            store.add_to_counter(&[TEXT_LITERALS], delta)?;
+            if let Some(value) = text_literal_value(quad.object, |id| store.get_text_str(id))? {
+                for (token, frequency) in token_frequencies(&value) {
+                    buffer.clear();
+                    write_posting_key(&mut buffer, &token, Some(quad.object));
+                    store.add_to_counter(&buffer, delta * i64::from(frequency))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the number of quads in the store using the `get_counter` lookup function
+pub(crate) fn read_quads_count<E>(get_counter: impl Fn(&[u8]) -> Result<u64, E>) -> Result<u64, E> {
+    get_counter(&[TOTAL_QUADS])
+}
+
+/// Reads the statistics of a predicate using the `get_counter` lookup function
+pub(crate) fn read_predicate_statistics<E>(
+    predicate: EncodedTerm,
+    get_counter: impl Fn(&[u8]) -> Result<u64, E>,
+) -> Result<PredicateStatistics, E> {
+    let mut buffer = Vec::with_capacity(COUNTER_KEY_MAX_SIZE);
+    write_counter_key(&mut buffer, PREDICATE_QUADS, predicate, None);
+    let quads = get_counter(&buffer)?;
+    buffer.clear();
+    write_counter_key(&mut buffer, PREDICATE_SUBJECTS, predicate, None);
+    let distinct_subjects = get_counter(&buffer)?;
+    buffer.clear();
+    write_counter_key(&mut buffer, PREDICATE_OBJECTS, predicate, None);
+    let distinct_objects = get_counter(&buffer)?;
+    Ok(PredicateStatistics {
+        quads,
+        distinct_subjects,
+        distinct_objects,
+    })
+}
+
+/// Evaluates a full-text query against the index counters
+///
+/// `get_counter` looks up a counter and `scan_counters` returns the counters whose key starts with the given prefix
+/// with the remaining part of their keys.
+pub(crate) fn search_text_index(
+    query: &str,
+    get_counter: impl Fn(&[u8]) -> Result<u64, io::Error>,
+    scan_counters: impl Fn(&[u8]) -> Result<Vec<(Vec<u8>, u64)>, io::Error>,
+) -> Result<Vec<TextMatch<StrHash>>, io::Error> {
+    let literals_count = get_counter(&[TEXT_LITERALS])?;
+    search_text(query, literals_count, |token| {
+        let mut prefix = Vec::with_capacity(size_of::<u8>() + size_of::<StrHash>());
+        write_posting_key(&mut prefix, token, None);
+        scan_counters(&prefix)?
+            .into_iter()
+            .map(|(literal, frequency)| Ok::<_, io::Error>((decode_term(&literal)?, frequency)))
+            .collect()
+    })
+}
+
+fn write_posting_key(sink: &mut Vec<u8>, token: &str, literal: Option<EncodedTerm>) {
+    sink.push(TEXT_POSTINGS);
+    sink.extend_from_slice(&StrHash::new(token).to_be_bytes());
+    if let Some(literal) = literal {
+        write_term(sink, literal);
+    }
+}
+
+fn write_counter_key(
+    sink: &mut Vec<u8>,
+    kind: u8,
+    predicate: EncodedTerm,
+    other: Option<EncodedTerm>,
+) {
+    sink.push(kind);
+    write_term(sink, predicate);
+    if let Some(other) = other {
+        write_term(sink, other);
+    }
+}
+
+/// Returns the counter value after the addition of `delta`
+pub(crate) fn add_delta(value: u64, delta: i64) -> u64 {
+    if delta >= 0 {
+        value.saturating_add(delta.unsigned_abs())
+    } else {
+        value.saturating_sub(delta.unsigned_abs())
+    }
+}
+
+pub(crate) fn decode_counter(value: &[u8]) -> Result<u64, io::Error> {
+    let mut buffer = [0; 8];
+    if value.len() != buffer.len() {
+        return Err(invalid_data_error("Invalid statistics counter encoding"));
+    }
+    buffer.copy_from_slice(value);
+    Ok(u64::from_be_bytes(buffer))
+}
+
+pub(crate) fn encode_counter(value: u64) -> [u8; 8] {
+    value.to_be_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::UnwrapInfallible;
+    use crate::store::numeric_encoder::{StrContainer, StrEncodingAware, WriteEncoder};
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+
+    #[derive(Default)]
+    struct MemoryCounters {
+        counters: HashMap<Vec<u8>, u64>,
+        strings: HashMap<StrHash, String>,
+    }
+
+    impl CountersStore for MemoryCounters {
+        type Error = Infallible;
+
+        fn add_to_counter(&mut self, key: &[u8], delta: i64) -> Result<u64, Infallible> {
+            let value = add_delta(self.counters.get(key).copied().unwrap_or(0), delta);
+            if value == 0 {
+                self.counters.remove(key);
+            } else {
+                self.counters.insert(key.to_vec(), value);
+            }
+            Ok(value)
+        }
+
+        fn has_text_index(&self) -> bool {
+            true
+        }
+
+        fn get_text_str(&self, id: StrHash) -> Result<Option<String>, Infallible> {
+            Ok(self.strings.get(&id).cloned())
+        }
+    }
+
+    impl StrEncodingAware for MemoryCounters {
+        type Error = Infallible;
+        type StrId = StrHash;
+    }
+
+    impl StrContainer for MemoryCounters {
+        fn insert_str(&mut self, value: &str) -> Result<StrHash, Infallible> {
+            let id = StrHash::new(value);
+            self.strings.insert(id, value.to_owned());
+            Ok(id)
+        }
+    }
+
+    #[test]
+    fn test_statistics_maintenance() -> Result<(), Infallible> {
+        use crate::model::*;
+
+        let mut store = MemoryCounters::default();
+        let s1 = store.encode_named_node(NamedNodeRef::new_unchecked("http://example.com/s1"))?;
+        let s2 = store.encode_named_node(NamedNodeRef::new_unchecked("http://example.com/s2"))?;
+        let p = store.encode_named_node(NamedNodeRef::new_unchecked("http://example.com/p"))?;
+        let o = store.encode_literal(LiteralRef::new_simple_literal("o"))?;
+        let g = store.encode_named_node(NamedNodeRef::new_unchecked("http://example.com/g"))?;
+        let quads = [
+            EncodedQuad::new(s1, p, o, EncodedTerm::DefaultGraph),
+            EncodedQuad::new(s1, p, o, g),
+            EncodedQuad::new(s2, p, o, g),
+        ];
+        for quad in &quads {
+            record_quad_insertion(&mut store, quad)?;
+        }
+        let get_counter = |key: &[u8]| -> Result<u64, Infallible> {
+            Ok(store.counters.get(key).copied().unwrap_or(0))
+        };
+        assert_eq!(read_quads_count(get_counter)?, 3);
+        assert_eq!(
+            read_predicate_statistics(p, get_counter)?,
+            PredicateStatistics {
+                quads: 3,
+                distinct_subjects: 2,
+                distinct_objects: 1
+            }
+        );
+
+        record_quad_removal(&mut store, &quads[2])?;
+        let get_counter = |key: &[u8]| -> Result<u64, Infallible> {
+            Ok(store.counters.get(key).copied().unwrap_or(0))
+        };
+        assert_eq!(
+            read_predicate_statistics(p, get_counter)?,
+            PredicateStatistics {
+                quads: 2,
+                distinct_subjects: 1,
+                distinct_objects: 1
+            }
+        );
+
+        record_quad_removal(&mut store, &quads[0])?;
+        record_quad_removal(&mut store, &quads[1])?;
+        assert!(store.counters.is_empty());
+        Ok(())
+    }
+    #[test]
+    fn test_text_index_maintenance() -> Result<(), io::Error> {
+        use crate::model::*;
+
+        let mut store = MemoryCounters::default();
+        let s = store
+            .encode_named_node(NamedNodeRef::new_unchecked("http://example.com/s"))
+            .unwrap_infallible();
+        let p = store
+            .encode_named_node(NamedNodeRef::new_unchecked("http://example.com/p"))
+            .unwrap_infallible();
+        let g = store
+            .encode_named_node(NamedNodeRef::new_unchecked("http://example.com/g"))
+            .unwrap_infallible();
+        let small = store
+            .encode_literal(LiteralRef::new_language_tagged_literal_unchecked(
+                "red apple",
+                "en",
+            ))
+            .unwrap_infallible();
+        let big = store
+            .encode_literal(LiteralRef::new_simple_literal(
+                "a red car and an other red car that are both very red",
+            ))
+            .unwrap_infallible();
+        let quads = [
+            EncodedQuad::new(s, p, small, EncodedTerm::DefaultGraph),
+            EncodedQuad::new(s, p, small, g),
+            EncodedQuad::new(s, p, big, g),
+        ];
+        for quad in &quads {
+            record_quad_insertion(&mut store, quad).unwrap_infallible();
+        }
+        let search = |store: &MemoryCounters, query: &str| {
+            search_text_index(
+                query,
+                |key| Ok(store.counters.get(key).copied().unwrap_or(0)),
+                |prefix| {
+                    Ok(store
+                        .counters
+                        .iter()
+                        .filter_map(|(key, value)| {
+                            Some((key.strip_prefix(prefix)?.to_vec(), *value))
+                        })
+                        .collect())
+                },
+            )
+        };
+        let matches = search(&store, "Red")?;
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].literal, big);
+        assert_eq!(search(&store, "red apple")?.len(), 1);
+        assert!(search(&store, "blue")?.is_empty());
+
+        record_quad_removal(&mut store, &quads[0]).unwrap_infallible();
+        assert_eq!(search(&store, "apple")?.len(), 1);
+        record_quad_removal(&mut store, &quads[1]).unwrap_infallible();
+        assert!(search(&store, "apple")?.is_empty());
+        record_quad_removal(&mut store, &quads[2]).unwrap_infallible();
+        assert!(store.counters.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+//! Full-text index of the string literals.
+//!
+//! The lexical forms of the string literals (`xsd:string` and `rdf:langString`) are split into tokens,
+//! i.e. their maximal sequences of alphanumeric characters, lowercased.
+//! The index maps each token to the literals containing it with its number of occurrences in them.
+//! A query matches the literals containing all its tokens and they are scored using the
+//! [BM25](https://en.wikipedia.org/wiki/Okapi_BM25) weighting without document length normalization.
+
+use crate::error::UnwrapInfallible;
+use crate::store::numeric_encoder::{EncodedTerm, StrId};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::convert::{Infallible, TryFrom};
+
+/// A string literal matching a full-text query
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TextMatch<I: StrId> {
+    pub literal: EncodedTerm<I>,
+    pub score: f64,
+}
+
+impl<I: StrId> TextMatch<I> {
+    pub fn map_id<J: StrId>(self, mapping: impl Fn(I) -> J) -> TextMatch<J> {
+        TextMatch {
+            literal: self.literal.map_id(mapping),
+            score: self.score,
+        }
+    }
+}
+
+/// Returns if the term is a string literal indexed by the full-text index
+pub(crate) fn is_text_literal<I: StrId>(term: EncodedTerm<I>) -> bool {
+    matches!(
+        term,
+        EncodedTerm::SmallStringLiteral(_)
+            | EncodedTerm::BigStringLiteral { .. }
+            | EncodedTerm::SmallSmallLangStringLiteral { .. }
+            | EncodedTerm::SmallBigLangStringLiteral { .. }
+            | EncodedTerm::BigSmallLangStringLiteral { .. }
+            | EncodedTerm::BigBigLangStringLiteral { .. }
+    )
+}
+
+/// Returns the lexical form of the term if it is a string literal indexed by the full-text index
+///
+/// `get_str` is used to resolve the strings that are not inlined in the term.
+pub(crate) fn text_literal_value<I: StrId, E>(
+    term: EncodedTerm<I>,
+    get_str: impl FnOnce(I) -> Result<Option<String>, E>,
+) -> Result<Option<String>, E> {
+    match term {
+        EncodedTerm::SmallStringLiteral(value)
+        | EncodedTerm::SmallSmallLangStringLiteral { value, .. }
+        | EncodedTerm::SmallBigLangStringLiteral { value, .. } => {
+            Ok(Some(value.as_str().to_owned()))
+        }
+        EncodedTerm::BigStringLiteral { value_id }
+        | EncodedTerm::BigSmallLangStringLiteral { value_id, .. }
+        | EncodedTerm::BigBigLangStringLiteral { value_id, .. } => get_str(value_id),
+        _ => Ok(None),
+    }
+}
+
+/// Splits a text into its lowercase tokens
+pub(crate) fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|token| !token.is_empty())
+        .map(str::to_lowercase)
+}
+
+/// Returns the tokens of a text with their number of occurrences
+pub(crate) fn token_frequencies(text: &str) -> BTreeMap<String, u32> {
+    let mut frequencies = BTreeMap::new();
+    for token in tokenize(text) {
+        let frequency = frequencies.entry(token).or_insert(0_u32);
+        *frequency = frequency.saturating_add(1);
+    }
+    frequencies
+}
+
+/// Evaluates a full-text query.
+///
+/// `literals_count` is the number of literals in the index and `postings` returns the literals containing a token
+/// with the number of occurrences of the token in them.
+/// The matches are sorted by decreasing score.
+pub(crate) fn search_text<I: StrId, E>(
+    query: &str,
+    literals_count: u64,
+    mut postings: impl FnMut(&str) -> Result<Vec<(EncodedTerm<I>, u64)>, E>,
+) -> Result<Vec<TextMatch<I>>, E> {
+    let mut scores: Option<HashMap<EncodedTerm<I>, f64>> = None;
+    for token in tokenize(query).collect::<BTreeSet<_>>() {
+        let postings = postings(&token)?;
+        let weight = inverse_document_frequency(literals_count, postings.len());
+        let token_scores = postings
+            .into_iter()
+            .map(|(literal, frequency)| (literal, weight * saturated_frequency(frequency)));
+        let new_scores = if let Some(scores) = scores {
+            token_scores
+                .filter_map(|(literal, score)| Some((literal, scores.get(&literal)? + score)))
+                .collect::<HashMap<_, _>>()
+        } else {
+            token_scores.collect()
+        };
+        if new_scores.is_empty() {
+            return Ok(Vec::new());
+        }
+        scores = Some(new_scores);
+    }
+    let mut matches = scores
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(literal, score)| TextMatch { literal, score })
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    Ok(matches)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn inverse_document_frequency(literals_count: u64, matching_literals_count: usize) -> f64 {
+    let matching_literals_count = matching_literals_count as f64;
+    let literals_count = (literals_count as f64).max(matching_literals_count);
+    (1. + (literals_count - matching_literals_count + 0.5) / (matching_literals_count + 0.5)).ln()
+}
+
+/// The BM25 term frequency saturation with k1 = 1.2
+#[allow(clippy::cast_precision_loss)]
+fn saturated_frequency(frequency: u64) -> f64 {
+    let frequency = frequency as f64;
+    frequency * 2.2 / (frequency + 1.2)
+}
+
+/// An in-memory full-text index
+pub(crate) struct MemoryTextIndex<I: StrId> {
+    /// The number of quads using each indexed literal as object
+    literals: HashMap<EncodedTerm<I>, u64>,
+    postings: HashMap<String, HashMap<EncodedTerm<I>, u64>>,
+}
+
+impl<I: StrId> Default for MemoryTextIndex<I> {
+    fn default() -> Self {
+        Self {
+            literals: HashMap::new(),
+            postings: HashMap::new(),
+        }
+    }
+}
+
+impl<I: StrId> MemoryTextIndex<I> {
+    /// Records a new quad using the literal as object
+    ///
+    /// `value` is only called if the literal was not used yet.
+    pub fn insert(&mut self, literal: EncodedTerm<I>, value: impl FnOnce() -> Option<String>) {
+        if !is_text_literal(literal) {
+            return;
+        }
+        let count = self.literals.entry(literal).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            return;
+        }
+        if let Some(value) = value() {
+            for (token, frequency) in token_frequencies(&value) {
+                self.postings
+                    .entry(token)
+                    .or_default()
+                    .insert(literal, frequency.into());
+            }
+        }
+    }
+
+    /// Records the removal of a quad using the literal as object
+    ///
+    /// `value` is only called if the literal is not used anymore.
+    pub fn remove(&mut self, literal: EncodedTerm<I>, value: impl FnOnce() -> Option<String>) {
+        if let Some(count) = self.literals.get_mut(&literal) {
+            *count -= 1;
+            if *count > 0 {
+                return;
+            }
+        } else {
+            return;
+        }
+        self.literals.remove(&literal);
+        if let Some(value) = value() {
+            for token in token_frequencies(&value).keys() {
+                if let Some(postings) = self.postings.get_mut(token) {
+                    postings.remove(&literal);
+                    if postings.is_empty() {
+                        self.postings.remove(token);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn search(&self, query: &str) -> Vec<TextMatch<I>> {
+        let literals_count = u64::try_from(self.literals.len()).unwrap_or(u64::MAX);
+        search_text(query, literals_count, |token| {
+            Ok::<_, Infallible>(self.postings.get(token).map_or_else(Vec::new, |postings| {
+                postings
+                    .iter()
+                    .map(|(literal, frequency)| (*literal, *frequency))
+                    .collect()
+            }))
+        })
+        .unwrap_infallible()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::small_string::SmallString;
+    use lasso::LargeSpur;
+    use std::convert::TryFrom;
+
+    fn literal(value: &str) -> EncodedTerm<LargeSpur> {
+        EncodedTerm::SmallStringLiteral(SmallString::try_from(value).unwrap())
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("Hello, World! l'\u{e9}t\u{e9} 2021").collect::<Vec<_>>(),
+            vec!["hello", "world", "l", "\u{e9}t\u{e9}", "2021"]
+        );
+        assert_eq!(
+            token_frequencies("foo bar Foo")
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![("bar".to_owned(), 1), ("foo".to_owned(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_memory_index() {
+        let mut index = MemoryTextIndex::default();
+        let values = [
+            "red apple",
+            "green apple",
+            "red car",
+            "apple pie apple",
+        ];
+        for value in &values {
+            index.insert(literal(value), || Some((*value).to_owned()));
+        }
+        index.insert(literal("red car"), || unreachable!());
+        index.insert(EncodedTerm::IntegerLiteral(1), || unreachable!());
+
+        let matches = index.search("RED");
+        assert_eq!(matches.len(), 2);
+        let matches = index.search("red apple");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].literal, literal("red apple"));
+        let matches = index.search("Apple");
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].literal, literal("apple pie apple"));
+        assert!(matches[0].score > matches[1].score);
+        assert!(index.search("blue").is_empty());
+        assert!(index.search("").is_empty());
+
+        index.remove(literal("red car"), || unreachable!());
+        assert_eq!(index.search("car").len(), 1);
+        index.remove(literal("red car"), || Some("red car".to_owned()));
+        assert!(index.search("car").is_empty());
+        assert_eq!(index.search("red").len(), 1);
+    }
+}
@@ -10,7 +10,7 @@ use pyo3::types::PyBytes;
 use pyo3::wrap_pyfunction;
 use pyo3::PyIterProtocol;
 use std::io;
-use std::io::{BufReader, Read, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 
 pub fn add_to_module(module: &PyModule) -> PyResult<()> {
     module.add_wrapped(wrap_pyfunction!(parse))?;
@@ -25,6 +25,7 @@ pub fn add_to_module(module: &PyModule) -> PyResult<()> {
 /// * `N-Quads <https://www.w3.org/TR/n-quads/>`_ (``application/n-quads``)
 /// * `Turtle <https://www.w3.org/TR/turtle/>`_ (``text/turtle``)
 /// * `TriG <https://www.w3.org/TR/trig/>`_ (``application/trig``)
+/// * `JSON-LD <https://www.w3.org/TR/json-ld11/>`_ (``application/ld+json``)
 /// * `RDF/XML <https://www.w3.org/TR/rdf-syntax-grammar/>`_ (``application/rdf+xml``)
 ///
 /// It supports also some MIME type aliases.
@@ -84,6 +85,39 @@ pub fn parse(
     }
 }
 
+/// A graph or dataset serialization format
+pub enum RdfFormat {
+    Graph(GraphFormat),
+    Dataset(DatasetFormat),
+}
+
+/// Finds the format from its MIME type or, if it is not given, from the first bytes of the input
+pub fn rdf_format(mime_type: Option<&str>, input: &mut impl BufRead) -> PyResult<RdfFormat> {
+    if let Some(mime_type) = mime_type {
+        if let Some(graph_format) = GraphFormat::from_media_type(mime_type) {
+            Ok(RdfFormat::Graph(graph_format))
+        } else if let Some(dataset_format) = DatasetFormat::from_media_type(mime_type) {
+            Ok(RdfFormat::Dataset(dataset_format))
+        } else {
+            Err(PyValueError::new_err(format!(
+                "Not supported MIME type: {}",
+                mime_type
+            )))
+        }
+    } else {
+        let data = input.fill_buf().map_err(map_io_err)?;
+        if let Some(graph_format) = GraphFormat::from_content(data) {
+            Ok(RdfFormat::Graph(graph_format))
+        } else if let Some(dataset_format) = DatasetFormat::from_content(data) {
+            Ok(RdfFormat::Dataset(dataset_format))
+        } else {
+            Err(PyValueError::new_err(
+                "The format could not be guessed from the input content, please provide its MIME type",
+            ))
+        }
+    }
+}
+
 /// Serializes an RDF graph or dataset
 ///
 /// It currently supports the following formats:
@@ -92,6 +126,7 @@ pub fn parse(
 /// * `N-Quads <https://www.w3.org/TR/n-quads/>`_ (``application/n-quads``)
 /// * `Turtle <https://www.w3.org/TR/turtle/>`_ (``text/turtle``)
 /// * `TriG <https://www.w3.org/TR/trig/>`_ (``application/trig``)
+/// * `JSON-LD <https://www.w3.org/TR/json-ld11/>`_ (``application/ld+json``)
 /// * `RDF/XML <https://www.w3.org/TR/rdf-syntax-grammar/>`_ (``application/rdf+xml``)
 ///
 /// It supports also some MIME type aliases.
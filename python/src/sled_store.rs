@@ -1,4 +1,4 @@
-use crate::io::PyFileLike;
+use crate::io::{rdf_format, PyFileLike, RdfFormat};
 use crate::model::*;
 use crate::sparql::*;
 use crate::store_utils::*;
@@ -9,6 +9,7 @@ use pyo3::exceptions::PyValueError;
 use pyo3::prelude::{
     pyclass, pymethods, pyproto, Py, PyAny, PyObject, PyRef, PyRefMut, PyResult, Python,
 };
+use pyo3::types::PyDict;
 use pyo3::{PyIterProtocol, PyObjectProtocol, PySequenceProtocol};
 use std::convert::TryFrom;
 use std::io::BufReader;
@@ -131,6 +132,8 @@ impl PySledStore {
     /// :type default_graph: NamedNode or BlankNode or DefaultGraph or list(NamedNode or BlankNode or DefaultGraph) or None, optional
     /// :param named_graphs: list of the named graphs that could be used in SPARQL `GRAPH` clause. By default, all the store named graphs are available.
     /// :type named_graphs: list(NamedNode or BlankNode) or None, optional
+    /// :param custom_functions: custom SPARQL functions indexed by their IRI. They are called with the evaluated arguments and should return the result term or :py:const:`None` to raise an evaluation error.
+    /// :type custom_functions: dict(NamedNode, callable) or None, optional
     /// :return: a :py:class:`bool` for ``ASK`` queries, an iterator of :py:class:`Triple` for ``CONSTRUCT`` and ``DESCRIBE`` queries and an iterator of :py:class:`QuerySolution` for ``SELECT`` queries.
     /// :rtype: QuerySolutions or QueryTriples or bool
     /// :raises SyntaxError: if the provided query is invalid
@@ -156,13 +159,14 @@ impl PySledStore {
     /// >>> store.add(Quad(NamedNode('http://example.com'), NamedNode('http://example.com/p'), Literal('1')))
     /// >>> store.query('ASK { ?s ?p ?o }')
     /// True
-    #[text_signature = "($self, query, *, use_default_graph_as_union, default_graph, named_graphs)"]
+    #[text_signature = "($self, query, *, use_default_graph_as_union, default_graph, named_graphs, custom_functions)"]
     #[args(
         query,
         "*",
         use_default_graph_as_union = "false",
         default_graph = "None",
-        named_graphs = "None"
+        named_graphs = "None",
+        custom_functions = "None"
     )]
     fn query(
         &self,
@@ -170,6 +174,7 @@ impl PySledStore {
         use_default_graph_as_union: bool,
         default_graph: Option<&PyAny>,
         named_graphs: Option<&PyAny>,
+        custom_functions: Option<&PyDict>,
         py: Python<'_>,
     ) -> PyResult<PyObject> {
         let query = parse_query(
@@ -178,7 +183,10 @@ impl PySledStore {
             default_graph,
             named_graphs,
         )?;
-        let results = self.inner.query(query).map_err(map_evaluation_error)?;
+        let results = self
+            .inner
+            .query_opt(query, query_options(custom_functions)?)
+            .map_err(map_evaluation_error)?;
         query_results_to_python(py, results)
     }
 
@@ -227,6 +235,8 @@ impl PySledStore {
     /// * `N-Quads <https://www.w3.org/TR/n-quads/>`_ (``application/n-quads``)
     /// * `Turtle <https://www.w3.org/TR/turtle/>`_ (``text/turtle``)
     /// * `TriG <https://www.w3.org/TR/trig/>`_ (``application/trig``)
+    /// * `JSON-LD <https://www.w3.org/TR/json-ld11/>`_ (``application/ld+json``)
+    /// * `JSON-LD <https://www.w3.org/TR/json-ld11/>`_ (``application/ld+json``)
     /// * `RDF/XML <https://www.w3.org/TR/rdf-syntax-grammar/>`_ (``application/rdf+xml``)
     ///
     /// It supports also some MIME type aliases.
@@ -235,13 +245,13 @@ impl PySledStore {
     ///
     /// :param input: The binary I/O object to read from. For example, it could be a file opened in binary mode with ``open('my_file.ttl', 'rb')``.
     /// :type input: io.RawIOBase or io.BufferedIOBase
-    /// :param mime_type: the MIME type of the RDF serialization
-    /// :type mime_type: str
+    /// :param mime_type: the MIME type of the RDF serialization. If it is not given, the format is guessed from the first bytes of the input.
+    /// :type mime_type: str or None, optional
     /// :param base_iri: the base IRI used to resolve the relative IRIs in the file or :py:const:`None` if relative IRI resolution should not be done
     /// :type base_iri: str or None, optional
     /// :param to_graph: if it is a file composed of triples, the graph in which store the triples. By default, the default graph is used.
     /// :type to_graph: NamedNode or BlankNode or DefaultGraph or None, optional
-    /// :raises ValueError: if the MIME type is not supported or the `to_graph` parameter is given with a quad file.
+    /// :raises ValueError: if the MIME type is not supported, the format could not be guessed or the `to_graph` parameter is given with a quad file.
     /// :raises SyntaxError: if the provided data is invalid
     /// :raises IOError: if an I/O error happens during a quad insertion
     ///
@@ -249,12 +259,17 @@ impl PySledStore {
     /// >>> store.load(io.BytesIO(b'<foo> <p> "1" .'), "text/turtle", base_iri="http://example.com/", to_graph=NamedNode("http://example.com/g"))
     /// >>> list(store)
     /// [<Quad subject=<NamedNode value=http://example.com/foo> predicate=<NamedNode value=http://example.com/p> object=<Literal value=1 datatype=<NamedNode value=http://www.w3.org/2001/XMLSchema#string>> graph_name=<NamedNode value=http://example.com/g>>]
-    #[text_signature = "($self, data, /, mime_type, *, base_iri = None, to_graph = None)"]
-    #[args(input, mime_type, "*", base_iri = "None", to_graph = "None")]
+    ///
+    /// >>> store = SledStore()
+    /// >>> store.load(io.BytesIO(b'<http://example.com/s> <http://example.com/p> "1" <http://example.com/g> .'))
+    /// >>> len(store)
+    /// 1
+    #[text_signature = "($self, data, /, mime_type = None, *, base_iri = None, to_graph = None)"]
+    #[args(input, mime_type = "None", "*", base_iri = "None", to_graph = "None")]
     fn load(
         &self,
         input: PyObject,
-        mime_type: &str,
+        mime_type: Option<&str>,
         base_iri: Option<&str>,
         to_graph: Option<&PyAny>,
     ) -> PyResult<()> {
@@ -263,30 +278,27 @@ impl PySledStore {
         } else {
             None
         };
-        let input = BufReader::new(PyFileLike::new(input));
-        if let Some(graph_format) = GraphFormat::from_media_type(mime_type) {
-            self.inner
+        let mut input = BufReader::new(PyFileLike::new(input));
+        match rdf_format(mime_type, &mut input)? {
+            RdfFormat::Graph(graph_format) => self
+                .inner
                 .load_graph(
                     input,
                     graph_format,
                     &to_graph_name.unwrap_or(PyGraphNameRef::DefaultGraph),
                     base_iri,
                 )
-                .map_err(map_io_err)
-        } else if let Some(dataset_format) = DatasetFormat::from_media_type(mime_type) {
-            if to_graph_name.is_some() {
-                return Err(PyValueError::new_err(
-                    "The target graph name parameter is not available for dataset formats",
-                ));
+                .map_err(map_io_err),
+            RdfFormat::Dataset(dataset_format) => {
+                if to_graph_name.is_some() {
+                    return Err(PyValueError::new_err(
+                        "The target graph name parameter is not available for dataset formats",
+                    ));
+                }
+                self.inner
+                    .load_dataset(input, dataset_format, base_iri)
+                    .map_err(map_io_err)
             }
-            self.inner
-                .load_dataset(input, dataset_format, base_iri)
-                .map_err(map_io_err)
-        } else {
-            Err(PyValueError::new_err(format!(
-                "Not supported MIME type: {}",
-                mime_type
-            )))
         }
     }
 
@@ -298,6 +310,8 @@ impl PySledStore {
     /// * `N-Quads <https://www.w3.org/TR/n-quads/>`_ (``application/n-quads``)
     /// * `Turtle <https://www.w3.org/TR/turtle/>`_ (``text/turtle``)
     /// * `TriG <https://www.w3.org/TR/trig/>`_ (``application/trig``)
+    /// * `JSON-LD <https://www.w3.org/TR/json-ld11/>`_ (``application/ld+json``)
+    /// * `JSON-LD <https://www.w3.org/TR/json-ld11/>`_ (``application/ld+json``)
     /// * `RDF/XML <https://www.w3.org/TR/rdf-syntax-grammar/>`_ (``application/rdf+xml``)
     ///
     /// It supports also some MIME type aliases.
@@ -7,6 +7,7 @@ use pyo3::prelude::{
     pyclass, pymethods, pyproto, FromPyObject, IntoPy, Py, PyAny, PyCell, PyErr, PyObject, PyRef,
     PyRefMut, PyResult, Python,
 };
+use pyo3::types::{PyDict, PyTuple};
 use pyo3::{PyIterProtocol, PyMappingProtocol, PyObjectProtocol};
 use std::vec::IntoIter;
 
@@ -58,6 +59,27 @@ pub fn parse_query(
     Ok(query)
 }
 
+pub fn query_options(custom_functions: Option<&PyDict>) -> PyResult<QueryOptions> {
+    let mut options = QueryOptions::default();
+    if let Some(custom_functions) = custom_functions {
+        for (name, function) in custom_functions {
+            let name = name.extract::<PyNamedNode>()?;
+            let function = PyObject::from(function);
+            options = options.with_custom_function(name.into(), move |args| {
+                Python::with_gil(|py| {
+                    let args = PyTuple::new(
+                        py,
+                        args.iter().map(|arg| PyTerm::from(arg.clone()).into_py(py)),
+                    );
+                    let result = function.call1(py, args).ok()?;
+                    Some(result.extract::<Option<PyTerm>>(py).ok()??.into())
+                })
+            })
+        }
+    }
+    Ok(options)
+}
+
 pub fn query_results_to_python(py: Python<'_>, results: QueryResults) -> PyResult<PyObject> {
     Ok(match results {
         QueryResults::Solutions(inner) => PyQuerySolutions { inner }.into_py(py),
@@ -139,6 +139,20 @@ class TestAbstractStore(unittest.TestCase, ABC):
         )
         self.assertEqual(len(list(results)), 3)
 
+    def test_select_query_with_custom_function(self):
+        store = self.store()
+        store.add(Quad(foo, bar, baz))
+        results = store.query(
+            "SELECT (<http://example.com/f>(?o) AS ?v) (<http://example.com/g>(?o) AS ?u) WHERE { ?s ?p ?o }",
+            custom_functions={
+                NamedNode("http://example.com/f"): lambda o: Literal(o.value.upper()),
+                NamedNode("http://example.com/g"): lambda o: None,
+            },
+        )
+        solution = next(results)
+        self.assertEqual(solution["v"], Literal("HTTP://BAZ"))
+        self.assertIsNone(solution["u"])
+
     def test_select_query_with_named_graph(self):
         store = self.store()
         graph_bnode = BlankNode("g")
@@ -217,6 +231,18 @@ class TestAbstractStore(unittest.TestCase, ABC):
         )
         self.assertEqual(set(store), {Quad(foo, bar, baz, graph)})
 
+    def test_load_without_mime_type(self):
+        store = self.store()
+        store.load(
+            BytesIO(b"<http://foo> <http://bar> <http://baz> <http://graph> .")
+        )
+        store.load(
+            BytesIO(b"@prefix ex: <http://> .\nex:foo ex:bar ex:baz ."), to_graph=graph
+        )
+        self.assertEqual(set(store), {Quad(foo, bar, baz, graph)})
+        with self.assertRaises(ValueError):
+            store.load(BytesIO(b"foo"))
+
     def test_dump_ntriples(self):
         store = self.store()
         store.add(Quad(foo, bar, baz, graph))
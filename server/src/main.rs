@@ -14,27 +14,37 @@ use async_std::future::Future;
 use async_std::io::Read;
 use async_std::net::{TcpListener, TcpStream};
 use async_std::prelude::*;
-use async_std::task::{block_on, spawn};
+use async_std::task::{block_on, spawn, spawn_blocking};
 use http_types::{
     bail_status, headers, Error, Method, Mime, Request, Response, Result, StatusCode,
 };
-use oxigraph::io::{DatasetFormat, GraphFormat};
-use oxigraph::model::{GraphName, GraphNameRef, NamedNode, NamedOrBlankNode};
+use oxigraph::io::{Compression, DatasetFormat, GraphFormat};
+use oxigraph::model::{GraphName, GraphNameRef, NamedNode, NamedOrBlankNode, Triple};
+use oxigraph::shacl::ShapesGraph;
 use oxigraph::sparql::algebra::GraphUpdateOperation;
-use oxigraph::sparql::{Query, QueryResults, QueryResultsFormat, Update};
+use oxigraph::sparql::{
+    EvaluationError, Query, QueryOptions, QueryResults, QueryResultsFormat, Update, UpdateOptions,
+};
+#[cfg(feature = "rocksdb")]
+use oxigraph::store::rocksdb::RocksDbBulkLoader;
 #[cfg(feature = "rocksdb")]
 use oxigraph::RocksDbStore as Store;
 #[cfg(all(feature = "sled", not(feature = "rocksdb")))]
 use oxigraph::SledStore as Store;
 use rand::random;
-use std::io::BufReader;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 use url::{form_urlencoded, Url};
 
 const MAX_SPARQL_BODY_SIZE: u64 = 1_048_576;
 const HTML_ROOT_PAGE: &str = include_str!("../templates/query.html");
 const LOGO: &str = include_str!("../logo.svg");
 const SERVER: &str = concat!("Oxigraph/", env!("CARGO_PKG_VERSION"));
+const DEFAULT_CHANGES_TIMEOUT: u64 = 30;
+const MAX_CHANGES_TIMEOUT: u64 = 300;
 
 #[derive(FromArgs)]
 /// Oxigraph SPARQL server
@@ -46,22 +56,217 @@ struct Args {
     /// directory in which persist the data
     #[argh(option, short = 'f')]
     file: String,
+
+    /// maximal duration in seconds of the SPARQL queries and updates evaluation
+    #[argh(option)]
+    query_timeout: Option<u64>,
+
+    /// directory in which the backups requested with the /backup endpoint are written
+    #[argh(option)]
+    backup_directory: Option<String>,
+
+    /// directory of the backups from which the latest backup is restored into the data directory before starting
+    #[argh(option)]
+    restore_from: Option<String>,
+
+    /// open the data directory in read-only mode and disable the SPARQL updates and the writes on /store
+    #[argh(switch)]
+    read_only: bool,
+
+    /// enable the change log of the store in order to stream its changes with the /changes endpoint
+    #[argh(switch)]
+    change_log: bool,
+
+    #[argh(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    Load(LoadCommand),
+}
+
+#[derive(FromArgs)]
+/// Loads files into the data directory and exits without starting the server
+#[argh(subcommand, name = "load")]
+struct LoadCommand {
+    /// file to load. The format is guessed from the file extension. This option could be repeated
+    #[argh(option, short = 'i')]
+    input: Vec<String>,
+
+    /// number of threads used to load the files. By default the number of CPUs
+    #[argh(option)]
+    threads: Option<usize>,
 }
 
 #[async_std::main]
 pub async fn main() -> Result<()> {
     let args: Args = argh::from_env();
-    let store = Store::open(args.file)?;
+    if let Some(restore_from) = args.restore_from {
+        restore(&restore_from, &args.file)?;
+    }
+    let store = if args.read_only {
+        open_read_only(&args.file)?
+    } else {
+        Store::open(args.file)?
+    };
+    if args.change_log {
+        enable_change_log(&store)?;
+    }
+    if let Some(Command::Load(command)) = args.command {
+        return load(&store, &command);
+    }
+    let query_timeout = args.query_timeout.map(Duration::from_secs);
+    let backup_directory = args.backup_directory.map(PathBuf::from);
+    let read_only = args.read_only;
 
     println!("Listening for requests at http://{}", &args.bind);
     http_server(&args.bind, move |request| {
-        handle_request(request, store.clone())
+        handle_request(
+            request,
+            store.clone(),
+            query_timeout,
+            backup_directory.clone(),
+            read_only,
+        )
     })
     .await
 }
 
-async fn handle_request(request: Request, store: Store) -> Result<Response> {
+#[cfg(feature = "rocksdb")]
+fn restore(backup_directory: &str, path: &str) -> Result<()> {
+    Ok(Store::restore(backup_directory, path)?)
+}
+
+#[cfg(feature = "rocksdb")]
+fn open_read_only(path: &str) -> Result<Store> {
+    Ok(Store::open_read_only(path)?)
+}
+
+#[cfg(all(feature = "sled", not(feature = "rocksdb")))]
+fn open_read_only(_path: &str) -> Result<Store> {
+    bail_status!(
+        501,
+        "The read-only mode is only supported by the RocksDB storage"
+    )
+}
+
+#[cfg(feature = "rocksdb")]
+fn enable_change_log(store: &Store) -> Result<()> {
+    Ok(store.set_change_log(true)?)
+}
+
+#[cfg(all(feature = "sled", not(feature = "rocksdb")))]
+fn enable_change_log(_store: &Store) -> Result<()> {
+    bail_status!(
+        501,
+        "The change log is only supported by the RocksDB storage"
+    )
+}
+
+#[cfg(all(feature = "sled", not(feature = "rocksdb")))]
+fn restore(_backup_directory: &str, _path: &str) -> Result<()> {
+    bail_status!(501, "Backups are only supported by the RocksDB storage")
+}
+
+fn load(store: &Store, command: &LoadCommand) -> Result<()> {
+    for file in &command.input {
+        let path = Path::new(file);
+        let reader = BufReader::new(File::open(path)?);
+        if let Some(format) = GraphFormat::from_path(path) {
+            load_graph(store, reader, format, command.threads)?;
+        } else if let Some(format) = DatasetFormat::from_path(path) {
+            load_dataset(store, reader, format, command.threads)?;
+        } else {
+            bail_status!(
+                400,
+                "The format of {} could not be guessed from its extension",
+                file
+            )
+        }
+        println!("{} loaded", file);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "rocksdb")]
+fn load_graph(
+    store: &Store,
+    reader: impl BufRead,
+    format: GraphFormat,
+    threads: Option<usize>,
+) -> Result<()> {
+    Ok(bulk_loader(store, threads).load_graph(reader, format, GraphNameRef::DefaultGraph, None)?)
+}
+
+#[cfg(feature = "rocksdb")]
+fn load_dataset(
+    store: &Store,
+    reader: impl BufRead,
+    format: DatasetFormat,
+    threads: Option<usize>,
+) -> Result<()> {
+    Ok(bulk_loader(store, threads).load_dataset(reader, format, None)?)
+}
+
+#[cfg(feature = "rocksdb")]
+fn bulk_loader(store: &Store, threads: Option<usize>) -> RocksDbBulkLoader {
+    let loader = store
+        .bulk_loader()
+        .on_progress(|count| println!("{} quads loaded", count));
+    if let Some(threads) = threads {
+        loader.with_num_threads(threads)
+    } else {
+        loader
+    }
+}
+
+#[cfg(all(feature = "sled", not(feature = "rocksdb")))]
+fn load_graph(
+    store: &Store,
+    reader: impl BufRead,
+    format: GraphFormat,
+    _threads: Option<usize>,
+) -> Result<()> {
+    Ok(store.load_graph(reader, format, GraphNameRef::DefaultGraph, None)?)
+}
+
+#[cfg(all(feature = "sled", not(feature = "rocksdb")))]
+fn load_dataset(
+    store: &Store,
+    reader: impl BufRead,
+    format: DatasetFormat,
+    _threads: Option<usize>,
+) -> Result<()> {
+    Ok(store.load_dataset(reader, format, None)?)
+}
+
+async fn handle_request(
+    request: Request,
+    store: Store,
+    query_timeout: Option<Duration>,
+    backup_directory: Option<PathBuf>,
+    read_only: bool,
+) -> Result<Response> {
     let mut response = match (request.url().path(), request.method()) {
+        ("/update", _) if read_only => {
+            bail_status!(
+                403,
+                "The server is in read-only mode, updates are not allowed"
+            )
+        }
+        (path, method)
+            if read_only
+                && path.starts_with("/store")
+                && method != Method::Get
+                && method != Method::Head =>
+        {
+            bail_status!(
+                403,
+                "The server is in read-only mode, the store could not be modified"
+            )
+        }
         ("/", Method::Get) => {
             let mut response = Response::new(StatusCode::Ok);
             response.append_header(headers::CONTENT_TYPE, "text/html");
@@ -74,9 +279,13 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
             response.set_body(LOGO);
             response
         }
-        ("/query", Method::Get) => {
-            configure_and_evaluate_sparql_query(store, url_query(&request), None, request)?
-        }
+        ("/query", Method::Get) => configure_and_evaluate_sparql_query(
+            store,
+            query_timeout,
+            url_query(&request),
+            None,
+            request,
+        )?,
         ("/query", Method::Post) => {
             if let Some(content_type) = request.content_type() {
                 if content_type.essence() == "application/sparql-query" {
@@ -89,6 +298,7 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
                         .await?;
                     configure_and_evaluate_sparql_query(
                         store,
+                        query_timeout,
                         url_query(&request),
                         Some(buffer),
                         request,
@@ -101,7 +311,13 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
                         .take(MAX_SPARQL_BODY_SIZE)
                         .read_to_end(&mut buffer)
                         .await?;
-                    configure_and_evaluate_sparql_query(store, buffer, None, request)?
+                    configure_and_evaluate_sparql_query(
+                        store,
+                        query_timeout,
+                        buffer,
+                        None,
+                        request,
+                    )?
                 } else {
                     bail_status!(415, "Not supported Content-Type given: {}", content_type)
                 }
@@ -121,6 +337,7 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
                         .await?;
                     configure_and_evaluate_sparql_update(
                         store,
+                        query_timeout,
                         url_query(&request),
                         Some(buffer),
                         request,
@@ -133,7 +350,13 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
                         .take(MAX_SPARQL_BODY_SIZE)
                         .read_to_end(&mut buffer)
                         .await?;
-                    configure_and_evaluate_sparql_update(store, buffer, None, request)?
+                    configure_and_evaluate_sparql_update(
+                        store,
+                        query_timeout,
+                        buffer,
+                        None,
+                        request,
+                    )?
                 } else {
                     bail_status!(415, "Not supported Content-Type given: {}", content_type)
                 }
@@ -141,6 +364,77 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
                 bail_status!(400, "No Content-Type given")
             }
         }
+        ("/validate", Method::Get) => {
+            let (shapes_graph, data_graph) = validation_target(&request)?;
+            let shapes_graph = if let Some(shapes_graph) = shapes_graph {
+                shapes_graph
+            } else {
+                bail_status!(400, "You should set the 'shapes-graph' parameter")
+            };
+            if !store.contains_named_graph(&shapes_graph)? {
+                bail_status!(404, "The graph {} does not exists", shapes_graph)
+            }
+            let shapes = ShapesGraph::from_triples(
+                store
+                    .quads_for_pattern(None, None, None, Some(shapes_graph.as_ref().into()))
+                    .map(|q| q.map(|q| Triple::new(q.subject, q.predicate, q.object)))
+                    .collect::<std::io::Result<Vec<_>>>()?,
+            )
+            .map_err(bad_request)?;
+            validate(store, &shapes, data_graph, request)?
+        }
+        ("/validate", Method::Post) => {
+            if let Some(content_type) = request.content_type() {
+                if let Some(format) = GraphFormat::from_media_type(content_type.essence()) {
+                    let (shapes_graph, data_graph) = validation_target(&request)?;
+                    if shapes_graph.is_some() {
+                        bail_status!(
+                            400,
+                            "The shapes-graph parameter should not be set when the shapes are sent in the request body"
+                        )
+                    }
+                    let mut request = request;
+                    let shapes = ShapesGraph::parse(
+                        BufReader::new(SyncAsyncReader::from(request.take_body())),
+                        format,
+                        None,
+                    )
+                    .map_err(bad_request)?;
+                    validate(store, &shapes, data_graph, request)?
+                } else {
+                    bail_status!(
+                        415,
+                        "No supported content Content-Type given: {}",
+                        content_type
+                    )
+                }
+            } else {
+                bail_status!(400, "No Content-Type given")
+            }
+        }
+        ("/backup", Method::Post) => {
+            if let Some(backup_directory) = backup_directory {
+                spawn_blocking(move || backup(&store, &backup_directory)).await?;
+                Response::new(StatusCode::NoContent)
+            } else {
+                bail_status!(
+                    404,
+                    "Backups are not enabled, use the --backup-directory option to enable them"
+                )
+            }
+        }
+        ("/changes", Method::Get) => {
+            let (since, timeout) = changes_parameters(&request)?;
+            let body = spawn_blocking(move || read_changes(&store, since, timeout)).await?;
+            if body.is_empty() {
+                Response::new(StatusCode::NoContent)
+            } else {
+                let mut response = Response::new(StatusCode::Ok);
+                response.append_header(headers::CONTENT_TYPE, "application/rdf-patch");
+                response.set_body(body);
+                response
+            }
+        }
         (path, Method::Get) if path.starts_with("/store") => {
             //TODO: stream
             let mut body = Vec::default();
@@ -165,6 +459,7 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
             response
         }
         (path, Method::Put) if path.starts_with("/store") => {
+            check_content_encoding(&request)?;
             if let Some(content_type) = request.content_type() {
                 if let Some(target) = store_target(&request)? {
                     if let Some(format) = GraphFormat::from_media_type(content_type.essence()) {
@@ -255,6 +550,7 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
             Response::new(StatusCode::NoContent)
         }
         (path, Method::Post) if path.starts_with("/store") => {
+            check_content_encoding(&request)?;
             if let Some(content_type) = request.content_type() {
                 if let Some(target) = store_target(&request)? {
                     if let Some(format) = GraphFormat::from_media_type(content_type.essence()) {
@@ -342,6 +638,43 @@ async fn handle_request(request: Request, store: Store) -> Result<Response> {
     Ok(response)
 }
 
+#[cfg(feature = "rocksdb")]
+fn backup(store: &Store, backup_directory: &Path) -> Result<()> {
+    Ok(store.backup(backup_directory)?)
+}
+
+#[cfg(all(feature = "sled", not(feature = "rocksdb")))]
+fn backup(_store: &Store, _backup_directory: &Path) -> Result<()> {
+    bail_status!(501, "Backups are only supported by the RocksDB storage")
+}
+
+/// Returns as RDF patches the change batches committed after `since`, waiting at most `timeout` for the first one
+#[cfg(feature = "rocksdb")]
+fn read_changes(store: &Store, since: u64, timeout: Duration) -> Result<Vec<u8>> {
+    if !store.is_change_log_enabled() {
+        bail_status!(
+            404,
+            "The change log is not enabled, use the --change-log option to enable it"
+        )
+    }
+    let mut changes = store.subscribe_from(since).map_err(bad_request)?;
+    let mut body = Vec::new();
+    let mut batch = changes.recv_timeout(timeout);
+    while let Some(current) = batch {
+        current.write_patch(&mut body)?;
+        batch = changes.try_recv();
+    }
+    Ok(body)
+}
+
+#[cfg(all(feature = "sled", not(feature = "rocksdb")))]
+fn read_changes(_store: &Store, _since: u64, _timeout: Duration) -> Result<Vec<u8>> {
+    bail_status!(
+        501,
+        "The change log is only supported by the RocksDB storage"
+    )
+}
+
 fn base_url(request: &Request) -> Result<Url> {
     let mut url = request.url().clone();
     if let Some(host) = request.host() {
@@ -358,14 +691,27 @@ fn url_query(request: &Request) -> Vec<u8> {
 
 fn configure_and_evaluate_sparql_query(
     store: Store,
+    query_timeout: Option<Duration>,
     encoded: Vec<u8>,
     mut query: Option<String>,
     request: Request,
 ) -> Result<Response> {
     let mut default_graph_uris = Vec::new();
     let mut named_graph_uris = Vec::new();
+    let mut explain = None;
     for (k, v) in form_urlencoded::parse(&encoded) {
         match k.as_ref() {
+            "explain" => {
+                explain = Some(match v.as_ref() {
+                    "plan" => false,
+                    "stats" => true,
+                    _ => bail_status!(
+                        400,
+                        "The explain parameter should be 'plan' or 'stats', found {}",
+                        v
+                    ),
+                })
+            }
             "query" => {
                 if query.is_some() {
                     bail_status!(400, "Multiple query parameters provided")
@@ -378,7 +724,15 @@ fn configure_and_evaluate_sparql_query(
         }
     }
     if let Some(query) = query {
-        evaluate_sparql_query(store, query, default_graph_uris, named_graph_uris, request)
+        evaluate_sparql_query(
+            store,
+            query_timeout,
+            query,
+            default_graph_uris,
+            named_graph_uris,
+            explain,
+            request,
+        )
     } else {
         bail_status!(400, "You should set the 'query' parameter")
     }
@@ -386,9 +740,11 @@ fn configure_and_evaluate_sparql_query(
 
 fn evaluate_sparql_query(
     store: Store,
+    query_timeout: Option<Duration>,
     query: String,
     default_graph_uris: Vec<String>,
     named_graph_uris: Vec<String>,
+    explain: Option<bool>,
     request: Request,
 ) -> Result<Response> {
     let mut query =
@@ -411,12 +767,35 @@ fn evaluate_sparql_query(
             .set_available_named_graphs(named_graph_uris);
     }
 
-    let results = store.query(query)?;
+    if let Some(with_stats) = explain {
+        let explanation = store
+            .explain_opt(query, query_options(query_timeout), with_stats)
+            .map_err(evaluation_error)?;
+        let is_text = content_negotiation(request, &["application/json", "text/plain"], |t| {
+            Some(t == "text/plain")
+        })?;
+        let (body, content_type) = if is_text {
+            (explanation.to_string().into_bytes(), "text/plain")
+        } else {
+            let mut body = Vec::default();
+            explanation.write_json(&mut body)?;
+            (body, "application/json")
+        };
+        let mut response = Response::from(body);
+        response.insert_header(headers::CONTENT_TYPE, content_type);
+        return Ok(response);
+    }
+
+    let results = store
+        .query_opt(query, query_options(query_timeout))
+        .map_err(evaluation_error)?;
     //TODO: stream
     if let QueryResults::Graph(_) = results {
         let format = graph_content_negotiation(request)?;
         let mut body = Vec::default();
-        results.write_graph(&mut body, format)?;
+        results
+            .write_graph(&mut body, format)
+            .map_err(evaluation_error)?;
         let mut response = Response::from(body);
         response.insert_header(headers::CONTENT_TYPE, format.media_type());
         Ok(response)
@@ -432,7 +811,7 @@ fn evaluate_sparql_query(
             QueryResultsFormat::from_media_type,
         )?;
         let mut body = Vec::default();
-        results.write(&mut body, format)?;
+        results.write(&mut body, format).map_err(evaluation_error)?;
         let mut response = Response::from(body);
         response.insert_header(headers::CONTENT_TYPE, format.media_type());
         Ok(response)
@@ -441,6 +820,7 @@ fn evaluate_sparql_query(
 
 fn configure_and_evaluate_sparql_update(
     store: Store,
+    query_timeout: Option<Duration>,
     encoded: Vec<u8>,
     mut update: Option<String>,
     request: Request,
@@ -461,7 +841,14 @@ fn configure_and_evaluate_sparql_update(
         }
     }
     if let Some(update) = update {
-        evaluate_sparql_update(store, update, default_graph_uris, named_graph_uris, request)
+        evaluate_sparql_update(
+            store,
+            query_timeout,
+            update,
+            default_graph_uris,
+            named_graph_uris,
+            request,
+        )
     } else {
         bail_status!(400, "You should set the 'update' parameter")
     }
@@ -469,6 +856,7 @@ fn configure_and_evaluate_sparql_update(
 
 fn evaluate_sparql_update(
     store: Store,
+    query_timeout: Option<Duration>,
     update: String,
     default_graph_uris: Vec<String>,
     named_graph_uris: Vec<String>,
@@ -499,7 +887,9 @@ fn evaluate_sparql_update(
             }
         }
     }
-    store.update(update)?;
+    store
+        .update_opt(update, UpdateOptions::from(query_options(query_timeout)))
+        .map_err(evaluation_error)?;
     Ok(Response::new(StatusCode::NoContent))
 }
 
@@ -544,6 +934,101 @@ fn store_target(request: &Request) -> Result<Option<GraphName>> {
     }
 }
 
+/// Checks that the request body is not compressed or compressed with a format the parsers detect by themselves
+fn check_content_encoding(request: &Request) -> Result<()> {
+    if let Some(content_encoding) = request.header(headers::CONTENT_ENCODING) {
+        let content_encoding = content_encoding.as_str();
+        if !content_encoding.trim().eq_ignore_ascii_case("identity")
+            && Compression::from_content_encoding(content_encoding).is_none()
+        {
+            bail_status!(
+                415,
+                "Not supported Content-Encoding given: {}",
+                content_encoding
+            )
+        }
+    }
+    Ok(())
+}
+
+fn validate(
+    store: Store,
+    shapes: &ShapesGraph,
+    data_graph: Option<GraphName>,
+    request: Request,
+) -> Result<Response> {
+    let report = if let Some(data_graph) = data_graph {
+        store.validate_graph(shapes, &data_graph)
+    } else {
+        store.validate(shapes)
+    }
+    .map_err(evaluation_error)?;
+    let format = graph_content_negotiation(request)?;
+    let mut body = Vec::default();
+    report.write(&mut body, format)?;
+    let mut response = Response::from(body);
+    response.insert_header(headers::CONTENT_TYPE, format.media_type());
+    Ok(response)
+}
+
+/// Returns the sequence number from which to stream the changes and the long polling timeout
+/// set by the `since` and `timeout` parameters of a `/changes` request
+fn changes_parameters(request: &Request) -> Result<(u64, Duration)> {
+    let mut since = None;
+    let mut timeout = DEFAULT_CHANGES_TIMEOUT;
+    for (k, v) in form_urlencoded::parse(request.url().query().unwrap_or("").as_bytes()) {
+        match k.as_ref() {
+            "since" => since = Some(u64::from_str(&v).map_err(bad_request)?),
+            "timeout" => timeout = u64::from_str(&v).map_err(bad_request)?,
+            _ => bail_status!(400, "Unexpected parameter: {}", k),
+        }
+    }
+    if let Some(since) = since {
+        Ok((since, Duration::from_secs(timeout.min(MAX_CHANGES_TIMEOUT))))
+    } else {
+        bail_status!(400, "You should set the 'since' parameter")
+    }
+}
+
+/// Returns the shapes graph and the data graph to validate of a `/validate` request
+fn validation_target(request: &Request) -> Result<(Option<NamedNode>, Option<GraphName>)> {
+    let mut shapes_graph = None;
+    let mut graph = None;
+    let mut default = false;
+    for (k, v) in form_urlencoded::parse(request.url().query().unwrap_or("").as_bytes()) {
+        match k.as_ref() {
+            "shapes-graph" => shapes_graph = Some(resolve_iri(request, &v)?),
+            "graph" => graph = Some(resolve_iri(request, &v)?),
+            "default" => default = true,
+            _ => bail_status!(400, "Unexpected parameter: {}", k),
+        }
+    }
+    let data_graph = if let Some(graph) = graph {
+        if default {
+            bail_status!(
+                400,
+                "Both graph and default parameters should not be set at the same time",
+            )
+        }
+        Some(graph.into())
+    } else if default {
+        Some(GraphName::DefaultGraph)
+    } else {
+        None
+    };
+    Ok((shapes_graph, data_graph))
+}
+
+fn resolve_iri(request: &Request, iri: &str) -> Result<NamedNode> {
+    NamedNode::new(
+        base_url(request)?
+            .join(iri)
+            .map_err(bad_request)?
+            .as_str(),
+    )
+    .map_err(bad_request)
+}
+
 async fn http_server<
     F: Clone + Send + Sync + 'static + Fn(Request) -> Fut,
     Fut: Send + Future<Output = Result<Response>>,
@@ -603,6 +1088,7 @@ fn dataset_content_negotiation(request: Request) -> Result<DatasetFormat> {
         &[
             DatasetFormat::NQuads.media_type(),
             DatasetFormat::TriG.media_type(),
+            DatasetFormat::JsonLd.media_type(),
         ],
         DatasetFormat::from_media_type,
     )
@@ -652,6 +1138,24 @@ fn content_negotiation<F>(
         .ok_or_else(|| Error::from_str(StatusCode::InternalServerError, "Unknown mime type"))
 }
 
+fn query_options(query_timeout: Option<Duration>) -> QueryOptions {
+    let options = QueryOptions::default();
+    if let Some(query_timeout) = query_timeout {
+        options.with_timeout(query_timeout)
+    } else {
+        options
+    }
+}
+
+fn evaluation_error(e: EvaluationError) -> Error {
+    match e {
+        EvaluationError::Timeout | EvaluationError::Cancelled => {
+            Error::new(StatusCode::ServiceUnavailable, e)
+        }
+        e => e.into(),
+    }
+}
+
 fn bad_request(e: impl Into<Error>) -> Error {
     let mut e = e.into();
     e.set_status(StatusCode::BadRequest);
@@ -681,6 +1185,8 @@ mod tests {
     use super::*;
     use crate::handle_request;
     use async_std::task::block_on;
+    use oxigraph::io::DatasetSerializer;
+    use oxigraph::model::{NamedNodeRef, QuadRef};
     use tempfile::{tempdir, TempDir};
 
     #[test]
@@ -699,6 +1205,72 @@ mod tests {
         ServerTest::new().test_status(request, StatusCode::NoContent)
     }
 
+    #[test]
+    fn post_and_get_json_ld_dataset() {
+        let server = ServerTest::new();
+        let mut request = Request::new(Method::Post, Url::parse("http://localhost/store").unwrap());
+        request.insert_header("Content-Type", "application/ld+json");
+        request.set_body(
+            r#"{"@id": "http://example.com/g", "@graph": {"@id": "http://example.com/s", "http://example.com/p": "o"}}"#,
+        );
+        server.test_status(request, StatusCode::NoContent);
+
+        let mut request = Request::new(Method::Get, Url::parse("http://localhost/store").unwrap());
+        request.insert_header("Accept", "application/ld+json");
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), StatusCode::Ok);
+        assert_eq!(
+            response.content_type().unwrap().essence(),
+            "application/ld+json"
+        );
+        assert_eq!(
+            block_on(response.body_string()).unwrap(),
+            r#"[{"@id":"http://example.com/g","@graph":[{"@id":"http://example.com/s","http://example.com/p":[{"@value":"o"}]}]}]"#
+        );
+    }
+
+    #[test]
+    fn post_compressed_dataset_file() {
+        let server = ServerTest::new();
+        let mut body = Vec::new();
+        let mut writer = DatasetSerializer::from_format(DatasetFormat::NQuads)
+            .with_compression(Compression::Gzip)
+            .quad_writer(&mut body)
+            .unwrap();
+        writer
+            .write(QuadRef::new(
+                NamedNodeRef::new("http://example.com/s").unwrap(),
+                NamedNodeRef::new("http://example.com/p").unwrap(),
+                NamedNodeRef::new("http://example.com/o").unwrap(),
+                NamedNodeRef::new("http://example.com/g").unwrap(),
+            ))
+            .unwrap();
+        writer.finish().unwrap();
+        let mut request = Request::new(Method::Post, Url::parse("http://localhost/store").unwrap());
+        request.insert_header("Content-Type", "application/n-quads");
+        request.insert_header("Content-Encoding", "gzip");
+        request.set_body(body);
+        server.test_status(request, StatusCode::NoContent);
+
+        let mut request = Request::new(Method::Get, Url::parse("http://localhost/store").unwrap());
+        request.insert_header("Accept", "application/n-quads");
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), StatusCode::Ok);
+        assert_eq!(
+            block_on(response.body_string()).unwrap(),
+            "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .\n"
+        );
+    }
+
+    #[test]
+    fn post_unsupported_content_encoding() {
+        let mut request = Request::new(Method::Post, Url::parse("http://localhost/store").unwrap());
+        request.insert_header("Content-Type", "application/n-quads");
+        request.insert_header("Content-Encoding", "br");
+        request.set_body("<http://example.com> <http://example.com> <http://example.com> .");
+        ServerTest::new().test_status(request, StatusCode::UnsupportedMediaType)
+    }
+
     #[test]
     fn post_wrong_file() {
         let mut request = Request::new(Method::Post, Url::parse("http://localhost/store").unwrap());
@@ -771,6 +1343,45 @@ mod tests {
         ServerTest::new().test_status(request, StatusCode::UnsupportedMediaType)
     }
 
+    #[test]
+    fn get_query_explanation() {
+        let server = ServerTest::new();
+        let mut request = Request::new(
+            Method::Get,
+            Url::parse(
+                "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}&explain=stats",
+            )
+            .unwrap(),
+        );
+        request.insert_header("Accept", "text/plain");
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), StatusCode::Ok);
+        assert!(block_on(response.body_string())
+            .unwrap()
+            .starts_with("Project ?o ?p ?s [rows: 0, evaluations: 1, duration: "));
+
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse(
+                    "http://localhost/query?query=SELECT%20*%20WHERE%20{%20?s%20?p%20?o%20}&explain=foo",
+                )
+                .unwrap(),
+            ),
+            StatusCode::BadRequest,
+        );
+    }
+
+    #[test]
+    fn post_query_timeout() {
+        let mut request = Request::new(Method::Post, Url::parse("http://localhost/query").unwrap());
+        request.insert_header("Content-Type", "application/sparql-query");
+        request.set_body("SELECT * WHERE { ?s <http://example.com>* ?o }");
+        ServerTest::new()
+            .with_query_timeout(Duration::from_secs(0))
+            .test_status(request, StatusCode::ServiceUnavailable)
+    }
+
     #[test]
     fn post_federated_query() {
         let mut request = Request::new(Method::Post, Url::parse("http://localhost/query").unwrap());
@@ -799,6 +1410,225 @@ mod tests {
         ServerTest::new().test_status(request, StatusCode::BadRequest)
     }
 
+    #[test]
+    fn post_validate() {
+        let server = ServerTest::new();
+        let mut request = Request::new(
+            Method::Put,
+            Url::parse("http://localhost/store?default").unwrap(),
+        );
+        request.insert_header("Content-Type", "application/n-triples");
+        request.set_body("<http://example.com/s> <http://example.com/p> \"o\" .");
+        server.test_status(request, StatusCode::NoContent);
+
+        let mut request = Request::new(
+            Method::Post,
+            Url::parse("http://localhost/validate?default").unwrap(),
+        );
+        request.insert_header("Content-Type", "text/turtle");
+        request.insert_header("Accept", "text/turtle");
+        request.set_body("@prefix sh: <http://www.w3.org/ns/shacl#> .\n<http://example.com/shape> sh:targetSubjectsOf <http://example.com/p> ; sh:property [ sh:path <http://example.com/p> ; sh:nodeKind sh:IRI ] .");
+        let mut response = server.exec(request);
+        assert_eq!(response.status(), StatusCode::Ok);
+        assert!(block_on(response.body_string())
+            .unwrap()
+            .contains("http://www.w3.org/ns/shacl#NodeKindConstraintComponent"));
+    }
+
+    #[test]
+    fn post_validate_bad_shapes() {
+        let mut request = Request::new(
+            Method::Post,
+            Url::parse("http://localhost/validate").unwrap(),
+        );
+        request.insert_header("Content-Type", "text/turtle");
+        request.set_body("@prefix sh: <http://www.w3.org/ns/shacl#> .\n<http://example.com/shape> sh:targetNode <http://example.com/s> ; sh:minCount \"foo\" .");
+        ServerTest::new().test_status(request, StatusCode::BadRequest)
+    }
+
+    #[test]
+    fn get_validate() {
+        let server = ServerTest::new();
+        let mut request = Request::new(
+            Method::Put,
+            Url::parse("http://localhost/store?graph=http://example.com/shapes").unwrap(),
+        );
+        request.insert_header("Content-Type", "text/turtle");
+        request.set_body("@prefix sh: <http://www.w3.org/ns/shacl#> .\n<http://example.com/shape> sh:targetNode <http://example.com/s> ; sh:property [ sh:path <http://example.com/p> ; sh:minCount 1 ] .");
+        server.test_status(request, StatusCode::Created);
+
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse(
+                    "http://localhost/validate?shapes-graph=http://example.com/shapes&default",
+                )
+                .unwrap(),
+            ),
+            StatusCode::Ok,
+        );
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse("http://localhost/validate?shapes-graph=http://example.com/unknown")
+                    .unwrap(),
+            ),
+            StatusCode::NotFound,
+        );
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse("http://localhost/validate").unwrap(),
+            ),
+            StatusCode::BadRequest,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rocksdb")]
+    fn post_backup() {
+        let server = ServerTest::new().with_backup_directory();
+        let mut request = Request::new(
+            Method::Put,
+            Url::parse("http://localhost/store?default").unwrap(),
+        );
+        request.insert_header("Content-Type", "application/n-triples");
+        request.set_body("<http://example.com/s> <http://example.com/p> <http://example.com/o> .");
+        server.test_status(request, StatusCode::NoContent);
+        server.test_status(
+            Request::new(Method::Post, Url::parse("http://localhost/backup").unwrap()),
+            StatusCode::NoContent,
+        );
+    }
+
+    #[test]
+    fn post_backup_without_directory() {
+        ServerTest::new().test_status(
+            Request::new(Method::Post, Url::parse("http://localhost/backup").unwrap()),
+            StatusCode::NotFound,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rocksdb")]
+    fn get_changes() {
+        let server = ServerTest::new();
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse("http://localhost/changes?since=0&timeout=0").unwrap(),
+            ),
+            StatusCode::NotFound,
+        );
+        server.store.set_change_log(true).unwrap();
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse("http://localhost/changes?since=0&timeout=0").unwrap(),
+            ),
+            StatusCode::NoContent,
+        );
+
+        let mut request = Request::new(
+            Method::Put,
+            Url::parse("http://localhost/store?default").unwrap(),
+        );
+        request.insert_header("Content-Type", "application/n-triples");
+        request.set_body("<http://example.com/s> <http://example.com/p> <http://example.com/o> .");
+        server.test_status(request, StatusCode::NoContent);
+
+        let mut response = server.exec(Request::new(
+            Method::Get,
+            Url::parse("http://localhost/changes?since=0&timeout=0").unwrap(),
+        ));
+        assert_eq!(response.status(), StatusCode::Ok);
+        assert_eq!(
+            response
+                .header(headers::CONTENT_TYPE)
+                .map(|values| values.as_str()),
+            Some("application/rdf-patch")
+        );
+        let body = block_on(response.body_string()).unwrap();
+        assert!(body
+            .contains("A <http://example.com/s> <http://example.com/p> <http://example.com/o> ."));
+
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse("http://localhost/changes?timeout=0").unwrap(),
+            ),
+            StatusCode::BadRequest,
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rocksdb")]
+    fn get_changes_after_bulk_load() {
+        let server = ServerTest::new();
+        server.store.set_change_log(true).unwrap();
+        load_dataset(
+            &server.store,
+            b"<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .\n".as_ref(),
+            DatasetFormat::NQuads,
+            Some(1),
+        )
+        .unwrap();
+
+        let mut response = server.exec(Request::new(
+            Method::Get,
+            Url::parse("http://localhost/changes?since=0&timeout=0").unwrap(),
+        ));
+        assert_eq!(response.status(), StatusCode::Ok);
+        let body = block_on(response.body_string()).unwrap();
+        assert!(body.contains("H resync \"true\"^^<http://www.w3.org/2001/XMLSchema#boolean> ."));
+        assert!(!body.contains("A <http://example.com/s>"));
+    }
+
+    #[test]
+    fn read_only() {
+        let server = ServerTest::new().with_read_only();
+        let mut request =
+            Request::new(Method::Post, Url::parse("http://localhost/update").unwrap());
+        request.insert_header("Content-Type", "application/sparql-update");
+        request.set_body(
+            "INSERT DATA { <http://example.com/s> <http://example.com/p> <http://example.com/o> }",
+        );
+        server.test_status(request, StatusCode::Forbidden);
+
+        let mut request = Request::new(
+            Method::Put,
+            Url::parse("http://localhost/store?default").unwrap(),
+        );
+        request.insert_header("Content-Type", "application/n-triples");
+        request.set_body("<http://example.com/s> <http://example.com/p> <http://example.com/o> .");
+        server.test_status(request, StatusCode::Forbidden);
+
+        server.test_status(
+            Request::new(
+                Method::Delete,
+                Url::parse("http://localhost/store?default").unwrap(),
+            ),
+            StatusCode::Forbidden,
+        );
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse("http://localhost/store?default").unwrap(),
+            ),
+            StatusCode::Ok,
+        );
+        server.test_status(
+            Request::new(
+                Method::Get,
+                Url::parse(
+                    "http://localhost/query?query=SELECT%20*%20WHERE%20%7B%20?s%20?p%20?o%20%7D",
+                )
+                .unwrap(),
+            ),
+            StatusCode::Ok,
+        );
+    }
+
     #[test]
     fn graph_store_protocol() {
         // Tests from https://www.w3.org/2009/sparql/docs/tests/data-sparql11/http-rdf-update/
@@ -1035,6 +1865,9 @@ mod tests {
 
     struct ServerTest {
         store: Store,
+        query_timeout: Option<Duration>,
+        backup_directory: Option<TempDir>,
+        read_only: bool,
         _path: TempDir,
     }
 
@@ -1042,11 +1875,39 @@ mod tests {
         fn new() -> ServerTest {
             let path = tempdir().unwrap();
             let store = Store::open(path.path()).unwrap();
-            ServerTest { _path: path, store }
+            ServerTest {
+                _path: path,
+                store,
+                query_timeout: None,
+                backup_directory: None,
+                read_only: false,
+            }
+        }
+
+        fn with_query_timeout(mut self, query_timeout: Duration) -> ServerTest {
+            self.query_timeout = Some(query_timeout);
+            self
+        }
+
+        #[cfg(feature = "rocksdb")]
+        fn with_backup_directory(mut self) -> ServerTest {
+            self.backup_directory = Some(tempdir().unwrap());
+            self
+        }
+
+        fn with_read_only(mut self) -> ServerTest {
+            self.read_only = true;
+            self
         }
 
         fn exec(&self, request: Request) -> Response {
-            match block_on(handle_request(request, self.store.clone())) {
+            match block_on(handle_request(
+                request,
+                self.store.clone(),
+                self.query_timeout,
+                self.backup_directory.as_ref().map(|d| d.path().to_owned()),
+                self.read_only,
+            )) {
                 Ok(response) => response,
                 Err(e) => {
                     let mut response = Response::new(e.status());
@@ -326,7 +326,7 @@ impl<'a> Iterator for RdfListIterator<'a> {
 
 fn object_for_subject_predicate<'a>(
     store: &MemoryStore,
-    subject: impl Into<NamedOrBlankNodeRef<'a>>,
+    subject: impl Into<SubjectRef<'a>>,
     predicate: impl Into<NamedNodeRef<'a>>,
 ) -> Option<Term> {
     objects_for_subject_predicate(store, subject, predicate).next()
@@ -334,7 +334,7 @@ fn object_for_subject_predicate<'a>(
 
 fn objects_for_subject_predicate<'a>(
     store: &MemoryStore,
-    subject: impl Into<NamedOrBlankNodeRef<'a>>,
+    subject: impl Into<SubjectRef<'a>>,
     predicate: impl Into<NamedNodeRef<'a>>,
 ) -> impl Iterator<Item = Term> {
     store
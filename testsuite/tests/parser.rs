@@ -41,3 +41,333 @@ fn trig_w3c_testsuite() -> Result<()> {
 fn rdf_xml_w3c_testsuite() -> Result<()> {
     run_testsuite("http://www.w3.org/2013/RDFXMLTests/manifest.ttl")
 }
+
+#[test]
+fn oxigraph_parser_star_testsuite() -> Result<()> {
+    run_testsuite("https://github.com/oxigraph/oxigraph/tests/parser-star/manifest.ttl")
+}
+
+/// The syntaxes of the W3C Turtle family without their RDF-star extensions
+/// are checked against the Rio parsers that pass the W3C test suites
+mod rio_differential {
+    use anyhow::{anyhow, Result};
+    use oxigraph::io::{DatasetFormat, GraphFormat};
+    use oxigraph::model::*;
+    use oxigraph::MemoryStore;
+    use rio_api::model as rio;
+    use rio_api::parser::{QuadsParser, TriplesParser};
+    use rio_turtle::{NQuadsParser, NTriplesParser, TriGParser, TurtleError, TurtleParser};
+
+    const BASE_IRI: &str = "http://example.com/base/dir/file";
+
+    #[derive(Clone, Copy, Debug)]
+    enum Syntax {
+        NTriples,
+        NQuads,
+        Turtle,
+        TriG,
+    }
+
+    fn parse_with_oxigraph(data: &str, syntax: Syntax) -> Result<MemoryStore> {
+        let store = MemoryStore::new();
+        match syntax {
+            Syntax::NTriples => store.load_graph(
+                data.as_bytes(),
+                GraphFormat::NTriples,
+                GraphNameRef::DefaultGraph,
+                Some(BASE_IRI),
+            )?,
+            Syntax::Turtle => store.load_graph(
+                data.as_bytes(),
+                GraphFormat::Turtle,
+                GraphNameRef::DefaultGraph,
+                Some(BASE_IRI),
+            )?,
+            Syntax::NQuads => {
+                store.load_dataset(data.as_bytes(), DatasetFormat::NQuads, Some(BASE_IRI))?
+            }
+            Syntax::TriG => {
+                store.load_dataset(data.as_bytes(), DatasetFormat::TriG, Some(BASE_IRI))?
+            }
+        }
+        Ok(store)
+    }
+
+    fn parse_with_rio(data: &str, syntax: Syntax) -> Result<MemoryStore> {
+        let store = MemoryStore::new();
+        let base_iri = Some(oxiri::Iri::parse(BASE_IRI.to_owned())?);
+        let mut on_triple = |t: rio::Triple<'_>| {
+            store.insert(Quad::new(
+                subject(t.subject),
+                NamedNode::new_unchecked(t.predicate.iri),
+                term(t.object),
+                GraphName::DefaultGraph,
+            ));
+            Ok(()) as Result<(), TurtleError>
+        };
+        let mut on_quad = |q: rio::Quad<'_>| {
+            store.insert(Quad::new(
+                subject(q.subject),
+                NamedNode::new_unchecked(q.predicate.iri),
+                term(q.object),
+                q.graph_name
+                    .map_or(GraphName::DefaultGraph, |g| subject(g).into()),
+            ));
+            Ok(()) as Result<(), TurtleError>
+        };
+        match syntax {
+            Syntax::NTriples => NTriplesParser::new(data.as_bytes()).parse_all(&mut on_triple),
+            Syntax::Turtle => {
+                TurtleParser::new(data.as_bytes(), base_iri).parse_all(&mut on_triple)
+            }
+            Syntax::NQuads => NQuadsParser::new(data.as_bytes()).parse_all(&mut on_quad),
+            Syntax::TriG => TriGParser::new(data.as_bytes(), base_iri).parse_all(&mut on_quad),
+        }?;
+        Ok(store)
+    }
+
+    fn subject(node: rio::NamedOrBlankNode<'_>) -> NamedOrBlankNode {
+        match node {
+            rio::NamedOrBlankNode::NamedNode(node) => NamedNode::new_unchecked(node.iri).into(),
+            rio::NamedOrBlankNode::BlankNode(node) => BlankNode::new_unchecked(node.id).into(),
+        }
+    }
+
+    fn term(term: rio::Term<'_>) -> Term {
+        match term {
+            rio::Term::NamedNode(node) => NamedNode::new_unchecked(node.iri).into(),
+            rio::Term::BlankNode(node) => BlankNode::new_unchecked(node.id).into(),
+            rio::Term::Literal(rio::Literal::Simple { value }) => {
+                Literal::new_simple_literal(value).into()
+            }
+            rio::Term::Literal(rio::Literal::LanguageTaggedString { value, language }) => {
+                Literal::new_language_tagged_literal(value, language)
+                    .unwrap()
+                    .into()
+            }
+            rio::Term::Literal(rio::Literal::Typed { value, datatype }) => {
+                Literal::new_typed_literal(value, NamedNode::new_unchecked(datatype.iri)).into()
+            }
+        }
+    }
+
+    fn check_same_result(data: &str, syntax: Syntax) -> Result<()> {
+        match (
+            parse_with_oxigraph(data, syntax),
+            parse_with_rio(data, syntax),
+        ) {
+            (Ok(actual), Ok(expected)) => {
+                if actual.is_isomorphic(&expected) {
+                    Ok(())
+                } else {
+                    Err(anyhow!(
+                        "{:?} document parsed differently than by Rio:\n{}\nOxigraph:\n{}\nRio:\n{}",
+                        syntax,
+                        data,
+                        actual,
+                        expected
+                    ))
+                }
+            }
+            (Err(_), Err(_)) => Ok(()),
+            (Ok(_), Err(e)) => Err(anyhow!(
+                "{:?} document rejected by Rio with error {} but accepted:\n{}",
+                syntax,
+                e,
+                data
+            )),
+            (Err(e), Ok(_)) => Err(anyhow!(
+                "{:?} document accepted by Rio but rejected with error {}:\n{}",
+                syntax,
+                e,
+                data
+            )),
+        }
+    }
+
+    fn check_all(documents: &[&str], syntax: Syntax) {
+        let errors = documents
+            .iter()
+            .filter_map(|data| check_same_result(data, syntax).err())
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        assert!(errors.is_empty(), "\n{}\n", errors.join("\n\n"));
+    }
+
+    const NTRIPLES: &[&str] = &[
+        "",
+        "# comment only\n",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> . # comment\n\n",
+        "<http://example.com/s><http://example.com/p><http://example.com/o>.",
+        "_:a <http://example.com/p> _:b .\n_:b <http://example.com/p> _:a .\n",
+        "_:a.b <http://example.com/p> _:1-c .",
+        "<http://example.com/s> <http://example.com/p> \"\" .",
+        "<http://example.com/s> <http://example.com/p> \"a\\t\\b\\n\\r\\f\\\"\\'\\\\\" .",
+        "<http://example.com/s> <http://example.com/p> \"\\u00E9\\U0001F600\" .",
+        "<http://example.com/s> <http://example.com/p> \"é😀\" .",
+        "<http://example.com/s> <http://example.com/p> \"chat\"@fr .",
+        "<http://example.com/s> <http://example.com/p> \"color\"@en-US .",
+        "<http://example.com/s> <http://example.com/p> \"1\"^^<http://www.w3.org/2001/XMLSchema#integer> .",
+        "<http://example.com/s> <http://example.com/p> \"x\"^^<http://example.com/dt> .",
+        "<http://example.com/\\u00E9> <http://example.com/p> <http://example.com/o> .",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> .\r\n<http://example.com/s> <http://example.com/p> <http://example.com/o2> .\r\n",
+        "\t <http://example.com/s> \t<http://example.com/p>  <http://example.com/o> \t.",
+        // invalid documents
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o>",
+        "<s> <http://example.com/p> <http://example.com/o> .",
+        "\"s\" <http://example.com/p> <http://example.com/o> .",
+        "<http://example.com/s> _:p <http://example.com/o> .",
+        "<http://example.com/s> <http://example.com/p> \"o\\a\" .",
+        "<http://example.com/s> <http://example.com/p> \"o .",
+        "<http://example.com/s> <http://example.com/p> \"o\"@ .",
+        "<http://example.com/s> <http://example.com/p> \"o\"^^\"dt\" .",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .",
+        "<http://example.com/s> <http://example.com/p> 1 .",
+        "<http://example .com/s> <http://example.com/p> <http://example.com/o> .",
+        "_: <http://example.com/p> <http://example.com/o> .",
+        "@prefix ex: <http://example.com/> .",
+    ];
+
+    const NQUADS: &[&str] = &[
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> _:g .\n_:g <http://example.com/p> _:g _:g .",
+        "_:a <http://example.com/p> \"o\"@en <http://example.com/g> .",
+        "_:a <http://example.com/p> \"o\"^^<http://example.com/dt> <http://example.com/g> . # comment",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> .\n<http://example.com/s> <http://example.com/p> <http://example.com/o> .\n",
+        // invalid documents
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> \"g\" .",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> <g> .",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g>",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> <http://example.com/g> <http://example.com/g2> .",
+    ];
+
+    const TURTLE: &[&str] = &[
+        "",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o .",
+        "PREFIX ex: <http://example.com/>\nex:s ex:p ex:o .",
+        "prefix ex: <http://example.com/>\nex:s ex:p ex:o .",
+        "@prefix : <http://example.com/> .\n:s :p : .",
+        "@base <http://example.org/> .\n<s> <p> <o> .",
+        "BASE <http://example.org/a/b>\n<s> <#p> <../o> .",
+        "<s> <p> <o> .",
+        "<> <?q> <#f> .",
+        "<../../up> <//other.example/x> <./here> .",
+        "@base <http://example.org/a/> .\n@base <b/> .\n<s> <p> <o> .",
+        "@prefix ex: <http://example.com/> .\n@prefix ex: <http://example.org/> .\nex:s ex:p ex:o .",
+        "@prefix ex: <rel/> .\nex:s ex:p ex:o .",
+        "@prefix ex: <http://example.com/> .\nex:s a ex:C .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o1, ex:o2 ; ex:q ex:o3 ;; ex:r ex:o4 ; .",
+        "@prefix ex: <http://example.com/> .\nex:a.b ex:p-q ex:o_1 .",
+        "@prefix ex: <http://example.com/> .\nex:1 ex:p ex:2.3 .",
+        "@prefix ex: <http://example.com/> .\nex:a\\~b ex:p\\.q ex:c%20d .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:a: .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex: .",
+        "@prefix ex.a: <http://example.com/> .\nex.a:s ex.a:p ex.a:o .",
+        "@prefix ex: <http://example.com/> .\n_:a ex:p _:b .\n_:b ex:p _:a .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p [] .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p [ ex:q ex:o ; ex:r [ ex:t ex:u ] ] .",
+        "@prefix ex: <http://example.com/> .\n[ ex:p ex:o ] .",
+        "@prefix ex: <http://example.com/> .\n[ ex:p ex:o ] ex:q ex:r .",
+        "@prefix ex: <http://example.com/> .\n[] ex:q ex:r .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p () .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ( ex:a \"b\" 1 ( ex:c ) [ ex:d ex:e ] ) .",
+        "@prefix ex: <http://example.com/> .\n( ex:a ex:b ) ex:p ex:o .",
+        "@prefix ex: <http://example.com/> .\n() ex:p ex:o .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p 1, -2, +3, 4.5, .5, -.5, 6e7, 8E-9, 1.0e+2, 0012 .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p 1.ex:q 2 .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p true, false .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p \"a\", 'b', \"\"\"c\n\"d\" \"\"e\"\"\", '''f\n'g' ''h''', \"\"\"\"\"\", '''''' .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p \"\"\"a\"\"\"\"\" .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p \"a\\\"b\\u00E9\\U0001F600\\n\" .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p \"a\"@en, 'b'@en-GB, \"\"\"c\"\"\"@fr .",
+        "@prefix ex: <http://example.com/> .\n@prefix xsd: <http://www.w3.org/2001/XMLSchema#> .\nex:s ex:p \"1\"^^xsd:integer, \"b\"^^<http://example.com/dt>, \"c\"^^ex:dt .",
+        "@prefix ex: <http://example.com/> . # comment\nex:s # comment\n ex:p # comment\n ex:o # comment\n . # comment",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o .ex:s ex:p ex:o2.",
+        "<http://example.com/s> <http://example.com/p> <http://example.com/o> .",
+        "<http://example.com/\\u00E9> <http://example.com/p> <http://example.com/o> .",
+        // invalid documents
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o",
+        "ex:s ex:p ex:o .",
+        "@prefix ex: <http://example.com/>\nex:s ex:p ex:o .",
+        "PREFIX ex: <http://example.com/> .\nex:s ex:p ex:o .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o, .",
+        "@prefix ex: <http://example.com/> .\n\"s\" ex:p ex:o .",
+        "@prefix ex: <http://example.com/> .\ntrue ex:p ex:o .",
+        "@prefix ex: <http://example.com/> .\nex:s \"p\" ex:o .",
+        "@prefix ex: <http://example.com/> .\nex:s [] ex:o .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p \"o\\a\" .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p \"o\n\" .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p \"o\"@ .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p \"o\"^^\"dt\" .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o.",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:a.",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p [ ex:q ex:o .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ( ex:o .",
+        "@prefix ex: <http://example.com/> .\n[ ex:p ex:o ] ex:q .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p 1e .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p _: .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:a\\b .",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p <a b> .",
+        "@prefix ex: <http://example.com/> .\nGRAPH ex:g { ex:s ex:p ex:o . }",
+        "@prefix ex: <http://example.com/> .\n{ ex:s ex:p ex:o . }",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o ex:g .",
+        "@prefix ex: <http://example.com/> .\nex:s a ex:o ; a .",
+    ];
+
+    const TRIG: &[&str] = &[
+        "",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o .",
+        "@prefix ex: <http://example.com/> .\n{ ex:s ex:p ex:o . }",
+        "@prefix ex: <http://example.com/> .\n{ ex:s ex:p ex:o }",
+        "@prefix ex: <http://example.com/> .\n{}",
+        "@prefix ex: <http://example.com/> .\nex:g { ex:s ex:p ex:o . ex:s ex:p ex:o2 }",
+        "@prefix ex: <http://example.com/> .\nGRAPH ex:g { ex:s ex:p ex:o }",
+        "@prefix ex: <http://example.com/> .\n_:g { ex:s ex:p _:g }",
+        "@prefix ex: <http://example.com/> .\n[] { ex:s ex:p ex:o }",
+        "@prefix ex: <http://example.com/> .\nGRAPH [] { ex:s ex:p ex:o }",
+        "@prefix ex: <http://example.com/> .\n<g> { <s> <p> <o> }",
+        "@prefix ex: <http://example.com/> .\nex:g { _:a ex:p _:b } ex:g2 { _:a ex:p _:b }",
+        "@prefix ex: <http://example.com/> .\nex:g { ex:s ex:p ex:o }\n@prefix ex: <http://example.org/> .\nex:g { ex:s ex:p ex:o }",
+        "@prefix ex: <http://example.com/> .\nex:g { [ ex:p ( 1 2 ) ] ex:q \"\"\"a\nb\"\"\"@en }",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o . ex:g { ex:s ex:p ex:o } ex:s ex:p ex:o2 .",
+        "PREFIX ex: <http://example.com/>\nBASE <http://example.org/>\nGRAPH <g> { <s> <p> <o> }",
+        // invalid documents
+        "@prefix ex: <http://example.com/> .\nex:g { ex:s ex:p ex:o . } .",
+        "@prefix ex: <http://example.com/> .\nex:g { ex:g2 { ex:s ex:p ex:o } }",
+        "@prefix ex: <http://example.com/> .\nex:g { ex:s ex:p ex:o ",
+        "@prefix ex: <http://example.com/> .\n\"g\" { ex:s ex:p ex:o }",
+        "@prefix ex: <http://example.com/> .\nGRAPH { ex:s ex:p ex:o }",
+        "@prefix ex: <http://example.com/> .\nex:g { @prefix ex2: <http://example.org/> . }",
+        "@prefix ex: <http://example.com/> .\nex:g { ex:s ex:p ex:o . . }",
+        "@prefix ex: <http://example.com/> .\n[ ex:p ex:o ] { ex:s ex:p ex:o }",
+        "@prefix ex: <http://example.com/> .\nex:s ex:p ex:o",
+        "@prefix ex: <http://example.com/> .\n( ex:a ) { ex:s ex:p ex:o }",
+    ];
+
+    #[test]
+    fn ntriples_same_as_rio() {
+        check_all(NTRIPLES, Syntax::NTriples)
+    }
+
+    #[test]
+    fn nquads_same_as_rio() {
+        check_all(NTRIPLES, Syntax::NQuads);
+        check_all(NQUADS, Syntax::NQuads)
+    }
+
+    #[test]
+    fn turtle_same_as_rio() {
+        check_all(NTRIPLES, Syntax::Turtle);
+        check_all(TURTLE, Syntax::Turtle)
+    }
+
+    #[test]
+    fn trig_same_as_rio() {
+        check_all(NTRIPLES, Syntax::TriG);
+        check_all(TURTLE, Syntax::TriG);
+        check_all(TRIG, Syntax::TriG)
+    }
+}